@@ -2,25 +2,29 @@
 CK3 Character History Generator — FastAPI backend.
 
 Dev:        uvicorn api.main:app --host 127.0.0.1 --port 8000 --reload
-Packaged:   run as a PyInstaller sidecar spawned by the Tauri shell
+Packaged:   run as a PyInstaller sidecar spawned by the Tauri shell, which
+            picks a free port and passes it as ``--port``, along with any
+            ``--config-dir``, ``--seed`` and ``--output-dir`` launch options
 """
 
 from __future__ import annotations
 
+import argparse
 import asyncio
 import json
 import logging
+import os
 import queue
-import shutil
+import secrets
 import sys
 import threading
 from pathlib import Path
-from typing import AsyncGenerator
+from typing import Any, AsyncGenerator
 
 import uvicorn
-from fastapi import FastAPI, HTTPException
+from fastapi import FastAPI, Header, HTTPException
 from fastapi.middleware.cors import CORSMiddleware
-from fastapi.responses import FileResponse, StreamingResponse
+from fastapi.responses import StreamingResponse
 
 # ---------------------------------------------------------------------------
 #  Path setup — ensures project root is importable in dev and when frozen
@@ -36,10 +40,39 @@ else:
 if str(_project_root) not in sys.path:
     sys.path.insert(0, str(_project_root))
 
+
+def _parse_args() -> argparse.Namespace:
+    parser = argparse.ArgumentParser(description="CK3 Character History Generator API server")
+    parser.add_argument("--port", type=int, default=8000, help="loopback port to bind (default: 8000)")
+    parser.add_argument("--config-dir", help="directory holding the JSON config files")
+    parser.add_argument("--seed", type=int, help="seed for the simulation's random number generator")
+    parser.add_argument("--output-dir", help="directory to write exports and family trees into")
+    return parser.parse_args()
+
+
+# ck3gen resolves its paths and settings from the environment at import time,
+# so command-line launch options are exported before anything is imported.
+_args = _parse_args() if __name__ == "__main__" else None
+if _args is not None:
+    for _name, _value in (
+        ("CK3GEN_CONFIG_DIR", _args.config_dir),
+        ("CK3GEN_SEED", _args.seed),
+        ("CK3GEN_OUTPUT_DIR", _args.output_dir),
+    ):
+        if _value is not None:
+            os.environ[_name] = str(_value)
+
 # Import the CLI runner — single source of truth for the simulation pipeline.
 from main import run_main  # noqa: E402
-from api.models import InitializationConfig, LifeStagesConfig
-from ck3gen.paths import CONFIG_DIR, FALLBACK_CONFIG_DIR, TREE_OUTPUT_DIR
+from api.models import SimulationRequest
+from ck3gen import protocol
+from ck3gen.control import SimulationCancelled, control
+from ck3gen.paths import (
+    CHARACTER_OUTPUT_DIR,
+    CONFIG_DIR,
+    NAME_LISTS_DIR,
+    TREE_OUTPUT_DIR,
+)
 
 # ---------------------------------------------------------------------------
 #  App setup
@@ -55,88 +88,73 @@ app.add_middleware(
 )
 
 # ---------------------------------------------------------------------------
-#  Config helpers
+#  Health check
 # ---------------------------------------------------------------------------
 
-
-def _read_json(path: Path) -> dict:
-    if not path.exists():
-        raise HTTPException(status_code=404, detail=f"Config file not found: {path.name}")
-    with open(path, encoding="utf-8") as f:
-        return json.load(f)
+# Set while a simulation thread is running, so the Tauri shell's heartbeat can
+# tell a long silent run apart from a hung process.
+_simulation_running = threading.Event()
 
 
-def _write_json(path: Path, data: dict) -> None:
-    path.parent.mkdir(parents=True, exist_ok=True)
-    with open(path, "w", encoding="utf-8") as f:
-        json.dump(data, f, indent=4, ensure_ascii=False)
+@app.get("/health")
+def health() -> dict[str, str]:
+    return {"status": "busy" if _simulation_running.is_set() else "ready"}
 
 
 # ---------------------------------------------------------------------------
-#  Initialization config endpoints
+#  Graceful shutdown
 # ---------------------------------------------------------------------------
 
-
-@app.get("/config/initialization")
-def get_initialization() -> InitializationConfig:
-    return _read_json(CONFIG_DIR / "initialization.json")
-
-
-@app.put("/config/initialization")
-def put_initialization(body: InitializationConfig) -> dict[str, str]:
-    _write_json(CONFIG_DIR / "initialization.json", body.model_dump(mode="json"))
-    return {"status": "saved"}
-
-
-@app.post("/config/initialization/reset")
-def reset_initialization() -> dict[str, str]:
-    src = FALLBACK_CONFIG_DIR / "initialization.json"
-    dst = CONFIG_DIR / "initialization.json"
-    if not src.exists():
-        raise HTTPException(status_code=404, detail="Fallback initialization config not found")
-    shutil.copy2(src, dst)
-    return {"status": "reset"}
-
-
-@app.post("/config/initialization/set-fallback")
-def set_initialization_fallback() -> dict[str, str]:
-    src = CONFIG_DIR / "initialization.json"
-    dst = FALLBACK_CONFIG_DIR / "initialization.json"
-    if not src.exists():
-        raise HTTPException(status_code=404, detail="Active initialization config not found")
-    shutil.copy2(src, dst)
-    return {"status": "fallback updated"}
+# The uvicorn server instance when started via ``python -m api.main`` / the
+# packaged sidecar; None under ``uvicorn api.main:app`` in development.
+_server: uvicorn.Server | None = None
+
+# The token the shell gave this engine at launch; only a request carrying it
+# may shut the engine down, so no other local process can. Taken out of the
+# environment so that nothing the engine runs inherits it.
+_shutdown_token: str | None = os.environ.pop("CK3GEN_SHUTDOWN_TOKEN", None)
+
+
+@app.post("/shutdown")
+def shutdown(x_ck3gen_token: str | None = Header(default=None)) -> dict[str, str]:
+    """Exit once in-flight requests — including a running simulation stream — finish.
+
+    Called by the Tauri shell on exit so history files are never left half
+    written; the shell kills the process if this takes too long.
+    """
+    if _server is None:
+        raise HTTPException(status_code=409, detail="Server is not running as a sidecar")
+    if not (
+        _shutdown_token
+        and x_ck3gen_token
+        and secrets.compare_digest(x_ck3gen_token, _shutdown_token)
+    ):
+        raise HTTPException(status_code=403, detail="Missing or wrong shutdown token")
+    _server.should_exit = True
+    # Open event streams would hold the server up forever.
+    _events.close()
+    return {"status": "shutting down"}
 
 
 # ---------------------------------------------------------------------------
-#  Life stages config endpoints
+#  Paths
 # ---------------------------------------------------------------------------
 
 
-@app.get("/config/life-stages")
-def get_life_stages() -> LifeStagesConfig:
-    return _read_json(CONFIG_DIR / "life_stages.json")
-
-
-@app.get("/config/life-stages/fallback")
-def get_life_stages_fallback() -> LifeStagesConfig:
-    return _read_json(FALLBACK_CONFIG_DIR / "life_stages.json")
+@app.get("/paths")
+def get_paths() -> dict[str, str]:
+    """Where this engine reads its config and writes its output.
 
-
-@app.put("/config/life-stages")
-def put_life_stages(body: LifeStagesConfig) -> dict[str, str]:
-    _write_json(CONFIG_DIR / "life_stages.json", body.model_dump(mode="json"))
-    return {"status": "saved"}
-
-
-@app.post("/config/life-stages/reset")
-def reset_life_stages() -> dict[str, str]:
-    src = FALLBACK_CONFIG_DIR / "life_stages.json"
-    dst = CONFIG_DIR / "life_stages.json"
-    if not src.exists():
-        raise HTTPException(status_code=404, detail="Fallback life stages config not found")
-    shutil.copy2(src, dst)
-    return {"status": "reset"}
+    The Tauri shell points parallel workers at ``configDir`` and merges their
+    output into ``characterDir`` and ``treeDir``; the native engine also
+    reads the name lists from ``nameListsDir``.
+    """
+    return {
+        "configDir": str(CONFIG_DIR),
+        "characterDir": str(CHARACTER_OUTPUT_DIR),
+        "treeDir": str(TREE_OUTPUT_DIR),
+        "nameListsDir": str(NAME_LISTS_DIR),
+    }
 
 
 # ---------------------------------------------------------------------------
@@ -155,7 +173,7 @@ class _QueueHandler(logging.Handler):
         self._queue.put(self.format(record))
 
 
-async def _stream_simulation() -> AsyncGenerator[str, None]:
+async def _stream_simulation(dynasties: list[str] | None) -> AsyncGenerator[str, None]:
     """Run the simulation in a background thread, yield SSE-formatted log lines."""
     log_queue: queue.Queue[str | None] = queue.Queue()
     handler = _QueueHandler(log_queue)
@@ -167,11 +185,22 @@ async def _stream_simulation() -> AsyncGenerator[str, None]:
     exception_holder: list[Exception] = []
 
     def _run() -> None:
+        control.reset()
+        _simulation_running.set()
+        outcome: dict[str, Any] = {"status": "failed"}
         try:
-            run_main()
+            characters = run_main(dynasties)
+            outcome = {"status": "complete", "characters": characters}
+        except SimulationCancelled as exc:
+            exception_holder.append(exc)
+            outcome = {"status": "cancelled", "removed_files": len(exc.removed)}
         except Exception as exc:  # noqa: BLE001
             exception_holder.append(exc)
+            outcome = {"status": "failed", "error": str(exc)}
         finally:
+            # Cleared first, so the state pushed with the outcome is idle.
+            _simulation_running.clear()
+            control.finish(**outcome)
             log_queue.put(None)  # sentinel — signals stream end
 
     thread = threading.Thread(target=_run, daemon=True)
@@ -195,16 +224,20 @@ async def _stream_simulation() -> AsyncGenerator[str, None]:
 
     thread.join(timeout=5)
 
-    if exception_holder:
+    if exception_holder and isinstance(exception_holder[0], SimulationCancelled):
+        removed = len(exception_holder[0].removed)
+        yield f"data: {json.dumps({'status': 'cancelled', 'removedFiles': removed})}\n\n"
+    elif exception_holder:
         yield f"data: {json.dumps({'error': str(exception_holder[0])})}\n\n"
     else:
         yield f"data: {json.dumps({'status': 'complete'})}\n\n"
 
 
 @app.post("/simulation/run")
-def run_simulation() -> StreamingResponse:
+def run_simulation(body: SimulationRequest | None = None) -> StreamingResponse:
+    dynasties = body.dynasties if body else None
     return StreamingResponse(
-        _stream_simulation(),
+        _stream_simulation(dynasties),
         media_type="text/event-stream",
         headers={
             "Cache-Control": "no-cache",
@@ -214,25 +247,138 @@ def run_simulation() -> StreamingResponse:
 
 
 # ---------------------------------------------------------------------------
-#  Dynasty tree image endpoints
+#  Simulation — progress, pause and cancel
+# ---------------------------------------------------------------------------
+
+
+@app.get("/simulation/progress")
+def get_simulation_progress() -> dict[str, object]:
+    """Where the running simulation is and how the last one ended.
+
+    The same state is pushed to ``/events`` on every change.
+    """
+    running = _simulation_running.is_set()
+    return {
+        "running": running,
+        "paused": running and control.paused,
+        "percent": control.percent if running else None,
+        "year": control.year if running else None,
+        "progress": control.progress if running else None,
+        "lastRun": control.last_run,
+    }
+
+
+def _require_running() -> None:
+    if not _simulation_running.is_set():
+        raise HTTPException(status_code=409, detail="No simulation is running")
+
+
+@app.post("/simulation/pause")
+def pause_simulation() -> dict[str, str]:
+    _require_running()
+    control.pause()
+    return {"status": "paused"}
+
+
+@app.post("/simulation/resume")
+def resume_simulation() -> dict[str, str]:
+    _require_running()
+    control.resume()
+    return {"status": "running"}
+
+
+@app.post("/simulation/cancel")
+def cancel_simulation() -> dict[str, str]:
+    """Stop the running simulation after the year or output file in progress.
+
+    The files the run had written by then are deleted again.
+    """
+    _require_running()
+    control.cancel()
+    return {"status": "cancelling"}
+
+
+# ---------------------------------------------------------------------------
+#  Simulation — event stream
 # ---------------------------------------------------------------------------
 
 
-@app.get("/images")
-def list_images() -> list[str]:
-    if not TREE_OUTPUT_DIR.exists():
-        return []
-    return sorted(p.name for p in TREE_OUTPUT_DIR.glob("family_tree_*.png"))
+class _EventHub:
+    """Fans events out to the queues of every ``/events`` subscriber.
+
+    Events are published from the simulation thread and from request
+    handlers, and handed to each subscriber's event loop to queue.
+    """
+
+    def __init__(self) -> None:
+        self._lock = threading.Lock()
+        self._subscribers: list[tuple[asyncio.AbstractEventLoop, asyncio.Queue[dict | None]]] = []
+
+    def subscribe(self) -> asyncio.Queue[dict | None]:
+        events: asyncio.Queue[dict | None] = asyncio.Queue()
+        with self._lock:
+            self._subscribers.append((asyncio.get_running_loop(), events))
+        return events
+
+    def unsubscribe(self, events: asyncio.Queue[dict | None]) -> None:
+        with self._lock:
+            self._subscribers = [(loop, other) for loop, other in self._subscribers if other is not events]
+
+    def publish(self, event: dict | None) -> None:
+        with self._lock:
+            subscribers = list(self._subscribers)
+        for loop, events in subscribers:
+            try:
+                loop.call_soon_threadsafe(events.put_nowait, event)
+            except RuntimeError:
+                # The subscriber's loop has closed; it unsubscribes itself.
+                pass
+
+    def close(self) -> None:
+        """End every stream."""
+        self.publish(None)
+
+
+_events = _EventHub()
+# Seconds between the comments that keep an idle stream's connection alive.
+_KEEPALIVE_SECONDS = 15.0
 
+control.on_change(lambda: _events.publish({"type": "state", **get_simulation_progress()}))
 
-@app.get("/images/{filename}")
-def get_image(filename: str) -> FileResponse:
-    if not filename.startswith("family_tree_") or not filename.endswith(".png"):
-        raise HTTPException(status_code=400, detail="Invalid image filename")
-    path = TREE_OUTPUT_DIR / filename
-    if not path.exists():
-        raise HTTPException(status_code=404, detail="Image not found")
-    return FileResponse(path, media_type="image/png")
+
+@app.get("/events")
+async def stream_events() -> StreamingResponse:
+    """Push the simulation's state, as ``/simulation/progress`` gives it.
+
+    The current state is sent first, then the new one after every change of
+    progress, of the pause flag or of the last run, each as a ``state``
+    event. The Tauri shell keeps one of these open rather than polling.
+    """
+
+    async def _stream() -> AsyncGenerator[str, None]:
+        events = _events.subscribe()
+        try:
+            yield f"data: {json.dumps({'type': 'state', **get_simulation_progress()})}\n\n"
+            while True:
+                try:
+                    event = await asyncio.wait_for(events.get(), timeout=_KEEPALIVE_SECONDS)
+                except asyncio.TimeoutError:
+                    yield ": keepalive\n\n"
+                    continue
+                if event is None:
+                    break
+                yield f"data: {json.dumps(event)}\n\n"
+        finally:
+            _events.unsubscribe(events)
+
+    return StreamingResponse(
+        _stream(),
+        media_type="text/event-stream",
+        headers={
+            "Cache-Control": "no-cache",
+            "X-Accel-Buffering": "no",
+        },
+    )
 
 
 # ---------------------------------------------------------------------------
@@ -240,4 +386,14 @@ def get_image(filename: str) -> FileResponse:
 # ---------------------------------------------------------------------------
 
 if __name__ == "__main__":
-    uvicorn.run(app, host="127.0.0.1", port=8000, log_level="info")
\ No newline at end of file
+    assert _args is not None
+    # The shell reads the run's progress and the engine's log as protocol
+    # messages on the sidecar's stdout; uvicorn logs through the root logger
+    # rather than to stderr of its own.
+    protocol.hello(sys.stdout)
+    logging.basicConfig(level=logging.INFO, handlers=[protocol.LogHandler(sys.stdout)], force=True)
+    control.report_to(sys.stdout)
+    _server = uvicorn.Server(
+        uvicorn.Config(app, host="127.0.0.1", port=_args.port, log_level="info", log_config=None)
+    )
+    _server.run()
\ No newline at end of file
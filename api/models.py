@@ -70,6 +70,56 @@ class NegativeEvent(BaseModel):
         return self
 
 
+class DynastyTitle(BaseModel):
+    """A title a dynasty holds; unset laws fall back to the dynasty's."""
+
+    id: str = Field(min_length=1)
+    succession: str | None = None
+    gender_law: str | None = None
+    liege: str | None = None
+    development: int | None = Field(default=None, ge=0, le=100)
+
+
+class FaithConversion(BaseModel):
+    """A span of years over which a dynasty converts to a faith (native engine only)."""
+
+    faithID: str = Field(min_length=1)
+    startYear: int
+    endYear: int
+
+    @model_validator(mode="after")
+    def end_after_start(self) -> FaithConversion:
+        if self.endYear < self.startYear:
+            raise ValueError("endYear must be greater than or equal to startYear.")
+        return self
+
+
+class War(BaseModel):
+    """A span of years a dynasty is at war (native engine only)."""
+
+    name: str = ""
+    startYear: int
+    endYear: int
+    battleDeathChance: float = Field(ge=0.0, le=1.0)
+    woundChance: float = Field(default=0.0, ge=0.0, le=1.0)
+    veteranChance: float = Field(default=0.0, ge=0.0, le=1.0)
+    womenFight: bool = False
+
+    @model_validator(mode="after")
+    def end_after_start(self) -> War:
+        if self.endYear < self.startYear:
+            raise ValueError("endYear must be greater than or equal to startYear.")
+        return self
+
+
+class MarriageDoctrine(BaseModel):
+    """How many wives and concubines a man may have (native engine only)."""
+
+    maxSpouses: int = Field(default=1, ge=1)
+    maxConcubines: int = Field(default=0, ge=0)
+    consortChance: float = Field(default=0.0, ge=0.0, le=1.0)
+
+
 class Dynasty(BaseModel):
     """A single dynasty definition inside initialization.json."""
 
@@ -84,13 +134,43 @@ class Dynasty(BaseModel):
     progenitorMaleBirthYear: int
     allowFirstCousinMarriage: bool = False
     prioritiseLowbornMarriage: bool = False
+    # The dynasty's women always marry matrilineally (native engine only).
+    matrilinealMarriage: bool = False
+    # Weights over the matchmaking ones of matches across faiths and cultures
+    # (native engine only).
+    differentFaithWeight: float | None = Field(default=None, ge=0.0)
+    differentCultureWeight: float | None = Field(default=None, ge=0.0)
     numenorBloodTier: int | None = None
     forceDynastyAlive: bool = False
     # Each entry is a comma-separated "language_id,start_year,end_year" string.
     languages: list[str] = Field(default_factory=list)
+    titles: list[DynastyTitle] = Field(default_factory=list)
+    conversions: list[FaithConversion] = Field(default_factory=list)
+    wars: list[War] = Field(default_factory=list)
+    marriageDoctrine: MarriageDoctrine | None = None
     nameInheritance: NameInheritance
 
 
+class Calendar(BaseModel):
+    """The calendar output dates are written in; config years stay simulation years."""
+
+    # Calendar year of simulation year 0.
+    epochOffset: int = 0
+    # Simulation years to each calendar year.
+    yearLength: int = Field(default=1, ge=1)
+
+
+class ModSettings(BaseModel):
+    """How the shell's scaffold_mod describes the mod to the launcher."""
+
+    name: Annotated[str, Field(min_length=1, pattern=r'^[^"\n]+$')]
+    version: Annotated[str, Field(min_length=1, pattern=r'^[^"\n]+$')] = "1.0.0"
+    supportedVersion: Annotated[str, Field(min_length=1, pattern=r'^[^"\n]+$')] = "1.*"
+    tags: list[Annotated[str, Field(min_length=1, pattern=r'^[^"\n]+$')]] = Field(
+        default_factory=list
+    )
+
+
 class InitializationConfig(BaseModel):
     """Full shape of config/initialization.json."""
 
@@ -100,6 +180,22 @@ class InitializationConfig(BaseModel):
     maxYear: int
     generationMax: int = Field(gt=0)
     initialCharID: int = Field(gt=0)
+    calendar: Calendar = Field(default_factory=Calendar)
+    # Languages the localisation files are written for; English alone if empty.
+    localizationLanguages: list[Annotated[str, Field(pattern=r"^[a-z_]+$")]] = Field(
+        default_factory=list
+    )
+
+    modSettings: ModSettings | None = None
+    # The game version the files are written for; the latest format if unset.
+    gameVersion: Annotated[str, Field(pattern=r"^[vV]?\d+\.\d+(\.\d+)?$")] | None = None
+
+    @field_validator("localizationLanguages")
+    @classmethod
+    def unique_languages(cls, languages: list[str]) -> list[str]:
+        if len(set(languages)) != len(languages):
+            raise ValueError("localizationLanguages must not repeat a language.")
+        return languages
 
     @model_validator(mode="after")
     def max_year_after_min(self) -> InitializationConfig:
@@ -124,18 +220,171 @@ class RateSet(BaseModel):
     Female: RateList
 
 
+class SurvivalCurve(BaseModel):
+    """Gompertz–Makeham survival curve with an infant term."""
+
+    infant: float = Field(ge=0.0)
+    infantDecay: float = Field(ge=0.0)
+    background: float = Field(ge=0.0)
+    aging: float = Field(ge=0.0)
+    agingRate: float = Field(ge=0.0)
+
+
+class SurvivalCurveSet(BaseModel):
+    Male: SurvivalCurve
+    Female: SurvivalCurve
+
+
+class MortalityEra(BaseModel):
+    """A span of years with its own mortality."""
+
+    name: str = ""
+    startYear: int
+    endYear: int
+    curve: SurvivalCurveSet | None = None
+    multiplier: float = Field(default=1.0, ge=0.0)
+
+    @model_validator(mode="after")
+    def end_after_start(self) -> MortalityEra:
+        if self.endYear < self.startYear:
+            raise ValueError("endYear must be greater than or equal to startYear.")
+        return self
+
+
+class Mortality(BaseModel):
+    """Era and trait adjustments to the mortality tables, and lifespan caps."""
+
+    eras: list[MortalityEra] = []
+    traitMultipliers: dict[str, float] = {}
+    maxLifespan: int = Field(default=120, gt=0)
+    bloodTierMaxLifespan: dict[str, int] = {}
+
+    @field_validator("traitMultipliers", mode="after")
+    @classmethod
+    def multipliers_non_negative(cls, multipliers: dict[str, float]) -> dict[str, float]:
+        for trait, multiplier in multipliers.items():
+            if multiplier < 0.0:
+                raise ValueError(f"Multiplier for '{trait}' must be non-negative.")
+        return multipliers
+
+    @field_validator("bloodTierMaxLifespan", mode="after")
+    @classmethod
+    def tiers_in_range(cls, caps: dict[str, int]) -> dict[str, int]:
+        for tier, lifespan in caps.items():
+            if not (tier.isdigit() and 1 <= int(tier) <= 10):
+                raise ValueError(f"Blood tier '{tier}' must be between 1 and 10.")
+            if lifespan <= 0:
+                raise ValueError(f"Lifespan cap for tier {tier} must be greater than 0.")
+        return caps
+
+
+class Pregnancy(BaseModel):
+    """When characters conceive, and how long births take (native engine only)."""
+
+    minMaternalAge: int = Field(default=16, ge=0)
+    maxMaternalAge: int = Field(default=45, ge=0)
+    gestationMonths: int = Field(default=9, gt=0)
+    monthsBetweenBirths: int = Field(default=12, ge=0)
+    fertilityOnset: int = Field(default=16, ge=0)
+    bloodTierFertilityOnset: dict[str, int] = {}
+
+    @model_validator(mode="after")
+    def window_in_order(self) -> Pregnancy:
+        if self.maxMaternalAge < self.minMaternalAge:
+            raise ValueError("maxMaternalAge must be greater than or equal to minMaternalAge.")
+        return self
+
+    @field_validator("bloodTierFertilityOnset", mode="after")
+    @classmethod
+    def tiers_in_range(cls, onsets: dict[str, int]) -> dict[str, int]:
+        for tier, onset in onsets.items():
+            if not (tier.isdigit() and 1 <= int(tier) <= 10):
+                raise ValueError(f"Blood tier '{tier}' must be between 1 and 10.")
+            if onset < 0:
+                raise ValueError(f"Fertility onset for tier {tier} must be non-negative.")
+        return onsets
+
+
+class AgeLethality(BaseModel):
+    fromAge: int = Field(ge=0)
+    toAge: int = Field(ge=0)
+    multiplier: float = Field(ge=0.0)
+
+    @model_validator(mode="after")
+    def to_after_from(self) -> AgeLethality:
+        if self.toAge < self.fromAge:
+            raise ValueError("toAge must be greater than or equal to fromAge.")
+        return self
+
+
+class Epidemic(BaseModel):
+    """A wave of plague (native engine only)."""
+
+    name: str = ""
+    startYear: int
+    endYear: int
+    dynasties: list[str] = []
+    cultures: list[str] = []
+    lethality: list[AgeLethality]
+    deathReason: str = Field(default="death_ill", min_length=1)
+
+    @model_validator(mode="after")
+    def end_after_start(self) -> Epidemic:
+        if self.endYear < self.startYear:
+            raise ValueError("endYear must be greater than or equal to startYear.")
+        return self
+
+
+class Bastardy(BaseModel):
+    """What becomes of bastards (native engine only)."""
+
+    legitimizationChance: float = Field(default=0.0, ge=0.0, le=1.0)
+    maxLegitimizationAge: int = Field(default=16, ge=0)
+    lowbornLineChance: float = Field(default=0.0, ge=0.0, le=1.0)
+
+    @model_validator(mode="after")
+    def chances_add_up(self) -> Bastardy:
+        if self.legitimizationChance + self.lowbornLineChance > 1.0:
+            raise ValueError(
+                "legitimizationChance and lowbornLineChance must add up to at most 1.0."
+            )
+        return self
+
+
+class BloodTierFertility(BaseModel):
+    """How a blood tier bends the fertility tables (native engine only)."""
+
+    peakYears: int = Field(ge=0)
+    factor: float = Field(ge=0.0)
+
+
 class LifeStagesConfig(BaseModel):
     """Full shape of config/life_stages.json."""
 
     mortalityRates: RateSet
     marriageRates: RateSet
     fertilityRates: RateSet
+    bloodTierFertility: dict[str, BloodTierFertility] = {}
     desperationMarriageRates: list[float]
     marriageMaxAgeDifference: int = Field(ge=0)
     maximumNumberOfChildren: int = Field(ge=0)
     minimumYearsBetweenChildren: int = Field(ge=0)
     bastardyChanceMale: float = Field(ge=0.0, le=1.0)
     bastardyChanceFemale: float = Field(ge=0.0, le=1.0)
+    mortality: Mortality | None = None
+    pregnancy: Pregnancy | None = None
+    epidemics: list[Epidemic] = []
+    bastardy: Bastardy | None = None
+
+    @field_validator("bloodTierFertility", mode="after")
+    @classmethod
+    def tiers_in_range(
+        cls, curves: dict[str, BloodTierFertility]
+    ) -> dict[str, BloodTierFertility]:
+        for tier in curves:
+            if not (tier.isdigit() and 1 <= int(tier) <= 10):
+                raise ValueError(f"Blood tier '{tier}' must be between 1 and 10.")
+        return curves
 
     @field_validator("mortalityRates", "marriageRates", "fertilityRates", mode="after")
     @classmethod
@@ -149,4 +398,15 @@ class LifeStagesConfig(BaseModel):
                     )
         return rate_set
 
-    model_config = {"extra": "allow"}
\ No newline at end of file
+    model_config = {"extra": "allow"}
+
+# ---------------------------------------------------------------------------
+#  Simulation request models
+# ---------------------------------------------------------------------------
+
+
+class SimulationRequest(BaseModel):
+    """Optional body of POST /simulation/run."""
+
+    # Restrict the run to these dynasty IDs; all dynasties when omitted.
+    dynasties: list[str] | None = Field(default=None, min_length=1)
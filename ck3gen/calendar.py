@@ -0,0 +1,88 @@
+"""
+The calendar the generated files are dated in.
+
+The simulation dates everything in its own years, and every year in the
+config is one. The ``calendar`` section of initialization.json sets the
+calendar the output is written in instead: simulation year ``y`` is calendar
+year ``epochOffset + y // yearLength``, so that years before the epoch come
+out as zero or negative and a calendar year longer than one simulation year,
+such as the Elvish yén of 144 years, spreads its days evenly over them.
+Mirrors ``date.rs`` of the native simulation.
+"""
+
+from __future__ import annotations
+
+import re
+
+# Days in each month of the game's calendar, which has no leap years.
+MONTH_DAYS = (31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31)
+YEAR_DAYS = 365
+
+# A "year.month.day" date key, years of any length and sign included.
+DATE_PATTERN = r"(-?\d+)\.(\d{1,2})\.(\d{1,2})"
+
+
+class Calendar:
+    def __init__(self, epoch_offset: int = 0, year_length: int = 1) -> None:
+        self.epoch_offset = epoch_offset
+        self.year_length = year_length
+
+    @classmethod
+    def from_config(cls, initialization: dict) -> Calendar:
+        calendar = initialization.get("calendar", {})
+        return cls(calendar.get("epochOffset", 0), calendar.get("yearLength", 1))
+
+    @property
+    def is_default(self) -> bool:
+        return self.epoch_offset == 0 and self.year_length == 1
+
+    def to_calendar(self, year: int, month: int, day: int) -> tuple[int, int, int]:
+        """The calendar date of a simulation date."""
+        length = self.year_length
+        if length == 1:
+            return year + self.epoch_offset, month, day
+        position = (year % length) * YEAR_DAYS + _day_of_year(month, day)
+        return _from_day_of_year(self.epoch_offset + year // length, position // length)
+
+    def from_calendar(self, year: int, month: int, day: int) -> tuple[int, int, int]:
+        """The simulation date of a calendar date: the first that dates to it."""
+        length = self.year_length
+        if length == 1:
+            return year - self.epoch_offset, month, day
+        position = _day_of_year(month, day) * length
+        return _from_day_of_year(
+            (year - self.epoch_offset) * length + position // YEAR_DAYS,
+            position % YEAR_DAYS,
+        )
+
+    def format(self, year: int, month: int, day: int) -> str:
+        """A simulation date as the history files write it in this calendar."""
+        return format_date(*self.to_calendar(year, month, day))
+
+    def format_text(self, text: str) -> str:
+        """A "year.month.day" simulation date as written in this calendar."""
+        match = re.fullmatch(DATE_PATTERN, text)
+        if not match:
+            return text
+        return self.format(*map(int, match.groups()))
+
+
+def format_date(year: int, month: int, day: int) -> str:
+    """
+    A date as the history files write it: the year unpadded, with its sign
+    if negative, and the month and day in two digits, e.g. "-12.03.07".
+    """
+    return f"{year}.{month:02}.{day:02}"
+
+
+def _day_of_year(month: int, day: int) -> int:
+    month = min(max(month, 1), 12)
+    return sum(MONTH_DAYS[:month - 1]) + min(max(day, 1), MONTH_DAYS[month - 1]) - 1
+
+
+def _from_day_of_year(year: int, day: int) -> tuple[int, int, int]:
+    month = 0
+    while day >= MONTH_DAYS[month]:
+        day -= MONTH_DAYS[month]
+        month += 1
+    return year, month + 1, day + 1
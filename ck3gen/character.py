@@ -1,6 +1,7 @@
 import random
 import logging
 from utils.utils import generate_random_date
+from ck3gen.calendar import Calendar
 
 # ==============================================================
 # Congenital‑trait helper (beauty / intellect / physique)
@@ -46,6 +47,16 @@ class Character:
     # Populated by the simulation pipeline before export_characters() is called.
     # Maps dynastyID -> list of (language_id, start_year, end_year) tuples.
     DYNASTY_LANGUAGE_RULES: dict = {}
+    # The calendar event dates are written in; they are kept in simulation years.
+    CALENDAR: Calendar = Calendar()
+    # The (major, minor) game version the history is written for; the latest
+    # format if None. Languages came with 1.5 and memories with 1.9.
+    GAME_VERSION: tuple[int, int] | None = None
+
+    @classmethod
+    def supports(cls, since: tuple[int, int]) -> bool:
+        """Whether the targeted game version is ``since`` or later."""
+        return cls.GAME_VERSION is None or cls.GAME_VERSION >= since
 
     def __init__(
         self,
@@ -508,12 +519,12 @@ class Character:
                 # Standard event formatting
                 event_lines = []
                 if event_detail == "birth = yes":
-                    event_lines.append(f"\t{event_date} = {{")
+                    event_lines.append(f"\t{self.CALENDAR.format_text(event_date)} = {{")
                     event_lines.append(f"\t    {event_detail}")
 
                     lang_effects = []
                     for lang, start, end in self.DYNASTY_LANGUAGE_RULES.get(self.dynasty, []):
-                        if start <= self.birth_year <= end:
+                        if start <= self.birth_year <= end and self.supports((1, 5)):
                             lang_effects.append(lang)
                     if lang_effects:
                         event_lines.append(f"\t    effect = {{")
@@ -531,7 +542,7 @@ class Character:
                     else:
                         event_desc = f"# Event at age {age}"
 
-                    event_lines.append(f"\t{event_date} = {{  {event_desc}")
+                    event_lines.append(f"\t{self.CALENDAR.format_text(event_date)} = {{  {event_desc}")
                     for detail_line in event_detail.strip().splitlines():
                         event_lines.append(f"\t    {detail_line.strip()}")
                     event_lines.append(f"\t}}")
@@ -552,24 +563,32 @@ class Character:
         if self.is_adopted:
             for ev_date, ev_detail in self.events:
                 if ev_detail.startswith("adopted_by ="):
+                    parent_id: str = (
+                        self.father.char_id if self.father
+                        else self.mother.char_id if self.mother
+                        else ev_detail.split("=", 1)[1].strip()
+                    )
+                    # A ward of the dynasty nobody took in has no one to
+                    # remember, nor anything else to record.
+                    if parent_id == "unknown":
+                        break
+                    memories = self.supports((1, 9))
+                    if not memories and not (self.father or self.mother):
+                        break
                     lines.append("")
-                    lines.append(f"\t{ev_date} = {{  # Adopted")
+                    lines.append(f"\t{self.CALENDAR.format_text(ev_date)} = {{  # Adopted")
                     lines.append(f"\t    effect = {{")
                     if self.father:
                         lines.append(f"\t        set_father = {self.father.char_id}")
                     if self.mother:
                         lines.append(f"\t        set_mother = {self.mother.char_id}")
-                    parent_id: str = (
-                        self.father.char_id if self.father
-                        else self.mother.char_id if self.mother
-                        else ev_detail.split("=", 1)[1].strip()
-                    )
-                    lines.append(f"\t        create_character_memory = {{")
-                    lines.append(f"\t            type = was_adopted")
-                    lines.append(f"\t            participants = {{")
-                    lines.append(f"\t                parent = {parent_id}")
-                    lines.append(f"\t            }}")
-                    lines.append(f"\t        }}")
+                    if memories:
+                        lines.append(f"\t        create_character_memory = {{")
+                        lines.append(f"\t            type = was_adopted")
+                        lines.append(f"\t            participants = {{")
+                        lines.append(f"\t                parent = {parent_id}")
+                        lines.append(f"\t            }}")
+                        lines.append(f"\t        }}")
                     lines.append(f"\t    }}")
                     lines.append(f"\t}}")
                     break
@@ -8,6 +8,7 @@ Loads, validates, and provides access to the three JSON configuration files:
 
 NUM_SIMULATIONS is read from the ``CK3GEN_NUM_SIMULATIONS`` environment
 variable (default 1), so it can be overridden without touching source code.
+SEED is read from ``CK3GEN_SEED``; when set, runs are reproducible.
 All other formerly hardcoded debug flags have been removed — use the standard
 ``logging`` level instead (e.g. ``--log-level DEBUG`` or set
 ``logging.basicConfig(level=logging.DEBUG)`` in your entry point).
@@ -18,8 +19,11 @@ from __future__ import annotations
 import json
 import logging
 import os
+import re
 from pathlib import Path
 
+from utils.utils import parse_game_version
+
 logger = logging.getLogger(__name__)
 
 # ---------------------------------------------------------------------------
@@ -30,6 +34,10 @@ logger = logging.getLogger(__name__)
 # Override via environment variable: CK3GEN_NUM_SIMULATIONS=5
 NUM_SIMULATIONS: int = int(os.environ.get("CK3GEN_NUM_SIMULATIONS", "1"))
 
+# Seed for the random number generator; unseeded when unset.
+# Override via environment variable: CK3GEN_SEED=1234
+SEED: int | None = int(os.environ["CK3GEN_SEED"]) if os.environ.get("CK3GEN_SEED") else None
+
 
 # ---------------------------------------------------------------------------
 #  ConfigLoader
@@ -118,6 +126,22 @@ class ConfigLoader:
                     f"do not sum to 1.0 (got {total:.6f})."
                 )
 
+        languages = init.get("localizationLanguages", [])
+        for language in languages:
+            if not re.fullmatch(r"[a-z_]+", language):
+                raise ValueError(
+                    f"Localization language '{language}' must be lowercase letters and underscores."
+                )
+        if len(set(languages)) != len(languages):
+            raise ValueError("localizationLanguages must not repeat a language.")
+
+        version = init.get("gameVersion")
+        if version is not None and parse_game_version(version) is None:
+            raise ValueError(f"gameVersion '{version}' is not a game version such as 1.12 or 1.12.5.")
+
+        if init.get("calendar", {}).get("yearLength", 1) < 1:
+            raise ValueError("calendar.yearLength must be at least 1.")
+
     def _validate_life_stages(self) -> None:
         life = self.config.get("life_stages", {})
 
@@ -148,6 +172,8 @@ class ConfigLoader:
             if not isinstance(life[key], int) or life[key] < 0:
                 raise ValueError(f"'{key}' must be a non-negative integer.")
 
+        self._validate_mortality(life.get("mortality") or {})
+
         for key in ("childbirthMinAge", "childbirthMaxAge"):
             if key in life:
                 logger.warning(
@@ -156,6 +182,40 @@ class ConfigLoader:
                     key,
                 )
 
+    def _validate_mortality(self, mortality: dict) -> None:
+        for i, era in enumerate(mortality.get("eras", [])):
+            if era["endYear"] < era["startYear"]:
+                raise ValueError(
+                    f"life_stages.mortality.eras[{i}].endYear must be greater than "
+                    "or equal to startYear."
+                )
+            values = {"multiplier": era.get("multiplier", 1.0)}
+            for sex, curve in (era.get("curve") or {}).items():
+                for field, value in curve.items():
+                    values[f"curve.{sex}.{field}"] = value
+            for field, value in values.items():
+                if not (0.0 <= value < float("inf")):
+                    raise ValueError(
+                        f"life_stages.mortality.eras[{i}].{field} must be a non-negative number."
+                    )
+        for trait, multiplier in mortality.get("traitMultipliers", {}).items():
+            if not (0.0 <= multiplier < float("inf")):
+                raise ValueError(
+                    f"life_stages.mortality.traitMultipliers.{trait} must be a non-negative number."
+                )
+        if mortality.get("maxLifespan", 120) <= 0:
+            raise ValueError("life_stages.mortality.maxLifespan must be greater than 0.")
+        for tier, lifespan in mortality.get("bloodTierMaxLifespan", {}).items():
+            if not (tier.isdigit() and 1 <= int(tier) <= 10):
+                raise ValueError(
+                    f"life_stages.mortality.bloodTierMaxLifespan.{tier}: "
+                    "blood tiers run from 1 to 10."
+                )
+            if lifespan <= 0:
+                raise ValueError(
+                    f"life_stages.mortality.bloodTierMaxLifespan.{tier} must be greater than 0."
+                )
+
     def _validate_skills_and_traits(self) -> None:
         skills = self.config.get("skills_and_traits", {})
 
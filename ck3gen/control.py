@@ -0,0 +1,272 @@
+"""
+ck3gen/control.py
+~~~~~~~~~~~~~~~~~
+Progress, pausing and cancellation of a running simulation.
+
+The simulation loop calls :meth:`SimulationControl.checkpoint` once per
+simulated year, and the pipeline calls :meth:`SimulationControl.writing`
+between the output files it writes; a cancelled run stops at the next of
+either. The API server reads the progress and flips the pause and
+cancel flags from request handlers, which run on other threads than the
+simulation itself.
+
+A run moves through three phases: ``seeding`` the dynasties, ``simulating``
+the years, and ``writing`` the output files. Once :meth:`report_to` is given
+a stream, every change of phase or year is also written to it as a
+``progress`` notification of :mod:`ck3gen.protocol`, with the phase, the
+year being simulated and which of how many it is, the percentage of the
+whole run and the seconds it is expected to take still. The packaged server
+reports to its stdout, which the Tauri shell turns into progress events. Functions given to
+:meth:`on_change` are called after every change of progress, of the pause
+flag and of the last run's outcome, which is how the API server pushes
+state to its event stream instead of being polled.
+"""
+
+from __future__ import annotations
+
+import threading
+import time
+from typing import Callable, TextIO
+
+from ck3gen import protocol
+
+SEEDING = "seeding"
+SIMULATING = "simulating"
+WRITING = "writing"
+
+# Share of a run's percentage the simulated years take up; seeding is quick,
+# and writing the files takes the rest.
+_SIMULATING_SHARE = 90.0
+# Output steps of the writing phase: character history, title history and
+# family trees.
+WRITING_STEPS = 3
+
+
+class SimulationCancelled(Exception):
+    """Raised out of the simulation loop once a cancel has been requested.
+
+    ``removed`` lists the partial output files deleted once the run stopped.
+    """
+
+    def __init__(self, message: str = "Simulation cancelled.", removed: list[str] | None = None) -> None:
+        super().__init__(message)
+        self.removed = removed or []
+
+
+class SimulationControl:
+    """Thread-safe progress and pause/cancel flags for one simulation at a time."""
+
+    def __init__(self) -> None:
+        self._lock = threading.Lock()
+        self._resumed = threading.Event()
+        self._resumed.set()
+        self._cancelled = threading.Event()
+        self._min_year: int | None = None
+        self._max_year: int | None = None
+        self._year: int | None = None
+        self._phase: str | None = None
+        self._written = 0
+        self._run = 0
+        self._started: float | None = None
+        self._simulating_since: float | None = None
+        self._last_run: dict[str, object] | None = None
+        self._stream: TextIO | None = None
+        self._listeners: list[Callable[[], None]] = []
+
+    def report_to(self, stream: TextIO | None) -> None:
+        """Notify ``stream`` of every change of progress, or stop if None."""
+        self._stream = stream
+
+    def on_change(self, listener: Callable[[], None]) -> None:
+        """Call ``listener`` after every change, on the thread making it."""
+        self._listeners.append(listener)
+
+    def reset(self) -> None:
+        """Clear the flags and progress left over from a previous run."""
+        with self._lock:
+            self._min_year = self._max_year = self._year = None
+            self._phase = None
+            self._written = 0
+            self._run += 1
+            self._started = time.monotonic()
+            self._simulating_since = None
+        self._cancelled.clear()
+        self._resumed.set()
+
+    def seeding(self) -> None:
+        """Record that the dynasties are being set up, before the first year.
+
+        Raises :class:`SimulationCancelled` if a cancel has been requested.
+        """
+        with self._lock:
+            self._phase = SEEDING
+        self._report()
+        self._raise_if_cancelled()
+
+    def writing(self, step: int) -> None:
+        """Record that writing the output has reached ``step``, counting from 0.
+
+        Each step starts once the one before has written its files, so a
+        cancel is honoured here too: raises :class:`SimulationCancelled` if
+        one has been requested.
+        """
+        with self._lock:
+            self._phase = WRITING
+            self._written = max(0, min(WRITING_STEPS, step))
+        self._report()
+        if step < WRITING_STEPS:
+            self._raise_if_cancelled()
+
+    def finish(
+        self,
+        status: str,
+        characters: int | None = None,
+        error: str | None = None,
+        removed_files: int | None = None,
+    ) -> None:
+        """Record how the run started by the last :meth:`reset` ended.
+
+        ``status`` is ``complete``, ``failed`` or ``cancelled``; a cancelled
+        run gives the number of partial output files it deleted.
+        """
+        with self._lock:
+            elapsed = time.monotonic() - self._started if self._started is not None else 0.0
+            self._last_run = {
+                "run": self._run,
+                "status": status,
+                "characters": characters,
+                "elapsedSeconds": round(elapsed, 1),
+                "error": error,
+                "removedFiles": removed_files,
+            }
+        self._changed()
+
+    def begin(self, min_year: int, max_year: int) -> None:
+        """Record the simulated period, before the first checkpoint."""
+        with self._lock:
+            self._min_year, self._max_year, self._year = min_year, max_year, None
+            self._phase = SIMULATING
+            self._simulating_since = time.monotonic()
+
+    def checkpoint(self, year: int) -> None:
+        """Record that ``year`` is being simulated, waiting while paused.
+
+        Raises :class:`SimulationCancelled` if a cancel has been requested.
+        """
+        with self._lock:
+            self._year = year
+        self._report()
+        paused = time.monotonic()
+        while not self._resumed.wait(timeout=0.1):
+            if self._cancelled.is_set():
+                break
+        # Time spent paused does not count towards the estimate.
+        with self._lock:
+            if self._simulating_since is not None:
+                self._simulating_since += time.monotonic() - paused
+        self._raise_if_cancelled()
+
+    def _raise_if_cancelled(self) -> None:
+        if self._cancelled.is_set():
+            raise SimulationCancelled()
+
+    def pause(self) -> None:
+        self._resumed.clear()
+        self._changed()
+
+    def resume(self) -> None:
+        self._resumed.set()
+        self._changed()
+
+    def cancel(self) -> None:
+        """Stop the run at the next checkpoint or writing step, even if it is paused."""
+        self._cancelled.set()
+        self._resumed.set()
+
+    @property
+    def paused(self) -> bool:
+        return not self._resumed.is_set()
+
+    @property
+    def year(self) -> int | None:
+        with self._lock:
+            return self._year
+
+    @property
+    def last_run(self) -> dict[str, object] | None:
+        """Outcome of the last finished run, numbered so pollers can spot a new one."""
+        with self._lock:
+            return dict(self._last_run) if self._last_run is not None else None
+
+    @property
+    def percent(self) -> float | None:
+        """Share of the simulated period done so far, or None before it starts."""
+        with self._lock:
+            return self._simulated_percent()
+
+    @property
+    def progress(self) -> dict[str, object] | None:
+        """Where the run is, as its progress lines give it, or None before it starts."""
+        with self._lock:
+            return self._progress()
+
+    def _simulated_percent(self) -> float | None:
+        if self._min_year is None or self._max_year is None or self._year is None:
+            return None
+        span = self._max_year - self._min_year + 1
+        done = self._year - self._min_year
+        return max(0.0, min(100.0, 100.0 * done / span))
+
+    def _progress(self) -> dict[str, object] | None:
+        if self._phase is None:
+            return None
+        years = None
+        if self._min_year is not None and self._max_year is not None:
+            years = self._max_year - self._min_year + 1
+        index = None
+        if self._year is not None and self._min_year is not None:
+            index = self._year - self._min_year + 1
+
+        eta: float | None = None
+        if self._phase == SEEDING:
+            percent = 0.0
+        elif self._phase == SIMULATING:
+            simulated = self._simulated_percent() or 0.0
+            percent = _SIMULATING_SHARE * simulated / 100.0
+            # The years done so far say how long the rest will take; the
+            # first year gives no measure yet.
+            if self._simulating_since is not None and index is not None and years and index > 1:
+                elapsed = time.monotonic() - self._simulating_since
+                eta = elapsed / (index - 1) * (years - index + 1)
+        else:
+            rest = 100.0 - _SIMULATING_SHARE
+            percent = _SIMULATING_SHARE + rest * self._written / WRITING_STEPS
+
+        return {
+            "run": self._run,
+            "phase": self._phase,
+            "year": self._year,
+            "yearIndex": index,
+            "years": years,
+            "percent": round(percent, 1),
+            "etaSeconds": round(eta, 1) if eta is not None else None,
+        }
+
+    def _changed(self) -> None:
+        for listener in list(self._listeners):
+            listener()
+
+    def _report(self) -> None:
+        self._changed()
+        stream = self._stream
+        if stream is None:
+            return
+        with self._lock:
+            progress = self._progress()
+        if progress is None:
+            return
+        protocol.notify(stream, "progress", progress)
+
+
+# The single instance shared by the simulation loop and the API server.
+control = SimulationControl()
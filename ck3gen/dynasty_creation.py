@@ -3,11 +3,13 @@ Dynasty file generation utilities.
 
 Produces three CK3-compatible output files from the active initialization
 config:
-  - dynasty_definitions.txt       (dynasty blocks)
-  - lotr_dynasty_names_l_english.yml   (name localisation)
-  - lotr_mottos_l_english.yml          (motto localisation)
+  - dynasty_definitions.txt       (dynasty blocks, for common/dynasties)
+  - localization/<language>/lotr_dynasty_names_l_<language>.yml   (name localisation)
+  - localization/<language>/lotr_mottos_l_<language>.yml          (motto localisation)
 
-All files are written to CHARACTER_OUTPUT_DIR defined in ck3gen.paths.
+All files are written to CHARACTER_OUTPUT_DIR defined in ck3gen.paths, the
+localisation once for each of the config's localizationLanguages (English if
+it names none).
 """
 
 from __future__ import annotations
@@ -20,6 +22,11 @@ from ck3gen.paths import CHARACTER_OUTPUT_DIR, CONFIG_DIR
 
 logger = logging.getLogger(__name__)
 
+# Directory of the localisation files, under CHARACTER_OUTPUT_DIR.
+LOCALIZATION_DIR = "localization"
+# Language of the localisation files when the config names none.
+DEFAULT_LANGUAGE = "english"
+
 
 # ---------------------------------------------------------------------------
 #  Internal helpers
@@ -41,6 +48,30 @@ def _ensure_output_dir() -> None:
     CHARACTER_OUTPUT_DIR.mkdir(parents=True, exist_ok=True)
 
 
+def _write_localization(
+    config: dict, file_prefix: str, entries: list[tuple[str, str]]
+) -> list[Path]:
+    """Write ``entries`` as a localisation file for every configured language.
+
+    Each goes to ``localization/<language>/<file_prefix>_l_<language>.yml``
+    under a ``l_<language>:`` header, with the same text in every language.
+    Returns the paths written.
+    """
+    languages = config.get("localizationLanguages") or [DEFAULT_LANGUAGE]
+    paths: list[Path] = []
+    for language in languages:
+        output_dir = CHARACTER_OUTPUT_DIR / LOCALIZATION_DIR / language
+        output_dir.mkdir(parents=True, exist_ok=True)
+        lines = [f"l_{language}:"]
+        for key, text in entries:
+            text = text.replace("\\", "\\\\").replace('"', '\\"')
+            lines.append(f' {key}:0 "{text}"')
+        output_path = output_dir / f"{file_prefix}_l_{language}.yml"
+        output_path.write_text("\n".join(lines) + "\n", encoding="utf-8-sig")
+        paths.append(output_path)
+    return paths
+
+
 # ---------------------------------------------------------------------------
 #  Public API
 # ---------------------------------------------------------------------------
@@ -63,27 +94,33 @@ def generate_dynasty_definitions(
     lines: list[str] = []
 
     for dynasty in dynasties:
-        dynasty_id = dynasty.get("dynastyID", "").replace("dynasty_", "")
+        # Characters refer to their dynasty by its config ID, prefix or not.
+        dynasty_id = dynasty.get("dynastyID", "")
+        key = dynasty_id.replace("dynasty_", "")
         culture_id = dynasty.get("cultureID", "unknown_culture")
 
         if not dynasty_id:
             logger.warning("Skipping dynasty with missing dynastyID.")
             continue
+        # Houses belong to a dynasty the target mod defines.
+        if dynasty.get("isHouse", False):
+            continue
 
-        lines.append(f"dynasty_{dynasty_id} = {{")
-        lines.append(f'\tname = "dynn_{dynasty_id}"')
+        lines.append(f"{dynasty_id} = {{")
+        lines.append(f'\tname = "dynn_{key}"')
         lines.append(f'\tculture = "{culture_id}"')
-        lines.append(f"\tmotto = dynn_{dynasty_id}_motto")
+        if dynasty.get("dynastyMotto"):
+            lines.append(f"\tmotto = dynn_{key}_motto")
         lines.append("}\n")
 
-    output_path.write_text("\n".join(lines), encoding="utf-8")
+    output_path.write_text("\n".join(lines), encoding="utf-8-sig")
 
     logger.debug("Dynasty definitions exported to %s.", output_path)
 
 
 def generate_dynasty_name_localization(
     config_file: Path | str = CONFIG_DIR / "initialization.json",
-    output_file: str = "lotr_dynasty_names_l_english.yml",
+    file_prefix: str = "lotr_dynasty_names",
 ) -> None:
     """Write CK3 dynasty name localisation entries."""
     config_file = Path(config_file)
@@ -91,13 +128,8 @@ def generate_dynasty_name_localization(
     if config is None:
         return
 
-    _ensure_output_dir()
-    output_path = CHARACTER_OUTPUT_DIR / output_file
-
-    dynasties = config.get("dynasties", [])
-    lines: list[str] = []
-
-    for dynasty in dynasties:
+    entries: list[tuple[str, str]] = []
+    for dynasty in config.get("dynasties", []):
         dynasty_id = dynasty.get("dynastyID", "").replace("dynasty_", "")
         dynasty_name = dynasty.get("dynastyName", "")
 
@@ -105,16 +137,15 @@ def generate_dynasty_name_localization(
             logger.warning("Skipping dynasty with missing dynastyName.")
             continue
 
-        lines.append(f'dynn_{dynasty_id}: "{dynasty_name}"')
-
-    output_path.write_text("\n".join(lines) + "\n", encoding="utf-8")
+        entries.append((f"dynn_{dynasty_id}", dynasty_name))
 
-    logger.debug("Dynasty names exported to %s.", output_path)
+    for output_path in _write_localization(config, file_prefix, entries):
+        logger.debug("Dynasty names exported to %s.", output_path)
 
 
 def generate_dynasty_motto_localization(
     config_file: Path | str = CONFIG_DIR / "initialization.json",
-    output_file: str = "lotr_mottos_l_english.yml",
+    file_prefix: str = "lotr_mottos",
 ) -> None:
     """Write CK3 dynasty motto localisation entries."""
     config_file = Path(config_file)
@@ -122,13 +153,8 @@ def generate_dynasty_motto_localization(
     if config is None:
         return
 
-    _ensure_output_dir()
-    output_path = CHARACTER_OUTPUT_DIR / output_file
-
-    dynasties = config.get("dynasties", [])
-    lines: list[str] = []
-
-    for dynasty in dynasties:
+    entries: list[tuple[str, str]] = []
+    for dynasty in config.get("dynasties", []):
         dynasty_id = dynasty.get("dynastyID", "").replace("dynasty_", "")
         motto = dynasty.get("dynastyMotto", "")
 
@@ -136,8 +162,7 @@ def generate_dynasty_motto_localization(
             logger.warning("Skipping dynasty with missing dynastyMotto.")
             continue
 
-        lines.append(f'dynn_{dynasty_id}_motto: "{motto}"')
-
-    output_path.write_text("\n".join(lines) + "\n", encoding="utf-8")
+        entries.append((f"dynn_{dynasty_id}_motto", motto))
 
-    logger.debug("Dynasty mottos exported to %s.", output_path)
\ No newline at end of file
+    for output_path in _write_localization(config, file_prefix, entries):
+        logger.debug("Dynasty mottos exported to %s.", output_path)
@@ -0,0 +1,107 @@
+"""
+ck3gen/embedded.py
+~~~~~~~~~~~~~~~~~~
+Entry points for the engine embedded in the Tauri shell.
+
+A shell built with its ``embedded-engine`` feature runs the engine in its own
+process, through PyO3, instead of spawning the packaged server. It calls
+these functions directly: :func:`start` once, then :func:`run` for every
+generation and :func:`pause`, :func:`resume` and :func:`cancel` from other
+threads while one runs. What the server's endpoints would answer is returned
+as JSON, and what it would write to its stdout is written to the stream the
+shell hands to :func:`start`, as the notifications of :mod:`ck3gen.protocol`.
+That stream is also sent a ``state`` notification, with what the server's
+``/simulation/progress`` endpoint gives, on every change the server would
+push to its ``/events`` stream.
+
+The launch options are read from the environment, which the shell sets
+before this module is first imported.
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+import threading
+from typing import TextIO
+
+from ck3gen import protocol
+from ck3gen.control import SimulationCancelled, control
+
+# Set while a run is going, as the server's own flag is.
+_running = threading.Event()
+
+
+def start(stream: TextIO) -> None:
+    """Send every notification to ``stream``, opening with ``hello``."""
+    protocol.hello(stream)
+    logging.basicConfig(level=logging.INFO, handlers=[protocol.LogHandler(stream)], force=True)
+    control.report_to(stream)
+    control.on_change(lambda: protocol.notify(stream, "state", progress()))
+
+
+def progress() -> dict[str, object]:
+    """Where the running simulation is and how the last one ended."""
+    running = _running.is_set()
+    return {
+        "running": running,
+        "paused": running and control.paused,
+        "percent": control.percent if running else None,
+        "year": control.year if running else None,
+        "progress": control.progress if running else None,
+        "lastRun": control.last_run,
+    }
+
+
+def run(dynasties: list[str] | None = None) -> str:
+    """Run a generation to its end and return its last message as JSON.
+
+    The message is the one the server's ``/simulation/run`` stream ends with:
+    the status of a complete or cancelled run, or the error of a failed one.
+    """
+    # Imported here so that it is the shell, not this module, that decides
+    # when the engine's paths and seed are read.
+    from main import run_main
+
+    if _running.is_set():
+        return json.dumps({"error": "A simulation is already running"})
+    control.reset()
+    _running.set()
+    outcome: dict[str, object] = {"status": "failed"}
+    try:
+        characters = run_main(dynasties)
+        outcome = {"status": "complete", "characters": characters}
+        message: dict[str, object] = {"status": "complete"}
+    except SimulationCancelled as exc:
+        outcome = {"status": "cancelled", "removed_files": len(exc.removed)}
+        message = {"status": "cancelled", "removedFiles": len(exc.removed)}
+    except Exception as exc:  # noqa: BLE001
+        logging.exception("Simulation failed")
+        outcome = {"status": "failed", "error": str(exc)}
+        message = {"error": str(exc)}
+    finally:
+        # Cleared first, so the state pushed with the outcome is idle.
+        _running.clear()
+        control.finish(**outcome)
+    return json.dumps(message)
+
+
+def pause() -> None:
+    _require_running()
+    control.pause()
+
+
+def resume() -> None:
+    _require_running()
+    control.resume()
+
+
+def cancel() -> None:
+    """Stop the running simulation after the year or output file in progress."""
+    _require_running()
+    control.cancel()
+
+
+def _require_running() -> None:
+    if not _running.is_set():
+        raise RuntimeError("No simulation is running")
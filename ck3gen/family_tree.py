@@ -2,6 +2,7 @@ import os
 import re
 import graphviz
 from collections import defaultdict
+from ck3gen.calendar import DATE_PATTERN, Calendar
 from ck3gen.config_loader import ConfigLoader
 from ck3gen.paths import TREE_OUTPUT_DIR
 
@@ -25,27 +26,34 @@ class FamilyTree:
         self.characters = {}
         self.dynasties = defaultdict(list)  # Stores characters by dynasty
         self.title_holders = {}  # Store characters who inherited the title
+        self.config = config
+        self.calendar = Calendar.from_config(config['initialization'])
         self.load_characters(character_file)
         self.load_titles(title_file)
         self.graphs = {}  # Stores Graphviz objects for each dynasty
-        self.config = config
         self.graphLook = self.config['initialization'].get('treeGeneration', 'LR')
 
+    def convert_to_ingame_date(self, year):
+        """
+        Convert a year of the files into T.A. or S.A. format. Files dated in
+        a configured calendar already are, and keep their years.
+        """
+        if not re.fullmatch(r"-?\d+", year):
+            return ""  # Default if invalid
+        if not self.calendar.is_default:
+            return year
+        year = int(year)
+        if year > 4033:
+            return f"{year - 4033}"
+        elif 592 < year <= 4033:
+            return f"{year - 592}"
+        return ""
+
     def load_characters(self, filename):
         """Parse the .txt file to extract character details."""
-        with open(filename, "r", encoding="utf-8") as f:  # Ensure UTF-8 encoding
+        with open(filename, "r", encoding="utf-8-sig") as f:  # Ensure UTF-8 encoding
             data = f.read()
 
-        def convert_to_ingame_date(year):
-            """Convert the year into T.A. or S.A. format."""
-            if year.isdigit():  # Ensure it's a valid number
-                year = int(year)
-                if year > 4033:
-                    return f"{year - 4033}"
-                elif 592 < year <= 4033:
-                    return f"{year - 592}"
-            return ""  # Default if invalid
-
         # Regex to find each character block
         character_blocks = re.findall(r"(\w+) = \{\s*((?:[^{}]*|\{(?:[^{}]*|\{[^}]*\})*\})*)\s*\}", data, re.DOTALL)
 
@@ -75,23 +83,23 @@ class FamilyTree:
 
             # Extract birth and death years
             birth_match = re.search(
-                r"(\d{4})\.\d{2}\.\d{2}\s*=\s*\{\s*birth\s*=\s*yes",
+                DATE_PATTERN + r"\s*=\s*\{\s*birth\s*=\s*yes",
                 content
             )
             char_data["birth_year"] = (
-                convert_to_ingame_date(birth_match.group(1))
+                self.convert_to_ingame_date(birth_match.group(1))
                 if birth_match else ""
             )
 
             char_data["death_year"] = ""
             for m in re.finditer(
-                r"(\d{4})\.(\d{2})\.(\d{2})\s*=\s*\{([^}]*)\}",
+                DATE_PATTERN + r"\s*=\s*\{([^}]*)\}",
                 content,
                 re.DOTALL
             ):
                 y, inner = m.group(1), m.group(4)
                 if re.search(r"\bdeath\b", inner):
-                    char_data["death_year"] = convert_to_ingame_date(y)
+                    char_data["death_year"] = self.convert_to_ingame_date(y)
                     break
 
             # Store character data
@@ -104,7 +112,7 @@ class FamilyTree:
     def load_titles(self, filename):
         """Parse title history to find characters who held a title and track their ruling dates."""
         try:
-            with open(filename, "r", encoding="utf-8") as f:
+            with open(filename, "r", encoding="utf-8-sig") as f:
                 data = f.read()
         except FileNotFoundError:
             print(f"Warning: {filename} not found. Skipping title processing.")
@@ -113,7 +121,7 @@ class FamilyTree:
         title_blocks = re.findall(r"(\w+)\s*=\s*\{([^{}]*(?:\{[^{}]*\}[^{}]*)*)\}", data, re.DOTALL)
 
         for title_name, content in title_blocks:
-            matches = re.findall(r"(\d{4}\.\d{2}\.\d{2})\s*=\s*\{[^}]*\bholder\s*=\s*(\w+)", content)
+            matches = re.findall(r"(-?\d+\.\d{2}\.\d{2})\s*=\s*\{[^}]*\bholder\s*=\s*(\w+)", content)
             
             previous_holder = None
             previous_date = None
@@ -158,16 +166,6 @@ class FamilyTree:
         for dynasty, members in self.dynasties.items():
             graph = graphviz.Digraph(comment=f"{dynasty} Family Tree", graph_attr={"rankdir": self.graphLook, "bgcolor": "#A0C878"})
 
-            def convert_to_ingame_date(year):
-                """Convert the year into T.A. or S.A. format."""
-                if year.isdigit():  # Ensure it's a valid number
-                    year = int(year)
-                    if year > 4033:
-                        return f"{year - 4033}"
-                    elif 592 < year <= 4033:
-                        return f"{year - 592}"
-                return ""  # Default if invalid
-
             # Categorize members into males, females, and rulers
             male_count = sum(1 for char_id in members if self.characters[char_id].get("female") != "yes")
             female_count = sum(1 for char_id in members if self.characters[char_id].get("female") == "yes")
@@ -179,8 +177,8 @@ class FamilyTree:
             youngest_birth_year = max(birth_years)
 
             # Convert birth years to in-game format
-            oldest_in_game_year = convert_to_ingame_date(str(oldest_birth_year))
-            youngest_in_game_year = convert_to_ingame_date(str(youngest_birth_year))
+            oldest_in_game_year = self.convert_to_ingame_date(str(oldest_birth_year))
+            youngest_in_game_year = self.convert_to_ingame_date(str(youngest_birth_year))
 
             # Create a label with the counts for males, females, rulers, and the span of the dynasty
             count_label = (f"Total Members: {len(members)}\n"
@@ -206,15 +204,15 @@ class FamilyTree:
                 birth_date = char["birth_year"]
                 death_date = char["death_year"]
                 age_suffix = ""
-                if birth_date.isdigit() and death_date.isdigit():
+                if birth_date and death_date:
                     age = int(death_date) - int(birth_date)
                     age_suffix = f" ({age})"
                 start_date = self.title_holders.get(char_id, {}).get("start_date", "N/A")
                 end_date = self.title_holders.get(char_id, {}).get("end_date", "N/A")
                 
                 # Convert the start and end dates to in-game year format
-                start_year = convert_to_ingame_date(start_date.split('.')[0] if start_date != "N/A" else "N/A")
-                end_year = convert_to_ingame_date(end_date.split('.')[0] if end_date != "N/A" else "N/A")
+                start_year = self.convert_to_ingame_date(start_date.split('.')[0] if start_date != "N/A" else "N/A")
+                end_year = self.convert_to_ingame_date(end_date.split('.')[0] if end_date != "N/A" else "N/A")
 
                 # blood tier (blank if None)
                 tier = char.get("numenor_tier")
@@ -8,6 +8,8 @@ class NameLoader:
         self.name_list_folder = name_list_folder
         # Single shared cache keyed as "culture_gender" strings.
         self.name_cache: dict[str, list[str]] = {}
+        # Weights of the cached names, only for lists that give any.
+        self.weight_cache: dict[str, list[float]] = {}
         if not os.path.isdir(self.name_list_folder):
             logging.warning(f"Name lists folder '{self.name_list_folder}' not found. Using fallback names.")
             os.makedirs(self.name_list_folder, exist_ok=True)
@@ -19,10 +21,12 @@ class NameLoader:
             file_path = os.path.join(self.name_list_folder, f"{key}.txt")
             try:
                 with open(file_path, 'r', encoding='utf-8') as f:
-                    names = [line.strip() for line in f if line.strip()]
-                if not names:
+                    entries = [_parse_entry(line) for line in f if line.strip()]
+                if not entries:
                     raise ValueError("Name list is empty.")
-                self.name_cache[key] = names
+                self.name_cache[key] = [name for name, _ in entries]
+                if any(weight != 1.0 for _, weight in entries):
+                    self.weight_cache[key] = [weight for _, weight in entries]
             except (FileNotFoundError, ValueError):
                 logging.warning(
                     f"Name file missing or empty for {key}. Using fallback namelist."
@@ -31,9 +35,25 @@ class NameLoader:
         return self.name_cache[key]
 
     def load_names(self, culture: str, gender: str) -> str:
-        """Return a random name for the given culture and gender."""
-        return random.choice(self._load(culture, gender))
+        """Return a random name for the given culture and gender, by weight."""
+        names = self._load(culture, gender)
+        weights = self.weight_cache.get(f"{culture}_{gender}")
+        if weights is None:
+            return random.choice(names)
+        return random.choices(names, weights=weights)[0]
 
     def get_all_names(self, culture: str, sex: str) -> list[str]:
         """Return the full name list for the given culture and sex."""
-        return self._load(culture, sex)
\ No newline at end of file
+        return self._load(culture, sex)
+
+
+def _parse_entry(line: str) -> tuple[str, float]:
+    """Split a name list line into its name and weight, e.g. ``Aragorn 5``."""
+    line = line.strip()
+    parts = line.rsplit(None, 1)
+    if len(parts) == 2:
+        try:
+            return parts[0], float(parts[1])
+        except ValueError:
+            pass
+    return line, 1.0
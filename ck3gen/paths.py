@@ -4,10 +4,16 @@ Centralised path constants for the CK3 Character History Generator.
 All output directories are resolved relative to PROJECT_ROOT so that the
 application works correctly regardless of the working directory — whether
 launched via the CLI, the FastAPI dev server, or a packaged Tauri sidecar.
+
+The config directory and the output root can be redirected with the
+``CK3GEN_CONFIG_DIR`` and ``CK3GEN_OUTPUT_DIR`` environment variables. The
+Tauri shell uses them to point parallel engine workers at the shared config
+and at a private output directory each.
 """
 
 from __future__ import annotations
 
+import os
 import sys
 from pathlib import Path
 
@@ -32,14 +38,16 @@ PROJECT_ROOT: Path = _find_project_root()
 
 # ── Input directories ─────────────────────────────────────────────────────────
 
-CONFIG_DIR: Path = PROJECT_ROOT / "config"
+CONFIG_DIR: Path = Path(os.environ.get("CK3GEN_CONFIG_DIR", PROJECT_ROOT / "config"))
 FALLBACK_CONFIG_DIR: Path = CONFIG_DIR / "fallback_config_files"
 NAME_LISTS_DIR: Path = PROJECT_ROOT / "name_lists"
 
 # ── Output directories ────────────────────────────────────────────────────────
 
+OUTPUT_ROOT: Path = Path(os.environ.get("CK3GEN_OUTPUT_DIR", PROJECT_ROOT))
+
 # Character history, title history, and dynasty definition exports
-CHARACTER_OUTPUT_DIR: Path = PROJECT_ROOT / "Character and Title files"
+CHARACTER_OUTPUT_DIR: Path = OUTPUT_ROOT / "Character and Title files"
 
 # Rendered family tree PNG images (served by the FastAPI /images endpoint)
-TREE_OUTPUT_DIR: Path = PROJECT_ROOT / "Dynasty Preview"
\ No newline at end of file
+TREE_OUTPUT_DIR: Path = OUTPUT_ROOT / "Dynasty Preview"
\ No newline at end of file
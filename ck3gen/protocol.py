@@ -0,0 +1,75 @@
+"""
+ck3gen/protocol.py
+~~~~~~~~~~~~~~~~~~
+The messages the packaged engine sends the Tauri shell over its stdout.
+
+Each message is a JSON-RPC 2.0 notification on a line of its own, such as
+``{"jsonrpc": "2.0", "method": "progress", "params": {...}}``, so that the
+shell reads typed messages instead of parsing log wording. The engine opens
+with ``hello``, giving the :data:`PROTOCOL_VERSION` it speaks, then sends a
+``progress`` notification on every change of a run's progress and a ``log``
+notification for every log record. Any other line on stdout is ordinary
+output.
+
+Bump :data:`PROTOCOL_VERSION` whenever a message changes in a way an older
+shell would misread, and ``sidecar_manager::protocol`` along with it.
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+import os
+import threading
+import traceback
+from typing import Any, TextIO
+
+PROTOCOL_VERSION = 1
+
+# Notifications are written from the simulation thread, request handlers and
+# the logging machinery at once; a line must never interleave with another.
+_lock = threading.Lock()
+
+
+def notify(stream: TextIO, method: str, params: dict[str, Any]) -> None:
+    """Write the notification ``method`` with ``params`` to ``stream``."""
+    line = json.dumps({"jsonrpc": "2.0", "method": method, "params": params})
+    try:
+        with _lock:
+            stream.write(line + "\n")
+            stream.flush()
+    except (OSError, ValueError):
+        # A closed stream only loses the message, not the run.
+        pass
+
+
+def hello(stream: TextIO) -> None:
+    """Tell the shell which version of the protocol the engine speaks."""
+    notify(stream, "hello", {"protocol": PROTOCOL_VERSION, "pid": os.getpid()})
+
+
+class LogHandler(logging.Handler):
+    """Sends every log record to the shell as a ``log`` notification."""
+
+    def __init__(self, stream: TextIO) -> None:
+        super().__init__()
+        self._stream = stream
+
+    def emit(self, record: logging.LogRecord) -> None:
+        try:
+            message = record.getMessage()
+        except Exception:  # noqa: BLE001 - a bad format string must not be lost
+            message = str(record.msg)
+        trace = None
+        if record.exc_info:
+            trace = "".join(traceback.format_exception(*record.exc_info)).rstrip()
+        notify(
+            self._stream,
+            "log",
+            {
+                "level": record.levelname,
+                "logger": record.name,
+                "message": message,
+                "traceback": trace,
+            },
+        )
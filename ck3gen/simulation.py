@@ -16,14 +16,17 @@ Orchestrates the full lifecycle of all dynasties:
 from __future__ import annotations
 
 import logging
+import math
 import os
 import random
 import re
 from typing import Optional
 
+from ck3gen.calendar import Calendar
 from ck3gen.character import Character
+from ck3gen.control import control
 from ck3gen.paths import CHARACTER_OUTPUT_DIR
-from utils.utils import generate_char_id, generate_random_date
+from utils.utils import generate_char_id, generate_random_date, parse_game_version
 
 logger = logging.getLogger(__name__)
 
@@ -97,7 +100,7 @@ class Simulation:
             birth_year: int = dynasty["progenitorMaleBirthYear"]
 
             dynasty_prefix = dynasty_id.split("_")[1] if "_" in dynasty_id else dynasty_id
-            char_id = generate_char_id(dynasty_prefix, self.dynasty_char_counters)
+            char_id = generate_char_id(dynasty_prefix, self.dynasty_char_counters, self.current_char_id)
             prog_name: str = self.name_loader.load_names(culture, "male")
 
             progenitor = Character(
@@ -260,7 +263,7 @@ class Simulation:
             if character.dynasty and "_" in character.dynasty
             else "lowborn"
         )
-        spouse_char_id = generate_char_id(dynasty_prefix, self.dynasty_char_counters)
+        spouse_char_id = generate_char_id(dynasty_prefix, self.dynasty_char_counters, self.current_char_id)
         spouse_sex = "Male" if character.sex == "Female" else "Female"
         spouse_name: str = self.name_loader.load_names(
             character.culture, spouse_sex.lower()
@@ -527,24 +530,50 @@ class Simulation:
 
     def character_death_check(self, character: Character) -> bool:
         """
-        Return True if the character dies this year based on mortality tables
-        and any active negative events.
+        Return True if the character dies this year based on mortality tables,
+        the mortality eras and trait multipliers, and any active negative
+        events. Death is certain at the lifespan cap of the character's blood
+        tier whatever the modifiers.
         """
+        mortality = self.config["life_stages"].get("mortality") or {}
         tier = character.numenorean_blood_tier or 0
-        effective_age = max(0, min(character.age - 20 * tier, 120))
+        if character.age >= self.max_lifespan(mortality, tier):
+            return True
 
+        effective_age = max(0, character.age - 20 * tier)
         if character.is_progenitor and effective_age < 50:
             return False
 
         sex = character.sex
+        year = character.birth_year + character.age
+        eras = [
+            era for era in mortality.get("eras", [])
+            if era["startYear"] <= year <= era["endYear"]
+        ]
+        curve = next((era["curve"][sex] for era in eras if era.get("curve")), None)
         mortality_rates: list[float] = self.config["life_stages"]["mortalityRates"][sex]
 
-        if effective_age < 1:
-            mortality_rate = 0.0
+        if curve is not None:
+            mortality_rate = self.curve_rate(curve, effective_age)
         elif effective_age < len(mortality_rates):
             mortality_rate = mortality_rates[effective_age]
         else:
-            mortality_rate = 1.0
+            return True
+        if effective_age < 1:
+            mortality_rate = 0.0
+
+        for era in eras:
+            mortality_rate *= era.get("multiplier", 1.0)
+        trait_multipliers = mortality.get("traitMultipliers", {})
+        if trait_multipliers:
+            traits = [*character.traits, *character.personality_traits,
+                      *character.congenital_traits.values()]
+            if character.education_tier is not None and character.education_skill is not None:
+                skill = "martial" if character.education_skill == "prowess" else character.education_skill
+                traits.append(f"education_{skill}_{character.education_tier}")
+            for trait in traits:
+                mortality_rate *= trait_multipliers.get(trait, 1.0)
+        mortality_rate = min(1.0, mortality_rate)
 
         for event in self.config.get("initialization", {}).get("events", []):
             if (
@@ -562,6 +591,24 @@ class Simulation:
 
         return roll < mortality_rate
 
+    @staticmethod
+    def max_lifespan(mortality: dict, tier: int) -> int:
+        """Age by which every character of blood tier *tier* has died."""
+        base = mortality.get("maxLifespan", 120)
+        if tier == 0:
+            return base
+        return mortality.get("bloodTierMaxLifespan", {}).get(str(tier), base + 20 * tier)
+
+    @staticmethod
+    def curve_rate(curve: dict, age: int) -> float:
+        """Chance of dying within a year at *age* under a survival curve."""
+        hazard = (
+            curve["infant"] * math.exp(-curve["infantDecay"] * age)
+            + curve["background"]
+            + curve["aging"] * math.exp(curve["agingRate"] * age)
+        )
+        return 1.0 - math.exp(-hazard)
+
     # ------------------------------------------------------------------
     #  Child creation
     # ------------------------------------------------------------------
@@ -687,7 +734,7 @@ class Simulation:
             if child_dynasty and "_" in child_dynasty
             else "lowborn"
         )
-        child_char_id = generate_char_id(dynasty_prefix, self.dynasty_char_counters)
+        child_char_id = generate_char_id(dynasty_prefix, self.dynasty_char_counters, self.current_char_id)
         child_name = self.assign_child_name(child_sex, mother, father, child_dynasty)
 
         birth_order = len(mother.children) + 1
@@ -796,7 +843,7 @@ class Simulation:
             if parent.dynasty and "_" in parent.dynasty
             else "lowborn"
         )
-        child_char_id = generate_char_id(dynasty_prefix, self.dynasty_char_counters)
+        child_char_id = generate_char_id(dynasty_prefix, self.dynasty_char_counters, self.current_char_id)
         child_name = self.assign_child_name(child_sex, parent, parent, parent.dynasty)
 
         adjusted_birth_year = max(birth_year, parent.birth_year + 16)
@@ -1308,7 +1355,7 @@ class Simulation:
         )
 
         dynasty_prefix = dynasty_id.split("_")[1] if "_" in dynasty_id else dynasty_id
-        child_char_id = generate_char_id(dynasty_prefix, self.dynasty_char_counters)
+        child_char_id = generate_char_id(dynasty_prefix, self.dynasty_char_counters, self.current_char_id)
 
         dynasties_cfg: list[dict] = self.config["initialization"]["dynasties"]
         dynasty_cfg = next(
@@ -1422,7 +1469,9 @@ class Simulation:
 
         self._backfill_pre_simulation_events(min_year)
 
+        control.begin(min_year, max_year)
         for year in range(min_year, max_year + 1):
+            control.checkpoint(year)
             self._process_yearly_updates(year)
             self._process_marriages(year)
             self._process_births(year)
@@ -1437,12 +1486,16 @@ class Simulation:
     #  Export
     # ------------------------------------------------------------------
 
-    def export_characters(self, output_filename: str = "family_history.txt") -> None:
+    def export_characters(
+        self, output_filename: str = "family_history.txt", seed: int | None = None
+    ) -> None:
         """
         Write all characters to the character history output file, grouped by dynasty.
 
-        Also wires dynasty language rules into the Character class before
-        calling format_for_export() on each character.
+        The file opens with a ``# Seed:`` comment when the run's ``seed`` is given.
+
+        Also wires dynasty language rules and the output calendar into the
+        Character class before calling format_for_export() on each character.
         """
         language_rules: dict[str, list[tuple[str, int, int]]] = {}
         for dynasty in self.config.get("initialization", {}).get("dynasties", []):
@@ -1457,6 +1510,10 @@ class Simulation:
                         pass
             language_rules[dynasty_id] = rules
         Character.DYNASTY_LANGUAGE_RULES = language_rules
+        Character.CALENDAR = Calendar.from_config(self.config.get("initialization", {}))
+        Character.GAME_VERSION = parse_game_version(
+            self.config.get("initialization", {}).get("gameVersion")
+        )
 
         CHARACTER_OUTPUT_DIR.mkdir(parents=True, exist_ok=True)
         output_path = CHARACTER_OUTPUT_DIR / output_filename
@@ -1473,7 +1530,9 @@ class Simulation:
             dynasty_groups.setdefault(dynasty, []).append(character)
 
         exported_count = 0
-        with open(output_path, "w", encoding="utf-8") as fh:
+        with open(output_path, "w", encoding="utf-8-sig") as fh:
+            if seed is not None:
+                fh.write(f"# Seed: {seed}\n\n")
             for dynasty, characters in sorted(dynasty_groups.items()):
                 fh.write("################\n")
                 fh.write(f"### Dynasty {dynasty}\n")
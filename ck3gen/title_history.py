@@ -1,13 +1,33 @@
 import json
 import os
+import random
 import re
 import logging
 from collections import defaultdict
 from datetime import datetime
 from enum import Enum
 
+from ck3gen.calendar import DATE_PATTERN, Calendar
+
 logger = logging.getLogger(__name__)
 
+# Title a dynasty holds when its config lists none of its own
+PLACEHOLDER_TITLE = "placeholder_title"
+# Date standing in for the death of characters still alive
+LIVING = (9999, 12, 31)
+# Age from which dynasty members may vote and stand in elections
+ELECTOR_AGE = 16
+# Age from which an heir inheriting under ELECTOR_AGE is given a guardian
+GUARDIAN_FROM_AGE = 6
+# Chance of a ward taking up the focus of their guardian's education
+GUARDIAN_FOCUS_CHANCE = 0.5
+# Chance of a ward gaining an education tier from a better educated guardian
+GUARDIAN_TIER_CHANCE = 0.5
+# How a guardian's personality changes the chance of their ward gaining a tier
+GUARDIAN_TRAIT_TIER_CHANCE = {"diligent": 0.25, "lazy": -0.25}
+# Highest education tier
+MAX_EDUCATION_TIER = 5
+
 ############################
 # Enums for Succession/Gender
 ############################
@@ -16,6 +36,8 @@ class SuccessionType(Enum):
     PRIMOGENITURE = "primogeniture"
     ULTIMOGENITURE = "ultimogeniture"
     SENIORITY = "seniority"
+    PARTITION = "partition"
+    ELECTIVE = "elective"
 
 class GenderLaw(Enum):
     AGNATIC = "agnatic"
@@ -31,7 +53,8 @@ class GenderLaw(Enum):
 class Character:
     def __init__(self, identifier, name, father, mother, dynasty, female, is_bastard,
                  birth_year, birth_month=None, birth_day=None,
-                 death_year=None, death_month=None, death_day=None):
+                 death_year=None, death_month=None, death_day=None,
+                 education=None, traits=()):
         self.id = identifier
         self.name = name
         self.father = father
@@ -45,6 +68,8 @@ class Character:
         self.death_year = death_year
         self.death_month = death_month
         self.death_day = death_day
+        self.education = education  # (skill, tier) of the education trait, if any
+        self.traits = list(traits)
         self.is_progenitor = self.check_if_progenitor(identifier)
 
     def check_if_progenitor(self, identifier):
@@ -56,13 +81,15 @@ class Character:
         return f"<Character {self.name} ({self.id})>"
 
 class CharacterLoader:
-    def __init__(self):
+    def __init__(self, calendar=None):
         self.characters = {}
         self.dynasties = defaultdict(list)
+        # The calendar the file is dated in; characters are kept in simulation dates.
+        self.calendar = calendar or Calendar()
 
     def load_characters(self, filename):
         """Parse the .txt file to extract character details and store them in memory."""
-        with open(filename, "r", encoding="utf-8") as f:
+        with open(filename, "r", encoding="utf-8-sig") as f:
             data = f.read()
 
         # This regex might need adjusting if your data has quotes or extra spaces
@@ -79,26 +106,33 @@ class CharacterLoader:
             dynasty = self.extract_value(r"dynasty\s*=\s*(\w+)", content, default="Lowborn")
             female = bool(re.search(r"female\s*=\s*yes", content))
             is_bastard = bool(re.search(r"trait\s*=\s*bastard", content))
+            traits = re.findall(r"\btrait\s*=\s*(\w+)", content)
+            education_match = re.search(r"\btrait\s*=\s*education_([a-z]+)_(\d+)\b", content)
+            education = None
+            if education_match:
+                education = (education_match.group(1), int(education_match.group(2)))
 
-            birth_match = re.search(r"(\d{4})\.(\d{2})\.(\d{2})\s*=\s*\{\s*birth\s*=\s*yes", content)
+            birth_match = re.search(DATE_PATTERN + r"\s*=\s*\{\s*birth\s*=\s*yes", content)
             death_year = death_month = death_day = None
-            for m in re.finditer(r"(\d{4})\.(\d{2})\.(\d{2})\s*=\s*\{([^}]*)\}", content, re.DOTALL):
-                y, mo, d, inner = m.group(1), m.group(2), m.group(3), m.group(4)
-                if re.search(r"\bdeath\b", inner):
-                    death_year  = int(y)
-                    death_month = int(mo)
-                    death_day   = int(d)
+            for m in re.finditer(DATE_PATTERN + r"\s*=\s*\{([^}]*)\}", content, re.DOTALL):
+                if re.search(r"\bdeath\b", m.group(4)):
+                    death_year, death_month, death_day = self.calendar.from_calendar(
+                        *map(int, m.group(1, 2, 3))
+                    )
                     break
 
-            birth_year = int(birth_match.group(1)) if birth_match else None
-            birth_month = int(birth_match.group(2)) if birth_match else None
-            birth_day = int(birth_match.group(3)) if birth_match else None
+            birth_year = birth_month = birth_day = None
+            if birth_match:
+                birth_year, birth_month, birth_day = self.calendar.from_calendar(
+                    *map(int, birth_match.group(1, 2, 3))
+                )
 
             # Create a Character object
             character = Character(
                 identifier, name, father, mother, dynasty, female, is_bastard,
                 birth_year, birth_month, birth_day,
-                death_year, death_month, death_day
+                death_year, death_month, death_day,
+                education, traits
             )
             self.characters[identifier] = character
             self.dynasties[dynasty].append(character)
@@ -142,11 +176,16 @@ class CharacterLoader:
 
 class TitleHistory:
     def __init__(self, character_loader, config_file):
-        self.titles = {}   # dynasty_name -> list of (ruler_id, startY, startM, startD, endY, endM, endD)
+        self.titles = []   # (title_id, [(holder_id, (Y, M, D)), ...]) in dynasty order
         self.characters = character_loader.characters
-        self.dynasties = character_loader.dynasties 
+        self.dynasties = character_loader.dynasties
+        self.calendar = character_loader.calendar
         self.config = self.load_json_file(config_file)
         self.parent_to_children = defaultdict(list)
+        self._ancestors = {}
+        self.landed = {}   # title_id -> {"dynasty", "liege", "development"} for configured titles
+        self.vacated = {}  # title_id -> (Y, M, D) its line ran out on
+        self.guardians = {}  # ward_id -> (guardian_id, (Y, M, D), (skill, tier) or None)
         # After all characters are loaded, build an index:
         for char_id, char in self.characters.items():
             if char.father in self.characters:
@@ -160,13 +199,19 @@ class TitleHistory:
         {
           "dynasties": [
             {
-              "name": "HouseDurin",
+              "dynastyID": "dynasty_durin",
               "succession": "PRIMOGENITURE",
-              "gender_law": "AGNATIC"
+              "gender_law": "AGNATIC",
+              "titles": [
+                {"id": "k_erebor", "development": 20},
+                {"id": "d_iron_hills", "succession": "PARTITION", "liege": "k_erebor"}
+              ]
             },
             ...
           ]
         }
+        Titles without their own succession or gender_law use the dynasty's.
+        "liege" and "development" only go into the landed title files.
         """
         try:
             with open(filename, "r", encoding="utf-8") as file:
@@ -181,53 +226,73 @@ class TitleHistory:
     ###########################################
     def build_title_histories(self):
         """
-        For each dynasty, find the progenitor and build a timeline of rulers
-        until we reach someone who is still alive or the line runs out.
+        For each dynasty, hand its titles to the progenitor and pass each on
+        at its holder's death under the title's law, until every title is
+        held by someone still alive or its line has run out.
         """
         dynasty_settings = self.parse_config()
-        
-        for dynasty_name, members in self.dynasties.items():
-            # 1. Determine laws
-            succession_type, gender_law = dynasty_settings.get(
-                dynasty_name,
-                (SuccessionType.PRIMOGENITURE, GenderLaw.AGNATIC_COGNATIC)
-            )
+        default_titles = [
+            (PLACEHOLDER_TITLE, SuccessionType.PRIMOGENITURE, GenderLaw.AGNATIC_COGNATIC)
+        ]
 
-            # 2. Find progenitor
+        for dynasty_name in self.dynasties:
+            titles = dynasty_settings.get(dynasty_name, default_titles)
             progenitor = self.find_progenitor(dynasty_name)
             if not progenitor:
                 continue
+            self.titles.extend(self.build_realm(progenitor, titles))
+        self.assign_guardians()
 
-            title_line = []
-            current_ruler = progenitor
-            rule_start = self.get_birth_date(current_ruler)
-
-            while current_ruler:
-                # Get date of death
-                rule_end = self.get_death_date(current_ruler)  # e.g. (9999,12,31) if no death date
-
-                # If rule_end indicates "still alive" (your sentinel for no date):
-                # We'll say if it exactly equals (9999,12,31), that means they're living or immortal
-                if rule_end == (9999, 12, 31):
-                    # They are still alive => add them to timeline once, then stop
-                    title_line.append((current_ruler.id, *rule_start, *rule_end))
-                    break
-
-                # Otherwise, record them and find their successor
-                title_line.append((current_ruler.id, *rule_start, *rule_end))
-                
-                # Move on to the next heir
-                next_ruler = self.determine_heir(current_ruler, succession_type, gender_law)
-                current_ruler = next_ruler
-                rule_start = rule_end if next_ruler else None
-
-            self.titles[dynasty_name] = title_line
+    def build_realm(self, progenitor, titles):
+        """
+        Follow one dynasty's titles from its progenitor on. Holders die in
+        date order, so a ruler holding several titles passes them all on at
+        once, and partitioned titles are split among the ruler's children.
+        """
+        laws = {title: (succession, gender_law) for title, succession, gender_law in titles}
+        start = self.get_birth_date(progenitor)
+        reigns = {title: [(progenitor.id, start)] for title in laws}
+        holders = {title: progenitor for title in laws}
+
+        while holders:
+            ruler = min(holders.values(), key=self.get_death_date)
+            death = self.get_death_date(ruler)
+            if death == LIVING:
+                break
+            held = [title for title, holder in holders.items() if holder is ruler]
+            for title, heir in self.divide_titles(ruler, held, laws).items():
+                if heir is None:
+                    # The line has run out; the title history stops with its last holder
+                    self.vacated[title] = death
+                    del holders[title]
+                else:
+                    reigns[title].append((heir.id, death))
+                    holders[title] = heir
+
+        return [(title, reigns[title]) for title in laws]
+
+    def divide_titles(self, ruler, held, laws):
+        """
+        Map each title in 'held' to the heir who takes it at 'ruler's death,
+        or None if nobody can.
+        """
+        successors = {}
+        partitioned = defaultdict(list)
+        for title in held:
+            succession_type, gender_law = laws[title]
+            if succession_type == SuccessionType.PARTITION:
+                partitioned[gender_law].append(title)
+            else:
+                successors[title] = self.determine_heir(ruler, succession_type, gender_law)
+        for gender_law, titles in partitioned.items():
+            successors.update(self.partition_titles(ruler, titles, gender_law))
+        return successors
 
     def parse_config(self):
         """
         Create a dictionary:
           {
-            dynasty_name: (SuccessionType.X, GenderLaw.Y),
+            dynasty_name: [(title_id, SuccessionType.X, GenderLaw.Y), ...],
             ...
           }
         from the JSON config data
@@ -237,23 +302,41 @@ class TitleHistory:
             name = entry.get("dynastyID")
             if not name:
                 continue
-            # Parse succession
-            succ = entry.get("succession", "PRIMOGENITURE").upper()
-            try:
-                succession_type = SuccessionType[succ]
-            except KeyError:
-                succession_type = SuccessionType.PRIMOGENITURE
-
-            # Parse gender law
-            gl = entry.get("gender_law", "AGNATIC_COGNATIC").upper()
-            try:
-                gender_law = GenderLaw[gl]
-            except KeyError:
-                gender_law = GenderLaw.AGNATIC_COGNATIC
-
-            results[name] = (succession_type, gender_law)
+            succession_type = self.parse_succession(entry.get("succession"), SuccessionType.PRIMOGENITURE)
+            gender_law = self.parse_gender_law(entry.get("gender_law"), GenderLaw.AGNATIC_COGNATIC)
+
+            titles = []
+            for title in entry.get("titles") or [{"id": PLACEHOLDER_TITLE}]:
+                if not title.get("id"):
+                    continue
+                titles.append((
+                    title["id"],
+                    self.parse_succession(title.get("succession"), succession_type),
+                    self.parse_gender_law(title.get("gender_law"), gender_law),
+                ))
+                if title["id"] != PLACEHOLDER_TITLE:
+                    self.landed[title["id"]] = {
+                        "dynasty": name,
+                        "liege": title.get("liege"),
+                        "development": title.get("development"),
+                    }
+            results[name] = titles
         return results
 
+    @staticmethod
+    def parse_succession(value, default):
+        try:
+            return SuccessionType[value.upper()]
+        except (AttributeError, KeyError):
+            return default
+
+    @staticmethod
+    def parse_gender_law(value, default):
+        try:
+            return GenderLaw[value.upper()]
+        except (AttributeError, KeyError):
+            return default
+
     def find_progenitor(self, dynasty):
         """Return the character in self.dynasties[dynasty] whose is_progenitor == True"""
         for person in self.dynasties[dynasty]:
@@ -276,8 +359,10 @@ class TitleHistory:
 
         if succession_type == SuccessionType.SENIORITY:
             return self.find_heir_seniority(ruler, gender_law, parent_death_date)
+        if succession_type == SuccessionType.ELECTIVE:
+            return self.find_heir_elective(ruler, gender_law, parent_death_date)
 
-        # For Primogeniture / Ultimogeniture, do a 2-stage search:
+        # For Primogeniture / Ultimogeniture (and Partition's primary heir), do a 2-stage search:
         # 1) legitimate only
         visited = set()
         heir = self.find_heir_primoultimo(ruler, succession_type, gender_law, parent_death_date, visited, allow_bastards=False)
@@ -314,10 +399,12 @@ class TitleHistory:
                 continue
             if not self.was_alive_or_posthumous(c, parent_death_date):
                 continue
+            if not self.is_alive_at(c, parent_death_date):
+                continue
             valid.append(c)
 
-        # Sort by birth date ascending => oldest first
-        valid.sort(key=lambda x: self.get_birth_date(x))
+        # Sort by birth date ascending => oldest first, among the preferred sex if any
+        valid = self.preferred_sex_first(valid, gender_law, self.get_birth_date)
         # Return the first truly "alive" at parent's death date 
         # or at least not disqualified by it.
         for candidate in valid:
@@ -336,6 +423,38 @@ class TitleHistory:
             return None
         visited.add(ruler.id)
 
+        ordered_kids = self.ordered_children(ruler, succession_type, gender_law,
+                                             parent_death_date, allow_bastards)
+
+        # 4. Check each child in that new order
+        for child in ordered_kids:
+            if self.is_alive_at(child, parent_death_date):
+                return child
+            else:
+                # posthumous pass
+                next_heir = self.find_heir_primoultimo(child, succession_type, gender_law,
+                                                    parent_death_date, visited, allow_bastards)
+                if next_heir:
+                    return next_heir
+
+        # 5. If no child, check the relevant parent line
+        parent = self.get_relevant_parent(ruler, gender_law)
+        if parent:
+            if self.is_alive_at(parent, parent_death_date):
+                return parent
+            else:
+                return self.find_heir_primoultimo(parent, succession_type, gender_law,
+                                                parent_death_date, visited, allow_bastards)
+
+        # 6. None found
+        return None
+
+    def ordered_children(self, ruler, succession_type, gender_law, parent_death_date, allow_bastards):
+        """
+        The children of 'ruler' who may inherit, in the order of the law:
+        eldest first (youngest first under Ultimogeniture), the preferred sex
+        of a cognatic law ahead of the other.
+        """
         # 1. Gather children (already unsorted).
         children = self.get_children_in_birth_order(ruler.id)
 
@@ -354,51 +473,158 @@ class TitleHistory:
         # 3. Sort them by your law:
         # - For AGNATIC/ENATIC, is_valid_by_gender_law already excludes the opposite gender.
         # - For AGNATIC_COGNATIC or ENATIC_COGNATIC, we do a male-first or female-first partition.
-
         reverse_sort = (succession_type == SuccessionType.ULTIMOGENITURE)
+        return self.preferred_sex_first(valid_kids, gender_law, self.get_birth_date, reverse_sort)
 
-        if gender_law == GenderLaw.AGNATIC_COGNATIC:
-            # partition
-            male_kids = [c for c in valid_kids if not c.female]
-            female_kids = [c for c in valid_kids if c.female]
-            male_kids.sort(key=lambda c: self.get_birth_date(c), reverse=reverse_sort)
-            female_kids.sort(key=lambda c: self.get_birth_date(c), reverse=reverse_sort)
-            ordered_kids = male_kids + female_kids
+    def partition_titles(self, ruler, titles, gender_law):
+        """
+        Partition: share 'titles' out among the ruler's living children of the
+        preferred sex, eldest first, the primary title going to the eldest.
+        Without such children, everything goes to the primogeniture heir.
+        """
+        death = self.get_death_date(ruler)
+        heirs = []
+        for allow_bastards in (False, True):
+            children = self.ordered_children(ruler, SuccessionType.PRIMOGENITURE, gender_law,
+                                             death, allow_bastards)
+            heirs = [c for c in children if self.is_alive_at(c, death)]
+            if heirs:
+                break
+        if not heirs:
+            heir = self.determine_heir(ruler, SuccessionType.PARTITION, gender_law)
+            return {title: heir for title in titles}
+
+        preferred = self.preferred_sex(gender_law)
+        if preferred is not None and any(c.female == preferred for c in heirs):
+            heirs = [c for c in heirs if c.female == preferred]
+        return {title: heirs[i % len(heirs)] for i, title in enumerate(titles)}
+
+    def find_heir_elective(self, ruler, gender_law, parent_death_date):
+        """
+        Elective: the adult members of the dynasty alive at the ruler's death
+        vote among those of them the gender law allows, each for the candidate
+        closest to them in the family tree, other than themselves if there is
+        anyone else. Most votes wins; ties go to the elder. Bastards only
+        stand if no one else can.
+        """
+        members = self.dynasties.get(ruler.dynasty, [])
+        electors = [
+            c for c in members
+            if self.is_of_age_at(c, parent_death_date) and self.is_alive_at(c, parent_death_date)
+        ]
+        candidates = []
+        for allow_bastards in (False, True):
+            candidates = [
+                c for c in electors
+                if (allow_bastards or not c.is_bastard) and self.is_valid_by_gender_law(c, gender_law)
+            ]
+            if candidates:
+                break
+        if not candidates:
+            return None
 
-        elif gender_law == GenderLaw.ENATIC_COGNATIC:
-            # partition reversed
-            female_kids = [c for c in valid_kids if c.female]
-            male_kids = [c for c in valid_kids if not c.female]
-            female_kids.sort(key=lambda c: self.get_birth_date(c), reverse=reverse_sort)
-            male_kids.sort(key=lambda c: self.get_birth_date(c), reverse=reverse_sort)
-            ordered_kids = female_kids + male_kids
+        preferred = self.preferred_sex(gender_law)
+        if preferred is not None and any(c.female == preferred for c in candidates):
+            candidates = [c for c in candidates if c.female == preferred]
 
-        else:
-            # all other laws => single sorted list
-            ordered_kids = sorted(valid_kids, key=lambda c: self.get_birth_date(c), reverse=reverse_sort)
+        votes = defaultdict(int)
+        for elector in electors:
+            choice = min(
+                candidates,
+                key=lambda c: (c is elector, self.kin_distance(elector, c), self.get_birth_date(c)),
+            )
+            votes[choice.id] += 1
+        return min(candidates, key=lambda c: (-votes[c.id], self.get_birth_date(c)))
 
-        # 4. Check each child in that new order
-        for child in ordered_kids:
-            if self.is_alive_at(child, parent_death_date):
-                return child
-            else:
-                # posthumous pass
-                next_heir = self.find_heir_primoultimo(child, succession_type, gender_law,
-                                                    parent_death_date, visited, allow_bastards)
-                if next_heir:
-                    return next_heir
+    def kin_distance(self, a, b):
+        """
+        Generations between 'a' and 'b' through their closest common
+        ancestor, or infinity if they have none on record.
+        """
+        ancestors_a = self.ancestors(a)
+        ancestors_b = self.ancestors(b)
+        common = ancestors_a.keys() & ancestors_b.keys()
+        if not common:
+            return float("inf")
+        return min(ancestors_a[c] + ancestors_b[c] for c in common)
+
+    def ancestors(self, character):
+        """Every known ancestor of 'character', themselves included, by generations up."""
+        if character.id in self._ancestors:
+            return self._ancestors[character.id]
+        found = {character.id: 0}
+        frontier = [character]
+        while frontier:
+            next_frontier = []
+            for person in frontier:
+                for parent_id in (person.father, person.mother):
+                    if parent_id in self.characters and parent_id not in found:
+                        found[parent_id] = found[person.id] + 1
+                        next_frontier.append(self.characters[parent_id])
+            frontier = next_frontier
+        self._ancestors[character.id] = found
+        return found
 
-        # 5. If no child, check the relevant parent line
-        parent = self.get_relevant_parent(ruler, gender_law)
-        if parent:
-            if self.is_alive_at(parent, parent_death_date):
-                return parent
-            else:
-                return self.find_heir_primoultimo(parent, succession_type, gender_law,
-                                                parent_death_date, visited, allow_bastards)
+    ###########################################
+    # Guardians of Child Heirs
+    ###########################################
 
-        # 6. None found
-        return None
+    def assign_guardians(self):
+        """
+        Give every heir who first inherited under ELECTOR_AGE a guardian from
+        GUARDIAN_FROM_AGE, or from the inheritance if later: the living adult
+        relative closest to them in the family tree, the elder on a tie. The
+        guardian's education and personality may change the ward's education.
+        """
+        inherited = {}
+        for _, reigns in self.titles:
+            for holder_id, date in reigns[1:]:
+                if holder_id in self.characters:
+                    inherited[holder_id] = min(date, inherited.get(holder_id, LIVING))
+
+        for ward_id, date in sorted(inherited.items(), key=lambda item: (item[1], item[0])):
+            ward = self.characters[ward_id]
+            if self.is_of_age_at(ward, date):
+                continue
+            by, bm, bd = self.get_birth_date(ward)
+            date = max(date, (by + GUARDIAN_FROM_AGE, bm, bd))
+            if not self.is_alive_at(ward, date):
+                continue
+            guardian = self.find_guardian(ward, date)
+            if guardian:
+                self.guardians[ward_id] = (guardian.id, date, self.guardian_education(ward, guardian))
+
+    def find_guardian(self, ward, date):
+        """The living adult relative closest to 'ward' on 'date', or None."""
+        relatives = []
+        for c in self.characters.values():
+            if c is ward or not self.is_of_age_at(c, date) or not self.is_alive_at(c, date):
+                continue
+            distance = self.kin_distance(ward, c)
+            if distance != float("inf"):
+                relatives.append((distance, self.get_birth_date(c), c.id, c))
+        return min(relatives)[3] if relatives else None
+
+    @staticmethod
+    def guardian_education(ward, guardian):
+        """
+        The education 'ward' ends up with under 'guardian': they may take up
+        the guardian's focus, and gain a tier from a guardian with a higher
+        one, the likelier if the guardian is diligent and the less if lazy.
+        """
+        if ward.education is None or guardian.education is None:
+            return ward.education
+        skill, tier = ward.education
+        guardian_skill, guardian_tier = guardian.education
+        if guardian_skill != skill and random.random() < GUARDIAN_FOCUS_CHANCE:
+            skill = guardian_skill
+        if guardian_tier > tier:
+            chance = GUARDIAN_TIER_CHANCE + sum(
+                GUARDIAN_TRAIT_TIER_CHANCE.get(trait, 0.0) for trait in guardian.traits
+            )
+            if random.random() < chance:
+                tier = min(tier + 1, MAX_EDUCATION_TIER)
+        return skill, tier
 
     ###########################################
     # Checking Validity, Date Comparisons
@@ -430,6 +656,27 @@ class TitleHistory:
             return True
         return True
 
+    @staticmethod
+    def preferred_sex(gender_law):
+        """
+        True if the law prefers women, False if it prefers men, or None if
+        it treats both alike.
+        """
+        if gender_law in (GenderLaw.AGNATIC, GenderLaw.AGNATIC_COGNATIC):
+            return False
+        if gender_law in (GenderLaw.ENATIC, GenderLaw.ENATIC_COGNATIC):
+            return True
+        return None
+
+    def preferred_sex_first(self, characters, gender_law, key, reverse=False):
+        """Sort 'characters' by 'key', the law's preferred sex ahead of the other."""
+        preferred = self.preferred_sex(gender_law)
+        ordered = sorted(characters, key=key, reverse=reverse)
+        if preferred is None:
+            return ordered
+        return ([c for c in ordered if c.female == preferred]
+                + [c for c in ordered if c.female != preferred])
+
     def get_relevant_parent(self, ruler, gender_law):
         """
         Return father or mother depending on the gender law. 
@@ -457,6 +704,11 @@ class TitleHistory:
         c_death = self.get_death_date(character)  # (dy,dm,dd)
         return c_death > date_tuple
 
+    def is_of_age_at(self, character, date_tuple):
+        """Return True if 'character' was at least ELECTOR_AGE on 'date_tuple'."""
+        by, bm, bd = self.get_birth_date(character)
+        return (by + ELECTOR_AGE, bm, bd) <= date_tuple
+
     def was_alive_or_posthumous(self, character, parent_death_date):
         """
         For Seniority or other checks, we want to see if the character 
@@ -514,61 +766,153 @@ class TitleHistory:
         return "?"
 
     def print_title_histories(self):
-        """Print each dynasty's rulers to the console with in-game formatted dates."""
-        for dynasty, rulers in self.titles.items():
-            print(f"\n--- Dynasty: {dynasty} ---")
-            for ruler_id, by, bm, bd, dy, dm, dd in rulers:
+        """Print each title's holders to the console with in-game formatted dates."""
+        for title, reigns in self.titles:
+            print(f"\n--- Title: {title} ---")
+            for (holder_id, start), (_, end) in zip(reigns, reigns[1:] + [(None, None)]):
+                by, bm, bd = start
                 inherited = f"{self.convert_to_ingame_date(by)}.{bm:02}.{bd:02}"
+                if end is None:
+                    print(f"Ruler: {holder_id} | Inherited: {inherited}")
+                    continue
+                dy, dm, dd = end
                 died = f"{self.convert_to_ingame_date(dy)}.{dm:02}.{dd:02}"
-                print(f"Ruler: {ruler_id} | Inherited: {inherited} | Died: {died}")
+                print(f"Ruler: {holder_id} | Inherited: {inherited} | Until: {died}")
         print("\n")
 
     def write_title_histories_to_file(self):
         """
-        Output the collected data (self.titles) to a file, 
-        in the style of placeholder_title blocks.
+        Output the collected data (self.titles) to a file, one block per
+        title with a dated holder entry for every change of holder.
         """
         from ck3gen.paths import CHARACTER_OUTPUT_DIR
         CHARACTER_OUTPUT_DIR.mkdir(parents=True, exist_ok=True)
         output_path = CHARACTER_OUTPUT_DIR / 'title_history.txt'
 
-        with open(output_path, 'w', encoding='utf-8') as file:
-            for dynasty, rulers in self.titles.items():
-                # Write the placeholder_title header
-                file.write("placeholder_title = {\n")
-                
-                placeholder_title = {}
-                
-                for ruler_data in rulers:
-                    # ruler_data = (ruler_id, by, bm, bd, dy, dm, dd)
-                    ruler_id, by, bm, bd, dy, dm, dd = ruler_data
-                    # Find the ruler’s name
-                    if ruler_id in self.characters:
-                        ruler_name = self.characters[ruler_id].name
+        with open(output_path, 'w', encoding='utf-8-sig') as file:
+            for title, reigns in self.titles:
+                file.write(f"{title} = {{\n")
+                for holder_id, (y, m, d) in reigns:
+                    if holder_id in self.characters:
+                        holder = f"{holder_id} #{self.characters[holder_id].name}"
                     else:
-                        ruler_name = "Unknown"
+                        holder = holder_id
+                    file.write(f"    {self.calendar.format(y, m, d)} = {{\n")
+                    file.write(f"        holder = {holder}\n")
+                    file.write("    }\n")
 
-                    # Add ruler to the dynasty's placeholder_title
-                    date_string = f"{by:04}.{bm:02}.{bd:02}"
-                    placeholder_title[date_string] = {
-                        "holder": f"{ruler_id}"
-                    }
+                file.write("}\n\n")  # Close the title block and add blank line
 
-                    # If there's a next ruler, mark the previous ruler's end date
-                    idx = rulers.index(ruler_data)
-                    if idx + 1 < len(rulers):
-                        next_ruler = rulers[idx + 1]
-                        next_start_year, next_start_month, next_start_day = next_ruler[1], next_ruler[2], next_ruler[3]
-                        next_holder_id = next_ruler[0]
-                        next_name = self.characters[next_holder_id].name if next_holder_id in self.characters else "Unknown"
-                        placeholder_title[f"{next_start_year:04}.{next_start_month:02}.{next_start_day:02}"] = {
-                            "holder": f"{next_holder_id} #{next_name}"
-                        }
-
-                # Write placeholder_title entries to the file
-                for date, entry in placeholder_title.items():
-                    file.write(f"    {date} = {{\n")
-                    file.write(f"        holder = {entry['holder']}\n")
-                    file.write("    }\n")
-                
-                file.write("}\n\n")  # Close the placeholder_title block and add blank line
\ No newline at end of file
+    def write_guardianships_to_file(self):
+        """
+        Record each ward's guardian in the character history: a dated
+        set_relation_guardian effect in the ward's block, in date order among
+        their entries, and the education the guardian left them with.
+        """
+        if not self.guardians:
+            return
+        from ck3gen.paths import CHARACTER_OUTPUT_DIR
+        path = CHARACTER_OUTPUT_DIR / "family_history.txt"
+        with open(path, "r", encoding="utf-8-sig") as file:
+            lines = file.read().split("\n")
+
+        output = []
+        ward = None
+        block = []
+        for line in lines:
+            if ward is None:
+                match = re.match(r"(\w+) = \{$", line)
+                if match and match.group(1) in self.guardians:
+                    ward = match.group(1)
+                    block = [line]
+                else:
+                    output.append(line)
+                continue
+            block.append(line)
+            if line == "}":
+                output.extend(self.with_guardian(block, ward))
+                ward = None
+        if ward:
+            output.extend(block)
+
+        with open(path, "w", encoding="utf-8-sig") as file:
+            file.write("\n".join(output))
+
+    def with_guardian(self, block, ward_id):
+        """The lines of 'ward_id's character block with their guardianship added."""
+        guardian_id, (y, m, d), education = self.guardians[ward_id]
+        if education is not None:
+            skill, tier = education
+            block = [
+                re.sub(r"\btrait = education_[a-z]+_\d+\b", f"trait = education_{skill}_{tier}", line)
+                for line in block
+            ]
+
+        ward = self.characters[ward_id]
+        by, bm, bd = self.get_birth_date(ward)
+        age = y - by - ((m, d) < (bm, bd))
+        entry = [
+            f"\t{self.calendar.format(y, m, d)} = {{  # Guardian at age {age}",
+            "\t    effect = {",
+            f"\t        set_relation_guardian = character:{guardian_id}",
+            "\t    }",
+            "\t}",
+        ]
+        position = len(block) - 1
+        written = self.calendar.to_calendar(y, m, d)
+        for i, line in enumerate(block):
+            dated = re.match(r"\t" + DATE_PATTERN + r" = \{", line)
+            if dated and tuple(map(int, dated.groups())) > written:
+                position = i
+                break
+        return block[:position] + entry + block[position:]
+
+    def write_landed_title_files(self):
+        """
+        Write the configured titles as CK3 landed title history, one
+        history/titles/<dynasty>.txt per dynasty, ready to drop into a mod.
+        Every change of holder gets a dated entry; the first also sets the
+        title's development and liege, and a liege title whose line runs out
+        frees its vassals on that date.
+        """
+        from ck3gen.paths import CHARACTER_OUTPUT_DIR
+        output_dir = CHARACTER_OUTPUT_DIR / "history" / "titles"
+
+        by_dynasty = defaultdict(list)
+        for title, reigns in self.titles:
+            if title in self.landed:
+                by_dynasty[self.landed[title]["dynasty"]].append((title, reigns))
+        if not by_dynasty:
+            return
+        output_dir.mkdir(parents=True, exist_ok=True)
+
+        for dynasty, titles in by_dynasty.items():
+            with open(output_dir / f"{dynasty}.txt", "w", encoding="utf-8-sig") as file:
+                for title, reigns in titles:
+                    file.write(f"{title} = {{\n")
+                    for date, lines in sorted(self.landed_title_entries(title, reigns).items()):
+                        y, m, d = self.calendar.to_calendar(*date)
+                        file.write(f"\t{y}.{m}.{d} = {{\n")
+                        for line in lines:
+                            file.write(f"\t\t{line}\n")
+                        file.write("\t}\n")
+                    file.write("}\n\n")
+
+    def landed_title_entries(self, title, reigns):
+        """The effect lines of one landed title's history, by date."""
+        settings = self.landed[title]
+        entries = defaultdict(list)
+        for holder_id, date in reigns:
+            name = self.characters[holder_id].name if holder_id in self.characters else "Unknown"
+            entries[date].append(f"holder = {holder_id} # {name}")
+        start = reigns[0][1]
+        liege = settings["liege"]
+        if liege:
+            entries[start].append(f"liege = {liege}")
+        if settings["development"] is not None:
+            entries[start].append(f"change_development_level = {settings['development']}")
+        if liege in self.vacated:
+            freed = self.vacated[liege]
+            if start <= freed < self.vacated.get(title, LIVING):
+                entries[freed].append("liege = 0")
+        return entries
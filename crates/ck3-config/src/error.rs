@@ -0,0 +1,66 @@
+//! Validation errors with the location of the offending value.
+
+use serde::Serialize;
+
+/// A problem with one value of a config file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigError {
+    /// Path of the value, e.g. `dynasties[2].nameInheritance`; empty for
+    /// problems with the document as a whole.
+    pub path: String,
+    pub message: String,
+    /// 1-based position in the file, for syntax errors.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl ConfigError {
+    pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+            line: None,
+            column: None,
+        }
+    }
+
+    /// Convert a deserialization error, keeping the path to the value.
+    pub(crate) fn from_json(err: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let path = err.path().to_string();
+        let inner = err.into_inner();
+        let (line, column) = match inner.classify() {
+            serde_json::error::Category::Syntax | serde_json::error::Category::Eof => {
+                (Some(inner.line()), Some(inner.column()))
+            }
+            _ => (None, None),
+        };
+        Self {
+            // The path library renders the root as ".".
+            path: if path == "." { String::new() } else { path },
+            message: strip_position(&inner.to_string()),
+            line,
+            column,
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Drop the " at line X column Y" suffix serde_json appends to its messages.
+fn strip_position(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(end) => message[..end].to_string(),
+        None => message.to_string(),
+    }
+}
@@ -0,0 +1,98 @@
+//! The game version the generated files are written for.
+//!
+//! Effects the history files use came with later versions of the game: an
+//! older game logs every one it does not know and skips it. A config naming
+//! its `gameVersion` has them left out where the game cannot read them, and
+//! the descriptor claims that version rather than every one.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// A game version, such as `1.12`; the patch number of `1.12.5` is dropped,
+/// as the file format never changes with a patch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct GameVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+/// What the history files can hold from some game version on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Feature {
+    /// The `learn_language` effect, from Royal Court's 1.5.
+    Languages,
+    /// The `create_character_memory` effect, from 1.9.
+    Memories,
+}
+
+impl Feature {
+    /// The first game version with the feature.
+    pub fn since(self) -> GameVersion {
+        match self {
+            Feature::Languages => GameVersion::new(1, 5),
+            Feature::Memories => GameVersion::new(1, 9),
+        }
+    }
+}
+
+impl GameVersion {
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    pub fn supports(self, feature: Feature) -> bool {
+        self >= feature.since()
+    }
+
+    /// The launcher's `supported_version` for this version, such as
+    /// `1.12.*`.
+    pub fn supported_version(self) -> String {
+        format!("{self}.*")
+    }
+}
+
+/// Whether a game of version `version`, or the latest if none, supports
+/// `feature`.
+pub fn supports(version: Option<GameVersion>, feature: Feature) -> bool {
+    version.is_none_or(|version| version.supports(feature))
+}
+
+impl FromStr for GameVersion {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let text = text.strip_prefix(['v', 'V']).unwrap_or(text);
+        let mut parts = text.split('.');
+        let mut number = || parts.next().and_then(|part| part.parse::<u32>().ok());
+        match (number(), number()) {
+            (Some(major), Some(minor)) => Ok(Self::new(major, minor)),
+            _ => Err(format!(
+                "'{text}' is not a game version such as 1.12 or 1.12.5"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for GameVersion {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<GameVersion> for String {
+    fn from(version: GameVersion) -> Self {
+        version.to_string()
+    }
+}
+
+impl fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
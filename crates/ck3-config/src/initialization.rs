@@ -0,0 +1,657 @@
+//! `initialization.json`: dynasties, negative events and the simulated period.
+//!
+//! Field names follow the file, which mixes camelCase with the odd
+//! snake_case key (`gender_law`). Keys this crate does not know about are kept
+//! in `extra` so a round trip never drops them.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::game_version::GameVersion;
+
+/// Language of the localisation files when the config names none.
+pub const DEFAULT_LANGUAGE: &str = "english";
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializationConfig {
+    pub dynasties: Vec<Dynasty>,
+    #[serde(default)]
+    pub events: Vec<NegativeEvent>,
+    /// First simulated year.
+    pub min_year: i32,
+    /// Last simulated year.
+    pub max_year: i32,
+    /// Maximum number of generations per dynasty.
+    pub generation_max: u32,
+    /// First number used for character IDs.
+    #[serde(rename = "initialCharID")]
+    pub initial_char_id: u64,
+    /// Graphviz rank direction of the family trees, e.g. `TB` or `LR`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tree_generation: Option<String>,
+    /// `"yes"` to draw spouses from other dynasties in the family trees.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spouse_visible: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numenor_inheritance: Option<NumenorInheritance>,
+    /// Year by which each Númenórean blood tier (`"1"` to `"10"`) has declined.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numenor_decline: Option<BTreeMap<String, i32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numenor_blood: Option<NumenorBlood>,
+    #[serde(default, skip_serializing_if = "Matchmaking::is_default")]
+    pub matchmaking: Matchmaking,
+    #[serde(default, skip_serializing_if = "MarriageChecks::is_default")]
+    pub marriage_checks: MarriageChecks,
+    #[serde(default, skip_serializing_if = "ComingOfAge::is_default")]
+    pub coming_of_age: ComingOfAge,
+    #[serde(default, skip_serializing_if = "CadetHouses::is_default")]
+    pub cadet_houses: CadetHouses,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub portrait_genetics: Option<PortraitGenetics>,
+    #[serde(default, skip_serializing_if = "Calendar::is_default")]
+    pub calendar: Calendar,
+    /// Languages the localisation files are written for, such as `english`
+    /// or `simp_chinese`. English alone if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub localization_languages: Vec<String>,
+    /// Bookmark dates, as `year.month.day` in the dates of the history,
+    /// such as the game's `867.1.1` and `1066.9.15`, to write a snapshot of
+    /// the characters living on for review. None if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bookmark_snapshots: Vec<String>,
+    /// How the project is packaged as a mod around its history files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mod_settings: Option<ModSettings>,
+    /// The game version the generated files are written for, such as
+    /// `1.12`; the latest format if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_version: Option<GameVersion>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dynasty {
+    #[serde(rename = "dynastyID")]
+    pub dynasty_id: String,
+    pub dynasty_name: String,
+    #[serde(default)]
+    pub dynasty_motto: String,
+    pub succession: Succession,
+    #[serde(default)]
+    pub is_house: bool,
+    #[serde(rename = "faithID")]
+    pub faith_id: String,
+    #[serde(rename = "cultureID")]
+    pub culture_id: String,
+    #[serde(rename = "gender_law")]
+    pub gender_law: GenderLaw,
+    pub progenitor_male_birth_year: i32,
+    #[serde(default)]
+    pub allow_first_cousin_marriage: bool,
+    #[serde(default)]
+    pub prioritise_lowborn_marriage: bool,
+    /// Whether the dynasty's women marry matrilineally whatever their law,
+    /// their children joining the dynasty. Only the native simulation reads
+    /// this.
+    #[serde(default)]
+    pub matrilineal_marriage: bool,
+    /// Weights over the matchmaking ones of matches across faiths and
+    /// cultures for the dynasty's members: low for an insular dynasty, high
+    /// for a cosmopolitan one. Only the native simulation reads these.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub different_faith_weight: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub different_culture_weight: Option<f64>,
+    #[serde(default)]
+    pub numenor_blood_tier: Option<u32>,
+    #[serde(default)]
+    pub force_dynasty_alive: bool,
+    /// `"language_id,start_year,end_year"` entries: the languages the
+    /// dynasty speaks and the years, inclusive, it speaks them in.
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Titles the progenitor holds, primary first. Without any, the dynasty
+    /// holds a single placeholder title under its own laws.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub titles: Vec<DynastyTitle>,
+    /// Faiths the dynasty converts to, each over its own span of years. Only
+    /// the native simulation reads these.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conversions: Vec<FaithConversion>,
+    /// Spans of years the dynasty is at war. Only the native simulation
+    /// reads these.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wars: Vec<War>,
+    /// How many wives and concubines the dynasty's men may have, over the
+    /// doctrine of their faith. Only the native simulation reads this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marriage_doctrine: Option<MarriageDoctrine>,
+    pub name_inheritance: NameInheritance,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The yes-or-no settings of a dynasty, by their key in the file.
+pub const DYNASTY_FLAGS: [&str; 5] = [
+    "isHouse",
+    "allowFirstCousinMarriage",
+    "prioritiseLowbornMarriage",
+    "matrilinealMarriage",
+    "forceDynastyAlive",
+];
+
+impl Dynasty {
+    /// The setting of the flag `name`, one of [`DYNASTY_FLAGS`].
+    pub fn flag(&self, name: &str) -> Option<bool> {
+        match name {
+            "isHouse" => Some(self.is_house),
+            "allowFirstCousinMarriage" => Some(self.allow_first_cousin_marriage),
+            "prioritiseLowbornMarriage" => Some(self.prioritise_lowborn_marriage),
+            "matrilinealMarriage" => Some(self.matrilineal_marriage),
+            "forceDynastyAlive" => Some(self.force_dynasty_alive),
+            _ => None,
+        }
+    }
+}
+
+/// A span of years over which a dynasty's members convert to a faith, one by
+/// one, until every member alive at its end has.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FaithConversion {
+    #[serde(rename = "faithID")]
+    pub faith_id: String,
+    pub start_year: i32,
+    pub end_year: i32,
+}
+
+/// A span of years a dynasty is at war, when its members of fighting age may
+/// fall in battle, be wounded or come back seasoned by it.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct War {
+    #[serde(default)]
+    pub name: String,
+    pub start_year: i32,
+    pub end_year: i32,
+    /// Yearly chance of each fighter dying in battle.
+    pub battle_death_chance: f64,
+    /// Yearly chance of each fighter who lives being wounded.
+    #[serde(default)]
+    pub wound_chance: f64,
+    /// Yearly chance of each fighter who lives gaining a trait of prowess.
+    #[serde(default)]
+    pub veteran_chance: f64,
+    /// Whether the dynasty's women fight as well as its men.
+    #[serde(default)]
+    pub women_fight: bool,
+}
+
+/// A title a dynasty holds, and how it passes on when its holder dies.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DynastyTitle {
+    /// Title key, e.g. `k_gondor`.
+    pub id: String,
+    /// Succession law of the title, if not the dynasty's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub succession: Option<Succession>,
+    /// Gender law of the title, if not the dynasty's.
+    #[serde(
+        rename = "gender_law",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub gender_law: Option<GenderLaw>,
+    /// Key of the title this one is a vassal of, written to its landed title
+    /// history.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liege: Option<String>,
+    /// Development level the title starts at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub development: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Succession {
+    Primogeniture,
+    Ultimogeniture,
+    Seniority,
+    /// Titles are shared out among the children of the preferred sex.
+    Partition,
+    /// The dynasty's adults vote for the next holder.
+    Elective,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GenderLaw {
+    Agnatic,
+    AgnaticCognatic,
+    AbsoluteCognatic,
+    EnaticCognatic,
+    Enatic,
+}
+
+/// Probability weights for how a child's name is chosen; they sum to 1.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameInheritance {
+    pub grandparent_name_inheritance_chance: f64,
+    pub parent_name_inheritance_chance: f64,
+    pub no_name_inheritance_chance: f64,
+}
+
+/// A historical event that raises the death rate within a year and age window.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NegativeEvent {
+    #[serde(rename = "eventID")]
+    pub event_id: String,
+    pub start_year: i32,
+    pub end_year: i32,
+    pub death_reason: String,
+    pub death_multiplier: f64,
+    pub character_age_start: u32,
+    pub character_age_end: u32,
+}
+
+/// Chances of a child inheriting its parents' Númenórean blood tier.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumenorInheritance {
+    pub same_tier_chance: f64,
+    pub close_tier_chance: f64,
+    pub far_tier_chance: f64,
+}
+
+/// Númenórean blood as a strength from 0 to 1 that children take from both
+/// parents, each tier being a band of it. Only the native simulation reads
+/// this section; without it a child keeps the higher parent's tier or drops
+/// below it by `numenorInheritance`, whatever the other parent's.
+///
+/// A parent's blood counts as the middle of their tier's band, none for the
+/// untiered, and a child's as the mean of both parents', unknown parents
+/// counting as untiered, straying from it by up to `drift` either way. The
+/// child of a tier 10 and an untiered parent comes out about tier 5, and
+/// that of a tier 4 and a tier 8 parent about tier 6.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumenorBlood {
+    /// Strength from which each tier (`"1"` to `"10"`) begins. Tiers left
+    /// out begin a twentieth short of a tenth a tier, so that tier 5 is the
+    /// band around 0.5.
+    #[serde(default)]
+    pub tier_thresholds: BTreeMap<String, f64>,
+    /// Most strength a child's blood may stray from its parents' mean.
+    #[serde(default = "default_blood_drift")]
+    pub drift: f64,
+}
+
+fn default_blood_drift() -> f64 {
+    0.05
+}
+
+impl NumenorBlood {
+    /// Strength from which blood tier `tier` begins, 0 for tier 0.
+    pub fn threshold(&self, tier: u32) -> f64 {
+        if tier == 0 {
+            return 0.0;
+        }
+        self.tier_thresholds
+            .get(&tier.to_string())
+            .copied()
+            .unwrap_or((f64::from(tier) - 0.5) / 10.0)
+    }
+}
+
+/// Who may marry whom, and whom they prefer. Only the native simulation
+/// reads this section; the Python engine keeps its own pairing.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Matchmaking {
+    /// Most years a husband may be older than his wife, before blood tier
+    /// allowances. Defaults to `marriageMaxAgeDifference` of
+    /// `life_stages.json`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_husband_older: Option<u32>,
+    /// Most years a wife may be older than her husband, likewise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_wife_older: Option<u32>,
+    /// Age gap, husband older, that matches are drawn towards.
+    #[serde(default = "default_preferred_age_gap")]
+    pub preferred_age_gap: i32,
+    /// Weight of a match across faiths relative to one within a faith; 0
+    /// forbids them. Dynasties may set their own.
+    #[serde(default = "default_weight")]
+    pub different_faith_weight: f64,
+    /// Weight of a match across cultures relative to one within a culture.
+    #[serde(default = "default_weight")]
+    pub different_culture_weight: f64,
+    /// How much each dynasty favours matches with another, by its ID and then
+    /// the other's; 0 forbids them. Pairs left out weigh 1 and both sides'
+    /// weights count.
+    #[serde(default)]
+    pub dynasty_relations: BTreeMap<String, BTreeMap<String, f64>>,
+    /// Factor on a match's weight for every living marriage already joining
+    /// the two dynasties: above 1 to build up alliances, below 1 to spread
+    /// them.
+    #[serde(default = "default_weight")]
+    pub alliance_weight: f64,
+    /// Highest coefficient of relationship allowed between spouses, e.g.
+    /// 0.125 for first cousins and 0.03125 for second cousins. Dynasties that
+    /// allow first cousin marriage may always go up to 0.125.
+    #[serde(default = "default_max_relatedness")]
+    pub max_relatedness: f64,
+    /// Generations of ancestry searched for common ancestors.
+    #[serde(default = "default_consanguinity_generations")]
+    pub consanguinity_generations: u32,
+    /// How many wives and concubines men may have, by faith ID. Faiths left
+    /// out are monogamous.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub marriage_doctrines: BTreeMap<String, MarriageDoctrine>,
+}
+
+/// How many wives and concubines a man may have at once, and how readily he
+/// takes another while he has room.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarriageDoctrine {
+    /// Wives a man may have at once, his first included.
+    #[serde(default = "default_max_spouses")]
+    pub max_spouses: u32,
+    #[serde(default)]
+    pub max_concubines: u32,
+    /// Yearly chance of a married man with room for another wife or
+    /// concubine taking one.
+    #[serde(default)]
+    pub consort_chance: f64,
+}
+
+fn default_max_spouses() -> u32 {
+    1
+}
+
+impl Default for Matchmaking {
+    fn default() -> Self {
+        Self {
+            max_husband_older: None,
+            max_wife_older: None,
+            preferred_age_gap: default_preferred_age_gap(),
+            different_faith_weight: default_weight(),
+            different_culture_weight: default_weight(),
+            dynasty_relations: BTreeMap::new(),
+            alliance_weight: default_weight(),
+            max_relatedness: default_max_relatedness(),
+            consanguinity_generations: default_consanguinity_generations(),
+            marriage_doctrines: BTreeMap::new(),
+        }
+    }
+}
+
+impl Matchmaking {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn default_preferred_age_gap() -> i32 {
+    2
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_max_relatedness() -> f64 {
+    0.0625
+}
+
+fn default_consanguinity_generations() -> u32 {
+    4
+}
+
+/// The rules the marriages of a history are checked against, and how much
+/// breaking each one matters. Either engine's output can be checked; the
+/// native simulation checks its own before writing it.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarriageChecks {
+    /// Youngest age, in the years the history is dated in, a character may
+    /// marry at.
+    #[serde(default = "default_min_marriage_age")]
+    pub min_age: u32,
+    /// A spouse younger than `minAge`.
+    #[serde(default = "default_warning")]
+    pub underage: Severity,
+    /// A spouse not yet born or already dead on the day.
+    #[serde(default = "default_error")]
+    pub dead_spouse: Severity,
+    /// A spouse with as many living spouses as their marriage doctrine
+    /// allows already, one for women and men of monogamous faiths.
+    #[serde(default = "default_error")]
+    pub already_married: Severity,
+    /// A parent marrying their child.
+    #[serde(default = "default_error")]
+    pub parent_child: Severity,
+}
+
+/// How much breaking a rule matters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    /// Not checked.
+    Off,
+    /// Reported, but the history is written all the same.
+    Warning,
+    /// Keeps the native simulation from writing the history.
+    Error,
+}
+
+impl Default for MarriageChecks {
+    fn default() -> Self {
+        Self {
+            min_age: default_min_marriage_age(),
+            underage: default_warning(),
+            dead_spouse: default_error(),
+            already_married: default_error(),
+            parent_child: default_error(),
+        }
+    }
+}
+
+impl MarriageChecks {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn default_min_marriage_age() -> u32 {
+    16
+}
+
+fn default_warning() -> Severity {
+    Severity::Warning
+}
+
+fn default_error() -> Severity {
+    Severity::Error
+}
+
+/// How children grow up in the history file. Only the native simulation
+/// reads this section.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComingOfAge {
+    /// Write a block on each 16th birthday that swaps the childhood trait
+    /// for the adult personality traits, as the game's coming of age does.
+    #[serde(default)]
+    pub swap_childhood_traits: bool,
+    /// Start dates of the bookmarks the history is played from, as
+    /// `year.month.day`. Swaps after the last one are left to the game;
+    /// with none, every swap is written.
+    #[serde(default = "default_bookmarks")]
+    pub bookmarks: Vec<String>,
+}
+
+impl Default for ComingOfAge {
+    fn default() -> Self {
+        Self {
+            swap_childhood_traits: false,
+            bookmarks: default_bookmarks(),
+        }
+    }
+}
+
+impl ComingOfAge {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// When branches of a dynasty split off into cadet houses. Only the native
+/// simulation reads this section. Dynasties configured as houses never
+/// split, as the dynasty their cadets would belong to is not known.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CadetHouses {
+    /// Living members a house may have before its largest branch splits off
+    /// under a cadet house of its own. Houses never split for size if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_house_size: Option<u32>,
+    /// Chance of a younger son, or a younger daughter under enatic laws,
+    /// founding a house of their own on coming of age.
+    #[serde(default)]
+    pub younger_child_chance: f64,
+}
+
+impl CadetHouses {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Portrait DNA passed down from parents to children. Only the native
+/// simulation reads this section; without it the game draws every
+/// character's portrait at random.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortraitGenetics {
+    /// Largest step, out of 255, a gene may drift from the value a child
+    /// inherits.
+    #[serde(default = "default_gene_mutation")]
+    pub mutation: u32,
+}
+
+/// The calendar the generated files are dated in. Every year in the config
+/// is a simulation year; only the dates written out are in this calendar,
+/// which by default is the simulation's own.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Calendar {
+    /// Calendar year of simulation year 0, so that simulation year `y`
+    /// starts calendar year `epochOffset + y`. Years before the epoch come
+    /// out as zero or negative.
+    #[serde(default)]
+    pub epoch_offset: i32,
+    /// Simulation years to each calendar year, for calendars counting in
+    /// longer years, such as the Elvish yén of 144 years. The days of a
+    /// calendar year are spread evenly over them.
+    #[serde(default = "default_year_length")]
+    pub year_length: i32,
+}
+
+impl Calendar {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self {
+            epoch_offset: 0,
+            year_length: default_year_length(),
+        }
+    }
+}
+
+fn default_year_length() -> i32 {
+    1
+}
+
+/// The launcher entry of the mod the generated files are packaged as.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModSettings {
+    /// Name shown in the launcher.
+    pub name: String,
+    #[serde(default = "default_mod_version")]
+    pub version: String,
+    /// Game versions the mod runs on, such as `1.12.*`.
+    #[serde(default = "default_supported_version")]
+    pub supported_version: String,
+    /// Launcher tags, such as `Historical` or `Total Conversion`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl ModSettings {
+    /// Settings for a mod named `name`, at version 1.0.0 for any version of
+    /// the game.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: default_mod_version(),
+            supported_version: default_supported_version(),
+            tags: Vec::new(),
+        }
+    }
+}
+
+fn default_mod_version() -> String {
+    "1.0.0".into()
+}
+
+fn default_supported_version() -> String {
+    "1.*".into()
+}
+
+fn default_gene_mutation() -> u32 {
+    12
+}
+
+/// The start dates of the game's own bookmarks.
+fn default_bookmarks() -> Vec<String> {
+    vec!["867.1.1".into(), "1066.9.15".into(), "1178.10.1".into()]
+}
+
+impl InitializationConfig {
+    /// Serialize the way the engine writes the file: four-space indents.
+    pub fn to_json_pretty(&self) -> serde_json::Result<String> {
+        let mut out = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+        let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+        self.serialize(&mut serializer)?;
+        Ok(String::from_utf8(out).expect("serde_json writes UTF-8"))
+    }
+
+    /// The languages the localisation files are written for.
+    pub fn languages(&self) -> Vec<&str> {
+        if self.localization_languages.is_empty() {
+            vec![DEFAULT_LANGUAGE]
+        } else {
+            self.localization_languages
+                .iter()
+                .map(String::as_str)
+                .collect()
+        }
+    }
+}
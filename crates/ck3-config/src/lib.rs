@@ -0,0 +1,71 @@
+//! Typed configuration files of the CK3 Character History Generator.
+//!
+//! The engine reads its settings from JSON files in the config directory.
+//! This crate models them as serde types and validates them with the same
+//! rules as the engine's `ConfigLoader` and the API's Pydantic models, so a
+//! broken file is rejected with the location of every problem instead of
+//! crashing the engine halfway through a run. The `.ck3hist` project files
+//! bundling them are versioned and migrated up to the current format when
+//! read. Dynasty templates, partial dynasties bundled with the app or saved
+//! by users, are checked against the same rules.
+
+pub mod error;
+pub mod game_version;
+pub mod initialization;
+pub mod life_stages;
+pub mod project;
+pub mod skills_and_traits;
+pub mod templates;
+mod validate;
+
+pub use error::ConfigError;
+pub use game_version::{Feature, GameVersion};
+pub use initialization::{
+    CadetHouses, Calendar, ComingOfAge, Dynasty, DynastyTitle, FaithConversion, GenderLaw,
+    InitializationConfig, MarriageChecks, MarriageDoctrine, Matchmaking, ModSettings,
+    NameInheritance, NegativeEvent, NumenorBlood, NumenorInheritance, PortraitGenetics, Severity,
+    Succession, War, DEFAULT_LANGUAGE, DYNASTY_FLAGS,
+};
+pub use life_stages::{
+    AgeLethality, Bastardy, BloodTierFertility, BySex, Epidemic, LifeStages, Mortality,
+    MortalityEra, Pregnancy, SurvivalCurve,
+};
+pub use project::{
+    parse_project, project_from_value, OutputSettings, Project, ProjectConfig,
+    PROJECT_SCHEMA_VERSION,
+};
+pub use skills_and_traits::{PersonalityTrait, PersonalityTraits, SkillsAndTraits, TraitRule};
+pub use templates::{bundled_templates, DynastyTemplate, TEMPLATE_EXCLUDED_FIELDS};
+
+/// Parse and validate the contents of `initialization.json`.
+pub fn parse_initialization(text: &str) -> Result<InitializationConfig, Vec<ConfigError>> {
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    let config: InitializationConfig = serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|err| vec![ConfigError::from_json(err)])?;
+    config.check()
+}
+
+/// Validate an already parsed `initialization.json` document.
+pub fn initialization_from_value(
+    value: serde_json::Value,
+) -> Result<InitializationConfig, Vec<ConfigError>> {
+    let config: InitializationConfig =
+        serde_path_to_error::deserialize(value).map_err(|err| vec![ConfigError::from_json(err)])?;
+    config.check()
+}
+
+/// Parse and validate the contents of `life_stages.json`.
+pub fn parse_life_stages(text: &str) -> Result<LifeStages, Vec<ConfigError>> {
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    let config: LifeStages = serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|err| vec![ConfigError::from_json(err)])?;
+    config.check()
+}
+
+/// Parse and validate the contents of `skills_and_traits.json`.
+pub fn parse_skills_and_traits(text: &str) -> Result<SkillsAndTraits, Vec<ConfigError>> {
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    let config: SkillsAndTraits = serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|err| vec![ConfigError::from_json(err)])?;
+    config.check()
+}
@@ -0,0 +1,300 @@
+//! `life_stages.json`: per-age rates for marriage, fertility and death.
+//!
+//! Every rate table holds one value per age from 0 to 120. The optional
+//! `mortality` section bends the mortality tables by era and trait and caps
+//! how long anyone lives, the optional `pregnancy` section dates births
+//! from their conception, `epidemics` lists waves of plague and `bastardy`
+//! decides what becomes of bastards.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LifeStages {
+    /// Chance per age of an unmarried dynasty member settling for a lowborn
+    /// spouse.
+    #[serde(default)]
+    pub desperation_marriage_rates: Vec<f64>,
+    pub mortality_rates: BySex<Vec<f64>>,
+    pub marriage_rates: BySex<Vec<f64>>,
+    pub fertility_rates: BySex<Vec<f64>>,
+    /// How each blood tier (`"1"` to `"10"`) bends the fertility tables.
+    /// Tiers left out stay at peak fertility ten years a tier and lose 2.5%
+    /// a tier after it. Only the native simulation reads these.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub blood_tier_fertility: BTreeMap<String, BloodTierFertility>,
+    /// Largest age gap between spouses, before blood tier allowances.
+    #[serde(default = "default_max_age_difference")]
+    pub marriage_max_age_difference: u32,
+    pub maximum_number_of_children: u32,
+    pub minimum_years_between_children: u32,
+    pub bastardy_chance_male: f64,
+    pub bastardy_chance_female: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mortality: Option<Mortality>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pregnancy: Option<Pregnancy>,
+    /// Waves of plague. Only the native simulation reads these.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub epidemics: Vec<Epidemic>,
+    #[serde(default, skip_serializing_if = "Bastardy::is_default")]
+    pub bastardy: Bastardy,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn default_max_age_difference() -> u32 {
+    5
+}
+
+impl LifeStages {
+    /// How blood tier `tier` bends the fertility tables, not at all for
+    /// tier 0.
+    pub fn blood_fertility(&self, tier: u32) -> BloodTierFertility {
+        self.blood_tier_fertility
+            .get(&tier.to_string())
+            .copied()
+            .unwrap_or(BloodTierFertility {
+                peak_years: 10 * tier,
+                factor: (1.0 - f64::from(tier) * 0.025).max(0.0),
+            })
+    }
+}
+
+/// The fertility curve of a blood tier.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BloodTierFertility {
+    /// Years after 16 spent at the peak of the fertility tables, by which
+    /// the rest of them is put off, as is the oldest age a mother may
+    /// conceive at.
+    pub peak_years: u32,
+    /// Factor on the fertility tables after the peak.
+    pub factor: f64,
+}
+
+/// Adjustments to the mortality tables, and the lifespan caps.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mortality {
+    /// Spans of years with their own mortality. Where eras overlap, every
+    /// multiplier applies and the first era with a curve supplies it.
+    #[serde(default)]
+    pub eras: Vec<MortalityEra>,
+    /// Factor on the yearly chance of death of characters with a trait, by
+    /// trait ID.
+    #[serde(default)]
+    pub trait_multipliers: BTreeMap<String, f64>,
+    /// Age by which every character without Númenórean blood has died.
+    #[serde(default = "default_max_lifespan")]
+    pub max_lifespan: u32,
+    /// Age by which every character of a blood tier has died, keyed `"1"`
+    /// to `"10"`. Tiers left out get 20 years a tier on top of
+    /// `maxLifespan`.
+    #[serde(default)]
+    pub blood_tier_max_lifespan: BTreeMap<String, u32>,
+}
+
+impl Default for Mortality {
+    fn default() -> Self {
+        Self {
+            eras: Vec::new(),
+            trait_multipliers: BTreeMap::new(),
+            max_lifespan: default_max_lifespan(),
+            blood_tier_max_lifespan: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_max_lifespan() -> u32 {
+    120
+}
+
+/// When in their lives characters conceive, and how long children take to be
+/// born. Only the native simulation reads this section.
+///
+/// Without it a child can be born on any day of the year it is rolled in;
+/// with it, the birth falls `gestationMonths` after a conception both
+/// parents were old enough for, and `monthsBetweenBirths` after the mother's
+/// last child.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pregnancy {
+    /// Youngest a woman may conceive, if later than her fertility onset.
+    #[serde(default = "default_min_maternal_age")]
+    pub min_maternal_age: u32,
+    /// Oldest a woman without Númenórean blood may conceive. Blood tiers add
+    /// the peak years they add to the fertility tables.
+    #[serde(default = "default_max_maternal_age")]
+    pub max_maternal_age: u32,
+    /// Months from conception to birth.
+    #[serde(default = "default_gestation_months")]
+    pub gestation_months: u32,
+    /// Fewest months between the births of a mother's children.
+    #[serde(default = "default_months_between_births")]
+    pub months_between_births: u32,
+    /// Age from which characters without Númenórean blood can have
+    /// children.
+    #[serde(default = "default_fertility_onset")]
+    pub fertility_onset: u32,
+    /// Age from which characters of a blood tier can have children, keyed
+    /// `"1"` to `"10"`. Tiers left out get five years a tier on top of
+    /// `fertilityOnset`, as they marry five years a tier later.
+    #[serde(default)]
+    pub blood_tier_fertility_onset: BTreeMap<String, u32>,
+}
+
+impl Default for Pregnancy {
+    fn default() -> Self {
+        Self {
+            min_maternal_age: default_min_maternal_age(),
+            max_maternal_age: default_max_maternal_age(),
+            gestation_months: default_gestation_months(),
+            months_between_births: default_months_between_births(),
+            fertility_onset: default_fertility_onset(),
+            blood_tier_fertility_onset: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_min_maternal_age() -> u32 {
+    16
+}
+
+fn default_max_maternal_age() -> u32 {
+    45
+}
+
+fn default_gestation_months() -> u32 {
+    9
+}
+
+fn default_months_between_births() -> u32 {
+    12
+}
+
+fn default_fertility_onset() -> u32 {
+    16
+}
+
+/// What becomes of bastards. Only the native simulation reads this section.
+///
+/// A bastard may be legitimized, keeping their parent's dynasty, or found a
+/// lowborn line of their own outside it; the rest stay in the dynasty as
+/// bastards, as they all do without this section.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bastardy {
+    /// Chance of a bastard being legitimized.
+    #[serde(default)]
+    pub legitimization_chance: f64,
+    /// Age by which legitimized bastards have been legitimized.
+    #[serde(default = "default_max_legitimization_age")]
+    pub max_legitimization_age: u32,
+    /// Chance of a bastard growing up outside their parent's dynasty, as the
+    /// founder of a lowborn line.
+    #[serde(default)]
+    pub lowborn_line_chance: f64,
+}
+
+impl Default for Bastardy {
+    fn default() -> Self {
+        Self {
+            legitimization_chance: 0.0,
+            max_legitimization_age: default_max_legitimization_age(),
+            lowborn_line_chance: 0.0,
+        }
+    }
+}
+
+impl Bastardy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn default_max_legitimization_age() -> u32 {
+    16
+}
+
+/// A wave of plague, raising the chance of death of those it strikes while
+/// it lasts.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Epidemic {
+    #[serde(default)]
+    pub name: String,
+    pub start_year: i32,
+    pub end_year: i32,
+    /// Dynasties it strikes, by ID, lowborn spouses included; every dynasty
+    /// if empty.
+    #[serde(default)]
+    pub dynasties: Vec<String>,
+    /// Cultures it strikes, by ID; every culture if empty.
+    #[serde(default)]
+    pub cultures: Vec<String>,
+    /// Factor on the yearly chance of death by age. Ages outside every band
+    /// are spared.
+    pub lethality: Vec<AgeLethality>,
+    /// Death reason of those who die while it strikes them.
+    #[serde(default = "default_epidemic_death_reason")]
+    pub death_reason: String,
+}
+
+fn default_epidemic_death_reason() -> String {
+    "death_ill".to_string()
+}
+
+/// An epidemic's factor on the chance of death between two ages.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgeLethality {
+    pub from_age: u32,
+    pub to_age: u32,
+    pub multiplier: f64,
+}
+
+/// A span of years with its own mortality.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MortalityEra {
+    #[serde(default)]
+    pub name: String,
+    pub start_year: i32,
+    pub end_year: i32,
+    /// Survival curve used instead of the mortality tables during the era.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curve: Option<BySex<SurvivalCurve>>,
+    /// Factor on the yearly chance of death during the era.
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+}
+
+fn default_multiplier() -> f64 {
+    1.0
+}
+
+/// A Gompertz–Makeham survival curve with an infant term: the yearly hazard
+/// at age `x` is `infant * e^(-infantDecay * x) + background + aging *
+/// e^(agingRate * x)`, and the chance of dying that year `1 - e^(-hazard)`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SurvivalCurve {
+    pub infant: f64,
+    pub infant_decay: f64,
+    pub background: f64,
+    pub aging: f64,
+    pub aging_rate: f64,
+}
+
+/// A value for each sex, keyed `Male` and `Female` as in the files.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct BySex<T> {
+    #[serde(rename = "Male")]
+    pub male: T,
+    #[serde(rename = "Female")]
+    pub female: T,
+}
@@ -0,0 +1,272 @@
+//! `.ck3hist` project files.
+//!
+//! A project file bundles what it takes to generate a history again: the
+//! config files, the settings the project overrides, the seed and where the
+//! output goes. Every file records the `schemaVersion` it was written with.
+//! Reading one moves the JSON document up to [`PROJECT_SCHEMA_VERSION`] one
+//! version at a time, through the migration from each version to the next,
+//! before it is deserialized, so files written by older versions of the app
+//! keep loading, while a file written by a newer one is refused rather than
+//! misread. Changing the format means bumping the version and adding the
+//! migration from the one before to [`MIGRATIONS`].
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::error::ConfigError;
+
+/// Version of the format written by this version of the app.
+pub const PROJECT_SCHEMA_VERSION: u64 = 1;
+
+/// Key of the format version in a project file.
+const VERSION_KEY: &str = "schemaVersion";
+
+/// The migration from version `i` to `i + 1` at index `i`. Each works on the
+/// JSON document and leaves setting the version to the caller.
+const MIGRATIONS: [fn(Value) -> Value; PROJECT_SCHEMA_VERSION as usize] = [from_bare_config];
+
+/// Everything a project file holds, in its current version.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub schema_version: u64,
+    pub config: ProjectConfig,
+    /// Settings the project overrides, by key, as the UI keeps them in the
+    /// project database.
+    #[serde(default)]
+    pub settings: BTreeMap<String, Value>,
+    /// Seed runs are started with; a random one each run if none.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Seed of the history last generated, to reproduce it with.
+    #[serde(default)]
+    pub last_seed: Option<u64>,
+    #[serde(default)]
+    pub output: OutputSettings,
+}
+
+/// The config files, as the JSON documents the engine reads.
+///
+/// They are kept as written rather than as their types, so that a project
+/// carries values this version of the app does not know about on unchanged.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectConfig {
+    pub initialization: Value,
+    /// Left as the config directory has it if none.
+    #[serde(default)]
+    pub life_stages: Option<Value>,
+    /// Left as the config directory has it if none.
+    #[serde(default)]
+    pub skills_and_traits: Option<Value>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputSettings {
+    /// Output root the engine writes into; its default one if none.
+    pub output_dir: Option<PathBuf>,
+}
+
+impl Project {
+    /// A project of the current version.
+    pub fn new(config: ProjectConfig) -> Self {
+        Self {
+            schema_version: PROJECT_SCHEMA_VERSION,
+            config,
+            settings: BTreeMap::new(),
+            seed: None,
+            last_seed: None,
+            output: OutputSettings::default(),
+        }
+    }
+
+    /// The project as the text of a project file.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a project serializes")
+    }
+}
+
+/// Parse a project file of any version up to the current one, and validate
+/// its config files.
+pub fn parse_project(text: &str) -> Result<Project, Vec<ConfigError>> {
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    let value: Value = serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|err| vec![ConfigError::from_json(err)])?;
+    project_from_value(value)
+}
+
+/// Migrate an already parsed project file to the current version, and
+/// validate its config files.
+pub fn project_from_value(value: Value) -> Result<Project, Vec<ConfigError>> {
+    let version = match value.get(VERSION_KEY) {
+        // Only bare config files have no version.
+        None => 0,
+        Some(version) => version.as_u64().ok_or_else(|| {
+            vec![ConfigError::new(
+                VERSION_KEY,
+                "must be a whole number of at least 0",
+            )]
+        })?,
+    };
+    if version > PROJECT_SCHEMA_VERSION {
+        return Err(vec![ConfigError::new(
+            VERSION_KEY,
+            format!(
+                "the project was written by a newer version of the app, in version \
+                 {version} of the format; this one reads up to version {PROJECT_SCHEMA_VERSION}"
+            ),
+        )]);
+    }
+    let mut value = MIGRATIONS[version as usize..]
+        .iter()
+        .fold(value, |value, migrate| migrate(value));
+    if let Some(fields) = value.as_object_mut() {
+        fields.insert(VERSION_KEY.into(), PROJECT_SCHEMA_VERSION.into());
+    }
+    let project: Project =
+        serde_path_to_error::deserialize(value).map_err(|err| vec![ConfigError::from_json(err)])?;
+    check(&project)?;
+    Ok(project)
+}
+
+/// Validate the config files of `project`, locating problems within it.
+fn check(project: &Project) -> Result<(), Vec<ConfigError>> {
+    let config = &project.config;
+    let mut errors = Vec::new();
+    let mut check_file = |name: &str, result: Result<(), Vec<ConfigError>>| {
+        if let Err(found) = result {
+            errors.extend(found.into_iter().map(|error| ConfigError {
+                path: if error.path.is_empty() {
+                    format!("config.{name}")
+                } else {
+                    format!("config.{name}.{}", error.path)
+                },
+                // Positions in the project file are not known.
+                line: None,
+                column: None,
+                ..error
+            }));
+        }
+    };
+    check_file(
+        "initialization",
+        crate::initialization_from_value(config.initialization.clone()).map(drop),
+    );
+    if let Some(life_stages) = &config.life_stages {
+        check_file(
+            "lifeStages",
+            crate::parse_life_stages(&life_stages.to_string()).map(drop),
+        );
+    }
+    if let Some(skills_and_traits) = &config.skills_and_traits {
+        check_file(
+            "skillsAndTraits",
+            crate::parse_skills_and_traits(&skills_and_traits.to_string()).map(drop),
+        );
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Version 0 to 1: a file without a version is taken for a bare
+/// `initialization.json` given the project extension, and becomes the
+/// project's initialization config.
+fn from_bare_config(config: Value) -> Value {
+    json!({ "config": { "initialization": config } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The `initialization.json` the engine ships with.
+    const SHIPPED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../config/initialization.json"
+    ));
+
+    fn initialization() -> Value {
+        serde_json::from_str(SHIPPED).unwrap()
+    }
+
+    #[test]
+    fn projects_load_as_they_were_saved() {
+        let mut project = Project::new(ProjectConfig {
+            initialization: initialization(),
+            life_stages: None,
+            skills_and_traits: None,
+        });
+        project
+            .settings
+            .insert("dotExport".into(), json!({"generations": 3}));
+        project.seed = Some(42);
+        project.last_seed = Some(7);
+        project.output.output_dir = Some(PathBuf::from("output"));
+        assert_eq!(parse_project(&project.to_json()), Ok(project));
+    }
+
+    #[test]
+    fn a_bare_config_migrates_to_a_project_of_it() {
+        let project = parse_project(SHIPPED).unwrap();
+        assert_eq!(project.schema_version, PROJECT_SCHEMA_VERSION);
+        assert_eq!(
+            project,
+            Project::new(ProjectConfig {
+                initialization: initialization(),
+                life_stages: None,
+                skills_and_traits: None,
+            })
+        );
+    }
+
+    #[test]
+    fn projects_of_a_newer_version_are_refused() {
+        let mut project = Project::new(ProjectConfig {
+            initialization: initialization(),
+            life_stages: None,
+            skills_and_traits: None,
+        });
+        project.schema_version = PROJECT_SCHEMA_VERSION + 1;
+        let errors = parse_project(&project.to_json()).unwrap_err();
+        assert_eq!(
+            errors,
+            [ConfigError::new(
+                VERSION_KEY,
+                "the project was written by a newer version of the app, in version 2 of the \
+                 format; this one reads up to version 1"
+            )]
+        );
+        assert_eq!(
+            project_from_value(json!({"schemaVersion": "one"})).unwrap_err(),
+            [ConfigError::new(
+                VERSION_KEY,
+                "must be a whole number of at least 0"
+            )]
+        );
+    }
+
+    #[test]
+    fn problems_of_the_config_files_are_located_within_the_project() {
+        let mut initialization = initialization();
+        initialization["maxYear"] = initialization["minYear"].clone();
+        let errors = project_from_value(json!({
+            "schemaVersion": 1,
+            "config": {"initialization": initialization},
+        }))
+        .unwrap_err();
+        assert_eq!(
+            errors,
+            [ConfigError::new(
+                "config.initialization.maxYear",
+                "must be greater than minYear"
+            )]
+        );
+    }
+}
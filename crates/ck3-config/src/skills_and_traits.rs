@@ -0,0 +1,89 @@
+//! `skills_and_traits.json`: skill levels, education and personality traits.
+//!
+//! Probability tables are keyed by level as a string (`"1"`, `"2"`, ...), as
+//! in the file. `traitRules` make personality traits likelier or rarer for
+//! the characters they apply to, say every member of a dynasty born after
+//! 1100 more often zealous; only the native simulation reads them.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillsAndTraits {
+    /// Power the skill levels are raised to when weighting the education
+    /// focus; higher values favour the best skill more strongly.
+    #[serde(default = "default_education_weight_exponent")]
+    pub education_weight_exponent: f64,
+    /// Chance of each sexuality, e.g. `heterosexual`.
+    pub sexuality_distribution: BTreeMap<String, f64>,
+    /// Chance of each level, per skill.
+    pub skill_probabilities: BTreeMap<String, BTreeMap<String, f64>>,
+    /// Chance of each education tier, per skill.
+    pub education_probabilities: BTreeMap<String, BTreeMap<String, f64>>,
+    pub personality_traits: PersonalityTraits,
+    /// Rules weighting personality traits, applied in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trait_rules: Vec<TraitRule>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn default_education_weight_exponent() -> f64 {
+    1.0
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalityTraits {
+    #[serde(default = "default_traits_per_character")]
+    pub total_traits_per_character: u32,
+    /// Every other key of the object is a trait.
+    #[serde(flatten)]
+    pub traits: BTreeMap<String, PersonalityTrait>,
+}
+
+fn default_traits_per_character() -> u32 {
+    3
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PersonalityTrait {
+    pub weight: f64,
+    /// Traits a character with this one cannot also have.
+    #[serde(default)]
+    pub excludes: Vec<String>,
+}
+
+/// A rule multiplying the weight of personality traits for the characters
+/// it applies to: those matching every condition given. A list condition is
+/// met by any of its entries, except `dynastyFlags`, where every flag must
+/// be set.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraitRule {
+    /// Personality traits whose weight is multiplied.
+    pub traits: Vec<String>,
+    /// Factor the weights are multiplied by; 0 rules the traits out.
+    pub multiplier: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cultures: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub religions: Vec<String>,
+    /// Dynasty IDs; lowborn characters belong to none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dynasties: Vec<String>,
+    /// Flags of the character's dynasty, such as `forceDynastyAlive`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dynasty_flags: Vec<String>,
+    /// Traits of either parent, personality, congenital or any other.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parent_traits: Vec<String>,
+    /// First and last years of birth the rule applies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub born_from: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub born_until: Option<i32>,
+}
@@ -0,0 +1,140 @@
+//! Dynasty templates.
+//!
+//! A template is a partial dynasty: the fields that make a kind of dynasty
+//! what it is, such as its succession, marriage customs and name
+//! inheritance, without those that make it one dynasty of one project, its
+//! ID, names, progenitor's birth year, titles, conversions and wars, nor its
+//! faith and culture, whose IDs belong to the game or mod played. Applying a
+//! template to a dynasty sets the fields it has, merging objects such as
+//! `nameInheritance` key by key, and leaves the rest as they were. A few
+//! templates are bundled with the app, so that a new config need not start
+//! from nothing; users save their own from dynasties they have set up.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::ConfigError;
+use crate::initialization::Dynasty;
+
+/// Fields of a dynasty a template leaves out.
+pub const TEMPLATE_EXCLUDED_FIELDS: [&str; 10] = [
+    "dynastyID",
+    "dynastyName",
+    "dynastyMotto",
+    "isHouse",
+    "faithID",
+    "cultureID",
+    "progenitorMaleBirthYear",
+    "titles",
+    "conversions",
+    "wars",
+];
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DynastyTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// The dynasty fields it sets, as `initialization.json` writes them.
+    pub fields: Map<String, Value>,
+}
+
+/// The templates bundled with the app.
+pub fn bundled_templates() -> Vec<DynastyTemplate> {
+    serde_json::from_str(include_str!("templates.json")).expect("the bundled templates parse")
+}
+
+impl DynastyTemplate {
+    /// A template of the fields of `dynasty` that are not one dynasty's own.
+    pub fn from_dynasty(
+        name: &str,
+        description: &str,
+        dynasty: &Map<String, Value>,
+    ) -> Result<Self, Vec<ConfigError>> {
+        let template = Self {
+            name: name.trim().to_string(),
+            description: description.trim().to_string(),
+            fields: dynasty
+                .iter()
+                .filter(|(key, _)| !TEMPLATE_EXCLUDED_FIELDS.contains(&key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        };
+        template.check()?;
+        Ok(template)
+    }
+
+    /// `dynasty` with the template's fields set.
+    pub fn apply(&self, dynasty: &Map<String, Value>) -> Map<String, Value> {
+        let mut applied = dynasty.clone();
+        for (key, value) in &self.fields {
+            match (applied.get_mut(key), value) {
+                (Some(Value::Object(current)), Value::Object(fields)) => {
+                    current.extend(fields.iter().map(|(k, v)| (k.clone(), v.clone())));
+                }
+                _ => {
+                    applied.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        applied
+    }
+
+    /// Check that the template has a name, sets none of the fields it
+    /// leaves out, and makes a valid dynasty of a valid one.
+    pub fn check(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        if self.name.is_empty() {
+            errors.push(ConfigError::new("name", "is required"));
+        }
+        for key in self.fields.keys() {
+            if TEMPLATE_EXCLUDED_FIELDS.contains(&key.as_str()) {
+                errors.push(ConfigError::new(
+                    format!("fields.{key}"),
+                    "belongs to a dynasty of its own rather than a template",
+                ));
+            }
+        }
+        // The placeholder's own fields are valid, so every problem found is
+        // one of the template's.
+        let applied = Value::Object(self.apply(&placeholder()));
+        match serde_path_to_error::deserialize::<_, Dynasty>(applied) {
+            Ok(dynasty) => crate::validate::validate_dynasty(&dynasty, "fields", &mut errors),
+            Err(err) => {
+                let error = ConfigError::from_json(err);
+                errors.push(ConfigError {
+                    path: format!("fields.{}", error.path),
+                    ..error
+                });
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// A valid dynasty to apply a template to when checking it.
+fn placeholder() -> Map<String, Value> {
+    let value = serde_json::json!({
+        "dynastyID": "dynasty_template",
+        "dynastyName": "Template",
+        "succession": "PRIMOGENITURE",
+        "faithID": "faith_template",
+        "cultureID": "culture_template",
+        "gender_law": "AGNATIC",
+        "progenitorMaleBirthYear": 0,
+        "nameInheritance": {
+            "grandparentNameInheritanceChance": 0.0,
+            "parentNameInheritanceChance": 0.0,
+            "noNameInheritanceChance": 1.0
+        }
+    });
+    match value {
+        Value::Object(fields) => fields,
+        _ => unreachable!("the placeholder is an object"),
+    }
+}
@@ -0,0 +1,945 @@
+//! Semantic checks the type system cannot express.
+//!
+//! The rules mirror `ConfigLoader` in `ck3gen/config_loader.py` and the
+//! Pydantic models in `api/models.py`; keep them in step. Language entries
+//! are checked strictly here, where the engine merely skips malformed ones
+//! with a warning.
+
+use std::collections::HashSet;
+
+use crate::error::ConfigError;
+use crate::initialization::{
+    Dynasty, DynastyTitle, InitializationConfig, MarriageDoctrine, Matchmaking, ModSettings,
+    NameInheritance, NegativeEvent, NumenorBlood, DYNASTY_FLAGS,
+};
+use crate::life_stages::{Epidemic, LifeStages, Mortality, Pregnancy, SurvivalCurve};
+use crate::skills_and_traits::SkillsAndTraits;
+
+/// Tolerance for probability weights that must sum to 1.
+const SUM_TOLERANCE: f64 = 1e-6;
+/// Highest Númenórean blood tier.
+const MAX_BLOOD_TIER: u32 = 10;
+/// Highest development level a title can have in game.
+const MAX_DEVELOPMENT: u32 = 100;
+/// Entries in each rate table of `life_stages.json`, one per age from 0.
+const RATE_TABLE_LEN: usize = 121;
+
+impl InitializationConfig {
+    /// Every problem with the config, empty if it is valid.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+
+        if self.dynasties.is_empty() {
+            errors.push(ConfigError::new(
+                "dynasties",
+                "at least one dynasty is required",
+            ));
+        }
+        if self.max_year <= self.min_year {
+            errors.push(ConfigError::new("maxYear", "must be greater than minYear"));
+        }
+        if self.generation_max == 0 {
+            errors.push(ConfigError::new("generationMax", "must be greater than 0"));
+        }
+        if self.initial_char_id == 0 {
+            errors.push(ConfigError::new("initialCharID", "must be greater than 0"));
+        }
+
+        let mut ids = HashSet::new();
+        let mut titles = HashSet::new();
+        for (i, dynasty) in self.dynasties.iter().enumerate() {
+            let path = format!("dynasties[{i}]");
+            if !ids.insert(dynasty.dynasty_id.as_str()) {
+                errors.push(ConfigError::new(
+                    format!("{path}.dynastyID"),
+                    format!("duplicate dynasty ID '{}'", dynasty.dynasty_id),
+                ));
+            }
+            validate_dynasty(dynasty, &path, &mut errors);
+            for (j, title) in dynasty.titles.iter().enumerate() {
+                let path = format!("{path}.titles[{j}].id");
+                if title.id.trim().is_empty() {
+                    errors.push(ConfigError::new(path, "is required"));
+                } else if !titles.insert(title.id.as_str()) {
+                    errors.push(ConfigError::new(
+                        path,
+                        format!("title '{}' is already held by a dynasty", title.id),
+                    ));
+                }
+            }
+            validate_titles(&dynasty.titles, &path, &mut errors);
+        }
+        for (i, event) in self.events.iter().enumerate() {
+            validate_event(event, &format!("events[{i}]"), &mut errors);
+        }
+
+        if let Some(chances) = &self.numenor_inheritance {
+            for (field, chance) in [
+                ("sameTierChance", chances.same_tier_chance),
+                ("closeTierChance", chances.close_tier_chance),
+                ("farTierChance", chances.far_tier_chance),
+            ] {
+                check_probability(&format!("numenorInheritance.{field}"), chance, &mut errors);
+            }
+        }
+        if let Some(decline) = &self.numenor_decline {
+            for tier in decline.keys() {
+                let valid = tier
+                    .parse::<u32>()
+                    .is_ok_and(|tier| (1..=MAX_BLOOD_TIER).contains(&tier));
+                if !valid {
+                    errors.push(ConfigError::new(
+                        format!("numenorDecline.{tier}"),
+                        format!("blood tiers run from 1 to {MAX_BLOOD_TIER}"),
+                    ));
+                }
+            }
+        }
+        if let Some(blood) = &self.numenor_blood {
+            validate_numenor_blood(blood, &mut errors);
+        }
+        validate_matchmaking(&self.matchmaking, &ids, &mut errors);
+        for (i, bookmark) in self.coming_of_age.bookmarks.iter().enumerate() {
+            if let Err(message) = check_date(bookmark) {
+                errors.push(ConfigError::new(
+                    format!("comingOfAge.bookmarks[{i}]"),
+                    message,
+                ));
+            }
+        }
+        for (i, bookmark) in self.bookmark_snapshots.iter().enumerate() {
+            if let Err(message) = check_date(bookmark) {
+                errors.push(ConfigError::new(format!("bookmarkSnapshots[{i}]"), message));
+            }
+        }
+        let houses = &self.cadet_houses;
+        if houses.max_house_size.is_some_and(|size| size < 2) {
+            errors.push(ConfigError::new(
+                "cadetHouses.maxHouseSize",
+                "must be at least 2",
+            ));
+        }
+        check_probability(
+            "cadetHouses.youngerChildChance",
+            houses.younger_child_chance,
+            &mut errors,
+        );
+        if self
+            .portrait_genetics
+            .as_ref()
+            .is_some_and(|genetics| genetics.mutation > 255)
+        {
+            errors.push(ConfigError::new(
+                "portraitGenetics.mutation",
+                "must be at most 255",
+            ));
+        }
+        if self.calendar.year_length < 1 {
+            errors.push(ConfigError::new(
+                "calendar.yearLength",
+                "must be at least 1",
+            ));
+        }
+        if let Some(settings) = &self.mod_settings {
+            validate_mod_settings(settings, &mut errors);
+        }
+        let mut languages = HashSet::new();
+        for (i, language) in self.localization_languages.iter().enumerate() {
+            let path = format!("localizationLanguages[{i}]");
+            if language.is_empty()
+                || !language
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b == b'_')
+            {
+                errors.push(ConfigError::new(
+                    path,
+                    format!("language '{language}' must be lowercase letters and underscores"),
+                ));
+            } else if !languages.insert(language.as_str()) {
+                errors.push(ConfigError::new(
+                    path,
+                    format!("duplicate language '{language}'"),
+                ));
+            }
+        }
+        errors
+    }
+
+    /// Return the config if it is valid, or every problem with it.
+    pub(crate) fn check(self) -> Result<Self, Vec<ConfigError>> {
+        let errors = self.validate();
+        if errors.is_empty() {
+            Ok(self)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl LifeStages {
+    /// Every problem with the config, empty if it is valid.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        check_probability("bastardyChanceMale", self.bastardy_chance_male, &mut errors);
+        check_probability(
+            "bastardyChanceFemale",
+            self.bastardy_chance_female,
+            &mut errors,
+        );
+        for (table, rates) in [
+            ("mortalityRates", &self.mortality_rates),
+            ("marriageRates", &self.marriage_rates),
+            ("fertilityRates", &self.fertility_rates),
+        ] {
+            for (sex, rates) in [("Male", &rates.male), ("Female", &rates.female)] {
+                check_rate_table(&format!("{table}.{sex}"), rates, &mut errors);
+            }
+        }
+        if !self.desperation_marriage_rates.is_empty() {
+            check_rate_table(
+                "desperationMarriageRates",
+                &self.desperation_marriage_rates,
+                &mut errors,
+            );
+        }
+        for (tier, fertility) in &self.blood_tier_fertility {
+            let path = format!("bloodTierFertility.{tier}");
+            if !tier
+                .parse::<u32>()
+                .is_ok_and(|tier| (1..=MAX_BLOOD_TIER).contains(&tier))
+            {
+                errors.push(ConfigError::new(
+                    path,
+                    format!("blood tiers run from 1 to {MAX_BLOOD_TIER}"),
+                ));
+            } else {
+                check_non_negative(&format!("{path}.factor"), fertility.factor, &mut errors);
+            }
+        }
+        if let Some(mortality) = &self.mortality {
+            validate_mortality(mortality, &mut errors);
+        }
+        if let Some(pregnancy) = &self.pregnancy {
+            validate_pregnancy(self, pregnancy, &mut errors);
+        }
+        let bastardy = &self.bastardy;
+        for (field, chance) in [
+            ("legitimizationChance", bastardy.legitimization_chance),
+            ("lowbornLineChance", bastardy.lowborn_line_chance),
+        ] {
+            check_probability(&format!("bastardy.{field}"), chance, &mut errors);
+        }
+        if bastardy.legitimization_chance + bastardy.lowborn_line_chance > 1.0 {
+            errors.push(ConfigError::new(
+                "bastardy",
+                "legitimizationChance and lowbornLineChance must add up to at most 1.0",
+            ));
+        }
+        for (i, epidemic) in self.epidemics.iter().enumerate() {
+            validate_epidemic(epidemic, &format!("epidemics[{i}]"), &mut errors);
+        }
+        errors
+    }
+
+    /// Return the config if it is valid, or every problem with it.
+    pub(crate) fn check(self) -> Result<Self, Vec<ConfigError>> {
+        let errors = self.validate();
+        if errors.is_empty() {
+            Ok(self)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl SkillsAndTraits {
+    /// Every problem with the config, empty if it is valid.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        if self.education_weight_exponent < 1.0 {
+            errors.push(ConfigError::new(
+                "educationWeightExponent",
+                "must be at least 1",
+            ));
+        }
+        for (table, levels) in [
+            ("skillProbabilities", &self.skill_probabilities),
+            ("educationProbabilities", &self.education_probabilities),
+        ] {
+            for (skill, chances) in levels {
+                for level in chances.keys() {
+                    if level.parse::<u32>().is_err() {
+                        errors.push(ConfigError::new(
+                            format!("{table}.{skill}.{level}"),
+                            "levels must be whole numbers",
+                        ));
+                    }
+                }
+            }
+        }
+        for (name, traits) in &self.personality_traits.traits {
+            if traits.weight < 0.0 {
+                errors.push(ConfigError::new(
+                    format!("personalityTraits.{name}.weight"),
+                    "must not be negative",
+                ));
+            }
+        }
+        for (i, rule) in self.trait_rules.iter().enumerate() {
+            let path = format!("traitRules[{i}]");
+            if rule.traits.is_empty() {
+                errors.push(ConfigError::new(
+                    format!("{path}.traits"),
+                    "at least one trait is required",
+                ));
+            }
+            for name in &rule.traits {
+                if !self.personality_traits.traits.contains_key(name) {
+                    errors.push(ConfigError::new(
+                        format!("{path}.traits"),
+                        format!("'{name}' is not one of the personalityTraits"),
+                    ));
+                }
+            }
+            if !(rule.multiplier >= 0.0 && rule.multiplier.is_finite()) {
+                errors.push(ConfigError::new(
+                    format!("{path}.multiplier"),
+                    "must be a number of at least 0",
+                ));
+            }
+            for flag in &rule.dynasty_flags {
+                if !DYNASTY_FLAGS.contains(&flag.as_str()) {
+                    errors.push(ConfigError::new(
+                        format!("{path}.dynastyFlags"),
+                        format!(
+                            "'{flag}' is not a dynasty flag; the flags are {}",
+                            DYNASTY_FLAGS.join(", ")
+                        ),
+                    ));
+                }
+            }
+            if let (Some(from), Some(until)) = (rule.born_from, rule.born_until) {
+                if until < from {
+                    errors.push(ConfigError::new(
+                        format!("{path}.bornUntil"),
+                        "must not be before bornFrom",
+                    ));
+                }
+            }
+        }
+        errors
+    }
+
+    /// Return the config if it is valid, or every problem with it.
+    pub(crate) fn check(self) -> Result<Self, Vec<ConfigError>> {
+        let errors = self.validate();
+        if errors.is_empty() {
+            Ok(self)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+pub(crate) fn validate_dynasty(dynasty: &Dynasty, path: &str, errors: &mut Vec<ConfigError>) {
+    for (field, value) in [
+        ("dynastyID", &dynasty.dynasty_id),
+        ("dynastyName", &dynasty.dynasty_name),
+        ("faithID", &dynasty.faith_id),
+        ("cultureID", &dynasty.culture_id),
+    ] {
+        if value.trim().is_empty() {
+            errors.push(ConfigError::new(format!("{path}.{field}"), "is required"));
+        }
+    }
+    if let Some(tier) = dynasty.numenor_blood_tier {
+        if tier > MAX_BLOOD_TIER {
+            errors.push(ConfigError::new(
+                format!("{path}.numenorBloodTier"),
+                format!("must be at most {MAX_BLOOD_TIER}"),
+            ));
+        }
+    }
+    for (field, weight) in [
+        ("differentFaithWeight", dynasty.different_faith_weight),
+        ("differentCultureWeight", dynasty.different_culture_weight),
+    ] {
+        if let Some(weight) = weight {
+            check_non_negative(&format!("{path}.{field}"), weight, errors);
+        }
+    }
+    let mut spans: Vec<(i32, i32)> = Vec::new();
+    for (i, conversion) in dynasty.conversions.iter().enumerate() {
+        let path = format!("{path}.conversions[{i}]");
+        if conversion.faith_id.trim().is_empty() {
+            errors.push(ConfigError::new(format!("{path}.faithID"), "is required"));
+        }
+        if conversion.end_year < conversion.start_year {
+            errors.push(ConfigError::new(
+                format!("{path}.endYear"),
+                "must be greater than or equal to startYear",
+            ));
+        } else if spans
+            .iter()
+            .any(|&(start, end)| conversion.start_year <= end && start <= conversion.end_year)
+        {
+            errors.push(ConfigError::new(
+                path,
+                "overlaps another conversion of the dynasty",
+            ));
+        } else {
+            spans.push((conversion.start_year, conversion.end_year));
+        }
+    }
+    if let Some(doctrine) = &dynasty.marriage_doctrine {
+        validate_marriage_doctrine(doctrine, &format!("{path}.marriageDoctrine"), errors);
+    }
+    for (i, war) in dynasty.wars.iter().enumerate() {
+        let path = format!("{path}.wars[{i}]");
+        if war.end_year < war.start_year {
+            errors.push(ConfigError::new(
+                format!("{path}.endYear"),
+                "must be greater than or equal to startYear",
+            ));
+        }
+        for (field, chance) in [
+            ("battleDeathChance", war.battle_death_chance),
+            ("woundChance", war.wound_chance),
+            ("veteranChance", war.veteran_chance),
+        ] {
+            check_probability(&format!("{path}.{field}"), chance, errors);
+        }
+    }
+    for (i, spec) in dynasty.languages.iter().enumerate() {
+        if let Err(message) = check_language_spec(spec) {
+            errors.push(ConfigError::new(format!("{path}.languages[{i}]"), message));
+        }
+    }
+    validate_name_inheritance(
+        &dynasty.name_inheritance,
+        &format!("{path}.nameInheritance"),
+        errors,
+    );
+}
+
+fn validate_titles(titles: &[DynastyTitle], path: &str, errors: &mut Vec<ConfigError>) {
+    for (i, title) in titles.iter().enumerate() {
+        let path = format!("{path}.titles[{i}]");
+        if let Some(liege) = &title.liege {
+            let outranks = match (title_tier(liege), title_tier(&title.id)) {
+                (Some(liege), Some(vassal)) => liege > vassal,
+                _ => true,
+            };
+            if !outranks {
+                errors.push(ConfigError::new(
+                    format!("{path}.liege"),
+                    format!("'{liege}' does not outrank '{}'", title.id),
+                ));
+            }
+        }
+        if title
+            .development
+            .is_some_and(|level| level > MAX_DEVELOPMENT)
+        {
+            errors.push(ConfigError::new(
+                format!("{path}.development"),
+                format!("must be at most {MAX_DEVELOPMENT}"),
+            ));
+        }
+    }
+}
+
+/// The rank of a title by the tier prefix of its key, from baronies up to
+/// empires.
+fn title_tier(key: &str) -> Option<usize> {
+    let (prefix, _) = key.split_once('_')?;
+    ["b", "c", "d", "k", "e"]
+        .iter()
+        .position(|tier| *tier == prefix)
+}
+
+fn validate_name_inheritance(chances: &NameInheritance, path: &str, errors: &mut Vec<ConfigError>) {
+    let fields = [
+        (
+            "grandparentNameInheritanceChance",
+            chances.grandparent_name_inheritance_chance,
+        ),
+        (
+            "parentNameInheritanceChance",
+            chances.parent_name_inheritance_chance,
+        ),
+        (
+            "noNameInheritanceChance",
+            chances.no_name_inheritance_chance,
+        ),
+    ];
+    for (field, chance) in fields {
+        check_probability(&format!("{path}.{field}"), chance, errors);
+    }
+    let total: f64 = fields.iter().map(|(_, chance)| chance).sum();
+    if (total - 1.0).abs() >= SUM_TOLERANCE {
+        errors.push(ConfigError::new(
+            path,
+            format!("chances must sum to 1.0 (got {total:.6})"),
+        ));
+    }
+}
+
+fn validate_event(event: &NegativeEvent, path: &str, errors: &mut Vec<ConfigError>) {
+    if event.event_id.trim().is_empty() {
+        errors.push(ConfigError::new(format!("{path}.eventID"), "is required"));
+    }
+    if event.death_reason.trim().is_empty() {
+        errors.push(ConfigError::new(
+            format!("{path}.deathReason"),
+            "is required",
+        ));
+    }
+    if event.death_multiplier <= 0.0 {
+        errors.push(ConfigError::new(
+            format!("{path}.deathMultiplier"),
+            "must be greater than 0",
+        ));
+    }
+    if event.end_year < event.start_year {
+        errors.push(ConfigError::new(
+            format!("{path}.endYear"),
+            "must be greater than or equal to startYear",
+        ));
+    }
+    if event.character_age_end < event.character_age_start {
+        errors.push(ConfigError::new(
+            format!("{path}.characterAgeEnd"),
+            "must be greater than or equal to characterAgeStart",
+        ));
+    }
+}
+
+fn validate_matchmaking(
+    matchmaking: &Matchmaking,
+    dynasties: &HashSet<&str>,
+    errors: &mut Vec<ConfigError>,
+) {
+    for (field, weight) in [
+        ("differentFaithWeight", matchmaking.different_faith_weight),
+        (
+            "differentCultureWeight",
+            matchmaking.different_culture_weight,
+        ),
+        ("allianceWeight", matchmaking.alliance_weight),
+    ] {
+        check_non_negative(&format!("matchmaking.{field}"), weight, errors);
+    }
+    check_probability(
+        "matchmaking.maxRelatedness",
+        matchmaking.max_relatedness,
+        errors,
+    );
+    if matchmaking.consanguinity_generations == 0 {
+        errors.push(ConfigError::new(
+            "matchmaking.consanguinityGenerations",
+            "must be greater than 0",
+        ));
+    }
+    for (dynasty, relations) in &matchmaking.dynasty_relations {
+        for (other, &weight) in relations {
+            let path = format!("matchmaking.dynastyRelations.{dynasty}.{other}");
+            for id in [dynasty, other] {
+                if !dynasties.contains(id.as_str()) {
+                    errors.push(ConfigError::new(
+                        path.clone(),
+                        format!("unknown dynasty '{id}'"),
+                    ));
+                }
+            }
+            check_non_negative(&path, weight, errors);
+        }
+    }
+    for (faith, doctrine) in &matchmaking.marriage_doctrines {
+        validate_marriage_doctrine(
+            doctrine,
+            &format!("matchmaking.marriageDoctrines.{faith}"),
+            errors,
+        );
+    }
+}
+
+fn validate_marriage_doctrine(
+    doctrine: &MarriageDoctrine,
+    path: &str,
+    errors: &mut Vec<ConfigError>,
+) {
+    if doctrine.max_spouses == 0 {
+        errors.push(ConfigError::new(
+            format!("{path}.maxSpouses"),
+            "must be at least 1",
+        ));
+    }
+    check_probability(
+        &format!("{path}.consortChance"),
+        doctrine.consort_chance,
+        errors,
+    );
+}
+
+fn validate_mortality(mortality: &Mortality, errors: &mut Vec<ConfigError>) {
+    for (i, era) in mortality.eras.iter().enumerate() {
+        let path = format!("mortality.eras[{i}]");
+        if era.end_year < era.start_year {
+            errors.push(ConfigError::new(
+                format!("{path}.endYear"),
+                "must be greater than or equal to startYear",
+            ));
+        }
+        check_non_negative(&format!("{path}.multiplier"), era.multiplier, errors);
+        if let Some(curve) = &era.curve {
+            for (sex, curve) in [("Male", &curve.male), ("Female", &curve.female)] {
+                validate_curve(curve, &format!("{path}.curve.{sex}"), errors);
+            }
+        }
+    }
+    for (name, &multiplier) in &mortality.trait_multipliers {
+        check_non_negative(
+            &format!("mortality.traitMultipliers.{name}"),
+            multiplier,
+            errors,
+        );
+    }
+    if mortality.max_lifespan == 0 {
+        errors.push(ConfigError::new(
+            "mortality.maxLifespan",
+            "must be greater than 0",
+        ));
+    }
+    for (tier, &lifespan) in &mortality.blood_tier_max_lifespan {
+        let path = format!("mortality.bloodTierMaxLifespan.{tier}");
+        let valid = tier
+            .parse::<u32>()
+            .is_ok_and(|tier| (1..=MAX_BLOOD_TIER).contains(&tier));
+        if !valid {
+            errors.push(ConfigError::new(
+                path,
+                format!("blood tiers run from 1 to {MAX_BLOOD_TIER}"),
+            ));
+        } else if lifespan == 0 {
+            errors.push(ConfigError::new(path, "must be greater than 0"));
+        }
+    }
+}
+
+fn validate_pregnancy(stages: &LifeStages, pregnancy: &Pregnancy, errors: &mut Vec<ConfigError>) {
+    if pregnancy.max_maternal_age < pregnancy.min_maternal_age {
+        errors.push(ConfigError::new(
+            "pregnancy.maxMaternalAge",
+            "must be greater than or equal to minMaternalAge",
+        ));
+    }
+    if pregnancy.gestation_months == 0 {
+        errors.push(ConfigError::new(
+            "pregnancy.gestationMonths",
+            "must be greater than 0",
+        ));
+    }
+    for (tier, &onset) in &pregnancy.blood_tier_fertility_onset {
+        let path = format!("pregnancy.bloodTierFertilityOnset.{tier}");
+        match tier.parse::<u32>() {
+            Ok(tier) if (1..=MAX_BLOOD_TIER).contains(&tier) => {
+                if onset > pregnancy.max_maternal_age + stages.blood_fertility(tier).peak_years {
+                    errors.push(ConfigError::new(
+                        path,
+                        "must not be later than the tier's maximum maternal age",
+                    ));
+                }
+            }
+            _ => errors.push(ConfigError::new(
+                path,
+                format!("blood tiers run from 1 to {MAX_BLOOD_TIER}"),
+            )),
+        }
+    }
+}
+
+fn validate_numenor_blood(blood: &NumenorBlood, errors: &mut Vec<ConfigError>) {
+    for (tier, &threshold) in &blood.tier_thresholds {
+        let path = format!("numenorBlood.tierThresholds.{tier}");
+        let Some(tier) = tier
+            .parse::<u32>()
+            .ok()
+            .filter(|tier| (1..=MAX_BLOOD_TIER).contains(tier))
+        else {
+            errors.push(ConfigError::new(
+                path,
+                format!("blood tiers run from 1 to {MAX_BLOOD_TIER}"),
+            ));
+            continue;
+        };
+        if !(threshold > 0.0 && threshold <= 1.0) {
+            errors.push(ConfigError::new(path, "must be above 0 and at most 1"));
+        } else if threshold <= blood.threshold(tier - 1) {
+            errors.push(ConfigError::new(
+                path,
+                "must be above the threshold of the tier below",
+            ));
+        }
+    }
+    check_probability("numenorBlood.drift", blood.drift, errors);
+}
+
+fn validate_epidemic(epidemic: &Epidemic, path: &str, errors: &mut Vec<ConfigError>) {
+    if epidemic.end_year < epidemic.start_year {
+        errors.push(ConfigError::new(
+            format!("{path}.endYear"),
+            "must be greater than or equal to startYear",
+        ));
+    }
+    if epidemic.death_reason.trim().is_empty() {
+        errors.push(ConfigError::new(
+            format!("{path}.deathReason"),
+            "must not be empty",
+        ));
+    }
+    for (i, band) in epidemic.lethality.iter().enumerate() {
+        let path = format!("{path}.lethality[{i}]");
+        if band.to_age < band.from_age {
+            errors.push(ConfigError::new(
+                format!("{path}.toAge"),
+                "must be greater than or equal to fromAge",
+            ));
+        }
+        check_non_negative(&format!("{path}.multiplier"), band.multiplier, errors);
+    }
+}
+
+fn validate_curve(curve: &SurvivalCurve, path: &str, errors: &mut Vec<ConfigError>) {
+    for (field, value) in [
+        ("infant", curve.infant),
+        ("infantDecay", curve.infant_decay),
+        ("background", curve.background),
+        ("aging", curve.aging),
+        ("agingRate", curve.aging_rate),
+    ] {
+        check_non_negative(&format!("{path}.{field}"), value, errors);
+    }
+}
+
+fn check_non_negative(path: &str, value: f64, errors: &mut Vec<ConfigError>) {
+    if !(value >= 0.0 && value.is_finite()) {
+        errors.push(ConfigError::new(path, "must be a non-negative number"));
+    }
+}
+
+fn validate_mod_settings(settings: &ModSettings, errors: &mut Vec<ConfigError>) {
+    // The launcher reads descriptor values as quoted strings, without escapes.
+    let mut fields = vec![
+        ("name".to_string(), &settings.name),
+        ("version".to_string(), &settings.version),
+        ("supportedVersion".to_string(), &settings.supported_version),
+    ];
+    fields.extend(
+        settings
+            .tags
+            .iter()
+            .enumerate()
+            .map(|(i, tag)| (format!("tags[{i}]"), tag)),
+    );
+    for (field, value) in fields {
+        let path = format!("modSettings.{field}");
+        if value.trim().is_empty() {
+            errors.push(ConfigError::new(path, "must not be empty"));
+        } else if value.contains(['"', '\n']) {
+            errors.push(ConfigError::new(
+                path,
+                "must not contain quotes or line breaks",
+            ));
+        }
+    }
+}
+
+fn check_probability(path: &str, value: f64, errors: &mut Vec<ConfigError>) {
+    if !(0.0..=1.0).contains(&value) {
+        errors.push(ConfigError::new(path, "must be between 0.0 and 1.0"));
+    }
+}
+
+fn check_rate_table(path: &str, rates: &[f64], errors: &mut Vec<ConfigError>) {
+    if rates.len() != RATE_TABLE_LEN {
+        errors.push(ConfigError::new(
+            path,
+            format!(
+                "must have exactly {RATE_TABLE_LEN} entries (ages 0-120); got {}",
+                rates.len()
+            ),
+        ));
+    }
+    for (age, &rate) in rates.iter().enumerate() {
+        check_probability(&format!("{path}[{age}]"), rate, errors);
+    }
+}
+
+/// Check a `year.month.day` date.
+fn check_date(date: &str) -> Result<(), String> {
+    let parts: Vec<&str> = date.trim().split('.').collect();
+    let [year, month, day] = parts[..] else {
+        return Err("expected 'year.month.day'".into());
+    };
+    let (Ok(_), Ok(month), Ok(day)) = (year.parse::<i32>(), month.parse::<u8>(), day.parse::<u8>())
+    else {
+        return Err("year, month and day must be whole numbers".into());
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err("month must be 1-12 and day 1-31".into());
+    }
+    Ok(())
+}
+
+/// Check a `"language_id,start_year,end_year"` entry.
+fn check_language_spec(spec: &str) -> Result<(), String> {
+    let parts: Vec<&str> = spec.split(',').map(str::trim).collect();
+    let [language, start, end] = parts[..] else {
+        return Err("expected 'language_id,start_year,end_year'".into());
+    };
+    if language.is_empty() {
+        return Err("language ID is missing".into());
+    }
+    let (Ok(start), Ok(end)) = (start.parse::<i32>(), end.parse::<i32>()) else {
+        return Err("start and end years must be whole numbers".into());
+    };
+    if end < start {
+        return Err("end year must not be before start year".into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{initialization_from_value, parse_initialization};
+
+    /// The `initialization.json` the engine ships with.
+    const SHIPPED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../config/initialization.json"
+    ));
+
+    /// The problems of the shipped config once `edit` has changed it.
+    fn errors(edit: impl FnOnce(&mut Value)) -> Vec<ConfigError> {
+        let mut config: Value = serde_json::from_str(SHIPPED).unwrap();
+        edit(&mut config);
+        initialization_from_value(config).err().unwrap_or_default()
+    }
+
+    fn error(path: &str, message: &str) -> ConfigError {
+        ConfigError::new(path, message)
+    }
+
+    #[test]
+    fn the_shipped_config_is_valid() {
+        assert_eq!(parse_initialization(SHIPPED).err(), None);
+    }
+
+    #[test]
+    fn values_of_the_wrong_type_are_reported_where_they_are() {
+        assert_eq!(
+            errors(|config| config["dynasties"][0]["dynastyName"] = json!(5)),
+            [error(
+                "dynasties[0].dynastyName",
+                "invalid type: integer `5`, expected a string"
+            )]
+        );
+        assert_eq!(
+            errors(|config| config["maxYear"] = json!("late")),
+            [error(
+                "maxYear",
+                "invalid type: string \"late\", expected i32"
+            )]
+        );
+    }
+
+    #[test]
+    fn syntax_errors_give_the_line_and_column() {
+        let errors = parse_initialization("{\n  \"minYear\": 6000,\n  \"maxYear\": }").unwrap_err();
+        assert_eq!(
+            errors,
+            [ConfigError {
+                path: "maxYear".into(),
+                message: "expected value".into(),
+                line: Some(3),
+                column: Some(14),
+            }]
+        );
+    }
+
+    #[test]
+    fn out_of_range_values_are_reported_where_they_are() {
+        assert_eq!(
+            errors(|config| config["dynasties"][0]["numenorBloodTier"] = json!(11)),
+            [error("dynasties[0].numenorBloodTier", "must be at most 10")]
+        );
+        assert_eq!(
+            errors(|config| {
+                config["dynasties"][1]["nameInheritance"] = json!({
+                    "grandparentNameInheritanceChance": 0.25,
+                    "parentNameInheritanceChance": 0.25,
+                    "noNameInheritanceChance": 1.5,
+                })
+            }),
+            [
+                error(
+                    "dynasties[1].nameInheritance.noNameInheritanceChance",
+                    "must be between 0.0 and 1.0"
+                ),
+                error(
+                    "dynasties[1].nameInheritance",
+                    "chances must sum to 1.0 (got 2.000000)"
+                ),
+            ]
+        );
+        assert_eq!(
+            errors(|config| config["maxYear"] = config["minYear"].clone()),
+            [error("maxYear", "must be greater than minYear")]
+        );
+        assert_eq!(
+            errors(|config| config["numenorDecline"] = json!({"0": 1, "11": 1})),
+            [
+                error("numenorDecline.0", "blood tiers run from 1 to 10"),
+                error("numenorDecline.11", "blood tiers run from 1 to 10"),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_dates_are_reported_where_they_are() {
+        let bookmarks = errors(|config| {
+            config["comingOfAge"] = json!({
+                "bookmarks": ["7000.1.1", "7000.13.1", "7000.1.32", "7000.1", "7000.a.1"]
+            });
+        });
+        assert_eq!(
+            bookmarks,
+            [
+                error(
+                    "comingOfAge.bookmarks[1]",
+                    "month must be 1-12 and day 1-31"
+                ),
+                error(
+                    "comingOfAge.bookmarks[2]",
+                    "month must be 1-12 and day 1-31"
+                ),
+                error("comingOfAge.bookmarks[3]", "expected 'year.month.day'"),
+                error(
+                    "comingOfAge.bookmarks[4]",
+                    "year, month and day must be whole numbers"
+                ),
+            ]
+        );
+        assert_eq!(
+            errors(|config| config["bookmarkSnapshots"] = json!(["867.1.1", "1066.0.15"])),
+            [error(
+                "bookmarkSnapshots[1]",
+                "month must be 1-12 and day 1-31"
+            )]
+        );
+    }
+}
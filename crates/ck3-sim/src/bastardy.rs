@@ -0,0 +1,68 @@
+//! What becomes of bastards.
+//!
+//! Bastards are born with the `bastard` trait and, without a `bastardy`
+//! section, into their parent's dynasty. With one, some are set to be
+//! legitimized on a birthday no later than the configured age, when they swap
+//! the trait for `legitimized_bastard` and count as legitimate children of
+//! their line from then on; others are born outside the dynasty to found
+//! lowborn lines, and the children they have are lowborn too.
+
+use rand::Rng;
+
+use crate::character::{CharacterId, EventKind};
+use crate::simulation::Simulation;
+
+/// What a bastard grows up to be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BastardFate {
+    /// A bastard of their parent's dynasty all their life.
+    Bastard,
+    Legitimized,
+    /// Outside the dynasty, the founder of a lowborn line.
+    LowbornLine,
+}
+
+impl Simulation {
+    /// Roll the fate of a new bastard.
+    pub(crate) fn bastard_fate(&mut self) -> BastardFate {
+        let bastardy = &self.config.life_stages.bastardy;
+        let (legitimized, lowborn) = (bastardy.legitimization_chance, bastardy.lowborn_line_chance);
+        if legitimized + lowborn <= 0.0 {
+            return BastardFate::Bastard;
+        }
+        let roll = self.rng.random::<f64>();
+        if roll < legitimized {
+            BastardFate::Legitimized
+        } else if roll < legitimized + lowborn {
+            BastardFate::LowbornLine
+        } else {
+            BastardFate::Bastard
+        }
+    }
+
+    /// Set the birthday on which bastard `id` is legitimized.
+    pub(crate) fn schedule_legitimization(&mut self, id: CharacterId) {
+        let max_age = self.config.life_stages.bastardy.max_legitimization_age as i32;
+        let age = self.rng.random_range(0..=max_age);
+        let character = self.character_mut(id);
+        character.legitimized_on = Some(character.birth.years_later(age));
+    }
+
+    /// Legitimize the bastards whose day has come by the end of `year`, if
+    /// they lived to see it.
+    pub(crate) fn legitimize_bastards(&mut self, year: i32) {
+        for character in &mut self.characters {
+            if character.legitimized_on.is_none_or(|date| date.year > year) {
+                continue;
+            }
+            let Some(date) = character.legitimized_on.take() else {
+                continue;
+            };
+            if character.death.is_some_and(|death| death < date) {
+                continue;
+            }
+            character.is_bastard = false;
+            character.add_event(date, EventKind::Legitimization);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//! Snapshots of a history on its bookmark dates.
+//!
+//! A game started from a bookmark shows the characters alive on its date as
+//! the history has them then, not as they end up. [`output_snapshot`] reads
+//! an output directory, whichever engine wrote it, and gives a Markdown page
+//! of everyone living on a date, by dynasty: their age, the titles they hold,
+//! their spouses and the traits they have by then, childhood traits only for
+//! those still children. [`write_snapshots`] writes one for each date of the
+//! config's `bookmarkSnapshots` into the `bookmarks` folder of the output,
+//! so that every bookmark can be reviewed on its own.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use paradox_script::Date;
+
+use crate::character::CHILDHOOD_TRAITS;
+use crate::error::SimError;
+use crate::export::{history_seed, HISTORY_FILE};
+use crate::localization::{dynasty_key, house_key, read_names};
+use crate::people::{read_house_dynasties, read_people, read_titles, Holders, Person};
+
+/// Folder of the snapshots, in the output directory.
+pub const SNAPSHOTS_DIR: &str = "bookmarks";
+/// Extension of a snapshot file, named after its date.
+const SNAPSHOT_EXTENSION: &str = "md";
+/// Age from which childhood traits no longer show.
+const ADULT_AGE: i32 = 16;
+/// Heading of the section of characters of no dynasty.
+const LOWBORN_HEADING: &str = "Lowborn";
+
+/// Write a snapshot of the history of the output directory `dir` on each of
+/// the `bookmarks`, as `year.month.day`, to its `bookmarks` folder,
+/// replacing the snapshots written before.
+pub fn write_snapshots(dir: &Path, bookmarks: &[String]) -> Result<(), SimError> {
+    let folder = dir.join(SNAPSHOTS_DIR);
+    // Left in place, the snapshot of a date no longer configured would pass
+    // for one of this history.
+    if let Ok(entries) = fs::read_dir(&folder) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path
+                .extension()
+                .is_some_and(|ext| ext == SNAPSHOT_EXTENSION)
+            {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+    let dates: Vec<Date> = bookmarks
+        .iter()
+        .filter_map(|bookmark| Date::parse(bookmark.trim()))
+        .collect();
+    if dates.is_empty() {
+        return Ok(());
+    }
+    let people = read_people(dir)?;
+    let history = History::read(dir, &people);
+    fs::create_dir_all(&folder).map_err(|source| SimError::Io {
+        path: folder.clone(),
+        source,
+    })?;
+    for date in dates {
+        let path = folder.join(format!("{date}.{SNAPSHOT_EXTENSION}"));
+        fs::write(&path, history.snapshot(date)).map_err(|source| SimError::Io { path, source })?;
+    }
+    Ok(())
+}
+
+/// The Markdown snapshot of the characters of the history of the output
+/// directory `dir` living on `date`.
+pub fn output_snapshot(dir: &Path, date: Date) -> Result<String, SimError> {
+    let people = read_people(dir)?;
+    Ok(History::read(dir, &people).snapshot(date))
+}
+
+/// A marriage, from the day it was made until the day it was dissolved,
+/// if it was.
+struct Marriage {
+    from: Date,
+    until: Option<Date>,
+}
+
+struct History<'a> {
+    people: &'a [Person],
+    seed: Option<u64>,
+    names: HashMap<String, String>,
+    house_dynasties: HashMap<String, String>,
+    titles: Vec<(String, Holders)>,
+    /// Each marriage, by the indices of both spouses, the lower first.
+    marriages: BTreeMap<(usize, usize), Marriage>,
+}
+
+impl<'a> History<'a> {
+    fn read(dir: &Path, people: &'a [Person]) -> Self {
+        let index: HashMap<&str, usize> = people
+            .iter()
+            .enumerate()
+            .map(|(i, person)| (person.id.as_str(), i))
+            .collect();
+        let mut marriages = BTreeMap::new();
+        for (i, person) in people.iter().enumerate() {
+            // Either side of a marriage may record it, or its end.
+            for (date, spouse) in &person.marriages {
+                if let Some(&spouse) = index.get(spouse.as_str()) {
+                    marriages
+                        .entry((i.min(spouse), i.max(spouse)))
+                        .or_insert(Marriage {
+                            from: *date,
+                            until: None,
+                        });
+                }
+            }
+        }
+        for (i, person) in people.iter().enumerate() {
+            for (date, key, spouse) in &person.events {
+                let Some(&spouse) = index.get(spouse.as_str()) else {
+                    continue;
+                };
+                if key == "remove_spouse" {
+                    if let Some(marriage) = marriages.get_mut(&(i.min(spouse), i.max(spouse))) {
+                        marriage.until.get_or_insert(*date);
+                    }
+                }
+            }
+        }
+        let seed = fs::read(dir.join(HISTORY_FILE))
+            .ok()
+            .and_then(|history| history_seed(&String::from_utf8_lossy(&history)));
+        Self {
+            people,
+            seed,
+            names: read_names(dir),
+            house_dynasties: read_house_dynasties(dir),
+            titles: read_titles(dir),
+            marriages,
+        }
+    }
+
+    fn snapshot(&self, date: Date) -> String {
+        let living: Vec<usize> = (0..self.people.len())
+            .filter(|&i| self.is_alive(i, date))
+            .collect();
+        let mut titles: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (title, holders) in &self.titles {
+            let holder = holders
+                .iter()
+                .take_while(|(from, _)| *from <= date)
+                .last()
+                .and_then(|(_, holder)| holder.as_deref());
+            if let Some(holder) = holder {
+                titles.entry(holder).or_default().push(title);
+            }
+        }
+
+        // Dynasties in the order the history first mentions them, those of
+        // no dynasty last.
+        let mut groups: Vec<(Option<&str>, Vec<usize>)> = Vec::new();
+        for &i in &living {
+            let lineage = self.people[i].lineage(&self.house_dynasties);
+            match groups.iter_mut().find(|(other, _)| *other == lineage) {
+                Some((_, members)) => members.push(i),
+                None => groups.push((lineage, vec![i])),
+            }
+        }
+        groups.sort_by_key(|(lineage, _)| lineage.is_none());
+
+        let mut out = format!("# Bookmark {date}\n\n");
+        let count = match living.len() {
+            1 => "1 character living".to_string(),
+            n => format!("{n} characters living"),
+        };
+        out.push_str(&match self.seed {
+            Some(seed) => format!("*{count} in the history of seed {seed}.*\n"),
+            None => format!("*{count}.*\n"),
+        });
+        for (lineage, mut members) in groups {
+            let heading = match lineage {
+                Some(dynasty) => {
+                    let name = self
+                        .names
+                        .get(&dynasty_key(dynasty))
+                        .or_else(|| self.names.get(&house_key(dynasty)));
+                    match name {
+                        Some(name) => format!("House {name} ({dynasty})"),
+                        None => format!("House {dynasty}"),
+                    }
+                }
+                None => LOWBORN_HEADING.to_string(),
+            };
+            out.push_str(&format!("\n## {heading}\n\n"));
+            out.push_str("| Character | Born | Age | Titles | Spouses | Traits |\n");
+            out.push_str("| --- | --- | --- | --- | --- | --- |\n");
+            members.sort_by_key(|&i| self.people[i].birth);
+            for i in members {
+                let person = &self.people[i];
+                let born = person.birth.unwrap_or(date);
+                let age = age(born, date);
+                let held = titles.get(person.id.as_str()).map(Vec::as_slice);
+                let spouses: Vec<String> = self
+                    .spouses(i, date)
+                    .map(|spouse| self.label(spouse))
+                    .collect();
+                let traits = traits_on(person, date, age < ADULT_AGE);
+                out.push_str(&format!(
+                    "| {} | {born} | {age} | {} | {} | {} |\n",
+                    self.label(i),
+                    held.map_or(String::new(), |titles| titles.join(", ")),
+                    spouses.join(", "),
+                    traits.join(", "),
+                ));
+            }
+        }
+        out
+    }
+
+    /// Whether character `i` is born by `date` and not yet dead.
+    fn is_alive(&self, i: usize, date: Date) -> bool {
+        let person = &self.people[i];
+        person.birth.is_some_and(|birth| birth <= date)
+            && person.death.is_none_or(|death| date < death)
+    }
+
+    /// The living spouses character `i` is married to on `date`.
+    fn spouses(&self, i: usize, date: Date) -> impl Iterator<Item = usize> + '_ {
+        self.marriages
+            .iter()
+            .filter(move |(_, marriage)| {
+                marriage.from <= date && marriage.until.is_none_or(|until| date < until)
+            })
+            .filter_map(move |(&(a, b), _)| match i {
+                _ if i == a => Some(b),
+                _ if i == b => Some(a),
+                _ => None,
+            })
+            .filter(move |&spouse| self.is_alive(spouse, date))
+    }
+
+    /// Character `i` by name and ID.
+    fn label(&self, i: usize) -> String {
+        let person = &self.people[i];
+        format!("{} (`{}`)", person.name, person.id)
+    }
+}
+
+/// Age on `date` of someone born on `birth`, in the years of the history.
+fn age(birth: Date, date: Date) -> i32 {
+    let years = date.year - birth.year;
+    if (date.month, date.day) < (birth.month, birth.day) {
+        years - 1
+    } else {
+        years
+    }
+}
+
+/// The traits `person` has on `date`, in the order they gained them;
+/// childhood traits only if they are still a `child`.
+fn traits_on(person: &Person, date: Date, child: bool) -> Vec<String> {
+    // What the dated blocks give is listed after the traits held from
+    // birth, once for every time it is given.
+    let mut held = person.traits.clone();
+    for (_, key, name) in &person.events {
+        if key == "trait" {
+            if let Some(position) = held.iter().rposition(|other| other == name) {
+                held.remove(position);
+            }
+        }
+    }
+    let mut changes: Vec<&(Date, String, String)> = person
+        .events
+        .iter()
+        .filter(|(on, _, _)| *on <= date)
+        .collect();
+    changes.sort_by_key(|(on, _, _)| *on);
+    for (_, key, name) in changes {
+        match key.as_str() {
+            "trait" | "add_trait" if !held.contains(name) => held.push(name.clone()),
+            "remove_trait" | "make_trait_inactive" => held.retain(|other| other != name),
+            _ => {}
+        }
+    }
+    if !child {
+        held.retain(|name| !CHILDHOOD_TRAITS.contains(&name.as_str()));
+    }
+    held
+}
@@ -0,0 +1,556 @@
+//! Characters and the traits they are born or grow up with.
+
+use std::collections::{BTreeMap, HashMap};
+
+use ck3_config::{GenderLaw, NumenorBlood, NumenorInheritance, SkillsAndTraits};
+use rand::Rng;
+use serde::Serialize;
+
+use crate::config::SimConfig;
+use crate::date::Date;
+use crate::dna::Dna;
+use crate::random;
+
+/// Index of a character in its [`Simulation`](crate::Simulation).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CharacterId(pub(crate) usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum Sex {
+    Male,
+    Female,
+}
+
+impl Sex {
+    pub fn opposite(self) -> Sex {
+        match self {
+            Sex::Male => Sex::Female,
+            Sex::Female => Sex::Male,
+        }
+    }
+
+    /// The sex with the given [`key`](Sex::key).
+    pub fn from_key(key: &str) -> Option<Sex> {
+        match key {
+            "male" => Some(Sex::Male),
+            "female" => Some(Sex::Female),
+            _ => None,
+        }
+    }
+
+    /// `male` or `female`, as in name list file names.
+    pub fn key(self) -> &'static str {
+        match self {
+            Sex::Male => "male",
+            Sex::Female => "female",
+        }
+    }
+}
+
+/// The skills every character has, in the order they are written.
+pub const SKILLS: [&str; 6] = [
+    "diplomacy",
+    "martial",
+    "stewardship",
+    "intrigue",
+    "learning",
+    "prowess",
+];
+
+/// Childhood traits, which only show while a character is under 16.
+pub const CHILDHOOD_TRAITS: [&str; 5] = ["charming", "curious", "rowdy", "bossy", "pensive"];
+
+/// The education a character received.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Education {
+    /// One of [`SKILLS`].
+    pub skill: &'static str,
+    pub tier: u32,
+}
+
+impl Education {
+    /// The matching `education_<skill>_<tier>` trait. Prowess has no
+    /// education of its own and counts as martial.
+    pub fn trait_name(&self) -> String {
+        let skill = if self.skill == "prowess" {
+            "martial"
+        } else {
+            self.skill
+        };
+        format!("education_{skill}_{}", self.tier)
+    }
+
+    /// The childhood traits a child with this education may show.
+    pub fn childhood_traits(&self) -> [&'static str; 2] {
+        match self.skill {
+            "intrigue" => ["charming", "rowdy"],
+            "martial" => ["rowdy", "bossy"],
+            "stewardship" => ["pensive", "bossy"],
+            "learning" => ["pensive", "curious"],
+            _ => ["charming", "curious"],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub date: Date,
+    pub kind: EventKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventKind {
+    Birth,
+    /// Traits gained on the day, childhood or personality.
+    Traits(Vec<String>),
+    /// Recorded on the spouse who married the other into their family.
+    Marriage {
+        spouse: CharacterId,
+        matrilineal: bool,
+    },
+    /// Recorded on the man who took the concubine.
+    Concubinage {
+        concubine: CharacterId,
+    },
+    Death {
+        reason: String,
+    },
+    /// Taken in as the heir of a dynasty about to die out.
+    Adoption {
+        adopter: Option<CharacterId>,
+    },
+    LearnLanguage {
+        language: String,
+    },
+    /// A bastard made legitimate.
+    Legitimization,
+    /// Converted from the faith `from` to `to`.
+    Conversion {
+        from: String,
+        to: String,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct Character {
+    /// History file ID, e.g. `lineofbaggins12`.
+    pub id: String,
+    pub name: String,
+    pub sex: Sex,
+    pub birth: Date,
+    pub death: Option<Date>,
+    /// Age in the year being simulated.
+    pub age: i32,
+    /// First living wife for a man with several, the husband for each of
+    /// his wives and concubines.
+    pub spouse: Option<CharacterId>,
+    /// Living wives of a man besides [`spouse`](Character::spouse).
+    pub extra_spouses: Vec<CharacterId>,
+    /// Living concubines of a man.
+    pub concubines: Vec<CharacterId>,
+    pub father: Option<CharacterId>,
+    pub mother: Option<CharacterId>,
+    pub children: Vec<CharacterId>,
+    /// Dynasty ID, `None` for lowborn characters.
+    pub dynasty: Option<String>,
+    pub is_house: bool,
+    /// Cadet house ID, `None` for the dynasty's main house.
+    pub house: Option<String>,
+    pub culture: String,
+    pub religion: String,
+    pub gender_law: GenderLaw,
+    /// 1 for progenitors, one more than the elder parent for children.
+    pub generation: u32,
+    pub is_progenitor: bool,
+    pub is_bastard: bool,
+    /// Day a bastard is yet to be legitimized on, if they are to be.
+    pub legitimized_on: Option<Date>,
+    pub is_adopted: bool,
+    /// 1-based position among the children of the mother.
+    pub birth_order: u32,
+    /// Levels of [`SKILLS`], in order.
+    pub skills: [u32; 6],
+    pub education: Option<Education>,
+    pub traits: Vec<String>,
+    pub personality_traits: Vec<String>,
+    pub congenital_traits: Vec<&'static str>,
+    /// Genetic traits carried without showing, which children can inherit.
+    pub inactive_traits: Vec<&'static str>,
+    pub sexuality: String,
+    pub fertility_modifier: f64,
+    /// Númenórean blood tier, 0 for none.
+    pub blood_tier: u32,
+    /// Portrait genes, if the config passes them down.
+    pub dna: Option<Dna>,
+    pub events: Vec<Event>,
+    /// Reason given by a negative event that raised this year's death chance.
+    pub(crate) event_death_reason: Option<String>,
+}
+
+/// What a new character starts out as; the rest is rolled on creation.
+pub(crate) struct Birth {
+    pub id: String,
+    pub name: String,
+    pub sex: Sex,
+    pub birth_year: i32,
+    /// Day of birth when it is already settled, else a random day of
+    /// `birth_year`.
+    pub birth_date: Option<Date>,
+    pub dynasty: Option<String>,
+    pub is_house: bool,
+    pub house: Option<String>,
+    pub culture: String,
+    pub religion: String,
+    pub gender_law: GenderLaw,
+    pub generation: u32,
+    pub birth_order: u32,
+    pub blood_tier: u32,
+    pub fertility_modifier: f64,
+}
+
+impl Character {
+    /// A newborn with a random birthday and sexuality.
+    pub(crate) fn born(birth: Birth, skills: &SkillsAndTraits, rng: &mut impl Rng) -> Self {
+        let date = birth
+            .birth_date
+            .unwrap_or_else(|| Date::random_in(birth.birth_year, rng));
+        let sexuality = random::weighted(
+            rng,
+            skills
+                .sexuality_distribution
+                .iter()
+                .map(|(sexuality, &chance)| (sexuality.clone(), chance)),
+        )
+        .unwrap_or_else(|| "heterosexual".to_string());
+        Self {
+            id: birth.id,
+            name: birth.name,
+            sex: birth.sex,
+            birth: date,
+            death: None,
+            age: 0,
+            spouse: None,
+            extra_spouses: Vec::new(),
+            concubines: Vec::new(),
+            father: None,
+            mother: None,
+            children: Vec::new(),
+            dynasty: birth.dynasty,
+            is_house: birth.is_house,
+            house: birth.house,
+            culture: birth.culture,
+            religion: birth.religion,
+            gender_law: birth.gender_law,
+            generation: birth.generation,
+            is_progenitor: false,
+            is_bastard: false,
+            legitimized_on: None,
+            is_adopted: false,
+            birth_order: birth.birth_order,
+            skills: [0; 6],
+            education: None,
+            traits: Vec::new(),
+            personality_traits: Vec::new(),
+            congenital_traits: Vec::new(),
+            inactive_traits: Vec::new(),
+            sexuality,
+            fertility_modifier: birth.fertility_modifier,
+            blood_tier: birth.blood_tier,
+            dna: None,
+            events: vec![Event {
+                date,
+                kind: EventKind::Birth,
+            }],
+            event_death_reason: None,
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.death.is_none()
+    }
+
+    pub fn is_married(&self) -> bool {
+        self.spouse.is_some()
+    }
+
+    /// Only heterosexual characters marry and have legitimate children.
+    pub fn can_marry(&self) -> bool {
+        self.sexuality == "heterosexual"
+    }
+
+    pub fn has_dynasty(&self) -> bool {
+        self.dynasty.is_some()
+    }
+
+    pub fn has_congenital(&self, name: &str) -> bool {
+        self.congenital_traits.contains(&name)
+    }
+
+    pub fn carries_inactive(&self, name: &str) -> bool {
+        self.inactive_traits.contains(&name)
+    }
+
+    /// Multiplier on the age-based fertility rate.
+    pub fn fertility_mult(&self) -> f64 {
+        if self.has_congenital("infertile") {
+            return 0.0;
+        }
+        if self.has_congenital("fecund") {
+            self.fertility_modifier * 2.0
+        } else {
+            self.fertility_modifier
+        }
+    }
+
+    pub fn add_trait(&mut self, name: &str) {
+        if !self.traits.iter().any(|t| t == name) {
+            self.traits.push(name.to_string());
+        }
+    }
+
+    /// Whether the character has learned `language` by `date`.
+    pub fn knows_language(&self, language: &str, date: Date) -> bool {
+        self.languages(date).any(|known| known == language)
+    }
+
+    /// The languages the character has learned by `date`.
+    pub fn languages(&self, date: Date) -> impl Iterator<Item = &str> {
+        self.events
+            .iter()
+            .filter(move |event| event.date <= date)
+            .filter_map(|event| match &event.kind {
+                EventKind::LearnLanguage { language } => Some(language.as_str()),
+                _ => None,
+            })
+    }
+
+    /// The faith the character was born into.
+    pub fn birth_religion(&self) -> &str {
+        self.events
+            .iter()
+            .filter_map(|event| match &event.kind {
+                EventKind::Conversion { from, .. } => Some((event.date, from)),
+                _ => None,
+            })
+            .min_by_key(|&(date, _)| date)
+            .map_or(&self.religion, |(_, from)| from)
+    }
+
+    pub fn add_event(&mut self, date: Date, kind: EventKind) {
+        self.events.push(Event { date, kind });
+    }
+
+    /// Rewrite every reference to another character with `map`.
+    pub(crate) fn remap_ids(&mut self, map: impl Fn(CharacterId) -> CharacterId) {
+        for id in [&mut self.spouse, &mut self.father, &mut self.mother]
+            .into_iter()
+            .flatten()
+        {
+            *id = map(*id);
+        }
+        for id in self
+            .children
+            .iter_mut()
+            .chain(&mut self.extra_spouses)
+            .chain(&mut self.concubines)
+        {
+            *id = map(*id);
+        }
+        for event in &mut self.events {
+            match &mut event.kind {
+                EventKind::Marriage { spouse, .. } => *spouse = map(*spouse),
+                EventKind::Concubinage { concubine } => *concubine = map(*concubine),
+                EventKind::Adoption {
+                    adopter: Some(adopter),
+                } => *adopter = map(*adopter),
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether a childhood trait has been recorded.
+    pub fn has_childhood_trait(&self) -> bool {
+        self.events.iter().any(|event| match &event.kind {
+            EventKind::Traits(traits) => traits
+                .iter()
+                .any(|name| CHILDHOOD_TRAITS.contains(&name.as_str())),
+            _ => false,
+        })
+    }
+
+    /// Roll each skill's level.
+    pub(crate) fn assign_skills(&mut self, config: &SkillsAndTraits, rng: &mut impl Rng) {
+        for (level, skill) in self.skills.iter_mut().zip(SKILLS) {
+            *level = config
+                .skill_probabilities
+                .get(skill)
+                .and_then(|chances| random_level(chances, rng))
+                .unwrap_or(0);
+        }
+    }
+
+    /// Pick an education focus, favouring the best skills, and roll its tier.
+    pub(crate) fn assign_education(&mut self, config: &SkillsAndTraits, rng: &mut impl Rng) {
+        let exponent = config.education_weight_exponent;
+        let Some(skill) = random::weighted(
+            rng,
+            SKILLS
+                .iter()
+                .zip(self.skills)
+                .map(|(&skill, level)| (skill, f64::from(level).powf(exponent))),
+        ) else {
+            return;
+        };
+        let tier = config
+            .education_probabilities
+            .get(skill)
+            .and_then(|chances| random_level(chances, rng))
+            .unwrap_or(0);
+        self.education = Some(Education { skill, tier });
+    }
+
+    /// Draw personality traits by weight, times their factor in
+    /// `multipliers` if they have one, never two that exclude each other or
+    /// that the game's data makes opposites.
+    pub(crate) fn assign_personality_traits(
+        &mut self,
+        config: &SimConfig,
+        multipliers: &HashMap<String, f64>,
+        rng: &mut impl Rng,
+    ) {
+        let game = config.game_data.as_deref();
+        let config = &config.skills_and_traits.personality_traits;
+        let mut pool: Vec<&String> = config.traits.keys().collect();
+        let weight = |name: &String| {
+            config.traits[name].weight * multipliers.get(name).copied().unwrap_or(1.0)
+        };
+        self.personality_traits.clear();
+        while self.personality_traits.len() < config.total_traits_per_character as usize {
+            let Some(chosen) = random::weighted(rng, pool.iter().map(|&name| (name, weight(name))))
+            else {
+                break;
+            };
+            let excludes = &config.traits[chosen].excludes;
+            pool.retain(|&name| {
+                name != chosen
+                    && !excludes.contains(name)
+                    && !game.is_some_and(|game| game.are_opposites(chosen, name))
+            });
+            self.personality_traits.push(chosen.clone());
+        }
+    }
+
+    /// The trait events of a character born before the period simulated:
+    /// a childhood trait at 3 and the personality traits at 16.
+    pub(crate) fn add_upbringing_events(&mut self, rng: &mut impl Rng) {
+        let childhood = self.roll_childhood_trait(rng);
+        self.add_event(
+            self.birth.years_later(3),
+            EventKind::Traits(vec![childhood]),
+        );
+        let personality = self.personality_traits.clone();
+        self.add_event(self.birth.years_later(16), EventKind::Traits(personality));
+    }
+
+    /// A childhood trait suiting the character's education.
+    pub(crate) fn roll_childhood_trait(&self, rng: &mut impl Rng) -> String {
+        let education = self.education.unwrap_or(Education {
+            skill: "diplomacy",
+            tier: 0,
+        });
+        random::pick(rng, &education.childhood_traits())
+            .expect("two traits")
+            .to_string()
+    }
+}
+
+fn random_level(chances: &BTreeMap<String, f64>, rng: &mut impl Rng) -> Option<u32> {
+    random::weighted(
+        rng,
+        chances
+            .iter()
+            .filter_map(|(level, &chance)| Some((level.parse().ok()?, chance))),
+    )
+}
+
+/// Highest Númenórean blood tier.
+const MAX_BLOOD_TIER: u32 = 10;
+
+/// Roll the Númenórean blood tier `child` inherits from its parents.
+///
+/// With `numenorBlood` the child's blood is mixed from both parents', and
+/// may come out above the weaker parent's tier or below the stronger's.
+/// Otherwise the higher parental tier is kept with the configured chance
+/// for how far apart the parents' tiers are, and drops by one tier, or two
+/// for parents far apart, if not. Without `numenorInheritance` either the
+/// higher tier is always kept. The tier is then capped by `decline`: a
+/// child born after a tier's cutoff year falls below that tier.
+pub(crate) fn inherit_blood(
+    child: &mut Character,
+    father: Option<&Character>,
+    mother: Option<&Character>,
+    blood: Option<&NumenorBlood>,
+    chances: Option<&NumenorInheritance>,
+    decline: Option<&BTreeMap<String, i32>>,
+    rng: &mut impl Rng,
+) {
+    let father_tier = father.map_or(0, |c| c.blood_tier);
+    let mother_tier = mother.map_or(0, |c| c.blood_tier);
+    if father_tier == 0 && mother_tier == 0 {
+        return;
+    }
+    let mut tier = match blood {
+        Some(blood) => mixed_tier(blood, father_tier, mother_tier, rng),
+        None => {
+            let high = father_tier.max(mother_tier);
+            let diff = high - father_tier.min(mother_tier);
+            let (chance, drop) = match chances {
+                None => (1.0, 0),
+                Some(chances) if diff == 0 => (chances.same_tier_chance, 1),
+                Some(chances) if diff <= 2 => (chances.close_tier_chance, 1),
+                Some(chances) => (chances.far_tier_chance, 2),
+            };
+            if random::chance(rng, chance) {
+                high
+            } else {
+                high.saturating_sub(drop)
+            }
+        }
+    };
+    if let Some(decline) = decline {
+        for (cutoff_tier, &cutoff) in decline {
+            let Ok(cutoff_tier) = cutoff_tier.parse::<u32>() else {
+                continue;
+            };
+            if cutoff_tier > 0 && tier >= cutoff_tier && child.birth.year > cutoff {
+                tier = cutoff_tier - 1;
+            }
+        }
+    }
+    child.blood_tier = tier;
+}
+
+/// The tier of a child of parents of tiers `father` and `mother`, whose
+/// blood is the mean of theirs give or take `drift`.
+fn mixed_tier(blood: &NumenorBlood, father: u32, mother: u32, rng: &mut impl Rng) -> u32 {
+    let mean = (blood_strength(blood, father) + blood_strength(blood, mother)) / 2.0;
+    let strength = mean + rng.random_range(-blood.drift..=blood.drift);
+    (1..=MAX_BLOOD_TIER)
+        .rev()
+        .find(|&tier| strength >= blood.threshold(tier))
+        .unwrap_or(0)
+}
+
+/// The strength of the blood of tier `tier`: the middle of its band.
+fn blood_strength(blood: &NumenorBlood, tier: u32) -> f64 {
+    if tier == 0 {
+        return 0.0;
+    }
+    let tier = tier.min(MAX_BLOOD_TIER);
+    let top = if tier == MAX_BLOOD_TIER {
+        1.0
+    } else {
+        blood.threshold(tier + 1)
+    };
+    (blood.threshold(tier) + top) / 2.0
+}
@@ -0,0 +1,388 @@
+//! Exporting the generated dynasties as a written chronicle.
+//!
+//! Lore writers want prose rather than tables. [`output_chronicle`] reads an
+//! output directory, whichever engine wrote it, and gives a Markdown document
+//! with a chapter per dynasty, telling year by year what the history records
+//! of its members: who was born to whom, who married whom, who came to hold a
+//! title and who died, and how. A character who held a ranked title is styled
+//! by the highest one they held at the time, such as "Duke Alric".
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use paradox_script::Date;
+
+use crate::error::SimError;
+use crate::export::{history_seed, HISTORY_FILE};
+use crate::localization::{dynasty_key, house_key, read_names};
+use crate::people::{read_house_dynasties, read_people, read_titles, Person};
+
+/// What a clause tells, in the order clauses of the same day are told.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Birth,
+    Marriage,
+    Death,
+    Succession,
+}
+
+/// A time a character held a title: the title's key, from when, and until
+/// when if someone else took it.
+struct Tenure<'a> {
+    title: &'a str,
+    from: Date,
+    until: Option<Date>,
+}
+
+/// The Markdown chronicle of the history of the output directory `dir`, a
+/// chapter per dynasty in the order the history first mentions them.
+pub fn output_chronicle(dir: &Path) -> Result<String, SimError> {
+    let people = read_people(dir)?;
+    let names = read_names(dir);
+    let house_dynasties = read_house_dynasties(dir);
+    let titles = read_titles(dir);
+    let seed = fs::read(dir.join(HISTORY_FILE))
+        .ok()
+        .and_then(|history| history_seed(&String::from_utf8_lossy(&history)));
+
+    let index: HashMap<&str, usize> = people
+        .iter()
+        .enumerate()
+        .map(|(i, person)| (person.id.as_str(), i))
+        .collect();
+    let mut tenures: HashMap<usize, Vec<Tenure>> = HashMap::new();
+    let mut successions: Vec<(usize, Date, &str, Option<usize>)> = Vec::new();
+    for (title, holders) in &titles {
+        for (i, (from, holder)) in holders.iter().enumerate() {
+            let Some(&holder) = holder.as_deref().and_then(|id| index.get(id)) else {
+                continue;
+            };
+            tenures.entry(holder).or_default().push(Tenure {
+                title,
+                from: *from,
+                until: holders.get(i + 1).map(|(until, _)| *until),
+            });
+            let previous = i
+                .checked_sub(1)
+                .and_then(|previous| holders[previous].1.as_deref())
+                .and_then(|id| index.get(id).copied());
+            successions.push((holder, *from, title, previous));
+        }
+    }
+    let chronicle = Chronicle {
+        people: &people,
+        index,
+        tenures,
+        house_dynasties: &house_dynasties,
+        names: &names,
+    };
+
+    let mut dynasties: Vec<(&str, Vec<usize>)> = Vec::new();
+    for (i, person) in people.iter().enumerate() {
+        let Some(dynasty) = person.lineage(&house_dynasties) else {
+            continue;
+        };
+        match dynasties.iter_mut().find(|(id, _)| *id == dynasty) {
+            Some((_, members)) => members.push(i),
+            None => dynasties.push((dynasty, vec![i])),
+        }
+    }
+
+    let mut out = match seed {
+        Some(seed) => format!("# Chronicle of the history of seed {seed}\n"),
+        None => "# Chronicle\n".to_string(),
+    };
+    let chapter_names: Vec<&str> = dynasties
+        .iter()
+        .map(|(dynasty, _)| {
+            names
+                .get(&dynasty_key(dynasty))
+                .or_else(|| names.get(&house_key(dynasty)))
+                .map_or(*dynasty, String::as_str)
+        })
+        .collect();
+    for ((dynasty, members), name) in dynasties.iter().zip(&chapter_names) {
+        let in_dynasty: HashSet<usize> = members.iter().copied().collect();
+        let mut clauses: Vec<(Date, Kind, String)> = Vec::new();
+        let mut married: HashSet<(usize, usize)> = HashSet::new();
+        for &i in members {
+            let person = &people[i];
+            if let Some(birth) = person.birth {
+                clauses.push((birth, Kind::Birth, chronicle.birth(i, birth)));
+            }
+            for (date, spouse) in &person.marriages {
+                let Some(&spouse) = chronicle.index.get(spouse.as_str()) else {
+                    continue;
+                };
+                // Both sides of a marriage may record it.
+                if married.insert((i.min(spouse), i.max(spouse))) {
+                    clauses.push((
+                        *date,
+                        Kind::Marriage,
+                        chronicle.marriage(i, spouse, *date, dynasty),
+                    ));
+                }
+            }
+            if let Some(death) = person.death {
+                clauses.push((death, Kind::Death, chronicle.death(i, death)));
+            }
+        }
+        for &(holder, date, title, previous) in &successions {
+            if in_dynasty.contains(&holder) {
+                let clause = chronicle.succession(holder, title, previous);
+                clauses.push((date, Kind::Succession, clause));
+            }
+        }
+        clauses.sort_by_key(|(date, kind, _)| (*date, *kind));
+
+        // Dynasties of the same name are told apart by their IDs.
+        if chapter_names.iter().filter(|other| *other == name).count() > 1 {
+            out.push_str(&format!("\n## House {name} ({dynasty})\n\n"));
+        } else {
+            out.push_str(&format!("\n## House {name}\n\n"));
+        }
+        let births = members.iter().filter_map(|&i| people[i].birth);
+        let first = births.clone().map(|date| date.year).min();
+        let last = births.map(|date| date.year).max();
+        let count = match members.len() {
+            1 => "1 member".to_string(),
+            n => format!("{n} members"),
+        };
+        out.push_str(&match (first, last) {
+            (Some(first), Some(last)) if first != last => {
+                format!("*{count}, born from {first} to {last}.*\n")
+            }
+            (Some(first), _) => format!("*{count}, born in {first}.*\n"),
+            _ => format!("*{count}.*\n"),
+        });
+        let mut clauses = clauses.into_iter().peekable();
+        while let Some((date, _, clause)) = clauses.next() {
+            let mut year = vec![clause];
+            while let Some((_, _, clause)) = clauses.next_if(|(next, _, _)| next.year == date.year)
+            {
+                year.push(clause);
+            }
+            out.push_str(&format!("\nIn {}, {}.\n", date.year, year.join("; ")));
+        }
+    }
+    Ok(out)
+}
+
+struct Chronicle<'a> {
+    people: &'a [Person],
+    index: HashMap<&'a str, usize>,
+    tenures: HashMap<usize, Vec<Tenure<'a>>>,
+    house_dynasties: &'a HashMap<String, String>,
+    names: &'a HashMap<String, String>,
+}
+
+impl Chronicle<'_> {
+    /// Character `i` as they would be styled on `date`: by the highest ranked
+    /// title they held, if any.
+    fn styled(&self, i: usize, date: Date) -> String {
+        let person = &self.people[i];
+        let rank = self
+            .tenures
+            .get(&i)
+            .into_iter()
+            .flatten()
+            .filter(|tenure| tenure.from <= date && tenure.until.is_none_or(|until| date <= until))
+            .filter_map(|tenure| rank(tenure.title))
+            .min_by_key(|(rank, _, _)| *rank);
+        match rank {
+            Some((_, male, female)) => {
+                let style = if person.female { female } else { male };
+                format!("{style} {}", person.name)
+            }
+            None => person.name.clone(),
+        }
+    }
+
+    /// The house or dynasty name of character `i`, if they have one.
+    fn surname(&self, i: usize) -> Option<&str> {
+        let person = &self.people[i];
+        person
+            .surname_key()
+            .and_then(|key| self.names.get(&key))
+            .map(String::as_str)
+            .or(person.house.as_deref())
+            .or(person.dynasty.as_deref())
+    }
+
+    fn birth(&self, i: usize, date: Date) -> String {
+        let person = &self.people[i];
+        let parents: Vec<String> = [&person.father, &person.mother]
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.index.get(id.as_str()))
+            .map(|&parent| self.styled(parent, date))
+            .collect();
+        let child = if person.female { "daughter" } else { "son" };
+        if parents.is_empty() {
+            format!("{} was born", person.name)
+        } else {
+            format!(
+                "{}, {child} of {}, was born",
+                person.name,
+                parents.join(" and ")
+            )
+        }
+    }
+
+    /// The marriage of member `i` of `dynasty` and `spouse`, who is named
+    /// with their house unless they are of the dynasty too.
+    fn marriage(&self, i: usize, spouse: usize, date: Date, dynasty: &str) -> String {
+        let mut clause = format!(
+            "{} married {}",
+            self.styled(i, date),
+            self.styled(spouse, date)
+        );
+        if self.people[spouse].lineage(self.house_dynasties) != Some(dynasty) {
+            if let Some(surname) = self.surname(spouse) {
+                clause.push_str(&format!(" of House {surname}"));
+            }
+        }
+        clause
+    }
+
+    /// Character `i` taking the title `title` from `previous`, named as
+    /// their parent if they are one.
+    fn succession(&self, i: usize, title: &str, previous: Option<usize>) -> String {
+        let person = &self.people[i];
+        let title = rank(title).map(|(_, male, female)| {
+            let style = if person.female { female } else { male };
+            format!(" as {style} of {}", title_name(title))
+        });
+        let Some(previous) = previous else {
+            return match title {
+                Some(title) => format!("{} became{}", person.name, title.trim_start_matches(" as")),
+                None => format!("{} came to rule", person.name),
+            };
+        };
+        let predecessor = &self.people[previous];
+        let relation = if person.father.as_ref() == Some(&predecessor.id) {
+            "father "
+        } else if person.mother.as_ref() == Some(&predecessor.id) {
+            "mother "
+        } else {
+            ""
+        };
+        let pronoun = match (relation, person.female) {
+            ("", _) => "",
+            (_, true) => "her ",
+            (_, false) => "his ",
+        };
+        format!(
+            "{} succeeded {pronoun}{relation}{}{}",
+            person.name,
+            predecessor.name,
+            title.unwrap_or_default()
+        )
+    }
+
+    fn death(&self, i: usize, date: Date) -> String {
+        let person = &self.people[i];
+        let mut clause = format!("{} died", self.styled(i, date));
+        if let Some(birth) = person.birth {
+            let age = date.year
+                - birth.year
+                - i32::from((date.month, date.day) < (birth.month, birth.day));
+            clause.push_str(&format!(" aged {age}"));
+        }
+        if let Some(reason) = person.death_reason.as_deref() {
+            clause.push_str(", ");
+            clause.push_str(&cause(reason));
+        }
+        clause
+    }
+}
+
+/// The rank of the title `key` by its tier prefix, highest first, with the
+/// styles of its male and female holders; none for a placeholder title.
+fn rank(key: &str) -> Option<(u8, &'static str, &'static str)> {
+    Some(match key.split_once('_')?.0 {
+        "e" => (0, "Emperor", "Empress"),
+        "k" => (1, "King", "Queen"),
+        "d" => (2, "Duke", "Duchess"),
+        "c" => (3, "Count", "Countess"),
+        "b" => (4, "Baron", "Baroness"),
+        _ => return None,
+    })
+}
+
+/// The name of the title `key` as its key spells it, such as `Gondor` for
+/// `d_gondor`.
+fn title_name(key: &str) -> String {
+    let name = key.split_once('_').map_or(key, |(_, name)| name);
+    name.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// How a character died of the death reason `reason`.
+fn cause(reason: &str) -> String {
+    let reason = reason.strip_prefix("death_").unwrap_or(reason);
+    let reason = reason.strip_suffix("_passive").unwrap_or(reason);
+    match reason {
+        "natural_causes" => "of natural causes".to_string(),
+        "ill" => "of an illness".to_string(),
+        "accident" => "in an accident".to_string(),
+        "attacked" => "in an attack".to_string(),
+        "battle" => "in battle".to_string(),
+        "murder" => "murdered".to_string(),
+        "drinking" => "of drink".to_string(),
+        "dungeon" => "in a dungeon".to_string(),
+        "childbirth" => "in childbirth".to_string(),
+        other => format!("of {}", other.replace('_', " ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn each_dynasty_is_a_chapter_told_year_by_year() {
+        let dir = testing::family("chronicle");
+        let chronicle = output_chronicle(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            chronicle,
+            "\
+# Chronicle of the history of seed 7
+
+## House Alda
+
+*2 members, born from 6000 to 6026.*
+
+In 6000, Aldor was born.
+
+In 6025, Aldor married Beren of House Bregol.
+
+In 6026, Anborn, son of Aldor and Beren, was born.
+
+In 6030, Aldor became King of Gondor.
+
+In 6060, King Aldor died aged 60, in battle; Anborn succeeded his father Aldor as King of \
+             Gondor.
+
+## House Bregol
+
+*1 member, born in 6002.*
+
+In 6002, Beren was born.
+"
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! The three config files a simulation reads, and its plugins.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use ck3_config::{ConfigError, Feature, InitializationConfig, LifeStages, SkillsAndTraits};
+
+use crate::error::SimError;
+use crate::game_data::GameData;
+use crate::plugins::Plugins;
+
+pub const INITIALIZATION_FILE: &str = "initialization.json";
+pub const LIFE_STAGES_FILE: &str = "life_stages.json";
+pub const SKILLS_AND_TRAITS_FILE: &str = "skills_and_traits.json";
+
+/// Everything a simulation is configured with.
+#[derive(Clone, Debug)]
+pub struct SimConfig {
+    pub initialization: InitializationConfig,
+    pub life_stages: LifeStages,
+    pub skills_and_traits: SkillsAndTraits,
+    /// What the target game defines, if the config is checked against it.
+    pub game_data: Option<Arc<GameData>>,
+    /// Scripts of the directory's `plugins` folder.
+    pub plugins: Plugins,
+}
+
+impl SimConfig {
+    /// Read and validate the config files in `dir`, the engine's config
+    /// directory.
+    pub fn load(dir: &Path) -> Result<Self, SimError> {
+        Ok(Self {
+            initialization: read(dir, INITIALIZATION_FILE, ck3_config::parse_initialization)?,
+            life_stages: read(dir, LIFE_STAGES_FILE, ck3_config::parse_life_stages)?,
+            skills_and_traits: read(
+                dir,
+                SKILLS_AND_TRAITS_FILE,
+                ck3_config::parse_skills_and_traits,
+            )?,
+            game_data: None,
+            plugins: Plugins::load(dir)?,
+        })
+    }
+
+    /// Like [`SimConfig::load`], but with `initialization` instead of the
+    /// directory's `initialization.json`, such as a config being edited.
+    pub fn load_with(dir: &Path, initialization: InitializationConfig) -> Result<Self, SimError> {
+        Ok(Self {
+            initialization,
+            life_stages: read(dir, LIFE_STAGES_FILE, ck3_config::parse_life_stages)?,
+            skills_and_traits: read(
+                dir,
+                SKILLS_AND_TRAITS_FILE,
+                ck3_config::parse_skills_and_traits,
+            )?,
+            game_data: None,
+            plugins: Plugins::load(dir)?,
+        })
+    }
+
+    /// Whether the game version the config writes for, the latest if none,
+    /// can read `feature`.
+    pub(crate) fn supports(&self, feature: Feature) -> bool {
+        ck3_config::game_version::supports(self.initialization.game_version, feature)
+    }
+}
+
+fn read<T>(
+    dir: &Path,
+    file: &str,
+    parse: fn(&str) -> Result<T, Vec<ConfigError>>,
+) -> Result<T, SimError> {
+    let path = dir.join(file);
+    let text = fs::read_to_string(&path).map_err(|source| SimError::Io { path, source })?;
+    parse(&text).map_err(|errors| SimError::Config {
+        file: file.to_string(),
+        errors,
+    })
+}
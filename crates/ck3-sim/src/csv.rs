@@ -0,0 +1,158 @@
+//! Exporting the generated characters as CSV tables.
+//!
+//! Spreadsheets and data frames read CSV, not history files. [`output_csv`]
+//! reads the character history of an output directory, whichever engine
+//! wrote it, and gives two tables: one row per character, with their family,
+//! culture, faith, skills, traits and the dates of their life, and one row
+//! per event of the history, such as a birth, a trait gained or a marriage,
+//! named after what the history file does. An adopted character's parents
+//! are their adoptive ones, and their spouses those their own block marries
+//! them to. Dates are `year.month.day` in the calendar the history was
+//! written in. Both tables start with a byte order mark, so that Excel reads
+//! them as UTF-8; pandas wants `encoding="utf-8-sig"`.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use paradox_script::Date;
+
+use crate::character::SKILLS;
+use crate::error::SimError;
+use crate::people::read_people;
+
+/// The characters table and the events table of the history of the output
+/// directory `dir`, in that order.
+pub fn output_csv(dir: &Path) -> Result<(String, String), SimError> {
+    let people = read_people(dir)?;
+    let date = |date: Option<Date>| date.map_or(String::new(), |date| date.to_string());
+    let text = |text: &Option<String>| text.clone().unwrap_or_default();
+
+    let mut characters = Table::new(
+        [
+            "id",
+            "name",
+            "sex",
+            "dynasty",
+            "house",
+            "culture",
+            "religion",
+            "sexuality",
+            "father",
+            "mother",
+            "adopted",
+            "birth",
+            "death",
+            "death_reason",
+        ]
+        .into_iter()
+        .chain(SKILLS)
+        .chain(["traits", "spouses"]),
+    );
+    let mut events = Table::new(["character", "date", "event", "value"]);
+    for person in &people {
+        let sex = if person.female { "female" } else { "male" };
+        let spouses: Vec<&str> = person
+            .marriages
+            .iter()
+            .map(|(_, spouse)| spouse.as_str())
+            .collect();
+        characters.row(
+            [
+                person.id.clone(),
+                person.name.clone(),
+                sex.to_string(),
+                text(&person.dynasty),
+                text(&person.house),
+                text(&person.culture),
+                text(&person.religion),
+                text(&person.sexuality),
+                text(&person.father),
+                text(&person.mother),
+                date(person.adopted),
+                date(person.birth),
+                date(person.death),
+                text(&person.death_reason),
+            ]
+            .into_iter()
+            .chain(person.skills.iter().map(text))
+            .chain([person.traits.join(";"), spouses.join(";")]),
+        );
+        for (when, event, value) in &person.events {
+            events.row([
+                person.id.clone(),
+                when.to_string(),
+                event.clone(),
+                value.clone(),
+            ]);
+        }
+    }
+    Ok((characters.0, events.0))
+}
+
+/// A CSV table being written.
+struct Table(String);
+
+impl Table {
+    fn new<'a>(header: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut table = Self(paradox_script::BOM.to_string());
+        table.row(header.into_iter().map(str::to_string));
+        table
+    }
+
+    fn row(&mut self, fields: impl IntoIterator<Item = String>) {
+        for (i, field) in fields.into_iter().enumerate() {
+            if i > 0 {
+                self.0.push(',');
+            }
+            if field.contains([',', '"', '\n', '\r']) {
+                write!(self.0, "\"{}\"", field.replace('"', "\"\""))
+                    .expect("writing to a String cannot fail");
+            } else {
+                self.0.push_str(&field);
+            }
+        }
+        self.0.push_str("\r\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn characters_and_events_are_tabled() {
+        let dir = testing::family("csv");
+        let (characters, events) = output_csv(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            characters.split("\r\n").collect::<Vec<_>>(),
+            [
+                "\u{feff}id,name,sex,dynasty,house,culture,religion,sexuality,father,mother,\
+                 adopted,birth,death,death_reason,diplomacy,martial,stewardship,intrigue,\
+                 learning,prowess,traits,spouses",
+                "lineofa1,Aldor,male,dynasty_a,,gondorian,faith_gondor,,,,,6000.1.1,6060.1.1,\
+                 death_battle,5,,,,,,brave,lineofb1",
+                "lineofb1,Beren,female,dynasty_b,,rohirric,faith_rohan,,,,,6002.1.1,,,,,,,,,,",
+                "lineofa2,Anborn,male,,house_a_cadet,gondorian,faith_gondor,,lineofa1,lineofb1,,\
+                 6026.5.1,,,,,,,,,shy,",
+                "",
+            ]
+        );
+        assert_eq!(
+            events.split("\r\n").collect::<Vec<_>>(),
+            [
+                "\u{feff}character,date,event,value",
+                "lineofa1,6000.1.1,birth,yes",
+                "lineofa1,6025.3.1,add_spouse,lineofb1",
+                "lineofa1,6060.1.1,death,death_battle",
+                "lineofb1,6002.1.1,birth,yes",
+                "lineofa2,6026.5.1,birth,yes",
+                "lineofa2,6026.5.1,trait,shy",
+                "",
+            ]
+        );
+    }
+}
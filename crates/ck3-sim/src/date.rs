@@ -0,0 +1,255 @@
+//! Calendar dates as CK3 history files write them.
+//!
+//! The simulation dates everything in its own years. Output is dated in the
+//! configured [`Calendar`] instead: [`Date::to_calendar`] converts a date on
+//! the way out and [`Date::from_calendar`] a date read back in.
+
+use std::fmt;
+
+use ck3_config::Calendar;
+use rand::Rng;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Days in each month of the game's calendar, which has no leap years.
+pub(crate) const MONTH_DAYS: [u8; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+const YEAR_DAYS: i32 = 365;
+
+/// A day in the simulated calendar, ordered chronologically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u8, day: u8) -> Self {
+        Self { year, month, day }
+    }
+
+    /// Parse a `year.month.day` date such as `867.1.1`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.trim().split('.');
+        let date = Self::new(
+            parts.next()?.parse().ok()?,
+            parts.next()?.parse().ok()?,
+            parts.next()?.parse().ok()?,
+        );
+        parts.next().is_none().then_some(date)
+    }
+
+    /// A random day of `year`. Days stop at the 28th so every month has them.
+    pub fn random_in(year: i32, rng: &mut impl Rng) -> Self {
+        Self::new(year, rng.random_range(1..=12), rng.random_range(1..=28))
+    }
+
+    /// A random day from `first` to `last`, both included, counting 28 days
+    /// to every month as [`Date::random_in`] does.
+    pub fn random_between(first: Date, last: Date, rng: &mut impl Rng) -> Self {
+        Self::from_day(rng.random_range(first.day_number()..=last.day_number()))
+    }
+
+    /// The same day `months` later.
+    pub fn months_later(self, months: i32) -> Self {
+        let month = self.year * 12 + i32::from(self.month) - 1 + months;
+        Self::new(
+            month.div_euclid(12),
+            (month.rem_euclid(12) + 1) as u8,
+            self.day,
+        )
+    }
+
+    /// Days since the start of year 0, in 28-day months.
+    fn day_number(self) -> i32 {
+        (self.year * 12 + i32::from(self.month) - 1) * 28 + i32::from(self.day.clamp(1, 28)) - 1
+    }
+
+    fn from_day(day: i32) -> Self {
+        let month = day.div_euclid(28);
+        Self::new(
+            month.div_euclid(12),
+            (month.rem_euclid(12) + 1) as u8,
+            (day.rem_euclid(28) + 1) as u8,
+        )
+    }
+
+    /// The same day and month `years` later.
+    pub fn years_later(self, years: i32) -> Self {
+        Self::new(self.year + years, self.month, self.day)
+    }
+
+    /// Whole years from `self` to `later`, counting a year only once its
+    /// anniversary has passed.
+    pub fn years_until(self, later: Date) -> i32 {
+        let years = later.year - self.year;
+        if (later.month, later.day) < (self.month, self.day) {
+            years - 1
+        } else {
+            years
+        }
+    }
+
+    /// The date in `calendar` of this simulation date.
+    ///
+    /// A calendar year longer than one simulation year spreads its days over
+    /// all of them, so that dates a few days apart may share a day.
+    pub fn to_calendar(self, calendar: &Calendar) -> Self {
+        let length = calendar.year_length;
+        if length == 1 {
+            return self.years_later(calendar.epoch_offset);
+        }
+        let day = self.year.rem_euclid(length) * YEAR_DAYS + self.day_of_year();
+        Self::from_day_of_year(
+            calendar.epoch_offset + self.year.div_euclid(length),
+            day / length,
+        )
+    }
+
+    /// The simulation date of this date in `calendar`: the first day that
+    /// [`Date::to_calendar`] dates to it.
+    pub fn from_calendar(self, calendar: &Calendar) -> Self {
+        let length = calendar.year_length;
+        if length == 1 {
+            return self.years_later(-calendar.epoch_offset);
+        }
+        let day = self.day_of_year() * length;
+        Self::from_day_of_year(
+            (self.year - calendar.epoch_offset) * length + day / YEAR_DAYS,
+            day % YEAR_DAYS,
+        )
+    }
+
+    /// Days since the start of the year, from 0.
+    fn day_of_year(self) -> i32 {
+        let month = usize::from(self.month.clamp(1, 12)) - 1;
+        let before: i32 = MONTH_DAYS[..month]
+            .iter()
+            .map(|&days| i32::from(days))
+            .sum();
+        before + i32::from(self.day.clamp(1, MONTH_DAYS[month])) - 1
+    }
+
+    fn from_day_of_year(year: i32, mut day: i32) -> Self {
+        let mut month = 0;
+        while day >= i32::from(MONTH_DAYS[month]) {
+            day -= i32::from(MONTH_DAYS[month]);
+            month += 1;
+        }
+        Self::new(year, month as u8 + 1, day as u8 + 1)
+    }
+}
+
+impl fmt::Display for Date {
+    /// `867.01.01`, the format of history file date keys.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:02}.{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Written as [displayed](fmt::Display), as in history files.
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Date::parse(&text).ok_or_else(|| de::Error::custom(format!("'{text}' is not a date")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    fn calendar(epoch_offset: i32, year_length: i32) -> Calendar {
+        Calendar {
+            epoch_offset,
+            year_length,
+        }
+    }
+
+    #[test]
+    fn dates_parse_and_display_as_history_keys() {
+        assert_eq!(Date::parse("867.1.1"), Some(Date::new(867, 1, 1)));
+        assert_eq!(Date::parse(" -3.12.31 "), Some(Date::new(-3, 12, 31)));
+        assert_eq!(Date::parse("867.1"), None);
+        assert_eq!(Date::parse("867.1.1.1"), None);
+        assert_eq!(Date::new(867, 1, 1).to_string(), "867.01.01");
+    }
+
+    #[test]
+    fn months_carry_over_the_year() {
+        let date = Date::new(867, 11, 5);
+        assert_eq!(date.months_later(3), Date::new(868, 2, 5));
+        assert_eq!(date.months_later(-11), Date::new(866, 12, 5));
+        assert_eq!(Date::new(0, 1, 1).months_later(-1), Date::new(-1, 12, 1));
+    }
+
+    #[test]
+    fn years_count_from_the_anniversary() {
+        let birth = Date::new(800, 5, 10);
+        assert_eq!(birth.years_until(Date::new(820, 5, 9)), 19);
+        assert_eq!(birth.years_until(Date::new(820, 5, 10)), 20);
+        assert_eq!(birth.years_until(birth), 0);
+    }
+
+    #[test]
+    fn random_days_stay_between_their_bounds() {
+        let mut rng = ChaCha12Rng::seed_from_u64(7);
+        let (first, last) = (Date::new(866, 12, 20), Date::new(867, 2, 3));
+        for _ in 0..1000 {
+            let date = Date::random_between(first, last, &mut rng);
+            assert!(first <= date && date <= last, "{date}");
+            assert!((1..=28).contains(&date.day), "{date}");
+        }
+    }
+
+    #[test]
+    fn a_one_year_calendar_moves_the_epoch() {
+        let calendar = calendar(6000, 1);
+        let date = Date::new(867, 3, 4);
+        assert_eq!(date.to_calendar(&calendar), Date::new(6867, 3, 4));
+        assert_eq!(Date::new(6867, 3, 4).from_calendar(&calendar), date);
+    }
+
+    #[test]
+    fn long_years_spread_their_days_over_the_simulation_years() {
+        let yen = calendar(1, 144);
+        assert_eq!(Date::new(0, 1, 1).to_calendar(&yen), Date::new(1, 1, 1));
+        assert_eq!(
+            Date::new(143, 12, 31).to_calendar(&yen),
+            Date::new(1, 12, 31)
+        );
+        assert_eq!(Date::new(144, 1, 1).to_calendar(&yen), Date::new(2, 1, 1));
+        assert_eq!(
+            Date::new(-1, 12, 31).to_calendar(&yen),
+            Date::new(0, 12, 31)
+        );
+        // Each calendar day lasts 144 simulation days: the 1st of March,
+        // day 59, starts on simulation day 59 * 144, the 12th of April 23.
+        assert_eq!(Date::new(23, 4, 12).to_calendar(&yen), Date::new(1, 3, 1));
+        assert_eq!(Date::new(23, 4, 11).to_calendar(&yen), Date::new(1, 2, 28));
+    }
+
+    #[test]
+    fn calendar_dates_come_back_as_their_first_simulation_day() {
+        let yen = calendar(1, 144);
+        for month in 1..=12 {
+            for day in 1..=MONTH_DAYS[usize::from(month) - 1] {
+                let date = Date::new(3, month, day);
+                let first = date.from_calendar(&yen);
+                assert_eq!(first.to_calendar(&yen), date);
+                let before = Date::from_day_of_year(
+                    first.year - i32::from(first.day_of_year() == 0),
+                    (first.day_of_year() - 1).rem_euclid(YEAR_DAYS),
+                );
+                assert_ne!(before.to_calendar(&yen), date, "{date}");
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! The definitions of the dynasties the characters belong to.
+//!
+//! Characters name their dynasty by the ID it has in the config, so each
+//! configured dynasty gets an entry for `common/dynasties` under that same
+//! ID, with its culture and the localisation keys of its name and motto.
+//! Entries marked `isHouse` are houses of a dynasty the target mod already
+//! defines, and are left to it.
+
+use paradox_script::{Block, Scalar};
+
+use crate::localization::{dynasty_key, motto_key};
+use crate::simulation::Simulation;
+
+/// Name of the file of dynasty definitions.
+pub const DYNASTIES_FILE: &str = "dynasty_definitions.txt";
+
+impl Simulation {
+    /// The contents of the dynasty definitions file.
+    pub(crate) fn dynasty_definitions(&self) -> String {
+        let mut file = Block::default();
+        for dynasty in &self.config.initialization.dynasties {
+            if dynasty.is_house {
+                continue;
+            }
+            let mut definition = Block::default();
+            definition.push("name", Scalar::Word(dynasty_key(&dynasty.dynasty_id)));
+            definition.push("culture", Scalar::Word(dynasty.culture_id.clone()));
+            if !dynasty.dynasty_motto.is_empty() {
+                definition.push("motto", Scalar::Word(motto_key(&dynasty.dynasty_id)));
+            }
+            file.push(&dynasty.dynasty_id, definition);
+        }
+        paradox_script::to_string(&file)
+    }
+}
@@ -0,0 +1,228 @@
+//! Portrait DNA.
+//!
+//! The game draws a character's face from genes, each held twice: a
+//! dominant value it shows and a recessive one it can pass on. Colour genes
+//! hold a point on the hair, skin or eye palette, and morph genes a value out
+//! of 255 with 128 as the neutral face, written with the template that
+//! pulls the feature the way the value leans. A child gets one of each
+//! parent's two values of every gene, drifting a little from it, and either
+//! may end up dominant, so that siblings differ but a family still looks
+//! alike. Characters without known parents get random DNA, faces near the
+//! neutral one. Each character is written as an entry of the DNA file and
+//! refers to it by `dna` in their history.
+
+use paradox_script::{Block, Scalar};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::character::Character;
+
+/// Name of the file of portrait DNA entries.
+pub const DNA_FILE: &str = "dna_data.txt";
+
+/// Genes picking a point on the colour palettes.
+const COLOUR_GENES: [&str; 3] = ["hair_color", "skin_color", "eye_color"];
+
+/// Morph genes, with the stem of the templates pulling the feature either
+/// way; `gene_height` has a single template.
+const MORPH_GENES: [(&str, &str); 24] = [
+    ("gene_chin_forward", "chin_forward"),
+    ("gene_chin_height", "chin_height"),
+    ("gene_chin_width", "chin_width"),
+    ("gene_eye_angle", "eye_angle"),
+    ("gene_eye_depth", "eye_depth"),
+    ("gene_eye_height", "eye_height"),
+    ("gene_eye_distance", "eye_distance"),
+    ("gene_forehead_angle", "forehead_angle"),
+    ("gene_forehead_height", "forehead_height"),
+    ("gene_forehead_width", "forehead_width"),
+    ("gene_head_height", "head_height"),
+    ("gene_head_width", "head_width"),
+    ("gene_jaw_angle", "jaw_angle"),
+    ("gene_jaw_forward", "jaw_forward"),
+    ("gene_jaw_height", "jaw_height"),
+    ("gene_jaw_width", "jaw_width"),
+    ("gene_mouth_height", "mouth_height"),
+    ("gene_mouth_width", "mouth_width"),
+    ("gene_nose_curve", "nose_curve"),
+    ("gene_nose_height", "nose_height"),
+    ("gene_nose_length", "nose_length"),
+    ("gene_nose_size", "nose_size"),
+    ("gene_neck_width", "neck_width"),
+    ("gene_height", "normal_height"),
+];
+
+/// Largest distance from the neutral face of a random morph value, before
+/// the two draws averaged into it.
+const RANDOM_MORPH_SPREAD: i32 = 48;
+
+/// A character's portrait genes, dominant value first.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Dna {
+    /// Palette points of [`COLOUR_GENES`], in order.
+    colours: [[[u8; 2]; 2]; COLOUR_GENES.len()],
+    /// Values of [`MORPH_GENES`], in order.
+    morphs: [[u8; 2]; MORPH_GENES.len()],
+}
+
+impl Dna {
+    /// DNA for a character without known parents.
+    pub(crate) fn random(rng: &mut impl Rng) -> Self {
+        let mut dna = Self {
+            colours: [[[0; 2]; 2]; COLOUR_GENES.len()],
+            morphs: [[0; 2]; MORPH_GENES.len()],
+        };
+        for gene in &mut dna.colours {
+            *gene = [random_colour(rng), random_colour(rng)];
+        }
+        for gene in &mut dna.morphs {
+            *gene = [random_morph(rng), random_morph(rng)];
+        }
+        dna
+    }
+
+    /// DNA for a child of parents with `father` and `mother`, either of
+    /// whom may be unknown, drifting up to `mutation` from what it inherits.
+    pub(crate) fn inherit<R: Rng>(
+        father: Option<&Dna>,
+        mother: Option<&Dna>,
+        mutation: u32,
+        rng: &mut R,
+    ) -> Self {
+        let mutation = mutation as i32;
+        let mut dna = Self::random(rng);
+        for (i, gene) in dna.colours.iter_mut().enumerate() {
+            let from = |parent: Option<&Dna>, random: [u8; 2], rng: &mut R| {
+                parent.map_or(random, |p| {
+                    let [x, y] = p.colours[i][rng.random_range(0..2)];
+                    [drift(x, mutation, rng), drift(y, mutation, rng)]
+                })
+            };
+            let father = from(father, gene[0], rng);
+            let mother = from(mother, gene[1], rng);
+            *gene = ordered(father, mother, rng);
+        }
+        for (i, gene) in dna.morphs.iter_mut().enumerate() {
+            let from = |parent: Option<&Dna>, random: u8, rng: &mut R| {
+                parent.map_or(random, |p| {
+                    drift(p.morphs[i][rng.random_range(0..2)], mutation, rng)
+                })
+            };
+            let father = from(father, gene[0], rng);
+            let mother = from(mother, gene[1], rng);
+            *gene = ordered(father, mother, rng);
+        }
+        dna
+    }
+
+    /// The DNA of the `genes` block of a DNA entry, if it has every gene
+    /// the generator writes. Other genes are ignored.
+    pub(crate) fn from_genes(genes: &Block) -> Option<Self> {
+        // The integers of a gene's values, skipping morph templates.
+        let values = |name: &str| -> Option<Vec<u8>> {
+            let values = genes.get(name)?.as_block()?.values();
+            let numbers = values.filter_map(|value| match value.as_scalar()?.value() {
+                Scalar::Integer(number) => Some(u8::try_from(*number).ok()),
+                _ => None,
+            });
+            numbers.collect()
+        };
+        let mut dna = Self {
+            colours: [[[0; 2]; 2]; COLOUR_GENES.len()],
+            morphs: [[0; 2]; MORPH_GENES.len()],
+        };
+        for (gene, name) in dna.colours.iter_mut().zip(COLOUR_GENES) {
+            let &[x1, y1, x2, y2] = values(name)?.as_slice() else {
+                return None;
+            };
+            *gene = [[x1, y1], [x2, y2]];
+        }
+        for (gene, (name, _)) in dna.morphs.iter_mut().zip(MORPH_GENES) {
+            let &[dominant, recessive] = values(name)?.as_slice() else {
+                return None;
+            };
+            *gene = [dominant, recessive];
+        }
+        Some(dna)
+    }
+
+    /// The `genes` block of a DNA entry, one gene a line.
+    fn genes(&self) -> Block {
+        let mut genes = Block::default();
+        for (name, [[x1, y1], [x2, y2]]) in COLOUR_GENES.iter().zip(self.colours) {
+            let mut values = Block::default();
+            for value in [x1, y1, x2, y2] {
+                values.push_value(Scalar::Integer(value.into()));
+            }
+            genes.push(name, values);
+        }
+        for (&(name, stem), [dominant, recessive]) in MORPH_GENES.iter().zip(self.morphs) {
+            let mut values = Block::default();
+            for value in [dominant, recessive] {
+                values.push_value(Scalar::Quoted(template(name, stem, value)));
+                values.push_value(Scalar::Integer(value.into()));
+            }
+            genes.push(name, values);
+        }
+        genes
+    }
+}
+
+/// The template of morph gene `name` for `value`.
+fn template(name: &str, stem: &str, value: u8) -> String {
+    if name == "gene_height" {
+        stem.to_string()
+    } else if value < 128 {
+        format!("{stem}_neg")
+    } else {
+        format!("{stem}_pos")
+    }
+}
+
+fn random_colour(rng: &mut impl Rng) -> [u8; 2] {
+    [rng.random(), rng.random()]
+}
+
+/// A morph value near the neutral face, from the average of two draws.
+fn random_morph(rng: &mut impl Rng) -> u8 {
+    let offset = rng.random_range(-RANDOM_MORPH_SPREAD..=RANDOM_MORPH_SPREAD)
+        + rng.random_range(-RANDOM_MORPH_SPREAD..=RANDOM_MORPH_SPREAD);
+    (128 + offset / 2) as u8
+}
+
+/// `value` moved by up to `mutation` either way.
+fn drift(value: u8, mutation: i32, rng: &mut impl Rng) -> u8 {
+    (i32::from(value) + rng.random_range(-mutation..=mutation)).clamp(0, 255) as u8
+}
+
+/// The values from each parent, the dominant one picked by a coin toss.
+fn ordered<T>(father: T, mother: T, rng: &mut impl Rng) -> [T; 2] {
+    if rng.random() {
+        [father, mother]
+    } else {
+        [mother, father]
+    }
+}
+
+/// The ID of `character`'s DNA entry.
+pub(crate) fn dna_id(character: &Character) -> String {
+    format!("{}_dna", character.id)
+}
+
+/// The contents of the DNA file for `characters`, skipping those without
+/// DNA.
+pub(crate) fn dna_entries<'a>(characters: impl IntoIterator<Item = &'a Character>) -> String {
+    let mut file = Block::default();
+    for character in characters {
+        let Some(dna) = &character.dna else {
+            continue;
+        };
+        let mut portrait = Block::default();
+        portrait.push("genes", dna.genes());
+        let mut entry = Block::default();
+        entry.push("portrait_info", portrait);
+        entry.push("enabled", Scalar::Bool(true));
+        file.push(&dna_id(character), entry);
+    }
+    paradox_script::to_string(&file)
+}
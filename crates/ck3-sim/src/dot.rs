@@ -0,0 +1,395 @@
+//! Drawing the generated dynasties as Graphviz graphs.
+//!
+//! [`output_dot`] reads an output directory, whichever engine wrote it, and
+//! gives a DOT graph of each dynasty in it: a box for every member, named and
+//! with the years of their life, an edge from each parent to each child and
+//! a dashed line between spouses, with spouses from outside the dynasty
+//! drawn apart. [`TreeOptions`] can cut a tree off after a number of
+//! generations, fill in the members who held a title in the title histories,
+//! and gather the members of each cadet house in a box of its own. Layout is
+//! left to Graphviz, whose `dot -Tsvg` draws a graph as an SVG.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use paradox_script::Date;
+use serde::Deserialize;
+
+use crate::error::SimError;
+use crate::localization::{dynasty_key, house_key, read_names};
+use crate::people::{read_house_dynasties, read_people, read_titles, Person};
+
+/// Fill of title holders' boxes.
+const RULER_COLOR: &str = "#f4c2c2";
+/// Outline of cadet house boxes.
+const HOUSE_COLOR: &str = "#5b7fa6";
+
+/// What the dynasty graphs show.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TreeOptions {
+    /// Generations drawn, counting the dynasty's founders as the first; all
+    /// of them if unset.
+    pub max_generations: Option<u32>,
+    /// Fill in the members who held a title.
+    pub highlight_rulers: bool,
+    /// Draw the members of each cadet house in a box of its own.
+    pub mark_cadet_houses: bool,
+}
+
+/// The DOT graph of each dynasty in the output directory `dir`, by dynasty
+/// ID, in the order the history first mentions them. Characters of no
+/// dynasty are only drawn as spouses.
+pub fn output_dot(dir: &Path, options: &TreeOptions) -> Result<Vec<(String, String)>, SimError> {
+    let people = read_people(dir)?;
+    let graphs = Graphs {
+        index: people
+            .iter()
+            .enumerate()
+            .map(|(i, person)| (person.id.as_str(), i))
+            .collect(),
+        house_dynasties: read_house_dynasties(dir),
+        rulers: if options.highlight_rulers {
+            read_rulers(dir)
+        } else {
+            HashSet::new()
+        },
+        names: read_names(dir),
+        people: &people,
+        options,
+    };
+
+    let mut dynasties: Vec<(String, Vec<usize>)> = Vec::new();
+    for (i, person) in people.iter().enumerate() {
+        let Some(dynasty) = person.lineage(&graphs.house_dynasties) else {
+            continue;
+        };
+        match dynasties.iter_mut().find(|(id, _)| id == dynasty) {
+            Some((_, members)) => members.push(i),
+            None => dynasties.push((dynasty.to_string(), vec![i])),
+        }
+    }
+    Ok(dynasties
+        .into_iter()
+        .map(|(dynasty, members)| {
+            let graph = graphs.graph(&dynasty, &members);
+            (dynasty, graph)
+        })
+        .collect())
+}
+
+struct Graphs<'a> {
+    people: &'a [Person],
+    index: HashMap<&'a str, usize>,
+    /// The dynasty of each cadet house.
+    house_dynasties: HashMap<String, String>,
+    rulers: HashSet<String>,
+    names: HashMap<String, String>,
+    options: &'a TreeOptions,
+}
+
+impl Graphs<'_> {
+    fn graph(&self, dynasty: &str, members: &[usize]) -> String {
+        let in_dynasty: HashSet<usize> = members.iter().copied().collect();
+        let parents = |i: usize| {
+            let person = &self.people[i];
+            [&person.father, &person.mother]
+                .into_iter()
+                .flatten()
+                .filter_map(|id| self.index.get(id.as_str()).copied())
+        };
+        let mut generations: HashMap<usize, u32> = HashMap::new();
+        let kept: Vec<usize> = members
+            .iter()
+            .copied()
+            .filter(|&i| {
+                let generation = generation(i, &in_dynasty, &parents, &mut generations);
+                self.options
+                    .max_generations
+                    .is_none_or(|max| generation < max)
+            })
+            .collect();
+        let members: HashSet<usize> = kept.iter().copied().collect();
+        let mut spouses = Vec::new();
+        let mut marriages = Vec::new();
+        for &i in &kept {
+            for (_, spouse) in &self.people[i].marriages {
+                let Some(&spouse) = self.index.get(spouse.as_str()) else {
+                    continue;
+                };
+                // Members past the last generation drawn are left out.
+                if in_dynasty.contains(&spouse) && !members.contains(&spouse) {
+                    continue;
+                }
+                if !members.contains(&spouse) && !spouses.contains(&spouse) {
+                    spouses.push(spouse);
+                }
+                // Both sides of a marriage may record it.
+                if !marriages.contains(&(spouse, i)) && !marriages.contains(&(i, spouse)) {
+                    marriages.push((i, spouse));
+                }
+            }
+        }
+
+        let title = self
+            .names
+            .get(&dynasty_key(dynasty))
+            .map_or(dynasty, String::as_str);
+        let mut out = String::new();
+        let mut line = |text: String| {
+            out.push_str(&text);
+            out.push('\n');
+        };
+        line(format!("digraph {} {{", quote(dynasty)));
+        line(format!("\tlabel={}", quote(title)));
+        line("\tlabelloc=t".into());
+        line("\tnode [shape=box, style=\"rounded,filled\", fillcolor=white]".into());
+
+        let mut drawn = kept.clone();
+        let mut houses: Vec<(&str, Vec<usize>)> = Vec::new();
+        if self.options.mark_cadet_houses {
+            drawn.retain(|&i| {
+                let Some(house) = self.people[i].house.as_deref() else {
+                    return true;
+                };
+                if self
+                    .house_dynasties
+                    .get(house)
+                    .is_none_or(|of| of != dynasty)
+                {
+                    return true;
+                }
+                match houses.iter_mut().find(|(id, _)| *id == house) {
+                    Some((_, members)) => members.push(i),
+                    None => houses.push((house, vec![i])),
+                }
+                false
+            });
+        }
+        for &i in &drawn {
+            line(format!("\t{}", self.node(i, "")));
+        }
+        for (house, members) in &houses {
+            let name = self
+                .names
+                .get(&house_key(house))
+                .map_or(*house, String::as_str);
+            line(format!(
+                "\tsubgraph {} {{",
+                quote(&format!("cluster_{house}"))
+            ));
+            line(format!("\t\tlabel={}", quote(name)));
+            line(format!("\t\tstyle=dashed\n\t\tcolor=\"{HOUSE_COLOR}\""));
+            for &i in members {
+                line(format!("\t\t{}", self.node(i, "")));
+            }
+            line("\t}".into());
+        }
+        for &i in &spouses {
+            line(format!(
+                "\t{}",
+                self.node(i, ", style=\"rounded,filled,dashed\"")
+            ));
+        }
+
+        for (a, b) in marriages {
+            let (a, b) = (&self.people[a].id, &self.people[b].id);
+            line(format!(
+                "\t{} -> {} [dir=none, style=dashed]",
+                quote(a),
+                quote(b)
+            ));
+        }
+        let shown: HashSet<usize> = members.iter().chain(&spouses).copied().collect();
+        for &child in &kept {
+            for parent in parents(child).filter(|parent| shown.contains(parent)) {
+                line(format!(
+                    "\t{} -> {}",
+                    quote(&self.people[parent].id),
+                    quote(&self.people[child].id)
+                ));
+            }
+        }
+        line("}".into());
+        out
+    }
+
+    /// The node statement of character `i`, with any more `attributes`.
+    fn node(&self, i: usize, attributes: &str) -> String {
+        let person = &self.people[i];
+        let year = |date: Option<Date>| date.map_or("?".to_string(), |date| date.year.to_string());
+        let life = match person.death {
+            Some(death) => format!("{} – {}", year(person.birth), year(Some(death))),
+            None => format!("b. {}", year(person.birth)),
+        };
+        let mut node = format!(
+            "{} [label={}",
+            quote(&person.id),
+            quote(&format!("{}\n{life}", person.name))
+        );
+        if self.rulers.contains(&person.id) {
+            write!(node, ", fillcolor=\"{RULER_COLOR}\"").expect("writing to a String cannot fail");
+        }
+        node.push_str(attributes);
+        node.push(']');
+        node
+    }
+}
+
+/// The generation of character `i` within a dynasty, from 0 for a member
+/// with no parent in it.
+fn generation<I: Iterator<Item = usize>>(
+    i: usize,
+    in_dynasty: &HashSet<usize>,
+    parents: &impl Fn(usize) -> I,
+    generations: &mut HashMap<usize, u32>,
+) -> u32 {
+    if let Some(&generation) = generations.get(&i) {
+        return generation;
+    }
+    // Counted as a founder while their parents are, in case of a loop.
+    generations.insert(i, 0);
+    let generation = parents(i)
+        .filter(|parent| in_dynasty.contains(parent))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|parent| generation(parent, in_dynasty, parents, generations) + 1)
+        .max()
+        .unwrap_or(0);
+    generations.insert(i, generation);
+    generation
+}
+
+/// Every character who holds a title in the output's title histories.
+fn read_rulers(dir: &Path) -> HashSet<String> {
+    read_titles(dir)
+        .into_iter()
+        .flat_map(|(_, holders)| holders)
+        .filter_map(|(_, holder)| holder)
+        .collect()
+}
+
+/// `text` as a quoted DOT ID.
+fn quote(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::testing;
+
+    /// The graphs of [`testing::FAMILY`] drawn with `options`.
+    fn graphs(name: &str, options: &TreeOptions) -> Vec<(String, String)> {
+        let dir = testing::family(name);
+        let graphs = output_dot(&dir, options).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        graphs
+    }
+
+    const DYNASTY_B: &str = "\
+digraph \"dynasty_b\" {
+	label=\"Bregol\"
+	labelloc=t
+	node [shape=box, style=\"rounded,filled\", fillcolor=white]
+	\"lineofb1\" [label=\"Beren\\nb. 6002\"]
+}
+";
+
+    #[test]
+    fn each_dynasty_is_a_graph_of_its_members_and_their_spouses() {
+        assert_eq!(
+            graphs("dot", &TreeOptions::default()),
+            [
+                (
+                    "dynasty_a".to_string(),
+                    "\
+digraph \"dynasty_a\" {
+	label=\"Alda\"
+	labelloc=t
+	node [shape=box, style=\"rounded,filled\", fillcolor=white]
+	\"lineofa1\" [label=\"Aldor\\n6000 – 6060\"]
+	\"lineofa2\" [label=\"Anborn\\nb. 6026\"]
+	\"lineofb1\" [label=\"Beren\\nb. 6002\", style=\"rounded,filled,dashed\"]
+	\"lineofa1\" -> \"lineofb1\" [dir=none, style=dashed]
+	\"lineofa1\" -> \"lineofa2\"
+	\"lineofb1\" -> \"lineofa2\"
+}
+"
+                    .to_string()
+                ),
+                ("dynasty_b".to_string(), DYNASTY_B.to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn trees_can_be_cut_off_and_rulers_filled_in() {
+        let options = TreeOptions {
+            max_generations: Some(1),
+            highlight_rulers: true,
+            ..TreeOptions::default()
+        };
+        assert_eq!(
+            graphs("dot-options", &options),
+            [
+                (
+                    "dynasty_a".to_string(),
+                    format!(
+                        "\
+digraph \"dynasty_a\" {{
+	label=\"Alda\"
+	labelloc=t
+	node [shape=box, style=\"rounded,filled\", fillcolor=white]
+	\"lineofa1\" [label=\"Aldor\\n6000 – 6060\", fillcolor=\"{RULER_COLOR}\"]
+	\"lineofb1\" [label=\"Beren\\nb. 6002\", style=\"rounded,filled,dashed\"]
+	\"lineofa1\" -> \"lineofb1\" [dir=none, style=dashed]
+}}
+"
+                    )
+                ),
+                ("dynasty_b".to_string(), DYNASTY_B.to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn cadet_houses_can_be_boxed() {
+        let options = TreeOptions {
+            mark_cadet_houses: true,
+            ..TreeOptions::default()
+        };
+        let graphs = graphs("dot-houses", &options);
+        assert_eq!(
+            graphs[0].1,
+            format!(
+                "\
+digraph \"dynasty_a\" {{
+	label=\"Alda\"
+	labelloc=t
+	node [shape=box, style=\"rounded,filled\", fillcolor=white]
+	\"lineofa1\" [label=\"Aldor\\n6000 – 6060\"]
+	subgraph \"cluster_house_a_cadet\" {{
+		label=\"Anbornion\"
+		style=dashed
+		color=\"{HOUSE_COLOR}\"
+		\"lineofa2\" [label=\"Anborn\\nb. 6026\"]
+	}}
+	\"lineofb1\" [label=\"Beren\\nb. 6002\", style=\"rounded,filled,dashed\"]
+	\"lineofa1\" -> \"lineofb1\" [dir=none, style=dashed]
+	\"lineofa1\" -> \"lineofa2\"
+	\"lineofb1\" -> \"lineofa2\"
+}}
+"
+            )
+        );
+    }
+}
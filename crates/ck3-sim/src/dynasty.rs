@@ -0,0 +1,69 @@
+//! Dynasty rules shared by the simulation steps.
+
+use std::collections::BTreeMap;
+
+use ck3_config::{Dynasty, GenderLaw};
+use serde::Serialize;
+
+/// Whether the law passes the dynasty through men.
+pub(crate) fn patrilineal(law: GenderLaw) -> bool {
+    matches!(law, GenderLaw::Agnatic | GenderLaw::AgnaticCognatic)
+}
+
+/// Whether the law passes the dynasty through women.
+pub(crate) fn matrilineal(law: GenderLaw) -> bool {
+    matches!(law, GenderLaw::Enatic | GenderLaw::EnaticCognatic)
+}
+
+/// The part of a dynasty ID used in its members' character IDs: the second
+/// `_`-separated word, or the whole ID if it has none. Lowborn characters
+/// use `lowborn`.
+pub(crate) fn id_prefix(dynasty: Option<&str>) -> &str {
+    match dynasty {
+        Some(id) => id.split('_').nth(1).unwrap_or(id),
+        None => "lowborn",
+    }
+}
+
+/// A language the dynasty speaks between two years, inclusive.
+pub(crate) struct LanguageRule {
+    pub language: String,
+    pub start: i32,
+    pub end: i32,
+}
+
+/// The well-formed `"language_id,start_year,end_year"` entries of `dynasty`.
+pub(crate) fn language_rules(dynasty: &Dynasty) -> Vec<LanguageRule> {
+    dynasty
+        .languages
+        .iter()
+        .filter_map(|spec| {
+            let parts: Vec<&str> = spec.split(',').map(str::trim).collect();
+            let [language, start, end] = parts[..] else {
+                return None;
+            };
+            Some(LanguageRule {
+                language: language.to_string(),
+                start: start.parse().ok()?,
+                end: end.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// How a dynasty fared in a run.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DynastySummary {
+    pub dynasty_id: String,
+    /// Characters ever born into or adopted by the dynasty.
+    pub members: usize,
+    /// Members alive at the end of the simulated period.
+    pub living_at_end: usize,
+    /// Heirs adopted to keep the dynasty from dying out.
+    pub adopted: usize,
+    /// Marriages of members with members of each other dynasty, by its ID.
+    pub alliances: BTreeMap<String, usize>,
+    /// Cadet houses split off the dynasty.
+    pub houses: usize,
+}
@@ -0,0 +1,68 @@
+//! Editing characters of an output directory by hand.
+//!
+//! [`edit_character`] changes one of the fields of a character's definition
+//! in `family_history.txt` that are a single value, its name, culture or
+//! religion, where the value stands, so the rest of the file, comments and
+//! layout included, is left as generated. It returns the value it replaced,
+//! which edits it again back to what it was.
+
+use std::fs;
+use std::path::Path;
+
+use paradox_script::{rewrite_words, Word};
+
+use crate::error::SimError;
+use crate::export::HISTORY_FILE;
+
+/// Fields of a character that [`edit_character`] changes.
+pub const EDITABLE_FIELDS: [&str; 3] = ["name", "culture", "religion"];
+
+/// Set the field `field` of the character `id` in the history in `dir` to
+/// `value`, and return the value it had.
+pub fn edit_character(dir: &Path, id: &str, field: &str, value: &str) -> Result<String, SimError> {
+    let edit_error = |message: String| SimError::History { message };
+    if !EDITABLE_FIELDS.contains(&field) {
+        return Err(edit_error(format!(
+            "the {field} of a character cannot be edited"
+        )));
+    }
+    // Only the name is quoted; the others are keys of the game's data.
+    let is_word = !value.is_empty()
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || "{}=<>!\"#".contains(c));
+    if field != "name" && !is_word {
+        return Err(edit_error(format!("{value:?} is not a {field}")));
+    }
+
+    let path = dir.join(HISTORY_FILE);
+    let bytes = fs::read(&path).map_err(|source| SimError::Io {
+        path: path.clone(),
+        source,
+    })?;
+    let mut in_character = false;
+    let mut old = None;
+    let text = rewrite_words(&String::from_utf8_lossy(&bytes), |word: &Word| {
+        if word.depth == 0 && word.is_key {
+            in_character = word.text == id;
+            return None;
+        }
+        if !in_character || old.is_some() || word.depth != 1 || word.key != Some(field) {
+            return None;
+        }
+        if field == "name" && !word.quoted && !is_word {
+            // Written bare, the name cannot hold what the new one does.
+            return None;
+        }
+        old = Some(word.text.to_string());
+        Some(value.to_string())
+    })
+    .map_err(|err| edit_error(format!("the {HISTORY_FILE} file cannot be read: {err}")))?;
+    let old = old.ok_or_else(|| {
+        edit_error(format!(
+            "{id} is not defined with a {field} that can be edited in {HISTORY_FILE}"
+        ))
+    })?;
+    paradox_script::write_file(&path, &text).map_err(|source| SimError::Io { path, source })?;
+    Ok(old)
+}
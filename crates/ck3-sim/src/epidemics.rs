@@ -0,0 +1,50 @@
+//! Waves of plague.
+//!
+//! An epidemic strikes the members of its dynasties, and the lowborn married
+//! into them, who are of its cultures, for as long as it lasts. Those it
+//! strikes have their yearly chance of death scaled by its lethality at
+//! their effective age, and whoever dies that year dies of it.
+
+use crate::character::CharacterId;
+use crate::simulation::Simulation;
+
+impl Simulation {
+    /// The factor on the chance of death of `id` at `effective_age` during
+    /// `year` from the epidemics striking them, and the death reason of the
+    /// last of them, or `None` if none strikes them.
+    pub(crate) fn epidemic_lethality(
+        &self,
+        id: CharacterId,
+        year: i32,
+        effective_age: i32,
+    ) -> Option<(f64, String)> {
+        let character = self.character(id);
+        let dynasty = character.dynasty.as_deref().or_else(|| {
+            let spouse = self.character(character.spouse?);
+            spouse.dynasty.as_deref()
+        });
+        let (mut multiplier, mut reason) = (1.0, None);
+        for epidemic in &self.config.life_stages.epidemics {
+            if !(epidemic.start_year..=epidemic.end_year).contains(&year) {
+                continue;
+            }
+            let in_dynasties = epidemic.dynasties.is_empty()
+                || dynasty.is_some_and(|d| epidemic.dynasties.iter().any(|id| id == d));
+            let in_cultures =
+                epidemic.cultures.is_empty() || epidemic.cultures.contains(&character.culture);
+            if !in_dynasties || !in_cultures {
+                continue;
+            }
+            let Some(band) = epidemic
+                .lethality
+                .iter()
+                .find(|band| (band.from_age as i32..=band.to_age as i32).contains(&effective_age))
+            else {
+                continue;
+            };
+            multiplier *= band.multiplier;
+            reason = Some(epidemic.death_reason.clone());
+        }
+        reason.map(|reason| (multiplier, reason))
+    }
+}
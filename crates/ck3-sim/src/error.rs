@@ -0,0 +1,64 @@
+//! Errors that stop a simulation before it starts.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use ck3_config::ConfigError;
+
+#[derive(Debug)]
+pub enum SimError {
+    /// A config file could not be read.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A config file is malformed; every problem is listed.
+    Config {
+        file: String,
+        errors: Vec<ConfigError>,
+    },
+    /// A history file to continue from cannot be read as one.
+    History { message: String },
+    /// A saved simulation state cannot be loaded.
+    State { message: String },
+    /// A game installation or mod lacks what is needed of it.
+    Game { message: String },
+    /// IDs cannot be renumbered as asked.
+    Ids { message: String },
+    /// A plugin script does not compile.
+    Plugin { message: String },
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimError::Io { path, source } => write!(f, "cannot read {}: {source}", path.display()),
+            SimError::Config { file, errors } => {
+                write!(f, "{file} is invalid")?;
+                for error in errors {
+                    write!(f, "\n  {error}")?;
+                }
+                Ok(())
+            }
+            SimError::History { message }
+            | SimError::State { message }
+            | SimError::Game { message }
+            | SimError::Ids { message }
+            | SimError::Plugin { message } => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for SimError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SimError::Io { source, .. } => Some(source),
+            SimError::Config { .. }
+            | SimError::History { .. }
+            | SimError::State { .. }
+            | SimError::Game { .. }
+            | SimError::Ids { .. }
+            | SimError::Plugin { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,428 @@
+//! Writing the simulated characters as a CK3 character history file.
+//!
+//! The layout follows the engine's `family_history.txt`: characters grouped
+//! by dynasty under a banner comment, lowborn spouses with the dynasty they
+//! married into, and each character's events in dated blocks. Genetic traits
+//! a character only carries are made inactive at birth, and childhood traits
+//! can be removed again on coming of age, and members of cadet houses are
+//! written with their house. Characters keep the faith they were born into
+//! at the top of their block and change it in dated `faith` entries, and
+//! refer to their portrait DNA if they have any. The file opens with the seed
+//! of the run, so that it can be reproduced. Every file is written with the
+//! byte order mark the game needs to read names outside ASCII. A config
+//! targeting a game's data leaves out the traits that game does not define,
+//! and one written for an older game version the effects it lacks: languages
+//! before 1.5 and memories before 1.9. Dates are written in the configured
+//! calendar. Each dynasty's characters are built as script blocks and
+//! written by `paradox_script`, in the layout of the game's own files.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ck3_config::Feature;
+use paradox_script::{Block, Scalar};
+
+use crate::bookmarks::write_snapshots;
+use crate::character::{Character, CharacterId, EventKind, Sex, CHILDHOOD_TRAITS, SKILLS};
+use crate::definitions::DYNASTIES_FILE;
+use crate::dna::{dna_entries, dna_id, DNA_FILE};
+use crate::houses::HOUSES_FILE;
+use crate::localization::write_localization;
+use crate::simulation::{swaps_childhood_traits, Simulation};
+use crate::titles::write_title_histories;
+
+/// Name of the character history file.
+pub const HISTORY_FILE: &str = "family_history.txt";
+/// Start of the history file's first line, followed by the run's seed.
+const SEED_HEADER: &str = "# Seed: ";
+/// Group of lowborn characters not married into a dynasty.
+pub(crate) const LOWBORN: &str = "Lowborn";
+
+impl Simulation {
+    /// Write the history file, the dynasty and cadet house definitions, the
+    /// portrait DNA, the localisation files, the title history and the bookmark
+    /// snapshots to
+    /// `dir`, creating the directories if needed.
+    pub fn write_history(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        paradox_script::write_file(&dir.join(HISTORY_FILE), &self.history())?;
+        paradox_script::write_file(&dir.join(DYNASTIES_FILE), &self.dynasty_definitions())?;
+        paradox_script::write_file(&dir.join(HOUSES_FILE), &self.house_definitions())?;
+        paradox_script::write_file(&dir.join(DNA_FILE), &self.dna_entries())?;
+        write_localization(dir, &self.localization_files())?;
+        let init = &self.config.initialization;
+        write_title_histories(dir, init).map_err(io::Error::other)?;
+        write_snapshots(dir, &init.bookmark_snapshots).map_err(io::Error::other)
+    }
+
+    /// The contents of the portrait DNA file.
+    pub(crate) fn dna_entries(&self) -> String {
+        dna_entries(&self.characters)
+    }
+
+    /// The contents of the history file.
+    pub fn history(&self) -> String {
+        assemble_history(self.seed(), &self.history_sections())
+    }
+
+    /// The history file's dynasty sections, banner included, by dynasty ID.
+    pub(crate) fn history_sections(&self) -> BTreeMap<String, String> {
+        let mut groups: BTreeMap<&str, Vec<&Character>> = BTreeMap::new();
+        for (_, character) in self.characters() {
+            let group = character
+                .dynasty
+                .as_deref()
+                .or_else(|| {
+                    let spouse = self.character(character.spouse?);
+                    spouse.dynasty.as_deref()
+                })
+                .unwrap_or(LOWBORN);
+            groups.entry(group).or_default().push(character);
+        }
+
+        let mut sections = BTreeMap::new();
+        for (group, mut characters) in groups {
+            let mut out = String::new();
+            out.push_str("################\n");
+            let _ = writeln!(out, "### Dynasty {group}");
+            out.push_str("################\n\n");
+            characters.sort_by_key(|c| id_number(&c.id));
+            let mut file = Block::default();
+            for character in characters {
+                self.push_character(&mut file, character);
+            }
+            out.push_str(&paradox_script::to_string(&file));
+            out.push('\n');
+            sections.insert(group.to_string(), out);
+        }
+        sections
+    }
+
+    /// Add the block of `c` to `file`, after a comment for each plugin that
+    /// failed on it.
+    fn push_character(&self, file: &mut Block, c: &Character) {
+        let output = self.output_hooks(c);
+        let c = output.character.as_ref();
+        for failure in &output.failures {
+            file.push_comment(format!("Plugin failed: {failure}"));
+        }
+        let id = |index: CharacterId| Scalar::Word(self.character(index).id.clone());
+        let mut block = Block::default();
+        block.push("name", Scalar::Word(c.name.clone()));
+        if c.sex == Sex::Female {
+            block.push("female", Scalar::Bool(true));
+        }
+        block.push("culture", Scalar::Word(c.culture.clone()));
+        block.push("religion", Scalar::Word(c.birth_religion().to_string()));
+
+        if let Some(house) = &c.house {
+            block.push("dynasty_house", Scalar::Word(house.clone()));
+        } else if let Some(dynasty) = &c.dynasty {
+            let key = if c.is_house {
+                "dynasty_house"
+            } else {
+                "dynasty"
+            };
+            block.push(key, Scalar::Word(dynasty.clone()));
+        }
+        // Adopted characters get their parents from the adoption block.
+        if !c.is_adopted {
+            if let Some(father) = c.father {
+                block.push("father", id(father));
+            }
+            if let Some(mother) = c.mother {
+                block.push("mother", id(mother));
+            }
+        }
+
+        block.push("sexuality", Scalar::Word(c.sexuality.clone()));
+        if c.dna.is_some() {
+            block.push("dna", Scalar::Word(dna_id(c)));
+        }
+        for (skill, level) in SKILLS.iter().zip(c.skills) {
+            block.push(skill, Scalar::Integer(level.into()));
+        }
+        // Traits the target game does not define are left out throughout.
+        let emits = |name: &str| self.config.emits_trait(name);
+        let push_trait = |block: &mut Block, name: &str| {
+            block.push("trait", Scalar::Word(name.to_string()));
+        };
+        for name in c.traits.iter().filter(|name| emits(name)) {
+            push_trait(&mut block, name);
+        }
+        let education = c
+            .education
+            .as_ref()
+            .map(|education| education.trait_name())
+            .filter(|name| emits(name));
+        if let Some(education) = education {
+            push_trait(&mut block, &education);
+        }
+        for &name in c.congenital_traits.iter().filter(|name| emits(name)) {
+            push_trait(&mut block, name);
+        }
+        let blood = format!("blood_of_numenor_{}", c.blood_tier);
+        if (1..=10).contains(&c.blood_tier) && emits(&blood) {
+            push_trait(&mut block, &blood);
+        }
+
+        let mut events: Vec<_> = c.events.iter().collect();
+        events.sort_by_key(|event| event.date);
+        // Childhood traits are only written for a character still a child at
+        // the time, and then as plain traits rather than dated ones.
+        let calendar = &self.config.initialization.calendar;
+        let mut childhood = Vec::new();
+        let mut dated = Block::default();
+        let languages = self.config.supports(Feature::Languages);
+        for event in events {
+            let age = c.birth.years_until(event.date);
+            let date = event.date;
+            // Ages and rules go by the simulation's date, the file by the
+            // calendar's.
+            let written = date.to_calendar(calendar).to_string();
+            let mut entry = Block::default();
+            let mut effect = Block::default();
+            match &event.kind {
+                EventKind::Birth => {
+                    entry.push("birth", Scalar::Bool(true));
+                    // Languages known from birth are learned with it.
+                    if languages {
+                        for language in c.languages(c.birth) {
+                            effect.push("learn_language", Scalar::Word(language.to_string()));
+                        }
+                    }
+                    for &name in c.inactive_traits.iter().filter(|name| emits(name)) {
+                        effect.push("make_trait_inactive", Scalar::Word(name.to_string()));
+                    }
+                }
+                EventKind::LearnLanguage { .. } if date == c.birth || !languages => continue,
+                EventKind::LearnLanguage { language } => {
+                    effect.push("learn_language", Scalar::Word(language.clone()));
+                }
+                EventKind::Conversion { to, .. } => {
+                    entry.push("faith", Scalar::Word(to.clone()));
+                }
+                EventKind::Traits(traits) => {
+                    let (young, adult): (Vec<&String>, Vec<&String>) = traits
+                        .iter()
+                        .filter(|name| emits(name))
+                        .partition(|name| CHILDHOOD_TRAITS.contains(&name.as_str()));
+                    if age < 16 {
+                        childhood.extend(young);
+                    }
+                    if adult.is_empty() {
+                        continue;
+                    }
+                    for name in adult {
+                        push_trait(&mut entry, name);
+                    }
+                    if age >= 16 && swaps_childhood_traits(&self.config, date) {
+                        let grown_out_of = c
+                            .events
+                            .iter()
+                            .filter_map(|event| match &event.kind {
+                                EventKind::Traits(traits) => Some(traits),
+                                _ => None,
+                            })
+                            .flatten()
+                            .filter(|name| CHILDHOOD_TRAITS.contains(&name.as_str()))
+                            .filter(|name| emits(name));
+                        for name in grown_out_of {
+                            effect.push("remove_trait", Scalar::Word(name.clone()));
+                        }
+                    }
+                }
+                EventKind::Marriage {
+                    spouse,
+                    matrilineal,
+                } => {
+                    let key = if *matrilineal {
+                        "add_matrilineal_spouse"
+                    } else {
+                        "add_spouse"
+                    };
+                    dated.push_comment(format!("Married at age {age}"));
+                    entry.push(key, id(*spouse));
+                }
+                EventKind::Concubinage { concubine } => {
+                    dated.push_comment(format!("Took a concubine at age {age}"));
+                    entry.push("add_concubine", id(*concubine));
+                }
+                EventKind::Death { reason } => {
+                    dated.push_comment(format!("Died at age {age}"));
+                    let mut death = Block::default();
+                    death.push("death_reason", Scalar::Word(reason.clone()));
+                    entry.push("death", death);
+                }
+                EventKind::Legitimization => {
+                    let (added, removed) = (emits("legitimized_bastard"), emits("bastard"));
+                    if !added && !removed {
+                        continue;
+                    }
+                    dated.push_comment(format!("Legitimized at age {age}"));
+                    if added {
+                        push_trait(&mut entry, "legitimized_bastard");
+                    }
+                    if removed {
+                        effect.push("remove_trait", Scalar::Word("bastard".into()));
+                    }
+                }
+                EventKind::Adoption { .. } => continue,
+            }
+            if !effect.items.is_empty() {
+                entry.push("effect", effect);
+            }
+            dated.push(&written, entry);
+        }
+        for name in childhood {
+            push_trait(&mut block, name);
+        }
+        block.items.extend(dated.items);
+        if !output.effects.is_empty() {
+            self.plugin_effects(&mut block, c, &output.effects);
+        }
+        if c.is_adopted {
+            self.adoption_block(&mut block, c);
+        }
+        file.push(&c.id, block);
+    }
+
+    /// The effect block on the birthday of `c` holding the `effects` its
+    /// plugins added. An effect that is not script is left out, with a
+    /// comment saying so.
+    fn plugin_effects(&self, block: &mut Block, c: &Character, effects: &[String]) {
+        let mut effect = Block::default();
+        for line in effects {
+            match paradox_script::parse(line) {
+                Ok(parsed) => effect.items.extend(parsed.items),
+                Err(err) => effect.push_comment(format!("Plugin effect left out: {line}: {err}")),
+            }
+        }
+        let mut entry = Block::default();
+        entry.push("effect", effect);
+        let written = c.birth.to_calendar(&self.config.initialization.calendar);
+        block.push_comment("Added by plugins");
+        block.push(&written.to_string(), entry);
+    }
+
+    /// The effect block recording an adoption: the adoptive parent and a
+    /// `was_adopted` memory, if the game has memories.
+    fn adoption_block(&self, block: &mut Block, c: &Character) {
+        let Some((date, adopter)) = c.events.iter().find_map(|event| match event.kind {
+            EventKind::Adoption { adopter } => Some((event.date, adopter)),
+            _ => None,
+        }) else {
+            return;
+        };
+        // A ward of the dynasty nobody took in has no one to remember, nor
+        // anything else to record.
+        let Some(parent) = adopter.or(c.father).or(c.mother) else {
+            return;
+        };
+        let memories = self.config.supports(Feature::Memories);
+        if !memories && c.father.is_none() && c.mother.is_none() {
+            return;
+        }
+        let id = |index: CharacterId| Scalar::Word(self.character(index).id.clone());
+        let mut effect = Block::default();
+        if let Some(father) = c.father {
+            effect.push("set_father", id(father));
+        }
+        if let Some(mother) = c.mother {
+            effect.push("set_mother", id(mother));
+        }
+        if memories {
+            let mut participants = Block::default();
+            participants.push("parent", id(parent));
+            let mut memory = Block::default();
+            memory.push("type", Scalar::Word("was_adopted".into()));
+            memory.push("participants", participants);
+            effect.push("create_character_memory", memory);
+        }
+        let mut entry = Block::default();
+        entry.push("effect", effect);
+        let date = date.to_calendar(&self.config.initialization.calendar);
+        block.push_comment("Adopted");
+        block.push(&date.to_string(), entry);
+    }
+}
+
+/// A history file made of `sections`, in order, under the seed header.
+pub(crate) fn assemble_history(seed: u64, sections: &BTreeMap<String, String>) -> String {
+    let mut out = format!("{SEED_HEADER}{seed}\n\n");
+    for section in sections.values() {
+        out.push_str(section);
+    }
+    out
+}
+
+/// The seed recorded in the header of a history file, if it has one.
+pub fn history_seed(history: &str) -> Option<u64> {
+    let history = history.strip_prefix(paradox_script::BOM).unwrap_or(history);
+    history
+        .lines()
+        .next()?
+        .strip_prefix(SEED_HEADER)?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// The digits of a character ID as a number, for ordering within a dynasty.
+fn id_number(id: &str) -> u64 {
+    id.chars()
+        .filter(char::is_ascii_digit)
+        .collect::<String>()
+        .parse()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn history_is_laid_out_like_the_game_files() {
+        let history = testing::run(7).history();
+        assert_eq!(history_seed(&history), Some(7));
+        for line in history.lines() {
+            assert_eq!(line, line.trim_end(), "trailing whitespace: {line:?}");
+            let indent = &line[..line.len() - line.trim_start().len()];
+            assert!(indent.chars().all(|c| c == '\t'), "not tabbed: {line:?}");
+        }
+    }
+
+    #[test]
+    fn history_round_trips_through_the_parser() {
+        let run = testing::run(7);
+        for (group, section) in run.history_sections() {
+            let body: String = section
+                .lines()
+                .skip_while(|line| line.starts_with('#') || line.is_empty())
+                .map(|line| format!("{line}\n"))
+                .collect();
+            let parsed = paradox_script::parse(&body).unwrap();
+            let written = paradox_script::to_string(&parsed);
+            // Parsing drops the comments, and nothing else.
+            let uncommented: String = body
+                .lines()
+                .filter(|line| !line.trim_start().starts_with('#'))
+                .map(|line| format!("{line}\n"))
+                .collect();
+            assert_eq!(
+                written.trim_end(),
+                uncommented.trim_end(),
+                "dynasty {group}"
+            );
+        }
+    }
+
+    #[test]
+    fn history_is_reproducible() {
+        assert_eq!(testing::run(11).history(), testing::run(11).history());
+    }
+}
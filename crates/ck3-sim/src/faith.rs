@@ -0,0 +1,59 @@
+//! Dynasties converting to new faiths over the years.
+//!
+//! A dynasty's `conversions` each name a faith and the span of years the
+//! dynasty takes to adopt it. Every year of the span, each living member not
+//! yet of the faith converts with a chance that rises to certainty in its
+//! last year, so conversions spread over the span rather than landing all
+//! at once, and children born in the meantime follow the faith of the parent
+//! they take their dynasty from.
+
+use rand::Rng;
+
+use crate::character::{CharacterId, EventKind};
+use crate::date::Date;
+use crate::simulation::Simulation;
+
+impl Simulation {
+    /// Roll the conversions of every dynasty converting in `year`.
+    pub(crate) fn convert_faiths(&mut self, year: i32) {
+        let converting: Vec<(String, String, i32)> = self
+            .config
+            .initialization
+            .dynasties
+            .iter()
+            .flat_map(|d| {
+                d.conversions
+                    .iter()
+                    .filter(|c| (c.start_year..=c.end_year).contains(&year))
+                    .map(|c| (d.dynasty_id.clone(), c.faith_id.clone(), c.end_year))
+            })
+            .collect();
+        for (dynasty, faith, end_year) in converting {
+            // One in the number of years left, so no one is left by the end.
+            let chance = 1.0 / f64::from(end_year - year + 1);
+            for id in self.ids().collect::<Vec<_>>() {
+                let c = self.character(id);
+                if !c.is_alive() || c.dynasty.as_deref() != Some(dynasty.as_str()) {
+                    continue;
+                }
+                if c.religion != faith && self.rng.random::<f64>() < chance {
+                    self.convert(id, &faith, year);
+                }
+            }
+        }
+    }
+
+    fn convert(&mut self, id: CharacterId, faith: &str, year: i32) {
+        let date = Date::random_in(year, &mut self.rng);
+        let character = self.character_mut(id);
+        let date = date.max(character.birth);
+        let from = std::mem::replace(&mut character.religion, faith.to_string());
+        character.add_event(
+            date,
+            EventKind::Conversion {
+                from,
+                to: faith.to_string(),
+            },
+        );
+    }
+}
@@ -0,0 +1,328 @@
+//! The traits, cultures, faiths, dynasties and houses a game defines.
+//!
+//! The generator can be pointed at a CK3 installation or at a total
+//! conversion mod, whose `common/traits`, `common/culture/cultures`,
+//! `common/religion/religions`, `common/dynasties` and
+//! `common/dynasty_houses` define what a history may refer to. Every
+//! top-level block of those files is a definition, except in religion files,
+//! where the faiths are the blocks under each religion's `faiths`. A config using
+//! anything else is flagged by [`GameData::check`], and once a config is
+//! given the game's data, histories leave out the traits it does not define.
+//! A category whose directory is missing, as in a mod that only adds
+//! cultures, is not checked. The trait definitions also tell which traits
+//! cannot go together: those a trait lists as `opposites`, and two of the
+//! same `group`, such as two tiers of one genetic trait.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ck3_config::ConfigError;
+use paradox_script::{Block, Scalar, Value};
+
+use crate::config::{SimConfig, INITIALIZATION_FILE, LIFE_STAGES_FILE, SKILLS_AND_TRAITS_FILE};
+use crate::error::SimError;
+
+/// Directories of the definitions, relative to the `common` directory.
+const TRAITS_DIR: &str = "traits";
+const CULTURES_DIR: &str = "culture/cultures";
+const RELIGIONS_DIR: &str = "religion/religions";
+const DYNASTIES_DIR: &str = "dynasties";
+const HOUSES_DIR: &str = "dynasty_houses";
+
+/// The IDs a game defines, each category `None` if it defines none.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GameData {
+    traits: Option<BTreeSet<String>>,
+    cultures: Option<BTreeSet<String>>,
+    faiths: Option<BTreeSet<String>>,
+    dynasties: Option<BTreeSet<String>>,
+    houses: Option<BTreeSet<String>>,
+    /// What the game says of each trait it defines.
+    trait_rules: BTreeMap<String, TraitRule>,
+    /// Files that could not be read, each with why.
+    errors: Vec<String>,
+}
+
+impl GameData {
+    /// Read the definitions of the installation or mod in `dir`. Files that
+    /// cannot be read or parsed are skipped and listed in
+    /// [`errors`](GameData::errors).
+    pub fn load(dir: &Path) -> Self {
+        let common = common_dir(dir);
+        let mut game = Self::default();
+        let mut trait_rules = BTreeMap::new();
+        game.traits = game.read_ids(&common.join(TRAITS_DIR), |file, ids| {
+            for entry in file.entries() {
+                let (Scalar::Word(name) | Scalar::Quoted(name), Some(definition)) =
+                    (&entry.key, entry.value.as_block())
+                else {
+                    continue;
+                };
+                trait_rules.insert(name.clone(), TraitRule::of(definition));
+                ids.insert(name.clone());
+            }
+        });
+        game.trait_rules = trait_rules;
+        game.cultures = game.read_ids(&common.join(CULTURES_DIR), |file, ids| {
+            ids.extend(blocks(file).map(str::to_string));
+        });
+        game.faiths = game.read_ids(&common.join(RELIGIONS_DIR), |file, ids| {
+            for religion in file.entries().filter_map(|entry| entry.value.as_block()) {
+                if let Some(faiths) = religion.get("faiths").and_then(|faiths| faiths.as_block()) {
+                    ids.extend(blocks(faiths).map(str::to_string));
+                }
+            }
+        });
+        game.dynasties = game.read_ids(&common.join(DYNASTIES_DIR), |file, ids| {
+            ids.extend(blocks(file).map(str::to_string));
+        });
+        game.houses = game.read_ids(&common.join(HOUSES_DIR), |file, ids| {
+            ids.extend(blocks(file).map(str::to_string));
+        });
+        game
+    }
+
+    pub fn has_trait(&self, name: &str) -> bool {
+        defines(&self.traits, name)
+    }
+
+    /// The category of the trait `name`, such as `personality` or
+    /// `education`, if the game defines it with one.
+    pub fn trait_category(&self, name: &str) -> Option<&str> {
+        self.trait_rules.get(name)?.category.as_deref()
+    }
+
+    /// The group of the trait `name`, such as `intellect_good` for a tier
+    /// of it, if the game puts it in one.
+    pub fn trait_group(&self, name: &str) -> Option<&str> {
+        self.trait_rules.get(name)?.group.as_deref()
+    }
+
+    /// Whether one of the traits `a` and `b` lists the other among its
+    /// opposites.
+    pub fn are_opposites(&self, a: &str, b: &str) -> bool {
+        let lists = |name: &str, other: &str| {
+            self.trait_rules
+                .get(name)
+                .is_some_and(|rule| rule.opposites.iter().any(|opposite| opposite == other))
+        };
+        lists(a, b) || lists(b, a)
+    }
+
+    pub fn has_culture(&self, id: &str) -> bool {
+        defines(&self.cultures, id)
+    }
+
+    pub fn has_faith(&self, id: &str) -> bool {
+        defines(&self.faiths, id)
+    }
+
+    pub fn has_dynasty(&self, id: &str) -> bool {
+        defines(&self.dynasties, id)
+    }
+
+    pub fn has_house(&self, id: &str) -> bool {
+        defines(&self.houses, id)
+    }
+
+    /// The files that could not be read, each with why.
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// How many traits, cultures and faiths the game defines.
+    pub fn counts(&self) -> [usize; 3] {
+        [&self.traits, &self.cultures, &self.faiths]
+            .map(|ids| ids.as_ref().map_or(0, BTreeSet::len))
+    }
+
+    /// The problems of `config` against the game: cultures, faiths and
+    /// traits it does not define, grouped by config file.
+    pub fn check(&self, config: &SimConfig) -> Vec<SimError> {
+        let mut initialization = Vec::new();
+        for (i, dynasty) in config.initialization.dynasties.iter().enumerate() {
+            let path = format!("dynasties[{i}]");
+            if !self.has_culture(&dynasty.culture_id) {
+                initialization.push(unknown(
+                    &format!("{path}.cultureID"),
+                    "culture",
+                    &dynasty.culture_id,
+                ));
+            }
+            if !self.has_faith(&dynasty.faith_id) {
+                initialization.push(unknown(
+                    &format!("{path}.faithID"),
+                    "faith",
+                    &dynasty.faith_id,
+                ));
+            }
+            for (j, conversion) in dynasty.conversions.iter().enumerate() {
+                if !self.has_faith(&conversion.faith_id) {
+                    let path = format!("{path}.conversions[{j}].faithID");
+                    initialization.push(unknown(&path, "faith", &conversion.faith_id));
+                }
+            }
+        }
+        for faith in config.initialization.matchmaking.marriage_doctrines.keys() {
+            if !self.has_faith(faith) {
+                let path = format!("matchmaking.marriageDoctrines.{faith}");
+                initialization.push(unknown(&path, "faith", faith));
+            }
+        }
+
+        let mut life_stages = Vec::new();
+        if let Some(mortality) = &config.life_stages.mortality {
+            for name in mortality.trait_multipliers.keys() {
+                if !self.has_trait(name) {
+                    let path = format!("mortality.traitMultipliers.{name}");
+                    life_stages.push(unknown(&path, "trait", name));
+                }
+            }
+        }
+        for (i, epidemic) in config.life_stages.epidemics.iter().enumerate() {
+            for (j, culture) in epidemic.cultures.iter().enumerate() {
+                if !self.has_culture(culture) {
+                    let path = format!("epidemics[{i}].cultures[{j}]");
+                    life_stages.push(unknown(&path, "culture", culture));
+                }
+            }
+        }
+
+        let mut skills_and_traits = Vec::new();
+        for name in config.skills_and_traits.personality_traits.traits.keys() {
+            if !self.has_trait(name) {
+                let path = format!("personalityTraits.traits.{name}");
+                skills_and_traits.push(unknown(&path, "trait", name));
+            }
+        }
+
+        [
+            (INITIALIZATION_FILE, initialization),
+            (LIFE_STAGES_FILE, life_stages),
+            (SKILLS_AND_TRAITS_FILE, skills_and_traits),
+        ]
+        .into_iter()
+        .filter(|(_, errors)| !errors.is_empty())
+        .map(|(file, errors)| SimError::Config {
+            file: file.to_string(),
+            errors,
+        })
+        .collect()
+    }
+
+    /// The IDs `collect` finds in the files of `dir`, or `None` if there is
+    /// no such directory.
+    fn read_ids(
+        &mut self,
+        dir: &Path,
+        mut collect: impl FnMut(&Block, &mut BTreeSet<String>),
+    ) -> Option<BTreeSet<String>> {
+        let entries = fs::read_dir(dir).ok()?;
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"))
+            })
+            .collect();
+        paths.sort();
+        let mut ids = BTreeSet::new();
+        for path in paths {
+            let bytes = match fs::read(&path) {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    self.errors.push(format!("{}: {err}", path.display()));
+                    continue;
+                }
+            };
+            match paradox_script::parse(&String::from_utf8_lossy(&bytes)) {
+                Ok(file) => collect(&file, &mut ids),
+                Err(err) => self.errors.push(format!("{}: {err}", path.display())),
+            }
+        }
+        Some(ids)
+    }
+}
+
+impl SimConfig {
+    /// The config targeting `game`: histories leave out the traits the game
+    /// does not define.
+    pub fn with_game_data(mut self, game: GameData) -> Self {
+        self.game_data = Some(std::sync::Arc::new(game));
+        self
+    }
+
+    /// Whether histories may refer to the trait `name`.
+    pub(crate) fn emits_trait(&self, name: &str) -> bool {
+        self.game_data
+            .as_ref()
+            .is_none_or(|game| game.has_trait(name))
+    }
+}
+
+/// What a trait definition says of the traits it goes with.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+struct TraitRule {
+    category: Option<String>,
+    group: Option<String>,
+    opposites: Vec<String>,
+}
+
+impl TraitRule {
+    fn of(definition: &Block) -> Self {
+        let word = |key| {
+            definition
+                .get(key)
+                .and_then(Value::as_scalar)
+                .map(|value| value.text().into_owned())
+        };
+        Self {
+            category: word("category"),
+            group: word("group"),
+            opposites: definition
+                .get("opposites")
+                .and_then(Value::as_block)
+                .map(|opposites| {
+                    opposites
+                        .values()
+                        .filter_map(Value::as_scalar)
+                        .map(|name| name.text().into_owned())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The `common` directory of the installation or mod in `dir`.
+pub(crate) fn common_dir(dir: &Path) -> PathBuf {
+    let installed = dir.join("game").join("common");
+    if installed.is_dir() {
+        installed
+    } else {
+        dir.join("common")
+    }
+}
+
+/// The keys of the entries of `block` whose values are blocks, leaving
+/// out script values such as `@cost = 10`.
+fn blocks(block: &Block) -> impl Iterator<Item = &str> {
+    block.entries().filter_map(|entry| {
+        entry.value.as_block()?;
+        match &entry.key {
+            paradox_script::Scalar::Word(key) | paradox_script::Scalar::Quoted(key) => {
+                Some(key.as_str())
+            }
+            _ => None,
+        }
+    })
+}
+
+fn defines(ids: &Option<BTreeSet<String>>, id: &str) -> bool {
+    ids.as_ref().is_none_or(|ids| ids.contains(id))
+}
+
+fn unknown(path: &str, kind: &str, id: &str) -> ConfigError {
+    ConfigError::new(path, format!("{kind} '{id}' is not defined by the game"))
+}
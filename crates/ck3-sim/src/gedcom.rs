@@ -0,0 +1,294 @@
+//! Exporting the generated characters as a GEDCOM family tree.
+//!
+//! Genealogy programs such as Gramps read GEDCOM 5.5.1, and can draw and
+//! print trees the game cannot. [`output_gedcom`] reads the character history
+//! of an output directory, whichever engine wrote it, and gives an individual
+//! for every character and a family for every couple, married or not, with
+//! their children. Individuals are named after their dynasty or house as
+//! localised in the output, keep their history ID as a reference number, and
+//! have their births, deaths and adoptions dated; families have their
+//! marriages. Dates stay in the calendar the history was written in, years
+//! before its year 1 written as B.C.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use paradox_script::Date;
+
+use crate::error::SimError;
+use crate::localization::read_names;
+use crate::people::{read_people, Person};
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+/// The GEDCOM file of the characters in the history of the output directory
+/// `dir`.
+pub fn output_gedcom(dir: &Path) -> Result<String, SimError> {
+    let people = read_people(dir)?;
+    Ok(Tree::new(&people).write(&people, &read_names(dir)))
+}
+
+/// The couples of a tree, each a family, and the individuals' references.
+struct Tree {
+    /// Index of each character by ID.
+    index: HashMap<String, usize>,
+    /// Father and mother of each family, in order of their first mention.
+    families: Vec<(Option<usize>, Option<usize>)>,
+    family_index: HashMap<(Option<usize>, Option<usize>), usize>,
+    /// Each family's children, and the date of its marriage if married.
+    children: Vec<Vec<usize>>,
+    married: Vec<Option<Date>>,
+}
+
+impl Tree {
+    fn new(people: &[Person]) -> Self {
+        let mut tree = Self {
+            index: people
+                .iter()
+                .enumerate()
+                .map(|(i, person)| (person.id.clone(), i))
+                .collect(),
+            families: Vec::new(),
+            family_index: HashMap::new(),
+            children: Vec::new(),
+            married: Vec::new(),
+        };
+        for (i, person) in people.iter().enumerate() {
+            for (date, spouse) in &person.marriages {
+                let Some(&spouse) = tree.index.get(spouse) else {
+                    continue;
+                };
+                let couple = if person.female {
+                    (Some(spouse), Some(i))
+                } else {
+                    (Some(i), Some(spouse))
+                };
+                let family = tree.family(couple);
+                let married = &mut tree.married[family];
+                *married = Some(married.map_or(*date, |earlier| earlier.min(*date)));
+            }
+        }
+        for (i, person) in people.iter().enumerate() {
+            let parent =
+                |id: &Option<String>| id.as_ref().and_then(|id| tree.index.get(id).copied());
+            let parents = (parent(&person.father), parent(&person.mother));
+            if parents != (None, None) {
+                let family = tree.family(parents);
+                tree.children[family].push(i);
+            }
+        }
+        tree
+    }
+
+    /// The family of `couple`, added if new.
+    fn family(&mut self, couple: (Option<usize>, Option<usize>)) -> usize {
+        if let Some(&family) = self.family_index.get(&couple) {
+            return family;
+        }
+        self.families.push(couple);
+        self.children.push(Vec::new());
+        self.married.push(None);
+        self.family_index.insert(couple, self.families.len() - 1);
+        self.families.len() - 1
+    }
+
+    fn write(&self, people: &[Person], names: &HashMap<String, String>) -> String {
+        let mut spouse_in = vec![Vec::new(); people.len()];
+        let mut child_in = vec![None; people.len()];
+        for (family, &(father, mother)) in self.families.iter().enumerate() {
+            for parent in [father, mother].into_iter().flatten() {
+                spouse_in[parent].push(family);
+            }
+            for &child in &self.children[family] {
+                child_in[child] = Some(family);
+            }
+        }
+
+        let mut out = String::new();
+        let mut line = |level: u8, text: String| {
+            out.push_str(&format!("{level} {text}\n"));
+        };
+        line(0, "HEAD".into());
+        line(1, "SOUR CK3_CHARACTER_HISTORY_GENERATOR".into());
+        line(2, "NAME CK3 Character History Generator".into());
+        line(1, "SUBM @SUBM@".into());
+        line(1, "GEDC".into());
+        line(2, "VERS 5.5.1".into());
+        line(2, "FORM LINEAGE-LINKED".into());
+        line(1, "CHAR UTF-8".into());
+        // GEDCOM 5.5.1 requires a submitter, which Gramps warns about without.
+        line(0, "@SUBM@ SUBM".into());
+        line(1, "NAME CK3 Character History Generator".into());
+
+        for (i, person) in people.iter().enumerate() {
+            line(0, format!("@I{}@ INDI", i + 1));
+            let surname = person.surname_key().and_then(|key| names.get(&key));
+            match surname {
+                Some(surname) => {
+                    line(1, format!("NAME {} /{surname}/", person.name));
+                    line(2, format!("GIVN {}", person.name));
+                    line(2, format!("SURN {surname}"));
+                }
+                None => line(1, format!("NAME {}", person.name)),
+            }
+            line(1, format!("SEX {}", if person.female { "F" } else { "M" }));
+            for (tag, date) in [("BIRT", person.birth), ("DEAT", person.death)] {
+                if let Some(date) = date {
+                    line(1, tag.into());
+                    line(2, format!("DATE {}", gedcom_date(date)));
+                }
+            }
+            for family in &spouse_in[i] {
+                line(1, format!("FAMS @F{}@", family + 1));
+            }
+            // An adopted child is in the family of their adoptive parents.
+            if let Some(family) = child_in[i] {
+                if let Some(date) = person.adopted {
+                    line(1, "ADOP".into());
+                    line(2, format!("DATE {}", gedcom_date(date)));
+                    line(2, format!("FAMC @F{}@", family + 1));
+                }
+                line(1, format!("FAMC @F{}@", family + 1));
+                if person.adopted.is_some() {
+                    line(2, "PEDI adopted".into());
+                }
+            }
+            line(1, format!("REFN {}", person.id));
+        }
+
+        for (family, &(father, mother)) in self.families.iter().enumerate() {
+            line(0, format!("@F{}@ FAM", family + 1));
+            if let Some(father) = father {
+                line(1, format!("HUSB @I{}@", father + 1));
+            }
+            if let Some(mother) = mother {
+                line(1, format!("WIFE @I{}@", mother + 1));
+            }
+            for child in &self.children[family] {
+                line(1, format!("CHIL @I{}@", child + 1));
+            }
+            if let Some(date) = self.married[family] {
+                line(1, "MARR".into());
+                line(2, format!("DATE {}", gedcom_date(date)));
+            }
+        }
+        line(0, "TRLR".into());
+        out
+    }
+}
+
+/// A date as GEDCOM writes it, such as `7 MAR 867` or `12 JAN 40 B.C.`. Year
+/// 0, which GEDCOM has no place for, is written as given.
+fn gedcom_date(date: Date) -> String {
+    let month = MONTHS[usize::from(date.month) - 1];
+    match date.year {
+        0 => format!("({date})"),
+        year if year < 0 => format!("{} {month} {} B.C.", date.day, -year),
+        year => format!("{} {month} {year}", date.day),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn the_family_is_written_as_individuals_and_a_family() {
+        let dir = testing::family("gedcom");
+        let gedcom = output_gedcom(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            gedcom,
+            "\
+0 HEAD
+1 SOUR CK3_CHARACTER_HISTORY_GENERATOR
+2 NAME CK3 Character History Generator
+1 SUBM @SUBM@
+1 GEDC
+2 VERS 5.5.1
+2 FORM LINEAGE-LINKED
+1 CHAR UTF-8
+0 @SUBM@ SUBM
+1 NAME CK3 Character History Generator
+0 @I1@ INDI
+1 NAME Aldor /Alda/
+2 GIVN Aldor
+2 SURN Alda
+1 SEX M
+1 BIRT
+2 DATE 1 JAN 6000
+1 DEAT
+2 DATE 1 JAN 6060
+1 FAMS @F1@
+1 REFN lineofa1
+0 @I2@ INDI
+1 NAME Beren /Bregol/
+2 GIVN Beren
+2 SURN Bregol
+1 SEX F
+1 BIRT
+2 DATE 1 JAN 6002
+1 FAMS @F1@
+1 REFN lineofb1
+0 @I3@ INDI
+1 NAME Anborn /Anbornion/
+2 GIVN Anborn
+2 SURN Anbornion
+1 SEX M
+1 BIRT
+2 DATE 1 MAY 6026
+1 FAMC @F1@
+1 REFN lineofa2
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+1 MARR
+2 DATE 1 MAR 6025
+0 TRLR
+"
+        );
+    }
+
+    #[test]
+    fn adopted_children_and_unmarried_parents_have_families() {
+        let dir = testing::output(
+            "gedcom-adopted",
+            "\
+a1 = { name = \"Aldor\" }
+b1 = { name = \"Beren\" female = yes }
+a2 = { name = \"Anborn\" father = a1 mother = b1 }
+a3 = { name = \"Ioreth\" female = yes 6040.2.3 = { effect = { set_father = a1 } } }
+",
+        );
+        let gedcom = output_gedcom(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let records: Vec<&str> = gedcom.split("\n0 ").skip(2).collect();
+        assert_eq!(
+            records,
+            [
+                "@I1@ INDI\n1 NAME Aldor\n1 SEX M\n1 FAMS @F1@\n1 FAMS @F2@\n1 REFN a1",
+                "@I2@ INDI\n1 NAME Beren\n1 SEX F\n1 FAMS @F1@\n1 REFN b1",
+                "@I3@ INDI\n1 NAME Anborn\n1 SEX M\n1 FAMC @F1@\n1 REFN a2",
+                "@I4@ INDI\n1 NAME Ioreth\n1 SEX F\n1 ADOP\n2 DATE 3 FEB 6040\n\
+                 2 FAMC @F2@\n1 FAMC @F2@\n2 PEDI adopted\n1 REFN a3",
+                "@F1@ FAM\n1 HUSB @I1@\n1 WIFE @I2@\n1 CHIL @I3@",
+                "@F2@ FAM\n1 HUSB @I1@\n1 CHIL @I4@",
+                "TRLR\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn years_before_the_first_are_written_as_bc() {
+        let date = |text: &str| gedcom_date(Date::parse(text).unwrap());
+        assert_eq!(date("867.3.7"), "7 MAR 867");
+        assert_eq!(date("-40.1.12"), "12 JAN 40 B.C.");
+        assert_eq!(date("0.6.1"), "(0.6.1)");
+    }
+}
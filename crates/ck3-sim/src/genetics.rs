@@ -0,0 +1,231 @@
+//! Genetic traits and inbreeding.
+//!
+//! As in the game, a character has a genetic trait either active, shown and
+//! in effect, or inactive, carried unseen and passed on to children. The
+//! chance of a child having a trait, and in which form, depends on how each
+//! parent has it; the tiered traits (beauty, intellect and physique, which
+//! run from imbecile through slow and quick to genius) give a child at most
+//! one tier of each active and one inactive, and may step back from a
+//! parent's tier towards the middle. Parents who are close kin reinforce the
+//! genes they share, and their children may be born inbred, the likelier
+//! the closer the kin.
+
+use rand::Rng;
+
+use crate::character::Character;
+use crate::random;
+
+/// Tiered genetic traits, worst to best.
+const GENETIC_TIERS: [[&str; 6]; 3] = [
+    [
+        "beauty_bad_3",
+        "beauty_bad_2",
+        "beauty_bad_1",
+        "beauty_good_1",
+        "beauty_good_2",
+        "beauty_good_3",
+    ],
+    [
+        "intellect_bad_3",
+        "intellect_bad_2",
+        "intellect_bad_1",
+        "intellect_good_1",
+        "intellect_good_2",
+        "intellect_good_3",
+    ],
+    [
+        "physique_bad_3",
+        "physique_bad_2",
+        "physique_bad_1",
+        "physique_good_1",
+        "physique_good_2",
+        "physique_good_3",
+    ],
+];
+
+/// Chance of each tier appearing without being inherited, in tier order.
+const TIER_MUTATION_CHANCE: [f64; 6] = [0.0015, 0.0025, 0.005, 0.005, 0.0025, 0.0015];
+
+/// Genetic traits without tiers.
+const SINGLE_GENETIC: [&str; 13] = [
+    "clubfooted",
+    "hunchbacked",
+    "lisping",
+    "stuttering",
+    "dwarf",
+    "giant",
+    "spindly",
+    "scaly",
+    "albino",
+    "wheezing",
+    "bleeder",
+    "fecund",
+    "infertile",
+];
+
+/// Single traits of which a character can have at most one active.
+const EXCLUSIVE_GENETIC: [[&str; 2]; 2] = [["dwarf", "giant"], ["fecund", "infertile"]];
+
+/// Chance of a single trait appearing without being inherited.
+const SINGLE_MUTATION_CHANCE: f64 = 0.005;
+/// Chance of a tier neither parent carries showing on the way from a
+/// parent's tier towards the middle.
+const STEP_BACK_CHANCE: f64 = 0.10;
+/// How much likelier a gene both parents carry is to show, per unit of the
+/// parents' relatedness.
+const CLOSE_KIN_REINFORCEMENT: f64 = 1.0;
+/// Chance of a child being born inbred, per square unit of the parents'
+/// relatedness: one in two for siblings, one in 32 for first cousins.
+const INBRED_CHANCE: f64 = 2.0;
+
+/// The genetic trait named `name`, if it is one.
+pub(crate) fn genetic_trait(name: &str) -> Option<&'static str> {
+    GENETIC_TIERS
+        .iter()
+        .flatten()
+        .chain(&SINGLE_GENETIC)
+        .find(|&&genetic| genetic == name)
+        .copied()
+}
+
+/// How a parent has a genetic trait.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Gene {
+    Absent,
+    Inactive,
+    Active,
+}
+
+fn gene(parent: &Character, name: &str) -> Gene {
+    if parent.has_congenital(name) {
+        Gene::Active
+    } else if parent.carries_inactive(name) {
+        Gene::Inactive
+    } else {
+        Gene::Absent
+    }
+}
+
+/// Chances of a child having a trait active and inactive, for how its
+/// parents have it and how closely they are related.
+fn gene_chances(father: Gene, mother: Gene, relatedness: f64) -> (f64, f64) {
+    let (active, inactive) = match (father.max(mother), father.min(mother)) {
+        (Gene::Active, Gene::Active) => (0.80, 0.20),
+        (Gene::Active, Gene::Inactive) => (0.50, 0.40),
+        (Gene::Active, Gene::Absent) => (0.25, 0.25),
+        (Gene::Inactive, Gene::Inactive) => (0.25, 0.50),
+        (Gene::Inactive, Gene::Absent) => (0.05, 0.25),
+        _ => (0.0, 0.0),
+    };
+    if father == Gene::Absent || mother == Gene::Absent {
+        return (active, inactive);
+    }
+    let active = (active * (1.0 + CLOSE_KIN_REINFORCEMENT * relatedness)).min(1.0);
+    (active, inactive.min(1.0 - active))
+}
+
+/// Roll the genetic traits `child` inherits from `father` and `mother`,
+/// whose coefficient of relationship is `relatedness`.
+pub(crate) fn inherit_genes(
+    child: &mut Character,
+    father: &Character,
+    mother: &Character,
+    relatedness: f64,
+    rng: &mut impl Rng,
+) {
+    for tiers in &GENETIC_TIERS {
+        inherit_tiers(child, father, mother, tiers, relatedness, rng);
+    }
+
+    for name in SINGLE_GENETIC {
+        let (f, m) = (gene(father, name), gene(mother, name));
+        let (active, inactive) = if f == Gene::Absent && m == Gene::Absent {
+            (SINGLE_MUTATION_CHANCE, 0.0)
+        } else {
+            gene_chances(f, m, relatedness)
+        };
+        let conflicts = EXCLUSIVE_GENETIC.iter().any(|group| {
+            group.contains(&name) && group.iter().any(|other| child.has_congenital(other))
+        });
+        let roll = rng.random::<f64>();
+        if roll < active && !conflicts {
+            child.congenital_traits.push(name);
+        } else if roll < active + inactive {
+            child.inactive_traits.push(name);
+        }
+    }
+
+    if random::chance(rng, INBRED_CHANCE * relatedness * relatedness) {
+        child.congenital_traits.push("inbred");
+    }
+}
+
+/// Roll the child's active and inactive tier of one tiered trait.
+///
+/// The active tier is tried from the best tier either parent carries towards
+/// the middle, bad tiers stepping back to `bad_1` and good ones all the way
+/// down unless neither parent carries a bad tier; failing that, any tier may
+/// appear as a rare mutation. The inactive tier comes from the remaining
+/// tiers the parents carry, best first.
+fn inherit_tiers(
+    child: &mut Character,
+    father: &Character,
+    mother: &Character,
+    tiers: &[&'static str; 6],
+    relatedness: f64,
+    rng: &mut impl Rng,
+) {
+    let carried: Vec<usize> = (0..tiers.len())
+        .filter(|&idx| {
+            gene(father, tiers[idx]) != Gene::Absent || gene(mother, tiers[idx]) != Gene::Absent
+        })
+        .collect();
+    let no_bad_parent = carried.iter().all(|&idx| idx > 2);
+
+    let order: Vec<usize> = match carried.last() {
+        None => Vec::new(),
+        Some(&best) if best <= 2 => (best..3).collect(),
+        Some(&best) => (0..=best).rev().collect(),
+    };
+    let mut active = None;
+    for idx in order {
+        let (f, m) = (gene(father, tiers[idx]), gene(mother, tiers[idx]));
+        let chance = if carried.contains(&idx) {
+            gene_chances(f, m, relatedness).0
+        } else {
+            STEP_BACK_CHANCE
+        };
+        if random::chance(rng, chance) {
+            active = Some(idx);
+            break;
+        }
+        if idx == 3 && no_bad_parent {
+            break;
+        }
+    }
+    if active.is_none() {
+        let roll = rng.random::<f64>();
+        let mut cumulative = 0.0;
+        for (idx, chance) in TIER_MUTATION_CHANCE.iter().enumerate() {
+            cumulative += chance;
+            if roll < cumulative {
+                active = Some(idx);
+                break;
+            }
+        }
+    }
+    if let Some(idx) = active {
+        child.congenital_traits.push(tiers[idx]);
+    }
+
+    for &idx in carried.iter().rev() {
+        if Some(idx) == active {
+            continue;
+        }
+        let (f, m) = (gene(father, tiers[idx]), gene(mother, tiers[idx]));
+        if random::chance(rng, gene_chances(f, m, relatedness).1) {
+            child.inactive_traits.push(tiers[idx]);
+            break;
+        }
+    }
+}
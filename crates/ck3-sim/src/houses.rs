@@ -0,0 +1,187 @@
+//! Cadet houses branching off dynasties.
+//!
+//! Every dynasty starts out as a single house. A younger son coming of age,
+//! or a younger daughter under enatic laws, may found a house of their own,
+//! and a house grown past the size limit splits, the living younger child
+//! with the largest branch of living members breaking away with it. A
+//! founder takes every descendant who carries their line into the new house,
+//! and children born to the line later join it in turn. Members of a cadet
+//! house are written with `dynasty_house` rather than `dynasty`, and each
+//! house gets a definition whose localised name is its founder's, under its
+//! dynasty and with the dynasty's motto.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use paradox_script::{Block, Scalar};
+
+use crate::character::{Character, CharacterId};
+use crate::localization::{house_key, motto_key};
+use crate::random;
+use crate::simulation::Simulation;
+
+/// Name of the file of cadet house definitions.
+pub const HOUSES_FILE: &str = "dynasty_houses.txt";
+/// Age at which a younger child may found a house of their own.
+const FOUNDING_AGE: i32 = 16;
+
+impl Simulation {
+    /// Found this year's cadet houses: those of younger children coming of
+    /// age, then one for each house past the size limit.
+    pub(crate) fn split_cadet_houses(&mut self) {
+        let houses = self.config.initialization.cadet_houses.clone();
+        if houses.younger_child_chance > 0.0 {
+            for id in self.ids().collect::<Vec<_>>() {
+                if self.character(id).age != FOUNDING_AGE || !self.may_found_house(id) {
+                    continue;
+                }
+                if random::chance(&mut self.rng, houses.younger_child_chance) {
+                    self.found_house(id);
+                }
+            }
+        }
+
+        let Some(max_size) = houses.max_house_size else {
+            return;
+        };
+        let mut by_house: BTreeMap<(&str, Option<&str>), Vec<CharacterId>> = BTreeMap::new();
+        for (id, c) in self.characters() {
+            if let (true, Some(dynasty)) = (c.is_alive(), c.dynasty.as_deref()) {
+                by_house
+                    .entry((dynasty, c.house.as_deref()))
+                    .or_default()
+                    .push(id);
+            }
+        }
+        let founders: Vec<CharacterId> = by_house
+            .into_values()
+            .filter(|members| members.len() > max_size as usize)
+            .filter_map(|members| {
+                let living: HashSet<CharacterId> = members.iter().copied().collect();
+                members
+                    .iter()
+                    .copied()
+                    .filter(|&id| {
+                        self.character(id).age >= FOUNDING_AGE && self.may_found_house(id)
+                    })
+                    .map(|id| {
+                        let size = self
+                            .branch(id)
+                            .iter()
+                            .filter(|member| living.contains(member))
+                            .count();
+                        (id, size)
+                    })
+                    .filter(|&(_, size)| size < members.len())
+                    .max_by_key(|&(id, size)| (size, Reverse(self.character(id).birth)))
+                    .map(|(id, _)| id)
+            })
+            .collect();
+        for founder in founders {
+            self.found_house(founder);
+        }
+    }
+
+    /// Whether `id` is a living, legitimate younger child who carries their
+    /// house's line after an elder sibling who does too.
+    fn may_found_house(&self, id: CharacterId) -> bool {
+        let c = self.character(id);
+        if !c.is_alive() || c.is_house || c.is_bastard || !c.has_dynasty() {
+            return false;
+        }
+        if !Self::carries_line(c.gender_law, c) {
+            return false;
+        }
+        let Some(parent) = self.line_parent(id) else {
+            return false;
+        };
+        self.character(parent).children.iter().any(|&sibling| {
+            let s = self.character(sibling);
+            s.birth < c.birth
+                && !s.is_bastard
+                && s.dynasty == c.dynasty
+                && s.house == c.house
+                && Self::carries_line(s.gender_law, s)
+        })
+    }
+
+    /// The parent `id` has their dynasty and house from.
+    fn line_parent(&self, id: CharacterId) -> Option<CharacterId> {
+        let c = self.character(id);
+        [c.father, c.mother].into_iter().flatten().find(|&parent| {
+            let p = self.character(parent);
+            p.dynasty == c.dynasty && p.house == c.house
+        })
+    }
+
+    /// `founder` and every descendant who has their dynasty and house through
+    /// them.
+    fn branch(&self, founder: CharacterId) -> Vec<CharacterId> {
+        let mut branch = vec![founder];
+        let mut i = 0;
+        while let Some(&id) = branch.get(i) {
+            let c = self.character(id);
+            branch.extend(c.children.iter().copied().filter(|&child| {
+                let child = self.character(child);
+                child.dynasty == c.dynasty && child.house == c.house
+            }));
+            i += 1;
+        }
+        branch
+    }
+
+    /// Move `founder` and their branch into a new cadet house.
+    fn found_house(&mut self, founder: CharacterId) {
+        let dynasty = self
+            .character(founder)
+            .dynasty
+            .clone()
+            .expect("founders belong to a dynasty");
+        let founded: BTreeSet<&str> = self
+            .characters
+            .iter()
+            .filter(|c| c.dynasty.as_deref() == Some(dynasty.as_str()))
+            .filter_map(|c| c.house.as_deref())
+            .collect();
+        let base = dynasty.strip_prefix("dynasty_").unwrap_or(&dynasty);
+        let house = format!("house_{base}_{}", founded.len() + 1);
+        for id in self.branch(founder) {
+            self.character_mut(id).house = Some(house.clone());
+        }
+    }
+
+    /// The founder of each cadet house, by house ID: its earliest-born
+    /// member.
+    pub(crate) fn house_founders(&self) -> BTreeMap<&str, &Character> {
+        let mut founders: BTreeMap<&str, &Character> = BTreeMap::new();
+        for c in &self.characters {
+            let Some(house) = c.house.as_deref() else {
+                continue;
+            };
+            let founder = founders.entry(house).or_insert(c);
+            if c.birth < founder.birth {
+                *founder = c;
+            }
+        }
+        founders
+    }
+
+    /// The contents of the cadet house definitions file.
+    pub(crate) fn house_definitions(&self) -> String {
+        let mut file = Block::default();
+        for (house, founder) in self.house_founders() {
+            let mut definition = Block::default();
+            definition.push("name", Scalar::Word(house_key(house)));
+            if let Some(dynasty) = &founder.dynasty {
+                definition.push("dynasty", Scalar::Word(dynasty.clone()));
+            }
+            // A house keeps the motto of its dynasty.
+            let config = self.dynasty_config(founder.dynasty.as_deref());
+            if let Some(config) = config.filter(|config| !config.dynasty_motto.is_empty()) {
+                definition.push("motto", Scalar::Word(motto_key(&config.dynasty_id)));
+            }
+            file.push(house, definition);
+        }
+        paradox_script::to_string(&file)
+    }
+}
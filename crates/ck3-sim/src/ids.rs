@@ -0,0 +1,658 @@
+//! Finding and renumbering IDs that collide.
+//!
+//! The game keeps one definition of each character, dynasty and house ID and
+//! drops any other without a word, so a history whose IDs are also defined by
+//! the mod it is for loses characters or whole dynasties once loaded.
+//! [`id_collisions`] lists the IDs an output directory defines more than
+//! once, or that other history and dynasty files define too, such as those of
+//! the mod or of an imported history. [`renumber_ids`] fixes them: each of
+//! the output's colliding definitions gets a numeric ID from a configured
+//! range that no file read uses, and every reference to it in the output's
+//! files follows, as do the localisation keys of renamed dynasties and
+//! houses. Files are rewritten in place, comments and layout kept. Of two
+//! definitions within the output, the second is renamed on its own, since
+//! references to the ID already meant the first.
+//!
+//! Collisions are best kept from happening at all, since renumbering breaks
+//! saves made with the old IDs. [`scan_character_ids`] reads the character
+//! histories of the game and of the mods played with it, lists the ranges of
+//! numbers each ID prefix takes up there, and suggests an `initialCharID`
+//! from which the characters of the config's dynasties, `lineof<prefix><n>`,
+//! are numbered clear of all of them.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use paradox_script::{rewrite_words, Block, Value, Word};
+use serde::{Deserialize, Serialize};
+
+use crate::config::SimConfig;
+use crate::definitions::DYNASTIES_FILE;
+use crate::dynasty::id_prefix;
+use crate::error::SimError;
+use crate::export::HISTORY_FILE;
+use crate::houses::HOUSES_FILE;
+use crate::localization::{dynasty_key, house_key, motto_key, LOCALIZATION_DIR};
+use crate::people::read;
+use crate::simulation::ID_START;
+
+/// Keys whose value is a character ID.
+const CHARACTER_REFERENCES: [&str; 10] = [
+    "father",
+    "mother",
+    "add_spouse",
+    "add_matrilineal_spouse",
+    "remove_spouse",
+    "add_concubine",
+    "set_father",
+    "set_mother",
+    "parent",
+    "holder",
+];
+/// Prefix of a character scope, such as `character:lineofx1`.
+const CHARACTER_SCOPE: &str = "character:";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IdKind {
+    Character,
+    Dynasty,
+    House,
+}
+
+impl IdKind {
+    /// The kind of the definitions of the output file `name`, if it has any.
+    pub(crate) fn of_file(name: &str) -> Option<Self> {
+        match name {
+            HISTORY_FILE => Some(IdKind::Character),
+            DYNASTIES_FILE => Some(IdKind::Dynasty),
+            HOUSES_FILE => Some(IdKind::House),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for IdKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            IdKind::Character => "character",
+            IdKind::Dynasty => "dynasty",
+            IdKind::House => "house",
+        })
+    }
+}
+
+/// The numeric IDs renamed definitions may take, both included.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdRange {
+    pub first: u64,
+    pub last: u64,
+}
+
+/// An ID of the output defined somewhere else as well.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Collision {
+    pub kind: IdKind,
+    pub id: String,
+    /// The output file defining it, such as `family_history.txt`, and the
+    /// 1-based line.
+    pub file: String,
+    pub line: usize,
+    /// The other file defining it, the output's own file name for an ID it
+    /// defines twice, and the line.
+    pub other_file: String,
+    pub other_line: usize,
+}
+
+/// An ID [`renumber_ids`] changed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Renumbered {
+    pub kind: IdKind,
+    pub from: String,
+    pub to: String,
+}
+
+/// The character IDs some files define with the same prefix.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsedIds {
+    /// What comes before the number, such as `lineofelros`; empty for IDs
+    /// that are only a number, as the game's are.
+    pub prefix: String,
+    /// The lowest and highest numbers, both included.
+    pub first: u64,
+    pub last: u64,
+    pub count: usize,
+    /// Whether the config's characters are given IDs with this prefix.
+    pub ours: bool,
+}
+
+/// What [`scan_character_ids`] found.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdScan {
+    /// By prefix.
+    pub used: Vec<UsedIds>,
+    /// The config's `initialCharID`, or the first round thousand past every
+    /// number our prefixes take up if that is higher.
+    pub initial_char_id: u64,
+}
+
+/// A definition of an ID.
+pub(crate) struct Definition {
+    pub(crate) kind: IdKind,
+    pub(crate) id: String,
+    file: String,
+    line: usize,
+}
+
+/// The collisions of the IDs of the output directory `dir`, among
+/// themselves and with those the history and dynasty files `others` define,
+/// each of which may be a directory of them. In file and line order.
+pub fn id_collisions(dir: &Path, others: &[PathBuf]) -> Vec<Collision> {
+    let own = output_definitions(dir);
+    let theirs = other_definitions(others);
+    let mut first: HashMap<(IdKind, &str), &Definition> = HashMap::new();
+    let mut elsewhere: HashMap<(IdKind, &str), &Definition> = HashMap::new();
+    for definition in &theirs {
+        elsewhere
+            .entry((definition.kind, &definition.id))
+            .or_insert(definition);
+    }
+    let mut collisions = Vec::new();
+    for definition in &own {
+        let key = (definition.kind, definition.id.as_str());
+        let other = match first.get(&key) {
+            Some(other) => Some(*other),
+            None => {
+                first.insert(key, definition);
+                elsewhere.get(&key).copied()
+            }
+        };
+        if let Some(other) = other {
+            collisions.push(Collision {
+                kind: definition.kind,
+                id: definition.id.clone(),
+                file: definition.file.clone(),
+                line: definition.line,
+                other_file: other.file.clone(),
+                other_line: other.line,
+            });
+        }
+    }
+    collisions.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    collisions
+}
+
+/// Give every colliding ID of the output directory `dir`, as
+/// [`id_collisions`] finds them against `others`, a free ID from `range`,
+/// and rewrite the output's files to match. Returns what was renamed, in
+/// file order; nothing is written if nothing collides.
+pub fn renumber_ids(
+    dir: &Path,
+    others: &[PathBuf],
+    range: IdRange,
+) -> Result<Vec<Renumbered>, SimError> {
+    let own = output_definitions(dir);
+    let theirs = other_definitions(others);
+    let elsewhere: HashSet<(IdKind, &str)> = theirs
+        .iter()
+        .map(|definition| (definition.kind, definition.id.as_str()))
+        .collect();
+    // Dynasty and house names share localisation keys, so every new ID is
+    // free among all kinds.
+    let taken: HashSet<&str> = own
+        .iter()
+        .chain(&theirs)
+        .map(|definition| definition.id.as_str())
+        .collect();
+    let mut next = range.first;
+    let mut new_id = |kind: IdKind| loop {
+        if next > range.last {
+            return Err(SimError::Ids {
+                message: format!(
+                    "no free ID is left from {} to {} for another {kind}",
+                    range.first, range.last
+                ),
+            });
+        }
+        let id = next.to_string();
+        next += 1;
+        if !taken.contains(id.as_str()) {
+            return Ok(id);
+        }
+    };
+
+    let mut seen: HashSet<(IdKind, &str)> = HashSet::new();
+    let mut renames: HashMap<(IdKind, &str), String> = HashMap::new();
+    let mut duplicates: HashMap<(IdKind, usize), String> = HashMap::new();
+    let mut renumbered = Vec::new();
+    for definition in &own {
+        let key = (definition.kind, definition.id.as_str());
+        let to = if !seen.insert(key) {
+            let to = new_id(definition.kind)?;
+            duplicates.insert((definition.kind, definition.line), to.clone());
+            to
+        } else if elsewhere.contains(&key) {
+            let to = new_id(definition.kind)?;
+            renames.insert(key, to.clone());
+            to
+        } else {
+            continue;
+        };
+        renumbered.push(Renumbered {
+            kind: definition.kind,
+            from: definition.id.clone(),
+            to,
+        });
+    }
+    if renumbered.is_empty() {
+        return Ok(renumbered);
+    }
+
+    let mut keys: HashMap<String, String> = HashMap::new();
+    for ((kind, from), to) in &renames {
+        match kind {
+            IdKind::Character => {}
+            IdKind::Dynasty => {
+                keys.insert(dynasty_key(from), dynasty_key(to));
+                keys.insert(motto_key(from), motto_key(to));
+            }
+            IdKind::House => {
+                keys.insert(house_key(from), house_key(to));
+            }
+        }
+    }
+    let rename = |kind: IdKind, id: &str| renames.get(&(kind, id)).cloned();
+    for path in files(dir, "txt") {
+        let name = relative(dir, &path);
+        let defines = IdKind::of_file(&name);
+        let Ok(bytes) = fs::read(&path) else {
+            continue;
+        };
+        let text = String::from_utf8_lossy(&bytes);
+        let rewritten = rewrite_words(&text, |word: &Word| {
+            if word.depth == 0 && word.is_key {
+                let kind = defines?;
+                return duplicates
+                    .get(&(kind, word.line))
+                    .cloned()
+                    .or_else(|| rename(kind, word.text));
+            }
+            if let Some(id) = word.text.strip_prefix(CHARACTER_SCOPE) {
+                return rename(IdKind::Character, id).map(|id| format!("{CHARACTER_SCOPE}{id}"));
+            }
+            match word.key? {
+                key if CHARACTER_REFERENCES.contains(&key) => rename(IdKind::Character, word.text),
+                "dynasty" => rename(IdKind::Dynasty, word.text),
+                "dynasty_house" => rename(IdKind::House, word.text),
+                "name" | "motto"
+                    if word.depth == 1
+                        && matches!(defines, Some(IdKind::Dynasty | IdKind::House)) =>
+                {
+                    keys.get(word.text).cloned()
+                }
+                _ => None,
+            }
+        })
+        .map_err(|err| SimError::History {
+            message: format!("{name} cannot be read: {err}"),
+        })?;
+        if rewritten != text {
+            write(&path, &rewritten)?;
+        }
+    }
+    if !keys.is_empty() {
+        for path in files(&dir.join(LOCALIZATION_DIR), "yml") {
+            let Ok(text) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let mut rewritten = String::with_capacity(text.len());
+            for line in text.split_inclusive('\n') {
+                let indent = line.len() - line.trim_start().len();
+                let renamed = line[indent..].split_once(':').and_then(|(key, rest)| {
+                    let key = key.trim_start_matches('\u{feff}');
+                    Some(format!("{}{}:{rest}", &line[..indent], keys.get(key)?))
+                });
+                rewritten.push_str(renamed.as_deref().unwrap_or(line));
+            }
+            if rewritten != text {
+                write(&path, &rewritten)?;
+            }
+        }
+    }
+    Ok(renumbered)
+}
+
+/// The character IDs the history files `paths`, and those in the
+/// directories among them, use, and an `initialCharID` for `config` that
+/// numbers its characters past all of those with the same prefix.
+pub fn scan_character_ids(paths: &[PathBuf], config: &SimConfig) -> IdScan {
+    let init = &config.initialization;
+    let ours: HashSet<String> = init
+        .dynasties
+        .iter()
+        .map(|dynasty| Some(dynasty.dynasty_id.as_str()))
+        .chain([None])
+        .map(|dynasty| format!("{ID_START}{}", id_prefix(dynasty)))
+        .collect();
+    let mut used: Vec<UsedIds> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for definition in other_definitions(paths) {
+        if definition.kind != IdKind::Character {
+            continue;
+        }
+        let id = definition.id;
+        let prefix = id.trim_end_matches(|c: char| c.is_ascii_digit());
+        let Ok(number) = id[prefix.len()..].parse::<u64>() else {
+            continue;
+        };
+        let i = *index.entry(prefix.to_string()).or_insert_with(|| {
+            used.push(UsedIds {
+                prefix: prefix.to_string(),
+                first: number,
+                last: number,
+                count: 0,
+                ours: ours.contains(prefix),
+            });
+            used.len() - 1
+        });
+        let range = &mut used[i];
+        range.first = range.first.min(number);
+        range.last = range.last.max(number);
+        range.count += 1;
+    }
+    used.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+    let initial_char_id = used
+        .iter()
+        .filter(|range| range.ours)
+        .map(|range| (range.last / 1000 + 1) * 1000)
+        .fold(init.initial_char_id, u64::max);
+    IdScan {
+        used,
+        initial_char_id,
+    }
+}
+
+/// The characters, dynasties and houses the output directory `dir` defines,
+/// in file order.
+fn output_definitions(dir: &Path) -> Vec<Definition> {
+    let mut definitions = Vec::new();
+    for (name, kind) in [
+        (HISTORY_FILE, IdKind::Character),
+        (DYNASTIES_FILE, IdKind::Dynasty),
+        (HOUSES_FILE, IdKind::House),
+    ] {
+        let Some(file) = read(&dir.join(name)) else {
+            continue;
+        };
+        for entry in file.entries() {
+            if entry.value.as_block().is_some() {
+                definitions.push(Definition {
+                    kind,
+                    id: entry.key.text().into_owned(),
+                    file: name.to_string(),
+                    line: entry.line,
+                });
+            }
+        }
+    }
+    definitions
+}
+
+/// The characters, dynasties and houses the files `paths`, and the files in
+/// the directories among them, define. What a block defines is told by what
+/// it holds, as the history import does: a character has a faith or dated
+/// entries, a house the dynasty it belongs to, and a dynasty a name.
+pub(crate) fn other_definitions(paths: &[PathBuf]) -> Vec<Definition> {
+    let mut definitions = Vec::new();
+    for path in paths {
+        let files = if path.is_dir() {
+            files(path, "txt")
+        } else {
+            vec![path.clone()]
+        };
+        for path in files {
+            let Some(file) = read(&path) else {
+                continue;
+            };
+            for entry in file.entries() {
+                let Some(block) = entry.value.as_block() else {
+                    continue;
+                };
+                let Some(kind) = kind_of(block) else {
+                    continue;
+                };
+                definitions.push(Definition {
+                    kind,
+                    id: entry.key.text().into_owned(),
+                    file: path.display().to_string(),
+                    line: entry.line,
+                });
+            }
+        }
+    }
+    definitions
+}
+
+fn kind_of(block: &Block) -> Option<IdKind> {
+    if block.get("religion").is_some()
+        || block.get("faith").is_some()
+        || block.dated().next().is_some()
+    {
+        Some(IdKind::Character)
+    } else if block.get("dynasty").is_some() {
+        Some(IdKind::House)
+    } else if block.get("name").and_then(Value::as_scalar).is_some() {
+        Some(IdKind::Dynasty)
+    } else {
+        None
+    }
+}
+
+/// The files with extension `extension` in `dir` and the directories in
+/// it, in path order.
+fn files(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+            {
+                found.push(path);
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+/// `path` relative to `dir`, with forward slashes.
+fn relative(dir: &Path, path: &Path) -> String {
+    let path = path.strip_prefix(dir).unwrap_or(path);
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn write(path: &Path, text: &str) -> Result<(), SimError> {
+    fs::write(path, text).map_err(|err| SimError::Ids {
+        message: format!("cannot write {}: {err}", path.display()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for the test `name`.
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ck3gen-ids-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// An output that defines `lineofa2` twice and shares `lineofa1` and
+    /// `dynasty_a` with a mod, and the mod's history files.
+    fn colliding(name: &str) -> (PathBuf, PathBuf) {
+        let root = dir(name);
+        let (output, other) = (root.join("output"), root.join("mod"));
+        fs::create_dir_all(output.join(LOCALIZATION_DIR)).unwrap();
+        fs::create_dir_all(&other).unwrap();
+        fs::write(
+            output.join(HISTORY_FILE),
+            "lineofa1 = {\n\tdynasty = dynasty_a\n\treligion = x\n}\n\
+             # The heir.\n\
+             lineofa2 = {\n\tfather = lineofa1\n\tdynasty = dynasty_a\n\treligion = x\n}\n\
+             lineofa2 = {\n\treligion = x\n\t867.1.1 = {\n\t\tadd_spouse = character:lineofa1\n\t}\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            output.join(DYNASTIES_FILE),
+            "dynasty_a = {\n\tname = dynn_a\n\tmotto = dynn_a_motto\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            output
+                .join(LOCALIZATION_DIR)
+                .join("dynasties_l_english.yml"),
+            "l_english:\n dynn_a:0 \"Alda\"\n dynn_a_motto:0 \"Ever\"\n",
+        )
+        .unwrap();
+        fs::write(
+            other.join("history.txt"),
+            "lineofa1 = {\n\treligion = x\n}\n100 = {\n\treligion = x\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            other.join("dynasties.txt"),
+            "dynasty_a = {\n\tname = dynn_a\n}\n",
+        )
+        .unwrap();
+        (output, other)
+    }
+
+    #[test]
+    fn collisions_are_found_within_the_output_and_with_other_files() {
+        let (output, other) = colliding("collisions");
+        let found: Vec<(IdKind, String, usize)> = id_collisions(&output, &[other])
+            .into_iter()
+            .map(|collision| (collision.kind, collision.id, collision.line))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (IdKind::Dynasty, "dynasty_a".to_string(), 1),
+                (IdKind::Character, "lineofa1".to_string(), 1),
+                (IdKind::Character, "lineofa2".to_string(), 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn renumbering_renames_the_collisions_and_what_refers_to_them() {
+        let (output, other) = colliding("renumber");
+        let others = [other];
+        let renumbered = renumber_ids(
+            &output,
+            &others,
+            IdRange {
+                first: 100,
+                last: 200,
+            },
+        )
+        .unwrap();
+        let renames: Vec<(&str, &str)> = renumbered
+            .iter()
+            .map(|r| (r.from.as_str(), r.to.as_str()))
+            .collect();
+        // 100 is the mod's.
+        assert_eq!(
+            renames,
+            [
+                ("lineofa1", "101"),
+                ("lineofa2", "102"),
+                ("dynasty_a", "103")
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(output.join(HISTORY_FILE)).unwrap(),
+            "101 = {\n\tdynasty = 103\n\treligion = x\n}\n\
+             # The heir.\n\
+             lineofa2 = {\n\tfather = 101\n\tdynasty = 103\n\treligion = x\n}\n\
+             102 = {\n\treligion = x\n\t867.1.1 = {\n\t\tadd_spouse = character:101\n\t}\n}\n",
+        );
+        assert_eq!(
+            fs::read_to_string(output.join(DYNASTIES_FILE)).unwrap(),
+            "103 = {\n\tname = dynn_103\n\tmotto = dynn_103_motto\n}\n",
+        );
+        assert_eq!(
+            fs::read_to_string(
+                output
+                    .join(LOCALIZATION_DIR)
+                    .join("dynasties_l_english.yml")
+            )
+            .unwrap(),
+            "l_english:\n dynn_103:0 \"Alda\"\n dynn_103_motto:0 \"Ever\"\n",
+        );
+        assert!(id_collisions(&output, &others).is_empty());
+    }
+
+    #[test]
+    fn renumbering_fails_once_the_range_runs_out() {
+        let (output, other) = colliding("exhausted");
+        let history = fs::read_to_string(output.join(HISTORY_FILE)).unwrap();
+        let result = renumber_ids(
+            &output,
+            &[other],
+            IdRange {
+                first: 100,
+                last: 102,
+            },
+        );
+        assert!(matches!(result, Err(SimError::Ids { .. })));
+        assert_eq!(
+            fs::read_to_string(output.join(HISTORY_FILE)).unwrap(),
+            history
+        );
+    }
+
+    #[test]
+    fn scans_suggest_an_initial_id_past_our_prefixes() {
+        let root = dir("scan");
+        fs::write(
+            root.join("history.txt"),
+            "lineofuyarazandim2480 = {\n\treligion = x\n}\n\
+             lineofuyarazandim17 = {\n\treligion = x\n}\n\
+             163110 = {\n\treligion = x\n}\n",
+        )
+        .unwrap();
+        let config = crate::testing::config();
+        let scan = scan_character_ids(&[root], &config);
+        let ranges: Vec<(&str, u64, u64, usize, bool)> = scan
+            .used
+            .iter()
+            .map(|u| (u.prefix.as_str(), u.first, u.last, u.count, u.ours))
+            .collect();
+        assert_eq!(
+            ranges,
+            [
+                ("", 163110, 163110, 1, false),
+                ("lineofuyarazandim", 17, 2480, 2, true)
+            ]
+        );
+        assert_eq!(
+            scan.initial_char_id,
+            config.initialization.initial_char_id.max(3000)
+        );
+    }
+}
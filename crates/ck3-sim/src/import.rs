@@ -0,0 +1,523 @@
+//! Continuing an existing character history.
+//!
+//! A history file, hand-written or from an earlier run, is read back into
+//! characters: their names, sexes, cultures, faiths and conversions, dynasty
+//! or cadet house, parents, skills, education, traits and blood tier, and
+//! their births, marriages, concubines, languages, legitimizations,
+//! adoptions and deaths. The run then goes on from the start of the
+//! configured period, the file's characters living to see it taking part
+//! like any other. Deaths dated on or after that start are undone, as a
+//! finished run buries its survivors after the end of its own period, and
+//! every character referred to but not in the file, such as a vanilla
+//! character, is left out. Configured dynasties without members in the
+//! file start from a progenitor as usual. Dates are read in the configured
+//! calendar, as the run that wrote the file dated them.
+
+use std::collections::HashMap;
+
+use ck3_config::{Calendar, GenderLaw};
+use paradox_script::{Block, Scalar, Value};
+
+use crate::character::{
+    Birth, Character, CharacterId, Education, EventKind, Sex, CHILDHOOD_TRAITS, SKILLS,
+};
+use crate::config::SimConfig;
+use crate::date::Date;
+use crate::dna::Dna;
+use crate::error::SimError;
+use crate::genetics::genetic_trait;
+use crate::names::NameLists;
+use crate::simulation::{random_seed, Simulation};
+
+/// Death reason of a character whose death has none.
+const DEFAULT_DEATH_REASON: &str = "death_natural_causes";
+
+/// What a character's block says about the others, by history ID.
+#[derive(Default)]
+struct Links {
+    father: Option<String>,
+    mother: Option<String>,
+    adopter: Option<String>,
+    /// Marriages recorded on the character: date, spouse and whether it is
+    /// matrilineal.
+    marriages: Vec<(Date, String, bool)>,
+    concubines: Vec<(Date, String)>,
+    /// `dynasty_house` naming something other than a configured dynasty.
+    cadet_house: Option<String>,
+    dna: Option<String>,
+}
+
+/// A union or a death, for working out the marriages standing at the end
+/// of the file.
+#[derive(Clone, Copy)]
+enum Turn {
+    /// A man taking a wife or a concubine.
+    Union {
+        man: CharacterId,
+        woman: CharacterId,
+        concubine: bool,
+    },
+    Death(CharacterId),
+}
+
+impl Simulation {
+    /// Prepare a run continuing the characters of `history`, the text of a
+    /// character history file, with their portrait DNA from `dna`, the text
+    /// of a DNA file, if given.
+    ///
+    /// Every random decision of the run is drawn from a generator seeded
+    /// with `seed`, or with a random seed if `None`.
+    pub fn from_history(
+        config: SimConfig,
+        names: NameLists,
+        seed: Option<u64>,
+        history: &str,
+        dna: Option<&str>,
+    ) -> Result<Self, SimError> {
+        let history = parse(history, "history")?;
+        let dna = dna.map(|dna| parse(dna, "DNA")).transpose()?;
+        let mut simulation = Self::empty(config, names, seed.unwrap_or_else(random_seed));
+
+        let mut links = Vec::new();
+        for entry in history.entries() {
+            let Some(block) = entry.value.as_block().filter(|block| is_character(block)) else {
+                continue;
+            };
+            let id = entry.key.text();
+            let (character, character_links) = simulation.read_character(&id, block)?;
+            simulation.reserve_id(&id);
+            simulation.characters.push(character);
+            links.push(character_links);
+        }
+        simulation.link_characters(&links);
+        simulation.attach_dna(&links, dna.as_ref());
+        simulation.revive_survivors();
+        simulation.seed_progenitors();
+        Ok(simulation)
+    }
+
+    /// A character from its block, with the references to others it makes.
+    fn read_character(&mut self, id: &str, block: &Block) -> Result<(Character, Links), SimError> {
+        let invalid = |message: &str| SimError::History {
+            message: format!("character {id} {message}"),
+        };
+        let calendar = &self.config.initialization.calendar;
+        let birth = dated(block, calendar)
+            .find(|(_, block)| block.get("birth").is_some())
+            .map(|(date, _)| date)
+            .ok_or_else(|| invalid("has no birth date"))?;
+        let mut links = Links::default();
+
+        let sex = if text(block, "female").as_deref() == Some("yes") {
+            Sex::Female
+        } else {
+            Sex::Male
+        };
+        let religion = text(block, "religion")
+            .or_else(|| text(block, "faith"))
+            .ok_or_else(|| invalid("has no faith"))?;
+        let (dynasty, is_house) = match (text(block, "dynasty"), text(block, "dynasty_house")) {
+            (Some(dynasty), _) => (Some(dynasty), false),
+            (None, Some(house)) => match self.dynasty_config(Some(&house)) {
+                Some(configured) => (Some(house), configured.is_house),
+                None => {
+                    links.cadet_house = Some(house);
+                    (None, false)
+                }
+            },
+            (None, None) => (None, false),
+        };
+        let gender_law = self
+            .dynasty_config(dynasty.as_deref())
+            .map_or(GenderLaw::AgnaticCognatic, |configured| {
+                configured.gender_law
+            });
+        let mut character = Character::born(
+            Birth {
+                id: id.to_string(),
+                name: text(block, "name").unwrap_or_default(),
+                sex,
+                birth_year: birth.year,
+                birth_date: Some(birth),
+                dynasty,
+                is_house,
+                house: None,
+                culture: text(block, "culture").ok_or_else(|| invalid("has no culture"))?,
+                religion,
+                gender_law,
+                generation: 1,
+                birth_order: 1,
+                blood_tier: 0,
+                fertility_modifier: 1.0,
+            },
+            &self.config.skills_and_traits,
+            &mut self.rng,
+        );
+        if let Some(sexuality) = text(block, "sexuality") {
+            character.sexuality = sexuality;
+        }
+        links.father = text(block, "father");
+        links.mother = text(block, "mother");
+        links.dna = text(block, "dna");
+        for (level, skill) in character.skills.iter_mut().zip(SKILLS) {
+            *level = text(block, skill)
+                .and_then(|level| level.parse().ok())
+                .unwrap_or(0);
+        }
+
+        let personality = &self.config.skills_and_traits.personality_traits.traits;
+        for name in block.get_all("trait").filter_map(scalar_text) {
+            if let Some(education) = education(&name) {
+                character.education = Some(education);
+            } else if let Some(tier) = name.strip_prefix("blood_of_numenor_") {
+                character.blood_tier = tier.parse().unwrap_or(0);
+            } else if let Some(genetic) = genetic_trait(&name) {
+                character.congenital_traits.push(genetic);
+            } else if CHILDHOOD_TRAITS.contains(&name.as_str()) {
+                character.add_event(birth.years_later(3), EventKind::Traits(vec![name]));
+            } else {
+                if personality.contains_key(&name) {
+                    character.personality_traits.push(name.clone());
+                }
+                character.add_trait(&name);
+            }
+        }
+        character.is_bastard = character.traits.iter().any(|name| name == "bastard");
+
+        let mut events: Vec<(Date, &Block)> = dated(block, calendar).collect();
+        events.sort_by_key(|&(date, _)| date);
+        for (date, entries) in events {
+            read_dated(&mut character, &mut links, date, entries);
+        }
+        Ok((character, links))
+    }
+
+    /// Resolve the references between the characters read, in the same
+    /// order as `links`, and work out what follows from them.
+    fn link_characters(&mut self, links: &[Links]) {
+        let index: HashMap<String, CharacterId> = self
+            .characters()
+            .map(|(id, character)| (character.id.clone(), id))
+            .collect();
+        let find = |id: &str| index.get(id).copied();
+
+        for (i, link) in links.iter().enumerate() {
+            let father = link.father.as_deref().and_then(find);
+            let mother = link.mother.as_deref().and_then(find);
+            let adopter = link.adopter.as_deref().and_then(find);
+            let character = &mut self.characters[i];
+            character.father = father;
+            character.mother = mother;
+            for event in &mut character.events {
+                if let EventKind::Adoption { adopter: slot } = &mut event.kind {
+                    *slot = adopter;
+                }
+            }
+            for parent in [father, mother].into_iter().flatten() {
+                self.characters[parent.0].children.push(CharacterId(i));
+            }
+        }
+        for (i, link) in links.iter().enumerate() {
+            for (date, spouse, matrilineal) in &link.marriages {
+                if let Some(spouse) = find(spouse) {
+                    self.characters[i].add_event(
+                        *date,
+                        EventKind::Marriage {
+                            spouse,
+                            matrilineal: *matrilineal,
+                        },
+                    );
+                }
+            }
+            for (date, concubine) in &link.concubines {
+                if let Some(concubine) = find(concubine) {
+                    self.characters[i].add_event(*date, EventKind::Concubinage { concubine });
+                }
+            }
+        }
+
+        // Parents come before their children once sorted by birth.
+        let mut by_birth: Vec<CharacterId> = self.ids().collect();
+        by_birth.sort_by_key(|&id| self.character(id).birth);
+        for &id in &by_birth {
+            let c = self.character(id);
+            let parents = [c.father, c.mother].into_iter().flatten();
+            let parents: Vec<&Character> = parents.map(|parent| self.character(parent)).collect();
+            let generation = parents.iter().map(|p| p.generation + 1).max().unwrap_or(1);
+            let house = links[id.0].cadet_house.clone();
+            // A cadet house belongs to the dynasty of the parent it came from.
+            let dynasty = house
+                .as_ref()
+                .and_then(|_| parents.iter().find_map(|parent| parent.dynasty.clone()));
+            let c = self.character_mut(id);
+            c.generation = generation;
+            c.is_progenitor = c.father.is_none() && c.mother.is_none() && c.has_dynasty();
+            match (house, dynasty) {
+                (Some(house), Some(dynasty)) => {
+                    c.house = Some(house);
+                    c.dynasty = Some(dynasty);
+                }
+                (Some(house), None) => {
+                    c.dynasty = Some(house);
+                    c.is_house = true;
+                }
+                _ => {}
+            }
+            if let Some(configured) = self.dynasty_config(self.character(id).dynasty.as_deref()) {
+                let gender_law = configured.gender_law;
+                self.character_mut(id).gender_law = gender_law;
+            }
+        }
+        for id in self.ids().collect::<Vec<_>>() {
+            let mut children = self.character(id).children.clone();
+            children.sort_by_key(|&child| self.character(child).birth);
+            self.character_mut(id).children = children;
+        }
+        for id in self.ids().collect::<Vec<_>>() {
+            let c = self.character(id);
+            let (father, mother) = (c.father, c.mother);
+            if let Some(mother) = mother {
+                let order = self
+                    .character(mother)
+                    .children
+                    .iter()
+                    .position(|&child| child == id);
+                self.character_mut(id).birth_order = order.map_or(1, |i| i as u32 + 1);
+            }
+            if let (Some(father), Some(mother)) = (father, mother) {
+                let year = self.character(id).birth.year;
+                let last = self.last_child_year.entry((father, mother)).or_insert(year);
+                *last = (*last).max(year);
+            }
+        }
+    }
+
+    /// Give the characters the DNA their blocks refer to, or random DNA if
+    /// the config passes portrait genes down and they have none.
+    fn attach_dna(&mut self, links: &[Links], dna: Option<&Block>) {
+        for (i, link) in links.iter().enumerate() {
+            let genes = link
+                .dna
+                .as_deref()
+                .zip(dna)
+                .and_then(|(key, dna)| dna.get(key)?.as_block()?.get("portrait_info"))
+                .and_then(|portrait| portrait.as_block()?.get("genes")?.as_block())
+                .and_then(Dna::from_genes);
+            self.characters[i].dna = match genes {
+                Some(genes) => Some(genes),
+                None if self.config.initialization.portrait_genetics.is_some() => {
+                    Some(Dna::random(&mut self.rng))
+                }
+                None => None,
+            };
+        }
+    }
+
+    /// Undo the deaths from the start of the period on, then settle who is
+    /// married to whom by going through the unions and deaths in order, as
+    /// the run that wrote the file would have.
+    fn revive_survivors(&mut self) {
+        let start = Date::new(self.config.initialization.min_year, 1, 1);
+        for character in &mut self.characters {
+            if character.death.is_some_and(|death| death >= start) {
+                character.death = None;
+                character
+                    .events
+                    .retain(|event| !matches!(event.kind, EventKind::Death { .. }));
+            }
+        }
+
+        let mut timeline: Vec<(Date, Turn)> = Vec::new();
+        for (id, character) in self.characters() {
+            timeline.extend(character.death.map(|death| (death, Turn::Death(id))));
+            for event in &character.events {
+                let (partner, concubine) = match event.kind {
+                    EventKind::Marriage { spouse, .. } => (spouse, false),
+                    EventKind::Concubinage { concubine } => (concubine, true),
+                    _ => continue,
+                };
+                let (man, woman) = if character.sex == Sex::Male {
+                    (id, partner)
+                } else {
+                    (partner, id)
+                };
+                let union = Turn::Union {
+                    man,
+                    woman,
+                    concubine,
+                };
+                timeline.push((event.date, union));
+            }
+        }
+        timeline.sort_by_key(|&(date, turn)| (date, matches!(turn, Turn::Death(_))));
+        let mut dead = vec![false; self.characters.len()];
+        for (_, turn) in timeline {
+            match turn {
+                Turn::Union {
+                    man,
+                    woman,
+                    concubine,
+                } => {
+                    self.character_mut(woman).spouse = Some(man);
+                    let husband = self.character_mut(man);
+                    if concubine {
+                        husband.concubines.push(woman);
+                    } else if husband.spouse.is_none() {
+                        husband.spouse = Some(woman);
+                    } else {
+                        husband.extra_spouses.push(woman);
+                    }
+                }
+                Turn::Death(id) => {
+                    dead[id.0] = true;
+                    if let Some(spouse) = self.character(id).spouse {
+                        if !dead[spouse.0] {
+                            self.widow(spouse, id);
+                        }
+                    }
+                    let c = self.character_mut(id);
+                    let consorts = std::mem::take(&mut c.extra_spouses)
+                        .into_iter()
+                        .chain(std::mem::take(&mut c.concubines));
+                    for consort in consorts.collect::<Vec<_>>() {
+                        if !dead[consort.0] {
+                            self.character_mut(consort).spouse = None;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether `block` describes a character rather than, say, a dynasty.
+fn is_character(block: &Block) -> bool {
+    block.get("religion").is_some() || block.get("faith").is_some()
+}
+
+/// The blocks of `block` keyed by a date in `calendar`, with their
+/// simulation dates.
+fn dated<'a>(block: &'a Block, calendar: &'a Calendar) -> impl Iterator<Item = (Date, &'a Block)> {
+    block.dated().filter_map(|(date, value)| {
+        let date = Date::new(date.year, date.month, date.day).from_calendar(calendar);
+        Some((date, value.as_block()?))
+    })
+}
+
+/// Read one dated block of a character into their events and links.
+fn read_dated(character: &mut Character, links: &mut Links, date: Date, block: &Block) {
+    let mut traits = Vec::new();
+    for entry in block.entries() {
+        let value = scalar_text(&entry.value);
+        match (entry.key.text().as_ref(), value) {
+            ("death", _) => {
+                let reason = entry
+                    .value
+                    .as_block()
+                    .and_then(|death| text(death, "death_reason"))
+                    .unwrap_or_else(|| DEFAULT_DEATH_REASON.to_string());
+                character.death = Some(date);
+                character.add_event(date, EventKind::Death { reason });
+            }
+            ("trait", Some(name)) if name == "legitimized_bastard" => {
+                character.is_bastard = false;
+                character.add_event(date, EventKind::Legitimization);
+            }
+            ("trait", Some(name)) => traits.push(name),
+            ("add_spouse", Some(spouse)) => links.marriages.push((date, spouse, false)),
+            ("add_matrilineal_spouse", Some(spouse)) => links.marriages.push((date, spouse, true)),
+            ("add_concubine", Some(concubine)) => links.concubines.push((date, concubine)),
+            ("faith" | "religion", Some(to)) => {
+                let from = std::mem::replace(&mut character.religion, to.clone());
+                character.add_event(date, EventKind::Conversion { from, to });
+            }
+            ("effect", _) => {
+                if let Some(effect) = entry.value.as_block() {
+                    read_effect(character, links, date, effect);
+                }
+            }
+            _ => {}
+        }
+    }
+    for name in &traits {
+        if !CHILDHOOD_TRAITS.contains(&name.as_str()) {
+            character.personality_traits.push(name.clone());
+        }
+    }
+    if !traits.is_empty() {
+        character.add_event(date, EventKind::Traits(traits));
+    }
+}
+
+/// Read the effects of a dated block.
+fn read_effect(character: &mut Character, links: &mut Links, date: Date, effect: &Block) {
+    for entry in effect.entries() {
+        let Some(value) = scalar_text(&entry.value) else {
+            if entry.key.text() == "create_character_memory" {
+                read_memory(links, &entry.value);
+            }
+            continue;
+        };
+        match entry.key.text().as_ref() {
+            "learn_language" => {
+                character.add_event(date, EventKind::LearnLanguage { language: value });
+            }
+            "make_trait_inactive" => character.inactive_traits.extend(genetic_trait(&value)),
+            "set_father" | "set_mother" => {
+                if entry.key.text() == "set_father" {
+                    links.father = Some(value);
+                } else {
+                    links.mother = Some(value);
+                }
+                if !character.is_adopted {
+                    character.is_adopted = true;
+                    character.add_event(date, EventKind::Adoption { adopter: None });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The adoptive parent of a `was_adopted` memory.
+fn read_memory(links: &mut Links, memory: &Value) {
+    let Some(memory) = memory.as_block() else {
+        return;
+    };
+    if text(memory, "type").as_deref() != Some("was_adopted") {
+        return;
+    }
+    links.adopter = memory
+        .get("participants")
+        .and_then(Value::as_block)
+        .and_then(|participants| text(participants, "parent"));
+}
+
+/// The education of an `education_<skill>_<tier>` trait.
+fn education(name: &str) -> Option<Education> {
+    let (skill, tier) = name.strip_prefix("education_")?.rsplit_once('_')?;
+    let skill = SKILLS.into_iter().find(|&known| known == skill)?;
+    Some(Education {
+        skill,
+        tier: tier.parse().ok()?,
+    })
+}
+
+/// The text of the first scalar under `key`.
+fn text(block: &Block, key: &str) -> Option<String> {
+    block.get(key).and_then(scalar_text)
+}
+
+fn scalar_text(value: &Value) -> Option<String> {
+    value.as_scalar().map(|scalar| match scalar.value() {
+        // Dates keep the zero padding the rest of the generator writes.
+        Scalar::Date(date) => Date::new(date.year, date.month, date.day).to_string(),
+        _ => scalar.text().into_owned(),
+    })
+}
+
+fn parse(text: &str, file: &str) -> Result<Block, SimError> {
+    paradox_script::parse(text).map_err(|err| SimError::History {
+        message: format!("the {file} file cannot be read: {err}"),
+    })
+}
@@ -0,0 +1,288 @@
+//! Regenerating only the dynasties whose inputs changed.
+//!
+//! A [`ShardCache`] keeps the dynasty runs of the last parallel run, each
+//! with a fingerprint of everything it depended on: the dynasty's config, the
+//! settings shared by every dynasty, its seed and its culture's name lists.
+//! [`Simulation::run_incremental`] only simulates again the dynasties whose
+//! fingerprint changed and merges them with the kept runs of the others.
+//!
+//! Dynasties still depend on each other through the marriages the merge step
+//! arranges between them, and through their IDs when they share an ID
+//! prefix. A change can ripple along those links, so the sections rewritten
+//! are those of every dynasty linked, in the last run or this one, to one
+//! that changed; the sections of the others are kept as they were written.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::ops::ControlFlow;
+use std::path::Path;
+
+use ck3_config::{Dynasty, InitializationConfig};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::bookmarks::write_snapshots;
+use crate::config::SimConfig;
+use crate::definitions::DYNASTIES_FILE;
+use crate::dna::DNA_FILE;
+use crate::dynasty;
+use crate::export::{self, HISTORY_FILE, LOWBORN};
+use crate::houses::HOUSES_FILE;
+use crate::localization::{write_localization, LocalizationFile};
+use crate::names::NameLists;
+use crate::simulation::{self, Simulation, Summary};
+use crate::titles::write_title_histories;
+
+/// Dynasties linked to each dynasty, both ways.
+type Links = BTreeMap<String, BTreeSet<String>>;
+
+/// What the last run of a project left for the next one to reuse.
+#[derive(Clone, Default)]
+pub struct ShardCache {
+    seed: Option<u64>,
+    shards: BTreeMap<String, Shard>,
+    links: Links,
+    /// The history file's sections as last written, by dynasty ID.
+    sections: BTreeMap<String, String>,
+    /// The dynasty definitions as last written.
+    dynasties: String,
+    /// The cadet house definitions as last written.
+    houses: String,
+    /// The portrait DNA as last written.
+    dna: String,
+    /// The localisation files as last written.
+    localization: Vec<LocalizationFile>,
+    /// The dynasties and bookmark dates of the last run.
+    initialization: Option<InitializationConfig>,
+}
+
+#[derive(Clone)]
+struct Shard {
+    fingerprint: u64,
+    run: Simulation,
+}
+
+/// The outcome of an incremental run.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Regeneration {
+    pub summary: Summary,
+    /// Dynasties simulated again, in config order.
+    pub simulated: Vec<String>,
+    /// Dynasties whose section of the history file was rewritten.
+    pub rewritten: Vec<String>,
+}
+
+impl ShardCache {
+    /// The seed of the last run, if there has been one.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// The history file as of the last run.
+    pub fn history(&self) -> String {
+        export::assemble_history(self.seed.unwrap_or_default(), &self.sections)
+    }
+
+    /// The dynasty and the cadet house definitions as of the last run.
+    pub(crate) fn definitions(&self) -> (&str, &str) {
+        (&self.dynasties, &self.houses)
+    }
+
+    /// Write the history file, the dynasty and cadet house definitions, the
+    /// portrait DNA, the localisation files, the title history and the bookmark
+    /// snapshots as of
+    /// the last run to `dir`, creating the directories if needed.
+    pub fn write_history(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        paradox_script::write_file(&dir.join(HISTORY_FILE), &self.history())?;
+        paradox_script::write_file(&dir.join(DYNASTIES_FILE), &self.dynasties)?;
+        paradox_script::write_file(&dir.join(HOUSES_FILE), &self.houses)?;
+        paradox_script::write_file(&dir.join(DNA_FILE), &self.dna)?;
+        write_localization(dir, &self.localization)?;
+        let Some(init) = &self.initialization else {
+            return Ok(());
+        };
+        write_title_histories(dir, init).map_err(io::Error::other)?;
+        write_snapshots(dir, &init.bookmark_snapshots).map_err(io::Error::other)
+    }
+}
+
+impl Simulation {
+    /// Run like [`Simulation::run_parallel`], reusing the dynasty runs in
+    /// `cache` whose inputs have not changed, and update the cache.
+    ///
+    /// Without a seed, the seed of the cached run is kept; a different seed
+    /// changes every dynasty.
+    pub fn run_incremental(
+        config: SimConfig,
+        names: NameLists,
+        seed: Option<u64>,
+        cache: &mut ShardCache,
+    ) -> (Self, Regeneration) {
+        let seed = seed.or(cache.seed).unwrap_or_else(simulation::random_seed);
+        if cache.seed != Some(seed) {
+            *cache = ShardCache::default();
+        }
+
+        let dynasties = &config.initialization.dynasties;
+        let shared = shared_fingerprint(&config);
+        let fingerprints: Vec<u64> = dynasties
+            .iter()
+            .map(|d| fingerprint(shared, d, &names))
+            .collect();
+        let stale: Vec<usize> = (0..dynasties.len())
+            .filter(|&i| {
+                cache
+                    .shards
+                    .get(&dynasties[i].dynasty_id)
+                    .is_none_or(|shard| shard.fingerprint != fingerprints[i])
+            })
+            .collect();
+        let fresh: Vec<(usize, Simulation)> = stale
+            .par_iter()
+            .map(|&i| {
+                let run =
+                    Self::simulate_shard(&config, i, &names, seed, |_| ControlFlow::Continue(()));
+                (i, run.expect("a run only stops early when asked to"))
+            })
+            .collect();
+
+        // Dynasties no longer configured count as changed too.
+        let mut changed: BTreeSet<String> = cache
+            .shards
+            .keys()
+            .filter(|id| !dynasties.iter().any(|d| &d.dynasty_id == *id))
+            .cloned()
+            .collect();
+        cache
+            .shards
+            .retain(|id, _| dynasties.iter().any(|d| &d.dynasty_id == id));
+        for (i, run) in fresh {
+            let id = dynasties[i].dynasty_id.clone();
+            changed.insert(id.clone());
+            let fingerprint = fingerprints[i];
+            cache.shards.insert(id, Shard { fingerprint, run });
+        }
+        let runs: Vec<Simulation> = dynasties
+            .iter()
+            .map(|d| cache.shards[&d.dynasty_id].run.clone())
+            .collect();
+        let simulated = stale
+            .iter()
+            .map(|&i| dynasties[i].dynasty_id.clone())
+            .collect();
+
+        let (merged, summary, marriages) = Self::merge_shards(config, names, seed, runs);
+        let links = links(&merged, &marriages);
+        let affected = reachable(&changed, [&cache.links, &links]);
+
+        let sections = merged.history_sections();
+        let mut rewritten = Vec::new();
+        let mut kept = BTreeMap::new();
+        for (group, section) in sections {
+            let cached = cache.sections.remove(&group);
+            match cached {
+                Some(cached) if group != LOWBORN && !affected.contains(&group) => {
+                    kept.insert(group, cached);
+                }
+                _ => {
+                    rewritten.push(group.clone());
+                    kept.insert(group, section);
+                }
+            }
+        }
+        cache.seed = Some(seed);
+        cache.links = links;
+        cache.sections = kept;
+        cache.dynasties = merged.dynasty_definitions();
+        cache.houses = merged.house_definitions();
+        cache.dna = merged.dna_entries();
+        cache.localization = merged.localization_files();
+        cache.initialization = Some(merged.config.initialization.clone());
+
+        let regeneration = Regeneration {
+            summary,
+            simulated,
+            rewritten,
+        };
+        (merged, regeneration)
+    }
+}
+
+/// Fingerprint of the settings every dynasty's run depends on.
+fn shared_fingerprint(config: &SimConfig) -> u64 {
+    let mut initialization = config.initialization.clone();
+    initialization.dynasties.clear();
+    let mut hasher = DefaultHasher::new();
+    for json in [
+        serde_json::to_string(&initialization),
+        serde_json::to_string(&config.life_stages),
+        serde_json::to_string(&config.skills_and_traits),
+    ] {
+        json.unwrap_or_default().hash(&mut hasher);
+    }
+    config.game_data.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Fingerprint of everything the run of `dynasty` depends on.
+fn fingerprint(shared: u64, dynasty: &Dynasty, names: &NameLists) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    shared.hash(&mut hasher);
+    serde_json::to_string(dynasty)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    names.fingerprint(&dynasty.culture_id).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Links between dynasties married by the merge step or sharing an ID prefix.
+fn links(simulation: &Simulation, marriages: &[(String, String)]) -> Links {
+    let mut links = Links::new();
+    let mut link = |a: &str, b: &str| {
+        links
+            .entry(a.to_string())
+            .or_default()
+            .insert(b.to_string());
+        links
+            .entry(b.to_string())
+            .or_default()
+            .insert(a.to_string());
+    };
+    for (a, b) in marriages {
+        link(a, b);
+    }
+    let mut by_prefix: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for d in &simulation.config.initialization.dynasties {
+        by_prefix
+            .entry(dynasty::id_prefix(Some(&d.dynasty_id)))
+            .or_default()
+            .push(&d.dynasty_id);
+    }
+    for group in by_prefix.values() {
+        for pair in group.windows(2) {
+            link(pair[0], pair[1]);
+        }
+    }
+    links
+}
+
+/// Every dynasty reachable from `start` through any of `graphs`.
+fn reachable(start: &BTreeSet<String>, graphs: [&Links; 2]) -> BTreeSet<String> {
+    let mut seen = start.clone();
+    let mut queue: Vec<String> = start.iter().cloned().collect();
+    while let Some(id) = queue.pop() {
+        for graph in graphs {
+            for next in graph.get(&id).into_iter().flatten() {
+                if seen.insert(next.clone()) {
+                    queue.push(next.clone());
+                }
+            }
+        }
+    }
+    seen
+}
@@ -0,0 +1,133 @@
+//! Family relations between characters.
+
+use std::collections::HashMap;
+
+use crate::character::CharacterId;
+use crate::simulation::Simulation;
+
+impl Simulation {
+    /// 0-based position of `id` among its parent's children by birth date.
+    pub(crate) fn sibling_index(&self, id: CharacterId) -> usize {
+        let character = self.character(id);
+        let Some(parent) = character.father.or(character.mother) else {
+            return 0;
+        };
+        let mut children = self.character(parent).children.clone();
+        children.sort_by_key(|&child| self.character(child).birth);
+        children
+            .iter()
+            .position(|&child| child == id)
+            .unwrap_or(children.len())
+    }
+
+    pub(crate) fn are_siblings(&self, a: CharacterId, b: CharacterId) -> bool {
+        let (a_ref, b_ref) = (self.character(a), self.character(b));
+        a != b
+            && ((a_ref.father.is_some() && a_ref.father == b_ref.father)
+                || (a_ref.mother.is_some() && a_ref.mother == b_ref.mother))
+    }
+
+    pub(crate) fn is_parent_of(&self, parent: CharacterId, child: CharacterId) -> bool {
+        let child = self.character(child);
+        child.father == Some(parent) || child.mother == Some(parent)
+    }
+
+    /// Coefficient of relationship between `a` and `b`, e.g. 0.5 for
+    /// siblings and 0.125 for first cousins, from the ancestors up to
+    /// `generations` back on each side. Adoptive parents are no blood
+    /// relation.
+    pub(crate) fn relatedness(&self, a: CharacterId, b: CharacterId, generations: u32) -> f64 {
+        let mut memo = HashMap::new();
+        2.0 * self.kinship(a, b, 2 * generations, &mut memo)
+    }
+
+    /// The kinship coefficient of `a` and `b`, the chance that a gene drawn
+    /// from each is inherited from the same ancestor, looking at most
+    /// `depth` parent links up.
+    fn kinship(
+        &self,
+        a: CharacterId,
+        b: CharacterId,
+        depth: u32,
+        memo: &mut HashMap<(CharacterId, CharacterId, u32), f64>,
+    ) -> f64 {
+        let key = (a.min(b), a.max(b), depth);
+        if let Some(&kinship) = memo.get(&key) {
+            return kinship;
+        }
+        let kinship = if a == b {
+            let inbreeding = match self.blood_parents(a) {
+                (Some(father), Some(mother)) if depth > 0 => {
+                    self.kinship(father, mother, depth - 1, memo)
+                }
+                _ => 0.0,
+            };
+            0.5 * (1.0 + inbreeding)
+        } else if depth == 0 {
+            0.0
+        } else {
+            // The younger cannot be an ancestor of the elder, so go up
+            // through the younger's parents.
+            let (young, old) = if self.character(a).birth >= self.character(b).birth {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            let (father, mother) = self.blood_parents(young);
+            [father, mother]
+                .into_iter()
+                .flatten()
+                .map(|parent| 0.5 * self.kinship(parent, old, depth - 1, memo))
+                .sum()
+        };
+        memo.insert(key, kinship);
+        kinship
+    }
+
+    fn blood_parents(&self, id: CharacterId) -> (Option<CharacterId>, Option<CharacterId>) {
+        let character = self.character(id);
+        if character.is_adopted {
+            (None, None)
+        } else {
+            (character.father, character.mother)
+        }
+    }
+
+    /// The parent who passed `id` its dynasty, the elder one if both did.
+    fn dynastic_parent(&self, id: CharacterId) -> Option<CharacterId> {
+        let character = self.character(id);
+        [character.father, character.mother]
+            .into_iter()
+            .flatten()
+            .filter(|&parent| {
+                let parent = self.character(parent);
+                parent.has_dynasty() && parent.dynasty == character.dynasty
+            })
+            .min_by_key(|&parent| self.sibling_index(parent))
+    }
+
+    /// Whether `a` belongs to a more senior line than `b`.
+    ///
+    /// A dynasty member outranks a lowborn, an elder sibling a younger one,
+    /// and otherwise the lines are compared one generation up until they
+    /// differ. Ties go to `a`.
+    pub(crate) fn first_is_elder(&self, a: CharacterId, b: CharacterId) -> bool {
+        let (a_ref, b_ref) = (self.character(a), self.character(b));
+        match (a_ref.has_dynasty(), b_ref.has_dynasty()) {
+            (true, false) => return true,
+            (false, true) => return false,
+            _ => {}
+        }
+        let (a_index, b_index) = (self.sibling_index(a), self.sibling_index(b));
+        if a_index != b_index {
+            return a_index < b_index;
+        }
+        match (self.dynastic_parent(a), self.dynastic_parent(b)) {
+            (Some(a_parent), Some(b_parent)) if a_parent != b_parent => {
+                self.first_is_elder(a_parent, b_parent)
+            }
+            (None, Some(_)) => false,
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! The languages characters know, and when they learned them.
+//!
+//! A dynasty's `languages` entries name a language and the years it is
+//! spoken in the dynasty: members born in that span learn it at birth, and
+//! members alive when it starts learn it then. Children also pick up the
+//! languages their parents know when they are born, and adopted wards those
+//! of their guardian when they are taken in, so a language can spread
+//! beyond its dynasty through marriage. Each is written to the history file
+//! as a `learn_language` effect on the day it was learned.
+
+use std::collections::HashMap;
+
+use crate::character::{CharacterId, EventKind};
+use crate::date::Date;
+use crate::dynasty::{self, LanguageRule};
+use crate::simulation::Simulation;
+
+impl Simulation {
+    /// Teach every living dynasty member the languages their dynasty
+    /// speaks in `year`.
+    pub(crate) fn teach_dynasty_languages(&mut self, year: i32) {
+        let spoken: HashMap<String, Vec<LanguageRule>> = self
+            .config
+            .initialization
+            .dynasties
+            .iter()
+            .map(|d| {
+                let rules = dynasty::language_rules(d)
+                    .into_iter()
+                    .filter(|rule| (rule.start..=rule.end).contains(&year))
+                    .collect::<Vec<_>>();
+                (d.dynasty_id.clone(), rules)
+            })
+            .filter(|(_, rules)| !rules.is_empty())
+            .collect();
+        if spoken.is_empty() {
+            return;
+        }
+        for id in self.ids().collect::<Vec<_>>() {
+            let character = self.character(id);
+            if !character.is_alive() {
+                continue;
+            }
+            let Some(rules) = character.dynasty.as_deref().and_then(|d| spoken.get(d)) else {
+                continue;
+            };
+            let birth = character.birth;
+            for rule in rules {
+                let date = birth.max(Date::new(rule.start, 1, 1));
+                self.learn_language(id, &rule.language, date);
+            }
+        }
+    }
+
+    /// Teach `id` on `date` every language `teacher` knows by then.
+    pub(crate) fn learn_languages_from(
+        &mut self,
+        id: CharacterId,
+        teacher: CharacterId,
+        date: Date,
+    ) {
+        let languages: Vec<String> = self
+            .character(teacher)
+            .languages(date)
+            .map(str::to_string)
+            .collect();
+        for language in languages {
+            self.learn_language(id, &language, date);
+        }
+    }
+
+    /// Record `id` learning `language` on `date`, unless they had already.
+    fn learn_language(&mut self, id: CharacterId, language: &str, date: Date) {
+        let character = self.character_mut(id);
+        let learned = character.events.iter_mut().find(|event| {
+            matches!(&event.kind, EventKind::LearnLanguage { language: known } if known == language)
+        });
+        match learned {
+            Some(event) => event.date = event.date.min(date),
+            None => character.add_event(
+                date,
+                EventKind::LearnLanguage {
+                    language: language.to_string(),
+                },
+            ),
+        }
+    }
+}
@@ -0,0 +1,532 @@
+//! Checking generated files the way the game's error log would.
+//!
+//! CK3 only reports a broken history in `error.log`, once the game has been
+//! started with the mod. [`lint_output`] reads the character history and the
+//! dynasty and house definitions of an output directory, whichever engine
+//! wrote them, and flags the same kinds of problems up front: files that do
+//! not parse, malformed dates, characters defined twice or with a field or
+//! effect given twice, parents, spouses, employers, guardians, title
+//! holders, dynasties and houses nobody defines, even in other history and
+//! dynasty files given, and, given the target game's data, traits, cultures
+//! and faiths it lacks. It
+//! also checks that the family tree could have happened: that nobody is
+//! their own ancestor, nobody is born before a parent, and nobody is born to
+//! a mother already dead or more than nine months after their father died.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use paradox_script::{Block, Date, Entry, Scalar, Value};
+use serde::Serialize;
+
+use crate::date::MONTH_DAYS;
+use crate::definitions::DYNASTIES_FILE;
+use crate::export::HISTORY_FILE;
+use crate::game_data::GameData;
+use crate::houses::HOUSES_FILE;
+use crate::ids::IdKind;
+use crate::people::TITLES_FILE;
+use crate::references::{dangling, Defined};
+
+/// Character fields the game reads once; a second one is ignored.
+const SINGLE_FIELDS: [&str; 12] = [
+    "name",
+    "female",
+    "dynasty",
+    "dynasty_house",
+    "father",
+    "mother",
+    "culture",
+    "religion",
+    "faith",
+    "sexuality",
+    "dna",
+    "disallow_random_traits",
+];
+/// Effects that take a trait.
+const TRAIT_EFFECTS: [&str; 3] = ["trait", "remove_trait", "make_trait_inactive"];
+/// Months a child can be born after their father's death.
+const GESTATION_MONTHS: i32 = 9;
+
+/// A problem in a generated file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Problem {
+    /// Name of the file, such as `family_history.txt`.
+    pub file: String,
+    /// 1-based line of the problem, or 0 for the file as a whole.
+    pub line: usize,
+    pub message: String,
+}
+
+/// The problems of the generated files in `dir`, checked against `game` if
+/// given, in file and line order. What the history and dynasty files
+/// `others`, and those in the directories among them, define counts as
+/// defined. A missing file has none.
+pub fn lint_output(dir: &Path, game: Option<&GameData>, others: &[PathBuf]) -> Vec<Problem> {
+    let mut lint = Lint {
+        game,
+        problems: Vec::new(),
+        file: "",
+    };
+    let dynasties = lint.read(dir, DYNASTIES_FILE);
+    let houses = lint.read(dir, HOUSES_FILE);
+    let history = lint.read(dir, HISTORY_FILE);
+    let titles = lint.read(dir, TITLES_FILE);
+
+    if let Some(dynasties) = &dynasties {
+        lint.file = DYNASTIES_FILE;
+        for (entry, definition) in lint.definitions(dynasties) {
+            if let Some(culture) = word(definition, "culture") {
+                lint.check_game(entry.line, culture, "culture", GameData::has_culture);
+            }
+        }
+    }
+    if let Some(houses) = &houses {
+        lint.file = HOUSES_FILE;
+        lint.definitions(houses);
+    }
+    if let Some(history) = &history {
+        lint.file = HISTORY_FILE;
+        lint.history(history);
+    }
+
+    let files: Vec<(&str, &Block)> = [
+        (DYNASTIES_FILE, &dynasties),
+        (HOUSES_FILE, &houses),
+        (HISTORY_FILE, &history),
+        (TITLES_FILE, &titles),
+    ]
+    .into_iter()
+    .filter_map(|(name, file)| Some((name, file.as_ref()?)))
+    .collect();
+    let mut defined = Defined::of_output(&files);
+    defined.add_others(others);
+    let references = dangling(&files, |kind, id| {
+        defined.contains(kind, id)
+            || match kind {
+                IdKind::Character => false,
+                IdKind::Dynasty => game.is_some_and(|game| game.has_dynasty(id)),
+                IdKind::House => game.is_some_and(|game| game.has_house(id)),
+            }
+    });
+    let mut problems = lint.problems;
+    problems.extend(references.into_iter().map(|reference| Problem {
+        file: reference.file,
+        line: reference.line,
+        message: reference.message,
+    }));
+    problems.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    problems
+}
+
+/// What the ancestry checks need of a character.
+struct Lineage {
+    line: usize,
+    father: Option<String>,
+    mother: Option<String>,
+    birth: Option<Date>,
+    death: Option<Date>,
+}
+
+struct Lint<'a> {
+    game: Option<&'a GameData>,
+    problems: Vec<Problem>,
+    /// The file being checked.
+    file: &'static str,
+}
+
+impl Lint<'_> {
+    fn problem(&mut self, line: usize, message: String) {
+        self.problems.push(Problem {
+            file: self.file.to_string(),
+            line,
+            message,
+        });
+    }
+
+    /// The parsed file `name` in `dir`, if there is one that parses.
+    fn read(&mut self, dir: &Path, name: &'static str) -> Option<Block> {
+        let bytes = fs::read(dir.join(name)).ok()?;
+        let text = String::from_utf8_lossy(&bytes);
+        let text = text.strip_prefix(paradox_script::BOM).unwrap_or(&text);
+        self.file = name;
+        match paradox_script::parse(text) {
+            Ok(file) => Some(file),
+            Err(err) => {
+                self.problem(err.line, err.message);
+                None
+            }
+        }
+    }
+
+    /// The top-level definitions of a definitions file, flagging any given
+    /// twice.
+    fn definitions<'b>(&mut self, file: &'b Block) -> Vec<(&'b Entry, &'b Block)> {
+        let mut seen = HashSet::new();
+        let mut definitions = Vec::new();
+        for entry in file.entries() {
+            let Some(definition) = entry.value.as_block() else {
+                continue;
+            };
+            let key = entry.key.text();
+            if !seen.insert(key.clone()) {
+                self.problem(entry.line, format!("'{key}' is defined more than once"));
+            }
+            definitions.push((entry, definition));
+        }
+        definitions
+    }
+
+    /// Flag `id` as an undefined `kind` if the game lacks it.
+    fn check_game(
+        &mut self,
+        line: usize,
+        id: &str,
+        kind: &str,
+        defines: fn(&GameData, &str) -> bool,
+    ) {
+        if self.game.is_some_and(|game| !defines(game, id)) {
+            self.problem(line, format!("unknown {kind} '{id}'"));
+        }
+    }
+
+    fn history(&mut self, history: &Block) {
+        let characters: Vec<(&Entry, &Block)> = history
+            .entries()
+            .filter_map(|entry| Some((entry, entry.value.as_block()?)))
+            .collect();
+        let mut defined: HashMap<String, usize> = HashMap::new();
+        for (entry, _) in &characters {
+            let id = entry.key.text().into_owned();
+            if let Some(first) = defined.get(&id) {
+                let message = format!("character '{id}' is already defined at line {first}");
+                self.problem(entry.line, message);
+            } else {
+                defined.insert(id, entry.line);
+            }
+        }
+
+        let mut lineages: Vec<(String, Lineage)> = Vec::new();
+        for (entry, character) in characters {
+            let id = entry.key.text();
+            let parent = |key| {
+                let parent = character.get(key)?.as_scalar()?.text();
+                Some(parent.into_owned())
+            };
+            let mut lineage = Lineage {
+                line: entry.line,
+                father: parent("father"),
+                mother: parent("mother"),
+                birth: None,
+                death: None,
+            };
+            let mut fields = HashSet::new();
+            let (mut births, mut deaths) = (0, 0);
+            for field in character.entries() {
+                let key = field.key.text();
+                let value = field.value.as_scalar().map(Scalar::text);
+                if SINGLE_FIELDS.contains(&key.as_ref()) && !fields.insert(key.clone()) {
+                    let message = format!("character '{id}' sets '{key}' more than once");
+                    self.problem(field.line, message);
+                }
+                match (key.as_ref(), value.as_deref()) {
+                    ("culture", Some(culture)) => {
+                        self.check_game(field.line, culture, "culture", GameData::has_culture);
+                    }
+                    ("religion" | "faith", Some(faith)) => {
+                        self.check_game(field.line, faith, "faith", GameData::has_faith);
+                    }
+                    ("trait", Some(name)) => {
+                        self.check_game(field.line, name, "trait", GameData::has_trait);
+                    }
+                    _ => {}
+                }
+                if let Some(block) = field.value.as_block() {
+                    if !matches!(field.key.value(), Scalar::Date(_)) {
+                        if is_date_like(&key) {
+                            self.problem(field.line, format!("malformed date '{key}'"));
+                        }
+                        continue;
+                    }
+                    if let &Scalar::Date(date) = field.key.value() {
+                        if !is_calendar_date(date) {
+                            self.problem(field.line, format!("malformed date '{key}'"));
+                        }
+                        if block.get("birth").is_some() {
+                            lineage.birth.get_or_insert(date);
+                        }
+                        if block.get("death").is_some() {
+                            lineage.death.get_or_insert(date);
+                        }
+                    }
+                    births += block.get_all("birth").count();
+                    deaths += block.get_all("death").count();
+                    self.dated_block(block);
+                }
+            }
+            if births > 1 {
+                self.problem(
+                    entry.line,
+                    format!("character '{id}' is born more than once"),
+                );
+            }
+            if deaths > 1 {
+                self.problem(entry.line, format!("character '{id}' dies more than once"));
+            }
+            if defined.get(id.as_ref()) == Some(&entry.line) {
+                lineages.push((id.into_owned(), lineage));
+            }
+        }
+        self.ancestry(&lineages);
+    }
+
+    /// Flag ancestors that cannot be: cycles of parents, and children born
+    /// before a parent or after their death.
+    fn ancestry(&mut self, lineages: &[(String, Lineage)]) {
+        let index: HashMap<&str, usize> = lineages
+            .iter()
+            .enumerate()
+            .map(|(i, (id, _))| (id.as_str(), i))
+            .collect();
+        let parents = |i: usize| {
+            let lineage = &lineages[i].1;
+            [("father", &lineage.father), ("mother", &lineage.mother)]
+                .into_iter()
+                .filter_map(|(role, parent)| Some((role, *index.get(parent.as_deref()?)?)))
+        };
+
+        for (i, (id, lineage)) in lineages.iter().enumerate() {
+            let Some(birth) = lineage.birth else {
+                continue;
+            };
+            for (role, parent) in parents(i) {
+                let (parent_id, parent) = &lineages[parent];
+                if parent.birth.is_some_and(|born| born > birth) {
+                    let message = format!(
+                        "character '{id}' is born on {birth}, before their {role} \
+                         '{parent_id}' on {}",
+                        parent.birth.expect("checked above")
+                    );
+                    self.problem(lineage.line, message);
+                }
+                let Some(death) = parent.death else {
+                    continue;
+                };
+                // A father can die while the child is carried; a mother
+                // cannot.
+                let latest = match role {
+                    "father" => months_later(death, GESTATION_MONTHS),
+                    _ => death,
+                };
+                if birth > latest {
+                    let message = format!(
+                        "character '{id}' is born on {birth}, after their {role} \
+                         '{parent_id}' died on {death}"
+                    );
+                    self.problem(lineage.line, message);
+                }
+            }
+        }
+
+        // Depth-first over parents, with the path walked so far: a parent
+        // already on it closes a cycle.
+        let mut done = vec![false; lineages.len()];
+        let mut on_path = vec![false; lineages.len()];
+        for start in 0..lineages.len() {
+            if done[start] {
+                continue;
+            }
+            let mut path: Vec<(usize, Vec<usize>)> =
+                vec![(start, parents(start).map(|(_, p)| p).collect())];
+            on_path[start] = true;
+            while let Some((i, pending)) = path.last_mut() {
+                let i = *i;
+                let Some(parent) = pending.pop() else {
+                    done[i] = true;
+                    on_path[i] = false;
+                    path.pop();
+                    continue;
+                };
+                if on_path[parent] {
+                    let from = path
+                        .iter()
+                        .position(|(i, _)| *i == parent)
+                        .expect("the parent is on the path");
+                    let cycle: Vec<&str> = path[from..]
+                        .iter()
+                        .map(|(i, _)| lineages[*i].0.as_str())
+                        .chain([lineages[parent].0.as_str()])
+                        .collect();
+                    let (id, lineage) = &lineages[parent];
+                    let message = format!(
+                        "character '{id}' is their own ancestor ({})",
+                        cycle.join(", child of ")
+                    );
+                    self.problem(lineage.line, message);
+                } else if !done[parent] {
+                    on_path[parent] = true;
+                    path.push((parent, parents(parent).map(|(_, p)| p).collect()));
+                }
+            }
+        }
+    }
+
+    /// Check the entries of a dated block and of the effects in it.
+    fn dated_block(&mut self, block: &Block) {
+        let mut seen = HashSet::new();
+        for entry in block.entries() {
+            let key = entry.key.text();
+            match &entry.value {
+                Value::Scalar(value) => {
+                    let value = value.text();
+                    if TRAIT_EFFECTS.contains(&key.as_ref()) {
+                        self.check_game(entry.line, &value, "trait", GameData::has_trait);
+                    }
+                    if !seen.insert((key.clone(), value.clone())) {
+                        self.problem(entry.line, format!("duplicate effect '{key} = {value}'"));
+                    }
+                }
+                Value::Block(effects) if key == "effect" => self.dated_block(effects),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The word value of the first `key` entry of `block`.
+fn word<'a>(block: &'a Block, key: &str) -> Option<&'a str> {
+    match block.get(key)?.as_scalar()? {
+        Scalar::Word(word) | Scalar::Quoted(word) => Some(word),
+        _ => None,
+    }
+}
+
+/// Whether a key that did not read as a date looks like it was meant as one.
+fn is_date_like(key: &str) -> bool {
+    key.contains('.') && key.bytes().all(|b| b.is_ascii_digit() || b == b'.')
+}
+
+/// The same day `months` later.
+fn months_later(date: Date, months: i32) -> Date {
+    let month = date.year * 12 + i32::from(date.month) - 1 + months;
+    Date::new(
+        month.div_euclid(12),
+        (month.rem_euclid(12) + 1) as u8,
+        date.day,
+    )
+}
+
+fn is_calendar_date(date: Date) -> bool {
+    date.day <= MONTH_DAYS[usize::from(date.month) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    /// The messages of the problems of the history `history`.
+    fn messages(name: &str, history: &str) -> Vec<(usize, String)> {
+        let dir = testing::output(name, history);
+        let problems = lint_output(&dir, None, &[]);
+        fs::remove_dir_all(&dir).unwrap();
+        problems
+            .into_iter()
+            .map(|problem| {
+                assert_eq!(problem.file, HISTORY_FILE);
+                (problem.line, problem.message)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_sound_family_has_no_problems() {
+        let history = "\
+a1 = { name = \"Aldor\" 6000.1.1 = { birth = yes } 6060.1.1 = { death = yes } }
+b1 = { name = \"Beren\" female = yes 6002.1.1 = { birth = yes } }
+a2 = { name = \"Anborn\" father = a1 mother = b1 6025.1.1 = { birth = yes } }
+a3 = { name = \"Ioreth\" father = a1 mother = b1 6060.8.1 = { birth = yes } }
+";
+        assert_eq!(messages("sound", history), []);
+    }
+
+    #[test]
+    fn characters_who_are_their_own_ancestors_are_flagged() {
+        let history = "\
+a1 = { father = a3 6000.1.1 = { birth = yes } }
+a2 = { father = a1 6020.1.1 = { birth = yes } }
+a3 = { father = a2 }
+b1 = { mother = b1 }
+";
+        assert_eq!(
+            messages("cycle", history),
+            [
+                (
+                    1,
+                    "character 'a1' is their own ancestor (a1, child of a3, child of a2, \
+                     child of a1)"
+                        .to_string()
+                ),
+                (
+                    4,
+                    "character 'b1' is their own ancestor (b1, child of b1)".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn children_born_before_a_parent_are_flagged() {
+        let history = "\
+a1 = { 6010.1.1 = { birth = yes } }
+b1 = { female = yes 6002.1.1 = { birth = yes } }
+a2 = { father = a1 mother = b1 6005.3.1 = { birth = yes } }
+";
+        assert_eq!(
+            messages("born-before", history),
+            [(
+                3,
+                "character 'a2' is born on 6005.3.1, before their father 'a1' on 6010.1.1"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn children_born_after_a_parent_died_are_flagged() {
+        // The father may die while the child is carried, up to nine months
+        // before the birth; the mother may not.
+        let history = "\
+a1 = { 6000.1.1 = { birth = yes } 6040.1.1 = { death = yes } }
+b1 = { female = yes 6002.1.1 = { birth = yes } 6050.1.1 = { death = yes } }
+a2 = { father = a1 mother = b1 6040.10.1 = { birth = yes } }
+a3 = { father = a1 mother = b1 6040.10.2 = { birth = yes } }
+a4 = { father = a1 mother = b1 6050.1.2 = { birth = yes } }
+";
+        assert_eq!(
+            messages("born-after", history),
+            [
+                (
+                    4,
+                    "character 'a3' is born on 6040.10.2, after their father 'a1' died on \
+                     6040.1.1"
+                        .to_string()
+                ),
+                (
+                    5,
+                    "character 'a4' is born on 6050.1.2, after their father 'a1' died on \
+                     6040.1.1"
+                        .to_string()
+                ),
+                (
+                    5,
+                    "character 'a4' is born on 6050.1.2, after their mother 'b1' died on \
+                     6050.1.1"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Localisation of the names the generated files refer to.
+//!
+//! Dynasty and house definitions name themselves by localisation key, and
+//! the game shows a key it finds no text for as it is. Every run therefore
+//! writes `localization/<language>/lotr_dynasty_names_l_<language>.yml`,
+//! with the names of the configured dynasties and of the cadet houses they
+//! split into, and `lotr_mottos_l_<language>.yml` with the dynasties' mottos,
+//! under the same keys the Python engine's dynasty definitions use. The
+//! names are not translated: each language configured gets the same text,
+//! so that none of them shows raw keys.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ck3_config::DEFAULT_LANGUAGE;
+
+use crate::simulation::Simulation;
+
+/// Directory of the localisation files, relative to the output directory.
+pub const LOCALIZATION_DIR: &str = "localization";
+/// Start of the name of the file of dynasty and house names.
+const NAMES_FILE: &str = "lotr_dynasty_names";
+/// Start of the name of the file of dynasty mottos.
+const MOTTOS_FILE: &str = "lotr_mottos";
+
+/// A localisation file's path, relative to the output directory, and its
+/// contents.
+pub(crate) type LocalizationFile = (PathBuf, String);
+
+impl Simulation {
+    /// The localisation files of every configured language.
+    pub(crate) fn localization_files(&self) -> Vec<LocalizationFile> {
+        let dynasties = &self.config.initialization.dynasties;
+        let mut names: Vec<(String, &str)> = dynasties
+            .iter()
+            .filter(|d| !d.dynasty_name.is_empty())
+            .map(|d| (dynasty_key(&d.dynasty_id), d.dynasty_name.as_str()))
+            .collect();
+        names.extend(
+            self.house_founders()
+                .into_iter()
+                .map(|(house, founder)| (house_key(house), founder.name.as_str())),
+        );
+        let mottos: Vec<(String, &str)> = dynasties
+            .iter()
+            .filter(|d| !d.dynasty_motto.is_empty())
+            .map(|d| (motto_key(&d.dynasty_id), d.dynasty_motto.as_str()))
+            .collect();
+
+        let mut files = Vec::new();
+        for language in self.config.initialization.languages() {
+            for (file, entries) in [(NAMES_FILE, &names), (MOTTOS_FILE, &mottos)] {
+                let path = Path::new(LOCALIZATION_DIR)
+                    .join(language)
+                    .join(format!("{file}_l_{language}.yml"));
+                files.push((path, localization(language, entries)));
+            }
+        }
+        files
+    }
+}
+
+/// Write `files` under `dir`, creating their directories if needed.
+pub(crate) fn write_localization(dir: &Path, files: &[LocalizationFile]) -> io::Result<()> {
+    for (path, text) in files {
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        paradox_script::write_file(&path, text)?;
+    }
+    Ok(())
+}
+
+/// The dynasty and house names localised in the output directory `dir`, by
+/// key: in English if there are English names, or else in the first language
+/// there are any in. Empty if there are none.
+pub fn read_names(dir: &Path) -> HashMap<String, String> {
+    let mut languages: Vec<String> = fs::read_dir(dir.join(LOCALIZATION_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    languages.sort_by_key(|language| (language != DEFAULT_LANGUAGE, language.clone()));
+    languages
+        .iter()
+        .find_map(|language| {
+            let path = Path::new(LOCALIZATION_DIR)
+                .join(language)
+                .join(format!("{NAMES_FILE}_l_{language}.yml"));
+            fs::read_to_string(dir.join(path)).ok()
+        })
+        .map(|text| text.lines().filter_map(entry).collect())
+        .unwrap_or_default()
+}
+
+/// The key and text of a ` key:0 "text"` line.
+fn entry(line: &str) -> Option<(String, String)> {
+    let (key, rest) = line.trim().split_once(':')?;
+    let text = rest.split_once('"')?.1.strip_suffix('"')?;
+    let text = text.replace("\\\"", "\"").replace("\\\\", "\\");
+    Some((key.to_string(), text))
+}
+
+/// The localisation key of the name of the dynasty `id`.
+pub fn dynasty_key(id: &str) -> String {
+    format!("dynn_{}", id.strip_prefix("dynasty_").unwrap_or(id))
+}
+
+/// The localisation key of the motto of the dynasty `id`.
+pub(crate) fn motto_key(id: &str) -> String {
+    format!("{}_motto", dynasty_key(id))
+}
+
+/// The localisation key of the name of the cadet house `id`.
+pub fn house_key(id: &str) -> String {
+    format!("dynn_{id}")
+}
+
+/// A localisation file of `entries` for `language`: a `l_<language>:` header
+/// and a ` key:0 "text"` line for each entry.
+fn localization(language: &str, entries: &[(String, &str)]) -> String {
+    let mut out = format!("l_{language}:\n");
+    for (key, text) in entries {
+        let text = text.replace('\\', "\\\\").replace('"', "\\\"");
+        out.push_str(&format!(" {key}:0 \"{text}\"\n"));
+    }
+    out
+}
@@ -0,0 +1,255 @@
+//! Marriages between dynasty members and with lowborn spouses.
+//!
+//! Each year the unmarried are entered into the marriage pool with their
+//! age's marriage rate, then the men are matched with women of other
+//! dynasties, smallest dynasties first so that they get the pick, by the
+//! rules and preferences of [`crate::matchmaking`]. A man left without a
+//! match may settle for a lowborn wife, more readily the fewer fertile
+//! members his dynasty has left, or else marry within his dynasty.
+
+use crate::character::{Birth, CharacterId, EventKind, Sex};
+use crate::date::Date;
+use crate::dynasty;
+use crate::matchmaking::{alliance_key, BLOOD_TIER_MARRIAGE_YEARS};
+use crate::random;
+use crate::simulation::{Simulation, FERTILE_FROM};
+/// Chance of a man of a dynasty that prioritises lowborn marriages looking
+/// no further than a lowborn wife.
+const LOWBORN_PRIORITY_CHANCE: f64 = 0.6;
+
+impl Simulation {
+    pub(crate) fn arrange_marriages(&mut self, year: i32) {
+        let (mut men, mut women) = self.marriage_pools();
+        if men.is_empty() {
+            return;
+        }
+        if women.is_empty() {
+            // Without this, dynasties seeded with only male progenitors would
+            // never marry.
+            for man in men {
+                self.try_desperate_marriage(man, year);
+            }
+            return;
+        }
+
+        let size = |sim: &Simulation, id: CharacterId| {
+            let dynasty = sim.character(id).dynasty.as_deref();
+            sim.characters
+                .iter()
+                .filter(|c| c.is_alive() && c.dynasty.as_deref() == dynasty)
+                .count()
+        };
+        men.sort_by_cached_key(|&id| (size(self, id), self.character(id).birth_order));
+        women.sort_by_cached_key(|&id| (size(self, id), self.character(id).birth_order));
+        let mut alliances = self.alliances();
+
+        for man in men {
+            let m = self.character(man);
+            if !m.is_alive() || m.is_married() || !m.can_marry() {
+                continue;
+            }
+            let prioritises_lowborn = self
+                .dynasty_config(m.dynasty.as_deref())
+                .is_some_and(|d| d.prioritise_lowborn_marriage);
+            if prioritises_lowborn && random::chance(&mut self.rng, LOWBORN_PRIORITY_CHANCE) {
+                self.marry_lowborn(man, year);
+                continue;
+            }
+
+            let outside: Vec<CharacterId> = women
+                .iter()
+                .copied()
+                .filter(|&woman| self.can_wed(man, woman, false))
+                .collect();
+            if !outside.is_empty() {
+                if let Some(wife) = self.pick_match(man, &outside, &alliances) {
+                    self.marry(man, wife, year);
+                    let dynasties = (
+                        self.character(man).dynasty.as_deref(),
+                        self.character(wife).dynasty.as_deref(),
+                    );
+                    if let Some(key) = alliance_key(dynasties.0, dynasties.1) {
+                        *alliances.entry(key).or_default() += 1;
+                    }
+                }
+                continue;
+            }
+            if self.try_desperate_marriage(man, year) {
+                continue;
+            }
+            let within: Vec<CharacterId> = women
+                .iter()
+                .copied()
+                .filter(|&woman| self.can_wed(man, woman, true))
+                .collect();
+            if let Some(wife) = self.pick_match(man, &within, &alliances) {
+                self.marry(man, wife, year);
+            }
+        }
+    }
+
+    /// The unmarried men and women looking for a spouse this year.
+    fn marriage_pools(&mut self) -> (Vec<CharacterId>, Vec<CharacterId>) {
+        let (mut men, mut women) = (Vec::new(), Vec::new());
+        for id in self.ids().collect::<Vec<_>>() {
+            let character = self.character(id);
+            if !character.is_alive() || character.is_married() || !character.can_marry() {
+                continue;
+            }
+            let delay = character.blood_tier as i32 * BLOOD_TIER_MARRIAGE_YEARS;
+            if character.age < FERTILE_FROM + delay {
+                continue;
+            }
+            let rates = &self.config.life_stages.marriage_rates;
+            let (table, pool) = match character.sex {
+                Sex::Male => (&rates.male, &mut men),
+                Sex::Female => (&rates.female, &mut women),
+            };
+            let effective = ((character.age - delay).max(0) as usize).min(table.len() - 1);
+            let rate = table[effective];
+            if rate > 0.0 && random::chance(&mut self.rng, rate) {
+                pool.push(id);
+            }
+        }
+        (men, women)
+    }
+
+    /// Whether `man` may marry `woman`, who must be of his dynasty if
+    /// `same_dynasty` and of another one otherwise.
+    pub(crate) fn can_wed(&self, man: CharacterId, woman: CharacterId, same_dynasty: bool) -> bool {
+        let (m, w) = (self.character(man), self.character(woman));
+        if !w.is_alive() || w.is_married() || !w.can_marry() {
+            return false;
+        }
+        if (w.dynasty == m.dynasty) != same_dynasty {
+            return false;
+        }
+        self.ages_fit(man, woman, m.age, w.age) && self.may_match(man, woman)
+    }
+
+    /// Chance of `id` settling for a lowborn spouse this year.
+    fn desperation(&self, id: CharacterId) -> f64 {
+        let character = self.character(id);
+        let rates = &self.config.life_stages.desperation_marriage_rates;
+        if rates.is_empty() {
+            return 0.0;
+        }
+        let effective = ((character.age - character.blood_tier as i32 * BLOOD_TIER_MARRIAGE_YEARS)
+            .max(0) as usize)
+            .min(rates.len() - 1);
+        let fertile = self.fertile_members(character.dynasty.as_deref());
+        let modifier = 10usize.saturating_sub(fertile) as f64 * 0.20 + 1.0;
+        (rates[effective] * modifier).min(1.0)
+    }
+
+    /// Marry `id` to a new lowborn with their desperation chance; returns
+    /// whether they married.
+    fn try_desperate_marriage(&mut self, id: CharacterId, year: i32) -> bool {
+        let character = self.character(id);
+        if !character.is_alive() || character.is_married() || !character.can_marry() {
+            return false;
+        }
+        let min_age = FERTILE_FROM + character.blood_tier as i32 * BLOOD_TIER_MARRIAGE_YEARS;
+        if character.age < min_age {
+            return false;
+        }
+        let chance = self.desperation(id);
+        if random::chance(&mut self.rng, chance) {
+            self.marry_lowborn(id, year);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Create a lowborn spouse of the opposite sex for `id` and marry them.
+    pub(crate) fn marry_lowborn(&mut self, id: CharacterId, year: i32) {
+        let spouse = self.spawn_lowborn_match(id, year);
+        self.marry(id, spouse, year);
+    }
+
+    /// Create an unmarried lowborn of the opposite sex for `id` to wed.
+    pub(crate) fn spawn_lowborn_match(&mut self, id: CharacterId, year: i32) -> CharacterId {
+        let character = self.character(id).clone();
+        let spouse_id = self.next_id(character.dynasty.as_deref());
+        let sex = character.sex.opposite();
+        let name = self.names.random(&mut self.rng, &character.culture, sex);
+        let age = rand::Rng::random_range(&mut self.rng, 18..=26);
+        let spouse = self.spawn_grown(Birth {
+            id: spouse_id,
+            name,
+            sex,
+            birth_year: year - age,
+            birth_date: None,
+            dynasty: None,
+            is_house: false,
+            house: None,
+            culture: character.culture,
+            religion: character.religion,
+            gender_law: character.gender_law,
+            generation: character.generation,
+            birth_order: 1,
+            blood_tier: character.blood_tier,
+            fertility_modifier: 1.0,
+        });
+        self.character_mut(spouse).age = age;
+        spouse
+    }
+
+    /// Marry `first` to `second`. The marriage is recorded on `first`,
+    /// matrilineal if `first` is a woman unless their law passes the dynasty
+    /// through men, or on `second` if she is a woman of a dynasty whose women
+    /// always marry matrilineally.
+    pub(crate) fn marry(&mut self, first: CharacterId, second: CharacterId, year: i32) {
+        let (first, second) = if self.marries_matrilineally(second) {
+            (second, first)
+        } else {
+            (first, second)
+        };
+        let (a, b) = (self.character(first), self.character(second));
+        if first == second || a.is_married() || b.is_married() || !a.is_alive() || !b.is_alive() {
+            return;
+        }
+        let matrilineal = if self.marries_matrilineally(first) {
+            true
+        } else if dynasty::patrilineal(a.gender_law) && a.sex == Sex::Male {
+            false
+        } else if dynasty::matrilineal(a.gender_law) && a.sex == Sex::Female {
+            true
+        } else {
+            a.sex == Sex::Female
+        };
+        let date = Date::random_in(year, &mut self.rng);
+        self.character_mut(first).spouse = Some(second);
+        self.character_mut(second).spouse = Some(first);
+        self.character_mut(first).add_event(
+            date,
+            EventKind::Marriage {
+                spouse: second,
+                matrilineal,
+            },
+        );
+        self.marriage_hooks(first, second, year);
+    }
+
+    /// Whether `id` is a woman of a dynasty whose women always marry
+    /// matrilineally.
+    pub(crate) fn marries_matrilineally(&self, id: CharacterId) -> bool {
+        let c = self.character(id);
+        c.sex == Sex::Female
+            && self
+                .dynasty_config(c.dynasty.as_deref())
+                .is_some_and(|d| d.matrilineal_marriage)
+    }
+
+    /// Whether `mother` married `father` matrilineally, so that their
+    /// children join her line.
+    pub(crate) fn married_matrilineally(&self, mother: CharacterId, father: CharacterId) -> bool {
+        self.character(mother).events.iter().any(|event| {
+            matches!(
+                event.kind,
+                EventKind::Marriage { spouse, matrilineal: true } if spouse == father
+            )
+        })
+    }
+}
@@ -0,0 +1,297 @@
+//! Checking the marriages of a history against the project's rules.
+//!
+//! The game takes whatever marriages a history gives it: a child bride, a
+//! husband wed years after his death, a second wife in a monogamous faith or
+//! a mother married to her son only show once someone plays the period.
+//! [`check_marriages`] goes through the marriages of a history's characters,
+//! whichever engine wrote them, and flags those breaking the rules of the
+//! config's `marriageChecks` section, each with the severity it gives that
+//! rule. The native simulation checks its own before writing them, and
+//! refuses to write a history with errors.
+
+use std::collections::{HashMap, HashSet};
+
+use ck3_config::{InitializationConfig, MarriageChecks, Severity};
+use paradox_script::Date;
+use serde::Serialize;
+
+use crate::people::Person;
+
+/// A marriage breaking one of the rules.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarriageProblem {
+    pub severity: Severity,
+    /// The character the problem is with, and the one they marry.
+    pub character: String,
+    pub spouse: String,
+    pub message: String,
+}
+
+/// The problems of the marriages of `people`, by the rules of `config`, in
+/// the order the marriages happen.
+pub fn check_marriages(people: &[Person], config: &InitializationConfig) -> Vec<MarriageProblem> {
+    let index: HashMap<&str, usize> = people
+        .iter()
+        .enumerate()
+        .map(|(i, person)| (person.id.as_str(), i))
+        .collect();
+
+    // Either side, or both, may record a marriage or its end.
+    let mut marriages: Vec<(Date, usize, usize)> = Vec::new();
+    let mut recorded = HashSet::new();
+    let mut divorces: HashMap<(usize, usize), Vec<Date>> = HashMap::new();
+    for (i, person) in people.iter().enumerate() {
+        for (date, spouse) in &person.marriages {
+            let Some(&spouse) = index.get(spouse.as_str()) else {
+                continue;
+            };
+            if recorded.insert((*date, i.min(spouse), i.max(spouse))) {
+                marriages.push((*date, i, spouse));
+            }
+        }
+        for (date, key, spouse) in &person.events {
+            if key != "remove_spouse" {
+                continue;
+            }
+            if let Some(&spouse) = index.get(spouse.as_str()) {
+                divorces
+                    .entry((i.min(spouse), i.max(spouse)))
+                    .or_default()
+                    .push(*date);
+            }
+        }
+    }
+    marriages.sort_by_key(|(date, _, _)| *date);
+
+    let mut check = Check {
+        people,
+        rules: &config.marriage_checks,
+        config,
+        problems: Vec::new(),
+    };
+    for (n, &(date, a, b)) in marriages.iter().enumerate() {
+        for (x, y) in [(a, b), (b, a)] {
+            check.alive(x, y, date);
+            check.age(x, y, date);
+            // The marriages before this one that have not ended by its day.
+            let married: Vec<usize> = marriages[..n]
+                .iter()
+                .filter_map(|&(since, p, q)| {
+                    let other = if p == x {
+                        q
+                    } else if q == x {
+                        p
+                    } else {
+                        return None;
+                    };
+                    let ended = people[other].death.is_some_and(|death| death <= date)
+                        || divorces
+                            .get(&(x.min(other), x.max(other)))
+                            .into_iter()
+                            .flatten()
+                            .any(|&divorce| since <= divorce && divorce <= date);
+                    (other != y && !ended).then_some(other)
+                })
+                .collect();
+            check.spouses(x, y, date, &married);
+        }
+        check.kinship(a, b, date);
+    }
+    check.problems
+}
+
+struct Check<'a> {
+    people: &'a [Person],
+    rules: &'a MarriageChecks,
+    config: &'a InitializationConfig,
+    problems: Vec<MarriageProblem>,
+}
+
+impl Check<'_> {
+    fn problem(&mut self, severity: Severity, x: usize, y: usize, message: String) {
+        if severity != Severity::Off {
+            self.problems.push(MarriageProblem {
+                severity,
+                character: self.people[x].id.clone(),
+                spouse: self.people[y].id.clone(),
+                message,
+            });
+        }
+    }
+
+    fn alive(&mut self, x: usize, y: usize, date: Date) {
+        let (person, spouse) = (&self.people[x], &self.people[y].id);
+        let message = if let Some(birth) = person.birth.filter(|&birth| birth > date) {
+            format!(
+                "character '{}' marries '{spouse}' on {date}, before they are born on {birth}",
+                person.id
+            )
+        } else if let Some(death) = person.death.filter(|&death| death < date) {
+            format!(
+                "character '{}' marries '{spouse}' on {date}, after they died on {death}",
+                person.id
+            )
+        } else {
+            return;
+        };
+        self.problem(self.rules.dead_spouse, x, y, message);
+    }
+
+    fn age(&mut self, x: usize, y: usize, date: Date) {
+        let (person, spouse) = (&self.people[x], &self.people[y].id);
+        let Some(birth) = person.birth.filter(|&birth| birth <= date) else {
+            return;
+        };
+        let age =
+            date.year - birth.year - i32::from((date.month, date.day) < (birth.month, birth.day));
+        if age < self.rules.min_age as i32 {
+            let message = format!(
+                "character '{}' marries '{spouse}' on {date} aged {age}, younger than {}",
+                person.id, self.rules.min_age
+            );
+            self.problem(self.rules.underage, x, y, message);
+        }
+    }
+
+    /// Flag `x` marrying `y` while already married to the spouses `married`,
+    /// if their doctrine has no room for another.
+    fn spouses(&mut self, x: usize, y: usize, date: Date, married: &[usize]) {
+        if married.len() < self.max_spouses(x) {
+            return;
+        }
+        let spouses: Vec<&str> = married
+            .iter()
+            .map(|&spouse| self.people[spouse].id.as_str())
+            .collect();
+        let message = format!(
+            "character '{}' marries '{}' on {date} while married to '{}'",
+            self.people[x].id,
+            self.people[y].id,
+            spouses.join("', '")
+        );
+        self.problem(self.rules.already_married, x, y, message);
+    }
+
+    fn kinship(&mut self, a: usize, b: usize, date: Date) {
+        let is_parent = |parent: usize, child: usize| {
+            let (parent, child) = (&self.people[parent].id, &self.people[child]);
+            child.father.as_ref() == Some(parent) || child.mother.as_ref() == Some(parent)
+        };
+        for (parent, child) in [(a, b), (b, a)] {
+            if is_parent(parent, child) {
+                let message = format!(
+                    "character '{}' marries their child '{}' on {date}",
+                    self.people[parent].id, self.people[child].id
+                );
+                self.problem(self.rules.parent_child, parent, child, message);
+            }
+        }
+    }
+
+    /// How many wives a man may have at once by the marriage doctrine of his
+    /// dynasty, or else his faith; women and men of monogamous faiths have
+    /// one spouse.
+    fn max_spouses(&self, i: usize) -> usize {
+        let person = &self.people[i];
+        if person.female {
+            return 1;
+        }
+        let lineage = person.dynasty.as_ref().or(person.house.as_ref());
+        self.config
+            .dynasties
+            .iter()
+            .find(|dynasty| Some(&dynasty.dynasty_id) == lineage)
+            .and_then(|dynasty| dynasty.marriage_doctrine.as_ref())
+            .or_else(|| {
+                let faith = person.religion.as_ref()?;
+                self.config.matchmaking.marriage_doctrines.get(faith)
+            })
+            .map_or(1, |doctrine| doctrine.max_spouses.max(1) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::people::parse_people;
+    use crate::testing;
+
+    /// The problems of the marriages of the history `history`, by the
+    /// shipped config with the marriage rules `rules`.
+    fn problems(history: &str, rules: MarriageChecks) -> Vec<(Severity, String)> {
+        let mut config = testing::config().initialization;
+        config.marriage_checks = rules;
+        check_marriages(&parse_people(history).unwrap(), &config)
+            .into_iter()
+            .map(|problem| (problem.severity, problem.message))
+            .collect()
+    }
+
+    fn rules() -> MarriageChecks {
+        serde_json::from_str("{}").unwrap()
+    }
+
+    const CHILD_BRIDE: &str = "\
+a1 = { 6000.1.1 = { birth = yes } 6026.6.1 = { add_spouse = b1 } }
+b1 = { female = yes 6010.6.2 = { birth = yes } }
+";
+
+    #[test]
+    fn marriages_under_the_minimum_age_are_flagged() {
+        assert_eq!(
+            problems(CHILD_BRIDE, rules()),
+            [(
+                Severity::Warning,
+                "character 'b1' marries 'a1' on 6026.6.1 aged 15, younger than 16".to_string()
+            )]
+        );
+        let rules = MarriageChecks {
+            min_age: 15,
+            ..rules()
+        };
+        assert_eq!(problems(CHILD_BRIDE, rules), []);
+    }
+
+    #[test]
+    fn rules_are_reported_with_their_severity() {
+        let strict = MarriageChecks {
+            underage: Severity::Error,
+            ..rules()
+        };
+        assert_eq!(problems(CHILD_BRIDE, strict)[0].0, Severity::Error);
+        let off = MarriageChecks {
+            underage: Severity::Off,
+            ..rules()
+        };
+        assert_eq!(problems(CHILD_BRIDE, off), []);
+    }
+
+    #[test]
+    fn marriages_of_the_dead_and_to_a_child_are_flagged() {
+        let history = "\
+a1 = { 6000.1.1 = { birth = yes } 6050.1.1 = { death = yes } 6051.1.1 = { add_spouse = b1 } }
+b1 = { female = yes 6002.1.1 = { birth = yes } }
+a2 = { father = a1 6020.1.1 = { birth = yes } }
+b2 = { female = yes mother = b1 6022.1.1 = { birth = yes } 6045.1.1 = { add_spouse = b1 } }
+";
+        assert_eq!(
+            problems(history, rules()),
+            [
+                (
+                    Severity::Error,
+                    "character 'b1' marries their child 'b2' on 6045.1.1".to_string()
+                ),
+                (
+                    Severity::Error,
+                    "character 'a1' marries 'b1' on 6051.1.1, after they died on 6050.1.1"
+                        .to_string()
+                ),
+                (
+                    Severity::Error,
+                    "character 'b1' marries 'a1' on 6051.1.1 while married to 'b2'".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Whom the unmarried may marry, and whom they prefer.
+//!
+//! A match is allowed when the age gap fits the window, neither faith,
+//! culture nor dynasty politics rule it out, and the spouses are not too
+//! closely related: siblings, parents and children never, anyone else up to
+//! the consanguinity limit. Among the allowed candidates a seeker draws one
+//! at random, weighted towards the preferred age gap, a close blood tier, a
+//! shared faith and culture, as much as the dynasties on both sides care for
+//! them, and the dynasties their own favours. Every marriage between two
+//! dynasties can make the next one likelier, so that alliances build up into
+//! webs rather than scattering at random.
+
+use std::collections::HashMap;
+
+use ck3_config::{Dynasty, Matchmaking};
+
+use crate::character::{CharacterId, Sex};
+use crate::random;
+use crate::simulation::Simulation;
+
+/// Years a blood tier adds to the marriage age and the allowed age gap.
+pub(crate) const BLOOD_TIER_MARRIAGE_YEARS: i32 = 5;
+/// Relatedness of first cousins, which dynasties allowing their marriage may
+/// always reach.
+const FIRST_COUSINS: f64 = 0.125;
+/// Slack for relatedness sums that should equal a limit exactly.
+const RELATEDNESS_TOLERANCE: f64 = 1e-9;
+/// Factor on a match's weight per blood tier between the spouses.
+const BLOOD_TIER_DISTANCE_WEIGHT: f64 = 0.5;
+
+/// Living marriages between two dynasties, by their IDs in order.
+pub(crate) type Alliances = HashMap<(String, String), u32>;
+
+impl Simulation {
+    fn matchmaking(&self) -> &Matchmaking {
+        &self.config.initialization.matchmaking
+    }
+
+    /// Largest age gap `id` may have with a spouse, either way round.
+    pub(crate) fn max_age_gap(&self, id: CharacterId) -> i32 {
+        let (husband, wife) = self.age_windows();
+        husband.max(wife) + self.character(id).blood_tier as i32 * BLOOD_TIER_MARRIAGE_YEARS
+    }
+
+    /// Most years a husband and a wife may be older than their spouse,
+    /// before blood tier allowances.
+    fn age_windows(&self) -> (i32, i32) {
+        let base = self.config.life_stages.marriage_max_age_difference;
+        let matchmaking = self.matchmaking();
+        (
+            matchmaking.max_husband_older.unwrap_or(base) as i32,
+            matchmaking.max_wife_older.unwrap_or(base) as i32,
+        )
+    }
+
+    /// Whether `a` aged `a_age` and `b` aged `b_age` are close enough in age
+    /// to marry.
+    pub(crate) fn ages_fit(&self, a: CharacterId, b: CharacterId, a_age: i32, b_age: i32) -> bool {
+        let (husband_age, wife_age) = match self.character(a).sex {
+            Sex::Male => (a_age, b_age),
+            Sex::Female => (b_age, a_age),
+        };
+        let tier = self
+            .character(a)
+            .blood_tier
+            .max(self.character(b).blood_tier);
+        let allowance = tier as i32 * BLOOD_TIER_MARRIAGE_YEARS;
+        let (husband_older, wife_older) = self.age_windows();
+        let gap = husband_age - wife_age;
+        if gap >= 0 {
+            gap <= husband_older + allowance
+        } else {
+            -gap <= wife_older + allowance
+        }
+    }
+
+    /// Whether `a` and `b` may marry, their ages aside.
+    pub(crate) fn may_match(&self, a: CharacterId, b: CharacterId) -> bool {
+        if self.standing_weight(a, b) <= 0.0 {
+            return false;
+        }
+        if self.are_siblings(a, b) || self.is_parent_of(a, b) || self.is_parent_of(b, a) {
+            return false;
+        }
+        let cousins_allowed = [a, b].into_iter().any(|id| {
+            self.dynasty_config(self.character(id).dynasty.as_deref())
+                .is_some_and(|d| d.allow_first_cousin_marriage)
+        });
+        let matchmaking = self.matchmaking();
+        let mut limit = matchmaking.max_relatedness;
+        if cousins_allowed {
+            limit = limit.max(FIRST_COUSINS);
+        }
+        let relatedness = self.relatedness(a, b, matchmaking.consanguinity_generations);
+        relatedness <= limit + RELATEDNESS_TOLERANCE
+    }
+
+    /// Draw a spouse for `seeker` from `candidates` by the weight of each
+    /// match.
+    pub(crate) fn pick_match(
+        &mut self,
+        seeker: CharacterId,
+        candidates: &[CharacterId],
+        alliances: &Alliances,
+    ) -> Option<CharacterId> {
+        let weights: Vec<(CharacterId, f64)> = candidates
+            .iter()
+            .map(|&candidate| (candidate, self.match_weight(seeker, candidate, alliances)))
+            .collect();
+        random::weighted(&mut self.rng, weights)
+    }
+
+    /// How likely `seeker` is to pick `candidate`, relative to the others.
+    fn match_weight(
+        &self,
+        seeker: CharacterId,
+        candidate: CharacterId,
+        alliances: &Alliances,
+    ) -> f64 {
+        let (s, c) = (self.character(seeker), self.character(candidate));
+        let matchmaking = self.matchmaking();
+        let (husband, wife) = match s.sex {
+            Sex::Male => (s, c),
+            Sex::Female => (c, s),
+        };
+        let gap = husband.age - wife.age;
+        let age = 1.0 / (1.0 + (gap - matchmaking.preferred_age_gap).abs() as f64);
+        let blood = BLOOD_TIER_DISTANCE_WEIGHT.powi(s.blood_tier.abs_diff(c.blood_tier) as i32);
+        let marriages = alliance_key(s.dynasty.as_deref(), c.dynasty.as_deref())
+            .and_then(|key| alliances.get(&key))
+            .copied()
+            .unwrap_or(0);
+        let alliance = matchmaking.alliance_weight.powi(marriages as i32);
+        self.standing_weight(seeker, candidate) * age * blood * alliance
+    }
+
+    /// The part of a match's weight that can forbid it: faith, culture and
+    /// what each side's dynasty thinks of the other's.
+    fn standing_weight(&self, a: CharacterId, b: CharacterId) -> f64 {
+        let matchmaking = self.matchmaking();
+        let mut weight = 1.0;
+        if self.character(a).religion != self.character(b).religion {
+            weight *= self.homophily_weight(a, b, matchmaking.different_faith_weight, |d| {
+                d.different_faith_weight
+            });
+        }
+        if self.character(a).culture != self.character(b).culture {
+            weight *= self.homophily_weight(a, b, matchmaking.different_culture_weight, |d| {
+                d.different_culture_weight
+            });
+        }
+        let (a, b) = (self.character(a), self.character(b));
+        if let (Some(a), Some(b)) = (a.dynasty.as_deref(), b.dynasty.as_deref()) {
+            for (from, to) in [(a, b), (b, a)] {
+                if let Some(&relation) = matchmaking
+                    .dynasty_relations
+                    .get(from)
+                    .and_then(|relations| relations.get(to))
+                {
+                    weight *= relation;
+                }
+            }
+        }
+        weight
+    }
+
+    /// The weight of a match between `a` and `b` across a faith or culture
+    /// line: the geometric mean of each side's dynasty's weight, `base` for
+    /// those without one, so that either side setting 0 forbids the match.
+    fn homophily_weight(
+        &self,
+        a: CharacterId,
+        b: CharacterId,
+        base: f64,
+        dynasty_weight: impl Fn(&Dynasty) -> Option<f64>,
+    ) -> f64 {
+        let [a, b] = [a, b].map(|id| {
+            self.dynasty_config(self.character(id).dynasty.as_deref())
+                .and_then(&dynasty_weight)
+        });
+        if a.is_none() && b.is_none() {
+            return base;
+        }
+        (a.unwrap_or(base) * b.unwrap_or(base)).sqrt()
+    }
+
+    /// The living marriages between dynasties.
+    pub(crate) fn alliances(&self) -> Alliances {
+        let mut alliances = Alliances::new();
+        for (id, character) in self.characters() {
+            let Some(spouse) = character.spouse else {
+                continue;
+            };
+            let spouse_ref = self.character(spouse);
+            if id > spouse || !character.is_alive() || !spouse_ref.is_alive() {
+                continue;
+            }
+            if let Some(key) =
+                alliance_key(character.dynasty.as_deref(), spouse_ref.dynasty.as_deref())
+            {
+                *alliances.entry(key).or_default() += 1;
+            }
+        }
+        alliances
+    }
+}
+
+/// The key of a marriage between members of `a` and `b` in [`Alliances`],
+/// if it joins two different dynasties.
+pub(crate) fn alliance_key(a: Option<&str>, b: Option<&str>) -> Option<(String, String)> {
+    let (a, b) = (a?, b?);
+    match a.cmp(b) {
+        std::cmp::Ordering::Less => Some((a.to_string(), b.to_string())),
+        std::cmp::Ordering::Greater => Some((b.to_string(), a.to_string())),
+        std::cmp::Ordering::Equal => None,
+    }
+}
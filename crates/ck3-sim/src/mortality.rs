@@ -0,0 +1,230 @@
+//! Yearly deaths and the lifespan caps.
+//!
+//! A character's chance of dying in a year comes from the mortality tables
+//! at their effective age, blood tiers taking 20 years a tier off it, or from
+//! the survival curve of the era the year falls in if it has one. Eras,
+//! traits, negative events and epidemics then scale it. Whatever the modifiers, no one
+//! lives past the lifespan cap of their blood tier: without one, event
+//! multipliers below 1 and the luck of dynasties that prioritise lowborn
+//! marriages let the last members of a line live for thousands of years.
+
+use ck3_config::{BySex, Mortality, SurvivalCurve};
+
+use crate::character::{Character, CharacterId, EventKind, Sex};
+use crate::date::Date;
+use crate::random;
+use crate::simulation::Simulation;
+
+/// Effective age before which progenitors cannot die, so every dynasty gets
+/// a chance to start.
+const PROGENITOR_SAFE_AGE: i32 = 50;
+/// Years of effective age a blood tier takes off for mortality, and adds to
+/// the lifespan cap of tiers without their own.
+pub(crate) const BLOOD_TIER_MORTALITY_YEARS: i32 = 20;
+/// Age after which deaths are put down to old age, before blood tiers.
+const OLD_AGE: i32 = 65;
+
+const MALE_DEATH_CAUSES: [&str; 9] = [
+    "death_ill",
+    "death_cancer",
+    "death_battle",
+    "death_attacked",
+    "death_accident",
+    "death_murder",
+    "death_natural_causes",
+    "death_drinking_passive",
+    "death_dungeon_passive",
+];
+const FEMALE_DEATH_CAUSES: [&str; 4] = [
+    "death_ill",
+    "death_cancer",
+    "death_accident",
+    "death_murder",
+];
+
+impl Simulation {
+    /// Roll whether `id` dies during `year`.
+    ///
+    /// Death is certain at the lifespan cap, and past the end of the
+    /// mortality table, which the engine read the last entry of forever.
+    pub(crate) fn dies(&mut self, id: CharacterId, year: i32) -> bool {
+        let default = Mortality::default();
+        let mortality = self
+            .config
+            .life_stages
+            .mortality
+            .as_ref()
+            .unwrap_or(&default);
+        let character = &self.characters[id.0];
+        if character.age >= max_lifespan(mortality, character.blood_tier) {
+            return true;
+        }
+        let effective_age =
+            (character.age - BLOOD_TIER_MORTALITY_YEARS * character.blood_tier as i32).max(0);
+        if character.is_progenitor && effective_age < PROGENITOR_SAFE_AGE {
+            return false;
+        }
+
+        let eras: Vec<_> = mortality
+            .eras
+            .iter()
+            .filter(|era| (era.start_year..=era.end_year).contains(&year))
+            .collect();
+        let base = match eras.iter().find_map(|era| era.curve.as_ref()) {
+            Some(curve) => curve_rate(by_sex(curve, character.sex), effective_age),
+            None => {
+                let rates = &self.config.life_stages.mortality_rates;
+                let Some(&base) = by_sex(rates, character.sex).get(effective_age as usize) else {
+                    return true;
+                };
+                base
+            }
+        };
+        let mut rate = if effective_age < 1 { 0.0 } else { base };
+        for era in eras {
+            rate *= era.multiplier;
+        }
+        if !mortality.trait_multipliers.is_empty() {
+            for name in trait_ids(character) {
+                if let Some(multiplier) = mortality.trait_multipliers.get(&name) {
+                    rate *= multiplier;
+                }
+            }
+        }
+        rate = rate.min(1.0);
+
+        let mut reason = None;
+        for event in &self.config.initialization.events {
+            let born_in = (event.start_year..=event.end_year).contains(&character.birth.year);
+            let aged = (event.character_age_start as i32..=event.character_age_end as i32)
+                .contains(&effective_age);
+            if born_in && aged {
+                rate = (rate * event.death_multiplier).min(1.0);
+                reason = Some(event.death_reason.clone());
+            }
+        }
+        if let Some((multiplier, epidemic)) = self.epidemic_lethality(id, year, effective_age) {
+            rate = (rate * multiplier).min(1.0);
+            reason = Some(epidemic);
+        }
+        let lucky = self
+            .dynasty_config(character.dynasty.as_deref())
+            .is_some_and(|d| d.prioritise_lowborn_marriage);
+        self.characters[id.0].event_death_reason = reason;
+
+        let mut roll = rand::Rng::random::<f64>(&mut self.rng);
+        if lucky {
+            roll *= 0.35;
+        }
+        roll < rate
+    }
+
+    /// Record the death of `id` during `year`.
+    pub(crate) fn kill(&mut self, id: CharacterId, year: i32) {
+        let mut date = Date::random_in(year, &mut self.rng);
+        // Nobody dies before what they did earlier in the year: marrying or
+        // bearing a child.
+        if let Some(last) = self.last_event_in(id, year).filter(|&last| last > date) {
+            date = Date::random_between(last, Date::new(year, 12, 28), &mut self.rng);
+        }
+        let character = &self.characters[id.0];
+        let old_age = OLD_AGE + BLOOD_TIER_MORTALITY_YEARS * character.blood_tier as i32;
+        let reason = if let Some(reason) = &character.event_death_reason {
+            reason.clone()
+        } else if character.age > old_age {
+            "death_natural_causes".to_string()
+        } else if character.age < 18 {
+            "death_ill".to_string()
+        } else {
+            let causes: &[&str] = match character.sex {
+                Sex::Male => &MALE_DEATH_CAUSES,
+                Sex::Female => &FEMALE_DEATH_CAUSES,
+            };
+            random::pick(&mut self.rng, causes)
+                .expect("causes are listed")
+                .to_string()
+        };
+        let character = &mut self.characters[id.0];
+        character.death = Some(date);
+        character.add_event(date, EventKind::Death { reason });
+        self.death_hooks(id, year);
+    }
+
+    /// The day of the latest event of `year` in the life of `id`: one of
+    /// their own, the birth of a child of theirs, or a marriage their spouse
+    /// records.
+    fn last_event_in(&self, id: CharacterId, year: i32) -> Option<Date> {
+        let character = &self.characters[id.0];
+        let own = character.events.iter().map(|event| event.date);
+        let children = character
+            .children
+            .iter()
+            .map(|&child| self.characters[child.0].birth);
+        // The spouse may have died first this year and left them widowed
+        // already, so this year's marriages are looked for on everyone.
+        let marriages = self
+            .characters
+            .iter()
+            .flat_map(|c| {
+                c.events
+                    .iter()
+                    .rev()
+                    .take_while(|event| event.date.year >= year)
+            })
+            .filter(
+                |event| matches!(event.kind, EventKind::Marriage { spouse, .. } if spouse == id),
+            )
+            .map(|event| event.date);
+        own.chain(children)
+            .chain(marriages)
+            .filter(|date| date.year == year)
+            .max()
+    }
+}
+
+/// The age by which every character of blood tier `tier` has died.
+pub(crate) fn max_lifespan(mortality: &Mortality, tier: u32) -> i32 {
+    let base = mortality.max_lifespan as i32;
+    if tier == 0 {
+        return base;
+    }
+    mortality
+        .blood_tier_max_lifespan
+        .get(&tier.to_string())
+        .map_or(base + BLOOD_TIER_MORTALITY_YEARS * tier as i32, |&cap| {
+            cap as i32
+        })
+}
+
+/// The chance of dying within a year at `age` under `curve`.
+fn curve_rate(curve: &SurvivalCurve, age: i32) -> f64 {
+    let age = f64::from(age);
+    let hazard = curve.infant * (-curve.infant_decay * age).exp()
+        + curve.background
+        + curve.aging * (curve.aging_rate * age).exp();
+    1.0 - (-hazard).exp()
+}
+
+fn by_sex<T>(values: &BySex<T>, sex: Sex) -> &T {
+    match sex {
+        Sex::Male => &values.male,
+        Sex::Female => &values.female,
+    }
+}
+
+/// Every trait `character` has, education and congenital traits included.
+fn trait_ids(character: &Character) -> Vec<String> {
+    character
+        .traits
+        .iter()
+        .chain(&character.personality_traits)
+        .cloned()
+        .chain(
+            character
+                .congenital_traits
+                .iter()
+                .map(|name| name.to_string()),
+        )
+        .chain(character.education.as_ref().map(|e| e.trait_name()))
+        .collect()
+}
@@ -0,0 +1,287 @@
+//! Reading name lists from bundled packs and from the game files.
+//!
+//! The game keeps its lists in `game/common/culture/name_lists`, as script
+//! blocks like `name_list_english = { male_names = { 10 = { Alfred } Edwin }
+//! ... }`, and each culture in `game/common/culture/cultures` names the lists
+//! it draws from. Only the parts the generator needs are read: the male and
+//! female names with their weights, and the patronym particles.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::names::{NameList, Patronym, WeightedName};
+
+/// Directory of the game's name lists, relative to the installation.
+const NAME_LISTS_DIR: &str = "game/common/culture/name_lists";
+/// Directory of the game's culture definitions, relative to the installation.
+const CULTURES_DIR: &str = "game/common/culture/cultures";
+/// Start of the localisation keys of patronym particles, e.g.
+/// `dynnpat_suf_son`.
+const PATRONYM_KEYS: [&str; 2] = ["dynnpat_pre_", "dynnpat_suf_"];
+
+/// Read a pack list, one name per line with an optional weight after it.
+/// A missing file reads as an empty list.
+pub(crate) fn read_pack_list(path: &Path) -> Vec<WeightedName> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.rsplit_once(char::is_whitespace) {
+            Some((name, weight)) if weight.parse::<f64>().is_ok() => WeightedName {
+                name: name.trim_end().to_string(),
+                weight: weight.parse().unwrap_or(1.0),
+            },
+            _ => WeightedName {
+                name: line.to_string(),
+                weight: 1.0,
+            },
+        })
+        .collect()
+}
+
+/// The name lists of a game installation.
+#[derive(Debug, Default)]
+pub(crate) struct GameNames {
+    lists: HashMap<String, NameList>,
+    /// The lists each culture draws from.
+    cultures: HashMap<String, Vec<String>>,
+}
+
+impl GameNames {
+    /// The names of `culture`: those of every list it draws from, or else
+    /// of `name_list_<culture>`.
+    pub(crate) fn culture(&self, culture: &str) -> Option<NameList> {
+        let fallback = [format!("name_list_{culture}")];
+        let ids = self
+            .cultures
+            .get(culture)
+            .map_or(&fallback[..], Vec::as_slice);
+        let mut merged: Option<NameList> = None;
+        for list in ids.iter().filter_map(|id| self.lists.get(id)) {
+            let merged = merged.get_or_insert_with(NameList::default);
+            merged.male.extend(list.male.iter().cloned());
+            merged.female.extend(list.female.iter().cloned());
+            if merged.patronym.is_none() {
+                merged.patronym = list.patronym.clone();
+            }
+        }
+        merged
+    }
+}
+
+/// Read every name list and culture of the game in `game_dir`. Files that
+/// cannot be read are skipped.
+pub(crate) fn load_game(game_dir: &Path) -> GameNames {
+    let mut game = GameNames::default();
+    for text in read_scripts(&game_dir.join(NAME_LISTS_DIR)) {
+        for (id, value) in fields(&parse(&text)) {
+            if let Value::Block(items) = value {
+                game.lists.insert(id.to_string(), name_list(items));
+            }
+        }
+    }
+    for text in read_scripts(&game_dir.join(CULTURES_DIR)) {
+        for (culture, value) in fields(&parse(&text)) {
+            let Value::Block(items) = value else {
+                continue;
+            };
+            let lists: Vec<String> = fields(items)
+                .filter(|(key, _)| *key == "name_list")
+                .filter_map(|(_, value)| value.word().map(str::to_string))
+                .collect();
+            if !lists.is_empty() {
+                game.cultures.insert(culture.to_string(), lists);
+            }
+        }
+    }
+    game
+}
+
+fn name_list(items: &[Item]) -> NameList {
+    let mut list = NameList::default();
+    let mut patronym = Patronym::default();
+    for (key, value) in fields(items) {
+        match key {
+            "male_names" => list.male = weighted_names(value),
+            "female_names" => list.female = weighted_names(value),
+            "patronym_prefix_male" => patronym.male_prefix = particle(value),
+            "patronym_suffix_male" => patronym.male_suffix = particle(value),
+            "patronym_prefix_female" => patronym.female_prefix = particle(value),
+            "patronym_suffix_female" => patronym.female_suffix = particle(value),
+            _ => {}
+        }
+    }
+    if patronym != Patronym::default() {
+        list.patronym = Some(patronym);
+    }
+    list
+}
+
+/// The names of a `male_names` or `female_names` block, where bare names
+/// weigh 1 and `<weight> = { ... }` groups give their names that weight.
+fn weighted_names(value: &Value) -> Vec<WeightedName> {
+    let Value::Block(items) = value else {
+        return Vec::new();
+    };
+    let mut names = Vec::new();
+    for item in items {
+        match item {
+            Item::Value(Value::Word(name)) => names.push(WeightedName {
+                name: name.clone(),
+                weight: 1.0,
+            }),
+            Item::Field(weight, Value::Block(group)) => {
+                let weight = weight.parse().unwrap_or(1.0);
+                for item in group {
+                    if let Item::Value(Value::Word(name)) = item {
+                        names.push(WeightedName {
+                            name: name.clone(),
+                            weight,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+/// The particle of a patronym localisation key, e.g. `son` for
+/// `dynnpat_suf_son`.
+fn particle(value: &Value) -> Option<String> {
+    let key = value.word()?;
+    let particle = PATRONYM_KEYS
+        .iter()
+        .find_map(|prefix| key.strip_prefix(prefix))
+        .unwrap_or(key);
+    Some(particle.to_string())
+}
+
+/// The contents of every `.txt` file directly in `dir`, in name order.
+fn read_scripts(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"))
+        })
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .filter_map(|path| fs::read(path).ok())
+        .map(|bytes| {
+            let text = String::from_utf8_lossy(&bytes);
+            text.strip_prefix('\u{feff}').unwrap_or(&text).to_string()
+        })
+        .collect()
+}
+
+enum Item {
+    Value(Value),
+    Field(String, Value),
+}
+
+enum Value {
+    Word(String),
+    Block(Vec<Item>),
+}
+
+impl Value {
+    fn word(&self) -> Option<&str> {
+        match self {
+            Value::Word(word) => Some(word),
+            Value::Block(_) => None,
+        }
+    }
+}
+
+/// The `key = value` items of a block.
+fn fields(items: &[Item]) -> impl Iterator<Item = (&str, &Value)> {
+    items.iter().filter_map(|item| match item {
+        Item::Field(key, value) => Some((key.as_str(), value)),
+        Item::Value(_) => None,
+    })
+}
+
+/// Parse a script into its top-level items, leniently: a stray `}` ends the
+/// file and an unclosed block runs to its end.
+fn parse(text: &str) -> Vec<Item> {
+    let tokens = tokenize(text);
+    let mut i = 0;
+    parse_block(&tokens, &mut i)
+}
+
+fn parse_block(tokens: &[Token], i: &mut usize) -> Vec<Item> {
+    let mut items = Vec::new();
+    while let Some(token) = tokens.get(*i) {
+        *i += 1;
+        match token {
+            Token::Close => break,
+            Token::Open => items.push(Item::Value(Value::Block(parse_block(tokens, i)))),
+            Token::Equals => {}
+            Token::Word(word) if tokens.get(*i) == Some(&Token::Equals) => {
+                *i += 1;
+                let value = match tokens.get(*i) {
+                    Some(Token::Open) => {
+                        *i += 1;
+                        Value::Block(parse_block(tokens, i))
+                    }
+                    Some(Token::Word(value)) => {
+                        *i += 1;
+                        Value::Word(value.clone())
+                    }
+                    _ => continue,
+                };
+                items.push(Item::Field(word.clone(), value));
+            }
+            Token::Word(word) => items.push(Item::Value(Value::Word(word.clone()))),
+        }
+    }
+    items
+}
+
+#[derive(PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Equals,
+    Word(String),
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => while chars.next_if(|&c| c != '\n').is_some() {},
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '=' => tokens.push(Token::Equals),
+            '"' => {
+                let word: String = chars.by_ref().take_while(|&c| c != '"').collect();
+                tokens.push(Token::Word(word));
+            }
+            c if c.is_whitespace() => {}
+            // Comparison operators only appear in triggers.
+            '<' | '>' | '!' | '?' => {}
+            c => {
+                let mut word = String::from(c);
+                while let Some(c) = chars
+                    .next_if(|&c| !c.is_whitespace() && !matches!(c, '{' | '}' | '=' | '#' | '"'))
+                {
+                    word.push(c);
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    tokens
+}
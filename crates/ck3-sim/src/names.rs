@@ -0,0 +1,249 @@
+//! Culture name lists and the names drawn from them.
+//!
+//! Names come from bundled packs, directories of `<culture>_<male|female>.txt`
+//! files with one name per line, and failing those from the game's own name
+//! lists, read through the culture that uses them. A pack line may end with
+//! a weight, as in `Aragorn 5`, and names in the game's weighted groups keep
+//! their weights; every other name weighs 1. A culture without any list
+//! falls back to placeholder names, as the engine does, so a typo in a
+//! culture ID shows up in the output rather than aborting the run.
+//!
+//! Children are named with a preference for the names their dynasty already
+//! carries, and for cultures whose game list has patronyms,
+//! [`NameLists::patronymic`] builds the name a child of a given father goes
+//! by in game.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+use crate::character::Sex;
+use crate::name_packs::{self, GameNames};
+use crate::random;
+
+const FALLBACK_NAMES: [&str; 2] = ["FallbackName1", "FallbackName2"];
+/// How much likelier a name already borne in the dynasty is to be picked.
+const DYNASTY_NAME_WEIGHT: f64 = 2.0;
+
+/// A name and how often it is picked relative to the others of its list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightedName {
+    pub name: String,
+    pub weight: f64,
+}
+
+/// How a culture forms the patronyms of its characters.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Patronym {
+    pub male_prefix: Option<String>,
+    pub male_suffix: Option<String>,
+    pub female_prefix: Option<String>,
+    pub female_suffix: Option<String>,
+}
+
+/// Every name of one culture.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NameList {
+    pub male: Vec<WeightedName>,
+    pub female: Vec<WeightedName>,
+    pub patronym: Option<Patronym>,
+}
+
+impl NameList {
+    pub fn names(&self, sex: Sex) -> &[WeightedName] {
+        match sex {
+            Sex::Male => &self.male,
+            Sex::Female => &self.female,
+        }
+    }
+
+    fn names_mut(&mut self, sex: Sex) -> &mut Vec<WeightedName> {
+        match sex {
+            Sex::Male => &mut self.male,
+            Sex::Female => &mut self.female,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NameLists {
+    /// Pack directories, searched in order.
+    packs: Vec<PathBuf>,
+    game: Option<Arc<GameNames>>,
+    cache: HashMap<String, NameList>,
+}
+
+impl NameLists {
+    /// Name lists read from the pack in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            packs: vec![dir.into()],
+            game: None,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Also search the pack in `dir`, after those already added.
+    pub fn with_pack(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.packs.push(dir.into());
+        self.cache.clear();
+        self
+    }
+
+    /// Fall back to the name lists of the game installed in `game_dir` for
+    /// cultures no pack has names for.
+    pub fn with_game_dir(mut self, game_dir: &Path) -> Self {
+        self.game = Some(Arc::new(name_packs::load_game(game_dir)));
+        self.cache.clear();
+        self
+    }
+
+    /// The names of `culture`.
+    pub fn list(&mut self, culture: &str) -> &NameList {
+        if !self.cache.contains_key(culture) {
+            let list = self.load(culture);
+            self.cache.insert(culture.to_string(), list);
+        }
+        &self.cache[culture]
+    }
+
+    /// Every name of `culture` for `sex`, never empty.
+    pub fn all(&mut self, culture: &str, sex: Sex) -> &[WeightedName] {
+        self.list(culture).names(sex)
+    }
+
+    /// A random name of `culture` for `sex`.
+    pub fn random(&mut self, rng: &mut impl Rng, culture: &str, sex: Sex) -> String {
+        self.pick(rng, culture, sex, &BTreeSet::new(), &BTreeSet::new())
+    }
+
+    /// A random name of `culture` for `sex` that is not in `avoid`, unless
+    /// every name is, with names in `favour` weighted up.
+    pub fn pick(
+        &mut self,
+        rng: &mut impl Rng,
+        culture: &str,
+        sex: Sex,
+        avoid: &BTreeSet<&str>,
+        favour: &BTreeSet<&str>,
+    ) -> String {
+        let all = self.all(culture, sex);
+        let weigh = |name: &WeightedName| {
+            let boost = if favour.contains(name.name.as_str()) {
+                DYNASTY_NAME_WEIGHT
+            } else {
+                1.0
+            };
+            (name.name.clone(), name.weight * boost)
+        };
+        let allowed = all
+            .iter()
+            .filter(|name| !avoid.contains(name.name.as_str()))
+            .map(weigh);
+        random::weighted(rng, allowed)
+            .or_else(|| random::weighted(rng, all.iter().map(weigh)))
+            .or_else(|| random::pick(rng, all).map(|name| name.name))
+            .expect("name lists are never empty")
+    }
+
+    /// Up to `count` different names of `culture` for `sex`, drawn by weight.
+    pub fn suggest(
+        &mut self,
+        culture: &str,
+        sex: Sex,
+        count: usize,
+        seed: Option<u64>,
+    ) -> Vec<String> {
+        let mut rng = match seed {
+            Some(seed) => ChaCha12Rng::seed_from_u64(seed),
+            None => ChaCha12Rng::from_os_rng(),
+        };
+        let mut remaining: Vec<WeightedName> = self.all(culture, sex).to_vec();
+        let mut names = Vec::new();
+        while names.len() < count && !remaining.is_empty() {
+            let options = remaining
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (i, name.weight));
+            let i = random::weighted(&mut rng, options).unwrap_or(0);
+            names.push(remaining.swap_remove(i).name);
+        }
+        names
+    }
+
+    /// The name a `sex` child called `name` of a father called `father`
+    /// goes by in a culture that uses patronyms, or `None` for one that
+    /// does not.
+    pub fn patronymic(
+        &mut self,
+        culture: &str,
+        sex: Sex,
+        name: &str,
+        father: &str,
+    ) -> Option<String> {
+        let patronym = self.list(culture).patronym.as_ref()?;
+        let (prefix, suffix) = match sex {
+            Sex::Male => (&patronym.male_prefix, &patronym.male_suffix),
+            Sex::Female => (&patronym.female_prefix, &patronym.female_suffix),
+        };
+        match (prefix, suffix) {
+            (Some(prefix), _) => Some(format!("{name} {prefix} {father}")),
+            (None, Some(suffix)) => Some(format!("{name} {father}{suffix}")),
+            (None, None) => None,
+        }
+    }
+
+    /// A hash of the names of `culture` as they are now on disk, for
+    /// telling whether they changed.
+    pub(crate) fn fingerprint(&self, culture: &str) -> u64 {
+        let list = self.load(culture);
+        let mut hasher = DefaultHasher::new();
+        for name in list.male.iter().chain(&list.female) {
+            name.name.hash(&mut hasher);
+            name.weight.to_bits().hash(&mut hasher);
+        }
+        format!("{:?}", list.patronym).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Read the names of `culture` from the first source that has some.
+    fn load(&self, culture: &str) -> NameList {
+        let mut list = NameList::default();
+        for sex in [Sex::Male, Sex::Female] {
+            *list.names_mut(sex) = self
+                .packs
+                .iter()
+                .map(|dir| {
+                    name_packs::read_pack_list(&dir.join(format!("{culture}_{}.txt", sex.key())))
+                })
+                .find(|names| !names.is_empty())
+                .unwrap_or_default();
+        }
+        if let Some(game) = self.game.as_deref().and_then(|game| game.culture(culture)) {
+            for sex in [Sex::Male, Sex::Female] {
+                if list.names(sex).is_empty() {
+                    *list.names_mut(sex) = game.names(sex).to_vec();
+                }
+            }
+            list.patronym = game.patronym.clone();
+        }
+        for sex in [Sex::Male, Sex::Female] {
+            let names = list.names_mut(sex);
+            if names.is_empty() {
+                *names = FALLBACK_NAMES
+                    .iter()
+                    .map(|name| WeightedName {
+                        name: name.to_string(),
+                        weight: 1.0,
+                    })
+                    .collect();
+            }
+        }
+        list
+    }
+}
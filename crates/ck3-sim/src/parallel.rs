@@ -0,0 +1,353 @@
+//! Simulating dynasties in parallel.
+//!
+//! [`Simulation::run_parallel`] simulates every dynasty as a run of its own
+//! on the rayon thread pool, each with a seed derived from the run's seed and
+//! the dynasty's ID, so the outcome depends neither on the number of threads
+//! nor on where the dynasty sits in the config. Dynasties simulated apart cannot marry each other,
+//! so they marry lowborn or within the dynasty instead; the merge step then
+//! gives lowborn spouses' places to members of other dynasties who never
+//! married and would have made a match, earliest marriages first.
+
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use rayon::prelude::*;
+
+use crate::character::{CharacterId, EventKind, Sex};
+use crate::config::SimConfig;
+use crate::date::Date;
+use crate::names::NameLists;
+use crate::random;
+use crate::simulation::{self, Simulation, Summary, FEMALE_FERTILE_UNTIL, FERTILE_FROM};
+
+/// A lowborn spouse who could give their place to a dynasty member.
+struct LowbornMatch {
+    lowborn: CharacterId,
+    partner: CharacterId,
+    married: Date,
+    /// When the partner married again after the lowborn spouse died, if
+    /// they did, by which day whoever takes the place must be dead too.
+    remarried: Option<Date>,
+}
+
+impl Simulation {
+    /// Simulate each dynasty on its own thread and merge the runs.
+    ///
+    /// Like [`Simulation::new`] followed by [`Simulation::run`], but with
+    /// marriages between dynasties only arranged once every dynasty has been
+    /// simulated.
+    pub fn run_parallel(config: SimConfig, names: NameLists, seed: Option<u64>) -> (Self, Summary) {
+        Self::run_parallel_until(config, names, seed, |_| ControlFlow::Continue(()))
+            .expect("a run only stops early when asked to")
+    }
+
+    /// Like [`Simulation::run_parallel`], but asking `before_year`, from
+    /// every dynasty's thread, before each of its years whether to go on.
+    /// Returns `None` if it stopped the run.
+    pub fn run_parallel_until(
+        config: SimConfig,
+        names: NameLists,
+        seed: Option<u64>,
+        before_year: impl Fn(i32) -> ControlFlow<()> + Sync,
+    ) -> Option<(Self, Summary)> {
+        let seed = seed.unwrap_or_else(simulation::random_seed);
+        let runs: Option<Vec<Simulation>> = (0..config.initialization.dynasties.len())
+            .into_par_iter()
+            .map(|i| Self::simulate_shard(&config, i, &names, seed, &before_year))
+            .collect();
+        let (merged, summary, _) = Self::merge_shards(config, names, seed, runs?);
+        Some((merged, summary))
+    }
+
+    /// Simulate the dynasty at `index` in `config` on its own, unless
+    /// `before_year` stops it.
+    pub(crate) fn simulate_shard(
+        config: &SimConfig,
+        index: usize,
+        names: &NameLists,
+        seed: u64,
+        before_year: impl Fn(i32) -> ControlFlow<()>,
+    ) -> Option<Simulation> {
+        let dynasty = &config.initialization.dynasties[index];
+        let mut shard = config.clone();
+        shard.initialization.dynasties = vec![dynasty.clone()];
+        let shard_seed = random::derive_seed(seed, random::stream_of(&dynasty.dynasty_id));
+        let mut run = Simulation::new(shard, names.clone(), Some(shard_seed));
+        run.run_until(before_year)?;
+        Some(run)
+    }
+
+    /// Merge the runs of every dynasty in `config`, in config order.
+    ///
+    /// Also returns the dynasties of each couple the merge step married, the
+    /// one taking in a spouse first.
+    pub(crate) fn merge_shards(
+        config: SimConfig,
+        names: NameLists,
+        seed: u64,
+        runs: Vec<Simulation>,
+    ) -> (Self, Summary, Vec<(String, String)>) {
+        let first_year = config.initialization.min_year;
+        let last_year = config.initialization.max_year;
+        let mut merged = Simulation::empty(config, names, seed);
+        for run in runs {
+            merged.absorb(run);
+        }
+        merged.deduplicate_ids();
+        let marriages = merged.merge_marriages();
+        let summary = merged.summary(first_year, last_year);
+        (merged, summary, marriages)
+    }
+
+    /// Append the characters of `run`.
+    fn absorb(&mut self, run: Simulation) {
+        let offset = self.characters.len();
+        for mut character in run.characters {
+            character.remap_ids(|id| CharacterId(id.0 + offset));
+            self.characters.push(character);
+        }
+        self.living_at_end.extend(run.living_at_end);
+        for error in run.plugin_errors {
+            if !self.plugin_errors.contains(&error) {
+                self.plugin_errors.push(error);
+            }
+        }
+    }
+
+    /// Renumber characters whose ID another run already used, which happens
+    /// when two dynasties share an ID prefix.
+    fn deduplicate_ids(&mut self) {
+        for i in 0..self.characters.len() {
+            let id = self.characters[i].id.clone();
+            self.reserve_id(&id);
+        }
+        let mut seen = HashSet::new();
+        for i in 0..self.characters.len() {
+            if seen.insert(self.characters[i].id.clone()) {
+                continue;
+            }
+            let (prefix, _) = simulation::split_id(&self.characters[i].id);
+            let prefix = prefix.to_string();
+            let id = self.next_prefixed_id(&prefix);
+            seen.insert(id.clone());
+            self.characters[i].id = id;
+        }
+    }
+
+    /// Replace lowborn spouses with unmarried members of other dynasties,
+    /// returning the dynasties of the couples.
+    fn merge_marriages(&mut self) -> Vec<(String, String)> {
+        // Marriages and concubinages each character took part in, on either
+        // side.
+        let mut unions = vec![0; self.characters.len()];
+        for (i, c) in self.characters.iter().enumerate() {
+            for event in &c.events {
+                if let EventKind::Marriage { spouse: other, .. }
+                | EventKind::Concubinage { concubine: other } = event.kind
+                {
+                    unions[i] += 1;
+                    unions[other.0] += 1;
+                }
+            }
+        }
+        let mut matches: Vec<LowbornMatch> =
+            self.ids()
+                .filter_map(|lowborn| {
+                    let c = self.character(lowborn);
+                    let partner = c.spouse?;
+                    // Only a spouse married once can be swapped for another
+                    // without rewriting anyone else's marriages.
+                    if c.has_dynasty()
+                        || c.father.is_some()
+                        || c.mother.is_some()
+                        || unions[lowborn.0] != 1
+                    {
+                        return None;
+                    }
+                    // Lowborn spouses with children by anyone else keep their place.
+                    let only_partner = c.children.iter().all(|&child| {
+                        let child = self.character(child);
+                        child.father == Some(partner) || child.mother == Some(partner)
+                    });
+                    if !only_partner {
+                        return None;
+                    }
+                    let married = self.character(partner).events.iter().find_map(|event| {
+                        match event.kind {
+                            EventKind::Marriage { spouse, .. } if spouse == lowborn => {
+                                Some(event.date)
+                            }
+                            _ => None,
+                        }
+                    })?;
+                    let remarried = c.death.and_then(|died| {
+                        self.character(partner)
+                            .events
+                            .iter()
+                            .filter(|event| matches!(event.kind, EventKind::Marriage { .. }))
+                            .map(|event| event.date)
+                            .filter(|&date| date >= died)
+                            .min()
+                    });
+                    Some(LowbornMatch {
+                        lowborn,
+                        partner,
+                        married,
+                        remarried,
+                    })
+                })
+                .collect();
+        matches.sort_by_key(|m| (m.married, m.lowborn));
+
+        let mut singles: Vec<CharacterId> = self
+            .ids()
+            .filter(|&id| {
+                let c = self.character(id);
+                // A lowborn wife's children are of her husband's line, so
+                // women who only marry matrilineally cannot take her place.
+                c.has_dynasty()
+                    && unions[id.0] == 0
+                    && c.can_marry()
+                    && !self.marries_matrilineally(id)
+            })
+            .collect();
+        singles.sort_by_key(|&id| (self.character(id).birth, id));
+
+        let mut replaced = vec![false; self.characters.len()];
+        let mut marriages = Vec::new();
+        for m in matches {
+            let Some(position) = self.best_single(&m, &singles) else {
+                continue;
+            };
+            let single = singles.remove(position);
+            self.take_place(m.lowborn, single);
+            replaced[m.lowborn.0] = true;
+            let dynasty = |id: CharacterId| self.character(id).dynasty.clone().unwrap_or_default();
+            marriages.push((dynasty(m.partner), dynasty(single)));
+        }
+        self.remove(&replaced);
+        marriages
+    }
+
+    /// Position in `singles` of the member closest in age to the lowborn
+    /// spouse of `m` who could have married their partner instead.
+    fn best_single(&self, m: &LowbornMatch, singles: &[CharacterId]) -> Option<usize> {
+        let lowborn = self.character(m.lowborn);
+        let partner = self.character(m.partner);
+        let gap = self.max_age_gap(m.partner);
+        // Singles are sorted by birth, so only a window can be close enough.
+        let start = singles
+            .partition_point(|&id| self.character(id).birth.year < partner.birth.year - gap - 1);
+        singles[start..]
+            .iter()
+            .take_while(|&&id| self.character(id).birth.year <= partner.birth.year + gap + 1)
+            .enumerate()
+            .filter(|&(_, &id)| self.could_replace(m, id))
+            .min_by_key(|&(_, &id)| {
+                let single = self.character(id);
+                single.birth.years_until(lowborn.birth).abs()
+            })
+            .map(|(i, _)| start + i)
+    }
+
+    /// Whether `single` could have married the partner of `m` on the day and
+    /// had all their children.
+    fn could_replace(&self, m: &LowbornMatch, single: CharacterId) -> bool {
+        let lowborn = self.character(m.lowborn);
+        let partner = self.character(m.partner);
+        let s = self.character(single);
+        if s.sex != lowborn.sex || s.dynasty == partner.dynasty {
+            return false;
+        }
+        let age = s.birth.years_until(m.married);
+        let partner_age = partner.birth.years_until(m.married);
+        let alive = s.death.is_none_or(|death| death > m.married);
+        let outlives = m
+            .remarried
+            .is_some_and(|remarried| s.death.is_none_or(|death| death > remarried));
+        if !alive
+            || outlives
+            || age < FERTILE_FROM
+            || !self.ages_fit(single, m.partner, age, partner_age)
+        {
+            return false;
+        }
+        if !self.may_match(single, m.partner) {
+            return false;
+        }
+        if s.has_congenital("infertile") && !lowborn.children.is_empty() {
+            return false;
+        }
+        // Blood tiers add to the age a woman can still have children at
+        // what they add in the fertility tables.
+        let peak_years = self
+            .config
+            .life_stages
+            .blood_fertility(s.blood_tier)
+            .peak_years;
+        let fertile_until = FEMALE_FERTILE_UNTIL + peak_years as i32;
+        lowborn.children.iter().all(|&child| {
+            let born = self.character(child).birth;
+            let lived = s.death.is_none_or(|death| death >= born);
+            lived && (s.sex == Sex::Male || s.birth.years_until(born) <= fertile_until)
+        })
+    }
+
+    /// Put `single` in the place of `lowborn` as spouse and parent.
+    fn take_place(&mut self, lowborn: CharacterId, single: CharacterId) {
+        let partner = self
+            .character(lowborn)
+            .spouse
+            .expect("lowborn spouses are married");
+        let children = std::mem::take(&mut self.character_mut(lowborn).children);
+        for &child in &children {
+            let child = self.character_mut(child);
+            for parent in [&mut child.father, &mut child.mother] {
+                if *parent == Some(lowborn) {
+                    *parent = Some(single);
+                }
+            }
+        }
+        self.character_mut(single).children.extend(children);
+        self.character_mut(single).spouse = Some(partner);
+        let partner = self.character_mut(partner);
+        match partner
+            .extra_spouses
+            .iter_mut()
+            .find(|&&mut wife| wife == lowborn)
+        {
+            Some(wife) => *wife = single,
+            None => partner.spouse = Some(single),
+        }
+        for event in &mut partner.events {
+            if let EventKind::Marriage { spouse, .. } = &mut event.kind {
+                if *spouse == lowborn {
+                    *spouse = single;
+                }
+            }
+        }
+        self.character_mut(lowborn).spouse = None;
+    }
+
+    /// Drop the characters flagged in `removed`, which nobody refers to.
+    fn remove(&mut self, removed: &[bool]) {
+        let mut new_index = Vec::with_capacity(removed.len());
+        let mut next = 0;
+        for &gone in removed {
+            new_index.push(next);
+            if !gone {
+                next += 1;
+            }
+        }
+        let characters = std::mem::take(&mut self.characters);
+        let living = std::mem::take(&mut self.living_at_end);
+        for ((mut character, alive), &gone) in characters.into_iter().zip(living).zip(removed) {
+            if gone {
+                continue;
+            }
+            character.remap_ids(|id| CharacterId(new_index[id.0]));
+            self.characters.push(character);
+            self.living_at_end.push(alive);
+        }
+    }
+}
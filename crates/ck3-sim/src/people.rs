@@ -0,0 +1,244 @@
+//! Reading the characters of a generated history back.
+//!
+//! The GEDCOM, DOT, CSV, report and chronicle exports and the project
+//! database work from the files of an output directory, so that they take
+//! whatever either engine wrote. A [`Person`] is what they need of each
+//! character: who they are, their family, what they are like, and the dates
+//! of their life.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use paradox_script::{Block, Date, Entry, Scalar, Value};
+
+use crate::character::SKILLS;
+use crate::error::SimError;
+use crate::export::HISTORY_FILE;
+use crate::houses::HOUSES_FILE;
+use crate::localization::{dynasty_key, house_key};
+
+/// Title history of every dynasty's titles.
+pub(crate) const TITLES_FILE: &str = "title_history.txt";
+/// Directory of the per-dynasty landed title histories.
+pub(crate) const TITLES_DIR: &str = "history/titles";
+
+/// A character of a history file.
+#[derive(Clone, Debug, Default)]
+pub struct Person {
+    pub id: String,
+    pub name: String,
+    pub female: bool,
+    pub dynasty: Option<String>,
+    pub house: Option<String>,
+    pub culture: Option<String>,
+    pub religion: Option<String>,
+    pub sexuality: Option<String>,
+    /// The ID of their entry in the DNA file.
+    pub dna: Option<String>,
+    /// Levels of [`SKILLS`], in order.
+    pub skills: [Option<String>; SKILLS.len()],
+    /// Traits held from birth, then those gained, in order.
+    pub traits: Vec<String>,
+    /// Parents, the adoptive ones of an adopted character.
+    pub father: Option<String>,
+    pub mother: Option<String>,
+    pub adopted: Option<Date>,
+    pub birth: Option<Date>,
+    pub death: Option<Date>,
+    pub death_reason: Option<String>,
+    /// Spouses married, with the date.
+    pub marriages: Vec<(Date, String)>,
+    /// What each dated block of theirs does, in order: the date, the key of
+    /// what is done, such as `trait` or `add_spouse`, and its value, or for
+    /// one given as a block such as `death`, the value of its first entry.
+    /// An `effect` block's effects are listed in its place.
+    pub events: Vec<(Date, String, String)>,
+}
+
+/// The characters of the history of the output directory `dir`, in file
+/// order.
+pub fn read_people(dir: &Path) -> Result<Vec<Person>, SimError> {
+    let path = dir.join(HISTORY_FILE);
+    let bytes = fs::read(&path).map_err(|source| SimError::Io { path, source })?;
+    parse_people(&String::from_utf8_lossy(&bytes))
+}
+
+/// The characters of the history file `text`, in file order.
+pub fn parse_people(text: &str) -> Result<Vec<Person>, SimError> {
+    let text = text.strip_prefix(paradox_script::BOM).unwrap_or(text);
+    let history = paradox_script::parse(text).map_err(|err| SimError::History {
+        message: format!("the {HISTORY_FILE} file cannot be read: {err}"),
+    })?;
+    Ok(history
+        .entries()
+        .filter_map(|entry| {
+            Some(Person::read(
+                entry.key.text().into_owned(),
+                entry.value.as_block()?,
+            ))
+        })
+        .collect())
+}
+
+impl Person {
+    fn read(id: String, block: &Block) -> Self {
+        let mut person = Self {
+            name: text(block, "name").unwrap_or_else(|| id.clone()),
+            female: text(block, "female").as_deref() == Some("yes"),
+            dynasty: text(block, "dynasty"),
+            house: text(block, "dynasty_house"),
+            culture: text(block, "culture"),
+            religion: text(block, "religion").or_else(|| text(block, "faith")),
+            sexuality: text(block, "sexuality"),
+            dna: text(block, "dna"),
+            skills: SKILLS.map(|skill| text(block, skill)),
+            traits: traits(block).collect(),
+            father: text(block, "father"),
+            mother: text(block, "mother"),
+            id,
+            ..Self::default()
+        };
+        for (date, value) in block.dated() {
+            let Some(dated) = value.as_block() else {
+                continue;
+            };
+            if dated.get("birth").is_some() {
+                person.birth.get_or_insert(date);
+            }
+            if let Some(death) = dated.get("death") {
+                person.death.get_or_insert(date);
+                person.death_reason = death
+                    .as_block()
+                    .and_then(|death| text(death, "death_reason"));
+            }
+            person.traits.extend(traits(dated));
+            for entry in dated.entries() {
+                let key = entry.key.text();
+                match entry.value.as_block() {
+                    Some(effect) if key == "effect" => {
+                        for entry in effect.entries() {
+                            person.events.push(event(date, entry));
+                        }
+                    }
+                    _ => person.events.push(event(date, entry)),
+                }
+            }
+            for key in ["add_spouse", "add_matrilineal_spouse"] {
+                for spouse in dated.get_all(key).filter_map(Value::as_scalar) {
+                    person.marriages.push((date, spouse.text().into_owned()));
+                }
+            }
+            for effect in dated.get_all("effect").filter_map(Value::as_block) {
+                let father = text(effect, "set_father");
+                let mother = text(effect, "set_mother");
+                if father.is_some() || mother.is_some() {
+                    person.adopted = Some(date);
+                    person.father = father.or(person.father.take());
+                    person.mother = mother.or(person.mother.take());
+                }
+            }
+        }
+        person
+    }
+
+    /// The dynasty they belong to: their own, their house's in
+    /// `house_dynasties`, or for a house of a dynasty defined elsewhere, that
+    /// house.
+    pub fn lineage<'p>(&'p self, house_dynasties: &'p HashMap<String, String>) -> Option<&'p str> {
+        self.dynasty.as_deref().or_else(|| {
+            let house = self.house.as_deref()?;
+            Some(house_dynasties.get(house).map_or(house, String::as_str))
+        })
+    }
+
+    /// Localisation key of the name of their house, or else of their
+    /// dynasty.
+    pub fn surname_key(&self) -> Option<String> {
+        self.house
+            .as_deref()
+            .map(house_key)
+            .or_else(|| self.dynasty.as_deref().map(dynasty_key))
+    }
+}
+
+/// The dynasty of each house defined in the output's house definitions.
+pub fn read_house_dynasties(dir: &Path) -> HashMap<String, String> {
+    let Some(houses) = read(&dir.join(HOUSES_FILE)) else {
+        return HashMap::new();
+    };
+    houses
+        .entries()
+        .filter_map(|entry| {
+            let dynasty = text(entry.value.as_block()?, "dynasty")?;
+            Some((entry.key.text().into_owned(), dynasty))
+        })
+        .collect()
+}
+
+/// The holders of a title, each with the date they took it, in order; none
+/// for a title left unheld.
+pub(crate) type Holders = Vec<(Date, Option<String>)>;
+
+/// The holders of each title in the output's title histories, with the
+/// title's key.
+pub(crate) fn read_titles(dir: &Path) -> Vec<(String, Holders)> {
+    let mut files = vec![dir.join(TITLES_FILE)];
+    if let Ok(entries) = fs::read_dir(dir.join(TITLES_DIR)) {
+        files.extend(entries.flatten().map(|entry| entry.path()));
+    }
+    let mut titles = Vec::new();
+    for file in files.iter().filter_map(|path| read(path)) {
+        for entry in file.entries() {
+            let Some(title) = entry.value.as_block() else {
+                continue;
+            };
+            let mut holders: Holders = title
+                .dated()
+                .filter_map(|(date, dated)| {
+                    let holder = text(dated.as_block()?, "holder")?;
+                    Some((date, (holder != "0").then_some(holder)))
+                })
+                .collect();
+            holders.sort_by_key(|(date, _)| *date);
+            titles.push((entry.key.text().into_owned(), holders));
+        }
+    }
+    titles
+}
+
+/// The event of `entry`, of a block dated `date`.
+fn event(date: Date, entry: &Entry) -> (Date, String, String) {
+    let value = match entry.value.as_block() {
+        Some(block) => block.entries().find_map(|entry| entry.value.as_scalar()),
+        None => entry.value.as_scalar(),
+    };
+    (
+        date,
+        entry.key.text().into_owned(),
+        value.map_or(String::new(), |value| value.text().into_owned()),
+    )
+}
+
+/// The traits `block` gives.
+fn traits(block: &Block) -> impl Iterator<Item = String> + '_ {
+    block
+        .get_all("trait")
+        .filter_map(Value::as_scalar)
+        .map(|name| name.text().into_owned())
+}
+
+/// The text of the first scalar under `key`.
+pub(crate) fn text(block: &Block, key: &str) -> Option<String> {
+    block
+        .get(key)
+        .and_then(Value::as_scalar)
+        .map(|scalar| Scalar::text(scalar).into_owned())
+}
+
+/// The parsed file at `path`, if there is one that parses.
+pub(crate) fn read(path: &Path) -> Option<Block> {
+    let bytes = fs::read(path).ok()?;
+    let text = String::from_utf8_lossy(&bytes);
+    paradox_script::parse(text.strip_prefix(paradox_script::BOM).unwrap_or(&text)).ok()
+}
@@ -0,0 +1,449 @@
+//! Plugins: Rhai scripts that add rules of their own to a run.
+//!
+//! Every `.rhai` file in the `plugins` folder of a config directory is
+//! compiled along with the config, and its hooks are called in order of file
+//! name. A script defines any of these functions:
+//!
+//! - `on_character_born(character, year)`, for every child born in the run;
+//! - `on_marriage(character, spouse, year)`, once for each of the two;
+//! - `on_death(character, year)`;
+//! - `on_output_character(character)`, as the character is written to the
+//!   history file.
+//!
+//! A character is a map of its `id`, `name`, `female`, `birth_year`, `age`,
+//! `dynasty`, `house`, `culture`, `religion`, `sexuality`, `traits`, `skills`
+//! by name, `generation`, `is_bastard`, `father` and `mother`, and on death
+//! its `death_reason`. A hook that returns such a map changes the character
+//! to match: its name, culture, religion, sexuality, traits, skills and death
+//! reason, the rest being read-only. `on_output_character` only changes what
+//! is written, and its map may also hold `effects`, lines such as
+//! `add_gold = 100` written in an effect block on the character's birthday.
+//!
+//! Scripts roll dice with `random()`, `chance(p)` and `random_int(low,
+//! high)`, drawn from the run's seed, so that runs with plugins stay
+//! reproducible; a run without any draws nothing and is as it was. A hook
+//! that fails changes nothing and is listed in the run's summary, or in a
+//! comment of the history file if it failed while writing it.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST, INT};
+
+use crate::character::{Character, CharacterId, EventKind, Sex, SKILLS};
+use crate::error::SimError;
+use crate::random;
+use crate::simulation::Simulation;
+
+/// Folder of a config directory holding the plugins.
+pub const PLUGINS_DIR: &str = "plugins";
+/// Extension of plugin scripts.
+const PLUGIN_EXTENSION: &str = "rhai";
+/// Most operations a single hook call may take, so that a script stuck in a
+/// loop fails instead of hanging the run.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+thread_local! {
+    /// Dice of the hook being called, seeded before each call.
+    static DICE: RefCell<ChaCha12Rng> = RefCell::new(ChaCha12Rng::seed_from_u64(0));
+}
+
+/// A moment of the run plugins can hook into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Hook {
+    CharacterBorn,
+    Marriage,
+    Death,
+    OutputCharacter,
+}
+
+impl Hook {
+    const ALL: [Hook; 4] = [
+        Hook::CharacterBorn,
+        Hook::Marriage,
+        Hook::Death,
+        Hook::OutputCharacter,
+    ];
+
+    fn function(self) -> &'static str {
+        match self {
+            Hook::CharacterBorn => "on_character_born",
+            Hook::Marriage => "on_marriage",
+            Hook::Death => "on_death",
+            Hook::OutputCharacter => "on_output_character",
+        }
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Hook::CharacterBorn | Hook::Death => 2,
+            Hook::Marriage => 3,
+            Hook::OutputCharacter => 1,
+        }
+    }
+}
+
+/// A character as the `on_output_character` hooks have it written.
+pub(crate) struct Output<'c> {
+    pub character: Cow<'c, Character>,
+    /// Effect lines to write on the character's birthday.
+    pub effects: Vec<String>,
+    /// Hooks that failed, to note in the history file.
+    pub failures: Vec<String>,
+}
+
+struct Script {
+    /// File name, e.g. `naming.rhai`.
+    name: String,
+    ast: AST,
+    hooks: Vec<Hook>,
+}
+
+struct Loaded {
+    engine: Engine,
+    scripts: Vec<Script>,
+}
+
+/// The plugins of a config directory, shared by every run of the config.
+#[derive(Clone, Default)]
+pub struct Plugins(Option<Arc<Loaded>>);
+
+impl fmt::Debug for Plugins {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl Plugins {
+    /// Compile the scripts in the `plugins` folder of `dir`, if it has one.
+    pub fn load(dir: &Path) -> Result<Self, SimError> {
+        let folder = dir.join(PLUGINS_DIR);
+        let Ok(entries) = fs::read_dir(&folder) else {
+            return Ok(Self::default());
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .extension()
+                        .is_some_and(|extension| extension == PLUGIN_EXTENSION)
+            })
+            .collect();
+        if paths.is_empty() {
+            return Ok(Self::default());
+        }
+        paths.sort();
+
+        let engine = engine();
+        let mut scripts = Vec::new();
+        for path in paths {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let name = name.into_owned();
+            let text = fs::read_to_string(&path).map_err(|source| SimError::Io {
+                path: path.clone(),
+                source,
+            })?;
+            let ast = engine.compile(&text).map_err(|err| SimError::Plugin {
+                message: format!("{PLUGINS_DIR}/{name} does not compile: {err}"),
+            })?;
+            let hooks = Hook::ALL
+                .into_iter()
+                .filter(|hook| {
+                    ast.iter_functions().any(|function| {
+                        function.name == hook.function() && function.params.len() == hook.arity()
+                    })
+                })
+                .collect();
+            scripts.push(Script { name, ast, hooks });
+        }
+        Ok(Self(Some(Arc::new(Loaded { engine, scripts }))))
+    }
+
+    /// File names of the scripts, in the order their hooks are called.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .iter()
+            .flat_map(|loaded| &loaded.scripts)
+            .map(|script| script.name.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+/// An engine with the dice functions, and limits on what a call may take.
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.register_fn("random", || {
+        DICE.with(|dice| dice.borrow_mut().random::<f64>())
+    });
+    engine.register_fn("chance", |p: f64| {
+        DICE.with(|dice| random::chance(&mut *dice.borrow_mut(), p))
+    });
+    engine.register_fn("random_int", |low: INT, high: INT| {
+        if high <= low {
+            return low;
+        }
+        DICE.with(|dice| dice.borrow_mut().random_range(low..=high))
+    });
+    engine
+}
+
+/// A seed for the dice of one hook call, from the run's seed and what the
+/// call is about, so that it does not depend on the calls made before it.
+fn call_seed(seed: u64, hook: Hook, script: &str, character: &str, year: i32) -> u64 {
+    // FNV-1a, which unlike the standard hasher is the same everywhere.
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64 ^ seed;
+    let year = year.to_le_bytes();
+    let parts: [&[u8]; 4] = [
+        hook.function().as_bytes(),
+        script.as_bytes(),
+        character.as_bytes(),
+        &year,
+    ];
+    for part in parts {
+        for &byte in part.iter().chain(&[0xff]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+impl Loaded {
+    /// Call `hook` of `script` with `args`, returning the changes it asks
+    /// for, if any.
+    fn call(
+        &self,
+        script: &Script,
+        hook: Hook,
+        seed: u64,
+        args: Vec<Dynamic>,
+    ) -> Result<Option<Map>, String> {
+        DICE.with(|dice| *dice.borrow_mut() = ChaCha12Rng::seed_from_u64(seed));
+        let options = CallFnOptions::new().eval_ast(false);
+        let returned: Dynamic = self
+            .engine
+            .call_fn_with_options(
+                options,
+                &mut Scope::new(),
+                &script.ast,
+                hook.function(),
+                args,
+            )
+            .map_err(|err| err.to_string())?;
+        Ok(returned.try_cast::<Map>())
+    }
+}
+
+impl Simulation {
+    /// Call the `on_character_born` hooks for `id`.
+    pub(crate) fn character_born_hooks(&mut self, id: CharacterId) {
+        let year = self.character(id).birth.year;
+        self.character_hooks(Hook::CharacterBorn, id, None, year);
+    }
+
+    /// Call the `on_marriage` hooks for both partners of a marriage made in
+    /// `year`.
+    pub(crate) fn marriage_hooks(&mut self, first: CharacterId, second: CharacterId, year: i32) {
+        self.character_hooks(Hook::Marriage, first, Some(second), year);
+        self.character_hooks(Hook::Marriage, second, Some(first), year);
+    }
+
+    /// Call the `on_death` hooks for `id`, who died in `year`.
+    pub(crate) fn death_hooks(&mut self, id: CharacterId, year: i32) {
+        self.character_hooks(Hook::Death, id, None, year);
+    }
+
+    fn character_hooks(
+        &mut self,
+        hook: Hook,
+        id: CharacterId,
+        other: Option<CharacterId>,
+        year: i32,
+    ) {
+        let Some(loaded) = self.config.plugins.0.clone() else {
+            return;
+        };
+        for script in loaded.scripts.iter().filter(|s| s.hooks.contains(&hook)) {
+            let mut args = vec![Dynamic::from_map(self.character_map(self.character(id)))];
+            if let Some(other) = other {
+                args.push(Dynamic::from_map(self.character_map(self.character(other))));
+            }
+            args.push(Dynamic::from_int(INT::from(year)));
+            let seed = call_seed(
+                self.seed(),
+                hook,
+                &script.name,
+                &self.character(id).id,
+                year,
+            );
+            let changed = loaded
+                .call(script, hook, seed, args)
+                .and_then(|changes| match changes {
+                    Some(changes) => apply(self.character_mut(id), &changes),
+                    None => Ok(()),
+                });
+            if let Err(err) = changed {
+                let error = format!("{PLUGINS_DIR}/{}: {}: {err}", script.name, hook.function());
+                if !self.plugin_errors.contains(&error) {
+                    self.plugin_errors.push(error);
+                }
+            }
+        }
+    }
+
+    /// The character `c` as the `on_output_character` hooks would have it
+    /// written, with the effects they add.
+    pub(crate) fn output_hooks<'c>(&self, c: &'c Character) -> Output<'c> {
+        let mut output = Output {
+            character: Cow::Borrowed(c),
+            effects: Vec::new(),
+            failures: Vec::new(),
+        };
+        let Some(loaded) = &self.config.plugins.0 else {
+            return output;
+        };
+        let hook = Hook::OutputCharacter;
+        for script in loaded.scripts.iter().filter(|s| s.hooks.contains(&hook)) {
+            let args = vec![Dynamic::from_map(self.character_map(&output.character))];
+            let seed = call_seed(self.seed(), hook, &script.name, &c.id, c.birth.year);
+            let changed = loaded
+                .call(script, hook, seed, args)
+                .and_then(|changes| match changes {
+                    Some(changes) => {
+                        let mut changed = output.character.as_ref().clone();
+                        apply(&mut changed, &changes)?;
+                        let effects = match changes.get("effects") {
+                            Some(effects) => strings(effects, "effects")?,
+                            None => Vec::new(),
+                        };
+                        output.character = Cow::Owned(changed);
+                        output.effects.extend(effects);
+                        Ok(())
+                    }
+                    None => Ok(()),
+                });
+            if let Err(err) = changed {
+                output.failures.push(format!(
+                    "{PLUGINS_DIR}/{}: {}: {}",
+                    script.name,
+                    hook.function(),
+                    err.replace('\n', " ")
+                ));
+            }
+        }
+        output
+    }
+
+    /// What a hook is told of `c`.
+    fn character_map(&self, c: &Character) -> Map {
+        let text = |value: &str| Dynamic::from(value.to_string());
+        let optional = |value: Option<&str>| value.map_or(Dynamic::UNIT, text);
+        let mut map = Map::new();
+        map.insert("id".into(), text(&c.id));
+        map.insert("name".into(), text(&c.name));
+        map.insert("female".into(), Dynamic::from_bool(c.sex == Sex::Female));
+        map.insert("birth_year".into(), (INT::from(c.birth.year)).into());
+        map.insert("age".into(), (INT::from(c.age)).into());
+        map.insert("dynasty".into(), optional(c.dynasty.as_deref()));
+        map.insert("house".into(), optional(c.house.as_deref()));
+        map.insert("culture".into(), text(&c.culture));
+        map.insert("religion".into(), text(&c.religion));
+        map.insert("sexuality".into(), text(&c.sexuality));
+        let traits: Array = c.traits.iter().map(|name| text(name)).collect();
+        map.insert("traits".into(), traits.into());
+        let skills: Map = SKILLS
+            .iter()
+            .zip(c.skills)
+            .map(|(skill, level)| ((*skill).into(), INT::from(level).into()))
+            .collect();
+        map.insert("skills".into(), skills.into());
+        map.insert("generation".into(), INT::from(c.generation).into());
+        map.insert("is_bastard".into(), Dynamic::from_bool(c.is_bastard));
+        let parent =
+            |id: Option<CharacterId>| optional(id.map(|id| self.character(id).id.as_str()));
+        map.insert("father".into(), parent(c.father));
+        map.insert("mother".into(), parent(c.mother));
+        if let Some(reason) = death_reason(c) {
+            map.insert("death_reason".into(), text(reason));
+        }
+        map
+    }
+}
+
+fn death_reason(c: &Character) -> Option<&str> {
+    c.events.iter().find_map(|event| match &event.kind {
+        EventKind::Death { reason } => Some(reason.as_str()),
+        _ => None,
+    })
+}
+
+/// Change `c` as `changes` asks, or say what is wrong with them. Nothing is
+/// changed unless all of them can be.
+fn apply(c: &mut Character, changes: &Map) -> Result<(), String> {
+    let mut changed = c.clone();
+    for (key, slot) in [
+        ("name", &mut changed.name),
+        ("culture", &mut changed.culture),
+        ("religion", &mut changed.religion),
+        ("sexuality", &mut changed.sexuality),
+    ] {
+        if let Some(value) = changes.get(key) {
+            *slot = string(value, key)?;
+        }
+    }
+    if let Some(traits) = changes.get("traits") {
+        changed.traits = strings(traits, "traits")?;
+    }
+    if let Some(skills) = changes.get("skills") {
+        let skills = skills
+            .read_lock::<Map>()
+            .ok_or("skills is not a map of levels by skill")?;
+        for (skill, level) in skills.iter() {
+            let index = SKILLS
+                .iter()
+                .position(|name| name == skill)
+                .ok_or_else(|| format!("{skill} is not a skill"))?;
+            let level = level
+                .as_int()
+                .ok()
+                .and_then(|level| u32::try_from(level).ok())
+                .ok_or_else(|| format!("skill {skill} is not a level"))?;
+            changed.skills[index] = level;
+        }
+    }
+    if let Some(reason) = changes.get("death_reason") {
+        let reason = string(reason, "death_reason")?;
+        for event in &mut changed.events {
+            if let EventKind::Death { reason: slot } = &mut event.kind {
+                *slot = reason.clone();
+            }
+        }
+    }
+    *c = changed;
+    Ok(())
+}
+
+fn string(value: &Dynamic, key: &str) -> Result<String, String> {
+    value
+        .clone()
+        .into_string()
+        .map_err(|_| format!("{key} is not a string"))
+}
+
+fn strings(value: &Dynamic, key: &str) -> Result<Vec<String>, String> {
+    let array = value
+        .read_lock::<Array>()
+        .ok_or_else(|| format!("{key} is not an array of strings"))?;
+    array.iter().map(|value| string(value, key)).collect()
+}
@@ -0,0 +1,114 @@
+//! Several wives and concubines.
+//!
+//! Men are monogamous unless the marriage doctrine of their faith, or one
+//! their dynasty sets over it, gives them room for more. A married man with
+//! room takes another wife or a concubine with the doctrine's yearly chance,
+//! picking among the unmarried women he could have wed by the rules and
+//! preferences of [`crate::matchmaking`], or else a lowborn. Wives and
+//! concubines bear him children alike. When his first wife dies the next one
+//! takes her place, and when he dies all of them are free to marry again.
+
+use ck3_config::MarriageDoctrine;
+use rand::Rng;
+
+use crate::character::{CharacterId, EventKind, Sex};
+use crate::date::Date;
+use crate::matchmaking::BLOOD_TIER_MARRIAGE_YEARS;
+use crate::random;
+use crate::simulation::{Simulation, FERTILE_FROM};
+
+impl Simulation {
+    /// The marriage doctrine `id` lives by, if they may have more than one
+    /// wife or any concubines.
+    fn marriage_doctrine(&self, id: CharacterId) -> Option<MarriageDoctrine> {
+        let c = self.character(id);
+        if c.sex != Sex::Male {
+            return None;
+        }
+        self.dynasty_config(c.dynasty.as_deref())
+            .and_then(|d| d.marriage_doctrine.as_ref())
+            .or_else(|| {
+                self.config
+                    .initialization
+                    .matchmaking
+                    .marriage_doctrines
+                    .get(&c.religion)
+            })
+            .cloned()
+    }
+
+    /// Let married men with room for another wife or concubine take one.
+    pub(crate) fn take_consorts(&mut self, year: i32) {
+        let init = &self.config.initialization;
+        if init.matchmaking.marriage_doctrines.is_empty()
+            && init.dynasties.iter().all(|d| d.marriage_doctrine.is_none())
+        {
+            return;
+        }
+        let alliances = self.alliances();
+        for man in self.ids().collect::<Vec<_>>() {
+            let m = self.character(man);
+            if !m.is_alive() || !m.is_married() {
+                continue;
+            }
+            let Some(doctrine) = self.marriage_doctrine(man) else {
+                continue;
+            };
+            let wife = m.extra_spouses.len() + 1 < doctrine.max_spouses as usize;
+            let concubine = m.concubines.len() < doctrine.max_concubines as usize;
+            if !(wife || concubine) || !random::chance(&mut self.rng, doctrine.consort_chance) {
+                continue;
+            }
+            let as_concubine = !wife || (concubine && self.rng.random());
+            let candidates: Vec<CharacterId> = self
+                .ids()
+                .filter(|&woman| self.may_become_consort(man, woman))
+                .collect();
+            let consort = match self.pick_match(man, &candidates, &alliances) {
+                Some(woman) => woman,
+                None => self.spawn_lowborn_match(man, year),
+            };
+            self.take_consort(man, consort, as_concubine, year);
+        }
+    }
+
+    /// Whether `woman` is of an age to marry and could wed `man`.
+    fn may_become_consort(&self, man: CharacterId, woman: CharacterId) -> bool {
+        let w = self.character(woman);
+        let min_age = FERTILE_FROM + w.blood_tier as i32 * BLOOD_TIER_MARRIAGE_YEARS;
+        w.sex == Sex::Female && w.age >= min_age && self.can_wed(man, woman, false)
+    }
+
+    /// Make `woman` a wife of `man` besides his first, or his concubine.
+    fn take_consort(&mut self, man: CharacterId, woman: CharacterId, concubine: bool, year: i32) {
+        let date = Date::random_in(year, &mut self.rng);
+        self.character_mut(woman).spouse = Some(man);
+        let m = self.character_mut(man);
+        if concubine {
+            m.concubines.push(woman);
+            m.add_event(date, EventKind::Concubinage { concubine: woman });
+        } else {
+            m.extra_spouses.push(woman);
+            m.add_event(
+                date,
+                EventKind::Marriage {
+                    spouse: woman,
+                    matrilineal: false,
+                },
+            );
+        }
+    }
+
+    /// Take `deceased` from the family of the living `survivor`: the next
+    /// wife becomes the first when the first dies, and a man's dead wives and
+    /// concubines leave his lists.
+    pub(crate) fn widow(&mut self, survivor: CharacterId, deceased: CharacterId) {
+        let s = self.character_mut(survivor);
+        if s.spouse == Some(deceased) {
+            s.spouse = (!s.extra_spouses.is_empty()).then(|| s.extra_spouses.remove(0));
+        } else {
+            s.extra_spouses.retain(|&id| id != deceased);
+            s.concubines.retain(|&id| id != deceased);
+        }
+    }
+}
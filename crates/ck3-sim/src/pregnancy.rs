@@ -0,0 +1,77 @@
+//! Conception and birth dates.
+//!
+//! Without a `pregnancy` section a child rolled for a year is born on any day
+//! of it. With one, the birth falls on a day that leaves room for the whole
+//! pregnancy: conceived `gestationMonths` earlier, when the mother was within
+//! her maternal age window and both parents past the fertility onset of their
+//! blood tier, and at least `monthsBetweenBirths` after the mother's last
+//! child. A year without such a day has no birth. The same rules hold for
+//! every couple, so a progenitor and his lowborn wife have their children as
+//! late as their blood makes any later generation have theirs.
+
+use ck3_config::Pregnancy;
+
+use crate::character::CharacterId;
+use crate::date::Date;
+use crate::matchmaking::BLOOD_TIER_MARRIAGE_YEARS;
+use crate::simulation::Simulation;
+
+impl Simulation {
+    /// The configured pregnancy rules, if any.
+    pub(crate) fn pregnancy(&self) -> Option<Pregnancy> {
+        self.config.life_stages.pregnancy.clone()
+    }
+
+    /// The birth date of a child of `mother` and `father` rolled in `year`,
+    /// either of whom may be unknown, or `None` if no day of the year fits
+    /// the pregnancy.
+    pub(crate) fn due_date(
+        &mut self,
+        pregnancy: &Pregnancy,
+        mother: Option<CharacterId>,
+        father: Option<CharacterId>,
+        year: i32,
+    ) -> Option<Date> {
+        let gestation = pregnancy.gestation_months as i32;
+        let mut first = Date::new(year, 1, 1);
+        let mut last = Date::new(year, 12, 28);
+        for parent in [mother, father].into_iter().flatten() {
+            let onset = fertility_onset(pregnancy, self.character(parent).blood_tier);
+            let fertile = self.character(parent).birth.years_later(onset);
+            first = first.max(fertile.months_later(gestation));
+        }
+        if let Some(mother) = mother {
+            let m = self.character(mother);
+            let min_age = pregnancy.min_maternal_age as i32;
+            first = first.max(m.birth.years_later(min_age).months_later(gestation));
+            // Blood tiers add to it the years they stretch the fertility
+            // tables by.
+            let peak_years = self
+                .config
+                .life_stages
+                .blood_fertility(m.blood_tier)
+                .peak_years;
+            let max_age = (pregnancy.max_maternal_age + peak_years) as i32;
+            // She may conceive until the birthday after her oldest age.
+            last = last.min(m.birth.years_later(max_age + 1).months_later(gestation));
+            if let Some(previous) = m.children.iter().map(|&id| self.character(id).birth).max() {
+                first = first.max(previous.months_later(pregnancy.months_between_births as i32));
+            }
+        }
+        (first <= last).then(|| Date::random_between(first, last, &mut self.rng))
+    }
+}
+
+/// Age from which a character of blood tier `tier` can have children.
+fn fertility_onset(pregnancy: &Pregnancy, tier: u32) -> i32 {
+    let base = pregnancy.fertility_onset as i32;
+    if tier == 0 {
+        return base;
+    }
+    pregnancy
+        .blood_tier_fertility_onset
+        .get(&tier.to_string())
+        .map_or(base + BLOOD_TIER_MARRIAGE_YEARS * tier as i32, |&onset| {
+            onset as i32
+        })
+}
@@ -0,0 +1,163 @@
+//! Quick previews of a dynasty.
+//!
+//! [`Simulation::preview`] simulates one dynasty of a config on its own, the
+//! way a parallel run simulates each, but only until a child of the
+//! generation after the ones asked for is born. A few generations take a
+//! fraction of a second, so a UI can show what a config gives while it is
+//! being edited, before committing to the whole period. The preview holds
+//! the dynasty's members up to the last generation asked for and the
+//! spouses they married, with the living left alive rather than given
+//! deaths.
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+use crate::character::{CharacterId, EventKind, Sex};
+use crate::config::SimConfig;
+use crate::names::NameLists;
+use crate::simulation::Simulation;
+
+/// Most generations a preview simulates.
+pub const MAX_PREVIEW_GENERATIONS: u32 = 5;
+
+/// The first generations of a dynasty.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Preview {
+    pub dynasty_id: String,
+    pub generations: u32,
+    pub first_year: i32,
+    /// The year the preview stopped after.
+    pub last_year: i32,
+    /// Members by generation, then birth, followed by the spouses who
+    /// married in.
+    pub members: Vec<PreviewMember>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewMember {
+    pub id: String,
+    pub name: String,
+    pub female: bool,
+    pub generation: u32,
+    pub born: i32,
+    /// None for the living.
+    pub died: Option<i32>,
+    pub father: Option<String>,
+    pub mother: Option<String>,
+    pub spouses: Vec<String>,
+    /// Whether the character married into the dynasty rather than being
+    /// born to it.
+    pub married_in: bool,
+}
+
+impl Simulation {
+    /// Simulate the first `generations` generations of the dynasty
+    /// `dynasty_id` of `config` on its own, at most
+    /// [`MAX_PREVIEW_GENERATIONS`], or none if the config has no such
+    /// dynasty.
+    ///
+    /// The preview stops at the end of the config's period if the
+    /// generations take longer.
+    pub fn preview(
+        config: &SimConfig,
+        names: NameLists,
+        dynasty_id: &str,
+        generations: u32,
+        seed: Option<u64>,
+    ) -> Option<Preview> {
+        let dynasty = config
+            .initialization
+            .dynasties
+            .iter()
+            .find(|dynasty| dynasty.dynasty_id == dynasty_id)?;
+        let generations = generations.clamp(1, MAX_PREVIEW_GENERATIONS);
+        let mut shard = config.clone();
+        shard.initialization.dynasties = vec![dynasty.clone()];
+        let first_year = shard.initialization.min_year;
+        let end_year = shard.initialization.max_year;
+        let mut run = Simulation::new(shard, names, seed);
+
+        let mut last_year = first_year;
+        for year in first_year..=end_year {
+            run.tick(year);
+            last_year = year;
+            if run.characters.iter().any(|c| c.generation > generations) {
+                break;
+            }
+        }
+        Some(run.preview_members(dynasty_id, generations, first_year, last_year))
+    }
+
+    fn preview_members(
+        &self,
+        dynasty_id: &str,
+        generations: u32,
+        first_year: i32,
+        last_year: i32,
+    ) -> Preview {
+        let born_to: BTreeSet<usize> = self
+            .ids()
+            .filter(|&id| {
+                let c = self.character(id);
+                c.dynasty.as_deref() == Some(dynasty_id) && c.generation <= generations
+            })
+            .map(|id| id.0)
+            .collect();
+        // Marriages are recorded on one of the spouses only.
+        let mut spouses: Vec<Vec<usize>> = vec![Vec::new(); self.characters.len()];
+        for (id, character) in self.characters() {
+            for event in &character.events {
+                if let EventKind::Marriage { spouse, .. } = event.kind {
+                    spouses[id.0].push(spouse.0);
+                    spouses[spouse.0].push(id.0);
+                }
+            }
+        }
+        let married_in: BTreeSet<usize> = born_to
+            .iter()
+            .flat_map(|&i| spouses[i].iter().copied())
+            .filter(|i| !born_to.contains(i))
+            .collect();
+
+        let mut order: Vec<usize> = born_to.iter().copied().collect();
+        order.sort_by_key(|&i| (self.characters[i].generation, self.characters[i].birth));
+        order.extend(married_in.iter().copied());
+        let shown: BTreeSet<usize> = born_to.union(&married_in).copied().collect();
+        let id_of = |i: usize| self.characters[i].id.clone();
+        let members = order
+            .into_iter()
+            .map(|i| {
+                let c = &self.characters[i];
+                let parent = |parent: Option<CharacterId>| {
+                    parent.filter(|p| shown.contains(&p.0)).map(|p| id_of(p.0))
+                };
+                PreviewMember {
+                    id: c.id.clone(),
+                    name: c.name.clone(),
+                    female: c.sex == Sex::Female,
+                    generation: c.generation,
+                    born: c.birth.year,
+                    died: c.death.map(|death| death.year),
+                    father: parent(c.father),
+                    mother: parent(c.mother),
+                    spouses: spouses[i]
+                        .iter()
+                        .filter(|s| shown.contains(s))
+                        .map(|&s| id_of(s))
+                        .collect(),
+                    married_in: married_in.contains(&i),
+                }
+            })
+            .collect();
+        Preview {
+            dynasty_id: dynasty_id.to_string(),
+            generations,
+            first_year,
+            last_year,
+            members,
+        }
+    }
+}
@@ -0,0 +1,315 @@
+//! Finding references to characters, dynasties and houses nobody defines.
+//!
+//! The game drops a spouse, parent, employer or guardian it cannot find with
+//! no more than a line in `error.log`, and a character of a dynasty it lacks
+//! loses their dynasty. [`Simulation::dangling_references`] checks the files
+//! a run is about to write, and [`crate::lint_output`] those of an output
+//! directory, flagging every reference to an ID that neither they nor, for
+//! the latter, the game or other history and dynasty files define, such as
+//! those of a mod or an imported history, at the file and line it is on.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use paradox_script::{Block, Scalar, Value};
+use serde::Serialize;
+
+use crate::definitions::DYNASTIES_FILE;
+use crate::export::HISTORY_FILE;
+use crate::houses::HOUSES_FILE;
+use crate::ids::{other_definitions, IdKind};
+use crate::incremental::ShardCache;
+use crate::simulation::Simulation;
+
+/// Prefix of a character scope, such as `character:lineofx1`.
+const CHARACTER_SCOPE: &str = "character:";
+
+/// A reference to an ID no file read defines.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DanglingReference {
+    /// The file it is in, such as `family_history.txt`, and the 1-based
+    /// line.
+    pub file: String,
+    pub line: usize,
+    pub kind: IdKind,
+    pub id: String,
+    pub message: String,
+}
+
+impl Simulation {
+    /// The dangling references of the history and definitions
+    /// [`Simulation::write_history`] would write, in file and line order.
+    pub fn dangling_references(&self) -> Vec<DanglingReference> {
+        output_references(&[
+            (HISTORY_FILE, self.history()),
+            (DYNASTIES_FILE, self.dynasty_definitions()),
+            (HOUSES_FILE, self.house_definitions()),
+        ])
+    }
+}
+
+impl ShardCache {
+    /// The dangling references of the history and definitions
+    /// [`ShardCache::write_history`] would write, in file and line order.
+    pub fn dangling_references(&self) -> Vec<DanglingReference> {
+        let (dynasties, houses) = self.definitions();
+        output_references(&[
+            (HISTORY_FILE, self.history()),
+            (DYNASTIES_FILE, dynasties.to_string()),
+            (HOUSES_FILE, houses.to_string()),
+        ])
+    }
+}
+
+/// The dangling references of the output files `files`, by name, which
+/// define everything there is.
+fn output_references(files: &[(&'static str, String)]) -> Vec<DanglingReference> {
+    let parsed: Vec<(&str, Block)> = files
+        .iter()
+        .filter_map(|(name, text)| {
+            let text = text.strip_prefix(paradox_script::BOM).unwrap_or(text);
+            Some((*name, paradox_script::parse(text).ok()?))
+        })
+        .collect();
+    let files: Vec<(&str, &Block)> = parsed.iter().map(|(name, file)| (*name, file)).collect();
+    let defined = Defined::of_output(&files);
+    dangling(&files, |kind, id| defined.contains(kind, id))
+}
+
+/// The IDs of each kind some file defines.
+#[derive(Default)]
+pub(crate) struct Defined {
+    ids: HashSet<(IdKind, String)>,
+}
+
+impl Defined {
+    /// What the output files `files`, by name, define.
+    pub(crate) fn of_output(files: &[(&str, &Block)]) -> Self {
+        let mut defined = Self::default();
+        for (name, file) in files {
+            let Some(kind) = IdKind::of_file(name) else {
+                continue;
+            };
+            for entry in file.entries() {
+                if entry.value.as_block().is_some() {
+                    defined.ids.insert((kind, entry.key.text().into_owned()));
+                }
+            }
+        }
+        defined
+    }
+
+    /// Count what the history and dynasty files `others`, and those in the
+    /// directories among them, define as defined too.
+    pub(crate) fn add_others(&mut self, others: &[PathBuf]) {
+        for definition in other_definitions(others) {
+            self.ids.insert((definition.kind, definition.id));
+        }
+    }
+
+    pub(crate) fn contains(&self, kind: IdKind, id: &str) -> bool {
+        self.ids.contains(&(kind, id.to_string()))
+    }
+}
+
+/// The references of the output files `files`, by name, to IDs `defined`
+/// does not know, in file and line order. Character histories, house
+/// definitions and title histories refer to IDs; other files are skipped.
+pub(crate) fn dangling(
+    files: &[(&str, &Block)],
+    defined: impl Fn(IdKind, &str) -> bool,
+) -> Vec<DanglingReference> {
+    let mut found = Vec::new();
+    for &(name, file) in files {
+        let owner = match name {
+            HISTORY_FILE => "character",
+            HOUSES_FILE => "house",
+            DYNASTIES_FILE => continue,
+            _ => "title",
+        };
+        for entry in file.entries() {
+            let Some(block) = entry.value.as_block() else {
+                continue;
+            };
+            let mut references = Vec::new();
+            if owner == "house" {
+                if let Some(dynasty) = block.get("dynasty").and_then(Value::as_scalar) {
+                    references.push((entry.line, IdKind::Dynasty, "dynasty", dynasty));
+                }
+            } else {
+                collect(block, owner == "title", &mut references);
+            }
+            for (line, kind, relation, value) in references {
+                let id = value.text();
+                let id = id.strip_prefix(CHARACTER_SCOPE).unwrap_or(&id);
+                if id == "0" || defined(kind, id) {
+                    continue;
+                }
+                found.push(DanglingReference {
+                    file: name.to_string(),
+                    line,
+                    kind,
+                    id: id.to_string(),
+                    message: format!(
+                        "{owner} '{}' has undefined {relation} '{id}'",
+                        entry.key.text()
+                    ),
+                });
+            }
+        }
+    }
+    found.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    found
+}
+
+/// The references of the entries of `block` and the blocks in it, with
+/// their lines: those of a title's history if `title`, else a character's.
+fn collect<'a>(
+    block: &'a Block,
+    title: bool,
+    references: &mut Vec<(usize, IdKind, &'static str, &'a Scalar)>,
+) {
+    for entry in block.entries() {
+        let value = match &entry.value {
+            Value::Scalar(value) => value,
+            Value::Block(block) | Value::Tagged { block, .. } => {
+                collect(block, title, references);
+                continue;
+            }
+        };
+        let key = entry.key.text();
+        let reference = if title {
+            (key == "holder").then_some((IdKind::Character, "holder"))
+        } else {
+            relation(&key)
+        };
+        let scoped = value.text().starts_with(CHARACTER_SCOPE);
+        if let Some((kind, relation)) =
+            reference.or(scoped.then_some((IdKind::Character, "character")))
+        {
+            references.push((entry.line, kind, relation, value));
+        }
+    }
+}
+
+/// The kind of ID a character history entry keyed `key` refers to, and what
+/// the character it names is to them.
+fn relation(key: &str) -> Option<(IdKind, &'static str)> {
+    Some(match key {
+        "father" | "set_father" => (IdKind::Character, "father"),
+        "mother" | "set_mother" => (IdKind::Character, "mother"),
+        "add_spouse" | "add_matrilineal_spouse" | "remove_spouse" => (IdKind::Character, "spouse"),
+        "add_concubine" => (IdKind::Character, "concubine"),
+        "employer" | "set_employer" => (IdKind::Character, "employer"),
+        "set_relation_guardian" => (IdKind::Character, "guardian"),
+        "parent" => (IdKind::Character, "parent"),
+        "dynasty" => (IdKind::Dynasty, "dynasty"),
+        "dynasty_house" => (IdKind::House, "house"),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Where, of what kind and how the dangling references of the history
+    /// `history` and house definitions `houses` are reported.
+    fn references(history: &str, houses: &str) -> Vec<(String, usize, IdKind, String)> {
+        output_references(&[
+            (HISTORY_FILE, history.to_string()),
+            (HOUSES_FILE, houses.to_string()),
+            (
+                DYNASTIES_FILE,
+                "dynasty_a = { name = dynn_a }\n".to_string(),
+            ),
+        ])
+        .into_iter()
+        .map(|reference| {
+            (
+                reference.file,
+                reference.line,
+                reference.kind,
+                reference.message,
+            )
+        })
+        .collect()
+    }
+
+    #[test]
+    fn references_to_ids_nobody_defines_are_flagged() {
+        let history = "\
+a1 = {
+\tdynasty = dynasty_a
+\tfather = a9
+\t6020.1.1 = { add_spouse = b1 }
+\t6021.1.1 = { effect = { set_relation_guardian = character:g1 } }
+}
+b1 = {
+\tdynasty = dynasty_b
+\tdynasty_house = house_b
+\tmother = 0
+\t6030.1.1 = { employer = a1 }
+}
+";
+        let houses = "house_a = { dynasty = dynasty_a }\nhouse_c = { dynasty = dynasty_c }\n";
+        assert_eq!(
+            references(history, houses),
+            [
+                (
+                    HOUSES_FILE.to_string(),
+                    2,
+                    IdKind::Dynasty,
+                    "house 'house_c' has undefined dynasty 'dynasty_c'".to_string()
+                ),
+                (
+                    HISTORY_FILE.to_string(),
+                    3,
+                    IdKind::Character,
+                    "character 'a1' has undefined father 'a9'".to_string()
+                ),
+                (
+                    HISTORY_FILE.to_string(),
+                    5,
+                    IdKind::Character,
+                    "character 'a1' has undefined guardian 'g1'".to_string()
+                ),
+                (
+                    HISTORY_FILE.to_string(),
+                    8,
+                    IdKind::Dynasty,
+                    "character 'b1' has undefined dynasty 'dynasty_b'".to_string()
+                ),
+                (
+                    HISTORY_FILE.to_string(),
+                    9,
+                    IdKind::House,
+                    "character 'b1' has undefined house 'house_b'".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn ids_other_files_define_are_not_dangling() {
+        let block = |text: &str| paradox_script::parse(text).unwrap();
+        let history = block("a1 = { father = a9 add_spouse = b1 }\nb1 = { }\n");
+        let titles =
+            block("k_gondor = { 6000.1.1 = { holder = a1 } 6050.1.1 = { holder = x1 } }\n");
+        let files = [(HISTORY_FILE, &history), ("title_history.txt", &titles)];
+        let defined = Defined::of_output(&files);
+        let found = dangling(&files, |kind, id| {
+            defined.contains(kind, id) || (kind, id) == (IdKind::Character, "a9")
+        });
+        let ids: Vec<(&str, &str)> = found
+            .iter()
+            .map(|reference| (reference.file.as_str(), reference.id.as_str()))
+            .collect();
+        assert_eq!(ids, [("title_history.txt", "x1")]);
+        assert_eq!(
+            found[0].message,
+            "title 'k_gondor' has undefined holder 'x1'"
+        );
+    }
+}
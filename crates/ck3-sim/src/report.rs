@@ -0,0 +1,375 @@
+//! Exporting a generation as a self-contained HTML report.
+//!
+//! Collaborators without the app can still open a web page. [`output_report`]
+//! reads an output directory, whichever engine wrote it, and gives a single
+//! HTML file with its style, script and data inline: the statistics of the
+//! run, a list of every character that can be searched by name, ID or
+//! dynasty, with each character's family a click away, and the tree of each
+//! dynasty, members under the first parent of theirs in the dynasty. Nothing
+//! is fetched, so the file works offline and as an email attachment.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use paradox_script::Date;
+
+use crate::error::SimError;
+use crate::export::{history_seed, HISTORY_FILE};
+use crate::localization::{dynasty_key, house_key, read_names};
+use crate::people::{read_house_dynasties, read_people, Person};
+
+const STYLE: &str = include_str!("report/report.css");
+const SCRIPT: &str = include_str!("report/report.js");
+/// Dynasties and causes of death listed in the statistics.
+const TOP: usize = 10;
+
+/// A character as the report's script reads it: ID, name, whether female,
+/// then the dynasty, father and mother as indices, -1 for none, birth, death,
+/// culture, religion, space-separated traits, and spouses as indices.
+type Row<'a> = (
+    &'a str,
+    &'a str,
+    u8,
+    isize,
+    isize,
+    isize,
+    String,
+    String,
+    &'a str,
+    &'a str,
+    String,
+    Vec<usize>,
+);
+
+/// The HTML report of the history of the output directory `dir`.
+pub fn output_report(dir: &Path) -> Result<String, SimError> {
+    let people = read_people(dir)?;
+    let names = read_names(dir);
+    let house_dynasties = read_house_dynasties(dir);
+    let seed = fs::read(dir.join(HISTORY_FILE))
+        .ok()
+        .and_then(|history| history_seed(&String::from_utf8_lossy(&history)));
+
+    let index: HashMap<&str, usize> = people
+        .iter()
+        .enumerate()
+        .map(|(i, person)| (person.id.as_str(), i))
+        .collect();
+    let mut dynasties: Vec<(&str, Option<&String>)> = Vec::new();
+    let mut dynasty_index: HashMap<&str, usize> = HashMap::new();
+    let mut members: Vec<usize> = Vec::new();
+    let lineages: Vec<isize> = people
+        .iter()
+        .map(|person| {
+            let Some(lineage) = person.lineage(&house_dynasties) else {
+                return -1;
+            };
+            let i = *dynasty_index.entry(lineage).or_insert_with(|| {
+                // A house standing for its dynasty is named as a house.
+                let name = names
+                    .get(&dynasty_key(lineage))
+                    .or_else(|| names.get(&house_key(lineage)));
+                dynasties.push((lineage, name));
+                members.push(0);
+                dynasties.len() - 1
+            });
+            members[i] += 1;
+            i as isize
+        })
+        .collect();
+    let position = |id: &Option<String>| {
+        id.as_deref()
+            .and_then(|id| index.get(id))
+            .map_or(-1, |&i| i as isize)
+    };
+    let date = |date: Option<Date>| date.map_or(String::new(), |date| date.to_string());
+    let mut rows: Vec<Row> = people
+        .iter()
+        .zip(&lineages)
+        .map(|(person, &lineage)| {
+            let mut spouses: Vec<usize> = Vec::new();
+            for (_, spouse) in &person.marriages {
+                if let Some(&spouse) = index.get(spouse.as_str()) {
+                    if !spouses.contains(&spouse) {
+                        spouses.push(spouse);
+                    }
+                }
+            }
+            (
+                person.id.as_str(),
+                person.name.as_str(),
+                person.female.into(),
+                lineage,
+                position(&person.father),
+                position(&person.mother),
+                date(person.birth),
+                date(person.death),
+                person.culture.as_deref().unwrap_or_default(),
+                person.religion.as_deref().unwrap_or_default(),
+                person.traits.join(" "),
+                spouses,
+            )
+        })
+        .collect();
+    // A marriage recorded by one side is listed on both.
+    for i in 0..rows.len() {
+        for spouse in rows[i].11.clone() {
+            if !rows[spouse].11.contains(&i) {
+                rows[spouse].11.push(i);
+            }
+        }
+    }
+    let dynasty_rows: Vec<(&str, &str)> = dynasties
+        .iter()
+        .map(|(id, name)| (*id, name.map_or("", String::as_str)))
+        .collect();
+    let data = serde_json::json!({ "dynasties": dynasty_rows, "people": rows }).to_string();
+
+    let title = match seed {
+        Some(seed) => format!("Character history, seed {seed}"),
+        None => "Character history".to_string(),
+    };
+    let mut out = String::new();
+    let mut line = |text: &str| {
+        out.push_str(text);
+        out.push('\n');
+    };
+    line("<!DOCTYPE html>");
+    line("<html lang=\"en\">");
+    line("<head>");
+    line("<meta charset=\"utf-8\">");
+    line(&format!("<title>{}</title>", escape(&title)));
+    line(&format!("<style>\n{STYLE}</style>"));
+    line("</head>");
+    line("<body>");
+    line(&format!("<h1>{}</h1>", escape(&title)));
+    line(&statistics(&people, &dynasties, &members));
+    line("<h2>Characters</h2>");
+    line("<div class=\"controls\">");
+    line("<input id=\"search\" type=\"search\" placeholder=\"Name, ID or dynasty\" size=\"30\">");
+    line("<button id=\"previous\">Previous</button><button id=\"next\">Next</button>");
+    line("<span id=\"count\" class=\"muted\"></span>");
+    line("</div>");
+    line("<div id=\"details\"></div>");
+    line("<table><thead><tr>");
+    line("<th>Name</th><th>Dynasty</th><th>Culture</th><th>Religion</th><th>Life</th>");
+    line("</tr></thead><tbody id=\"rows\"></tbody></table>");
+    line("<h2>Dynasty trees</h2>");
+    line("<div class=\"controls\"><select id=\"dynasty\"></select></div>");
+    line("<div id=\"tree\" class=\"tree\"></div>");
+    // `<` only appears in the JSON's strings, where `\u003c` reads the same,
+    // so no name can close the script element.
+    line(&format!(
+        "<script id=\"data\" type=\"application/json\">{}</script>",
+        data.replace('<', "\\u003c")
+    ));
+    line(&format!("<script>\n{SCRIPT}</script>"));
+    line("</body>");
+    line("</html>");
+    Ok(out)
+}
+
+/// The statistics section of the report.
+fn statistics(
+    people: &[Person],
+    dynasties: &[(&str, Option<&String>)],
+    members: &[usize],
+) -> String {
+    let women = people.iter().filter(|person| person.female).count();
+    let living = people
+        .iter()
+        .filter(|person| person.death.is_none())
+        .count();
+    let houses: HashSet<&str> = people
+        .iter()
+        .filter_map(|person| person.house.as_deref())
+        .collect();
+    let lifespans: Vec<i32> = people
+        .iter()
+        .filter_map(|person| Some(person.death?.year - person.birth?.year))
+        .collect();
+    let births = people.iter().filter_map(|person| person.birth);
+    let first = births.clone().map(|date| date.year).min();
+    let last = births.map(|date| date.year).max();
+
+    let mut causes: HashMap<&str, usize> = HashMap::new();
+    for person in people {
+        if let Some(reason) = person.death_reason.as_deref() {
+            *causes.entry(reason).or_default() += 1;
+        }
+    }
+    let mut causes: Vec<(&str, usize)> = causes.into_iter().collect();
+    causes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let mut largest: Vec<usize> = (0..dynasties.len()).collect();
+    largest.sort_by(|&a, &b| members[b].cmp(&members[a]).then(a.cmp(&b)));
+    let mut centuries: BTreeMap<i32, usize> = BTreeMap::new();
+    for person in people {
+        if let Some(birth) = person.birth {
+            *centuries
+                .entry(birth.year.div_euclid(100) * 100)
+                .or_default() += 1;
+        }
+    }
+
+    let mut out = String::from("<h2>Statistics</h2>\n<div class=\"stats\">\n<div><table>\n");
+    let mut row = |label: &str, value: String| {
+        writeln!(
+            out,
+            "<tr><th>{label}</th><td class=\"number\">{}</td></tr>",
+            escape(&value)
+        )
+        .expect("writing to a String cannot fail");
+    };
+    row("Characters", people.len().to_string());
+    row("Women", women.to_string());
+    row("Men", (people.len() - women).to_string());
+    row("Without a death date", living.to_string());
+    row("Dynasties", dynasties.len().to_string());
+    row("Houses", houses.len().to_string());
+    if let (Some(first), Some(last)) = (first, last) {
+        row("Births", format!("{first} – {last}"));
+    }
+    if !lifespans.is_empty() {
+        let average =
+            lifespans.iter().map(|&years| f64::from(years)).sum::<f64>() / lifespans.len() as f64;
+        row("Average lifespan", format!("{average:.1} years"));
+    }
+    out.push_str("</table></div>\n");
+
+    let mut table = |heading: &str, rows: Vec<(String, usize)>| {
+        if rows.is_empty() {
+            return;
+        }
+        writeln!(
+            out,
+            "<div><table><thead><tr><th>{heading}</th><th></th></tr></thead><tbody>"
+        )
+        .expect("writing to a String cannot fail");
+        for (label, count) in rows {
+            writeln!(
+                out,
+                "<tr><td>{}</td><td class=\"number\">{count}</td></tr>",
+                escape(&label)
+            )
+            .expect("writing to a String cannot fail");
+        }
+        out.push_str("</tbody></table></div>\n");
+    };
+    table(
+        "Largest dynasties",
+        largest
+            .iter()
+            .take(TOP)
+            .map(|&i| {
+                let (id, name) = dynasties[i];
+                (name.map_or(id, String::as_str).to_string(), members[i])
+            })
+            .collect(),
+    );
+    table(
+        "Causes of death",
+        causes
+            .iter()
+            .take(TOP)
+            .map(|&(reason, count)| (reason.trim_start_matches("death_").replace('_', " "), count))
+            .collect(),
+    );
+    table(
+        "Births by century",
+        centuries
+            .into_iter()
+            .map(|(century, count)| (format!("{century}s"), count))
+            .collect(),
+    );
+    out.push_str("</div>");
+    out
+}
+
+/// `text` escaped for HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::testing;
+
+    /// The report of the output directory `dir`, removing it.
+    fn report(dir: &Path) -> String {
+        let report = output_report(dir).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+        report
+    }
+
+    /// The part of `report` from `start` up to `end`.
+    fn between<'a>(report: &'a str, start: &str, end: &str) -> &'a str {
+        let from = report.find(start).unwrap();
+        &report[from..from + report[from..].find(end).unwrap()]
+    }
+
+    #[test]
+    fn the_report_has_the_statistics_and_data_of_the_history() {
+        let report = report(&testing::family("report"));
+        assert!(report.starts_with(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Character history, seed 7</title>\n"
+        ));
+        assert_eq!(
+            between(&report, "<h2>Statistics", "<h2>Characters"),
+            "\
+<h2>Statistics</h2>
+<div class=\"stats\">
+<div><table>
+<tr><th>Characters</th><td class=\"number\">3</td></tr>
+<tr><th>Women</th><td class=\"number\">1</td></tr>
+<tr><th>Men</th><td class=\"number\">2</td></tr>
+<tr><th>Without a death date</th><td class=\"number\">2</td></tr>
+<tr><th>Dynasties</th><td class=\"number\">2</td></tr>
+<tr><th>Houses</th><td class=\"number\">1</td></tr>
+<tr><th>Births</th><td class=\"number\">6000 – 6026</td></tr>
+<tr><th>Average lifespan</th><td class=\"number\">60.0 years</td></tr>
+</table></div>
+<div><table><thead><tr><th>Largest dynasties</th><th></th></tr></thead><tbody>
+<tr><td>Alda</td><td class=\"number\">2</td></tr>
+<tr><td>Bregol</td><td class=\"number\">1</td></tr>
+</tbody></table></div>
+<div><table><thead><tr><th>Causes of death</th><th></th></tr></thead><tbody>
+<tr><td>battle</td><td class=\"number\">1</td></tr>
+</tbody></table></div>
+<div><table><thead><tr><th>Births by century</th><th></th></tr></thead><tbody>
+<tr><td>6000s</td><td class=\"number\">3</td></tr>
+</tbody></table></div>
+</div>
+"
+        );
+        assert_eq!(
+            between(&report, "<script id=\"data\"", "\n"),
+            "<script id=\"data\" type=\"application/json\">{\"dynasties\":[[\"dynasty_a\",\
+             \"Alda\"],[\"dynasty_b\",\"Bregol\"]],\"people\":[[\"lineofa1\",\"Aldor\",0,0,-1,\
+             -1,\"6000.1.1\",\"6060.1.1\",\"gondorian\",\"faith_gondor\",\"brave\",[1]],\
+             [\"lineofb1\",\"Beren\",1,1,-1,-1,\"6002.1.1\",\"\",\"rohirric\",\"faith_rohan\",\
+             \"\",[0]],[\"lineofa2\",\"Anborn\",0,0,0,1,\"6026.5.1\",\"\",\"gondorian\",\
+             \"faith_gondor\",\"shy\",[]]]}</script>"
+        );
+        assert!(!report.contains("src=") && !report.contains("href="));
+    }
+
+    #[test]
+    fn names_cannot_break_out_of_the_page() {
+        let report = report(&testing::output(
+            "report-escaping",
+            "a1 = { name = \"</script><b>\" dynasty = d_a }\n",
+        ));
+        assert!(report.contains("[\"a1\",\"\\u003c/script>\\u003cb>\""));
+        assert!(report.contains("<tr><td>d_a</td><td class=\"number\">1</td></tr>"));
+        assert_eq!(report.matches("</script>").count(), 2);
+    }
+}
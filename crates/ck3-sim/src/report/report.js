@@ -0,0 +1,160 @@
+"use strict";
+(() => {
+  const data = JSON.parse(document.getElementById("data").textContent);
+  // A character is [id, name, female, dynasty, father, mother, birth, death,
+  // culture, religion, traits, spouses], with dynasty, parents and spouses as
+  // indices, -1 for none.
+  const people = data.people.map((p) => ({
+    id: p[0], name: p[1], female: p[2] === 1, dynasty: p[3], father: p[4],
+    mother: p[5], birth: p[6], death: p[7], culture: p[8], religion: p[9],
+    traits: p[10], spouses: p[11], children: [],
+  }));
+  people.forEach((person, i) => {
+    for (const parent of [person.father, person.mother]) {
+      if (parent >= 0) people[parent].children.push(i);
+    }
+  });
+  const dynastyName = (i) => (i >= 0 ? data.dynasties[i][1] || data.dynasties[i][0] : "lowborn");
+  const el = (tag, text) => {
+    const node = document.createElement(tag);
+    if (text !== undefined) node.textContent = text;
+    return node;
+  };
+  const link = (i) => {
+    const a = el("a", people[i].name);
+    a.title = people[i].id;
+    a.onclick = () => show(i);
+    return a;
+  };
+  const years = (person) => `${person.birth || "?"} – ${person.death || ""}`;
+
+  // Details of a character.
+  const details = document.getElementById("details");
+  const show = (i) => {
+    const person = people[i];
+    details.replaceChildren();
+    details.append(el("h3", `${person.name} ${person.female ? "♀" : "♂"}`));
+    const list = el("dl");
+    const row = (label, value) => {
+      const dd = el("dd");
+      if (Array.isArray(value)) {
+        value.forEach((j, k) => {
+          if (k > 0) dd.append(", ");
+          dd.append(link(j));
+        });
+        if (value.length === 0) dd.textContent = "—";
+      } else {
+        dd.textContent = value || "—";
+      }
+      list.append(el("dt", label), dd);
+    };
+    row("ID", person.id);
+    row("Dynasty", dynastyName(person.dynasty));
+    row("Born", person.birth);
+    row("Died", person.death);
+    row("Culture", person.culture);
+    row("Religion", person.religion);
+    row("Traits", person.traits.split(" ").join(", "));
+    row("Parents", [person.father, person.mother].filter((j) => j >= 0));
+    row("Spouses", person.spouses);
+    row("Children", person.children);
+    details.append(list);
+    details.scrollIntoView({ block: "nearest" });
+  };
+
+  // The searchable character list.
+  const PAGE = 100;
+  const search = document.getElementById("search");
+  const rows = document.getElementById("rows");
+  const count = document.getElementById("count");
+  let found = [];
+  let offset = 0;
+  const render = () => {
+    rows.replaceChildren();
+    for (const i of found.slice(offset, offset + PAGE)) {
+      const person = people[i];
+      const tr = el("tr");
+      const name = el("td");
+      name.append(link(i));
+      tr.append(name, el("td", dynastyName(person.dynasty)), el("td", person.culture),
+        el("td", person.religion), el("td", years(person)));
+      rows.append(tr);
+    }
+    count.textContent = found.length === 0
+      ? "No characters match."
+      : `${offset + 1}–${Math.min(offset + PAGE, found.length)} of ${found.length}`;
+    document.getElementById("previous").disabled = offset === 0;
+    document.getElementById("next").disabled = offset + PAGE >= found.length;
+  };
+  const filter = () => {
+    const text = search.value.trim().toLowerCase();
+    found = [];
+    people.forEach((person, i) => {
+      if (!text || person.name.toLowerCase().includes(text) || person.id === text
+        || dynastyName(person.dynasty).toLowerCase().includes(text)) {
+        found.push(i);
+      }
+    });
+    offset = 0;
+    render();
+  };
+  search.oninput = filter;
+  document.getElementById("previous").onclick = () => { offset = Math.max(0, offset - PAGE); render(); };
+  document.getElementById("next").onclick = () => { offset += PAGE; render(); };
+  filter();
+
+  // The tree of a dynasty: its founders, then each member under the first of
+  // their parents who is a member too.
+  const select = document.getElementById("dynasty");
+  const tree = document.getElementById("tree");
+  data.dynasties.forEach((dynasty, i) => {
+    const option = el("option", dynastyName(i));
+    option.value = i;
+    select.append(option);
+  });
+  const member = (i, dynasty) => i >= 0 && people[i].dynasty === dynasty;
+  const branch = (i, dynasty, depth) => {
+    const person = people[i];
+    const li = el("li");
+    const label = el("span");
+    label.append(link(i), ` (${years(person)})`);
+    if (person.spouses.length > 0) {
+      const spouses = el("span", " ⚭ ");
+      spouses.className = "spouse";
+      person.spouses.forEach((j, k) => {
+        if (k > 0) spouses.append(", ");
+        spouses.append(link(j));
+      });
+      label.append(spouses);
+    }
+    const heirs = person.children.filter((j) => member(j, dynasty)
+      && (member(people[j].father, dynasty) ? people[j].father : people[j].mother) === i);
+    if (heirs.length === 0) {
+      label.className = "leaf";
+      li.append(label);
+      return li;
+    }
+    const fold = el("details");
+    fold.open = depth < 3;
+    const summary = el("summary");
+    summary.append(label);
+    const ul = el("ul");
+    heirs.forEach((j) => ul.append(branch(j, dynasty, depth + 1)));
+    fold.append(summary, ul);
+    li.append(fold);
+    return li;
+  };
+  const drawTree = () => {
+    const dynasty = Number(select.value);
+    const ul = el("ul");
+    people.forEach((person, i) => {
+      if (person.dynasty === dynasty && !member(person.father, dynasty)
+        && !member(person.mother, dynasty)) {
+        ul.append(branch(i, dynasty, 0));
+      }
+    });
+    tree.replaceChildren(ul);
+  };
+  select.onchange = drawTree;
+  if (data.dynasties.length > 0) drawTree();
+})();
@@ -0,0 +1,191 @@
+//! Exporting portrait DNA as the ruler designer's persistent DNA strings.
+//!
+//! The ruler designer copies and pastes a face as a persistent DNA string:
+//! the base64 of every gene the game defines, four bytes each, in the order
+//! of the game's gene definitions. A colour gene is its dominant and
+//! recessive palette points; a morph or accessory gene is, for each of its
+//! two values, the index of its template and the value. Since the layout
+//! depends on the game's genes, [`ruler_designer_dna`] reads
+//! `common/genes` from an installation or mod, then gives the string of each
+//! character asked for from their entry in the output's DNA file, whichever
+//! engine wrote it. Genes the entry leaves out, such as clothes, get their
+//! first template at the neutral value.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use paradox_script::{Block, Scalar, Value};
+
+use crate::dna::DNA_FILE;
+use crate::error::SimError;
+use crate::game_data::common_dir;
+use crate::people::{read, read_people};
+
+/// Directory of the gene definitions, relative to the `common` directory.
+const GENES_DIR: &str = "genes";
+/// Categories of gene definitions, in the order the string holds them.
+const CATEGORIES: [&str; 3] = ["color_genes", "morph_genes", "accessory_genes"];
+/// Value of a morph gene the entry leaves out.
+const NEUTRAL: u8 = 128;
+
+/// A gene the game defines.
+struct Gene {
+    name: String,
+    colour: bool,
+    /// Index of each template by name.
+    templates: HashMap<String, u8>,
+    /// The template a gene the entry leaves out gets.
+    first: u8,
+}
+
+/// The persistent DNA string of each of the characters `ids` of the output
+/// directory `dir`, in order, laid out by the genes of the installation or
+/// mod in `game_dir`.
+pub fn ruler_designer_dna(
+    dir: &Path,
+    game_dir: &Path,
+    ids: &[String],
+) -> Result<Vec<(String, String)>, SimError> {
+    let genes = read_genes(&common_dir(game_dir).join(GENES_DIR))?;
+    let people = read_people(dir)?;
+    let entries = read(&dir.join(DNA_FILE)).unwrap_or_default();
+    ids.iter()
+        .map(|id| {
+            let no_dna = || SimError::History {
+                message: format!("{id} has no portrait DNA in {DNA_FILE}"),
+            };
+            let person = people
+                .iter()
+                .find(|person| &person.id == id)
+                .ok_or_else(|| SimError::History {
+                    message: format!("{id} is not a character of the history"),
+                })?;
+            let entry = person.dna.as_deref().ok_or_else(no_dna)?;
+            let portrait = entries
+                .get(entry)
+                .and_then(Value::as_block)
+                .and_then(|entry| entry.get("portrait_info"))
+                .and_then(Value::as_block)
+                .and_then(|portrait| portrait.get("genes"))
+                .and_then(Value::as_block)
+                .ok_or_else(no_dna)?;
+            Ok((id.clone(), encode(&genes, portrait)))
+        })
+        .collect()
+}
+
+/// The genes defined by the files of `dir`, read in file name order as the
+/// game does.
+fn read_genes(dir: &Path) -> Result<Vec<Gene>, SimError> {
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .map_err(|source| SimError::Io {
+            path: dir.to_path_buf(),
+            source,
+        })?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    files.sort();
+    let mut categories: [Vec<(Option<i64>, Gene)>; CATEGORIES.len()] = Default::default();
+    for file in files.iter().filter_map(|path| read(path)) {
+        for (category, genes) in CATEGORIES.iter().zip(&mut categories) {
+            for definitions in file.get_all(category).filter_map(Value::as_block) {
+                genes.extend(definitions.entries().filter_map(|entry| {
+                    let gene = gene(
+                        entry.key.text().into_owned(),
+                        *category == "color_genes",
+                        entry.value.as_block()?,
+                    );
+                    Some((integer(entry.value.as_block()?, "index"), gene))
+                }));
+            }
+        }
+    }
+    if categories.iter().all(Vec::is_empty) {
+        return Err(SimError::Game {
+            message: format!("{} defines no genes", dir.display()),
+        });
+    }
+    // Genes are laid out by their index where the game gives one, and
+    // otherwise in the order they are defined.
+    Ok(categories
+        .into_iter()
+        .flat_map(|mut genes| {
+            genes.sort_by_key(|(index, _)| index.unwrap_or(i64::MAX));
+            genes.into_iter().map(|(_, gene)| gene)
+        })
+        .collect())
+}
+
+fn gene(name: String, colour: bool, block: &Block) -> Gene {
+    let templates: HashMap<String, u8> = block
+        .entries()
+        .filter_map(|entry| {
+            let index = integer(entry.value.as_block()?, "index")?;
+            Some((entry.key.text().into_owned(), u8::try_from(index).ok()?))
+        })
+        .collect();
+    let first = templates.values().copied().min().unwrap_or(0);
+    Gene {
+        name,
+        colour,
+        templates,
+        first,
+    }
+}
+
+/// The persistent DNA string of the `genes` block of a DNA entry.
+fn encode(genes: &[Gene], portrait: &Block) -> String {
+    let mut bytes = Vec::with_capacity(genes.len() * 4);
+    for gene in genes {
+        let values: Vec<&Scalar> = portrait
+            .get(&gene.name)
+            .and_then(Value::as_block)
+            .map(|values| values.values().filter_map(Value::as_scalar).collect())
+            .unwrap_or_default();
+        let byte = |scalar: &Scalar| match scalar.value() {
+            Scalar::Integer(value) => (*value).clamp(0, 255) as u8,
+            other => gene
+                .templates
+                .get(other.text().as_ref())
+                .copied()
+                .unwrap_or(gene.first),
+        };
+        match values.as_slice() {
+            [_, _, _, _] => bytes.extend(values.iter().map(|&value| byte(value))),
+            _ if gene.colour => bytes.extend([0; 4]),
+            _ => bytes.extend([gene.first, NEUTRAL, gene.first, NEUTRAL]),
+        }
+    }
+    base64(&bytes)
+}
+
+/// The integer under `key`.
+fn integer(block: &Block, key: &str) -> Option<i64> {
+    match block.get(key)?.as_scalar()?.value() {
+        Scalar::Integer(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// `bytes` in standard base64, padded.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | u32::from(byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,1016 @@
+//! The yearly tick loop.
+//!
+//! Each simulated year ages everyone, arranges marriages, rolls births and
+//! bastards, rolls deaths, sends dynasties at war to battle and steps in for
+//! dynasties flagged `forceDynastyAlive` that can no longer continue on
+//! their own. Once the period is over, the survivors are aged on until each
+//! has a death date, since history files need one for every character.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::ControlFlow;
+
+use ck3_config::Dynasty;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::Serialize;
+
+use crate::bastardy::BastardFate;
+use crate::character::{inherit_blood, Birth, Character, CharacterId, EventKind, Sex};
+use crate::config::SimConfig;
+use crate::date::Date;
+use crate::dna::Dna;
+use crate::dynasty::{self, DynastySummary};
+use crate::genetics::inherit_genes;
+use crate::names::NameLists;
+use crate::random;
+
+/// Ages from which men and women count as able to have children, for the
+/// fertility and survival checks. Blood tiers stretch the fertility tables,
+/// not these bounds.
+pub(crate) const FERTILE_FROM: i32 = 16;
+pub(crate) const MALE_FERTILE_UNTIL: i32 = 70;
+pub(crate) const FEMALE_FERTILE_UNTIL: i32 = 45;
+/// Bound of the seeds picked for unseeded runs, which the UI has to be able
+/// to hold as JavaScript numbers.
+const RANDOM_SEEDS: u64 = 1 << 53;
+
+/// Start of every character ID, before the dynasty's prefix and a number.
+pub(crate) const ID_START: &str = "lineof";
+
+/// A run of the simulation over the configured dynasties and period.
+#[derive(Clone)]
+pub struct Simulation {
+    pub(crate) config: SimConfig,
+    pub(crate) names: NameLists,
+    /// Seed of `rng`; the same config and seed give the same history.
+    seed: u64,
+    /// Named rather than `StdRng`, whose algorithm `rand` may change, so
+    /// that a seed gives the same history on every build.
+    pub(crate) rng: ChaCha12Rng,
+    pub(crate) characters: Vec<Character>,
+    /// Position of each dynasty in the config, by ID.
+    dynasty_index: HashMap<String, usize>,
+    /// Last number used in character IDs, by ID prefix.
+    pub(crate) id_counters: HashMap<String, u32>,
+    /// Year of a couple's last child, by father and mother.
+    pub(crate) last_child_year: HashMap<(CharacterId, CharacterId), i32>,
+    /// Characters alive when the simulated period ended.
+    pub(crate) living_at_end: Vec<bool>,
+    /// Plugin hooks that failed, each once.
+    pub(crate) plugin_errors: Vec<String>,
+}
+
+/// The outcome of a run.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    pub first_year: i32,
+    pub last_year: i32,
+    pub characters: usize,
+    pub living_at_end: usize,
+    pub dynasties: Vec<DynastySummary>,
+    /// Plugin hooks that failed during the run.
+    pub plugin_errors: Vec<String>,
+}
+
+impl Simulation {
+    /// Prepare a run, seeding one male progenitor per dynasty.
+    ///
+    /// Every random decision of the run is drawn from a generator seeded
+    /// with `seed`, or with a random seed if `None`.
+    pub fn new(config: SimConfig, names: NameLists, seed: Option<u64>) -> Self {
+        let mut simulation = Self::empty(config, names, seed.unwrap_or_else(random_seed));
+        simulation.seed_progenitors();
+        simulation
+    }
+
+    /// A run without any characters yet.
+    pub(crate) fn empty(config: SimConfig, names: NameLists, seed: u64) -> Self {
+        let dynasty_index = config
+            .initialization
+            .dynasties
+            .iter()
+            .enumerate()
+            .map(|(i, dynasty)| (dynasty.dynasty_id.clone(), i))
+            .collect();
+        Self {
+            config,
+            names,
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            characters: Vec::new(),
+            dynasty_index,
+            id_counters: HashMap::new(),
+            last_child_year: HashMap::new(),
+            living_at_end: Vec::new(),
+            plugin_errors: Vec::new(),
+        }
+    }
+
+    /// The seed the run was started with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn character(&self, id: CharacterId) -> &Character {
+        &self.characters[id.0]
+    }
+
+    pub(crate) fn character_mut(&mut self, id: CharacterId) -> &mut Character {
+        &mut self.characters[id.0]
+    }
+
+    /// Every character created so far, in order of creation.
+    pub fn characters(&self) -> impl Iterator<Item = (CharacterId, &Character)> {
+        self.characters
+            .iter()
+            .enumerate()
+            .map(|(i, character)| (CharacterId(i), character))
+    }
+
+    pub(crate) fn ids(&self) -> impl Iterator<Item = CharacterId> {
+        (0..self.characters.len()).map(CharacterId)
+    }
+
+    pub(crate) fn dynasty_config(&self, id: Option<&str>) -> Option<&Dynasty> {
+        let index = *self.dynasty_index.get(id?)?;
+        Some(&self.config.initialization.dynasties[index])
+    }
+
+    /// A fresh character ID for a member of `dynasty`.
+    pub(crate) fn next_id(&mut self, dynasty: Option<&str>) -> String {
+        self.next_prefixed_id(dynasty::id_prefix(dynasty))
+    }
+
+    /// A fresh character ID starting with `lineof<prefix>`.
+    pub(crate) fn next_prefixed_id(&mut self, prefix: &str) -> String {
+        let counter = self.id_counter(prefix);
+        *counter += 1;
+        format!("{ID_START}{prefix}{counter}")
+    }
+
+    /// Make sure IDs handed out from now on come after `id`.
+    pub(crate) fn reserve_id(&mut self, id: &str) {
+        let (prefix, number) = split_id(id);
+        let counter = self.id_counter(prefix);
+        *counter = (*counter).max(number);
+    }
+
+    /// The number of the last ID handed out starting with `lineof<prefix>`,
+    /// one before the config's `initialCharID` for a prefix not used yet.
+    pub(crate) fn id_counter(&mut self, prefix: &str) -> &mut u32 {
+        let first = self.config.initialization.initial_char_id;
+        let before = u32::try_from(first.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.id_counters.entry(prefix.to_string()).or_insert(before)
+    }
+
+    /// Create a character and give it its skills and education.
+    pub(crate) fn spawn(&mut self, birth: Birth) -> CharacterId {
+        let skills = &self.config.skills_and_traits;
+        let mut character = Character::born(birth, skills, &mut self.rng);
+        character.assign_skills(skills, &mut self.rng);
+        character.assign_education(skills, &mut self.rng);
+        if self.config.initialization.portrait_genetics.is_some() {
+            character.dna = Some(Dna::random(&mut self.rng));
+        }
+        self.characters.push(character);
+        CharacterId(self.characters.len() - 1)
+    }
+
+    /// Create a character born before the period simulated, or at least
+    /// outside its marriages, with its whole upbringing already decided.
+    pub(crate) fn spawn_grown(&mut self, birth: Birth) -> CharacterId {
+        self.spawn_grown_child(birth, None, None)
+    }
+
+    /// Like [`Simulation::spawn_grown`], for a child of `father` and
+    /// `mother` not linked to them yet.
+    pub(crate) fn spawn_grown_child(
+        &mut self,
+        birth: Birth,
+        father: Option<CharacterId>,
+        mother: Option<CharacterId>,
+    ) -> CharacterId {
+        let id = self.spawn(birth);
+        let multipliers = self.trait_multipliers(id, father, mother);
+        let character = &mut self.characters[id.0];
+        character.assign_personality_traits(&self.config, &multipliers, &mut self.rng);
+        character.add_upbringing_events(&mut self.rng);
+        id
+    }
+
+    /// Seed a male progenitor for every dynasty without members yet.
+    pub(crate) fn seed_progenitors(&mut self) {
+        for i in 0..self.config.initialization.dynasties.len() {
+            let dynasty = self.config.initialization.dynasties[i].clone();
+            let founded = self
+                .characters
+                .iter()
+                .any(|c| c.dynasty.as_deref() == Some(dynasty.dynasty_id.as_str()));
+            if founded {
+                continue;
+            }
+            let id = self.next_id(Some(&dynasty.dynasty_id));
+            let name = self
+                .names
+                .random(&mut self.rng, &dynasty.culture_id, Sex::Male);
+            let progenitor = self.spawn_grown(Birth {
+                id,
+                name,
+                sex: Sex::Male,
+                birth_year: dynasty.progenitor_male_birth_year,
+                birth_date: None,
+                dynasty: Some(dynasty.dynasty_id),
+                is_house: dynasty.is_house,
+                house: None,
+                culture: dynasty.culture_id,
+                religion: dynasty.faith_id,
+                gender_law: dynasty.gender_law,
+                generation: 1,
+                birth_order: 1,
+                blood_tier: dynasty.numenor_blood_tier.unwrap_or(0),
+                fertility_modifier: 1.0,
+            });
+            self.character_mut(progenitor).is_progenitor = true;
+        }
+    }
+
+    /// Simulate every year of the configured period.
+    pub fn run(&mut self) -> Summary {
+        self.run_until(|_| ControlFlow::Continue(()))
+            .expect("a run only stops early when asked to")
+    }
+
+    /// Like [`Simulation::run`], but asking `before_year` before every year
+    /// whether to go on. Returns `None` if it stopped the run.
+    pub fn run_until(
+        &mut self,
+        mut before_year: impl FnMut(i32) -> ControlFlow<()>,
+    ) -> Option<Summary> {
+        let first_year = self.config.initialization.min_year;
+        let last_year = self.config.initialization.max_year;
+        for year in first_year..=last_year {
+            if before_year(year).is_break() {
+                return None;
+            }
+            self.tick(year);
+        }
+        self.living_at_end = self.characters.iter().map(Character::is_alive).collect();
+        self.bury_survivors(last_year);
+        self.legitimize_bastards(i32::MAX);
+        self.grow_up_minors(last_year);
+        Some(self.summary(first_year, last_year))
+    }
+
+    /// Simulate `year`.
+    pub(crate) fn tick(&mut self, year: i32) {
+        self.age_characters(year);
+        self.arrange_marriages(year);
+        self.take_consorts(year);
+        self.roll_births(year);
+        self.roll_deaths(year);
+        self.fight_wars(year);
+        self.enforce_dynasty_survival(year);
+        self.teach_dynasty_languages(year);
+        self.convert_faiths(year);
+        self.legitimize_bastards(year);
+        self.split_cadet_houses();
+    }
+
+    pub(crate) fn summary(&self, first_year: i32, last_year: i32) -> Summary {
+        let mut alliances: HashMap<&str, BTreeMap<String, usize>> = HashMap::new();
+        for character in &self.characters {
+            for event in &character.events {
+                let EventKind::Marriage { spouse, .. } = event.kind else {
+                    continue;
+                };
+                let (Some(a), Some(b)) = (
+                    character.dynasty.as_deref(),
+                    self.character(spouse).dynasty.as_deref(),
+                ) else {
+                    continue;
+                };
+                if a != b {
+                    *alliances
+                        .entry(a)
+                        .or_default()
+                        .entry(b.to_string())
+                        .or_default() += 1;
+                    *alliances
+                        .entry(b)
+                        .or_default()
+                        .entry(a.to_string())
+                        .or_default() += 1;
+                }
+            }
+        }
+        let dynasties = self
+            .config
+            .initialization
+            .dynasties
+            .iter()
+            .map(|dynasty| {
+                let members: Vec<usize> = (0..self.characters.len())
+                    .filter(|&i| {
+                        self.characters[i].dynasty.as_deref() == Some(dynasty.dynasty_id.as_str())
+                    })
+                    .collect();
+                DynastySummary {
+                    dynasty_id: dynasty.dynasty_id.clone(),
+                    members: members.len(),
+                    living_at_end: members.iter().filter(|&&i| self.living_at_end[i]).count(),
+                    adopted: members
+                        .iter()
+                        .filter(|&&i| self.characters[i].is_adopted)
+                        .count(),
+                    alliances: alliances
+                        .remove(dynasty.dynasty_id.as_str())
+                        .unwrap_or_default(),
+                    houses: members
+                        .iter()
+                        .filter_map(|&i| self.characters[i].house.as_deref())
+                        .collect::<BTreeSet<_>>()
+                        .len(),
+                }
+            })
+            .collect();
+        Summary {
+            first_year,
+            last_year,
+            characters: self.characters.len(),
+            living_at_end: self.living_at_end.iter().filter(|&&alive| alive).count(),
+            dynasties,
+            plugin_errors: self.plugin_errors.clone(),
+        }
+    }
+
+    /// Update ages and hand out the traits gained at 3 and 16.
+    fn age_characters(&mut self, year: i32) {
+        for i in 0..self.characters.len() {
+            let character = &mut self.characters[i];
+            character.event_death_reason = None;
+            if !character.is_alive() {
+                continue;
+            }
+            character.age = (year - character.birth.year).max(0);
+            let birthday = Date::new(year, character.birth.month, character.birth.day);
+            // Characters created with their upbringing already have the trait.
+            if character.age == 3 && !character.has_childhood_trait() {
+                let childhood = character.roll_childhood_trait(&mut self.rng);
+                character.add_event(birthday, EventKind::Traits(vec![childhood]));
+            }
+            if character.age == 16 && character.personality_traits.is_empty() {
+                let (father, mother) = (character.father, character.mother);
+                let multipliers = self.trait_multipliers(CharacterId(i), father, mother);
+                let character = &mut self.characters[i];
+                character.assign_personality_traits(&self.config, &multipliers, &mut self.rng);
+                let traits = character.personality_traits.clone();
+                character.add_event(birthday, EventKind::Traits(traits));
+            }
+        }
+    }
+
+    /// The chance of `id` having a child this year, before modifiers.
+    ///
+    /// Blood tiers delay the decline: the character stays at peak fertility
+    /// for the peak years of their tier's `bloodTierFertility` after 16
+    /// before following the table, shifted by the same amount and scaled
+    /// by its factor.
+    pub(crate) fn fertility_rate(&self, id: CharacterId) -> f64 {
+        let character = self.character(id);
+        let rates = &self.config.life_stages.fertility_rates;
+        let table = match character.sex {
+            Sex::Male => &rates.male,
+            Sex::Female => &rates.female,
+        };
+        let age = character.age;
+        let curve = self
+            .config
+            .life_stages
+            .blood_fertility(character.blood_tier);
+        let extra = curve.peak_years as i32;
+        if age < FERTILE_FROM {
+            return table.get(age as usize).copied().unwrap_or(0.0);
+        }
+        if age <= FERTILE_FROM + extra {
+            return table
+                .get(FERTILE_FROM as usize..)
+                .into_iter()
+                .flatten()
+                .copied()
+                .fold(0.0, f64::max);
+        }
+        let effective = ((age - extra).max(0) as usize).min(table.len().saturating_sub(1));
+        table.get(effective).copied().unwrap_or(0.0) * curve.factor
+    }
+
+    /// Whether `character` is of an age to have children.
+    pub(crate) fn is_fertile(character: &Character) -> bool {
+        let until = match character.sex {
+            Sex::Male => MALE_FERTILE_UNTIL,
+            Sex::Female => FEMALE_FERTILE_UNTIL,
+        };
+        character.is_alive() && (FERTILE_FROM..=until).contains(&character.age)
+    }
+
+    /// Living members of `dynasty` of an age to have children.
+    pub(crate) fn fertile_members(&self, dynasty: Option<&str>) -> usize {
+        self.characters
+            .iter()
+            .filter(|c| c.dynasty.as_deref() == dynasty && Self::is_fertile(c))
+            .count()
+    }
+
+    fn roll_births(&mut self, year: i32) {
+        let max_children = self.config.life_stages.maximum_number_of_children as usize;
+        for mother in self.ids().collect::<Vec<_>>() {
+            let character = self.character(mother);
+            if !character.is_alive() || character.sex != Sex::Female {
+                continue;
+            }
+            let Some(father) = character.spouse else {
+                continue;
+            };
+            if character.children.len() >= max_children {
+                continue;
+            }
+            let chance = self.fertility_rate(mother)
+                * character.fertility_mult()
+                * self.fertility_rate(father)
+                * self.character(father).fertility_mult();
+            if random::chance(&mut self.rng, chance) {
+                self.create_child(mother, father, year);
+            }
+        }
+        self.roll_bastards(year);
+    }
+
+    /// A legitimate child of a married couple, unless the mother has all the
+    /// children she may have, the couple had one too recently or the
+    /// generation cap is reached.
+    pub(crate) fn create_child(
+        &mut self,
+        mother: CharacterId,
+        father: CharacterId,
+        year: i32,
+    ) -> Option<CharacterId> {
+        let life = &self.config.life_stages;
+        let (m, f) = (self.character(mother), self.character(father));
+        if m.children.len() >= life.maximum_number_of_children as usize {
+            return None;
+        }
+        if let Some(&last) = self.last_child_year.get(&(father, mother)) {
+            if year < last + life.minimum_years_between_children as i32 {
+                return None;
+            }
+        }
+        let generation = m.generation.max(f.generation) + 1;
+        if generation > self.config.initialization.generation_max {
+            return None;
+        }
+        let due = match self.pregnancy() {
+            Some(pregnancy) => Some(self.due_date(&pregnancy, Some(mother), Some(father), year)?),
+            None => None,
+        };
+        let (m, f) = (self.character(mother), self.character(father));
+
+        // Lines short of an heir of the sex they pass through favour one.
+        let siblings: Vec<Sex> = m
+            .children
+            .iter()
+            .chain(&f.children)
+            .map(|&id| self.character(id).sex)
+            .collect();
+        let male_chance = if dynasty::patrilineal(f.gender_law) && !siblings.contains(&Sex::Male) {
+            0.9
+        } else if dynasty::matrilineal(m.gender_law) && !siblings.contains(&Sex::Female) {
+            0.1
+        } else {
+            0.5
+        };
+        // The child joins the mother's line if she married matrilineally,
+        // else the line the law passes it through, or the elder of the two
+        // when the parents' laws disagree.
+        let source = if self.married_matrilineally(mother, father) {
+            mother
+        } else if dynasty::patrilineal(f.gender_law) && dynasty::patrilineal(m.gender_law) {
+            father
+        } else if dynasty::matrilineal(m.gender_law) || self.first_is_elder(mother, father) {
+            mother
+        } else {
+            father
+        };
+        let source = self.character(source).clone();
+        let birth_order = m.children.len() as u32 + 1;
+        let fertility_modifier =
+            self.child_fertility_modifier(birth_order, father, mother, source.dynasty.as_deref());
+        let sex = if random::chance(&mut self.rng, male_chance) {
+            Sex::Male
+        } else {
+            Sex::Female
+        };
+
+        let id = self.next_id(source.dynasty.as_deref());
+        let name = self.child_name(sex, mother, father, source.dynasty.as_deref());
+        let child = self.spawn(Birth {
+            id,
+            name,
+            sex,
+            birth_year: year,
+            birth_date: due,
+            dynasty: source.dynasty,
+            is_house: source.is_house,
+            house: source.house,
+            culture: source.culture,
+            religion: source.religion,
+            gender_law: source.gender_law,
+            generation,
+            birth_order,
+            blood_tier: 0,
+            fertility_modifier,
+        });
+        self.link_parents(child, Some(father), Some(mother));
+        self.last_child_year.insert((father, mother), year);
+        self.character_born_hooks(child);
+        Some(child)
+    }
+
+    /// Record the parents of a new child and roll what it inherits.
+    pub(crate) fn link_parents(
+        &mut self,
+        child: CharacterId,
+        father: Option<CharacterId>,
+        mother: Option<CharacterId>,
+    ) {
+        let relatedness = match (father, mother) {
+            (Some(father), Some(mother)) => self.relatedness(
+                father,
+                mother,
+                self.config
+                    .initialization
+                    .matchmaking
+                    .consanguinity_generations,
+            ),
+            _ => 0.0,
+        };
+        let init = &self.config.initialization;
+        let father_ref = father.map(|id| &self.characters[id.0]);
+        let mother_ref = mother.map(|id| &self.characters[id.0]);
+        let mut born = self.characters[child.0].clone();
+        inherit_blood(
+            &mut born,
+            father_ref,
+            mother_ref,
+            init.numenor_blood.as_ref(),
+            init.numenor_inheritance.as_ref(),
+            init.numenor_decline.as_ref(),
+            &mut self.rng,
+        );
+        if let (Some(father), Some(mother)) = (father_ref, mother_ref) {
+            inherit_genes(&mut born, father, mother, relatedness, &mut self.rng);
+        }
+        if let Some(genetics) = &init.portrait_genetics {
+            born.dna = Some(Dna::inherit(
+                father_ref.and_then(|f| f.dna.as_ref()),
+                mother_ref.and_then(|m| m.dna.as_ref()),
+                genetics.mutation,
+                &mut self.rng,
+            ));
+        }
+        born.father = father;
+        born.mother = mother;
+        let birth = born.birth;
+        self.characters[child.0] = born;
+        for parent in [father, mother].into_iter().flatten() {
+            self.character_mut(parent).children.push(child);
+            self.learn_languages_from(child, parent, birth);
+        }
+    }
+
+    /// Fertility of a new child, lower for younger children of large
+    /// dynasties so that they do not grow without bound.
+    fn child_fertility_modifier(
+        &self,
+        birth_order: u32,
+        father: CharacterId,
+        mother: CharacterId,
+        dynasty: Option<&str>,
+    ) -> f64 {
+        let (f, m) = (self.character(father), self.character(mother));
+        let mut modifier = if self.fertile_members(dynasty) > 8 {
+            let by_order = match birth_order {
+                1 => 1.0,
+                2 => 0.80,
+                3 => 0.60,
+                4 => 0.40,
+                5 => 0.20,
+                _ => 0.10,
+            };
+            let parent = if f.dynasty.as_deref() == dynasty {
+                f
+            } else {
+                m
+            };
+            by_order * parent.fertility_modifier
+        } else {
+            1.0
+        };
+        let prioritises_lowborn = |c: &Character| {
+            c.dynasty.as_deref() == dynasty
+                && self
+                    .dynasty_config(dynasty)
+                    .is_some_and(|d| d.prioritise_lowborn_marriage)
+        };
+        if prioritises_lowborn(f) {
+            modifier *= f.fertility_modifier * 0.65;
+        } else if prioritises_lowborn(m) {
+            modifier *= m.fertility_modifier * 0.65;
+        }
+        modifier
+    }
+
+    /// A name for a child of `dynasty`, inherited from a parent or
+    /// grandparent of the same sex as the dynasty's name inheritance chances
+    /// dictate, or drawn from the mother's culture.
+    fn child_name(
+        &mut self,
+        sex: Sex,
+        mother: CharacterId,
+        father: CharacterId,
+        dynasty: Option<&str>,
+    ) -> String {
+        let Some(chances) = self.dynasty_config(dynasty).map(|d| d.name_inheritance) else {
+            return self.unique_name(sex, mother, father, dynasty);
+        };
+        let choice = random::weighted(
+            &mut self.rng,
+            [
+                (0, chances.grandparent_name_inheritance_chance),
+                (1, chances.parent_name_inheritance_chance),
+                (2, chances.no_name_inheritance_chance),
+            ],
+        );
+        let parent = match sex {
+            Sex::Male => father,
+            Sex::Female => mother,
+        };
+        match choice {
+            Some(0) => {
+                let parent = self.character(parent);
+                let grandparent = match sex {
+                    Sex::Male => parent.father,
+                    Sex::Female => parent.mother,
+                };
+                if let Some(grandparent) = grandparent {
+                    return self.character(grandparent).name.clone();
+                }
+            }
+            Some(1) => return self.character(parent).name.clone(),
+            _ => {}
+        }
+        self.unique_name(sex, mother, father, dynasty)
+    }
+
+    /// A name from the mother's culture no living child of the parents has,
+    /// if there is one left, preferring names borne in the child's dynasty.
+    fn unique_name(
+        &mut self,
+        sex: Sex,
+        mother: CharacterId,
+        father: CharacterId,
+        dynasty: Option<&str>,
+    ) -> String {
+        let (m, f) = (&self.characters[mother.0], &self.characters[father.0]);
+        let taken: BTreeSet<&str> = m
+            .children
+            .iter()
+            .chain(&f.children)
+            .map(|&id| &self.characters[id.0])
+            .filter(|c| c.is_alive())
+            .map(|c| c.name.as_str())
+            .collect();
+        let dynastic: BTreeSet<&str> = match dynasty {
+            Some(_) => self
+                .characters
+                .iter()
+                .filter(|c| c.sex == sex && c.dynasty.as_deref() == dynasty)
+                .map(|c| c.name.as_str())
+                .collect(),
+            None => BTreeSet::new(),
+        };
+        self.names
+            .pick(&mut self.rng, &m.culture, sex, &taken, &dynastic)
+    }
+
+    /// Illegitimate children of dynasty members, at most one per man a
+    /// year, and never for a woman who gave birth in wedlock this year.
+    fn roll_bastards(&mut self, year: i32) {
+        let life = &self.config.life_stages;
+        let (male_chance, female_chance) = (life.bastardy_chance_male, life.bastardy_chance_female);
+        for parent in self.ids().collect::<Vec<_>>() {
+            let character = self.character(parent);
+            if !character.is_alive() || !character.has_dynasty() {
+                continue;
+            }
+            let chance = match character.sex {
+                Sex::Female => {
+                    if self.fertility_rate(parent) * character.fertility_mult() == 0.0 {
+                        continue;
+                    }
+                    let gave_birth = character.spouse.is_some_and(|spouse| {
+                        self.last_child_year.get(&(spouse, parent)) == Some(&year)
+                    });
+                    if gave_birth {
+                        continue;
+                    }
+                    female_chance
+                }
+                Sex::Male => male_chance,
+            };
+            if random::chance(&mut self.rng, chance) {
+                self.create_bastard(parent, year);
+            }
+        }
+    }
+
+    fn create_bastard(&mut self, parent: CharacterId, year: i32) -> Option<CharacterId> {
+        let p = self.character(parent).clone();
+        if p.children.len() >= self.config.life_stages.maximum_number_of_children as usize {
+            return None;
+        }
+        if p.sex == Sex::Female {
+            let rates = &self.config.life_stages.fertility_rates.female;
+            if rates.get(p.age as usize).is_none_or(|&rate| rate == 0.0) {
+                return None;
+            }
+        }
+        let generation = p.generation + 1;
+        if generation > self.config.initialization.generation_max {
+            return None;
+        }
+        let (mother, father) = match p.sex {
+            Sex::Male => (None, Some(parent)),
+            Sex::Female => (Some(parent), None),
+        };
+        let due = match self.pregnancy() {
+            Some(pregnancy) => Some(self.due_date(&pregnancy, mother, father, year)?),
+            None => None,
+        };
+
+        let children: Vec<Sex> = p
+            .children
+            .iter()
+            .map(|&id| self.character(id).sex)
+            .collect();
+        let male_chance = if dynasty::patrilineal(p.gender_law) && !children.contains(&Sex::Male) {
+            0.65
+        } else if dynasty::matrilineal(p.gender_law) && !children.contains(&Sex::Female) {
+            0.35
+        } else {
+            0.5
+        };
+        let sex = if random::chance(&mut self.rng, male_chance) {
+            Sex::Male
+        } else {
+            Sex::Female
+        };
+        let fate = self.bastard_fate();
+        let (dynasty, house) = match fate {
+            BastardFate::LowbornLine => (None, None),
+            _ => (p.dynasty.clone(), p.house.clone()),
+        };
+        let id = self.next_id(p.dynasty.as_deref());
+        let name = self.child_name(sex, parent, parent, p.dynasty.as_deref());
+        let child = self.spawn_grown_child(
+            Birth {
+                id,
+                name,
+                sex,
+                birth_year: due.map_or(year.max(p.birth.year + FERTILE_FROM), |due| due.year),
+                birth_date: due,
+                is_house: p.is_house && dynasty.is_some(),
+                dynasty,
+                house,
+                culture: p.culture.clone(),
+                religion: p.religion.clone(),
+                gender_law: p.gender_law,
+                generation,
+                birth_order: p.children.len() as u32 + 1,
+                blood_tier: 0,
+                fertility_modifier: 1.0,
+            },
+            father,
+            mother,
+        );
+        self.link_parents(child, father, mother);
+        let bastard = self.character_mut(child);
+        bastard.is_bastard = true;
+        bastard.add_trait("bastard");
+        if fate == BastardFate::Legitimized {
+            self.schedule_legitimization(child);
+        }
+        self.character_born_hooks(child);
+        Some(child)
+    }
+
+    fn roll_deaths(&mut self, year: i32) {
+        for id in self.ids().collect::<Vec<_>>() {
+            if self.character(id).is_alive() && self.dies(id, year) {
+                self.die(id, year);
+            }
+        }
+    }
+
+    /// Record the death of `id` during `year` and widow their spouse.
+    pub(crate) fn die(&mut self, id: CharacterId, year: i32) {
+        self.kill(id, year);
+        // The widowed spouse may marry again.
+        if let Some(spouse) = self.character(id).spouse {
+            if self.character(spouse).is_alive() {
+                self.widow(spouse, id);
+            }
+        }
+        // So may a man's other wives and his concubines.
+        let c = self.character_mut(id);
+        let consorts = std::mem::take(&mut c.extra_spouses)
+            .into_iter()
+            .chain(std::mem::take(&mut c.concubines));
+        for consort in consorts.collect::<Vec<_>>() {
+            if self.character(consort).is_alive() {
+                self.character_mut(consort).spouse = None;
+            }
+        }
+    }
+
+    /// Give the children alive at the end of the period a childhood trait,
+    /// and when childhood traits are swapped on coming of age, the
+    /// personality traits of those who live to 16.
+    fn grow_up_minors(&mut self, last_year: i32) {
+        for i in 0..self.characters.len() {
+            let character = &mut self.characters[i];
+            if !self.living_at_end[i] || last_year - character.birth.year >= 16 {
+                continue;
+            }
+            if !character.has_childhood_trait() {
+                let childhood = character.roll_childhood_trait(&mut self.rng);
+                character.add_event(
+                    character.birth.years_later(3),
+                    EventKind::Traits(vec![childhood]),
+                );
+            }
+            let adult = character.birth.years_later(16);
+            let lives = character.death.is_none_or(|death| death > adult);
+            if !lives || !swaps_childhood_traits(&self.config, adult) {
+                continue;
+            }
+            let (father, mother) = (character.father, character.mother);
+            let multipliers = self.trait_multipliers(CharacterId(i), father, mother);
+            let character = &mut self.characters[i];
+            character.assign_personality_traits(&self.config, &multipliers, &mut self.rng);
+            let traits = character.personality_traits.clone();
+            character.add_event(adult, EventKind::Traits(traits));
+        }
+    }
+
+    /// Give everyone still alive at the end of the period a death date by
+    /// ageing them on year by year.
+    fn bury_survivors(&mut self, last_year: i32) {
+        let mut survivors: Vec<CharacterId> = self
+            .ids()
+            .filter(|&id| self.character(id).is_alive())
+            .collect();
+        let mut year = last_year;
+        while !survivors.is_empty() {
+            year += 1;
+            survivors.retain(|&id| {
+                let character = &mut self.characters[id.0];
+                character.age = year - character.birth.year;
+                character.event_death_reason = None;
+                if self.dies(id, year) {
+                    self.kill(id, year);
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+    }
+}
+
+/// Whether the history swaps a childhood trait for adult ones on a 16th
+/// birthday on `date`: if configured to, up to the last bookmark.
+pub(crate) fn swaps_childhood_traits(config: &SimConfig, date: Date) -> bool {
+    let coming_of_age = &config.initialization.coming_of_age;
+    let last_bookmark = coming_of_age
+        .bookmarks
+        .iter()
+        .filter_map(|bookmark| Date::parse(bookmark))
+        .max();
+    coming_of_age.swap_childhood_traits && last_bookmark.is_none_or(|last| date <= last)
+}
+
+/// A seed for a run that was not given one.
+pub(crate) fn random_seed() -> u64 {
+    ChaCha12Rng::from_os_rng().random_range(0..RANDOM_SEEDS)
+}
+
+/// A character ID's prefix and number, e.g. `("baggins", 12)`.
+pub(crate) fn split_id(id: &str) -> (&str, u32) {
+    let rest = id.strip_prefix(ID_START).unwrap_or(id);
+    let prefix = rest.trim_end_matches(|c: char| c.is_ascii_digit());
+    (prefix, rest[prefix.len()..].parse().unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::{check_marriages, parse_people};
+    use ck3_config::Severity;
+
+    /// Characters per run of the shipped config by the Python engine, over
+    /// its first `PYTHON_SEEDS` seeds.
+    const PYTHON_MEAN_CHARACTERS: f64 = 150.8;
+    const PYTHON_SEEDS: u64 = 30;
+
+    #[test]
+    fn runs_keep_families_consistent() {
+        for seed in 0..5 {
+            let run = testing::run(seed);
+            for (id, c) in run.characters() {
+                assert!(c.death.is_none_or(|death| death >= c.birth), "{}", c.id);
+                for parent in [c.father, c.mother].into_iter().flatten() {
+                    let p = run.character(parent);
+                    assert!(p.birth < c.birth, "{} is older than {}", c.id, p.id);
+                    assert!(p.children.contains(&id), "{} lacks {}", p.id, c.id);
+                }
+                for &child in &c.children {
+                    let child = run.character(child);
+                    assert!(
+                        [child.father, child.mother].contains(&Some(id)),
+                        "{}",
+                        child.id
+                    );
+                }
+                for event in &c.events {
+                    if let EventKind::Marriage { spouse, .. } = event.kind {
+                        let spouse = run.character(spouse);
+                        assert_ne!(spouse.sex, c.sex, "{} and {}", c.id, spouse.id);
+                        assert!(event.date >= c.birth && event.date >= spouse.birth);
+                    }
+                }
+            }
+            assert!(run.dangling_references().is_empty(), "seed {seed}");
+            let people = parse_people(&run.history()).unwrap();
+            let problems = check_marriages(&people, &run.config.initialization);
+            assert!(
+                problems.iter().all(|p| p.severity != Severity::Error),
+                "seed {seed}: {problems:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn forced_dynasties_survive() {
+        for seed in 0..5 {
+            let run = testing::run(seed);
+            for dynasty in run.config.initialization.dynasties.iter() {
+                if !dynasty.force_dynasty_alive {
+                    continue;
+                }
+                let alive = run.characters().any(|(id, c)| {
+                    run.living_at_end[id.0] && c.dynasty.as_deref() == Some(&dynasty.dynasty_id)
+                });
+                assert!(alive, "seed {seed}: {} died out", dynasty.dynasty_id);
+            }
+        }
+    }
+
+    #[test]
+    fn runs_stop_before_the_year_asked() {
+        let mut run = testing::simulation(testing::config(), 0);
+        let stop = run.config.initialization.min_year + 10;
+        let mut asked = Vec::new();
+        let summary = run.run_until(|year| {
+            asked.push(year);
+            if year == stop {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert!(summary.is_none());
+        assert_eq!(asked.last(), Some(&stop));
+        assert!(run.characters().count() < testing::run(0).characters().count());
+    }
+
+    #[test]
+    fn population_matches_the_python_engine() {
+        let total: usize = (0..PYTHON_SEEDS)
+            .map(|seed| testing::run(seed).characters().count())
+            .sum();
+        let mean = total as f64 / PYTHON_SEEDS as f64;
+        let band = PYTHON_MEAN_CHARACTERS * 0.8..=PYTHON_MEAN_CHARACTERS * 1.2;
+        assert!(band.contains(&mean), "{mean} characters a run");
+    }
+}
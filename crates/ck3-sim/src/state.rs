@@ -0,0 +1,432 @@
+//! Saving a whole simulation as JSON and loading it back.
+//!
+//! A history file only keeps what the game reads, so a run continued from
+//! one has to guess at the rest. [`Simulation::save_state`] writes out
+//! everything instead: every character with all they were rolled, such as
+//! skills, sexuality, carried genes and fertility, every relationship and
+//! every event, the seed and the counters IDs are handed out from, under a
+//! format version. Characters refer to each other by their history IDs, so
+//! the file can be read by other programs or attached to a bug report as it
+//! is. [`Simulation::from_state`] loads it back to carry the run on.
+
+use std::collections::{BTreeMap, HashMap};
+
+use ck3_config::GenderLaw;
+use serde::{Deserialize, Serialize};
+
+use crate::character::{Character, CharacterId, Education, Event, EventKind, Sex, SKILLS};
+use crate::config::SimConfig;
+use crate::date::Date;
+use crate::dna::Dna;
+use crate::error::SimError;
+use crate::genetics::genetic_trait;
+use crate::names::NameLists;
+use crate::simulation::Simulation;
+
+/// Version of the state format written, the only one read.
+pub const STATE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct State {
+    version: u32,
+    seed: u64,
+    /// Last year of the run, if it has been run.
+    last_year: Option<i32>,
+    characters: Vec<CharacterState>,
+    /// Last number used in character IDs, by ID prefix.
+    id_counters: BTreeMap<String, u32>,
+    /// Year of each couple's last child.
+    last_children: Vec<LastChild>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CharacterState {
+    id: String,
+    name: String,
+    female: bool,
+    birth: Date,
+    death: Option<Date>,
+    age: i32,
+    spouse: Option<String>,
+    extra_spouses: Vec<String>,
+    concubines: Vec<String>,
+    father: Option<String>,
+    mother: Option<String>,
+    children: Vec<String>,
+    dynasty: Option<String>,
+    is_house: bool,
+    house: Option<String>,
+    culture: String,
+    religion: String,
+    gender_law: GenderLaw,
+    generation: u32,
+    is_progenitor: bool,
+    is_bastard: bool,
+    legitimized_on: Option<Date>,
+    is_adopted: bool,
+    birth_order: u32,
+    /// Levels by skill.
+    skills: BTreeMap<String, u32>,
+    education: Option<EducationState>,
+    traits: Vec<String>,
+    personality_traits: Vec<String>,
+    congenital_traits: Vec<String>,
+    inactive_traits: Vec<String>,
+    sexuality: String,
+    fertility_modifier: f64,
+    blood_tier: u32,
+    dna: Option<Dna>,
+    events: Vec<EventState>,
+    /// Whether they were alive when the run ended.
+    alive_at_end: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EducationState {
+    skill: String,
+    tier: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EventState {
+    date: Date,
+    #[serde(flatten)]
+    kind: EventKindState,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum EventKindState {
+    Birth,
+    Traits { traits: Vec<String> },
+    Marriage { spouse: String, matrilineal: bool },
+    Concubinage { concubine: String },
+    Death { reason: String },
+    Adoption { adopter: Option<String> },
+    LearnLanguage { language: String },
+    Legitimization,
+    Conversion { from: String, to: String },
+}
+
+#[derive(Serialize, Deserialize)]
+struct LastChild {
+    father: String,
+    mother: String,
+    year: i32,
+}
+
+/// Just the version of a state, read first so that a state of another
+/// version is reported as such rather than as malformed.
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+impl Simulation {
+    /// The whole state of the run as pretty-printed JSON.
+    pub fn save_state(&self) -> String {
+        let id = |c: CharacterId| self.character(c).id.clone();
+        let ids = |cs: &[CharacterId]| cs.iter().map(|&c| id(c)).collect();
+        let characters = self
+            .characters()
+            .map(|(i, c)| CharacterState {
+                id: c.id.clone(),
+                name: c.name.clone(),
+                female: c.sex == Sex::Female,
+                birth: c.birth,
+                death: c.death,
+                age: c.age,
+                spouse: c.spouse.map(id),
+                extra_spouses: ids(&c.extra_spouses),
+                concubines: ids(&c.concubines),
+                father: c.father.map(id),
+                mother: c.mother.map(id),
+                children: ids(&c.children),
+                dynasty: c.dynasty.clone(),
+                is_house: c.is_house,
+                house: c.house.clone(),
+                culture: c.culture.clone(),
+                religion: c.religion.clone(),
+                gender_law: c.gender_law,
+                generation: c.generation,
+                is_progenitor: c.is_progenitor,
+                is_bastard: c.is_bastard,
+                legitimized_on: c.legitimized_on,
+                is_adopted: c.is_adopted,
+                birth_order: c.birth_order,
+                skills: SKILLS
+                    .iter()
+                    .zip(c.skills)
+                    .map(|(skill, level)| (skill.to_string(), level))
+                    .collect(),
+                education: c.education.map(|education| EducationState {
+                    skill: education.skill.to_string(),
+                    tier: education.tier,
+                }),
+                traits: c.traits.clone(),
+                personality_traits: c.personality_traits.clone(),
+                congenital_traits: c.congenital_traits.iter().map(|t| t.to_string()).collect(),
+                inactive_traits: c.inactive_traits.iter().map(|t| t.to_string()).collect(),
+                sexuality: c.sexuality.clone(),
+                fertility_modifier: c.fertility_modifier,
+                blood_tier: c.blood_tier,
+                dna: c.dna.clone(),
+                events: c
+                    .events
+                    .iter()
+                    .map(|event| EventState {
+                        date: event.date,
+                        kind: self.event_state(&event.kind),
+                    })
+                    .collect(),
+                alive_at_end: self.living_at_end.get(i.0).copied().unwrap_or(false),
+            })
+            .collect();
+        let state = State {
+            version: STATE_VERSION,
+            seed: self.seed(),
+            last_year: (!self.living_at_end.is_empty())
+                .then_some(self.config.initialization.max_year),
+            characters,
+            id_counters: self
+                .id_counters
+                .iter()
+                .map(|(prefix, &counter)| (prefix.clone(), counter))
+                .collect(),
+            last_children: {
+                let mut couples: Vec<_> = self.last_child_year.iter().collect();
+                couples.sort();
+                couples
+                    .into_iter()
+                    .map(|(&(father, mother), &year)| LastChild {
+                        father: id(father),
+                        mother: id(mother),
+                        year,
+                    })
+                    .collect()
+            },
+        };
+        serde_json::to_string_pretty(&state).expect("a state serializes")
+    }
+
+    fn event_state(&self, kind: &EventKind) -> EventKindState {
+        let id = |c: CharacterId| self.character(c).id.clone();
+        match kind {
+            EventKind::Birth => EventKindState::Birth,
+            EventKind::Traits(traits) => EventKindState::Traits {
+                traits: traits.clone(),
+            },
+            &EventKind::Marriage {
+                spouse,
+                matrilineal,
+            } => EventKindState::Marriage {
+                spouse: id(spouse),
+                matrilineal,
+            },
+            &EventKind::Concubinage { concubine } => EventKindState::Concubinage {
+                concubine: id(concubine),
+            },
+            EventKind::Death { reason } => EventKindState::Death {
+                reason: reason.clone(),
+            },
+            &EventKind::Adoption { adopter } => EventKindState::Adoption {
+                adopter: adopter.map(id),
+            },
+            EventKind::LearnLanguage { language } => EventKindState::LearnLanguage {
+                language: language.clone(),
+            },
+            EventKind::Legitimization => EventKindState::Legitimization,
+            EventKind::Conversion { from, to } => EventKindState::Conversion {
+                from: from.clone(),
+                to: to.clone(),
+            },
+        }
+    }
+
+    /// Prepare a run continuing the state `state`, as written by
+    /// [`save_state`](Simulation::save_state), over the configured period.
+    ///
+    /// Characters alive when the saved run ended have the deaths it gave
+    /// them afterwards undone, and configured dynasties without members
+    /// start from a progenitor as usual. Every random decision is drawn
+    /// from a generator seeded with `seed`, or if `None` with the seed of
+    /// the saved run, started over.
+    pub fn from_state(
+        config: SimConfig,
+        names: NameLists,
+        seed: Option<u64>,
+        state: &str,
+    ) -> Result<Self, SimError> {
+        let invalid = |message: String| SimError::State { message };
+        let Version { version } = serde_json::from_str(state)
+            .map_err(|err| invalid(format!("the state cannot be read: {err}")))?;
+        if version != STATE_VERSION {
+            return Err(invalid(format!(
+                "the state is of version {version}, but only version {STATE_VERSION} can be read"
+            )));
+        }
+        let state: State = serde_json::from_str(state)
+            .map_err(|err| invalid(format!("the state cannot be read: {err}")))?;
+
+        let index: HashMap<&str, CharacterId> = state
+            .characters
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.id.as_str(), CharacterId(i)))
+            .collect();
+        let find = |owner: &str, id: &str| {
+            index.get(id).copied().ok_or_else(|| {
+                invalid(format!(
+                    "character {owner} refers to '{id}', who is not in the state"
+                ))
+            })
+        };
+        let find_all = |owner: &str, ids: &[String]| -> Result<Vec<CharacterId>, SimError> {
+            ids.iter().map(|id| find(owner, id)).collect()
+        };
+        let trait_names = |owner: &str, names: &[String]| -> Result<Vec<&'static str>, SimError> {
+            names
+                .iter()
+                .map(|name| {
+                    genetic_trait(name).ok_or_else(|| {
+                        invalid(format!(
+                            "character {owner} has '{name}', which is not a genetic trait"
+                        ))
+                    })
+                })
+                .collect()
+        };
+
+        let mut simulation = Self::empty(config, names, seed.unwrap_or(state.seed));
+        let end = state.last_year.map(|year| Date::new(year, 12, 31));
+        for c in &state.characters {
+            let owner = c.id.as_str();
+            let education = c
+                .education
+                .as_ref()
+                .map(|education| {
+                    let skill = SKILLS
+                        .into_iter()
+                        .find(|&skill| skill == education.skill)
+                        .ok_or_else(|| {
+                            invalid(format!(
+                                "character {owner} is educated in '{}', which is not a skill",
+                                education.skill
+                            ))
+                        })?;
+                    Ok(Education {
+                        skill,
+                        tier: education.tier,
+                    })
+                })
+                .transpose()?;
+            let mut events = Vec::new();
+            for event in &c.events {
+                let kind = match &event.kind {
+                    EventKindState::Birth => EventKind::Birth,
+                    EventKindState::Traits { traits } => EventKind::Traits(traits.clone()),
+                    EventKindState::Marriage {
+                        spouse,
+                        matrilineal,
+                    } => EventKind::Marriage {
+                        spouse: find(owner, spouse)?,
+                        matrilineal: *matrilineal,
+                    },
+                    EventKindState::Concubinage { concubine } => EventKind::Concubinage {
+                        concubine: find(owner, concubine)?,
+                    },
+                    EventKindState::Death { reason } => EventKind::Death {
+                        reason: reason.clone(),
+                    },
+                    EventKindState::Adoption { adopter } => EventKind::Adoption {
+                        adopter: adopter.as_deref().map(|id| find(owner, id)).transpose()?,
+                    },
+                    EventKindState::LearnLanguage { language } => EventKind::LearnLanguage {
+                        language: language.clone(),
+                    },
+                    EventKindState::Legitimization => EventKind::Legitimization,
+                    EventKindState::Conversion { from, to } => EventKind::Conversion {
+                        from: from.clone(),
+                        to: to.clone(),
+                    },
+                };
+                events.push(Event {
+                    date: event.date,
+                    kind,
+                });
+            }
+            let mut death = c.death;
+            // A survivor's death was only settled to write the history.
+            if c.alive_at_end && end.is_some_and(|end| death.is_some_and(|death| death > end)) {
+                death = None;
+                events.retain(|event| !matches!(event.kind, EventKind::Death { .. }));
+            }
+            let mut skills = [0; 6];
+            for (level, skill) in skills.iter_mut().zip(SKILLS) {
+                *level = c.skills.get(skill).copied().unwrap_or(0);
+            }
+            simulation.reserve_id(&c.id);
+            simulation.characters.push(Character {
+                id: c.id.clone(),
+                name: c.name.clone(),
+                sex: if c.female { Sex::Female } else { Sex::Male },
+                birth: c.birth,
+                death,
+                age: c.age,
+                spouse: c.spouse.as_deref().map(|id| find(owner, id)).transpose()?,
+                extra_spouses: find_all(owner, &c.extra_spouses)?,
+                concubines: find_all(owner, &c.concubines)?,
+                father: c.father.as_deref().map(|id| find(owner, id)).transpose()?,
+                mother: c.mother.as_deref().map(|id| find(owner, id)).transpose()?,
+                children: find_all(owner, &c.children)?,
+                dynasty: c.dynasty.clone(),
+                is_house: c.is_house,
+                house: c.house.clone(),
+                culture: c.culture.clone(),
+                religion: c.religion.clone(),
+                gender_law: c.gender_law,
+                generation: c.generation,
+                is_progenitor: c.is_progenitor,
+                is_bastard: c.is_bastard,
+                legitimized_on: c.legitimized_on,
+                is_adopted: c.is_adopted,
+                birth_order: c.birth_order,
+                skills,
+                education,
+                traits: c.traits.clone(),
+                personality_traits: c.personality_traits.clone(),
+                congenital_traits: trait_names(owner, &c.congenital_traits)?,
+                inactive_traits: trait_names(owner, &c.inactive_traits)?,
+                sexuality: c.sexuality.clone(),
+                fertility_modifier: c.fertility_modifier,
+                blood_tier: c.blood_tier,
+                dna: c.dna.clone(),
+                events,
+                event_death_reason: None,
+            });
+        }
+        for (prefix, counter) in state.id_counters {
+            let reserved = simulation.id_counter(&prefix);
+            *reserved = (*reserved).max(counter);
+        }
+        for child in &state.last_children {
+            let parent = |id: &str| {
+                index.get(id).copied().ok_or_else(|| {
+                    invalid(format!(
+                        "a couple's last child has '{id}' as a parent, who is not in the state"
+                    ))
+                })
+            };
+            let couple = (parent(&child.father)?, parent(&child.mother)?);
+            simulation.last_child_year.insert(couple, child.year);
+        }
+        simulation.seed_progenitors();
+        Ok(simulation)
+    }
+}
@@ -0,0 +1,216 @@
+//! Keeping dynasties flagged `forceDynastyAlive` from dying out.
+//!
+//! After each year's deaths, such a dynasty that has no member left who could
+//! carry it on gets an heir: a child of an existing couple if one can still
+//! have children, else a child of its most suitable member and a new lowborn
+//! spouse, and failing both an adopted ward. The carriers are the members
+//! the dynasty's gender law passes it through, and only those of an age to
+//! have children count: a dynasty whose carriers are all children is given
+//! another ward each year until one comes of age, as the Python engine does.
+
+use ck3_config::GenderLaw;
+
+use crate::character::{Birth, Character, CharacterId, EventKind, Sex};
+use crate::date::Date;
+use crate::dynasty;
+use crate::simulation::{Simulation, FERTILE_FROM};
+
+/// Oldest ages at which men and women can still be given an heir.
+const MALE_CARRIER_UNTIL: i32 = 80;
+const FEMALE_CARRIER_UNTIL: i32 = 50;
+/// Age an emergency marriage looks for, closest first.
+const PREFERRED_CARRIER_AGE: i32 = 25;
+/// Ages between which a dynasty member can adopt.
+const ADOPTER_AGES: std::ops::RangeInclusive<i32> = 20..=60;
+/// Oldest age of an adopted ward.
+const MAX_WARD_AGE: i32 = 5;
+
+impl Simulation {
+    pub(crate) fn enforce_dynasty_survival(&mut self, year: i32) {
+        let forced: Vec<(String, GenderLaw)> = self
+            .config
+            .initialization
+            .dynasties
+            .iter()
+            .filter(|d| d.force_dynasty_alive)
+            .map(|d| (d.dynasty_id.clone(), d.gender_law))
+            .collect();
+        for (dynasty, law) in forced {
+            if self.can_continue(&dynasty, law) {
+                continue;
+            }
+            if !self.emergency_heir(&dynasty, law, year) {
+                self.adopt_heir(&dynasty, year);
+            }
+        }
+    }
+
+    fn living_members(&self, dynasty: &str) -> Vec<CharacterId> {
+        self.ids()
+            .filter(|&id| {
+                let c = self.character(id);
+                c.is_alive() && c.dynasty.as_deref() == Some(dynasty)
+            })
+            .collect()
+    }
+
+    /// Whether the law lets `character`'s sex carry the dynasty.
+    pub(crate) fn carries_line(law: GenderLaw, character: &Character) -> bool {
+        if dynasty::patrilineal(law) {
+            character.sex == Sex::Male
+        } else if dynasty::matrilineal(law) {
+            character.sex == Sex::Female
+        } else {
+            true
+        }
+    }
+
+    fn carrier_until(sex: Sex) -> i32 {
+        match sex {
+            Sex::Male => MALE_CARRIER_UNTIL,
+            Sex::Female => FEMALE_CARRIER_UNTIL,
+        }
+    }
+
+    /// Whether a living member can have a legitimate heir now.
+    fn can_continue(&self, dynasty: &str, law: GenderLaw) -> bool {
+        self.living_members(dynasty).into_iter().any(|id| {
+            let c = self.character(id);
+            if !c.can_marry() || !Self::carries_line(law, c) {
+                return false;
+            }
+            match c.spouse.map(|spouse| self.character(spouse)) {
+                // A married man depends on his wife's age, a woman on her own.
+                Some(spouse) if c.sex == Sex::Male => {
+                    c.age <= MALE_CARRIER_UNTIL
+                        && (FERTILE_FROM..=FEMALE_CARRIER_UNTIL).contains(&spouse.age)
+                }
+                _ => (FERTILE_FROM..=Self::carrier_until(c.sex)).contains(&c.age),
+            }
+        })
+    }
+
+    /// Force the birth of an heir; returns whether one was born.
+    fn emergency_heir(&mut self, dynasty: &str, law: GenderLaw, year: i32) -> bool {
+        let mut members: Vec<CharacterId> = self
+            .living_members(dynasty)
+            .into_iter()
+            .filter(|&id| {
+                let c = self.character(id);
+                c.can_marry()
+                    && Self::carries_line(law, c)
+                    && (FERTILE_FROM..=Self::carrier_until(c.sex)).contains(&c.age)
+            })
+            .collect();
+        members.sort_by_key(|&id| (self.character(id).age - PREFERRED_CARRIER_AGE).abs());
+
+        // A couple that can still have children comes first.
+        for &carrier in &members {
+            let Some(spouse) = self.character(carrier).spouse else {
+                continue;
+            };
+            let (mother, father) = match self.character(carrier).sex {
+                Sex::Female => (carrier, spouse),
+                Sex::Male => (spouse, carrier),
+            };
+            if !(FERTILE_FROM..=FEMALE_CARRIER_UNTIL).contains(&self.character(mother).age) {
+                continue;
+            }
+            if self.create_child(mother, father, year).is_some() {
+                return true;
+            }
+        }
+
+        let Some(carrier) = members
+            .into_iter()
+            .find(|&id| !self.character(id).is_married())
+        else {
+            return false;
+        };
+        self.marry_lowborn(carrier, year);
+        let Some(spouse) = self.character(carrier).spouse else {
+            return false;
+        };
+        let (mother, father) = match self.character(carrier).sex {
+            Sex::Female => (carrier, spouse),
+            Sex::Male => (spouse, carrier),
+        };
+        self.create_child(mother, father, year).is_some()
+    }
+
+    /// Adopt a young child into `dynasty`, a girl under enatic laws and a boy
+    /// otherwise, as the ward of its oldest member of an age to adopt if it
+    /// has one.
+    fn adopt_heir(&mut self, dynasty: &str, year: i32) {
+        let adopter = self
+            .living_members(dynasty)
+            .into_iter()
+            .filter(|&id| ADOPTER_AGES.contains(&self.character(id).age))
+            .max_by_key(|&id| self.character(id).age);
+        let config = self
+            .dynasty_config(Some(dynasty))
+            .expect("forced dynasties are configured")
+            .clone();
+        let (culture, religion, gender_law, blood_tier, generation, house) =
+            match adopter.map(|id| self.character(id)) {
+                Some(a) => (
+                    a.culture.clone(),
+                    a.religion.clone(),
+                    a.gender_law,
+                    a.blood_tier,
+                    a.generation + 1,
+                    a.house.clone(),
+                ),
+                None => (
+                    config.culture_id.clone(),
+                    config.faith_id.clone(),
+                    config.gender_law,
+                    config.numenor_blood_tier.unwrap_or(0),
+                    2,
+                    None,
+                ),
+            };
+
+        let sex = if dynasty::matrilineal(config.gender_law) {
+            Sex::Female
+        } else {
+            Sex::Male
+        };
+        let id = self.next_id(Some(dynasty));
+        let name = self.names.random(&mut self.rng, &culture, sex);
+        let age = rand::Rng::random_range(&mut self.rng, 0..=MAX_WARD_AGE);
+        let ward = self.spawn_grown(Birth {
+            id,
+            name,
+            sex,
+            birth_year: year - age,
+            birth_date: None,
+            dynasty: Some(dynasty.to_string()),
+            is_house: config.is_house,
+            house,
+            culture,
+            religion,
+            gender_law,
+            generation,
+            birth_order: 1,
+            blood_tier,
+            fertility_modifier: 1.0,
+        });
+
+        let child = self.character_mut(ward);
+        child.is_adopted = true;
+        child.age = age;
+        // Chosen to carry on the line.
+        child.sexuality = "heterosexual".to_string();
+        let date = Date::new(year, child.birth.month, child.birth.day);
+        child.add_event(date, EventKind::Adoption { adopter });
+        if let Some(adopter) = adopter {
+            match self.character(adopter).sex {
+                Sex::Male => self.character_mut(ward).father = Some(adopter),
+                Sex::Female => self.character_mut(ward).mother = Some(adopter),
+            }
+            self.character_mut(adopter).children.push(ward);
+            self.learn_languages_from(ward, adopter, date);
+        }
+    }
+}
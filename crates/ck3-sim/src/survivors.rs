@@ -0,0 +1,282 @@
+//! Catching characters who outlive every plausible lifespan.
+//!
+//! Before either engine capped lifespans, event multipliers below 1 and a
+//! dynasty's luck could keep the last members of a line alive for thousands
+//! of years, and with them a dynasty that should have died out; histories
+//! written then, or by hand, still have them. [`check_survivors`] reads an
+//! output directory's history, whichever engine wrote it, and finds everyone
+//! alive at the end of the project's period who lives, or has lived by then,
+//! past the lifespan cap the config gives their Númenórean blood tier. Each
+//! gets a new death date: the day they reach the cap, or else the last day
+//! the history has them doing something. [`repair_survivors`] writes those
+//! deaths into the history file, leaving the rest of it as it is.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use paradox_script::{append_to_blocks, rewrite_words, Block, Scalar, Word};
+use serde::Serialize;
+
+use crate::config::SimConfig;
+use crate::date::Date;
+use crate::error::SimError;
+use crate::export::HISTORY_FILE;
+use crate::mortality::max_lifespan;
+use crate::people::{read_house_dynasties, read_people, Person};
+
+/// Death reason given to the survivors repaired.
+const DEATH_REASON: &str = "death_natural_causes";
+
+/// A character alive at the end of the period past their lifespan cap.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Survivor {
+    pub id: String,
+    pub name: String,
+    pub dynasty: Option<String>,
+    pub birth: Date,
+    /// The day the history has them die, after the end of the period, if
+    /// any.
+    pub death: Option<Date>,
+    /// Age they die at, or are at the end of the period if they never die,
+    /// in simulation years.
+    pub age: i32,
+    /// The lifespan cap of their blood tier.
+    pub max_age: i32,
+    /// The day they are given to die on instead.
+    pub new_death: Date,
+}
+
+/// The survivors of a history, and the dynasties only they keep alive.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SurvivorReport {
+    /// Last day of the period, as the history dates it.
+    pub end: Date,
+    pub survivors: Vec<Survivor>,
+    /// Dynasties left with no member alive at the end of the period once
+    /// the survivors die on their new dates.
+    pub extinct: Vec<String>,
+}
+
+/// The characters of the history of the output directory `dir` alive at the
+/// end of the period of `config` past their lifespan cap, in file order.
+pub fn check_survivors(dir: &Path, config: &SimConfig) -> Result<SurvivorReport, SimError> {
+    let people = read_people(dir)?;
+    let house_dynasties = read_house_dynasties(dir);
+    let init = &config.initialization;
+    let mortality = config.life_stages.mortality.clone().unwrap_or_default();
+    let end = Date::new(init.max_year, 12, 31);
+    let dated = |date: paradox_script::Date| Date::new(date.year, date.month, date.day);
+
+    // The last day each character is seen doing anything, or having a
+    // child or a spouse do something with them.
+    let mut last_seen: HashMap<&str, Date> = HashMap::new();
+    for person in &people {
+        for (date, key, _) in &person.events {
+            if key == "death" {
+                continue;
+            }
+            see(&mut last_seen, &person.id, dated(*date));
+        }
+        if let Some(birth) = person.birth {
+            for parent in [&person.father, &person.mother].into_iter().flatten() {
+                see(&mut last_seen, parent, dated(birth));
+            }
+        }
+        for (date, spouse) in &person.marriages {
+            see(&mut last_seen, spouse, dated(*date));
+        }
+    }
+
+    let mut survivors = Vec::new();
+    let mut seen = HashSet::new();
+    let mut living: HashMap<&str, (usize, usize)> = HashMap::new();
+    for person in &people {
+        let Some(birth) = person.birth else {
+            continue;
+        };
+        let death = person
+            .death
+            .map(|death| dated(death).from_calendar(&init.calendar));
+        if !seen.insert(person.id.as_str()) || death.is_some_and(|death| death <= end) {
+            continue;
+        }
+        let birth = dated(birth).from_calendar(&init.calendar);
+        let age = birth.years_until(death.unwrap_or(end));
+        let max_age = max_lifespan(&mortality, blood_tier(person));
+        let dynasty = person.lineage(&house_dynasties);
+        if age <= max_age {
+            if let Some(dynasty) = dynasty {
+                living.entry(dynasty).or_default().0 += 1;
+            }
+            continue;
+        }
+        let capped = birth.years_later(max_age);
+        let new_death = last_seen.get(person.id.as_str()).map_or(capped, |&last| {
+            last.from_calendar(&init.calendar).max(capped)
+        });
+        if let Some(dynasty) = dynasty {
+            let counts = living.entry(dynasty).or_default();
+            counts.1 += 1;
+            counts.0 += usize::from(new_death > end);
+        }
+        survivors.push(Survivor {
+            id: person.id.clone(),
+            name: person.name.clone(),
+            dynasty: dynasty.map(str::to_string),
+            birth: birth.to_calendar(&init.calendar),
+            death: death.map(|death| death.to_calendar(&init.calendar)),
+            age,
+            max_age,
+            new_death: new_death.to_calendar(&init.calendar),
+        });
+    }
+    let mut extinct: Vec<String> = Vec::new();
+    for survivor in &survivors {
+        let Some(dynasty) = survivor.dynasty.as_deref() else {
+            continue;
+        };
+        if living.get(dynasty).is_some_and(|&(alive, _)| alive == 0)
+            && !extinct.iter().any(|d| d == dynasty)
+        {
+            extinct.push(dynasty.to_string());
+        }
+    }
+    Ok(SurvivorReport {
+        end: end.to_calendar(&init.calendar),
+        survivors,
+        extinct,
+    })
+}
+
+/// Give each of the survivors [`check_survivors`] finds their new death in
+/// the history file of `dir`, returning what was done.
+pub fn repair_survivors(dir: &Path, config: &SimConfig) -> Result<SurvivorReport, SimError> {
+    let report = check_survivors(dir, config)?;
+    if report.survivors.is_empty() {
+        return Ok(report);
+    }
+    let calendar = &config.initialization.calendar;
+    let path = dir.join(HISTORY_FILE);
+    let bytes = fs::read(&path).map_err(|source| SimError::Io {
+        path: path.clone(),
+        source,
+    })?;
+    let dated = |date: paradox_script::Date| Date::new(date.year, date.month, date.day);
+    let age = |survivor: &Survivor| {
+        survivor
+            .birth
+            .from_calendar(calendar)
+            .years_until(survivor.new_death.from_calendar(calendar))
+    };
+    let read_error = |err| SimError::History {
+        message: format!("the {HISTORY_FILE} file cannot be read: {err}"),
+    };
+
+    // A death the history dates after the end moves to the new date, along
+    // with whatever else happens that day; a character defined twice dies
+    // in their first definition.
+    let mut moved: HashMap<&str, &Survivor> = report
+        .survivors
+        .iter()
+        .filter(|survivor| survivor.death.is_some())
+        .map(|survivor| (survivor.id.as_str(), survivor))
+        .collect();
+    let mut character = None;
+    let mut ages: HashMap<usize, i32> = HashMap::new();
+    let text = rewrite_words(&String::from_utf8_lossy(&bytes), |word: &Word| {
+        if word.depth == 0 && word.is_key {
+            character = moved.get(word.text).copied();
+            return None;
+        }
+        let survivor = character.filter(|_| word.depth == 1 && word.is_key)?;
+        let date = paradox_script::Date::parse(word.text).map(dated)?;
+        if survivor.death != Some(date) {
+            return None;
+        }
+        moved.remove(survivor.id.as_str());
+        character = None;
+        ages.insert(word.line, age(survivor));
+        Some(survivor.new_death.to_string())
+    })
+    .map_err(read_error)?;
+    // The age is noted after the date, or on the line above it.
+    let mut text: String = text
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(i, line)| {
+            let age = ages.get(&(i + 1)).or_else(|| ages.get(&(i + 2)));
+            match (age, line.find("# Died at age ")) {
+                (Some(age), Some(at)) => {
+                    let end = line.trim_end_matches(['\r', '\n']).len();
+                    format!("{}# Died at age {age}{}", &line[..at], &line[end..])
+                }
+                _ => line.to_string(),
+            }
+        })
+        .collect();
+
+    let mut deaths: HashMap<&str, String> = report
+        .survivors
+        .iter()
+        .filter(|survivor| survivor.death.is_none())
+        .map(|survivor| {
+            (
+                survivor.id.as_str(),
+                death_block(survivor.new_death, age(survivor)),
+            )
+        })
+        .collect();
+    if !deaths.is_empty() {
+        text = append_to_blocks(&text, |id| deaths.remove(id)).map_err(read_error)?;
+    }
+    paradox_script::write_file(&path, &text).map_err(|source| SimError::Io { path, source })?;
+    Ok(report)
+}
+
+/// The dated block of a death on `date` at `age`, indented to go in a
+/// character's block.
+fn death_block(date: Date, age: i32) -> String {
+    let mut death = Block::default();
+    death.push("death_reason", Scalar::Word(DEATH_REASON.into()));
+    let mut entry = Block::default();
+    entry.push("death", death);
+    let mut block = Block::default();
+    block.push_comment(format!("Died at age {age}"));
+    block.push(&date.to_string(), entry);
+    paradox_script::to_string(&block)
+        .lines()
+        .map(|line| format!("\t{line}\n"))
+        .collect()
+}
+
+/// Note that `id` was seen on `date`.
+fn see<'a>(last_seen: &mut HashMap<&'a str, Date>, id: &'a str, date: Date) {
+    let seen = last_seen.entry(id).or_insert(date);
+    *seen = (*seen).max(date);
+}
+
+/// The highest Númenórean blood tier of `person`'s traits, 0 for none.
+fn blood_tier(person: &Person) -> u32 {
+    person
+        .traits
+        .iter()
+        .filter_map(|name| name.strip_prefix("blood_of_numenor_")?.parse().ok())
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn death_block_is_tabbed_into_the_character() {
+        assert_eq!(
+            death_block(Date::new(7001, 3, 9), 142),
+            "\t# Died at age 142\n\t7001.03.09 = {\n\t\tdeath = {\n\t\t\tdeath_reason = death_natural_causes\n\t\t}\n\t}\n"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Runs of the repository's own config, and output directories of small
+//! hand-written histories, for tests.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::SimConfig;
+use crate::export::HISTORY_FILE;
+use crate::houses::HOUSES_FILE;
+use crate::localization::LOCALIZATION_DIR;
+use crate::names::NameLists;
+use crate::people::TITLES_FILE;
+use crate::simulation::Simulation;
+
+/// The repository's directory.
+fn repo() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+}
+
+/// The config the engine ships with.
+pub(crate) fn config() -> SimConfig {
+    SimConfig::load(&repo().join("config")).unwrap()
+}
+
+/// A run of `config` with `seed`, before it has ticked.
+pub(crate) fn simulation(config: SimConfig, seed: u64) -> Simulation {
+    Simulation::new(
+        config,
+        NameLists::new(repo().join("name_lists")),
+        Some(seed),
+    )
+}
+
+/// The shipped config run to its end with `seed`.
+pub(crate) fn run(seed: u64) -> Simulation {
+    let mut simulation = simulation(config(), seed);
+    simulation.run();
+    simulation
+}
+
+/// A fresh output directory for the test `name`, holding the character
+/// history `history`.
+pub(crate) fn output(name: &str, history: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ck3gen-output-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join(HISTORY_FILE), history).unwrap();
+    dir
+}
+
+/// A small family: Aldor of `dynasty_a`, who holds `k_gondor` from 6030
+/// until he dies in battle in 6060, his wife Beren of `dynasty_b`, and their
+/// son Anborn, who founds the cadet house `house_a_cadet` and succeeds him.
+pub(crate) const FAMILY: &str = "\
+# Seed: 7
+
+lineofa1 = {
+\tname = \"Aldor\"
+\tdynasty = dynasty_a
+\tculture = gondorian
+\treligion = faith_gondor
+\ttrait = brave
+\tdiplomacy = 5
+\t6000.1.1 = { birth = yes }
+\t6025.3.1 = { add_spouse = lineofb1 }
+\t6060.1.1 = { death = { death_reason = death_battle } }
+}
+lineofb1 = {
+\tname = \"Beren\"
+\tfemale = yes
+\tdynasty = dynasty_b
+\tculture = rohirric
+\treligion = faith_rohan
+\t6002.1.1 = { birth = yes }
+}
+lineofa2 = {
+\tname = \"Anborn\"
+\tfather = lineofa1
+\tmother = lineofb1
+\tdynasty_house = house_a_cadet
+\tculture = gondorian
+\treligion = faith_gondor
+\t6026.5.1 = { birth = yes trait = shy }
+}
+";
+
+/// A fresh output directory for the test `name`, holding [`FAMILY`] with its
+/// house, title history and English dynasty names.
+pub(crate) fn family(name: &str) -> PathBuf {
+    let dir = output(name, FAMILY);
+    fs::write(
+        dir.join(HOUSES_FILE),
+        "house_a_cadet = {\n\tname = dynn_house_a_cadet\n\tdynasty = dynasty_a\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join(TITLES_FILE),
+        "k_gondor = {\n\t6030.1.1 = { holder = lineofa1 }\n\t6060.1.1 = { holder = lineofa2 }\n}\n",
+    )
+    .unwrap();
+    let english = dir.join(LOCALIZATION_DIR).join("english");
+    fs::create_dir_all(&english).unwrap();
+    fs::write(
+        english.join("lotr_dynasty_names_l_english.yml"),
+        "l_english:\n dynn_a:0 \"Alda\"\n dynn_b:0 \"Bregol\"\n dynn_house_a_cadet:0 \"Anbornion\"\n",
+    )
+    .unwrap();
+    dir
+}
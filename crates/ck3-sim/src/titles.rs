@@ -0,0 +1,1111 @@
+//! Title histories worked out from who was born and died when.
+//!
+//! Each dynasty's titles, those its config lists or else a single
+//! placeholder title, go to its progenitor at birth and pass on at each
+//! holder's death under the title's succession and gender laws: down the
+//! holder's line, the eldest child first or under ultimogeniture the
+//! youngest, through dead children to their own and then up to the holder's
+//! parent; to the dynasty's eldest member under seniority; shared out among
+//! the holder's children under partition; and under elective succession to
+//! whom the dynasty's adults vote for, each for their closest kin. Cognatic
+//! laws put their preferred sex first, and bastards only inherit when no one
+//! else can. A title whose line runs out stays with its last holder.
+//!
+//! [`write_title_histories`] reads the history of an output directory,
+//! whichever engine wrote it, and writes every title with its holders to
+//! `title_history.txt`, as the Python engine does. The titles a dynasty's
+//! config lists are also written as CK3 landed title history, one
+//! `history/titles/<dynasty>.txt` per dynasty, with the liege and
+//! development their config gives them, ready to drop into a mod.
+//!
+//! Heirs who first inherit as children are given a guardian from the age of
+//! six, or from their inheritance if later: the living adult relative
+//! closest to them in the family tree, the elder on a tie. The guardianship
+//! is written into the ward's block of the history, and the guardian may
+//! leave them with an education of their own focus or of a tier higher,
+//! likelier from a diligent guardian and less likely from a lazy one.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use ck3_config::{Calendar, Dynasty, DynastyTitle, GenderLaw, InitializationConfig, Succession};
+use paradox_script::{append_to_blocks, rewrite_words, Block, Scalar, Word};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+use crate::date::Date;
+use crate::error::SimError;
+use crate::export::{history_seed, HISTORY_FILE};
+use crate::people::{parse_people, read_house_dynasties, Person, TITLES_DIR, TITLES_FILE};
+use crate::random::chance;
+
+/// Title a dynasty holds when its config lists none of its own.
+const PLACEHOLDER_TITLE: &str = "placeholder_title";
+/// Date standing in for the death of characters still alive.
+const LIVING: Date = Date {
+    year: 9999,
+    month: 12,
+    day: 31,
+};
+/// Age from which dynasty members vote and stand in elections.
+const ELECTOR_AGE: i32 = 16;
+/// Age from which an heir inheriting under [`ELECTOR_AGE`] is given a
+/// guardian.
+const GUARDIAN_FROM_AGE: i32 = 6;
+/// Chance of a ward taking up the focus of their guardian's education.
+const GUARDIAN_FOCUS_CHANCE: f64 = 0.5;
+/// Chance of a ward gaining an education tier from a better educated
+/// guardian.
+const GUARDIAN_TIER_CHANCE: f64 = 0.5;
+/// How a guardian's personality changes the chance of their ward gaining a
+/// tier.
+const GUARDIAN_TRAIT_TIER_CHANCE: [(&str, f64); 2] = [("diligent", 0.25), ("lazy", -0.25)];
+/// Highest education tier.
+const MAX_EDUCATION_TIER: u32 = 5;
+
+/// The succession and gender laws a title passes under.
+type Laws = (Succession, GenderLaw);
+
+/// The holders of a title, each with the date they took it, and the date
+/// its line ran out if it did.
+struct Reigns {
+    holders: Vec<(usize, Date)>,
+    vacated: Option<Date>,
+}
+
+/// A dynasty's title and who held it.
+struct TitleHistory<'c> {
+    title: String,
+    dynasty: String,
+    /// The config of a title of the dynasty's own, not a placeholder.
+    landed: Option<&'c DynastyTitle>,
+    reigns: Reigns,
+}
+
+/// An education trait's skill and tier, such as `("martial", 3)`.
+type Education<'p> = (&'p str, u32);
+
+/// A ward and their guardian from `date` on, with the education the
+/// guardian leaves them with.
+struct Guardianship<'p> {
+    ward: usize,
+    guardian: usize,
+    date: Date,
+    education: Option<Education<'p>>,
+}
+
+/// A character as succession sees them, dated in simulation dates.
+struct Member<'p> {
+    person: &'p Person,
+    dynasty: Option<&'p str>,
+    bastard: bool,
+    education: Option<Education<'p>>,
+    birth: Date,
+    /// [`LIVING`] for those still alive.
+    death: Date,
+}
+
+/// The characters of a history and how they are related.
+struct Family<'p> {
+    members: Vec<Member<'p>>,
+    /// Each member's children, in file order.
+    children: Vec<Vec<usize>>,
+    /// Each member's known ancestors, themselves included, with the number
+    /// of generations up.
+    ancestors: Vec<HashMap<usize, u32>>,
+}
+
+/// Write the title history and the landed title histories of the character
+/// history in `dir`, under the laws of the dynasties of `init`, replacing the
+/// landed title histories written before, and add the guardians of child
+/// heirs to the history.
+pub fn write_title_histories(dir: &Path, init: &InitializationConfig) -> Result<(), SimError> {
+    let history_path = dir.join(HISTORY_FILE);
+    let bytes = fs::read(&history_path).map_err(|source| SimError::Io {
+        path: history_path.clone(),
+        source,
+    })?;
+    let history = String::from_utf8_lossy(&bytes);
+    let people = parse_people(&history)?;
+    let house_dynasties = read_house_dynasties(dir);
+    let family = Family::new(&people, &house_dynasties, &init.calendar);
+    let titles = family.title_histories(&init.dynasties);
+    let text = paradox_script::to_string(&family.title_history(&titles, &init.calendar));
+    let path = dir.join(TITLES_FILE);
+    paradox_script::write_file(&path, &text).map_err(|source| SimError::Io { path, source })?;
+
+    let folder = dir.join(TITLES_DIR);
+    // Left in place, the file of a dynasty no longer configured would be
+    // read back as part of this history.
+    if let Ok(entries) = fs::read_dir(&folder) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "txt") {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+    for (dynasty, file) in family.landed_titles(&titles, &init.calendar) {
+        let path = folder.join(format!("{dynasty}.txt"));
+        fs::create_dir_all(&folder)
+            .and_then(|()| paradox_script::write_file(&path, &paradox_script::to_string(&file)))
+            .map_err(|source| SimError::Io { path, source })?;
+    }
+
+    let mut rng = ChaCha12Rng::seed_from_u64(history_seed(&history).unwrap_or_default());
+    let guardianships = family.guardianships(&titles, &mut rng);
+    if guardianships.is_empty() {
+        return Ok(());
+    }
+    let text = family
+        .with_guardians(&history, &guardianships, &init.calendar)
+        .map_err(|err| SimError::History {
+            message: format!("the {HISTORY_FILE} file cannot be read: {err}"),
+        })?;
+    paradox_script::write_file(&history_path, &text).map_err(|source| SimError::Io {
+        path: history_path,
+        source,
+    })
+}
+
+impl<'p> Family<'p> {
+    fn new(
+        people: &'p [Person],
+        house_dynasties: &'p HashMap<String, String>,
+        calendar: &Calendar,
+    ) -> Self {
+        let dated = |date: paradox_script::Date| {
+            Date::new(date.year, date.month, date.day).from_calendar(calendar)
+        };
+        let members: Vec<Member> = people
+            .iter()
+            .map(|person| Member {
+                person,
+                dynasty: person.lineage(house_dynasties),
+                bastard: person.traits.iter().any(|name| name == "bastard"),
+                education: person.traits.iter().find_map(|name| education(name)),
+                birth: person.birth.map_or(Date::new(0, 1, 1), dated),
+                death: person.death.map_or(LIVING, dated),
+            })
+            .collect();
+        let index: HashMap<&str, usize> = people
+            .iter()
+            .enumerate()
+            .map(|(i, person)| (person.id.as_str(), i))
+            .collect();
+        let parents: Vec<[Option<usize>; 2]> = people
+            .iter()
+            .map(|person| {
+                [&person.father, &person.mother]
+                    .map(|parent| parent.as_deref().and_then(|id| index.get(id).copied()))
+            })
+            .collect();
+        let mut children = vec![Vec::new(); people.len()];
+        for (child, parents) in parents.iter().enumerate() {
+            for &parent in parents.iter().flatten() {
+                children[parent].push(child);
+            }
+        }
+        let ancestors = (0..people.len())
+            .map(|i| {
+                let mut found = HashMap::from([(i, 0)]);
+                let mut frontier = vec![i];
+                while !frontier.is_empty() {
+                    let mut next = Vec::new();
+                    for person in frontier {
+                        for &parent in parents[person].iter().flatten() {
+                            if !found.contains_key(&parent) {
+                                found.insert(parent, found[&person] + 1);
+                                next.push(parent);
+                            }
+                        }
+                    }
+                    frontier = next;
+                }
+                found
+            })
+            .collect();
+        Self {
+            members,
+            children,
+            ancestors,
+        }
+    }
+
+    /// The titles of each dynasty of the history with a progenitor, in the
+    /// order the dynasties first appear, under the laws `dynasties`
+    /// configure.
+    fn title_histories<'c>(&self, dynasties: &'c [Dynasty]) -> Vec<TitleHistory<'c>> {
+        let mut seen = HashSet::new();
+        let mut histories = Vec::new();
+        for member in &self.members {
+            let Some(dynasty) = member.dynasty else {
+                continue;
+            };
+            if !seen.insert(dynasty) {
+                continue;
+            }
+            let Some(progenitor) = self.progenitor(dynasty) else {
+                continue;
+            };
+            let titles = titles_of(dynasties.iter().find(|d| d.dynasty_id == dynasty));
+            let laws: Vec<Laws> = titles.iter().map(|(_, laws, _)| *laws).collect();
+            let reigns = self.realm(progenitor, &laws);
+            histories.extend(
+                titles
+                    .into_iter()
+                    .zip(reigns)
+                    .map(|((title, _, landed), reigns)| TitleHistory {
+                        title,
+                        dynasty: dynasty.to_string(),
+                        landed,
+                        reigns,
+                    }),
+            );
+        }
+        histories
+    }
+
+    /// The contents of the title history file of `titles`.
+    fn title_history(&self, titles: &[TitleHistory], calendar: &Calendar) -> Block {
+        let mut file = Block::default();
+        for history in titles {
+            let mut title = Block::default();
+            for &(holder, date) in &history.reigns.holders {
+                let person = self.members[holder].person;
+                let mut entry = Block::default();
+                entry.push_comment(person.name.clone());
+                entry.push("holder", Scalar::Word(person.id.clone()));
+                title.push(&date.to_calendar(calendar).to_string(), entry);
+            }
+            file.push(&history.title, title);
+        }
+        file
+    }
+
+    /// The landed title history file of each dynasty with titles of its own,
+    /// by dynasty ID. Each change of holder is dated, the first also setting
+    /// the title's liege and development, and a title is freed of its liege
+    /// on the day the liege's line runs out.
+    fn landed_titles(&self, titles: &[TitleHistory], calendar: &Calendar) -> Vec<(String, Block)> {
+        let vacated: HashMap<&str, Date> = titles
+            .iter()
+            .filter_map(|history| Some((history.title.as_str(), history.reigns.vacated?)))
+            .collect();
+        let mut files: Vec<(String, Block)> = Vec::new();
+        for history in titles {
+            let Some(landed) = history.landed else {
+                continue;
+            };
+            let mut entries: BTreeMap<Date, Block> = BTreeMap::new();
+            for &(holder, date) in &history.reigns.holders {
+                let person = self.members[holder].person;
+                let entry = entries.entry(date).or_default();
+                entry.push_comment(person.name.clone());
+                entry.push("holder", Scalar::Word(person.id.clone()));
+            }
+            let start = history.reigns.holders[0].1;
+            let first = entries.entry(start).or_default();
+            if let Some(liege) = &landed.liege {
+                first.push("liege", Scalar::Word(liege.clone()));
+            }
+            if let Some(development) = landed.development {
+                first.push(
+                    "change_development_level",
+                    Scalar::Integer(development.into()),
+                );
+            }
+            let freed = landed.liege.as_deref().and_then(|liege| vacated.get(liege));
+            if let Some(&freed) = freed {
+                if start <= freed && freed < history.reigns.vacated.unwrap_or(LIVING) {
+                    let entry = entries.entry(freed).or_default();
+                    entry.push("liege", Scalar::Integer(0));
+                }
+            }
+
+            let mut title = Block::default();
+            for (date, entry) in entries {
+                title.push(&date.to_calendar(calendar).to_string(), entry);
+            }
+            match files
+                .iter_mut()
+                .find(|(dynasty, _)| *dynasty == history.dynasty)
+            {
+                Some((_, file)) => file.push(&history.title, title),
+                None => {
+                    let mut file = Block::default();
+                    file.push(&history.title, title);
+                    files.push((history.dynasty.clone(), file));
+                }
+            }
+        }
+        files
+    }
+
+    /// The guardians of the heirs who first inherited one of `titles` as
+    /// children, in the order they inherited.
+    fn guardianships(
+        &self,
+        titles: &[TitleHistory],
+        rng: &mut ChaCha12Rng,
+    ) -> Vec<Guardianship<'p>> {
+        let mut inherited: HashMap<usize, Date> = HashMap::new();
+        for history in titles {
+            for &(heir, date) in &history.reigns.holders[1..] {
+                let first = inherited.entry(heir).or_insert(date);
+                *first = (*first).min(date);
+            }
+        }
+        let mut inherited: Vec<(usize, Date)> = inherited.into_iter().collect();
+        inherited.sort_by_key(|&(ward, date)| (date, &self.members[ward].person.id));
+
+        let mut guardianships = Vec::new();
+        for (ward, date) in inherited {
+            if self.of_age_at(ward, date) {
+                continue;
+            }
+            let date = date.max(self.members[ward].birth.years_later(GUARDIAN_FROM_AGE));
+            if !self.alive_at(ward, date) {
+                continue;
+            }
+            let Some(guardian) = self.guardian(ward, date) else {
+                continue;
+            };
+            guardianships.push(Guardianship {
+                ward,
+                guardian,
+                date,
+                education: self.guardian_education(ward, guardian, rng),
+            });
+        }
+        guardianships
+    }
+
+    /// The living adult relative closest to `ward` on `date`, the elder on
+    /// a tie.
+    fn guardian(&self, ward: usize, date: Date) -> Option<usize> {
+        (0..self.members.len())
+            .filter(|&member| {
+                member != ward && self.of_age_at(member, date) && self.alive_at(member, date)
+            })
+            .map(|member| (self.kin_distance(ward, member), member))
+            .filter(|&(distance, _)| distance != u32::MAX)
+            .min_by_key(|&(distance, member)| {
+                let m = &self.members[member];
+                (distance, m.birth, &m.person.id)
+            })
+            .map(|(_, member)| member)
+    }
+
+    /// The education `ward` ends up with under `guardian`: they may take up
+    /// the guardian's focus, and gain a tier from a guardian with a higher
+    /// one.
+    fn guardian_education(
+        &self,
+        ward: usize,
+        guardian: usize,
+        rng: &mut ChaCha12Rng,
+    ) -> Option<Education<'p>> {
+        let education = self.members[ward].education;
+        let ((mut skill, mut tier), (guardian_skill, guardian_tier)) =
+            (education?, self.members[guardian].education?);
+        if guardian_skill != skill && chance(rng, GUARDIAN_FOCUS_CHANCE) {
+            skill = guardian_skill;
+        }
+        if guardian_tier > tier {
+            let traits = &self.members[guardian].person.traits;
+            let modifier: f64 = GUARDIAN_TRAIT_TIER_CHANCE
+                .iter()
+                .filter(|(name, _)| traits.iter().any(|t| t == name))
+                .map(|(_, modifier)| modifier)
+                .sum();
+            if chance(rng, GUARDIAN_TIER_CHANCE + modifier) {
+                tier = (tier + 1).min(MAX_EDUCATION_TIER);
+            }
+        }
+        Some((skill, tier))
+    }
+
+    /// The history file `history` with each of `guardianships` added to the
+    /// end of the ward's block and the ward's education trait changed to the
+    /// one the guardian left them with.
+    fn with_guardians(
+        &self,
+        history: &str,
+        guardianships: &[Guardianship],
+        calendar: &Calendar,
+    ) -> Result<String, paradox_script::ParseError> {
+        let by_ward: HashMap<&str, &Guardianship> = guardianships
+            .iter()
+            .map(|guardianship| {
+                (
+                    self.members[guardianship.ward].person.id.as_str(),
+                    guardianship,
+                )
+            })
+            .collect();
+        let mut ward = None;
+        let history = rewrite_words(history, |word: &Word| {
+            if word.depth == 0 && word.is_key {
+                ward = by_ward.get(word.text).copied();
+                return None;
+            }
+            let (skill, tier) = ward?.education?;
+            (word.key == Some("trait") && !word.is_key && education(word.text).is_some())
+                .then(|| format!("education_{skill}_{tier}"))
+        })?;
+        let mut blocks: HashMap<&str, String> = by_ward
+            .into_iter()
+            .map(|(id, guardianship)| (id, self.guardian_block(guardianship, calendar)))
+            .collect();
+        append_to_blocks(&history, |id| blocks.remove(id))
+    }
+
+    /// The dated block of `guardianship`, indented to go in the ward's
+    /// block.
+    fn guardian_block(&self, guardianship: &Guardianship, calendar: &Calendar) -> String {
+        let guardian = &self.members[guardianship.guardian].person.id;
+        let mut effect = Block::default();
+        effect.push(
+            "set_relation_guardian",
+            Scalar::Word(format!("character:{guardian}")),
+        );
+        let mut entry = Block::default();
+        entry.push("effect", effect);
+        let age = self.members[guardianship.ward]
+            .birth
+            .years_until(guardianship.date);
+        let mut block = Block::default();
+        block.push_comment(format!("Guardian at age {age}"));
+        block.push(&guardianship.date.to_calendar(calendar).to_string(), entry);
+        paradox_script::to_string(&block)
+            .lines()
+            .map(|line| format!("\t{line}\n"))
+            .collect()
+    }
+
+    /// The member of `dynasty` numbered 1, such as `lineofelros1`.
+    fn progenitor(&self, dynasty: &str) -> Option<usize> {
+        self.members.iter().position(|member| {
+            let id = &member.person.id;
+            member.dynasty == Some(dynasty)
+                && id.ends_with('1')
+                && !id[..id.len() - 1].ends_with(|c: char| c.is_ascii_digit())
+        })
+    }
+
+    /// The holders of titles under each of `laws` from `progenitor` on.
+    /// Holders die in date order, so a ruler holding several titles passes
+    /// them all on at once, and partitioned titles are split among the
+    /// ruler's children.
+    fn realm(&self, progenitor: usize, laws: &[Laws]) -> Vec<Reigns> {
+        let start = self.members[progenitor].birth;
+        let mut reigns: Vec<Reigns> = (0..laws.len())
+            .map(|_| Reigns {
+                holders: vec![(progenitor, start)],
+                vacated: None,
+            })
+            .collect();
+        let mut holders = vec![Some(progenitor); laws.len()];
+        while let Some(ruler) = holders
+            .iter()
+            .flatten()
+            .copied()
+            .min_by_key(|&holder| self.members[holder].death)
+        {
+            let death = self.members[ruler].death;
+            if death == LIVING {
+                break;
+            }
+            let held: Vec<usize> = (0..laws.len())
+                .filter(|&title| holders[title] == Some(ruler))
+                .collect();
+            for (title, heir) in self.divide(ruler, &held, laws) {
+                holders[title] = heir;
+                match heir {
+                    Some(heir) => reigns[title].holders.push((heir, death)),
+                    None => reigns[title].vacated = Some(death),
+                }
+            }
+        }
+        reigns
+    }
+
+    /// The heir of each of the titles `held` at `ruler`'s death, if anyone
+    /// can take it.
+    fn divide(&self, ruler: usize, held: &[usize], laws: &[Laws]) -> Vec<(usize, Option<usize>)> {
+        let mut successors = Vec::new();
+        let mut partitioned: Vec<(GenderLaw, Vec<usize>)> = Vec::new();
+        for &title in held {
+            let (succession, gender_law) = laws[title];
+            if succession != Succession::Partition {
+                successors.push((title, self.heir(ruler, succession, gender_law)));
+                continue;
+            }
+            match partitioned.iter_mut().find(|(law, _)| *law == gender_law) {
+                Some((_, titles)) => titles.push(title),
+                None => partitioned.push((gender_law, vec![title])),
+            }
+        }
+        for (gender_law, titles) in partitioned {
+            successors.extend(self.partition(ruler, &titles, gender_law));
+        }
+        successors
+    }
+
+    /// Who takes a title of `ruler`'s at their death, if anyone can.
+    fn heir(&self, ruler: usize, succession: Succession, gender_law: GenderLaw) -> Option<usize> {
+        let death = self.members[ruler].death;
+        match succession {
+            Succession::Seniority => self.eldest(ruler, gender_law, death),
+            Succession::Elective => self.elected(ruler, gender_law, death),
+            _ => [false, true].into_iter().find_map(|bastards| {
+                let mut visited = HashSet::new();
+                self.line_heir(ruler, succession, gender_law, death, &mut visited, bastards)
+            }),
+        }
+    }
+
+    /// The first of `ruler`'s line alive on `death`: a child, or the heir of
+    /// a child who died before, and failing those the parent the law
+    /// follows, or their heir.
+    fn line_heir(
+        &self,
+        ruler: usize,
+        succession: Succession,
+        gender_law: GenderLaw,
+        death: Date,
+        visited: &mut HashSet<usize>,
+        bastards: bool,
+    ) -> Option<usize> {
+        if !visited.insert(ruler) {
+            return None;
+        }
+        for child in self.ordered_children(ruler, succession, gender_law, death, bastards) {
+            if self.alive_at(child, death) {
+                return Some(child);
+            }
+            let heir = self.line_heir(child, succession, gender_law, death, visited, bastards);
+            if heir.is_some() {
+                return heir;
+            }
+        }
+        let parent = self.line_parent(ruler, gender_law)?;
+        if self.alive_at(parent, death) {
+            return Some(parent);
+        }
+        self.line_heir(parent, succession, gender_law, death, visited, bastards)
+    }
+
+    /// The children of `ruler` born by `death` who may inherit, in the order
+    /// of the law: eldest first, or youngest under ultimogeniture, the
+    /// preferred sex of a cognatic law ahead of the other.
+    fn ordered_children(
+        &self,
+        ruler: usize,
+        succession: Succession,
+        gender_law: GenderLaw,
+        death: Date,
+        bastards: bool,
+    ) -> Vec<usize> {
+        let mut children: Vec<usize> = self.children[ruler]
+            .iter()
+            .copied()
+            .filter(|&child| {
+                let member = &self.members[child];
+                (bastards || !member.bastard) && allows(gender_law, member) && member.birth <= death
+            })
+            .collect();
+        children.sort_by_key(|&child| self.members[child].birth);
+        if succession == Succession::Ultimogeniture {
+            children.sort_by(|&a, &b| self.members[b].birth.cmp(&self.members[a].birth));
+        }
+        self.preferred_first(children, gender_law)
+    }
+
+    /// The eldest member of `ruler`'s dynasty alive on `death` whom the law
+    /// allows, of its preferred sex if any such is.
+    fn eldest(&self, ruler: usize, gender_law: GenderLaw, death: Date) -> Option<usize> {
+        [false, true].into_iter().find_map(|bastards| {
+            let mut valid: Vec<usize> = self
+                .dynasty_members(ruler)
+                .filter(|&member| {
+                    let m = &self.members[member];
+                    (bastards || !m.bastard)
+                        && allows(gender_law, m)
+                        && m.birth <= death
+                        && self.alive_at(member, death)
+                })
+                .collect();
+            valid.sort_by_key(|&member| self.members[member].birth);
+            self.preferred_first(valid, gender_law).first().copied()
+        })
+    }
+
+    /// Share `titles` out among `ruler`'s children living on their death, of
+    /// the preferred sex if any are, eldest first, the first title going to
+    /// the eldest. Without such children, all of them go to the heir by
+    /// primogeniture.
+    fn partition(
+        &self,
+        ruler: usize,
+        titles: &[usize],
+        gender_law: GenderLaw,
+    ) -> Vec<(usize, Option<usize>)> {
+        let death = self.members[ruler].death;
+        let heirs = [false, true].into_iter().find_map(|bastards| {
+            let heirs: Vec<usize> = self
+                .ordered_children(
+                    ruler,
+                    Succession::Primogeniture,
+                    gender_law,
+                    death,
+                    bastards,
+                )
+                .into_iter()
+                .filter(|&child| self.alive_at(child, death))
+                .collect();
+            (!heirs.is_empty()).then_some(heirs)
+        });
+        let Some(mut heirs) = heirs else {
+            let heir = self.heir(ruler, Succession::Partition, gender_law);
+            return titles.iter().map(|&title| (title, heir)).collect();
+        };
+        self.keep_preferred(&mut heirs, gender_law);
+        titles
+            .iter()
+            .enumerate()
+            .map(|(i, &title)| (title, Some(heirs[i % heirs.len()])))
+            .collect()
+    }
+
+    /// The winner of the election at `ruler`'s death: the adult members of
+    /// the dynasty alive then vote among those of them the law allows, of
+    /// its preferred sex if any are, each for the candidate closest to them
+    /// in the family tree other than themselves if there is anyone else.
+    /// Most votes wins, and the elder on a tie. Bastards only stand if no
+    /// one else can.
+    fn elected(&self, ruler: usize, gender_law: GenderLaw, death: Date) -> Option<usize> {
+        let electors: Vec<usize> = self
+            .dynasty_members(ruler)
+            .filter(|&member| self.of_age_at(member, death) && self.alive_at(member, death))
+            .collect();
+        let mut candidates = [false, true].into_iter().find_map(|bastards| {
+            let candidates: Vec<usize> = electors
+                .iter()
+                .copied()
+                .filter(|&elector| {
+                    let m = &self.members[elector];
+                    (bastards || !m.bastard) && allows(gender_law, m)
+                })
+                .collect();
+            (!candidates.is_empty()).then_some(candidates)
+        })?;
+        self.keep_preferred(&mut candidates, gender_law);
+
+        let mut votes: HashMap<usize, u32> = HashMap::new();
+        for &elector in &electors {
+            let choice = candidates.iter().copied().min_by_key(|&candidate| {
+                (
+                    candidate == elector,
+                    self.kin_distance(elector, candidate),
+                    self.members[candidate].birth,
+                )
+            });
+            if let Some(choice) = choice {
+                *votes.entry(choice).or_default() += 1;
+            }
+        }
+        candidates.into_iter().min_by_key(|candidate| {
+            (
+                std::cmp::Reverse(votes.get(candidate).copied().unwrap_or(0)),
+                self.members[*candidate].birth,
+            )
+        })
+    }
+
+    /// Generations between `a` and `b` through their closest common
+    /// ancestor, or `u32::MAX` if they have none on record.
+    fn kin_distance(&self, a: usize, b: usize) -> u32 {
+        let (a, b) = (&self.ancestors[a], &self.ancestors[b]);
+        a.iter()
+            .filter_map(|(ancestor, up)| Some(up + b.get(ancestor)?))
+            .min()
+            .unwrap_or(u32::MAX)
+    }
+
+    /// The members of `ruler`'s dynasty, in file order.
+    fn dynasty_members(&self, ruler: usize) -> impl Iterator<Item = usize> + '_ {
+        let dynasty = self.members[ruler].dynasty;
+        (0..self.members.len()).filter(move |&member| self.members[member].dynasty == dynasty)
+    }
+
+    /// The parent a line goes up to under `gender_law`: the mother under
+    /// enatic laws and the father otherwise.
+    fn line_parent(&self, ruler: usize, gender_law: GenderLaw) -> Option<usize> {
+        let person = self.members[ruler].person;
+        let parent = match gender_law {
+            GenderLaw::Enatic | GenderLaw::EnaticCognatic => &person.mother,
+            _ => &person.father,
+        };
+        let parent = parent.as_deref()?;
+        self.members
+            .iter()
+            .position(|member| member.person.id == parent)
+    }
+
+    /// `members` with the law's preferred sex ahead of the other, each in
+    /// the order given.
+    fn preferred_first(&self, members: Vec<usize>, gender_law: GenderLaw) -> Vec<usize> {
+        let Some(female) = prefers_women(gender_law) else {
+            return members;
+        };
+        let (mut preferred, others): (Vec<usize>, Vec<usize>) = members
+            .into_iter()
+            .partition(|&member| self.members[member].person.female == female);
+        preferred.extend(others);
+        preferred
+    }
+
+    /// Leave only those of the law's preferred sex in `members` if any are.
+    fn keep_preferred(&self, members: &mut Vec<usize>, gender_law: GenderLaw) {
+        let Some(female) = prefers_women(gender_law) else {
+            return;
+        };
+        let is_preferred = |member: &usize| self.members[*member].person.female == female;
+        if members.iter().any(is_preferred) {
+            members.retain(is_preferred);
+        }
+    }
+
+    fn alive_at(&self, member: usize, date: Date) -> bool {
+        self.members[member].death > date
+    }
+
+    fn of_age_at(&self, member: usize, date: Date) -> bool {
+        self.members[member].birth.years_later(ELECTOR_AGE) <= date
+    }
+}
+
+/// The titles of `dynasty`, each with its laws and its config if it is a
+/// landed title: those it lists, else a placeholder title under its own laws,
+/// or for a dynasty not configured, under male-preference primogeniture.
+fn titles_of(dynasty: Option<&Dynasty>) -> Vec<(String, Laws, Option<&DynastyTitle>)> {
+    let Some(dynasty) = dynasty else {
+        return vec![(
+            PLACEHOLDER_TITLE.to_string(),
+            (Succession::Primogeniture, GenderLaw::AgnaticCognatic),
+            None,
+        )];
+    };
+    let laws = (dynasty.succession, dynasty.gender_law);
+    if dynasty.titles.is_empty() {
+        return vec![(PLACEHOLDER_TITLE.to_string(), laws, None)];
+    }
+    dynasty
+        .titles
+        .iter()
+        .filter(|title| !title.id.is_empty())
+        .map(|title| {
+            let succession = title.succession.unwrap_or(laws.0);
+            let gender_law = title.gender_law.unwrap_or(laws.1);
+            let landed = (title.id != PLACEHOLDER_TITLE).then_some(title);
+            (title.id.clone(), (succession, gender_law), landed)
+        })
+        .collect()
+}
+
+/// The skill and tier of the education trait `name`, if it is one.
+fn education(name: &str) -> Option<Education<'_>> {
+    let (skill, tier) = name.strip_prefix("education_")?.rsplit_once('_')?;
+    let is_skill = !skill.is_empty() && skill.bytes().all(|c| c.is_ascii_lowercase());
+    is_skill.then_some((skill, tier.parse().ok()?))
+}
+
+/// Whether `gender_law` lets `member` inherit at all.
+fn allows(gender_law: GenderLaw, member: &Member) -> bool {
+    match gender_law {
+        GenderLaw::Agnatic => !member.person.female,
+        GenderLaw::Enatic => member.person.female,
+        _ => true,
+    }
+}
+
+/// Whether `gender_law` prefers women, or men, or neither.
+fn prefers_women(gender_law: GenderLaw) -> Option<bool> {
+    match gender_law {
+        GenderLaw::Agnatic | GenderLaw::AgnaticCognatic => Some(false),
+        GenderLaw::Enatic | GenderLaw::EnaticCognatic => Some(true),
+        GenderLaw::AbsoluteCognatic => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_people;
+
+    use GenderLaw::{AbsoluteCognatic, Agnatic, AgnaticCognatic, Enatic};
+    use Succession::{Elective, Partition, Primogeniture, Seniority, Ultimogeniture};
+
+    /// A member of `dynasty_a`, with `extra` such as their father.
+    fn character(id: &str, female: bool, born: i32, died: Option<i32>, extra: &str) -> String {
+        let female = if female { "female = yes" } else { "" };
+        let died = died.map_or(String::new(), |year| {
+            format!("{year}.1.1 = {{ death = yes }}")
+        });
+        format!("{id} = {{ name = {id} {female} dynasty = dynasty_a {extra} {born}.1.1 = {{ birth = yes }} {died} }}\n")
+    }
+
+    /// The holders of titles under each of `laws`, from the progenitor of
+    /// `dynasty_a` in `history` on.
+    fn holders(history: &[String], laws: &[Laws]) -> Vec<Vec<String>> {
+        let people = parse_people(&history.concat()).unwrap();
+        let house_dynasties = HashMap::new();
+        let family = Family::new(&people, &house_dynasties, &Calendar::default());
+        let progenitor = family.progenitor("dynasty_a").unwrap();
+        family
+            .realm(progenitor, laws)
+            .into_iter()
+            .map(|reigns| {
+                let ids = reigns
+                    .holders
+                    .into_iter()
+                    .map(|(holder, _)| people[holder].id.clone());
+                ids.collect()
+            })
+            .collect()
+    }
+
+    /// A founder dead in 850 with a daughter, and a son dead before him with
+    /// a son of his own.
+    fn dead_son() -> Vec<String> {
+        vec![
+            character("lineofa1", false, 800, Some(850), ""),
+            character("lineofa2", true, 820, None, "father = lineofa1"),
+            character("lineofa3", false, 825, Some(840), "father = lineofa1"),
+            character("lineofa4", false, 838, None, "father = lineofa3"),
+        ]
+    }
+
+    #[test]
+    fn primogeniture_goes_down_a_dead_heirs_line() {
+        let laws = [
+            (Primogeniture, AgnaticCognatic),
+            (Primogeniture, AbsoluteCognatic),
+            (Primogeniture, Enatic),
+            (Ultimogeniture, AbsoluteCognatic),
+        ];
+        assert_eq!(
+            holders(&dead_son(), &laws),
+            [
+                ["lineofa1", "lineofa4"],
+                ["lineofa1", "lineofa2"],
+                ["lineofa1", "lineofa2"],
+                ["lineofa1", "lineofa4"],
+            ]
+        );
+    }
+
+    #[test]
+    fn seniority_passes_to_the_eldest_of_the_dynasty() {
+        let history = [
+            character("lineofa1", false, 800, Some(850), ""),
+            character("lineofa2", false, 820, Some(860), "father = lineofa1"),
+            character("lineofa3", false, 825, None, "father = lineofa1"),
+            character("lineofa4", false, 840, None, "father = lineofa2"),
+        ];
+        let laws = [
+            (Seniority, AgnaticCognatic),
+            (Primogeniture, AgnaticCognatic),
+        ];
+        assert_eq!(
+            holders(&history, &laws),
+            [
+                ["lineofa1", "lineofa2", "lineofa3"],
+                ["lineofa1", "lineofa2", "lineofa4"],
+            ]
+        );
+    }
+
+    #[test]
+    fn partition_shares_titles_out_among_the_children() {
+        let history = [
+            character("lineofa1", false, 800, Some(850), ""),
+            character("lineofa2", false, 820, None, "father = lineofa1"),
+            character("lineofa3", true, 822, None, "father = lineofa1"),
+            character("lineofa4", false, 825, None, "father = lineofa1"),
+        ];
+        let male_preference = [(Partition, AgnaticCognatic); 3];
+        assert_eq!(
+            holders(&history, &male_preference),
+            [
+                ["lineofa1", "lineofa2"],
+                ["lineofa1", "lineofa4"],
+                ["lineofa1", "lineofa2"],
+            ]
+        );
+        let equal = [(Partition, AbsoluteCognatic); 3];
+        assert_eq!(
+            holders(&history, &equal),
+            [
+                ["lineofa1", "lineofa2"],
+                ["lineofa1", "lineofa3"],
+                ["lineofa1", "lineofa4"],
+            ]
+        );
+    }
+
+    #[test]
+    fn electors_vote_for_their_closest_kin() {
+        let history = [
+            character("lineofa1", false, 800, Some(850), ""),
+            character("lineofa2", false, 820, None, "father = lineofa1"),
+            character("lineofa3", false, 822, None, "father = lineofa1"),
+            character("lineofa4", false, 830, None, "father = lineofa3"),
+            character("lineofa5", false, 832, None, "father = lineofa3"),
+        ];
+        let laws = [
+            (Elective, AgnaticCognatic),
+            (Primogeniture, AgnaticCognatic),
+        ];
+        assert_eq!(
+            holders(&history, &laws),
+            [["lineofa1", "lineofa3"], ["lineofa1", "lineofa2"]]
+        );
+    }
+
+    #[test]
+    fn bastards_only_inherit_when_no_one_else_can() {
+        let mut history = vec![
+            character("lineofa1", false, 800, Some(850), ""),
+            character(
+                "lineofa2",
+                false,
+                815,
+                None,
+                "father = lineofa1 trait = bastard",
+            ),
+            character("lineofa3", false, 820, Some(840), "father = lineofa1"),
+        ];
+        let laws = [(Primogeniture, Agnatic)];
+        assert_eq!(holders(&history, &laws), [["lineofa1", "lineofa2"]]);
+        history.push(character("lineofa4", false, 835, None, "father = lineofa3"));
+        assert_eq!(holders(&history, &laws), [["lineofa1", "lineofa4"]]);
+    }
+
+    #[test]
+    fn a_title_without_an_heir_stays_with_its_last_holder() {
+        let history = [
+            character("lineofa1", false, 800, Some(850), ""),
+            character("lineofa2", true, 820, None, "father = lineofa1"),
+        ];
+        let laws = [(Primogeniture, Agnatic), (Primogeniture, AgnaticCognatic)];
+        assert_eq!(
+            holders(&history, &laws),
+            [vec!["lineofa1"], vec!["lineofa1", "lineofa2"]]
+        );
+    }
+
+    #[test]
+    fn landed_titles_are_freed_when_their_liege_runs_out() {
+        let history = [
+            character("lineofa1", false, 800, Some(850), ""),
+            character("lineofa2", true, 820, None, "father = lineofa1"),
+        ];
+        let people = parse_people(&history.concat()).unwrap();
+        let house_dynasties = HashMap::new();
+        let family = Family::new(&people, &house_dynasties, &Calendar::default());
+        let title = |id: &str, liege: Option<&str>, development| DynastyTitle {
+            id: id.to_string(),
+            succession: None,
+            gender_law: None,
+            liege: liege.map(str::to_string),
+            development,
+        };
+        let config = [title("k_a", None, None), title("d_a", Some("k_a"), Some(3))];
+        let laws = [(Primogeniture, Agnatic), (Primogeniture, AgnaticCognatic)];
+        let reigns = family.realm(family.progenitor("dynasty_a").unwrap(), &laws);
+        let titles: Vec<TitleHistory> = config
+            .iter()
+            .zip(reigns)
+            .map(|(landed, reigns)| TitleHistory {
+                title: landed.id.clone(),
+                dynasty: "dynasty_a".to_string(),
+                landed: Some(landed),
+                reigns,
+            })
+            .collect();
+        let files = family.landed_titles(&titles, &Calendar::default());
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, "dynasty_a");
+        assert_eq!(
+            paradox_script::to_string(&files[0].1),
+            "k_a = {\n\t800.01.01 = {\n\t\t# lineofa1\n\t\tholder = lineofa1\n\t}\n}\n\n\
+             d_a = {\n\t800.01.01 = {\n\t\t# lineofa1\n\t\tholder = lineofa1\n\t\tliege = k_a\n\t\tchange_development_level = 3\n\t}\n\
+             \t850.01.01 = {\n\t\t# lineofa2\n\t\tholder = lineofa2\n\t\tliege = 0\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn child_heirs_are_given_their_closest_adult_relative() {
+        let history = [
+            character("lineofa1", false, 800, Some(850), ""),
+            character("lineofa2", false, 845, None, "father = lineofa1"),
+            character("lineofa3", false, 825, None, "father = lineofa1"),
+            character("lineofa4", false, 830, None, "father = lineofa1"),
+            character("lineofa5", false, 810, None, ""),
+        ];
+        let people = parse_people(&history.concat()).unwrap();
+        let house_dynasties = HashMap::new();
+        let family = Family::new(&people, &house_dynasties, &Calendar::default());
+        let laws = [(Ultimogeniture, AgnaticCognatic)];
+        let reigns = family.realm(family.progenitor("dynasty_a").unwrap(), &laws);
+        let titles: Vec<TitleHistory> = reigns
+            .into_iter()
+            .map(|reigns| TitleHistory {
+                title: PLACEHOLDER_TITLE.to_string(),
+                dynasty: "dynasty_a".to_string(),
+                landed: None,
+                reigns,
+            })
+            .collect();
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        let guardianships = family.guardianships(&titles, &mut rng);
+        assert_eq!(guardianships.len(), 1);
+        let guardianship = &guardianships[0];
+        assert_eq!(people[guardianship.ward].id, "lineofa2");
+        assert_eq!(people[guardianship.guardian].id, "lineofa3");
+        assert_eq!(guardianship.date, Date::new(851, 1, 1));
+    }
+
+    #[test]
+    fn guardians_are_written_into_the_wards_block() {
+        let history = "lineofa1 = {\n\tname = A\n\ttrait = education_martial_1\n}\n";
+        let people = parse_people(history).unwrap();
+        let house_dynasties = HashMap::new();
+        let family = Family::new(&people, &house_dynasties, &Calendar::default());
+        let guardianship = Guardianship {
+            ward: 0,
+            guardian: 0,
+            date: Date::new(7, 1, 1),
+            education: Some(("learning", 2)),
+        };
+        assert_eq!(
+            family
+                .with_guardians(history, &[guardianship], &Calendar::default())
+                .unwrap(),
+            "lineofa1 = {\n\tname = A\n\ttrait = education_learning_2\n\
+             \t# Guardian at age 7\n\t7.01.01 = {\n\t\teffect = {\n\
+             \t\t\tset_relation_guardian = character:lineofa1\n\t\t}\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn education_traits_give_their_skill_and_tier() {
+        assert_eq!(education("education_martial_3"), Some(("martial", 3)));
+        assert_eq!(education("education_martial_prowess_3"), None);
+        assert_eq!(education("diligent"), None);
+    }
+}
@@ -0,0 +1,304 @@
+//! Checking that nobody has traits that cannot go together.
+//!
+//! The game takes a history's traits as written, so a character can start
+//! out both brave and craven, with two educations or with more personality
+//! traits than a character is ever given. [`check_traits`] goes through the
+//! traits each character of a history gains and loses, in date order, and
+//! flags every one gained that goes against another they hold: one the
+//! config's personality traits exclude or, given the game's data, one it
+//! makes an opposite, another education, or one of the same group, such as
+//! another tier of a genetic trait; without the game's data, tiers are told
+//! by their numbered names. Personality traits held at once are at most as
+//! many as the config gives each character. Native runs draw personality traits by the same rules,
+//! so only hand-kept histories and those of the Python engine should have
+//! any to flag.
+
+use std::fs;
+use std::path::Path;
+
+use paradox_script::{Block, Date, Scalar, Value};
+
+use crate::config::SimConfig;
+use crate::error::SimError;
+use crate::export::HISTORY_FILE;
+use crate::lint::Problem;
+
+/// Category of the traits a character has one of at a time.
+const EDUCATION: &str = "education";
+/// Category of the personality traits.
+const PERSONALITY: &str = "personality";
+
+/// The trait conflicts of the history of the output directory `dir`, by the
+/// rules of `config` and its game data, in file and line order.
+pub fn check_traits(dir: &Path, config: &SimConfig) -> Result<Vec<Problem>, SimError> {
+    let path = dir.join(HISTORY_FILE);
+    let bytes = fs::read(&path).map_err(|source| SimError::Io { path, source })?;
+    let text = String::from_utf8_lossy(&bytes);
+    let text = text.strip_prefix(paradox_script::BOM).unwrap_or(&text);
+    let history = paradox_script::parse(text).map_err(|err| SimError::History {
+        message: format!("the {HISTORY_FILE} file cannot be read: {err}"),
+    })?;
+
+    let rules = Rules { config };
+    let mut problems = Vec::new();
+    for entry in history.entries() {
+        let Some(character) = entry.value.as_block() else {
+            continue;
+        };
+        let id = entry.key.text();
+        // Traits outside dated blocks are held from birth.
+        let mut changes: Vec<(Option<Date>, usize, bool, String)> = Vec::new();
+        for field in character.entries() {
+            match (field.key.value(), &field.value) {
+                (Scalar::Date(date), Value::Block(dated)) => {
+                    collect(dated, Some(*date), &mut changes);
+                }
+                _ if field.key.text() == "trait" => {
+                    if let Some(name) = field.value.as_scalar() {
+                        changes.push((None, field.line, true, name.text().into_owned()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        changes.sort_by_key(|(date, _, _, _)| *date);
+
+        let mut held: Vec<String> = Vec::new();
+        for (_, line, gained, name) in changes {
+            if !gained {
+                held.retain(|other| *other != name);
+                continue;
+            }
+            if held.contains(&name) {
+                continue;
+            }
+            if let Some(message) = rules.conflict(&id, &held, &name) {
+                problems.push(Problem {
+                    file: HISTORY_FILE.to_string(),
+                    line,
+                    message,
+                });
+            }
+            held.push(name);
+        }
+    }
+    Ok(problems)
+}
+
+/// The traits `block`, dated `date`, and the effects in it give and take
+/// away, with their lines.
+fn collect(
+    block: &Block,
+    date: Option<Date>,
+    changes: &mut Vec<(Option<Date>, usize, bool, String)>,
+) {
+    for entry in block.entries() {
+        let key = entry.key.text();
+        match &entry.value {
+            Value::Scalar(name) => {
+                let gained = match key.as_ref() {
+                    "trait" | "add_trait" => true,
+                    "remove_trait" => false,
+                    _ => continue,
+                };
+                changes.push((date, entry.line, gained, name.text().into_owned()));
+            }
+            Value::Block(effects) if key == "effect" => collect(effects, date, changes),
+            _ => {}
+        }
+    }
+}
+
+struct Rules<'a> {
+    config: &'a SimConfig,
+}
+
+impl Rules<'_> {
+    /// Why character `id`, holding the traits `held`, cannot gain `name`,
+    /// if they cannot.
+    fn conflict(&self, id: &str, held: &[String], name: &str) -> Option<String> {
+        let personality = &self.config.skills_and_traits.personality_traits;
+        let game = self.config.game_data.as_deref();
+        for other in held {
+            let excluded = [(name, other.as_str()), (other.as_str(), name)]
+                .into_iter()
+                .any(|(a, b)| {
+                    personality
+                        .traits
+                        .get(a)
+                        .is_some_and(|config| config.excludes.iter().any(|excluded| excluded == b))
+                });
+            if excluded || game.is_some_and(|game| game.are_opposites(name, other)) {
+                return Some(format!(
+                    "character '{id}' gains '{name}' while having its opposite '{other}'"
+                ));
+            }
+            if self.category(name) == Some(EDUCATION) && self.category(other) == Some(EDUCATION) {
+                return Some(format!(
+                    "character '{id}' gains the education '{name}' while having '{other}'"
+                ));
+            }
+            let group = |name| match game {
+                Some(game) => game.trait_group(name),
+                None => tiers(name),
+            };
+            if group(name).is_some() && group(name) == group(other) {
+                return Some(format!(
+                    "character '{id}' gains '{name}' while having '{other}', of the same group"
+                ));
+            }
+        }
+        if self.category(name) == Some(PERSONALITY) {
+            let count = held
+                .iter()
+                .filter(|other| self.category(other) == Some(PERSONALITY))
+                .count()
+                + 1;
+            let most = personality.total_traits_per_character as usize;
+            if count > most {
+                return Some(format!(
+                    "character '{id}' gains '{name}' as their personality trait {count}, \
+                     more than the {most} a character is given"
+                ));
+            }
+        }
+        None
+    }
+
+    /// The category of the trait `name`: the game's, or without the game's
+    /// data, education for an education trait and personality for one of
+    /// the config's personality traits.
+    fn category(&self, name: &str) -> Option<&str> {
+        if let Some(game) = &self.config.game_data {
+            if game.has_trait(name) {
+                return game.trait_category(name);
+            }
+        }
+        if name.starts_with("education_") {
+            Some(EDUCATION)
+        } else if self
+            .config
+            .skills_and_traits
+            .personality_traits
+            .traits
+            .contains_key(name)
+        {
+            Some(PERSONALITY)
+        } else {
+            None
+        }
+    }
+}
+
+/// The trait `name` is a tier of, by its numbered name, such as
+/// `intellect_good` for `intellect_good_2`; education traits are told apart
+/// by their category instead.
+fn tiers(name: &str) -> Option<&str> {
+    let (base, tier) = name.rsplit_once('_')?;
+    (!tier.is_empty() && tier.bytes().all(|b| b.is_ascii_digit())).then_some(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    /// The lines and messages of the trait conflicts of the history
+    /// `history`, by the rules of the shipped config.
+    fn conflicts(name: &str, history: &str) -> Vec<(usize, String)> {
+        let dir = testing::output(name, history);
+        let problems = check_traits(&dir, &testing::config()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        problems
+            .into_iter()
+            .map(|problem| (problem.line, problem.message))
+            .collect()
+    }
+
+    #[test]
+    fn opposite_traits_on_one_character_are_flagged() {
+        let history = "\
+a1 = {
+\ttrait = brave
+\t6020.1.1 = { trait = craven }
+}
+a2 = {
+\ttrait = calm
+\t6010.1.1 = { remove_trait = calm }
+\t6020.1.1 = { trait = wrathful }
+}
+";
+        assert_eq!(
+            conflicts("opposites", history),
+            [(
+                3,
+                "character 'a1' gains 'craven' while having its opposite 'brave'".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn second_educations_and_tiers_of_one_trait_are_flagged() {
+        let history = "\
+a1 = {
+\t6016.1.1 = { trait = education_martial_2 }
+\t6030.1.1 = { effect = { add_trait = education_learning_3 } }
+}
+a2 = {
+\ttrait = intellect_good_1
+\t6020.1.1 = { trait = intellect_good_3 }
+}
+";
+        assert_eq!(
+            conflicts("groups", history),
+            [
+                (
+                    3,
+                    "character 'a1' gains the education 'education_learning_3' while having \
+                     'education_martial_2'"
+                        .to_string()
+                ),
+                (
+                    7,
+                    "character 'a2' gains 'intellect_good_3' while having 'intellect_good_1', \
+                     of the same group"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn more_personality_traits_than_the_config_gives_are_flagged() {
+        let most = testing::config()
+            .skills_and_traits
+            .personality_traits
+            .total_traits_per_character as usize;
+        let traits = [
+            "chaste",
+            "content",
+            "diligent",
+            "forgiving",
+            "generous",
+            "honest",
+        ];
+        assert!(most < traits.len());
+        let mut history = "a1 = {\n".to_string();
+        for name in &traits[..=most] {
+            history.push_str(&format!("\ttrait = {name}\n"));
+        }
+        history.push_str("}\n");
+        assert_eq!(
+            conflicts("personality", &history),
+            [(
+                most + 2,
+                format!(
+                    "character 'a1' gains '{}' as their personality trait {}, more than the \
+                     {most} a character is given",
+                    traits[most],
+                    most + 1
+                )
+            )]
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! The `traitRules` of `skills_and_traits.json`.
+//!
+//! A character's personality traits are rolled by their configured weight,
+//! multiplied by the multiplier of every rule applying to them: rules see the
+//! character's culture, faith, dynasty and its flags, and year of birth as
+//! they are when the traits are rolled, at 16 or on creation for characters
+//! created grown, and the traits of the parents known by then.
+
+use std::collections::HashMap;
+
+use ck3_config::TraitRule;
+
+use crate::character::{Character, CharacterId};
+use crate::simulation::Simulation;
+
+impl Simulation {
+    /// The factor each personality trait's weight is multiplied by for `id`,
+    /// whose parents are `father` and `mother`; traits no rule applies to
+    /// are left out.
+    pub(crate) fn trait_multipliers(
+        &self,
+        id: CharacterId,
+        father: Option<CharacterId>,
+        mother: Option<CharacterId>,
+    ) -> HashMap<String, f64> {
+        let mut multipliers = HashMap::new();
+        let rules = &self.config.skills_and_traits.trait_rules;
+        if rules.is_empty() {
+            return multipliers;
+        }
+        let c = self.character(id);
+        let parents: Vec<&Character> = [father, mother]
+            .into_iter()
+            .flatten()
+            .map(|parent| self.character(parent))
+            .collect();
+        for rule in rules.iter().filter(|rule| self.applies(rule, c, &parents)) {
+            for name in &rule.traits {
+                *multipliers.entry(name.clone()).or_insert(1.0) *= rule.multiplier;
+            }
+        }
+        multipliers
+    }
+
+    fn applies(&self, rule: &TraitRule, c: &Character, parents: &[&Character]) -> bool {
+        let listed = |list: &[String], value: Option<&str>| {
+            list.is_empty() || value.is_some_and(|value| list.iter().any(|entry| entry == value))
+        };
+        let dynasty = self.dynasty_config(c.dynasty.as_deref());
+        let flagged = rule.dynasty_flags.iter().all(|flag| {
+            dynasty
+                .and_then(|dynasty| dynasty.flag(flag))
+                .unwrap_or(false)
+        });
+        let inherited = rule.parent_traits.is_empty()
+            || parents.iter().any(|parent| {
+                parent
+                    .traits
+                    .iter()
+                    .chain(&parent.personality_traits)
+                    .map(String::as_str)
+                    .chain(parent.congenital_traits.iter().copied())
+                    .any(|name| rule.parent_traits.iter().any(|wanted| wanted == name))
+            });
+        let year = c.birth.year;
+        listed(&rule.cultures, Some(&c.culture))
+            && listed(&rule.religions, Some(&c.religion))
+            && listed(&rule.dynasties, c.dynasty.as_deref())
+            && flagged
+            && inherited
+            && rule.born_from.is_none_or(|from| year >= from)
+            && rule.born_until.is_none_or(|until| year <= until)
+    }
+}
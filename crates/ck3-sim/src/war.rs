@@ -0,0 +1,117 @@
+//! Dynasties at war.
+//!
+//! Each year of one of its `wars`, every member of a dynasty of fighting age,
+//! its men and, if the war says so, its women, goes to battle. A fighter may
+//! die there, dying `death_battle`; one who lives may come back wounded, or
+//! maimed for good, and may come back with a trait of prowess. Progenitors
+//! are spared death in battle, as they are death by age, so every dynasty
+//! gets a chance to start.
+
+use ck3_config::War;
+
+use crate::character::{Character, CharacterId, EventKind, Sex};
+use crate::date::Date;
+use crate::mortality::BLOOD_TIER_MORTALITY_YEARS;
+use crate::random;
+use crate::simulation::{Simulation, FERTILE_FROM};
+
+/// Effective age after which characters no longer fight.
+const FIGHTING_UNTIL: i32 = 60;
+/// Death reason of those who fall in battle.
+const BATTLE_DEATH: &str = "death_battle";
+/// Traits of fighters who come back wounded, with their weights. The
+/// lasting injuries are rarer than a wound that heals.
+const WOUNDS: [(&str, f64); 5] = [
+    ("wounded_1", 6.0),
+    ("one_eyed", 1.0),
+    ("one_legged", 1.0),
+    ("maimed", 1.0),
+    ("disfigured", 1.0),
+];
+/// Traits of fighters seasoned by battle.
+const VETERAN_TRAITS: [&str; 5] = [
+    "lifestyle_blademaster",
+    "aggressive_attacker",
+    "unyielding_defender",
+    "reaver",
+    "flexible_leader",
+];
+
+impl Simulation {
+    /// Send the fighters of every dynasty at war in `year` to battle.
+    pub(crate) fn fight_wars(&mut self, year: i32) {
+        let wars: Vec<(String, War)> = self
+            .config
+            .initialization
+            .dynasties
+            .iter()
+            .flat_map(|d| {
+                d.wars
+                    .iter()
+                    .filter(|war| (war.start_year..=war.end_year).contains(&year))
+                    .map(|war| (d.dynasty_id.clone(), war.clone()))
+            })
+            .collect();
+        for (dynasty, war) in wars {
+            for id in self.ids().collect::<Vec<_>>() {
+                let c = self.character(id);
+                if c.dynasty.as_deref() == Some(dynasty.as_str()) && fights(c, &war) {
+                    self.battle(id, &war, year);
+                }
+            }
+        }
+    }
+
+    /// One year of battle for `id`.
+    fn battle(&mut self, id: CharacterId, war: &War, year: i32) {
+        if !self.character(id).is_progenitor
+            && random::chance(&mut self.rng, war.battle_death_chance)
+        {
+            self.character_mut(id).event_death_reason = Some(BATTLE_DEATH.to_string());
+            self.die(id, year);
+            return;
+        }
+        let date = Date::random_in(year, &mut self.rng).max(self.character(id).birth);
+        let mut gained = Vec::new();
+        if random::chance(&mut self.rng, war.wound_chance) {
+            let wounds: Vec<(&str, f64)> = WOUNDS
+                .into_iter()
+                .filter(|(name, _)| !has_gained(self.character(id), name))
+                .collect();
+            if let Some(wound) = random::weighted(&mut self.rng, wounds) {
+                gained.push(wound.to_string());
+            }
+        }
+        if random::chance(&mut self.rng, war.veteran_chance) {
+            let traits: Vec<&str> = VETERAN_TRAITS
+                .into_iter()
+                .filter(|name| !has_gained(self.character(id), name))
+                .collect();
+            if let Some(name) = random::pick(&mut self.rng, &traits) {
+                gained.push(name.to_string());
+            }
+        }
+        if !gained.is_empty() {
+            self.character_mut(id)
+                .add_event(date, EventKind::Traits(gained));
+        }
+    }
+}
+
+/// Whether `c` is alive and of an age and sex to fight in `war`.
+fn fights(c: &Character, war: &War) -> bool {
+    let effective_age = c.age - BLOOD_TIER_MORTALITY_YEARS * c.blood_tier as i32;
+    c.is_alive()
+        && (c.sex == Sex::Male || war.women_fight)
+        && c.age >= FERTILE_FROM
+        && effective_age <= FIGHTING_UNTIL
+}
+
+/// Whether `c` has trait `name`, from birth or gained since.
+fn has_gained(c: &Character, name: &str) -> bool {
+    c.traits.iter().any(|t| t == name)
+        || c.events.iter().any(|event| match &event.kind {
+            EventKind::Traits(traits) => traits.iter().any(|t| t == name),
+            _ => false,
+        })
+}
@@ -0,0 +1,302 @@
+//! The parsed form of a script file.
+
+use std::fmt;
+
+/// A `{ }` block, or a whole file: entries and bare values in file order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Block {
+    pub items: Vec<Item>,
+}
+
+impl Block {
+    /// The entries of the block, in order.
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Entry(entry) => Some(entry),
+            Item::Value(_) | Item::Comment(_) => None,
+        })
+    }
+
+    /// The bare values of the block, as in `{ 1 2 3 }`, in order.
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Value(value) => Some(value),
+            Item::Entry(_) | Item::Comment(_) => None,
+        })
+    }
+
+    /// The value of the first entry under `key`.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries()
+            .find(|entry| entry.key.text() == key)
+            .map(|entry| &entry.value)
+    }
+
+    /// The values of every entry under `key`, in order, as keys such as
+    /// `trait` may repeat.
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a Value> {
+        self.entries()
+            .filter(move |entry| entry.key.text() == key)
+            .map(|entry| &entry.value)
+    }
+
+    /// Add a `key = value` entry at the end of the block.
+    pub fn push(&mut self, key: &str, value: impl Into<Value>) {
+        self.items.push(Item::Entry(Entry {
+            key: Scalar::from_word(key),
+            operator: Operator::Assign,
+            value: value.into(),
+            line: 0,
+        }));
+    }
+
+    /// Add a bare value at the end of the block.
+    pub fn push_value(&mut self, value: impl Into<Value>) {
+        self.items.push(Item::Value(value.into()));
+    }
+
+    /// Add a `# text` comment line at the end of the block.
+    pub fn push_comment(&mut self, text: impl Into<String>) {
+        self.items.push(Item::Comment(text.into()));
+    }
+
+    /// The entries keyed by a date, in file order.
+    pub fn dated(&self) -> impl Iterator<Item = (Date, &Value)> {
+        self.entries().filter_map(|entry| match entry.key.value() {
+            Scalar::Date(date) => Some((*date, &entry.value)),
+            _ => None,
+        })
+    }
+}
+
+/// One item of a block.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    Entry(Entry),
+    /// A value without a key.
+    Value(Value),
+    /// A comment line, without its `#`, written before the item after it.
+    /// Parsing drops comments, so only blocks built to be written hold them.
+    Comment(String),
+}
+
+/// A `key = value` entry, or one with another operator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub key: Scalar,
+    pub operator: Operator,
+    pub value: Value,
+    /// 1-based line of the key, or 0 for an entry not read from a file.
+    pub line: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// `=`
+    Assign,
+    /// `==`
+    Equal,
+    /// `!=`
+    NotEqual,
+    /// `<`
+    Less,
+    /// `<=`
+    LessOrEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterOrEqual,
+    /// `?=`, which only applies when the key exists.
+    Exists,
+}
+
+impl Operator {
+    /// The operator written as `text`.
+    pub fn from_text(text: &str) -> Option<Self> {
+        Some(match text {
+            "=" => Operator::Assign,
+            "==" => Operator::Equal,
+            "!=" => Operator::NotEqual,
+            "<" => Operator::Less,
+            "<=" => Operator::LessOrEqual,
+            ">" => Operator::Greater,
+            ">=" => Operator::GreaterOrEqual,
+            "?=" => Operator::Exists,
+            _ => return None,
+        })
+    }
+
+    pub fn text(self) -> &'static str {
+        match self {
+            Operator::Assign => "=",
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::LessOrEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Exists => "?=",
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Scalar(Scalar),
+    Block(Block),
+    /// A block behind a tag, as in `color = rgb { 255 0 0 }`.
+    Tagged {
+        tag: String,
+        block: Block,
+    },
+}
+
+impl Value {
+    pub fn as_scalar(&self) -> Option<&Scalar> {
+        match self {
+            Value::Scalar(scalar) => Some(scalar),
+            _ => None,
+        }
+    }
+
+    pub fn as_block(&self) -> Option<&Block> {
+        match self {
+            Value::Block(block) | Value::Tagged { block, .. } => Some(block),
+            Value::Scalar(_) => None,
+        }
+    }
+}
+
+impl From<Scalar> for Value {
+    fn from(scalar: Scalar) -> Self {
+        Value::Scalar(scalar)
+    }
+}
+
+impl From<Block> for Value {
+    fn from(block: Block) -> Self {
+        Value::Block(block)
+    }
+}
+
+/// A key or value that is not a block, typed by its text. Quoted text is
+/// never anything but [`Scalar::Quoted`], and a number or date spelled
+/// otherwise than it would be written is [`Scalar::Spelled`], so that it is
+/// written back as it was read; [`Scalar::value`] sees through it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scalar {
+    /// `yes` or `no`.
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    Date(Date),
+    /// Any other unquoted text: names, IDs, scopes such as `character:42`,
+    /// variables such as `@tier` and inline maths such as `@[tier + 1]`.
+    Word(String),
+    /// Quoted text, without the quotes and with escapes resolved.
+    Quoted(String),
+    /// A number or date whose spelling differs from how its value is
+    /// written, such as `007`, `0.50` or `6812.03.07`.
+    Spelled {
+        value: Box<Scalar>,
+        text: String,
+    },
+}
+
+impl Scalar {
+    /// Type unquoted `text`.
+    pub fn from_word(text: &str) -> Self {
+        let value = Self::typed(text);
+        match value {
+            Scalar::Integer(_) | Scalar::Float(_) | Scalar::Date(_) if value.text() != text => {
+                Scalar::Spelled {
+                    value: Box::new(value),
+                    text: text.to_string(),
+                }
+            }
+            _ => value,
+        }
+    }
+
+    fn typed(text: &str) -> Self {
+        match text {
+            "yes" => return Scalar::Bool(true),
+            "no" => return Scalar::Bool(false),
+            _ => {}
+        }
+        if let Ok(integer) = text.parse() {
+            return Scalar::Integer(integer);
+        }
+        if let Some(date) = Date::parse(text) {
+            return Scalar::Date(date);
+        }
+        // The float parser would also take `inf` and `nan`, which scripts
+        // only use as names.
+        let numeric = text
+            .bytes()
+            .all(|b| b.is_ascii_digit() || matches!(b, b'.' | b'-' | b'+'));
+        match text.parse() {
+            Ok(float) if numeric => Scalar::Float(float),
+            _ => Scalar::Word(text.to_string()),
+        }
+    }
+
+    /// The scalar the text stands for, whatever its spelling.
+    pub fn value(&self) -> &Scalar {
+        match self {
+            Scalar::Spelled { value, .. } => value,
+            _ => self,
+        }
+    }
+
+    /// The text of the scalar as the file wrote it, quotes aside.
+    pub fn text(&self) -> std::borrow::Cow<'_, str> {
+        match self {
+            Scalar::Bool(true) => "yes".into(),
+            Scalar::Bool(false) => "no".into(),
+            Scalar::Integer(integer) => integer.to_string().into(),
+            Scalar::Float(float) => float.to_string().into(),
+            Scalar::Date(date) => date.to_string().into(),
+            Scalar::Word(text) | Scalar::Quoted(text) | Scalar::Spelled { text, .. } => {
+                text.as_str().into()
+            }
+        }
+    }
+}
+
+/// A `year.month.day` date, ordered chronologically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u8, day: u8) -> Self {
+        Self { year, month, day }
+    }
+
+    /// Parse a date such as `867.1.1` or `6812.03.07`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split('.');
+        let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() || [month, day].iter().any(|p| p.starts_with(['-', '+'])) {
+            return None;
+        }
+        let date = Self::new(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+        ((1..=12).contains(&date.month) && (1..=31).contains(&date.day)).then_some(date)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.year, self.month, self.day)
+    }
+}
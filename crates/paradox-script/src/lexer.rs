@@ -0,0 +1,191 @@
+//! Splitting script text into tokens.
+
+use crate::ast::Operator;
+use crate::error::ParseError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum TokenKind {
+    Open,
+    Close,
+    Operator(Operator),
+    Word(String),
+    Quoted(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Token {
+    pub kind: TokenKind,
+    /// 1-based position of the token's first character.
+    pub line: usize,
+    pub column: usize,
+    /// Characters of the text the token spans, from and to, past the byte
+    /// order mark.
+    pub span: (usize, usize),
+}
+
+/// Characters that end a word.
+pub(crate) const DELIMITERS: &[char] = &['{', '}', '=', '<', '>', '!', '?', '#', '"'];
+
+/// The tokens of `text`, comments and whitespace dropped.
+pub(crate) fn tokenize(text: &str) -> Result<Vec<Token>, ParseError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut lexer = Lexer {
+        chars: text.chars().collect(),
+        pos: 0,
+        line: 1,
+        column: 1,
+    };
+    let mut tokens = Vec::new();
+    while let Some(c) = lexer.peek(0) {
+        let (line, column, start) = (lexer.line, lexer.column, lexer.pos);
+        let kind = match c {
+            _ if c.is_whitespace() => {
+                lexer.bump();
+                continue;
+            }
+            '#' => {
+                while lexer.peek(0).is_some_and(|c| c != '\n') {
+                    lexer.bump();
+                }
+                continue;
+            }
+            '{' => {
+                lexer.bump();
+                TokenKind::Open
+            }
+            '}' => {
+                lexer.bump();
+                TokenKind::Close
+            }
+            '"' => TokenKind::Quoted(lexer.quoted()?),
+            '=' | '<' | '>' | '!' | '?' => TokenKind::Operator(lexer.operator()?),
+            _ => TokenKind::Word(lexer.word()?),
+        };
+        tokens.push(Token {
+            kind,
+            line,
+            column,
+            span: (start, lexer.pos),
+        });
+    }
+    Ok(tokens)
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Lexer {
+    fn peek(&self, ahead: usize) -> Option<char> {
+        self.chars.get(self.pos + ahead).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: &str, line: usize, column: usize) -> ParseError {
+        ParseError::new(message, line, column)
+    }
+
+    /// A quoted string, from its opening quote. `\"` and `\\` are escapes;
+    /// other backslashes are kept as written.
+    fn quoted(&mut self) -> Result<String, ParseError> {
+        let (line, column) = (self.line, self.column);
+        self.bump();
+        let mut text = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(self.error("unterminated string", line, column)),
+                Some('"') => return Ok(text),
+                Some('\\') if matches!(self.peek(0), Some('"' | '\\')) => {
+                    text.extend(self.bump());
+                }
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    fn operator(&mut self) -> Result<Operator, ParseError> {
+        let (line, column) = (self.line, self.column);
+        let mut text = String::new();
+        text.extend(self.bump());
+        if self.peek(0) == Some('=') {
+            text.extend(self.bump());
+        }
+        Operator::from_text(&text)
+            .ok_or_else(|| self.error(&format!("unknown operator `{text}`"), line, column))
+    }
+
+    /// An unquoted word. Inline maths such as `@[tier + 1]` is one word,
+    /// spaces and all, and must be closed on its line like a string.
+    fn word(&mut self) -> Result<String, ParseError> {
+        let mut text = String::new();
+        if self.peek(0) == Some('@') && self.peek(1) == Some('[') {
+            let (line, column) = (self.line, self.column);
+            // Inline maths never spans lines.
+            while let Some(c) = self.peek(0).filter(|&c| c != '\n') {
+                text.extend(self.bump());
+                if c == ']' {
+                    return Ok(text);
+                }
+            }
+            return Err(self.error("unterminated inline maths", line, column));
+        }
+        while let Some(c) = self.peek(0) {
+            if c.is_whitespace() || DELIMITERS.contains(&c) {
+                break;
+            }
+            text.extend(self.bump());
+        }
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<String> {
+        let tokens = tokenize(text).unwrap();
+        tokens
+            .into_iter()
+            .filter_map(|token| match token.kind {
+                TokenKind::Word(word) => Some(word),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn inline_maths_is_one_word() {
+        assert_eq!(words("a = @[tier + 1] b"), ["a", "@[tier + 1]", "b"]);
+    }
+
+    #[test]
+    fn unterminated_inline_maths_is_an_error() {
+        let error = tokenize("a = @[oops").unwrap_err();
+        assert_eq!(error.message, "unterminated inline maths");
+        assert_eq!((error.line, error.column), (1, 5));
+        let error = tokenize("a = @[oops\nb = ]").unwrap_err();
+        assert_eq!(error.message, "unterminated inline maths");
+    }
+
+    #[test]
+    fn unterminated_string_is_an_error() {
+        let error = tokenize("a = \"oops").unwrap_err();
+        assert_eq!(error.message, "unterminated string");
+        assert_eq!((error.line, error.column), (1, 5));
+    }
+}
@@ -0,0 +1,26 @@
+//! Paradox script files.
+//!
+//! CK3 writes its histories, common data and saves in the Jomini engine's
+//! script: `key = value` entries whose values are scalars or `{ }` blocks,
+//! blocks holding entries, bare values or both, dates such as `867.1.1` as
+//! keys, comparison operators besides `=`, `#` comments to the end of the
+//! line and quoted strings. [`parse`] reads a whole file into a [`Block`],
+//! with every scalar typed as far as its text tells, so that existing
+//! histories can be imported and game data checked against. [`to_string`]
+//! writes a block back out in the layout of the game's own files, and
+//! [`write_file`] saves text with the byte order mark the game expects.
+//! [`rewrite_words`] changes words of a file where they stand, and
+//! [`append_to_blocks`] adds to its blocks, keeping its layout and comments.
+
+pub mod ast;
+pub mod error;
+mod lexer;
+mod parser;
+mod rewrite;
+mod writer;
+
+pub use ast::{Block, Date, Entry, Item, Operator, Scalar, Value};
+pub use error::ParseError;
+pub use parser::parse;
+pub use rewrite::{append_to_blocks, rewrite_words, Word};
+pub use writer::{to_string, write_file, BOM};
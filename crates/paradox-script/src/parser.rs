@@ -0,0 +1,120 @@
+//! Building the AST from tokens.
+
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+use crate::ast::{Block, Entry, Item, Scalar, Value};
+use crate::error::ParseError;
+use crate::lexer::{self, Token, TokenKind};
+
+/// Parse the whole of a script file.
+pub fn parse(text: &str) -> Result<Block, ParseError> {
+    let mut parser = Parser {
+        tokens: lexer::tokenize(text)?.into_iter().peekable(),
+        end: end_of(text),
+    };
+    parser.block(None)
+}
+
+/// The position just past the last character of `text`.
+fn end_of(text: &str) -> (usize, usize) {
+    let line = text.lines().count().max(1);
+    let column = text.lines().last().map_or(0, |last| last.chars().count()) + 1;
+    (line, column)
+}
+
+struct Parser {
+    tokens: Peekable<IntoIter<Token>>,
+    end: (usize, usize),
+}
+
+impl Parser {
+    /// The items up to the `}` closing the block opened at `open`, or up to
+    /// the end of the file for the file itself.
+    fn block(&mut self, open: Option<(usize, usize)>) -> Result<Block, ParseError> {
+        let mut block = Block::default();
+        loop {
+            let Some(token) = self.tokens.next() else {
+                return match open {
+                    None => Ok(block),
+                    Some((line, column)) => Err(ParseError::new("unclosed block", line, column)),
+                };
+            };
+            let item = match token.kind {
+                TokenKind::Close if open.is_some() => return Ok(block),
+                TokenKind::Close => {
+                    return Err(ParseError::new("unmatched `}`", token.line, token.column))
+                }
+                TokenKind::Operator(operator) => {
+                    return Err(ParseError::new(
+                        format!("`{operator}` without a key"),
+                        token.line,
+                        token.column,
+                    ))
+                }
+                TokenKind::Open => {
+                    Item::Value(Value::Block(self.block(Some((token.line, token.column)))?))
+                }
+                TokenKind::Word(_) | TokenKind::Quoted(_) => {
+                    match self
+                        .tokens
+                        .next_if(|next| matches!(next.kind, TokenKind::Operator(_)))
+                    {
+                        Some(Token {
+                            kind: TokenKind::Operator(operator),
+                            ..
+                        }) => Item::Entry(Entry {
+                            line: token.line,
+                            key: scalar(token.kind),
+                            operator,
+                            value: self.value(token.line, token.column)?,
+                        }),
+                        _ => Item::Value(self.scalar_or_tagged(token)?),
+                    }
+                }
+            };
+            block.items.push(item);
+        }
+    }
+
+    /// The value after the operator of the entry at `line` and `column`.
+    fn value(&mut self, line: usize, column: usize) -> Result<Value, ParseError> {
+        let Some(token) = self.tokens.next() else {
+            let (line, column) = self.end;
+            return Err(ParseError::new("expected a value", line, column));
+        };
+        match token.kind {
+            TokenKind::Open => Ok(Value::Block(self.block(Some((token.line, token.column)))?)),
+            TokenKind::Word(_) | TokenKind::Quoted(_) => self.scalar_or_tagged(token),
+            TokenKind::Close | TokenKind::Operator(_) => Err(ParseError::new(
+                format!("expected a value for the entry at line {line}, column {column}"),
+                token.line,
+                token.column,
+            )),
+        }
+    }
+
+    /// A scalar, or a tagged block if the scalar is a word followed by `{`.
+    fn scalar_or_tagged(&mut self, token: Token) -> Result<Value, ParseError> {
+        if let TokenKind::Word(tag) = &token.kind {
+            if let Some(open) = self.tokens.next_if(|next| next.kind == TokenKind::Open) {
+                let block = self.block(Some((open.line, open.column)))?;
+                return Ok(Value::Tagged {
+                    tag: tag.clone(),
+                    block,
+                });
+            }
+        }
+        Ok(Value::Scalar(scalar(token.kind)))
+    }
+}
+
+fn scalar(kind: TokenKind) -> Scalar {
+    match kind {
+        TokenKind::Word(text) => Scalar::from_word(&text),
+        TokenKind::Quoted(text) => Scalar::Quoted(text),
+        TokenKind::Open | TokenKind::Close | TokenKind::Operator(_) => {
+            unreachable!("only words and quoted strings are scalars")
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Rewriting words of a script file in place.
+//!
+//! Parsing a file and writing it back lays it out anew and drops its
+//! comments. [`rewrite_words`] instead replaces single words and quoted
+//! strings where they stand, so that renaming an ID in a hand-kept or
+//! generated file changes nothing else about it. [`append_to_blocks`] adds
+//! entries at the end of top-level blocks the same way.
+
+use crate::error::ParseError;
+use crate::lexer::{tokenize, TokenKind};
+
+/// A word or quoted string of a file, as [`rewrite_words`] is given it.
+#[derive(Clone, Copy, Debug)]
+pub struct Word<'a> {
+    /// Its text, unquoted.
+    pub text: &'a str,
+    pub quoted: bool,
+    /// The key it is the value of, if it is one.
+    pub key: Option<&'a str>,
+    /// Whether it is the key of an entry.
+    pub is_key: bool,
+    /// Blocks it is in, 0 at the top level.
+    pub depth: usize,
+    /// 1-based line it is on.
+    pub line: usize,
+}
+
+/// `text` with each word or quoted string that `rewrite` gives a new text
+/// for replaced by it, quoted as the old one was, and the rest as written.
+pub fn rewrite_words(
+    text: &str,
+    mut rewrite: impl FnMut(&Word) -> Option<String>,
+) -> Result<String, ParseError> {
+    let (bom, body) = match text.strip_prefix('\u{feff}') {
+        Some(body) => ("\u{feff}", body),
+        None => ("", text),
+    };
+    let tokens = tokenize(body)?;
+    let chars: Vec<char> = body.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    out.push_str(bom);
+    let mut copied = 0;
+    let mut depth: usize = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        let (word, quoted) = match &token.kind {
+            TokenKind::Open => {
+                depth += 1;
+                continue;
+            }
+            TokenKind::Close => {
+                depth = depth.saturating_sub(1);
+                continue;
+            }
+            TokenKind::Operator(_) => continue,
+            TokenKind::Word(word) => (word, false),
+            TokenKind::Quoted(word) => (word, true),
+        };
+        let is_operator = |j: Option<usize>| {
+            j.and_then(|j| tokens.get(j))
+                .is_some_and(|token| matches!(token.kind, TokenKind::Operator(_)))
+        };
+        let key = match (
+            is_operator(i.checked_sub(1)),
+            i.checked_sub(2).map(|j| &tokens[j].kind),
+        ) {
+            (true, Some(TokenKind::Word(key) | TokenKind::Quoted(key))) => Some(key.as_str()),
+            _ => None,
+        };
+        let word = Word {
+            text: word,
+            quoted,
+            key,
+            is_key: is_operator(Some(i + 1)),
+            depth,
+            line: token.line,
+        };
+        if let Some(new) = rewrite(&word) {
+            let (start, end) = token.span;
+            out.extend(&chars[copied..start]);
+            if quoted {
+                out.push('"');
+                out.push_str(&new.replace('\\', "\\\\").replace('"', "\\\""));
+                out.push('"');
+            } else {
+                out.push_str(&new);
+            }
+            copied = end;
+        }
+    }
+    out.extend(&chars[copied..]);
+    Ok(out)
+}
+
+/// `text` with what `append` gives for the key of each top-level block
+/// added at the end of the block, before its closing brace, and the rest as
+/// written. What is added should end with a line break.
+pub fn append_to_blocks(
+    text: &str,
+    mut append: impl FnMut(&str) -> Option<String>,
+) -> Result<String, ParseError> {
+    let (bom, body) = match text.strip_prefix('\u{feff}') {
+        Some(body) => ("\u{feff}", body),
+        None => ("", text),
+    };
+    let tokens = tokenize(body)?;
+    let chars: Vec<char> = body.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    out.push_str(bom);
+    let mut copied = 0;
+    let mut depth: usize = 0;
+    let mut key = None;
+    for (i, token) in tokens.iter().enumerate() {
+        match &token.kind {
+            TokenKind::Open => {
+                if depth == 0 {
+                    key = match i
+                        .checked_sub(2)
+                        .map(|j| (&tokens[j].kind, &tokens[j + 1].kind))
+                    {
+                        Some((
+                            TokenKind::Word(key) | TokenKind::Quoted(key),
+                            TokenKind::Operator(_),
+                        )) => Some(key.as_str()),
+                        _ => None,
+                    };
+                }
+                depth += 1;
+            }
+            TokenKind::Close => {
+                depth = depth.saturating_sub(1);
+                if depth > 0 {
+                    continue;
+                }
+                let Some(addition) = key.take().and_then(&mut append) else {
+                    continue;
+                };
+                let (start, _) = token.span;
+                let line_start = chars[..start]
+                    .iter()
+                    .rposition(|&c| c == '\n')
+                    .map_or(0, |i| i + 1);
+                // A brace on a line of its own keeps it; one after entries
+                // goes on the next line.
+                if chars[line_start..start].iter().all(|c| c.is_whitespace()) {
+                    out.extend(&chars[copied..line_start]);
+                    out.push_str(&addition);
+                    copied = line_start;
+                } else {
+                    out.extend(&chars[copied..start]);
+                    out.push('\n');
+                    out.push_str(&addition);
+                    copied = start;
+                }
+            }
+            _ => {}
+        }
+    }
+    out.extend(&chars[copied..]);
+    Ok(out)
+}
@@ -0,0 +1,265 @@
+//! Writing script files the way the game's own files are laid out.
+//!
+//! Entries go one to a line, indented by a tab per level of nesting, with
+//! `key = {` opening a block and its `}` on a line of its own. Blocks holding
+//! nothing but bare scalars, such as colours and gene values, stay on one
+//! line as `{ 1 2 3 }`. Items are written in the order the block holds them,
+//! so the same block always makes the same file, and blocks at the top level
+//! are set apart by a blank line. Comments stand on lines of their own, above
+//! the item they come before.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::ast::{Block, Entry, Item, Scalar, Value};
+
+/// The byte order mark the game expects at the start of its script and
+/// localisation files. Without it, text outside ASCII is read as Latin-1.
+pub const BOM: char = '\u{feff}';
+
+/// The text of `block` as a whole file, without the byte order mark.
+pub fn to_string(block: &Block) -> String {
+    let mut out = String::new();
+    // Comments go with the item after them.
+    let spaced = |i: usize| {
+        let item = block.items[i..]
+            .iter()
+            .find(|item| !matches!(item, Item::Comment(_)));
+        item.is_some_and(|item| {
+            matches!(
+                item_value(item),
+                Some(Value::Block(_) | Value::Tagged { .. })
+            )
+        })
+    };
+    for (i, item) in block.items.iter().enumerate() {
+        let follows_comment = i > 0 && matches!(block.items[i - 1], Item::Comment(_));
+        if i > 0 && !follows_comment && (spaced(i) || spaced(i - 1)) {
+            out.push('\n');
+        }
+        write_item(&mut out, item, 0);
+    }
+    out
+}
+
+/// Write `text` to `path` as a UTF-8 file with a byte order mark, adding the
+/// mark unless `text` already starts with it.
+pub fn write_file(path: &Path, text: &str) -> io::Result<()> {
+    let mut contents = String::with_capacity(text.len() + BOM.len_utf8());
+    if !text.starts_with(BOM) {
+        contents.push(BOM);
+    }
+    contents.push_str(text);
+    fs::write(path, contents)
+}
+
+fn item_value(item: &Item) -> Option<&Value> {
+    match item {
+        Item::Entry(entry) => Some(&entry.value),
+        Item::Value(value) => Some(value),
+        Item::Comment(_) => None,
+    }
+}
+
+fn write_item(out: &mut String, item: &Item, depth: usize) {
+    indent(out, depth);
+    match item {
+        Item::Entry(Entry {
+            key,
+            operator,
+            value,
+            ..
+        }) => {
+            write_scalar(out, key);
+            out.push(' ');
+            out.push_str(operator.text());
+            out.push(' ');
+            write_value(out, value, depth);
+        }
+        Item::Value(value) => write_value(out, value, depth),
+        Item::Comment(text) if text.is_empty() => out.push('#'),
+        Item::Comment(text) => {
+            out.push_str("# ");
+            out.push_str(text);
+        }
+    }
+    out.push('\n');
+}
+
+fn write_value(out: &mut String, value: &Value, depth: usize) {
+    match value {
+        Value::Scalar(scalar) => write_scalar(out, scalar),
+        Value::Block(block) => write_block(out, block, depth),
+        Value::Tagged { tag, block } => {
+            out.push_str(tag);
+            out.push(' ');
+            write_block(out, block, depth);
+        }
+    }
+}
+
+/// A block from its `{` to its `}`, the `}` at the indentation of the line
+/// that opened it.
+fn write_block(out: &mut String, block: &Block, depth: usize) {
+    if block.items.is_empty() {
+        out.push_str("{ }");
+        return;
+    }
+    let inline = block
+        .items
+        .iter()
+        .all(|item| matches!(item, Item::Value(Value::Scalar(_))));
+    if inline {
+        out.push('{');
+        for value in block.values() {
+            out.push(' ');
+            write_value(out, value, depth);
+        }
+        out.push_str(" }");
+        return;
+    }
+    out.push_str("{\n");
+    for item in &block.items {
+        write_item(out, item, depth + 1);
+    }
+    indent(out, depth);
+    out.push('}');
+}
+
+fn write_scalar(out: &mut String, scalar: &Scalar) {
+    match scalar {
+        Scalar::Quoted(text) => write_quoted(out, text),
+        // A whole float would otherwise read back as an integer.
+        Scalar::Float(float) if float.fract() == 0.0 => out.push_str(&format!("{float:.1}")),
+        Scalar::Word(text) if !is_bare_word(text) => write_quoted(out, text),
+        _ => out.push_str(&scalar.text()),
+    }
+}
+
+fn write_quoted(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        if matches!(c, '"' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+}
+
+/// Whether `text` reads back as the same single word without quotes.
+fn is_bare_word(text: &str) -> bool {
+    if text.starts_with("@[") && text.ends_with(']') {
+        return true;
+    }
+    !text.is_empty()
+        && !text
+            .chars()
+            .any(|c| c.is_whitespace() || crate::lexer::DELIMITERS.contains(&c))
+}
+
+fn indent(out: &mut String, depth: usize) {
+    for _ in 0..depth {
+        out.push('\t');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Date;
+    use crate::parse;
+
+    fn round_trip(text: &str) -> String {
+        to_string(&parse(text).unwrap())
+    }
+
+    #[test]
+    fn game_layout_round_trips() {
+        let text = "\
+1001 = {
+\tname = \"Aragorn\"
+\tdynasty = 7
+\ttrait = brave
+\tcolor = { 0.5 1.0 0 }
+\t2931.3.1 = {
+\t\tbirth = yes
+\t}
+\t3019.5.1 = {
+\t\tadd_spouse = 1002
+\t}
+}
+
+title = k_gondor
+";
+        assert_eq!(round_trip(text), text);
+        assert_eq!(round_trip(&round_trip(text)), text);
+    }
+
+    #[test]
+    fn numbers_and_dates_keep_their_spelling() {
+        for text in ["00.1.1", "007", "0.50", "6812.03.07", "1.0", "-0", "+3"] {
+            let written = round_trip(&format!("a = {text}\n"));
+            assert_eq!(written, format!("a = {text}\n"));
+        }
+        let written = round_trip("00.1.1 = { birth = yes }\n");
+        assert_eq!(written, "00.1.1 = {\n\tbirth = yes\n}\n");
+    }
+
+    #[test]
+    fn spelled_scalars_keep_their_value() {
+        let block = parse("a = 007 b = 0.50 6812.03.07 = { birth = yes }").unwrap();
+        let scalar = |key| block.get(key).and_then(Value::as_scalar).unwrap();
+        assert_eq!(scalar("a").value(), &Scalar::Integer(7));
+        assert_eq!(scalar("b").value(), &Scalar::Float(0.5));
+        let dates: Vec<Date> = block.dated().map(|(date, _)| date).collect();
+        assert_eq!(dates, [Date::new(6812, 3, 7)]);
+        assert_eq!(Scalar::from_word("7"), Scalar::Integer(7));
+        assert_eq!(
+            Scalar::from_word("867.1.1"),
+            Scalar::Date(Date::new(867, 1, 1))
+        );
+    }
+
+    #[test]
+    fn written_floats_read_back_as_floats() {
+        let mut block = Block::default();
+        block.push("fertility", Scalar::Float(2.0));
+        assert_eq!(to_string(&block), "fertility = 2.0\n");
+        let scalar = parse(&to_string(&block)).unwrap();
+        let value = scalar.get("fertility").and_then(Value::as_scalar).unwrap();
+        assert_eq!(value.value(), &Scalar::Float(2.0));
+    }
+
+    #[test]
+    fn comments_stand_above_their_item() {
+        let mut dated = Block::default();
+        dated.push("add_spouse", Scalar::Integer(1002));
+        let mut character = Block::default();
+        character.push("name", Scalar::Word("Aragorn".into()));
+        character.push_comment("Married at age 88");
+        character.push("3019.5.1", dated);
+        let mut file = Block::default();
+        file.push_comment("Plugin failed");
+        file.push("1001", character);
+        file.push_comment("");
+        file.push("1002", Block::default());
+        assert_eq!(
+            to_string(&file),
+            "# Plugin failed\n1001 = {\n\tname = Aragorn\n\t# Married at age 88\n\
+             \t3019.5.1 = {\n\t\tadd_spouse = 1002\n\t}\n}\n\n#\n1002 = { }\n"
+        );
+    }
+
+    #[test]
+    fn words_that_need_quotes_are_quoted() {
+        let mut block = Block::default();
+        block.push("name", Scalar::Word("Minas Tirith".into()));
+        block.push("maths", Scalar::Word("@[tier + 1]".into()));
+        assert_eq!(
+            to_string(&block),
+            "name = \"Minas Tirith\"\nmaths = @[tier + 1]\n"
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! Comparing two runs.
+//!
+//! [`Store::compare_runs`] lines up the characters of two runs by ID and
+//! tells which the later one added or removed and, of those in both, what
+//! changed: their dates, their other fields and the traits they gained or
+//! lost. A tweak of one parameter under the same seed usually changes a
+//! corner of the history, which the delta points at; two runs with different
+//! seeds share little but their progenitors, so each list is cut short at
+//! [`DIFF_LIMIT`] characters with the full count kept.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use ck3_sim::character::SKILLS;
+use rusqlite::params;
+use serde::Serialize;
+
+use crate::query::{character, CharacterRow, CHARACTERS, CHARACTER_COLUMNS};
+use crate::{Store, StoreError};
+
+/// The most characters each list of a [`RunDiff`] holds.
+pub const DIFF_LIMIT: usize = 500;
+
+/// What changed from one run to another.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunDiff {
+    pub before: i64,
+    pub after: i64,
+    /// Characters only the later run has, in order of birth.
+    pub added: Vec<CharacterRow>,
+    pub added_total: usize,
+    /// Characters only the earlier run has, in order of birth.
+    pub removed: Vec<CharacterRow>,
+    pub removed_total: usize,
+    /// Characters of both runs that differ, in order of birth in the later.
+    pub changed: Vec<CharacterChange>,
+    pub changed_total: usize,
+    /// Characters of both runs that are the same in each.
+    pub unchanged: usize,
+}
+
+/// How a character of both runs differs.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CharacterChange {
+    pub id: String,
+    /// Their name in the later run.
+    pub name: String,
+    /// Fields that differ, dates first.
+    pub fields: Vec<FieldChange>,
+    pub traits_gained: Vec<String>,
+    pub traits_lost: Vec<String>,
+}
+
+/// A field of a character, in each run; none where it is unset.
+#[derive(Clone, Debug, Serialize)]
+pub struct FieldChange {
+    /// As [`CharacterRow`] serializes it, or a skill of [`SKILLS`].
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl Store {
+    /// Compare the characters of run `before` with those of run `after`.
+    pub fn compare_runs(&self, before: i64, after: i64) -> Result<RunDiff, StoreError> {
+        let old = self.run_characters(before)?;
+        let new = self.run_characters(after)?;
+        let old_ids: BTreeSet<&str> = old.iter().map(|c| c.id.as_str()).collect();
+        let new_by_id: BTreeMap<&str, &CharacterRow> =
+            new.iter().map(|c| (c.id.as_str(), c)).collect();
+        let old_by_id: BTreeMap<&str, &CharacterRow> =
+            old.iter().map(|c| (c.id.as_str(), c)).collect();
+
+        let added: Vec<&CharacterRow> = new
+            .iter()
+            .filter(|c| !old_ids.contains(c.id.as_str()))
+            .collect();
+        let removed: Vec<&CharacterRow> = old
+            .iter()
+            .filter(|c| !new_by_id.contains_key(c.id.as_str()))
+            .collect();
+        let changed: Vec<CharacterChange> = new
+            .iter()
+            .filter_map(|c| compare(old_by_id.get(c.id.as_str())?, c))
+            .collect();
+        let unchanged = new.len() - added.len() - changed.len();
+
+        let first = |list: &[&CharacterRow]| -> Vec<CharacterRow> {
+            list.iter().take(DIFF_LIMIT).map(|&c| c.clone()).collect()
+        };
+        Ok(RunDiff {
+            before,
+            after,
+            added: first(&added),
+            added_total: added.len(),
+            removed: first(&removed),
+            removed_total: removed.len(),
+            changed_total: changed.len(),
+            changed: changed.into_iter().take(DIFF_LIMIT).collect(),
+            unchanged,
+        })
+    }
+
+    /// Every character of run `run`, in order of birth.
+    fn run_characters(&self, run: i64) -> Result<Vec<CharacterRow>, StoreError> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT {CHARACTER_COLUMNS} FROM {CHARACTERS} WHERE c.run_id = ?1 \
+             ORDER BY c.birth_year IS NULL, c.birth_year, c.id"
+        ))?;
+        let characters = statement
+            .query_map(params![run], character)?
+            .collect::<Result<_, _>>()?;
+        Ok(characters)
+    }
+}
+
+/// How `new` differs from `old`, or none if it does not.
+fn compare(old: &CharacterRow, new: &CharacterRow) -> Option<CharacterChange> {
+    let sex = |c: &CharacterRow| Some(if c.female { "female" } else { "male" }.to_string());
+    let mut fields: Vec<FieldChange> = [
+        ("birth", old.birth.clone(), new.birth.clone()),
+        ("death", old.death.clone(), new.death.clone()),
+        (
+            "deathReason",
+            old.death_reason.clone(),
+            new.death_reason.clone(),
+        ),
+        ("name", Some(old.name.clone()), Some(new.name.clone())),
+        ("female", sex(old), sex(new)),
+        ("dynasty", old.dynasty.clone(), new.dynasty.clone()),
+        ("house", old.house.clone(), new.house.clone()),
+        ("culture", old.culture.clone(), new.culture.clone()),
+        ("religion", old.religion.clone(), new.religion.clone()),
+        ("sexuality", old.sexuality.clone(), new.sexuality.clone()),
+        ("father", old.father.clone(), new.father.clone()),
+        ("mother", old.mother.clone(), new.mother.clone()),
+    ]
+    .into_iter()
+    .filter(|(_, before, after)| before != after)
+    .map(|(field, before, after)| FieldChange {
+        field: field.to_string(),
+        before,
+        after,
+    })
+    .collect();
+    for ((skill, before), after) in SKILLS.iter().zip(&old.skills).zip(&new.skills) {
+        if before != after {
+            fields.push(FieldChange {
+                field: skill.to_string(),
+                before: before.map(|level| level.to_string()),
+                after: after.map(|level| level.to_string()),
+            });
+        }
+    }
+    let traits_gained: Vec<String> = new
+        .traits
+        .iter()
+        .filter(|name| !old.traits.contains(name))
+        .cloned()
+        .collect();
+    let traits_lost: Vec<String> = old
+        .traits
+        .iter()
+        .filter(|name| !new.traits.contains(name))
+        .cloned()
+        .collect();
+    if fields.is_empty() && traits_gained.is_empty() && traits_lost.is_empty() {
+        return None;
+    }
+    Some(CharacterChange {
+        id: new.id.clone(),
+        name: new.name.clone(),
+        fields,
+        traits_gained,
+        traits_lost,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{self, HISTORY};
+
+    #[test]
+    fn runs_are_compared_character_by_character() {
+        let mut store = testing::store();
+        let before = testing::record(&mut store, "compare-before", HISTORY).id;
+        // Beren is born a year later, their son is brave rather than shy,
+        // and the progenitor has a daughter too.
+        let history = HISTORY
+            .replace("6002.1.1 = { birth = yes }", "6003.1.1 = { birth = yes }")
+            .replace("trait = shy", "trait = brave")
+            .replace(
+                "lineofa2 = {",
+                "lineofa3 = {\n\tname = \"Ioreth\"\n\tfemale = yes\n\tdynasty = dynasty_a\n\t\
+                 6027.1.1 = { birth = yes }\n}\nlineofa2 = {",
+            );
+        let after = testing::record(&mut store, "compare-after", &history).id;
+
+        let diff = store.compare_runs(before, after).unwrap();
+        let added: Vec<&str> = diff.added.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(added, ["lineofa3"]);
+        assert_eq!((diff.added_total, diff.removed_total), (1, 0));
+        assert_eq!((diff.changed_total, diff.unchanged), (2, 1));
+
+        let changes: Vec<_> = diff
+            .changed
+            .iter()
+            .map(|change| {
+                let fields = change
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        (
+                            field.field.as_str(),
+                            field.before.as_deref(),
+                            field.after.as_deref(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                (change.id.as_str(), fields)
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (
+                    "lineofb1",
+                    vec![("birth", Some("6002.1.1"), Some("6003.1.1"))]
+                ),
+                ("lineofa2", vec![]),
+            ]
+        );
+        assert_eq!(diff.changed[1].traits_gained, ["brave"]);
+        assert_eq!(diff.changed[1].traits_lost, ["shy"]);
+
+        // The other way round, the daughter is removed.
+        let reverse = store.compare_runs(after, before).unwrap();
+        let removed: Vec<&str> = reverse.removed.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(removed, ["lineofa3"]);
+        assert_eq!(reverse.added_total, 0);
+    }
+}
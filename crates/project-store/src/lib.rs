@@ -0,0 +1,106 @@
+//! SQLite storage of a project.
+//!
+//! Every project keeps a database of its own, `project.db` next to its
+//! config, holding each run the project recorded with its characters,
+//! marriages and dynasties, and the project's settings. A run is read from
+//! the output directory it wrote to, whichever engine generated it, and
+//! recorded in a single transaction, so that a crash mid-save leaves the
+//! database as it was. Indexed tables answer the UI's searches, filters and
+//! statistics over runs of hundreds of thousands of characters without
+//! parsing a history file again, and two runs can be compared character by
+//! character.
+
+pub mod compare;
+pub mod query;
+pub mod runs;
+pub mod settings;
+#[cfg(test)]
+mod testing;
+
+use std::fmt;
+use std::path::Path;
+
+use ck3_sim::SimError;
+use rusqlite::Connection;
+
+pub use compare::{CharacterChange, FieldChange, RunDiff, DIFF_LIMIT};
+pub use query::{CharacterPage, CharacterQuery, CharacterRow, DynastyStats, Facets};
+pub use runs::Run;
+
+/// Name of the database file in a project's config directory.
+pub const STORE_FILE: &str = "project.db";
+
+/// Statements bringing a database of each earlier version up to date, in
+/// order; a database's `user_version` is the number applied.
+const MIGRATIONS: [&str; 1] = [include_str!("schema.sql")];
+
+/// An open project database.
+pub struct Store {
+    connection: Connection,
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    /// The database could not be read or written.
+    Sqlite(rusqlite::Error),
+    /// The output of a run could not be read.
+    History(SimError),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Sqlite(err) => write!(f, "project database: {err}"),
+            StoreError::History(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StoreError::Sqlite(err) => Some(err),
+            StoreError::History(err) => Some(err),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(err: rusqlite::Error) -> Self {
+        StoreError::Sqlite(err)
+    }
+}
+
+impl From<SimError> for StoreError {
+    fn from(err: SimError) -> Self {
+        StoreError::History(err)
+    }
+}
+
+impl Store {
+    /// Open the database at `path`, creating it if missing and bringing it
+    /// up to date if it was made by an earlier version.
+    pub fn open(path: &Path) -> Result<Self, StoreError> {
+        let connection = Connection::open(path)?;
+        // Write-ahead logging keeps the UI's reads from waiting on a save.
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        let mut store = Self { connection };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// Apply the migrations the database has not had yet.
+    fn migrate(&mut self) -> Result<(), StoreError> {
+        let version: usize = self
+            .connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        let transaction = self.connection.transaction()?;
+        for migration in MIGRATIONS.iter().skip(version) {
+            transaction.execute_batch(migration)?;
+        }
+        transaction.pragma_update(None, "user_version", MIGRATIONS.len())?;
+        transaction.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,388 @@
+//! Searching a run's characters and the statistics of its dynasties.
+
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::{Store, StoreError};
+
+/// The most characters a page holds, and how many it does if the query does
+/// not say.
+const PAGE_LIMIT: u32 = 100;
+
+/// What characters a search finds. Every filter given must match.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CharacterQuery {
+    /// Part of the name, in any case, or the whole ID.
+    pub text: Option<String>,
+    /// A dynasty ID, matching its members and those of its houses, or the
+    /// ID of a house defined without one.
+    pub dynasty: Option<String>,
+    pub house: Option<String>,
+    pub culture: Option<String>,
+    pub religion: Option<String>,
+    pub female: Option<bool>,
+    /// A year the character lived in: born by it and not dead before it.
+    pub alive_in: Option<i32>,
+    #[serde(rename = "trait")]
+    pub trait_: Option<String>,
+    /// The size of a page, at most and by default [`PAGE_LIMIT`].
+    pub limit: Option<u32>,
+    /// How many matching characters come before the page.
+    pub offset: u32,
+}
+
+/// A page of the characters a search found, in order of birth.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CharacterPage {
+    /// How many characters match in all.
+    pub total: i64,
+    pub characters: Vec<CharacterRow>,
+}
+
+/// A character found.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CharacterRow {
+    pub id: String,
+    pub name: String,
+    pub female: bool,
+    /// The dynasty they belong to, as [`CharacterQuery::dynasty`] matches.
+    pub dynasty: Option<String>,
+    /// As localised in the output.
+    pub dynasty_name: Option<String>,
+    pub house: Option<String>,
+    pub culture: Option<String>,
+    pub religion: Option<String>,
+    pub sexuality: Option<String>,
+    pub father: Option<String>,
+    pub mother: Option<String>,
+    pub birth: Option<String>,
+    pub death: Option<String>,
+    pub death_reason: Option<String>,
+    /// Levels of the skills, in the order of [`ck3_sim::SKILLS`].
+    pub skills: Vec<Option<i64>>,
+    pub traits: Vec<String>,
+}
+
+/// The numbers of a dynasty of a run, counting the members of its houses.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DynastyStats {
+    pub id: String,
+    pub name: Option<String>,
+    pub members: i64,
+    /// Members without a death date.
+    pub living: i64,
+    /// Distinct houses its members belong to.
+    pub houses: i64,
+    pub first_birth: Option<i32>,
+    pub last_birth: Option<i32>,
+}
+
+/// The values a run's characters have, to filter them by.
+#[derive(Clone, Debug, Serialize)]
+pub struct Facets {
+    pub cultures: Vec<String>,
+    pub religions: Vec<String>,
+}
+
+/// A run's characters, each with their house and the dynasty they belong to:
+/// their own, their house's, or for a house of a dynasty defined elsewhere,
+/// that house.
+pub(crate) const CHARACTERS: &str = "characters c \
+     LEFT JOIN dynasties h ON h.run_id = c.run_id AND h.id = c.house AND h.is_house = 1 \
+     LEFT JOIN dynasties d ON d.run_id = c.run_id \
+     AND d.id = COALESCE(c.dynasty, h.dynasty, c.house)";
+
+pub(crate) const CHARACTER_COLUMNS: &str = "c.id, c.name, c.female, \
+     COALESCE(c.dynasty, h.dynasty, c.house), d.name, c.house, c.culture, c.religion, \
+     c.sexuality, c.father, c.mother, c.birth, c.death, c.death_reason, c.diplomacy, \
+     c.martial, c.stewardship, c.intrigue, c.learning, c.prowess, c.traits";
+
+impl Store {
+    /// The page of the characters of run `run` that `query` asks for.
+    pub fn search_characters(
+        &self,
+        run: i64,
+        query: &CharacterQuery,
+    ) -> Result<CharacterPage, StoreError> {
+        let mut filters = vec!["c.run_id = ?"];
+        let mut values = vec![Value::Integer(run)];
+        if let Some(name) = query
+            .text
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            filters.push("(instr(lower(c.name), lower(?)) > 0 OR c.id = ?)");
+            values.push(Value::Text(name.to_string()));
+            values.push(Value::Text(name.to_string()));
+        }
+        for (column, value) in [
+            (
+                "COALESCE(c.dynasty, h.dynasty, c.house) = ?",
+                &query.dynasty,
+            ),
+            ("c.house = ?", &query.house),
+            ("c.culture = ?", &query.culture),
+            ("c.religion = ?", &query.religion),
+        ] {
+            if let Some(value) = value {
+                filters.push(column);
+                values.push(Value::Text(value.clone()));
+            }
+        }
+        if let Some(female) = query.female {
+            filters.push("c.female = ?");
+            values.push(Value::Integer(female.into()));
+        }
+        if let Some(year) = query.alive_in {
+            filters.push("c.birth_year <= ? AND (c.death_year IS NULL OR c.death_year >= ?)");
+            values.push(Value::Integer(year.into()));
+            values.push(Value::Integer(year.into()));
+        }
+        if let Some(name) = &query.trait_ {
+            filters.push("instr(' ' || c.traits || ' ', ?) > 0");
+            values.push(Value::Text(format!(" {name} ")));
+        }
+        let filters = filters.join(" AND ");
+
+        let total = self.connection.query_row(
+            &format!("SELECT COUNT(*) FROM {CHARACTERS} WHERE {filters}"),
+            params_from_iter(&values),
+            |row| row.get(0),
+        )?;
+        values.push(Value::Integer(
+            query.limit.unwrap_or(PAGE_LIMIT).min(PAGE_LIMIT).into(),
+        ));
+        values.push(Value::Integer(query.offset.into()));
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT {CHARACTER_COLUMNS} FROM {CHARACTERS} WHERE {filters} \
+             ORDER BY c.birth_year IS NULL, c.birth_year, c.id LIMIT ? OFFSET ?"
+        ))?;
+        let characters = statement
+            .query_map(params_from_iter(&values), character)?
+            .collect::<Result<_, _>>()?;
+        Ok(CharacterPage { total, characters })
+    }
+
+    /// The dynasties of run `run`, the largest first.
+    pub fn dynasty_stats(&self, run: i64) -> Result<Vec<DynastyStats>, StoreError> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT COALESCE(c.dynasty, h.dynasty, c.house) AS lineage, d.name, COUNT(*), \
+             SUM(c.death IS NULL), COUNT(DISTINCT c.house), MIN(c.birth_year), \
+             MAX(c.birth_year) FROM {CHARACTERS} WHERE c.run_id = ?1 AND lineage IS NOT NULL \
+             GROUP BY lineage ORDER BY COUNT(*) DESC, lineage"
+        ))?;
+        let stats = statement
+            .query_map(params![run], |row| {
+                Ok(DynastyStats {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    members: row.get(2)?,
+                    living: row.get(3)?,
+                    houses: row.get(4)?,
+                    first_birth: row.get(5)?,
+                    last_birth: row.get(6)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(stats)
+    }
+
+    /// The cultures and religions the characters of run `run` have.
+    pub fn facets(&self, run: i64) -> Result<Facets, StoreError> {
+        let distinct = |column: &str| -> Result<Vec<String>, StoreError> {
+            let mut statement = self.connection.prepare(&format!(
+                "SELECT DISTINCT {column} FROM characters \
+                 WHERE run_id = ?1 AND {column} IS NOT NULL ORDER BY {column}"
+            ))?;
+            let values = statement
+                .query_map(params![run], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+            Ok(values)
+        };
+        Ok(Facets {
+            cultures: distinct("culture")?,
+            religions: distinct("religion")?,
+        })
+    }
+}
+
+/// The character of a row of [`CHARACTER_COLUMNS`].
+pub(crate) fn character(row: &Row) -> rusqlite::Result<CharacterRow> {
+    let traits: String = row.get(20)?;
+    Ok(CharacterRow {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        female: row.get(2)?,
+        dynasty: row.get(3)?,
+        dynasty_name: row.get(4)?,
+        house: row.get(5)?,
+        culture: row.get(6)?,
+        religion: row.get(7)?,
+        sexuality: row.get(8)?,
+        father: row.get(9)?,
+        mother: row.get(10)?,
+        birth: row.get(11)?,
+        death: row.get(12)?,
+        death_reason: row.get(13)?,
+        skills: (14..20).map(|i| row.get(i)).collect::<Result<_, _>>()?,
+        traits: traits.split_whitespace().map(str::to_string).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, HISTORY};
+
+    /// The IDs of the characters of `run` that `query` finds, and the total.
+    fn search(store: &Store, run: i64, query: CharacterQuery) -> (Vec<String>, i64) {
+        let page = store.search_characters(run, &query).unwrap();
+        let ids = page.characters.into_iter().map(|c| c.id).collect();
+        (ids, page.total)
+    }
+
+    #[test]
+    fn characters_are_found_by_every_filter() {
+        let mut store = testing::store();
+        let run = testing::record(&mut store, "search", HISTORY).id;
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            search(&store, run, CharacterQuery::default()),
+            (ids(&["lineofa1", "lineofb1", "lineofa2"]), 3)
+        );
+        let text = |text: &str| CharacterQuery {
+            text: Some(text.into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            search(&store, run, text("  bOrN ")),
+            (ids(&["lineofa2"]), 1)
+        );
+        assert_eq!(
+            search(&store, run, text("lineofb1")),
+            (ids(&["lineofb1"]), 1)
+        );
+        // A dynasty takes in the members of its houses.
+        let dynasty = CharacterQuery {
+            dynasty: Some("dynasty_a".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            search(&store, run, dynasty),
+            (ids(&["lineofa1", "lineofa2"]), 2)
+        );
+        let filtered = [
+            (
+                CharacterQuery {
+                    house: Some("house_a_cadet".into()),
+                    ..Default::default()
+                },
+                ids(&["lineofa2"]),
+            ),
+            (
+                CharacterQuery {
+                    culture: Some("rohirric".into()),
+                    ..Default::default()
+                },
+                ids(&["lineofb1"]),
+            ),
+            (
+                CharacterQuery {
+                    female: Some(false),
+                    religion: Some("faith_eru".into()),
+                    ..Default::default()
+                },
+                ids(&["lineofa1", "lineofa2"]),
+            ),
+            (
+                CharacterQuery {
+                    alive_in: Some(6001),
+                    ..Default::default()
+                },
+                ids(&["lineofa1"]),
+            ),
+            (
+                CharacterQuery {
+                    trait_: Some("shy".into()),
+                    ..Default::default()
+                },
+                ids(&["lineofa2"]),
+            ),
+        ];
+        for (query, expected) in filtered {
+            assert_eq!(search(&store, run, query.clone()).0, expected, "{query:?}");
+        }
+    }
+
+    #[test]
+    fn pages_are_cut_from_every_match() {
+        let mut store = testing::store();
+        let run = testing::record(&mut store, "page", HISTORY).id;
+        let query = CharacterQuery {
+            limit: Some(1),
+            offset: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            search(&store, run, query),
+            (vec!["lineofb1".to_string()], 3)
+        );
+
+        let page = store
+            .search_characters(run, &CharacterQuery::default())
+            .unwrap();
+        let founder = &page.characters[0];
+        assert_eq!(founder.dynasty_name.as_deref(), Some("Aldorion"));
+        assert_eq!(founder.skills[0], Some(5));
+        assert_eq!(founder.traits, ["brave"]);
+        assert_eq!(
+            founder.death_reason.as_deref(),
+            Some("death_natural_causes")
+        );
+    }
+
+    #[test]
+    fn dynasties_count_the_members_of_their_houses() {
+        let mut store = testing::store();
+        let run = testing::record(&mut store, "stats", HISTORY).id;
+        let stats: Vec<_> = store
+            .dynasty_stats(run)
+            .unwrap()
+            .into_iter()
+            .map(|d| {
+                (
+                    d.id,
+                    d.members,
+                    d.living,
+                    d.houses,
+                    d.first_birth,
+                    d.last_birth,
+                )
+            })
+            .collect();
+        assert_eq!(
+            stats,
+            [
+                ("dynasty_a".to_string(), 2, 1, 1, Some(6000), Some(6025)),
+                ("dynasty_b".to_string(), 1, 1, 0, Some(6002), Some(6002)),
+            ]
+        );
+    }
+
+    #[test]
+    fn facets_are_the_distinct_cultures_and_religions() {
+        let mut store = testing::store();
+        let run = testing::record(&mut store, "facets", HISTORY).id;
+        let facets = store.facets(run).unwrap();
+        assert_eq!(facets.cultures, ["gondorian", "rohirric"]);
+        assert_eq!(facets.religions, ["faith_eru"]);
+        assert!(store.facets(run + 1).unwrap().cultures.is_empty());
+    }
+}
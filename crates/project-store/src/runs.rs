@@ -0,0 +1,282 @@
+//! Recording runs.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ck3_sim::{
+    dynasty_key, house_key, read_house_dynasties, read_names, read_people, SimError, HISTORY_FILE,
+};
+use rusqlite::params;
+use serde::Serialize;
+
+use crate::{Store, StoreError};
+
+/// A recorded run.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Run {
+    pub id: i64,
+    /// Milliseconds since the Unix epoch.
+    pub recorded_at: i64,
+    /// `native` or `python`.
+    pub engine: String,
+    /// The seed at the top of its history, if it has one.
+    pub seed: Option<u64>,
+    pub characters: i64,
+}
+
+impl Store {
+    /// Record the run whose output is in `dir`, generated by `engine`, and
+    /// return it.
+    pub fn record_run(&mut self, dir: &Path, engine: &str) -> Result<Run, StoreError> {
+        let path = dir.join(HISTORY_FILE);
+        let history = fs::read(&path).map_err(|source| SimError::Io { path, source })?;
+        let seed = ck3_sim::history_seed(&String::from_utf8_lossy(&history));
+        let people = read_people(dir)?;
+        let names = read_names(dir);
+        let house_dynasties = read_house_dynasties(dir);
+        let recorded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as i64);
+
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO runs (recorded_at, engine, seed, characters) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                recorded_at,
+                engine,
+                seed.map(|seed| seed as i64),
+                people.len() as i64
+            ],
+        )?;
+        let run_id = transaction.last_insert_rowid();
+        {
+            let mut character = transaction.prepare(
+                "INSERT INTO characters (run_id, id, name, female, dynasty, house, culture, \
+                 religion, sexuality, father, mother, adopted, birth, birth_year, death, \
+                 death_year, death_reason, diplomacy, martial, stewardship, intrigue, learning, \
+                 prowess, traits) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, \
+                 ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24)",
+            )?;
+            let mut marriage = transaction.prepare(
+                "INSERT INTO marriages (run_id, character, spouse, date) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for person in &people {
+                let skill = |i: usize| {
+                    person.skills[i]
+                        .as_deref()
+                        .and_then(|level| level.parse::<i64>().ok())
+                };
+                character.execute(params![
+                    run_id,
+                    person.id,
+                    person.name,
+                    person.female,
+                    person.dynasty,
+                    person.house,
+                    person.culture,
+                    person.religion,
+                    person.sexuality,
+                    person.father,
+                    person.mother,
+                    person.adopted.map(|date| date.to_string()),
+                    person.birth.map(|date| date.to_string()),
+                    person.birth.map(|date| date.year),
+                    person.death.map(|date| date.to_string()),
+                    person.death.map(|date| date.year),
+                    person.death_reason,
+                    skill(0),
+                    skill(1),
+                    skill(2),
+                    skill(3),
+                    skill(4),
+                    skill(5),
+                    person.traits.join(" "),
+                ])?;
+                for (date, spouse) in &person.marriages {
+                    marriage.execute(params![run_id, person.id, spouse, date.to_string()])?;
+                }
+            }
+
+            let mut dynasty = transaction.prepare(
+                "INSERT INTO dynasties (run_id, id, name, is_house, dynasty) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            let dynasties: BTreeSet<&str> = people
+                .iter()
+                .filter_map(|person| person.dynasty.as_deref())
+                .collect();
+            let houses: BTreeSet<&str> = people
+                .iter()
+                .filter_map(|person| person.house.as_deref())
+                .collect();
+            for id in dynasties {
+                dynasty.execute(params![
+                    run_id,
+                    id,
+                    names.get(&dynasty_key(id)),
+                    false,
+                    None::<&str>
+                ])?;
+            }
+            for id in houses {
+                dynasty.execute(params![
+                    run_id,
+                    id,
+                    names.get(&house_key(id)),
+                    true,
+                    house_dynasties.get(id)
+                ])?;
+            }
+        }
+        transaction.commit()?;
+
+        Ok(Run {
+            id: run_id,
+            recorded_at,
+            engine: engine.to_string(),
+            seed,
+            characters: people.len() as i64,
+        })
+    }
+
+    /// The recorded runs, the latest first.
+    pub fn runs(&self) -> Result<Vec<Run>, StoreError> {
+        let mut statement = self.connection.prepare(
+            "SELECT id, recorded_at, engine, seed, characters FROM runs ORDER BY id DESC",
+        )?;
+        let runs = statement
+            .query_map([], |row| {
+                Ok(Run {
+                    id: row.get(0)?,
+                    recorded_at: row.get(1)?,
+                    engine: row.get(2)?,
+                    seed: row.get::<_, Option<i64>>(3)?.map(|seed| seed as u64),
+                    characters: row.get(4)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(runs)
+    }
+
+    /// The latest recorded run, if any.
+    pub fn latest_run(&self) -> Result<Option<Run>, StoreError> {
+        Ok(self.runs()?.into_iter().next())
+    }
+
+    /// Forget the run `run`, with its characters, marriages and dynasties.
+    pub fn delete_run(&mut self, run: i64) -> Result<(), StoreError> {
+        self.connection
+            .execute("DELETE FROM runs WHERE id = ?1", [run])?;
+        Ok(())
+    }
+
+    /// Set the field `field` of the character `id` of the run `run` to
+    /// `value`, after the run's history was edited by hand. Fields other
+    /// than those `ck3_sim::edit_character` edits are left alone.
+    pub fn edit_character(
+        &self,
+        run: i64,
+        id: &str,
+        field: &str,
+        value: &str,
+    ) -> Result<(), StoreError> {
+        let column = match field {
+            "name" => "name",
+            "culture" => "culture",
+            "religion" => "religion",
+            _ => return Ok(()),
+        };
+        self.connection.execute(
+            &format!("UPDATE characters SET {column} = ?1 WHERE run_id = ?2 AND id = ?3"),
+            params![value, run, id],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{self, HISTORY};
+
+    #[test]
+    fn runs_are_recorded_with_their_characters_marriages_and_dynasties() {
+        let mut store = testing::store();
+        assert!(store.latest_run().unwrap().is_none());
+        let run = testing::record(&mut store, "record", HISTORY);
+        assert_eq!(
+            (run.engine.as_str(), run.seed, run.characters),
+            ("native", Some(42), 3)
+        );
+        assert_eq!(testing::rows(&store, "characters", run.id), 3);
+        assert_eq!(testing::rows(&store, "marriages", run.id), 1);
+
+        let dynasties: Vec<(String, Option<String>, bool, Option<String>)> = store
+            .connection
+            .prepare("SELECT id, name, is_house, dynasty FROM dynasties ORDER BY id")
+            .unwrap()
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            dynasties,
+            [
+                ("dynasty_a".into(), Some("Aldorion".into()), false, None),
+                ("dynasty_b".into(), None, false, None),
+                (
+                    "house_a_cadet".into(),
+                    Some("Anbornion".into()),
+                    true,
+                    Some("dynasty_a".into())
+                ),
+            ]
+        );
+
+        let later = testing::record(&mut store, "record-later", HISTORY);
+        let ids: Vec<i64> = store.runs().unwrap().iter().map(|run| run.id).collect();
+        assert_eq!(ids, [later.id, run.id]);
+        assert_eq!(
+            store.latest_run().unwrap().map(|run| run.id),
+            Some(later.id)
+        );
+    }
+
+    #[test]
+    fn deleting_a_run_deletes_what_it_recorded() {
+        let mut store = testing::store();
+        let deleted = testing::record(&mut store, "delete", HISTORY);
+        let kept = testing::record(&mut store, "delete-kept", HISTORY);
+        store.delete_run(deleted.id).unwrap();
+
+        let ids: Vec<i64> = store.runs().unwrap().iter().map(|run| run.id).collect();
+        assert_eq!(ids, [kept.id]);
+        for (table, count) in [("characters", 3), ("marriages", 1), ("dynasties", 3)] {
+            assert_eq!(testing::rows(&store, table, deleted.id), 0, "{table}");
+            assert_eq!(testing::rows(&store, table, kept.id), count, "{table}");
+        }
+    }
+
+    #[test]
+    fn edited_characters_are_updated() {
+        let mut store = testing::store();
+        let run = testing::record(&mut store, "edit", HISTORY);
+        store
+            .edit_character(run.id, "lineofa2", "name", "Anárion")
+            .unwrap();
+        store
+            .edit_character(run.id, "lineofa2", "birth", "6030.1.1")
+            .unwrap();
+        let query = crate::CharacterQuery {
+            text: Some("lineofa2".into()),
+            ..Default::default()
+        };
+        let page = store.search_characters(run.id, &query).unwrap();
+        assert_eq!(page.characters[0].name, "Anárion");
+        assert_eq!(page.characters[0].birth.as_deref(), Some("6025.1.1"));
+    }
+}
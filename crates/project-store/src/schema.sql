@@ -0,0 +1,79 @@
+-- Version 1: runs, their characters, marriages and dynasties, and settings.
+
+CREATE TABLE runs (
+    id INTEGER PRIMARY KEY,
+    -- Milliseconds since the Unix epoch.
+    recorded_at INTEGER NOT NULL,
+    -- The engine that generated the run, "native" or "python".
+    engine TEXT NOT NULL,
+    -- The seed's bits, as SQLite integers are signed.
+    seed INTEGER,
+    characters INTEGER NOT NULL
+);
+
+CREATE TABLE characters (
+    run_id INTEGER NOT NULL REFERENCES runs (id) ON DELETE CASCADE,
+    id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    female INTEGER NOT NULL,
+    dynasty TEXT,
+    house TEXT,
+    culture TEXT,
+    religion TEXT,
+    sexuality TEXT,
+    -- The adoptive parents of an adopted character.
+    father TEXT,
+    mother TEXT,
+    adopted TEXT,
+    -- Dates as the history writes them, and their years for filtering.
+    birth TEXT,
+    birth_year INTEGER,
+    death TEXT,
+    death_year INTEGER,
+    death_reason TEXT,
+    diplomacy INTEGER,
+    martial INTEGER,
+    stewardship INTEGER,
+    intrigue INTEGER,
+    learning INTEGER,
+    prowess INTEGER,
+    -- Space-separated, in the order they were gained.
+    traits TEXT NOT NULL,
+    PRIMARY KEY (run_id, id)
+) WITHOUT ROWID;
+
+CREATE INDEX characters_by_name ON characters (run_id, name COLLATE NOCASE);
+CREATE INDEX characters_by_dynasty ON characters (run_id, dynasty);
+CREATE INDEX characters_by_house ON characters (run_id, house);
+CREATE INDEX characters_by_birth ON characters (run_id, birth_year);
+CREATE INDEX characters_by_father ON characters (run_id, father);
+CREATE INDEX characters_by_mother ON characters (run_id, mother);
+
+CREATE TABLE marriages (
+    run_id INTEGER NOT NULL REFERENCES runs (id) ON DELETE CASCADE,
+    -- The character whose history records the marriage.
+    character TEXT NOT NULL,
+    spouse TEXT NOT NULL,
+    date TEXT NOT NULL
+);
+
+CREATE INDEX marriages_by_character ON marriages (run_id, character);
+CREATE INDEX marriages_by_spouse ON marriages (run_id, spouse);
+
+CREATE TABLE dynasties (
+    run_id INTEGER NOT NULL REFERENCES runs (id) ON DELETE CASCADE,
+    id TEXT NOT NULL,
+    -- As localised in the output, if it is.
+    name TEXT,
+    -- 1 for a house of characters' dynasty_house, 0 for a dynasty.
+    is_house INTEGER NOT NULL,
+    -- The dynasty of a house, if the output's house definitions name it.
+    dynasty TEXT,
+    PRIMARY KEY (run_id, id)
+) WITHOUT ROWID;
+
+CREATE TABLE settings (
+    key TEXT PRIMARY KEY,
+    -- JSON.
+    value TEXT NOT NULL
+) WITHOUT ROWID;
@@ -0,0 +1,112 @@
+//! A project's settings.
+//!
+//! Settings are JSON values under a key, for the UI to keep whatever it
+//! remembers of a project, such as how it last exported its trees, with the
+//! project rather than the app.
+
+use std::collections::BTreeMap;
+
+use rusqlite::{params, OptionalExtension};
+use serde_json::Value;
+
+use crate::{Store, StoreError};
+
+impl Store {
+    /// The setting `key`, if it is set.
+    pub fn setting(&self, key: &str) -> Result<Option<Value>, StoreError> {
+        let value: Option<String> = self
+            .connection
+            .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        // A value that is not JSON was not written by `set_setting`; treat
+        // it as unset rather than fail the UI over it.
+        Ok(value.and_then(|value| serde_json::from_str(&value).ok()))
+    }
+
+    /// Every setting, by key.
+    pub fn settings(&self) -> Result<BTreeMap<String, Value>, StoreError> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT key, value FROM settings ORDER BY key")?;
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut settings = BTreeMap::new();
+        for row in rows {
+            let (key, value) = row?;
+            // Skipped like a single setting that is not JSON.
+            if let Ok(value) = serde_json::from_str(&value) {
+                settings.insert(key, value);
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Set the setting `key` to `value`, or unset it if `value` is null.
+    pub fn set_setting(&self, key: &str, value: &Value) -> Result<(), StoreError> {
+        if value.is_null() {
+            self.connection
+                .execute("DELETE FROM settings WHERE key = ?1", [key])?;
+        } else {
+            self.connection.execute(
+                "INSERT INTO settings (key, value) VALUES (?1, ?2) \
+                 ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+                params![key, value.to_string()],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::testing;
+
+    #[test]
+    fn settings_are_set_replaced_and_unset() {
+        let store = testing::store();
+        assert_eq!(store.setting("dotExport").unwrap(), None);
+
+        store
+            .set_setting("dotExport", &json!({"generations": 3}))
+            .unwrap();
+        store.set_setting("language", &json!("english")).unwrap();
+        store
+            .set_setting("dotExport", &json!({"generations": 5}))
+            .unwrap();
+        assert_eq!(
+            store.setting("dotExport").unwrap(),
+            Some(json!({"generations": 5}))
+        );
+        let settings = store.settings().unwrap();
+        assert_eq!(
+            settings.into_iter().collect::<Vec<_>>(),
+            [
+                ("dotExport".to_string(), json!({"generations": 5})),
+                ("language".to_string(), json!("english")),
+            ]
+        );
+
+        store.set_setting("dotExport", &json!(null)).unwrap();
+        assert_eq!(store.setting("dotExport").unwrap(), None);
+        assert_eq!(store.settings().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn values_that_are_not_json_read_as_unset() {
+        let store = testing::store();
+        store
+            .connection
+            .execute(
+                "INSERT INTO settings (key, value) VALUES ('broken', '{')",
+                [],
+            )
+            .unwrap();
+        assert_eq!(store.setting("broken").unwrap(), None);
+        assert!(store.settings().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+//! Small runs recorded in a database in memory, for tests.
+
+use std::fs;
+
+use ck3_sim::{HISTORY_FILE, HOUSES_FILE, LOCALIZATION_DIR};
+use rusqlite::Connection;
+
+use crate::{Run, Store};
+
+/// A history of three characters: the progenitor of `dynasty_a`, his wife
+/// of `dynasty_b` and their son, of a cadet house of `dynasty_a`.
+pub(crate) const HISTORY: &str = "\
+# Seed: 42
+
+lineofa1 = {
+\tname = \"Aldor\"
+\tdynasty = dynasty_a
+\tculture = gondorian
+\treligion = faith_eru
+\tdiplomacy = 5
+\ttrait = brave
+\t6000.1.1 = { birth = yes }
+\t6020.1.1 = { add_spouse = lineofb1 }
+\t6060.1.1 = { death = { death_reason = death_natural_causes } }
+}
+lineofb1 = {
+\tname = \"Beren\"
+\tfemale = yes
+\tdynasty = dynasty_b
+\tculture = rohirric
+\treligion = faith_eru
+\t6002.1.1 = { birth = yes }
+}
+lineofa2 = {
+\tname = \"Anborn\"
+\tdynasty_house = house_a_cadet
+\tculture = gondorian
+\treligion = faith_eru
+\tfather = lineofa1
+\tmother = lineofb1
+\ttrait = shy
+\t6025.1.1 = { birth = yes }
+}
+";
+
+/// An empty database in memory, as `Store::open` leaves a new one.
+pub(crate) fn store() -> Store {
+    let connection = Connection::open_in_memory().unwrap();
+    connection
+        .pragma_update(None, "foreign_keys", true)
+        .unwrap();
+    let mut store = Store { connection };
+    store.migrate().unwrap();
+    store
+}
+
+/// Record an output directory named after `name` whose history is
+/// `history`, with the house definitions and dynasty names of [`HISTORY`].
+pub(crate) fn record(store: &mut Store, name: &str, history: &str) -> Run {
+    let dir = std::env::temp_dir().join(format!("ck3gen-store-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let names = dir.join(LOCALIZATION_DIR).join("english");
+    fs::create_dir_all(&names).unwrap();
+    fs::write(dir.join(HISTORY_FILE), history).unwrap();
+    fs::write(
+        dir.join(HOUSES_FILE),
+        "house_a_cadet = { name = dynn_house_a_cadet dynasty = dynasty_a }\n",
+    )
+    .unwrap();
+    fs::write(
+        names.join("lotr_dynasty_names_l_english.yml"),
+        "l_english:\n dynn_a:0 \"Aldorion\"\n dynn_house_a_cadet:0 \"Anbornion\"\n",
+    )
+    .unwrap();
+    let run = store.record_run(&dir, "native").unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    run
+}
+
+/// How many rows of `table` belong to the run `run`.
+pub(crate) fn rows(store: &Store, table: &str, run: i64) -> i64 {
+    store
+        .connection
+        .query_row(
+            &format!("SELECT COUNT(*) FROM {table} WHERE run_id = ?1"),
+            [run],
+            |row| row.get(0),
+        )
+        .unwrap()
+}
@@ -0,0 +1,288 @@
+//! Crash reports.
+//!
+//! A report is a `crash-<date>-<time>-<kind>.zip` holding `report.json` (what
+//! crashed, the engine's exit code and the system it ran on), `engine.log`
+//! with the last lines of engine output and `config.json` with a snapshot of
+//! the configuration. Absolute paths in the config are replaced and the home
+//! directory is elided from the log, so a report can be shared as is.
+//! Reports are only ever written locally; the newest few are kept.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+use sysinfo::System;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Default number of reports kept on disk.
+pub const DEFAULT_MAX_REPORTS: usize = 20;
+
+/// Prefix shared by all report files.
+const REPORT_PREFIX: &str = "crash-";
+/// What redacted paths are replaced with.
+const REDACTED_PATH: &str = "<path>";
+
+/// What crashed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrashKind {
+    /// The shell itself panicked.
+    Shell,
+    /// The engine process exited unexpectedly.
+    Engine,
+}
+
+impl CrashKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            CrashKind::Shell => "shell",
+            CrashKind::Engine => "engine",
+        }
+    }
+
+    /// The kind recorded in the name of the report at `path`.
+    pub fn of_report(path: &Path) -> Option<Self> {
+        let stem = path.file_stem()?.to_str()?;
+        [CrashKind::Shell, CrashKind::Engine]
+            .into_iter()
+            .find(|kind| stem.ends_with(&format!("-{}", kind.label())))
+    }
+}
+
+/// The OS and hardware a crash happened on.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemInfo {
+    pub os: Option<String>,
+    pub os_version: Option<String>,
+    pub kernel_version: Option<String>,
+    pub arch: String,
+    pub cpus: usize,
+    pub total_memory_mb: u64,
+}
+
+impl SystemInfo {
+    pub fn collect() -> Self {
+        let mut system = System::new();
+        system.refresh_memory();
+        Self {
+            os: System::name(),
+            os_version: System::long_os_version(),
+            kernel_version: System::kernel_version(),
+            arch: System::cpu_arch(),
+            cpus: std::thread::available_parallelism().map_or(1, |cpus| cpus.get()),
+            total_memory_mb: system.total_memory() / (1024 * 1024),
+        }
+    }
+}
+
+/// Everything gathered about one crash.
+#[derive(Clone, Debug)]
+pub struct CrashReport {
+    pub kind: CrashKind,
+    /// Panic message and backtrace, or how the engine exited.
+    pub message: String,
+    /// Exit code of the engine, if it was the engine that died.
+    pub exit_code: Option<i32>,
+    pub app_version: String,
+    /// The last lines of engine output, oldest first.
+    pub log_lines: Vec<String>,
+    /// Configuration in use at the time; paths in it are redacted on write.
+    pub config: Value,
+    /// Home directory to elide from the message and log.
+    pub home_dir: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary<'a> {
+    kind: CrashKind,
+    message: &'a str,
+    exit_code: Option<i32>,
+    app_version: &'a str,
+    created_at: String,
+    system: SystemInfo,
+}
+
+impl CrashReport {
+    /// Write the report into `dir`, keeping at most `max_reports` there, and
+    /// return the path of the new zip.
+    pub fn write(&self, dir: &Path, max_reports: usize) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let now = chrono::Local::now();
+        let name = format!(
+            "{REPORT_PREFIX}{}-{}.zip",
+            now.format("%Y%m%d-%H%M%S%.3f"),
+            self.kind.label()
+        );
+        let path = dir.join(name);
+
+        let message = self.elide_home(&self.message);
+        let summary = Summary {
+            kind: self.kind,
+            message: &message,
+            exit_code: self.exit_code,
+            app_version: &self.app_version,
+            created_at: now.to_rfc3339(),
+            system: SystemInfo::collect(),
+        };
+        let log: String = self
+            .log_lines
+            .iter()
+            .map(|line| self.elide_home(line) + "\n")
+            .collect();
+        let mut config = self.config.clone();
+        redact_paths(&mut config);
+
+        let mut zip = ZipWriter::new(File::create(&path)?);
+        let options = SimpleFileOptions::default();
+        zip.start_file("report.json", options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&summary)?)?;
+        zip.start_file("engine.log", options)?;
+        zip.write_all(log.as_bytes())?;
+        zip.start_file("config.json", options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&config)?)?;
+        zip.finish()?;
+
+        let reports = list(dir)?;
+        let excess = reports.len().saturating_sub(max_reports.max(1));
+        for oldest in reports.iter().rev().take(excess) {
+            let _ = fs::remove_file(oldest);
+        }
+        Ok(path)
+    }
+
+    /// `text` with the home directory replaced by `~`.
+    fn elide_home(&self, text: &str) -> String {
+        let Some(home) = self.home_dir.as_deref().and_then(Path::to_str) else {
+            return text.to_string();
+        };
+        if home.len() < 2 {
+            return text.to_string();
+        }
+        // Tracebacks and config values use either separator on Windows.
+        text.replace(home, "~")
+            .replace(&home.replace('\\', "/"), "~")
+    }
+}
+
+/// All reports in `dir`, newest first.
+pub fn list(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut reports: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(REPORT_PREFIX) && name.ends_with(".zip"))
+            })
+            .collect(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(err),
+    };
+    // The timestamp in the name sorts chronologically.
+    reports.sort();
+    reports.reverse();
+    Ok(reports)
+}
+
+/// Replace every absolute path among the strings in `value`.
+pub fn redact_paths(value: &mut Value) {
+    match value {
+        Value::String(text) if is_absolute_path(text) => *text = REDACTED_PATH.to_string(),
+        Value::Array(items) => items.iter_mut().for_each(redact_paths),
+        Value::Object(fields) => fields.values_mut().for_each(redact_paths),
+        _ => {}
+    }
+}
+
+/// Whether `text` is an absolute Unix, Windows or UNC path, or starts at `~`.
+///
+/// Checked by hand rather than with `Path::is_absolute` so that a report
+/// written on one platform redacts the other's paths too.
+fn is_absolute_path(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    let drive = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/');
+    drive || text.starts_with('/') || text.starts_with('~') || text.starts_with("\\\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use serde_json::json;
+    use zip::ZipArchive;
+
+    use super::*;
+
+    #[test]
+    fn absolute_paths_of_any_platform_are_redacted() {
+        let mut config = json!({
+            "outputDir": "/home/ada/output",
+            "gameDir": "C:\\Games\\Crusader Kings III",
+            "modDir": "D:/mods",
+            "share": "\\\\server\\share",
+            "names": ["~/names", "names/relative", "C:relative"],
+            "nested": {"configDir": "/etc/ck3gen", "seed": 42, "name": "Númenor"},
+        });
+        redact_paths(&mut config);
+        assert_eq!(
+            config,
+            json!({
+                "outputDir": "<path>",
+                "gameDir": "<path>",
+                "modDir": "<path>",
+                "share": "<path>",
+                "names": ["<path>", "names/relative", "C:relative"],
+                "nested": {"configDir": "<path>", "seed": 42, "name": "Númenor"},
+            })
+        );
+    }
+
+    #[test]
+    fn reports_redact_the_config_and_elide_the_home_directory() {
+        let dir = std::env::temp_dir().join(format!("ck3gen-crash-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let report = CrashReport {
+            kind: CrashKind::Engine,
+            message: "exited in C:\\Users\\ada\\AppData".into(),
+            exit_code: Some(1),
+            app_version: "1.0.0".into(),
+            log_lines: vec![
+                "reading C:/Users/ada/config/initialization.json".into(),
+                "ERROR: C:\\Users\\ada\\output is not writable".into(),
+            ],
+            config: json!({"outputDir": "C:\\Users\\ada\\output", "seed": 7}),
+            home_dir: Some(PathBuf::from("C:\\Users\\ada")),
+        };
+        let path = report.write(&dir, 5).unwrap();
+        assert_eq!(CrashKind::of_report(&path), Some(CrashKind::Engine));
+        assert_eq!(list(&dir).unwrap(), std::slice::from_ref(&path));
+
+        let mut zip = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let mut read = |name: &str| {
+            let mut text = String::new();
+            zip.by_name(name)
+                .unwrap()
+                .read_to_string(&mut text)
+                .unwrap();
+            text
+        };
+        assert_eq!(
+            read("engine.log"),
+            "reading ~/config/initialization.json\nERROR: ~\\output is not writable\n"
+        );
+        let config: Value = serde_json::from_str(&read("config.json")).unwrap();
+        assert_eq!(config, json!({"outputDir": "<path>", "seed": 7}));
+        let summary: Value = serde_json::from_str(&read("report.json")).unwrap();
+        assert_eq!(summary["message"], "exited in ~\\AppData");
+        assert_eq!(summary["exitCode"], 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,300 @@
+//! The queue of generation jobs.
+//!
+//! A job is one generation run of a config directory with a seed of its
+//! own, written into an output root of its own. Jobs wait in the queue in
+//! the order given, which can be changed while they wait, and the shell
+//! starts the first waiting one whenever it has a free slot, so that with
+//! one slot they run one after another and with more side by side. The
+//! queue keeps finished jobs, with how they ended, until they are cleared.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// What to run: the config directory, the seed and where to write.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobSpec {
+    /// Label shown for the job; the config directory's name if none.
+    pub name: Option<String>,
+    pub config_dir: PathBuf,
+    /// Seed of the run; a random one if none.
+    pub seed: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Complete,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Whether the job has ended, one way or another.
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            JobStatus::Complete | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    /// Number of the job, counting from 1 since the queue was created.
+    pub id: u64,
+    pub name: String,
+    pub config_dir: PathBuf,
+    pub seed: Option<u64>,
+    /// Output root the job's engine writes into.
+    pub output_dir: PathBuf,
+    pub status: JobStatus,
+    /// Share of the run done while it runs, from 0 to 100.
+    pub percent: Option<f64>,
+    /// Why a failed job failed.
+    pub error: Option<String>,
+    /// Seconds the job ran for, once finished.
+    pub elapsed_seconds: Option<f64>,
+}
+
+/// The jobs, waiting ones in the order they are to start.
+#[derive(Debug, Default)]
+pub struct JobQueue {
+    jobs: Vec<Job>,
+    last_id: u64,
+}
+
+impl JobQueue {
+    /// All jobs: running and finished ones in the order they started,
+    /// then the waiting ones in the order they will.
+    pub fn jobs(&self) -> Vec<Job> {
+        let (waiting, started): (Vec<&Job>, Vec<&Job>) = self
+            .jobs
+            .iter()
+            .partition(|job| job.status == JobStatus::Queued);
+        started.into_iter().chain(waiting).cloned().collect()
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    /// The ID the next job pushed gets.
+    pub fn next_id(&self) -> u64 {
+        self.last_id + 1
+    }
+
+    /// Add the job `spec` at the end of the queue, writing into
+    /// `output_dir`, and return its ID.
+    pub fn push(&mut self, spec: JobSpec, output_dir: PathBuf) -> u64 {
+        self.last_id += 1;
+        let name = spec
+            .name
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| {
+                spec.config_dir.file_name().map_or_else(
+                    || spec.config_dir.display().to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                )
+            });
+        self.jobs.push(Job {
+            id: self.last_id,
+            name,
+            config_dir: spec.config_dir,
+            seed: spec.seed,
+            output_dir,
+            status: JobStatus::Queued,
+            percent: None,
+            error: None,
+            elapsed_seconds: None,
+        });
+        self.last_id
+    }
+
+    /// Number of jobs running.
+    pub fn running(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| job.status == JobStatus::Running)
+            .count()
+    }
+
+    /// Mark the first waiting job as running and return it.
+    pub fn start_next(&mut self) -> Option<Job> {
+        let job = self
+            .jobs
+            .iter_mut()
+            .find(|job| job.status == JobStatus::Queued)?;
+        job.status = JobStatus::Running;
+        job.percent = Some(0.0);
+        Some(job.clone())
+    }
+
+    /// Move the waiting job `id` to `position` among the waiting jobs,
+    /// counting from 0; a position past the end moves it last.
+    pub fn move_to(&mut self, id: u64, position: usize) -> Result<(), String> {
+        let from = self
+            .jobs
+            .iter()
+            .position(|job| job.id == id)
+            .ok_or_else(|| format!("there is no job {id}"))?;
+        if self.jobs[from].status != JobStatus::Queued {
+            return Err(format!("job {id} is no longer waiting"));
+        }
+        let job = self.jobs.remove(from);
+        // The waiting jobs keep their order among the others, so the job
+        // goes before the waiting job now at `position`.
+        let to = self
+            .jobs
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| job.status == JobStatus::Queued)
+            .nth(position)
+            .map_or(self.jobs.len(), |(index, _)| index);
+        self.jobs.insert(to, job);
+        Ok(())
+    }
+
+    /// Record that the job `id` ended with `status`, after `elapsed_seconds`.
+    pub fn finish(
+        &mut self,
+        id: u64,
+        status: JobStatus,
+        error: Option<String>,
+        elapsed_seconds: f64,
+    ) {
+        if let Some(job) = self.get_mut(id) {
+            job.status = status;
+            job.error = error;
+            job.percent = (status == JobStatus::Complete).then_some(100.0);
+            job.elapsed_seconds = Some(elapsed_seconds);
+        }
+    }
+
+    /// Cancel the job `id`: a waiting job is cancelled straight away, and
+    /// for a running one the caller has its engine stop. Returns the job's
+    /// status before.
+    pub fn cancel(&mut self, id: u64) -> Result<JobStatus, String> {
+        let job = self
+            .get_mut(id)
+            .ok_or_else(|| format!("there is no job {id}"))?;
+        let status = job.status;
+        match status {
+            JobStatus::Queued => {
+                job.status = JobStatus::Cancelled;
+                job.elapsed_seconds = Some(0.0);
+            }
+            JobStatus::Running => {}
+            _ => return Err(format!("job {id} has already finished")),
+        }
+        Ok(status)
+    }
+
+    /// Drop the finished jobs, returning them.
+    pub fn clear_finished(&mut self) -> Vec<Job> {
+        let (finished, kept) = self
+            .jobs
+            .drain(..)
+            .partition(|job| job.status.is_finished());
+        self.jobs = kept;
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A queue of jobs named `names`, in that order.
+    fn queue(names: &[&str]) -> JobQueue {
+        let mut queue = JobQueue::default();
+        for name in names {
+            let spec = JobSpec {
+                name: Some(name.to_string()),
+                config_dir: PathBuf::from("config"),
+                seed: None,
+            };
+            queue.push(spec, PathBuf::from("jobs").join(name));
+        }
+        queue
+    }
+
+    fn names(queue: &JobQueue) -> Vec<String> {
+        queue.jobs().into_iter().map(|job| job.name).collect()
+    }
+
+    #[test]
+    fn jobs_start_in_the_order_queued() {
+        let mut queue = queue(&["a", "b", "c"]);
+        assert_eq!(queue.next_id(), 4);
+        assert_eq!(queue.start_next().map(|job| job.name).as_deref(), Some("a"));
+        assert_eq!(queue.start_next().map(|job| job.name).as_deref(), Some("b"));
+        assert_eq!(queue.running(), 2);
+        queue.finish(1, JobStatus::Complete, None, 3.0);
+        assert_eq!(queue.get(1).unwrap().percent, Some(100.0));
+        assert_eq!(queue.running(), 1);
+        assert_eq!(queue.start_next().map(|job| job.name).as_deref(), Some("c"));
+        assert!(queue.start_next().is_none());
+    }
+
+    #[test]
+    fn jobs_without_a_name_are_named_after_their_config() {
+        let mut queue = JobQueue::default();
+        let spec = JobSpec {
+            name: Some("  ".into()),
+            config_dir: PathBuf::from("projects").join("numenor"),
+            seed: Some(7),
+        };
+        let id = queue.push(spec, PathBuf::from("out"));
+        assert_eq!(queue.get(id).unwrap().name, "numenor");
+    }
+
+    #[test]
+    fn waiting_jobs_move_among_the_waiting_ones() {
+        let mut queue = queue(&["a", "b", "c", "d"]);
+        queue.start_next();
+        queue.move_to(4, 0).unwrap();
+        assert_eq!(names(&queue), ["a", "d", "b", "c"]);
+        queue.move_to(4, 10).unwrap();
+        assert_eq!(names(&queue), ["a", "b", "c", "d"]);
+        queue.move_to(3, 1).unwrap();
+        assert_eq!(names(&queue), ["a", "b", "c", "d"]);
+        queue.move_to(2, 1).unwrap();
+        assert_eq!(names(&queue), ["a", "c", "b", "d"]);
+        assert_eq!(queue.start_next().map(|job| job.name).as_deref(), Some("c"));
+
+        assert_eq!(
+            queue.move_to(1, 0),
+            Err("job 1 is no longer waiting".into())
+        );
+        assert_eq!(queue.move_to(9, 0), Err("there is no job 9".into()));
+    }
+
+    #[test]
+    fn cancelling_depends_on_how_far_the_job_got() {
+        let mut queue = queue(&["a", "b", "c"]);
+        queue.start_next();
+        assert_eq!(queue.cancel(2), Ok(JobStatus::Queued));
+        assert_eq!(queue.get(2).unwrap().status, JobStatus::Cancelled);
+        // A running job is left for its run to stop.
+        assert_eq!(queue.cancel(1), Ok(JobStatus::Running));
+        assert_eq!(queue.get(1).unwrap().status, JobStatus::Running);
+        assert_eq!(queue.cancel(2), Err("job 2 has already finished".into()));
+        assert_eq!(queue.cancel(9), Err("there is no job 9".into()));
+        // The cancelled job is skipped.
+        assert_eq!(queue.start_next().map(|job| job.name).as_deref(), Some("c"));
+
+        queue.finish(1, JobStatus::Cancelled, None, 1.0);
+        let cleared: Vec<u64> = queue.clear_finished().iter().map(|job| job.id).collect();
+        assert_eq!(cleared, [1, 2]);
+        assert_eq!(names(&queue), ["c"]);
+    }
+}
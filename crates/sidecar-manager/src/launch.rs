@@ -0,0 +1,46 @@
+//! Launch options forwarded to the engine.
+//!
+//! Lets a sidecar start in a known state instead of relying on the engine's
+//! defaults: a different config directory, a fixed RNG seed for reproducible
+//! runs, and a different output directory. Each option maps onto one of the
+//! engine's command-line flags.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Directory under an output root holding the exported history files.
+pub const CHARACTER_DIR: &str = "Character and Title files";
+/// Directory under an output root holding the rendered family trees.
+pub const TREE_DIR: &str = "Dynasty Preview";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchOptions {
+    /// Directory holding the engine's JSON config files.
+    pub config_dir: Option<PathBuf>,
+    /// Seed for the engine's random number generator.
+    pub seed: Option<u64>,
+    /// Directory the engine writes its exports and family trees into.
+    pub output_dir: Option<PathBuf>,
+}
+
+impl LaunchOptions {
+    /// Command-line arguments passing these options to the engine.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(dir) = &self.config_dir {
+            args.push("--config-dir".to_string());
+            args.push(dir.display().to_string());
+        }
+        if let Some(seed) = self.seed {
+            args.push("--seed".to_string());
+            args.push(seed.to_string());
+        }
+        if let Some(dir) = &self.output_dir {
+            args.push("--output-dir".to_string());
+            args.push(dir.display().to_string());
+        }
+        args
+    }
+}
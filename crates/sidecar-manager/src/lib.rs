@@ -0,0 +1,49 @@
+//! Lifecycle management for the engine sidecar.
+//!
+//! Everything the Tauri shell needs to keep the Python engine alive and
+//! observable, independent of Tauri itself:
+//!
+//! - [`restart`]: when to restart a crashed engine, and when to give up.
+//! - [`status`]: the engine status the heartbeat tracks.
+//! - [`stderr`]: classification of what the engine writes to stderr.
+//! - [`launch`]: the options the engine is launched with, and the layout of
+//!   its output.
+//! - [`logs`]: rotating log files of its output.
+//! - [`port`]: loopback port allocation.
+//! - [`pidfile`]: PID files to find orphaned engines by.
+//! - [`protocol`] and [`progress`]: the typed messages the engine sends over
+//!   its stdout, and the progress they report.
+//! - [`shutdown`]: how long the engine gets to exit.
+//! - [`stats`]: sampling of its CPU and memory use.
+//! - [`jobs`]: the queue of generation jobs.
+//! - [`crash`]: crash reports.
+//!
+//! The shell only wires these into the `tauri-plugin-shell` process and the
+//! app's events and commands.
+
+pub mod crash;
+pub mod jobs;
+pub mod launch;
+pub mod logs;
+pub mod pidfile;
+pub mod port;
+pub mod progress;
+pub mod protocol;
+pub mod restart;
+pub mod shutdown;
+pub mod stats;
+pub mod status;
+pub mod stderr;
+
+pub use crash::{CrashKind, CrashReport};
+pub use jobs::{Job, JobQueue, JobSpec, JobStatus};
+pub use launch::LaunchOptions;
+pub use logs::{RotatingLog, Stream};
+pub use pidfile::{EngineRecord, PidFile};
+pub use progress::{Phase, Progress};
+pub use protocol::{Hello, LogLevel, LogRecord, Message, PROTOCOL_VERSION};
+pub use restart::{RestartPolicy, Restarts};
+pub use shutdown::{ShutdownPolicy, ShutdownToken};
+pub use stats::{EngineStats, ResourceMonitor};
+pub use status::{EngineStatus, HeartbeatMonitor};
+pub use stderr::{Classifier, StderrEntry, StderrKind};
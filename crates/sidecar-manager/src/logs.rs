@@ -0,0 +1,246 @@
+//! Rotating log files for engine and shell output.
+//!
+//! Every line is appended to a timestamped `<prefix><date>-<time>.log` file.
+//! Once a file grows past the size limit a new one is started, and only the
+//! newest files are kept.
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// Default size after which the current log file is rotated.
+pub const DEFAULT_MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+/// Default number of log files kept on disk.
+pub const DEFAULT_MAX_LOG_FILES: usize = 10;
+
+/// Which of the sidecar's streams a line came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    pub fn label(self) -> &'static str {
+        match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        }
+    }
+}
+
+/// A set of size-rotated log files sharing a directory and name prefix.
+#[derive(Debug)]
+pub struct RotatingLog {
+    dir: PathBuf,
+    prefix: String,
+    max_size: u64,
+    max_files: usize,
+    current: Option<LogFile>,
+}
+
+#[derive(Debug)]
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+}
+
+impl RotatingLog {
+    /// Log files named `<prefix><timestamp>.log` in `dir`, using the default
+    /// size and file-count limits. Nothing is created until the first write.
+    pub fn new(dir: impl Into<PathBuf>, prefix: impl Into<String>) -> Self {
+        Self {
+            dir: dir.into(),
+            prefix: prefix.into(),
+            max_size: DEFAULT_MAX_LOG_SIZE,
+            max_files: DEFAULT_MAX_LOG_FILES,
+            current: None,
+        }
+    }
+
+    pub fn with_limits(mut self, max_size: u64, max_files: usize) -> Self {
+        self.max_size = max_size;
+        self.max_files = max_files.max(1);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the file currently being written, if any.
+    pub fn current_path(&self) -> Option<&Path> {
+        self.current.as_ref().map(|log| log.path.as_path())
+    }
+
+    /// Append a timestamped line, rotating the current file if it is full.
+    pub fn write_line(&mut self, stream: Stream, line: &str) -> io::Result<()> {
+        self.write_labelled(stream.label(), line)
+    }
+
+    /// Append a timestamped line tagged `label` rather than a stream, such
+    /// as a log level.
+    pub fn write_labelled(&mut self, label: &str, line: &str) -> io::Result<()> {
+        if self
+            .current
+            .as_ref()
+            .is_none_or(|log| log.written >= self.max_size)
+        {
+            self.current = Some(self.open_new()?);
+        }
+
+        let log = self.current.as_mut().expect("log file was just opened");
+        let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+        let entry = format!("[{timestamp}] [{label}] {line}\n");
+        log.file.write_all(entry.as_bytes())?;
+        log.written += entry.len() as u64;
+        Ok(())
+    }
+
+    /// All log files with this prefix, oldest first.
+    ///
+    /// The timestamp in the file name sorts chronologically, so no metadata is
+    /// needed to order them.
+    pub fn files(&self) -> io::Result<Vec<PathBuf>> {
+        let mut logs: Vec<PathBuf> = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| {
+                            name.starts_with(&self.prefix) && name.ends_with(".log")
+                        })
+                })
+                .collect(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        logs.sort();
+        Ok(logs)
+    }
+
+    /// The last `limit` lines of the current file, or of the newest file from
+    /// an earlier session if nothing has been written yet.
+    pub fn recent_lines(&self, limit: usize) -> io::Result<Vec<String>> {
+        let path = match self.current_path() {
+            Some(path) => Some(path.to_path_buf()),
+            None => self.files()?.pop(),
+        };
+        let Some(path) = path else {
+            return Ok(Vec::new());
+        };
+
+        let mut recent = VecDeque::with_capacity(limit);
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if recent.len() == limit {
+                recent.pop_front();
+            }
+            if limit > 0 {
+                recent.push_back(line);
+            }
+        }
+        Ok(recent.into())
+    }
+
+    /// Start a new timestamped log file and prune the oldest ones.
+    fn open_new(&self) -> io::Result<LogFile> {
+        fs::create_dir_all(&self.dir)?;
+        let name = format!(
+            "{}{}.log",
+            self.prefix,
+            chrono::Local::now().format("%Y%m%d-%H%M%S%.3f")
+        );
+        let path = self.dir.join(name);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        let logs = self.files()?;
+        let excess = logs.len().saturating_sub(self.max_files);
+        for oldest in &logs[..excess] {
+            // A log that cannot be removed (e.g. open in an editor) is left for
+            // the next rotation rather than failing the write.
+            let _ = fs::remove_file(oldest);
+        }
+
+        Ok(LogFile {
+            path,
+            file,
+            written: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn log(name: &str) -> RotatingLog {
+        let dir = std::env::temp_dir().join(format!("ck3gen-logs-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        RotatingLog::new(dir, "engine-")
+    }
+
+    /// Write `line`, waiting first so that a rotation gets a file name of
+    /// its own.
+    fn write_apart(log: &mut RotatingLog, line: &str) {
+        thread::sleep(Duration::from_millis(5));
+        log.write_line(Stream::Stdout, line).unwrap();
+    }
+
+    #[test]
+    fn full_files_rotate_and_only_the_newest_are_kept() {
+        let mut log = log("rotate").with_limits(1, 2);
+        for line in ["first", "second", "third"] {
+            write_apart(&mut log, line);
+        }
+        let files = log.files().unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(log.current_path(), files.last().map(PathBuf::as_path));
+        let contents: Vec<String> = files
+            .iter()
+            .map(|file| fs::read_to_string(file).unwrap())
+            .collect();
+        assert!(
+            contents[0].ends_with("[stdout] second\n"),
+            "{}",
+            contents[0]
+        );
+        assert!(contents[1].ends_with("[stdout] third\n"), "{}", contents[1]);
+        fs::remove_dir_all(log.dir()).unwrap();
+    }
+
+    #[test]
+    fn files_are_only_rotated_once_full() {
+        let mut log = log("fill");
+        for line in ["first", "second"] {
+            write_apart(&mut log, line);
+        }
+        assert_eq!(log.files().unwrap().len(), 1);
+        fs::remove_dir_all(log.dir()).unwrap();
+    }
+
+    #[test]
+    fn recent_lines_are_the_last_of_the_current_file() {
+        let mut log = log("recent");
+        assert!(log.recent_lines(2).unwrap().is_empty());
+        for line in ["first", "second", "third"] {
+            log.write_labelled("INFO", line).unwrap();
+        }
+        let recent = log.recent_lines(2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert!(recent[0].ends_with("[INFO] second"), "{}", recent[0]);
+        assert!(recent[1].ends_with("[INFO] third"), "{}", recent[1]);
+        assert!(log.recent_lines(0).unwrap().is_empty());
+
+        // A later session reads the newest file of the earlier one.
+        let reopened = RotatingLog::new(log.dir(), "engine-");
+        assert_eq!(reopened.recent_lines(2).unwrap(), recent);
+        fs::remove_dir_all(log.dir()).unwrap();
+    }
+}
@@ -0,0 +1,153 @@
+//! PID files recording the engine processes of the current session.
+//!
+//! A sidecar outlives the shell if the app crashes. Each spawned process is
+//! recorded in a PID file that is removed again once the process exits, so a
+//! file still present at startup points at an orphan from an earlier session.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::shutdown::ShutdownToken;
+
+/// An engine process recorded in a PID file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EngineRecord {
+    pub pid: u32,
+    /// Loopback port the process was told to bind.
+    pub port: u16,
+    /// Token the process accepts shutdown requests with.
+    pub token: ShutdownToken,
+}
+
+#[derive(Clone, Debug)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record a freshly spawned process, replacing any previous record.
+    pub fn write(&self, record: EngineRecord) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(
+            &self.path,
+            format!("{} {} {}\n", record.pid, record.port, record.token),
+        )
+    }
+
+    /// Read the recorded process, or `None` if there is no valid record.
+    pub fn read(&self) -> Option<EngineRecord> {
+        let text = fs::read_to_string(&self.path).ok()?;
+        let mut fields = text.split_whitespace();
+        let pid = fields.next()?.parse().ok()?;
+        let port = fields.next()?.parse().ok()?;
+        // Files of older versions have no token.
+        let token = fields
+            .next()
+            .and_then(ShutdownToken::parse)
+            .unwrap_or_default();
+        Some(EngineRecord { pid, port, token })
+    }
+
+    /// Delete the file; a file that is already gone is not an error.
+    pub fn remove(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Whether `pid` is a running process whose executable name contains `name`.
+///
+/// Checking the name guards against the PID having been reused by an
+/// unrelated process since the file was written.
+pub fn is_running(pid: u32, name: &str) -> bool {
+    let output = if cfg!(windows) {
+        quiet(Command::new("tasklist").args(["/FO", "CSV", "/NH", "/FI", &format!("PID eq {pid}")]))
+            .output()
+    } else {
+        quiet(Command::new("ps").args(["-p", &pid.to_string(), "-o", "comm="])).output()
+    };
+    output.is_ok_and(|output| {
+        output.status.success() && String::from_utf8_lossy(&output.stdout).contains(name)
+    })
+}
+
+/// Forcibly terminate `pid` and, on Windows, its child processes.
+///
+/// The PyInstaller onefile bootloader runs the engine in a child process, so
+/// the whole tree has to go.
+pub fn terminate(pid: u32) -> io::Result<()> {
+    let status = if cfg!(windows) {
+        quiet(Command::new("taskkill").args(["/PID", &pid.to_string(), "/T", "/F"])).status()?
+    } else {
+        quiet(Command::new("kill").args(["-9", &pid.to_string()])).status()?
+    };
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "failed to terminate process {pid}"
+        )))
+    }
+}
+
+/// Keep helper commands from flashing a console window on Windows.
+fn quiet(command: &mut Command) -> &mut Command {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        /// `CREATE_NO_WINDOW` process creation flag.
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str) -> PidFile {
+        let dir = std::env::temp_dir().join(format!("ck3gen-pidfile-{}", std::process::id()));
+        PidFile::new(dir.join(name))
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let file = file("round-trip.pid");
+        let record = EngineRecord {
+            pid: 4321,
+            port: 51234,
+            token: ShutdownToken::generate(),
+        };
+        file.write(record).unwrap();
+        assert_eq!(file.read(), Some(record));
+        file.remove().unwrap();
+        assert_eq!(file.read(), None);
+        file.remove().unwrap();
+    }
+
+    #[test]
+    fn records_of_older_versions_have_no_token() {
+        let file = file("older.pid");
+        fs::create_dir_all(file.path().parent().unwrap()).unwrap();
+        fs::write(file.path(), "4321 51234\n").unwrap();
+        let record = file.read().unwrap();
+        assert_eq!((record.pid, record.port), (4321, 51234));
+        assert_eq!(record.token, ShutdownToken::default());
+        file.remove().unwrap();
+    }
+}
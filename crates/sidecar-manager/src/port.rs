@@ -0,0 +1,13 @@
+//! Loopback port allocation for the engine's HTTP server.
+
+use std::net::{Ipv4Addr, TcpListener};
+
+/// Ask the OS for an unused loopback port.
+///
+/// The listener is dropped before the sidecar binds the port, so another
+/// process could in theory grab it in between; callers should pick a new port
+/// when respawning the sidecar.
+pub fn free_loopback_port() -> std::io::Result<u16> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    Ok(listener.local_addr()?.port())
+}
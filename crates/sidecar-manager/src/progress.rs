@@ -0,0 +1,40 @@
+//! The engine's progress.
+//!
+//! While it generates, the engine sends a `progress` notification (see
+//! [`crate::protocol`]) each time its phase or the year it simulates
+//! changes, giving the phase, which of how many years it is on, the
+//! percentage of the whole run and the seconds it expects to take still.
+
+use serde::{Deserialize, Serialize};
+
+/// What a run is busy with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+    /// Setting up the dynasties, before the first year.
+    Seeding,
+    /// Simulating the years.
+    Simulating,
+    /// Writing the history files and family trees.
+    Writing,
+}
+
+/// Where a run is, as one progress notification gives it.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Progress {
+    /// Number of the run, counting from 1 since the engine started.
+    pub run: u64,
+    pub phase: Phase,
+    /// The year being simulated, or last simulated once writing.
+    pub year: Option<i32>,
+    /// Which of the `years` the `year` is, counting from 1.
+    pub year_index: Option<u32>,
+    /// Number of years the run simulates.
+    pub years: Option<u32>,
+    /// Share of the whole run done, from 0 to 100.
+    pub percent: f64,
+    /// Seconds the simulated years are expected to take still; unknown
+    /// before the first year is done and once all of them are.
+    pub eta_seconds: Option<f64>,
+}
@@ -0,0 +1,240 @@
+//! The messages the engine sends the shell over its stdout.
+//!
+//! Every message is a JSON-RPC 2.0 notification on a line of its own, so the
+//! shell reads typed messages rather than the engine's log wording: `hello`
+//! when the engine starts, giving the version of the protocol it speaks,
+//! `progress` each time a run's progress changes and `log` for every record
+//! of the engine's log. Lines that are not notifications are ordinary output,
+//! and notifications of methods this version does not know are passed on as
+//! [`Message::Unknown`] rather than dropped. The shell still makes its
+//! requests to the engine over HTTP. `ck3gen/protocol.py` is the engine's
+//! side; keep the two in step.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::progress::Progress;
+use crate::stderr::{StderrEntry, StderrKind};
+
+/// Version of the protocol the shell speaks, raised whenever a message
+/// changes in a way an older shell would misread.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// What the engine says it speaks when it starts.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Hello {
+    pub protocol: u32,
+    pub pid: Option<u32>,
+}
+
+impl Hello {
+    /// Why the shell cannot make sense of an engine speaking this, if it
+    /// cannot.
+    pub fn mismatch(&self) -> Option<String> {
+        (self.protocol != PROTOCOL_VERSION).then(|| {
+            format!(
+                "the engine speaks version {} of the shell protocol, but the shell speaks version \
+                 {PROTOCOL_VERSION}; reinstall the app so both are of the same release",
+                self.protocol
+            )
+        })
+    }
+}
+
+/// Level of a log record, as Python's `logging` names it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+/// A record of the engine's log.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    /// Name of the logger, e.g. `uvicorn.error`.
+    pub logger: String,
+    pub message: String,
+    /// The formatted exception the record was logged with, if any.
+    pub traceback: Option<String>,
+}
+
+impl LogRecord {
+    /// The record as the stderr line it used to be, e.g. `WARNING: ...`.
+    pub fn line(&self) -> String {
+        let level = match self.level {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warning => "WARNING",
+            LogLevel::Error => "ERROR",
+            LogLevel::Critical => "CRITICAL",
+        };
+        match &self.traceback {
+            Some(traceback) => format!("{level}: {}\n{traceback}", self.message),
+            None => format!("{level}: {}", self.message),
+        }
+    }
+}
+
+impl From<LogRecord> for StderrEntry {
+    fn from(record: LogRecord) -> Self {
+        let kind = match (record.level, &record.traceback) {
+            (_, Some(_)) => StderrKind::Traceback,
+            (LogLevel::Debug | LogLevel::Info, None) => StderrKind::Info,
+            (LogLevel::Warning, None) => StderrKind::Warning,
+            (LogLevel::Error | LogLevel::Critical, None) => StderrKind::Error,
+        };
+        StderrEntry {
+            kind,
+            message: record.line(),
+        }
+    }
+}
+
+/// A notification from the engine.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Hello(Hello),
+    Progress(Progress),
+    Log(LogRecord),
+    /// A notification of a method this version of the shell does not know,
+    /// or whose parameters it cannot read.
+    Unknown {
+        method: String,
+        params: Value,
+    },
+}
+
+#[derive(Deserialize)]
+struct Notification {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+impl Message {
+    /// The notification the stdout line `line` carries, if it is one.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if !line.starts_with('{') {
+            return None;
+        }
+        let notification: Notification = serde_json::from_str(line).ok()?;
+        if notification.jsonrpc != "2.0" {
+            return None;
+        }
+        let Notification { method, params, .. } = notification;
+        let message = match method.as_str() {
+            "hello" => serde_json::from_value(params.clone()).map(Message::Hello),
+            "progress" => serde_json::from_value(params.clone()).map(Message::Progress),
+            "log" => serde_json::from_value(params.clone()).map(Message::Log),
+            _ => return Some(Message::Unknown { method, params }),
+        };
+        Some(message.unwrap_or(Message::Unknown { method, params }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::progress::Phase;
+
+    #[test]
+    fn notifications_parse_into_their_messages() {
+        assert_eq!(
+            Message::parse(
+                r#"{"jsonrpc":"2.0","method":"hello","params":{"protocol":1,"pid":42}}"#
+            ),
+            Some(Message::Hello(Hello {
+                protocol: 1,
+                pid: Some(42),
+            }))
+        );
+        let progress = r#"  {"jsonrpc":"2.0","method":"progress","params":{"run":2,"phase":"simulating","year":7010,"yearIndex":11,"years":100,"percent":12.5,"etaSeconds":30.0}}"#;
+        assert_eq!(
+            Message::parse(progress),
+            Some(Message::Progress(Progress {
+                run: 2,
+                phase: Phase::Simulating,
+                year: Some(7010),
+                year_index: Some(11),
+                years: Some(100),
+                percent: 12.5,
+                eta_seconds: Some(30.0),
+            }))
+        );
+        let log = r#"{"jsonrpc":"2.0","method":"log","params":{"level":"WARNING","logger":"ck3gen","message":"no titles","traceback":null}}"#;
+        let Some(Message::Log(record)) = Message::parse(log) else {
+            panic!("not a log record");
+        };
+        assert_eq!(record.level, LogLevel::Warning);
+        assert_eq!(record.line(), "WARNING: no titles");
+    }
+
+    #[test]
+    fn other_lines_are_not_messages() {
+        for line in [
+            "INFO: Uvicorn running",
+            "{not json",
+            r#"{"jsonrpc":"1.0","method":"hello","params":{"protocol":1}}"#,
+            r#"{"method":"hello","params":{"protocol":1}}"#,
+            "",
+        ] {
+            assert_eq!(Message::parse(line), None, "{line}");
+        }
+    }
+
+    #[test]
+    fn unknown_methods_and_unreadable_params_are_passed_on() {
+        assert_eq!(
+            Message::parse(r#"{"jsonrpc":"2.0","method":"stats","params":{"cpu":3}}"#),
+            Some(Message::Unknown {
+                method: "stats".into(),
+                params: json!({"cpu": 3}),
+            })
+        );
+        assert_eq!(
+            Message::parse(r#"{"jsonrpc":"2.0","method":"hello","params":{"protocol":"one"}}"#),
+            Some(Message::Unknown {
+                method: "hello".into(),
+                params: json!({"protocol": "one"}),
+            })
+        );
+    }
+
+    #[test]
+    fn another_protocol_version_is_a_mismatch() {
+        let hello = |protocol| Hello {
+            protocol,
+            pid: None,
+        };
+        assert_eq!(hello(PROTOCOL_VERSION).mismatch(), None);
+        let mismatch = hello(PROTOCOL_VERSION + 1).mismatch().unwrap();
+        assert!(
+            mismatch.contains(&format!("version {}", PROTOCOL_VERSION + 1)),
+            "{mismatch}"
+        );
+    }
+
+    #[test]
+    fn records_with_a_traceback_are_tracebacks() {
+        let entry = StderrEntry::from(LogRecord {
+            level: LogLevel::Error,
+            logger: "ck3gen".into(),
+            message: "run failed".into(),
+            traceback: Some("Traceback (most recent call last):\nKeyError".into()),
+        });
+        assert_eq!(entry.kind, StderrKind::Traceback);
+        assert_eq!(
+            entry.message,
+            "ERROR: run failed\nTraceback (most recent call last):\nKeyError"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Restart policy for a crashed sidecar.
+//!
+//! Restarts back off exponentially and give up after a fixed number of
+//! consecutive attempts. A process that stays up long enough is considered
+//! healthy again, so an occasional crash in a long session does not use up the
+//! budget.
+
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug)]
+pub struct RestartPolicy {
+    /// Consecutive restarts allowed before the engine is declared unrecoverable.
+    pub max_restarts: u32,
+    /// Delay before the first restart; doubled for every further attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound for the restart delay.
+    pub max_backoff: Duration,
+    /// A process that stayed up at least this long resets the attempt count.
+    pub stable_uptime: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            stable_uptime: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Exponential backoff for the given 0-based restart attempt.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// Restart bookkeeping for one supervised process.
+#[derive(Debug)]
+pub struct Restarts {
+    policy: RestartPolicy,
+    attempt: u32,
+}
+
+impl Restarts {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Self { policy, attempt: 0 }
+    }
+
+    /// Number of restarts since the process was last healthy.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    pub fn policy(&self) -> &RestartPolicy {
+        &self.policy
+    }
+
+    /// Record that the process exited after running for `uptime`.
+    ///
+    /// Returns the delay to wait before restarting it, or `None` once the
+    /// restart budget is exhausted.
+    pub fn next_delay(&mut self, uptime: Duration) -> Option<Duration> {
+        if uptime >= self.policy.stable_uptime {
+            self.attempt = 0;
+        }
+        if self.attempt >= self.policy.max_restarts {
+            return None;
+        }
+        let delay = self.policy.delay(self.attempt);
+        self.attempt += 1;
+        Some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RestartPolicy {
+        RestartPolicy {
+            max_restarts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(350),
+            stable_uptime: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = policy();
+        let delays: Vec<u128> = (0..4).map(|n| policy.delay(n).as_millis()).collect();
+        assert_eq!(delays, [100, 200, 350, 350]);
+        assert_eq!(policy.delay(u32::MAX), policy.max_backoff);
+    }
+
+    #[test]
+    fn gives_up_after_the_budget() {
+        let mut restarts = Restarts::new(policy());
+        let crash = Duration::from_secs(1);
+        assert_eq!(restarts.next_delay(crash), Some(Duration::from_millis(100)));
+        assert_eq!(restarts.next_delay(crash), Some(Duration::from_millis(200)));
+        assert_eq!(restarts.next_delay(crash), Some(Duration::from_millis(350)));
+        assert_eq!(restarts.next_delay(crash), None);
+        assert_eq!(restarts.attempt(), 3);
+    }
+
+    #[test]
+    fn stable_uptime_resets_the_budget() {
+        let mut restarts = Restarts::new(policy());
+        for _ in 0..3 {
+            restarts.next_delay(Duration::from_secs(1));
+        }
+        assert_eq!(
+            restarts.next_delay(Duration::from_secs(60)),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(restarts.attempt(), 1);
+    }
+}
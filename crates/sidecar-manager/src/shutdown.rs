@@ -0,0 +1,120 @@
+//! Shutdown policy for the engine.
+//!
+//! The engine is first asked to exit so it can finish writing its output, and
+//! only killed once the shutdown timeout has run out. The request carries a
+//! token the shell gives each engine it spawns, so that no other local
+//! process can stop it.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Environment variable overriding [`ShutdownPolicy::timeout`], in seconds.
+pub const SHUTDOWN_TIMEOUT_ENV: &str = "CK3GEN_SHUTDOWN_TIMEOUT";
+/// Environment variable an engine is given its [`ShutdownToken`] in.
+pub const SHUTDOWN_TOKEN_ENV: &str = "CK3GEN_SHUTDOWN_TOKEN";
+/// Header of a shutdown request holding the engine's token.
+pub const SHUTDOWN_TOKEN_HEADER: &str = "X-CK3Gen-Token";
+
+/// The secret an engine accepts shutdown requests with, written as 32 hex
+/// digits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShutdownToken(u128);
+
+impl ShutdownToken {
+    /// A fresh token from the operating system's random source.
+    pub fn generate() -> Self {
+        let mut bytes = [0; 16];
+        getrandom::fill(&mut bytes).expect("the operating system has a random source");
+        Self(u128::from_le_bytes(bytes))
+    }
+
+    /// The token written as `text`, or `None` if it is not one.
+    pub fn parse(text: &str) -> Option<Self> {
+        (text.len() == 32)
+            .then(|| u128::from_str_radix(text, 16).ok())
+            .flatten()
+            .map(Self)
+    }
+}
+
+impl fmt::Display for ShutdownToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ShutdownPolicy {
+    /// Time the engine gets to exit after a shutdown request.
+    pub timeout: Duration,
+    /// How long the shutdown request itself may take to be acknowledged.
+    pub request_timeout: Duration,
+    /// Interval at which the process is checked for having exited.
+    pub poll_interval: Duration,
+}
+
+impl Default for ShutdownPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(2),
+            poll_interval: Duration::from_millis(50),
+        }
+    }
+}
+
+impl ShutdownPolicy {
+    /// The default policy, with the timeout taken from
+    /// [`SHUTDOWN_TIMEOUT_ENV`] when it is set to a whole number of seconds.
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Some(secs) = std::env::var(SHUTDOWN_TIMEOUT_ENV)
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+        {
+            policy.timeout = Duration::from_secs(secs);
+        }
+        policy
+    }
+
+    /// Poll `exited` until it returns true or the timeout elapses.
+    ///
+    /// Returns whether the process exited in time.
+    pub async fn wait_for_exit(&self, mut exited: impl FnMut() -> bool) -> bool {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            if exited() {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_round_trip_through_text() {
+        let token = ShutdownToken::generate();
+        let text = token.to_string();
+        assert_eq!(text.len(), 32);
+        assert_eq!(ShutdownToken::parse(&text), Some(token));
+    }
+
+    #[test]
+    fn tokens_differ_between_engines() {
+        assert_ne!(ShutdownToken::generate(), ShutdownToken::generate());
+    }
+
+    #[test]
+    fn malformed_tokens_are_rejected() {
+        assert_eq!(ShutdownToken::parse(""), None);
+        assert_eq!(ShutdownToken::parse("abc"), None);
+        assert_eq!(ShutdownToken::parse(&"g".repeat(32)), None);
+    }
+}
@@ -0,0 +1,81 @@
+//! CPU and memory sampling of the engine process.
+//!
+//! The PyInstaller onefile bootloader runs the actual Python interpreter in a
+//! child process, so the spawned PID alone says little; samples cover the
+//! spawned process and all of its descendants.
+
+use serde::Serialize;
+use sysinfo::{MemoryRefreshKind, Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// One resource sample of the engine process tree.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineStats {
+    /// CPU usage since the previous sample, where 100 is one fully used core.
+    pub cpu_percent: f32,
+    /// Resident memory of the process tree.
+    pub memory_bytes: u64,
+    /// Physical memory of the machine, to put `memory_bytes` in context.
+    pub total_memory_bytes: u64,
+    /// Number of processes in the tree.
+    pub processes: usize,
+}
+
+/// Samples the resource usage of a process tree.
+///
+/// CPU usage is measured between two samples, so the first sample of a new
+/// process reports zero.
+pub struct ResourceMonitor {
+    system: System,
+}
+
+impl Default for ResourceMonitor {
+    fn default() -> Self {
+        Self {
+            system: System::new(),
+        }
+    }
+}
+
+impl ResourceMonitor {
+    /// Sample `pid` and its descendants, or `None` if the process is gone.
+    pub fn sample(&mut self, pid: u32) -> Option<EngineStats> {
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_cpu().with_memory(),
+        );
+        self.system
+            .refresh_memory_specifics(MemoryRefreshKind::nothing().with_ram());
+
+        let root = Pid::from_u32(pid);
+        self.system.process(root)?;
+
+        let mut tree = vec![root];
+        let mut i = 0;
+        while i < tree.len() {
+            let parent = tree[i];
+            tree.extend(
+                self.system
+                    .processes()
+                    .iter()
+                    .filter(|(_, process)| process.parent() == Some(parent))
+                    .map(|(pid, _)| *pid),
+            );
+            i += 1;
+        }
+
+        let mut stats = EngineStats {
+            cpu_percent: 0.0,
+            memory_bytes: 0,
+            total_memory_bytes: self.system.total_memory(),
+            processes: 0,
+        };
+        for process in tree.iter().filter_map(|pid| self.system.process(*pid)) {
+            stats.cpu_percent += process.cpu_usage();
+            stats.memory_bytes += process.memory();
+            stats.processes += 1;
+        }
+        Some(stats)
+    }
+}
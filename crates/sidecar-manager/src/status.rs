@@ -0,0 +1,108 @@
+//! Engine status as seen by the heartbeat.
+
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineStatus {
+    /// Spawned (or not yet reachable) and still booting.
+    Starting,
+    /// Answering heartbeats and idle.
+    Ready,
+    /// Answering heartbeats while a simulation is running.
+    Busy,
+    /// Terminated, or stopped answering heartbeats.
+    Dead,
+}
+
+impl EngineStatus {
+    /// Map the `status` field of the engine's `/health` response.
+    pub fn from_health(status: &str) -> Self {
+        match status {
+            "busy" => EngineStatus::Busy,
+            _ => EngineStatus::Ready,
+        }
+    }
+}
+
+/// Folds heartbeat results into an engine status.
+#[derive(Debug)]
+pub struct HeartbeatMonitor {
+    max_missed: u32,
+    missed: u32,
+}
+
+impl Default for HeartbeatMonitor {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl HeartbeatMonitor {
+    /// A running engine is declared dead after `max_missed` consecutive
+    /// missed heartbeats.
+    pub fn new(max_missed: u32) -> Self {
+        Self {
+            max_missed,
+            missed: 0,
+        }
+    }
+
+    /// Record the outcome of one heartbeat given the `current` status.
+    ///
+    /// `reply` is the status the engine reported, or `None` if it did not
+    /// answer. Returns the new status, or `None` if it should stay as it is.
+    pub fn observe(
+        &mut self,
+        current: EngineStatus,
+        reply: Option<EngineStatus>,
+    ) -> Option<EngineStatus> {
+        match reply {
+            Some(status) => {
+                self.missed = 0;
+                Some(status)
+            }
+            // A booting engine is expected to be unreachable for a while; the
+            // supervisor reports it dead if it never comes up.
+            None if current == EngineStatus::Starting => None,
+            None => {
+                self.missed += 1;
+                (self.missed >= self.max_missed).then_some(EngineStatus::Dead)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declared_dead_after_consecutive_misses() {
+        let mut monitor = HeartbeatMonitor::new(2);
+        assert_eq!(monitor.observe(EngineStatus::Ready, None), None);
+        assert_eq!(
+            monitor.observe(EngineStatus::Ready, None),
+            Some(EngineStatus::Dead)
+        );
+    }
+
+    #[test]
+    fn a_reply_resets_the_misses() {
+        let mut monitor = HeartbeatMonitor::new(2);
+        monitor.observe(EngineStatus::Ready, None);
+        assert_eq!(
+            monitor.observe(EngineStatus::Ready, Some(EngineStatus::Busy)),
+            Some(EngineStatus::Busy)
+        );
+        assert_eq!(monitor.observe(EngineStatus::Busy, None), None);
+    }
+
+    #[test]
+    fn a_booting_engine_is_not_declared_dead() {
+        let mut monitor = HeartbeatMonitor::new(1);
+        for _ in 0..5 {
+            assert_eq!(monitor.observe(EngineStatus::Starting, None), None);
+        }
+    }
+}
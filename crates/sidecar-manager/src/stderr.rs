@@ -0,0 +1,185 @@
+//! Classification of the engine's stderr output.
+//!
+//! Python logging, uvicorn and uncaught exceptions all write to stderr. Lines
+//! are classified by their logging prefix and multi-line tracebacks are
+//! gathered into a single entry.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+/// First line Python prints for an uncaught exception.
+const TRACEBACK_HEADER: &str = "Traceback (most recent call last):";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StderrKind {
+    Info,
+    Warning,
+    Error,
+    Traceback,
+}
+
+impl StderrKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            StderrKind::Info => "INFO",
+            StderrKind::Warning => "WARNING",
+            StderrKind::Error => "ERROR",
+            StderrKind::Traceback => "TRACEBACK",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct StderrEntry {
+    pub kind: StderrKind,
+    /// The line, or the full traceback joined with newlines.
+    pub message: String,
+}
+
+impl StderrEntry {
+    /// Whether the entry is worth keeping beyond the session log.
+    pub fn is_problem(&self) -> bool {
+        self.kind != StderrKind::Info
+    }
+
+    /// Append the entry, timestamped, to the log file at `path`.
+    pub fn append_to(&self, path: &Path) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+        writeln!(file, "[{timestamp}] {} {}", self.kind.label(), self.message)
+    }
+}
+
+/// Turns a stream of stderr lines into [`StderrEntry`]s.
+#[derive(Debug, Default)]
+pub struct Classifier {
+    /// Lines of the traceback currently being collected, if any.
+    traceback: Option<Vec<String>>,
+}
+
+impl Classifier {
+    /// Feed one line; returns an entry once one is complete.
+    pub fn push(&mut self, line: &str) -> Option<StderrEntry> {
+        if let Some(lines) = &mut self.traceback {
+            lines.push(line.to_owned());
+            // Frames are indented and chained exceptions are separated by
+            // blank lines and a short explanation; the first other line is the
+            // exception itself, which ends the traceback.
+            let continues = line.is_empty()
+                || line.starts_with(char::is_whitespace)
+                || line == TRACEBACK_HEADER
+                || line.starts_with("During handling of the above exception")
+                || line.starts_with("The above exception was the direct cause");
+            return if continues { None } else { self.finish() };
+        }
+
+        if line == TRACEBACK_HEADER {
+            self.traceback = Some(vec![line.to_owned()]);
+            return None;
+        }
+
+        Some(StderrEntry {
+            kind: classify(line),
+            message: line.to_owned(),
+        })
+    }
+
+    /// Flush a traceback that was cut short, e.g. because the process exited.
+    pub fn finish(&mut self) -> Option<StderrEntry> {
+        self.traceback.take().map(|lines| StderrEntry {
+            kind: StderrKind::Traceback,
+            message: lines.join("\n"),
+        })
+    }
+}
+
+/// Classify a single line by its logging level prefix.
+fn classify(line: &str) -> StderrKind {
+    let line = line.trim_start();
+    if line.starts_with("WARNING") {
+        StderrKind::Warning
+    } else if line.starts_with("ERROR") || line.starts_with("CRITICAL") {
+        StderrKind::Error
+    } else {
+        StderrKind::Info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify_all(lines: &[&str]) -> Vec<(StderrKind, String)> {
+        let mut classifier = Classifier::default();
+        let mut entries: Vec<StderrEntry> = lines
+            .iter()
+            .filter_map(|line| classifier.push(line))
+            .collect();
+        entries.extend(classifier.finish());
+        entries
+            .into_iter()
+            .map(|entry| (entry.kind, entry.message))
+            .collect()
+    }
+
+    #[test]
+    fn lines_are_classified_by_their_level() {
+        let entries = classify_all(&[
+            "INFO:     Uvicorn running on http://127.0.0.1:8000",
+            "WARNING: dynasty lineofa has no titles",
+            "  ERROR failed to write the trees",
+            "CRITICAL: out of memory",
+            "plain output",
+        ]);
+        let kinds: Vec<StderrKind> = entries.iter().map(|(kind, _)| *kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                StderrKind::Info,
+                StderrKind::Warning,
+                StderrKind::Error,
+                StderrKind::Error,
+                StderrKind::Info,
+            ]
+        );
+        assert_eq!(entries[1].1, "WARNING: dynasty lineofa has no titles");
+    }
+
+    #[test]
+    fn tracebacks_are_gathered_up_to_the_exception() {
+        let traceback = [
+            TRACEBACK_HEADER,
+            "  File \"simulation.py\", line 10, in run",
+            "    raise KeyError(key)",
+            "KeyError: 'lineofa'",
+        ];
+        let mut lines = traceback.to_vec();
+        lines.push("INFO: shutting down");
+        assert_eq!(
+            classify_all(&lines),
+            [
+                (StderrKind::Traceback, traceback.join("\n")),
+                (StderrKind::Info, "INFO: shutting down".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_traceback_cut_short_is_flushed_by_finish() {
+        let mut classifier = Classifier::default();
+        assert!(classifier.push(TRACEBACK_HEADER).is_none());
+        assert!(classifier.push("  File \"simulation.py\"").is_none());
+        let entry = classifier.finish().unwrap();
+        assert_eq!(entry.kind, StderrKind::Traceback);
+        assert_eq!(
+            entry.message,
+            format!("{TRACEBACK_HEADER}\n  File \"simulation.py\"")
+        );
+        assert!(entry.is_problem());
+        assert!(classifier.finish().is_none());
+    }
+}
@@ -12,13 +12,16 @@ Full pipeline per run:
   2. Run the year-by-year simulation
   3. Export character history  →  Character and Title files/family_history.txt
   4. Build and write title histories  →  Character and Title files/title_history.txt
+     and, for configured titles, Character and Title files/history/titles/
   5. Render Graphviz family tree images  →  Dynasty Preview/
 """
 
 from __future__ import annotations
 
 import logging
+import random
 import sys
+import time
 from pathlib import Path
 
 # Ensure the project root is on sys.path whether this file is run directly or
@@ -27,14 +30,16 @@ PROJECT_ROOT = Path(__file__).parent
 if str(PROJECT_ROOT) not in sys.path:
     sys.path.insert(0, str(PROJECT_ROOT))
 
-from ck3gen.config_loader import ConfigLoader, NUM_SIMULATIONS
+from ck3gen.calendar import Calendar
+from ck3gen.config_loader import ConfigLoader, NUM_SIMULATIONS, SEED
+from ck3gen.control import WRITING_STEPS, SimulationCancelled, control
 from ck3gen.dynasty_creation import (
     generate_dynasty_definitions,
     generate_dynasty_name_localization,
     generate_dynasty_motto_localization,
 )
 from ck3gen.name_loader import NameLoader
-from ck3gen.paths import CHARACTER_OUTPUT_DIR, CONFIG_DIR
+from ck3gen.paths import CHARACTER_OUTPUT_DIR, CONFIG_DIR, TREE_OUTPUT_DIR
 
 logging.basicConfig(
     level=logging.INFO,
@@ -42,10 +47,43 @@ logging.basicConfig(
 )
 
 
-def run_main() -> None:
-    """Load config, run all simulation(s), and write every output file."""
+def run_main(dynasties: list[str] | None = None) -> int:
+    """Load config, run all simulation(s), and write every output file.
+
+    ``dynasties`` restricts the run to the given dynasty IDs; the Tauri shell
+    uses it to shard a project across several engine workers. Returns the
+    number of characters generated.
+
+    A cancelled run deletes the output files it had written so far, so that
+    no half-written history is left behind, and raises
+    :class:`SimulationCancelled` listing them.
+    """
+    started = time.time()
+    try:
+        return _run_pipeline(dynasties)
+    except SimulationCancelled:
+        removed = _remove_partial_output(started)
+        logging.info("Simulation cancelled; removed %d partial output file(s).", len(removed))
+        raise SimulationCancelled(removed=[str(path) for path in removed]) from None
+
+
+def _run_pipeline(dynasties: list[str] | None) -> int:
+    control.seeding()
     config_loader = ConfigLoader(config_folder=CONFIG_DIR)
     config = config_loader.config
+    if dynasties is not None:
+        init = config["initialization"]
+        init["dynasties"] = [d for d in init["dynasties"] if d["dynastyID"] in dynasties]
+        if not init["dynasties"]:
+            raise ValueError("None of the requested dynasties are defined in the configuration.")
+
+    # Reseed on every run so repeated runs with the same seed match. Unseeded
+    # runs pick a seed of their own, which is recorded in the output so the
+    # run can still be reproduced; they stay below 2**53 so that the UI can
+    # hold them as JavaScript numbers.
+    seed = SEED if SEED is not None else random.randrange(2**53)
+    random.seed(seed)
+    logging.info("Seed: %d", seed)
 
     # NameLoader takes a folder path, not the config dict.
     name_loader = NameLoader(name_list_folder=str(PROJECT_ROOT / "name_lists"))
@@ -56,33 +94,63 @@ def run_main() -> None:
     generate_dynasty_name_localization(config_file)
     generate_dynasty_motto_localization(config_file)
 
+    characters = 0
     for i in range(NUM_SIMULATIONS):
         if NUM_SIMULATIONS > 1:
             logging.info("── Simulation %d / %d ──", i + 1, NUM_SIMULATIONS)
 
         # ── 1. Simulate ───────────────────────────────────────────────────────
         from ck3gen.simulation import Simulation  # noqa: PLC0415
+        control.seeding()
         simulation = Simulation(config, name_loader)
         simulation.run_simulation()
+        characters += len(simulation.all_characters)
 
         # ── 2. Export character history ───────────────────────────────────────
-        simulation.export_characters()
+        control.writing(0)
+        simulation.export_characters(seed=seed)
 
         # ── 3. Title histories ────────────────────────────────────────────────
-        _run_title_history(config_file)
+        control.writing(1)
+        _run_title_history(config_file, Calendar.from_config(config["initialization"]))
 
         # ── 4. Family tree images ─────────────────────────────────────────────
+        control.writing(2)
         _run_family_trees(config)
+        control.writing(WRITING_STEPS)
 
     logging.info("Done. Output written to '%s'.", CHARACTER_OUTPUT_DIR)
+    return characters
 
 
 # ---------------------------------------------------------------------------
 #  Pipeline helpers
 # ---------------------------------------------------------------------------
 
-def _run_title_history(config_file: Path) -> None:
-    """Parse the exported character file and write title_history.txt."""
+def _remove_partial_output(since: float) -> list[Path]:
+    """Delete the output files written since the time ``since``, and the
+    folders that leaves empty."""
+    removed: list[Path] = []
+    for root in (CHARACTER_OUTPUT_DIR, TREE_OUTPUT_DIR):
+        if not root.is_dir():
+            continue
+        for path in sorted(root.rglob("*"), reverse=True):
+            try:
+                if path.is_file() and path.stat().st_mtime >= since:
+                    path.unlink()
+                    removed.append(path)
+                elif path.is_dir() and not any(path.iterdir()):
+                    path.rmdir()
+            except OSError as exc:
+                logging.warning("Could not remove partial output '%s': %s", path, exc)
+    return removed
+
+
+def _run_title_history(config_file: Path, calendar: Calendar) -> None:
+    """
+    Parse the exported character file, dated in ``calendar``, write the title
+    histories and add the guardians of child heirs to the character file.
+    """
     from ck3gen.title_history import CharacterLoader, TitleHistory  # noqa: PLC0415
 
     character_file = CHARACTER_OUTPUT_DIR / "family_history.txt"
@@ -90,12 +158,14 @@ def _run_title_history(config_file: Path) -> None:
         logging.warning("family_history.txt not found — skipping title history.")
         return
 
-    loader = CharacterLoader()
+    loader = CharacterLoader(calendar)
     loader.load_characters(str(character_file))
 
     history = TitleHistory(loader, str(config_file))
     history.build_title_histories()
     history.write_title_histories_to_file()
+    history.write_landed_title_files()
+    history.write_guardianships_to_file()
 
 
 def _run_family_trees(config: dict) -> None:
@@ -1,3 +1,3 @@
 fn main() {
     tauri_build::build()
-}
\ No newline at end of file
+}
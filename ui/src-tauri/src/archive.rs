@@ -0,0 +1,315 @@
+//! Project archives, for sharing a complete setup.
+//!
+//! An archive is a zip of everything another user needs to reproduce a
+//! project: `project.ck3hist`, the project file `save_project` writes but
+//! without the output folder, a path on the exporting machine; the name
+//! lists under `name_lists/`; the last history under `output/characters/`
+//! and its family trees under `output/trees/`; and `manifest.json`, saying
+//! which version of the archive format and of the app wrote it.
+//! `import_project_archive` reads and checks the whole archive before
+//! putting anything in place, then opens its project as `open_project`
+//! does, keeping the importer's output folder, and writes its name lists
+//! and outputs over those of the engine. Entries outside those folders, or
+//! whose paths would lead out of them, are refused.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ck3_config::{ConfigError, Project};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::config::document_error;
+use crate::launch::EngineLaunch;
+use crate::output::{self, EnginePaths};
+use crate::project_file;
+
+/// Version of the archive format written.
+const ARCHIVE_FORMAT: u32 = 1;
+const MANIFEST_ENTRY: &str = "manifest.json";
+const PROJECT_ENTRY: &str = "project.ck3hist";
+const NAME_LISTS_PREFIX: &str = "name_lists/";
+const CHARACTERS_PREFIX: &str = "output/characters/";
+const TREES_PREFIX: &str = "output/trees/";
+/// Most bytes the files of an archive may unpack to, so that a malicious
+/// archive cannot fill the disk.
+const MAX_UNPACKED_BYTES: u64 = 1 << 30;
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    format: u32,
+    app_version: String,
+    /// Milliseconds since the Unix epoch.
+    exported_at: u64,
+}
+
+/// Where the files of an archive folder go.
+#[derive(Clone, Copy)]
+enum Folder {
+    NameLists,
+    Characters,
+    Trees,
+}
+
+impl Folder {
+    const ALL: [Folder; 3] = [Folder::NameLists, Folder::Characters, Folder::Trees];
+
+    fn prefix(self) -> &'static str {
+        match self {
+            Folder::NameLists => NAME_LISTS_PREFIX,
+            Folder::Characters => CHARACTERS_PREFIX,
+            Folder::Trees => TREES_PREFIX,
+        }
+    }
+
+    fn dir(self, paths: &EnginePaths) -> &Path {
+        match self {
+            Folder::NameLists => &paths.name_lists_dir,
+            Folder::Characters => &paths.character_dir,
+            Folder::Trees => &paths.tree_dir,
+        }
+    }
+}
+
+/// How many files of each kind an archive held.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveContents {
+    pub name_lists: usize,
+    pub outputs: usize,
+}
+
+/// How an imported archive was put in place.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedArchive {
+    #[serde(flatten)]
+    pub contents: ArchiveContents,
+    /// Whether the seed changed, which the engine only picks up once
+    /// restarted.
+    pub restart_needed: bool,
+}
+
+/// Write the current project, its name lists and its last outputs to the
+/// archive at `path`.
+#[tauri::command]
+pub async fn export_project_archive(
+    app: AppHandle,
+    path: PathBuf,
+) -> Result<ArchiveContents, Vec<ConfigError>> {
+    let mut project = project_file::current_project(&app).await?;
+    project.output.output_dir = None;
+    let paths = output::current_paths(&app).await.map_err(document_error)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        write_archive(&path, &project, &paths)
+            .map_err(|err| document_error(format!("cannot write {}: {err}", path.display())))
+    })
+    .await
+    .map_err(document_error)?
+}
+
+/// Read the archive at `path` and put it in place.
+#[tauri::command]
+pub async fn import_project_archive(
+    app: AppHandle,
+    path: PathBuf,
+) -> Result<ImportedArchive, Vec<ConfigError>> {
+    let (mut project, files) = tauri::async_runtime::spawn_blocking(move || read_archive(&path))
+        .await
+        .map_err(document_error)??;
+    project.output.output_dir = app.state::<EngineLaunch>().options().output_dir;
+    let opened = project_file::put_in_place(&app, project).await?;
+
+    let paths = output::current_paths(&app).await.map_err(document_error)?;
+    let contents = tauri::async_runtime::spawn_blocking(move || {
+        let mut contents = ArchiveContents::default();
+        for (folder, relative, bytes) in files {
+            let path = folder.dir(&paths).join(&relative);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|err| {
+                    document_error(format!("cannot create {}: {err}", parent.display()))
+                })?;
+            }
+            fs::write(&path, bytes)
+                .map_err(|err| document_error(format!("cannot write {}: {err}", path.display())))?;
+            match folder {
+                Folder::NameLists => contents.name_lists += 1,
+                Folder::Characters | Folder::Trees => contents.outputs += 1,
+            }
+        }
+        Ok::<_, Vec<ConfigError>>(contents)
+    })
+    .await
+    .map_err(document_error)??;
+    Ok(ImportedArchive {
+        contents,
+        restart_needed: opened.restart_needed,
+    })
+}
+
+fn write_archive(
+    path: &Path,
+    project: &Project,
+    paths: &EnginePaths,
+) -> io::Result<ArchiveContents> {
+    let manifest = Manifest {
+        format: ARCHIVE_FORMAT,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as u64),
+    };
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default();
+    zip.start_file(MANIFEST_ENTRY, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+    zip.start_file(PROJECT_ENTRY, options)?;
+    zip.write_all(project.to_json().as_bytes())?;
+
+    let mut contents = ArchiveContents::default();
+    for folder in Folder::ALL {
+        let count = add_dir(&mut zip, options, folder.dir(paths), folder.prefix())?;
+        match folder {
+            Folder::NameLists => contents.name_lists += count,
+            Folder::Characters | Folder::Trees => contents.outputs += count,
+        }
+    }
+    zip.finish()?;
+    Ok(contents)
+}
+
+/// Add the files under `dir`, if there is one, as entries under `prefix`,
+/// and return how many there were.
+fn add_dir(
+    zip: &mut ZipWriter<File>,
+    options: SimpleFileOptions,
+    dir: &Path,
+    prefix: &str,
+) -> io::Result<usize> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err),
+    };
+    let mut entries = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    let mut count = 0;
+    for path in entries {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if path.is_dir() {
+            count += add_dir(zip, options, &path, &format!("{prefix}{name}/"))?;
+        } else {
+            zip.start_file(format!("{prefix}{name}"), options)?;
+            io::copy(&mut File::open(&path)?, zip)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+type ArchiveFile = (Folder, PathBuf, Vec<u8>);
+
+/// The project of the archive at `path` and the files to put in place,
+/// each in its folder, or every problem found.
+fn read_archive(path: &Path) -> Result<(Project, Vec<ArchiveFile>), Vec<ConfigError>> {
+    let file = File::open(path)
+        .map_err(|err| document_error(format!("cannot read {}: {err}", path.display())))?;
+    let mut zip = ZipArchive::new(file)
+        .map_err(|err| document_error(format!("{} is not a zip archive: {err}", path.display())))?;
+
+    let mut errors = Vec::new();
+    let mut manifest = None;
+    let mut project = None;
+    let mut seen_project = false;
+    let mut files = Vec::new();
+    let mut unpacked = 0u64;
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).map_err(document_error)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        unpacked += entry.size();
+        if unpacked > MAX_UNPACKED_BYTES {
+            return Err(document_error(format!(
+                "{} unpacks to more than {} MB",
+                path.display(),
+                MAX_UNPACKED_BYTES >> 20
+            )));
+        }
+        let mut bytes = Vec::new();
+        if let Err(err) = (&mut entry)
+            .take(MAX_UNPACKED_BYTES)
+            .read_to_end(&mut bytes)
+        {
+            errors.push(ConfigError::new(name, format!("cannot be read: {err}")));
+            continue;
+        }
+
+        if name == MANIFEST_ENTRY {
+            match serde_json::from_slice::<Manifest>(&bytes) {
+                Ok(read) => manifest = Some(read),
+                Err(err) => errors.push(ConfigError::new(name, format!("is not valid: {err}"))),
+            }
+        } else if name == PROJECT_ENTRY {
+            seen_project = true;
+            match String::from_utf8(bytes) {
+                Ok(text) => match ck3_config::parse_project(&text) {
+                    Ok(read) => project = Some(read),
+                    Err(problems) => {
+                        errors.extend(problems.into_iter().map(|problem| ConfigError {
+                            path: format!("{PROJECT_ENTRY}: {}", problem.path),
+                            ..problem
+                        }))
+                    }
+                },
+                Err(_) => errors.push(ConfigError::new(name, "is not UTF-8 text")),
+            }
+        } else {
+            let placed = Folder::ALL.into_iter().find_map(|folder| {
+                let relative = Path::new(name.strip_prefix(folder.prefix())?);
+                let safe = relative
+                    .components()
+                    .all(|component| matches!(component, std::path::Component::Normal(_)));
+                safe.then(|| (folder, relative.to_path_buf()))
+            });
+            match placed {
+                Some((folder, relative)) => files.push((folder, relative, bytes)),
+                None => errors.push(ConfigError::new(name, "is not part of a project archive")),
+            }
+        }
+    }
+
+    match &manifest {
+        None => errors.extend(document_error(format!(
+            "the archive holds no {MANIFEST_ENTRY}"
+        ))),
+        Some(manifest) if manifest.format > ARCHIVE_FORMAT => errors.push(ConfigError::new(
+            MANIFEST_ENTRY,
+            format!(
+                "is of archive format {}, but this version of the app reads up to {ARCHIVE_FORMAT}; \
+                 the archive was written by version {}",
+                manifest.format, manifest.app_version
+            ),
+        )),
+        Some(_) => {}
+    }
+    if !seen_project {
+        errors.extend(document_error(format!(
+            "the archive holds no {PROJECT_ENTRY}"
+        )));
+    }
+    match project {
+        Some(project) if errors.is_empty() => Ok((project, files)),
+        _ => Err(errors),
+    }
+}
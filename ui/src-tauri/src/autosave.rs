@@ -0,0 +1,278 @@
+//! Autosave and crash recovery.
+//!
+//! While the app runs, `session.json` in the app data directory says so; a
+//! clean exit removes it. Every [`AUTOSAVE_INTERVAL`] the shell snapshots
+//! what would be lost if the app went away, should it have changed: the
+//! config the UI is editing, as `set_working_config` last reported it, the
+//! jobs waiting or running and whether the primary engine was generating,
+//! into `autosave.json` next to it. If `session.json` is still there at the
+//! next launch, the last session ended abnormally and its snapshot is kept
+//! for `get_recovery` to offer. `restore_recovery` hands the configs back
+//! for the UI to take up as unsaved edits and queues the jobs again, the
+//! interrupted generation among them as a job of its own with the seed it
+//! was started with; `discard_recovery` forgets the snapshot.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ck3_sim::config::LIFE_STAGES_FILE;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sidecar_manager::JobSpec;
+use tauri::{AppHandle, Manager};
+
+use crate::config::INITIALIZATION_FILE;
+use crate::generation::Generation;
+use crate::jobs;
+use crate::launch::EngineLaunch;
+use crate::output;
+use crate::store;
+use crate::undo::ConfigFile;
+
+/// File in the app data directory present while a session runs.
+const SESSION_FILE: &str = "session.json";
+/// File in the app data directory holding the last snapshot.
+const AUTOSAVE_FILE: &str = "autosave.json";
+/// Time between snapshots.
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// What a session would lose, as last snapshot.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot {
+    /// Milliseconds since the Unix epoch.
+    saved_at: u64,
+    /// The project's config directory, if the engine had told it.
+    config_dir: Option<PathBuf>,
+    /// The configs being edited, if the UI had changed them.
+    initialization: Option<Value>,
+    life_stages: Option<Value>,
+    /// Jobs waiting or running, running ones first.
+    jobs: Vec<JobSpec>,
+    /// Whether the primary engine was generating, and how far it had got.
+    generating: bool,
+    percent: Option<f64>,
+    /// Seed of the launch options, that of the interrupted generation.
+    seed: Option<u64>,
+}
+
+impl Snapshot {
+    fn same_work(&self, other: &Snapshot) -> bool {
+        Snapshot {
+            saved_at: other.saved_at,
+            ..self.clone()
+        } == *other
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Session {
+    pid: u32,
+    started_at: u64,
+}
+
+/// Managed state holding the configs the UI reported, and the snapshot of
+/// a session that ended abnormally.
+#[derive(Default)]
+pub struct Autosave {
+    initialization: Mutex<Option<Value>>,
+    life_stages: Mutex<Option<Value>>,
+    recovery: Mutex<Option<Snapshot>>,
+}
+
+/// What a session that ended abnormally left, for the UI to offer.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recovery {
+    /// Milliseconds since the Unix epoch.
+    pub saved_at: u64,
+    pub config_dir: Option<PathBuf>,
+    /// Whether the configs being edited differ from those on disk.
+    pub config_edits: bool,
+    pub jobs: usize,
+    /// How far the interrupted generation had got, if one was going.
+    pub interrupted_at: Option<f64>,
+}
+
+/// What `restore_recovery` put back.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Restored {
+    /// The configs to take up as unsaved edits, if they differ from those
+    /// on disk.
+    pub initialization: Option<Value>,
+    pub life_stages: Option<Value>,
+    /// Jobs queued again, the interrupted generation included.
+    pub jobs: usize,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as u64)
+}
+
+/// Keep the snapshot of the last session if it ended abnormally, mark this
+/// one as running and start snapshotting it.
+pub fn start(app: &AppHandle) {
+    let session = store::data_file(app, SESSION_FILE);
+    if session.as_ref().is_ok_and(|path| path.exists()) {
+        let snapshot: Snapshot = store::load(store::data_file(app, AUTOSAVE_FILE));
+        if snapshot != Snapshot::default() {
+            tracing::warn!("the last session ended abnormally; its work can be restored");
+            *app.state::<Autosave>().recovery.lock().unwrap() = Some(snapshot);
+        }
+    }
+    let marked = session.and_then(|path| {
+        store::save(
+            &path,
+            &Session {
+                pid: std::process::id(),
+                started_at: now_ms(),
+            },
+        )
+    });
+    if let Err(err) = marked {
+        tracing::warn!("failed to mark the session as running: {err}");
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last = Snapshot::default();
+        loop {
+            tokio::time::sleep(AUTOSAVE_INTERVAL).await;
+            let snapshot = snapshot(&app).await;
+            if snapshot.same_work(&last) {
+                continue;
+            }
+            match store::data_file(&app, AUTOSAVE_FILE)
+                .and_then(|path| store::save(&path, &snapshot))
+            {
+                Ok(()) => last = snapshot,
+                Err(err) => tracing::warn!("failed to autosave: {err}"),
+            }
+        }
+    });
+}
+
+/// End the session cleanly, leaving nothing to recover.
+pub fn shutdown(app: &AppHandle) {
+    for name in [SESSION_FILE, AUTOSAVE_FILE] {
+        if let Ok(path) = store::data_file(app, name) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+async fn snapshot(app: &AppHandle) -> Snapshot {
+    let config_dir = output::current_paths(app)
+        .await
+        .ok()
+        .map(|paths| paths.config_dir);
+    let autosave = app.state::<Autosave>();
+    let initialization = autosave.initialization.lock().unwrap().clone();
+    let life_stages = autosave.life_stages.lock().unwrap().clone();
+    let progress = app.state::<Generation>().progress();
+    Snapshot {
+        saved_at: now_ms(),
+        config_dir,
+        initialization,
+        life_stages,
+        jobs: jobs::unfinished(app),
+        generating: progress.running,
+        percent: progress.percent.filter(|_| progress.running),
+        seed: app.state::<EngineLaunch>().options().seed,
+    }
+}
+
+/// The configs of `snapshot` that differ from those in its config directory.
+fn config_edits(snapshot: &Snapshot) -> (Option<Value>, Option<Value>) {
+    let on_disk = |name: &str| -> Option<Value> {
+        let dir = snapshot.config_dir.as_ref()?;
+        serde_json::from_str(&fs::read_to_string(dir.join(name)).ok()?).ok()
+    };
+    let edited = |config: &Option<Value>, name: &str| {
+        config
+            .clone()
+            .filter(|config| Some(config) != on_disk(name).as_ref())
+    };
+    (
+        edited(&snapshot.initialization, INITIALIZATION_FILE),
+        edited(&snapshot.life_stages, LIFE_STAGES_FILE),
+    )
+}
+
+/// Keep `config` as the config file `file` being edited, for the next
+/// snapshot.
+#[tauri::command]
+pub fn set_working_config(app: AppHandle, file: ConfigFile, config: Value) {
+    let autosave = app.state::<Autosave>();
+    let slot = match file {
+        ConfigFile::Initialization => &autosave.initialization,
+        ConfigFile::LifeStages => &autosave.life_stages,
+    };
+    *slot.lock().unwrap() = Some(config);
+}
+
+/// What the last session left, if it ended abnormally with work to restore.
+#[tauri::command]
+pub fn get_recovery(app: AppHandle) -> Option<Recovery> {
+    let recovery = app.state::<Autosave>().recovery.lock().unwrap().clone()?;
+    let (initialization, life_stages) = config_edits(&recovery);
+    let config_edits = initialization.is_some() || life_stages.is_some();
+    let interrupted_at = recovery.generating.then(|| recovery.percent.unwrap_or(0.0));
+    if !config_edits && recovery.jobs.is_empty() && interrupted_at.is_none() {
+        return None;
+    }
+    Some(Recovery {
+        saved_at: recovery.saved_at,
+        config_dir: recovery.config_dir,
+        config_edits,
+        jobs: recovery.jobs.len(),
+        interrupted_at,
+    })
+}
+
+/// Put back what the last session left, and forget it.
+#[tauri::command]
+pub fn restore_recovery(app: AppHandle) -> Result<Restored, String> {
+    let recovery = app
+        .state::<Autosave>()
+        .recovery
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or("there is nothing to restore")?;
+    let (initialization, life_stages) = config_edits(&recovery);
+    let mut specs = recovery.jobs.clone();
+    if recovery.generating {
+        if let Some(config_dir) = recovery.config_dir.clone() {
+            specs.push(JobSpec {
+                name: Some("Interrupted generation".into()),
+                config_dir,
+                seed: recovery.seed,
+            });
+        }
+    }
+    let mut queued = 0;
+    for spec in specs {
+        match jobs::enqueue(&app, spec) {
+            Ok(_) => queued += 1,
+            Err(err) => tracing::warn!("failed to queue a recovered job again: {err}"),
+        }
+    }
+    Ok(Restored {
+        initialization,
+        life_stages,
+        jobs: queued,
+    })
+}
+
+/// Forget what the last session left.
+#[tauri::command]
+pub fn discard_recovery(app: AppHandle) {
+    app.state::<Autosave>().recovery.lock().unwrap().take();
+}
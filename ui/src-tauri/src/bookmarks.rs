@@ -0,0 +1,42 @@
+//! Bookmark snapshots of the Python engine's runs.
+//!
+//! Native runs write a snapshot of the characters living on each date of
+//! the config's `bookmarkSnapshots` along with their history. The Python
+//! engine does not know the setting, so once one of its runs has completed
+//! the shell writes the snapshots from its output instead.
+
+use ck3_config::InitializationConfig;
+use tauri::AppHandle;
+
+use crate::config::INITIALIZATION_FILE;
+use crate::output;
+
+/// Write the snapshots of the run the primary engine just finished, in the
+/// background.
+pub fn write_finished(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let paths = match output::current_paths(&app).await {
+            Ok(paths) => paths,
+            Err(err) => {
+                tracing::warn!("not writing the bookmark snapshots: {err}");
+                return;
+            }
+        };
+        let written = tauri::async_runtime::spawn_blocking(move || {
+            let path = paths.config_dir.join(INITIALIZATION_FILE);
+            let text = std::fs::read_to_string(&path)
+                .map_err(|err| format!("cannot read {}: {err}", path.display()))?;
+            let config: InitializationConfig = ck3_config::parse_initialization(&text)
+                .map_err(|_| format!("{INITIALIZATION_FILE} is invalid"))?;
+            ck3_sim::write_snapshots(&paths.character_dir, &config.bookmark_snapshots)
+                .map_err(|err| err.to_string())
+        })
+        .await
+        .map_err(|err| err.to_string())
+        .and_then(|written| written);
+        if let Err(err) = written {
+            tracing::warn!("writing the bookmark snapshots failed: {err}");
+        }
+    });
+}
@@ -0,0 +1,145 @@
+//! Boot watchdog for the packaged engine.
+//!
+//! If the sidecar cannot bind its port or crashes on startup, the frontend
+//! has nothing to talk to and the window stays blank. The watchdog waits for
+//! the first successful heartbeat and, if none arrives in time, shows a native
+//! error dialog with the engine's recent stderr output and an option to copy
+//! the full diagnostics to the clipboard.
+
+use std::time::{Duration, Instant};
+
+use sidecar_manager::EngineStatus;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::endpoint::ApiEndpoint;
+use crate::health::EngineHealth;
+use crate::logs::{self, EngineLog};
+use crate::sidecar::Sidecar;
+use crate::splash;
+
+/// Environment variable overriding [`DEFAULT_BOOT_TIMEOUT`], in seconds.
+const BOOT_TIMEOUT_ENV: &str = "CK3GEN_BOOT_TIMEOUT";
+/// Time the engine gets to answer its first heartbeat.
+const DEFAULT_BOOT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Interval at which the engine status is checked while booting.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Log lines scanned for stderr output when building the diagnostics.
+const DIAGNOSTIC_LOG_LINES: usize = 500;
+/// Stderr lines shown in the dialog itself; the copied report has them all.
+const DIALOG_STDERR_LINES: usize = 15;
+
+/// Start the watchdog in the background.
+///
+/// Does nothing in development, where the engine is started by run_ui.bat,
+/// nor when the engine is embedded, which reports its own failure to start.
+pub fn start(app: &AppHandle) {
+    if cfg!(debug_assertions) || cfg!(feature = "embedded-engine") {
+        return;
+    }
+    tauri::async_runtime::spawn(watch(app.clone()));
+}
+
+async fn watch(app: AppHandle) {
+    let timeout = boot_timeout();
+    let deadline = Instant::now() + timeout;
+    loop {
+        let status = app.state::<EngineHealth>().status();
+        if matches!(status, EngineStatus::Ready | EngineStatus::Busy) {
+            return;
+        }
+        // The supervisor gives up early when the engine keeps crashing.
+        let reason = if !app.state::<Sidecar>().is_supervising() {
+            "The engine exited repeatedly while starting up."
+        } else if Instant::now() >= deadline {
+            &format!(
+                "The engine did not start within {} seconds.",
+                timeout.as_secs()
+            )
+        } else {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            continue;
+        };
+        report_failure(&app, reason);
+        return;
+    }
+}
+
+fn boot_timeout() -> Duration {
+    std::env::var(BOOT_TIMEOUT_ENV)
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_BOOT_TIMEOUT)
+}
+
+/// Show the failure dialog, copying the diagnostics if the user asks for it.
+fn report_failure(app: &AppHandle, reason: &str) {
+    splash::boot_failed(app);
+    let stderr = recent_stderr(app);
+    let diagnostics = diagnostics(app, reason, &stderr);
+    tracing::error!("{diagnostics}");
+
+    let mut message = format!("{reason}\n\n");
+    if stderr.is_empty() {
+        message.push_str("The engine did not write any error output.");
+    } else {
+        message.push_str("Last error output:\n");
+        let shown = stderr.len().saturating_sub(DIALOG_STDERR_LINES);
+        message.push_str(&stderr[shown..].join("\n"));
+    }
+
+    let handle = app.clone();
+    app.dialog()
+        .message(message)
+        .title("CK3 Character History Generator failed to start")
+        .kind(MessageDialogKind::Error)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Copy diagnostics".into(),
+            "Close".into(),
+        ))
+        .show(move |copy| {
+            if copy {
+                if let Err(err) = handle.clipboard().write_text(diagnostics) {
+                    tracing::warn!("failed to copy diagnostics: {err}");
+                }
+            }
+        });
+}
+
+/// Stderr lines from the current engine log, without the log's own prefixes.
+fn recent_stderr(app: &AppHandle) -> Vec<String> {
+    let lines = app
+        .state::<EngineLog>()
+        .recent_lines(app, DIAGNOSTIC_LOG_LINES)
+        .unwrap_or_default();
+    lines
+        .iter()
+        .filter_map(|line| line.split_once("[stderr] "))
+        .map(|(_, message)| message.to_string())
+        .collect()
+}
+
+/// Plain-text report suitable for pasting into a bug report.
+fn diagnostics(app: &AppHandle, reason: &str, stderr: &[String]) -> String {
+    let log_dir = logs::log_dir(app)
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|err| format!("unavailable ({err})"));
+    let mut report = format!(
+        "{reason}\n\nVersion: {}\nPlatform: {} {}\nEngine URL: {}\nEngine status: {:?}\nLog folder: {log_dir}\n\nStderr:\n",
+        app.package_info().version,
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        app.state::<ApiEndpoint>().base_url(),
+        app.state::<EngineHealth>().status(),
+    );
+    if stderr.is_empty() {
+        report.push_str("(none)\n");
+    }
+    for line in stderr {
+        report.push_str(line);
+        report.push('\n');
+    }
+    report
+}
@@ -0,0 +1,30 @@
+//! Exporting a generation as a written chronicle.
+//!
+//! `export_chronicle` writes the last export, whichever engine generated it,
+//! as a Markdown chronicle with a chapter per dynasty, telling year by year
+//! who was born, married, came to a title and died, for lore writers to build
+//! their own documentation on.
+
+use std::fs;
+use std::path::PathBuf;
+
+use ck3_sim::HISTORY_FILE;
+use tauri::AppHandle;
+
+use crate::output;
+
+/// Write the last export's chronicle to the Markdown file `path`, replacing
+/// it if it exists.
+#[tauri::command]
+pub async fn export_chronicle(app: AppHandle, path: PathBuf) -> Result<(), String> {
+    let dir = output::current_paths(&app).await?.character_dir;
+    if !dir.join(HISTORY_FILE).is_file() {
+        return Err("nothing has been generated yet".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let chronicle = ck3_sim::output_chronicle(&dir).map_err(|err| err.to_string())?;
+        fs::write(&path, chronicle).map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,219 @@
+//! Headless command-line mode.
+//!
+//! `ck3-charhist generate --config init.json --seed 42 --out ./mod` runs the
+//! native engine's whole pipeline without opening a window, for scripts and
+//! mod build pipelines: it reads the config, simulates it, checks the
+//! history as `run_native_simulation` does and builds a mod from it in the
+//! output folder as `scaffold_mod` does. `--config` names either an
+//! `initialization.json`, whose folder the other config files are read
+//! from, or a config folder. Name lists are read from `--names`, or else
+//! from the `name_lists` folder beside the config folder. Any other command
+//! line starts the app as usual.
+//!
+//! Progress and the summary go to stderr and stdout; the exit code is 0 on
+//! success, 1 if the run failed and 2 if the command line was not
+//! understood.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use ck3_sim::{NameLists, SimConfig, Simulation};
+
+use crate::{native, scaffold};
+
+/// The command that runs headless.
+const GENERATE: &str = "generate";
+/// Folder of the name lists, beside the config folder, if `--names` is not
+/// given.
+const NAME_LISTS_DIR: &str = "name_lists";
+
+const USAGE: &str = "\
+usage: ck3-charhist generate --config <initialization.json | config folder> --out <mod folder>
+                             [--seed <n>] [--names <folder>] [--game <folder>] [--parallel]
+
+  --config    the project's initialization.json, or the folder holding it
+  --out       folder to build the mod in, created if needed
+  --seed      seed of the run; a random one if not given
+  --names     folder of the name lists; the name_lists folder beside the config folder if not given
+  --game      game installation or total conversion to check the config against
+  --parallel  simulate each dynasty on a thread of its own";
+
+#[derive(Debug, Default)]
+struct Generate {
+    config: PathBuf,
+    out: PathBuf,
+    seed: Option<u64>,
+    names: Option<PathBuf>,
+    game: Option<PathBuf>,
+    parallel: bool,
+}
+
+/// Run the command line if it is a headless command, returning the exit
+/// code, or `None` if the app should start as usual.
+pub fn run() -> Option<ExitCode> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) != Some(GENERATE) {
+        return None;
+    }
+    attach_console();
+    let code = match parse(&args[1..]) {
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+        Ok(None) => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Ok(Some(generate)) => match generate.run() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("error: {err}");
+                ExitCode::FAILURE
+            }
+        },
+    };
+    Some(code)
+}
+
+/// The options of `generate`, or `None` if help was asked for.
+fn parse(args: &[String]) -> Result<Option<Generate>, String> {
+    let mut generate = Generate::default();
+    let (mut config, mut out) = (None, None);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{arg} needs a value"))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--config" => config = Some(PathBuf::from(value()?)),
+            "--out" => out = Some(PathBuf::from(value()?)),
+            "--seed" => {
+                let seed = value()?;
+                generate.seed = Some(
+                    seed.parse()
+                        .map_err(|_| format!("{seed} is not a seed; seeds are whole numbers"))?,
+                );
+            }
+            "--names" => generate.names = Some(PathBuf::from(value()?)),
+            "--game" => generate.game = Some(PathBuf::from(value()?)),
+            "--parallel" => generate.parallel = true,
+            _ => return Err(format!("unknown option {arg}")),
+        }
+    }
+    generate.config = config.ok_or("--config is required")?;
+    generate.out = out.ok_or("--out is required")?;
+    Ok(Some(generate))
+}
+
+impl Generate {
+    fn run(&self) -> Result<(), String> {
+        let (config_dir, config) = self.load_config()?;
+        let rules = config.initialization.clone();
+        let names_dir = match &self.names {
+            Some(dir) => dir.clone(),
+            None => config_dir
+                .parent()
+                .map(|parent| parent.join(NAME_LISTS_DIR))
+                .filter(|dir| dir.is_dir())
+                .ok_or_else(|| {
+                    format!(
+                        "there is no {NAME_LISTS_DIR} folder beside {}; name one with --names",
+                        config_dir.display()
+                    )
+                })?,
+        };
+
+        eprintln!("simulating {} ...", config_dir.display());
+        let names = NameLists::new(names_dir);
+        let (simulation, summary) = if self.parallel {
+            Simulation::run_parallel(config, names, self.seed)
+        } else {
+            let mut simulation = Simulation::new(config, names, self.seed);
+            let summary = simulation.run();
+            (simulation, summary)
+        };
+        native::check_marriages(&simulation.history(), &rules)?;
+        native::check_references(simulation.dangling_references())?;
+
+        // The history is written as the engine writes it, then laid out as a
+        // mod, like the app's own runs.
+        let history_dir = std::env::temp_dir().join(format!("ck3-charhist-{}", std::process::id()));
+        let built = simulation
+            .write_history(&history_dir)
+            .map_err(|err| err.to_string())
+            .and_then(|()| {
+                fs::create_dir_all(&self.out)
+                    .map_err(|err| format!("cannot create {}: {err}", self.out.display()))?;
+                let settings = scaffold::settings_of(&rules, &scaffold::folder_name(&self.out));
+                scaffold::scaffold(&history_dir, &self.out, &settings)
+                    .map_err(|err| format!("cannot build the mod: {err}"))
+            });
+        let _ = fs::remove_dir_all(&history_dir);
+        let written = built?;
+
+        println!(
+            "seed {}: {} characters from {} to {}, {} living at the end, in {} dynasties",
+            simulation.seed(),
+            summary.characters,
+            summary.first_year,
+            summary.last_year,
+            summary.living_at_end,
+            summary.dynasties.len()
+        );
+        for error in &summary.plugin_errors {
+            eprintln!("warning: plugin failed: {error}");
+        }
+        println!("wrote {} files to {}", written.len(), self.out.display());
+        Ok(())
+    }
+
+    /// The config folder and the config read from it, with the
+    /// `initialization.json` given if one was.
+    fn load_config(&self) -> Result<(PathBuf, SimConfig), String> {
+        let (dir, config) = if self.config.is_dir() {
+            let config = SimConfig::load(&self.config).map_err(|err| err.to_string())?;
+            (self.config.clone(), config)
+        } else {
+            // A bare file name is in the working directory.
+            let dir = match self.config.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                _ => PathBuf::from("."),
+            };
+            let text = fs::read_to_string(&self.config)
+                .map_err(|err| format!("cannot read {}: {err}", self.config.display()))?;
+            let initialization = ck3_config::parse_initialization(&text).map_err(|errors| {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                format!(
+                    "{} is not valid:\n{}",
+                    self.config.display(),
+                    errors.join("\n")
+                )
+            })?;
+            let config =
+                SimConfig::load_with(&dir, initialization).map_err(|err| err.to_string())?;
+            (dir, config)
+        };
+        let dir = dir.canonicalize().unwrap_or(dir);
+        Ok((dir, native::target_game(config, self.game.as_deref())))
+    }
+}
+
+/// Release builds run without a console on Windows; write to that of the
+/// shell the command was typed in, if there is one.
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // SAFETY: AttachConsole has no preconditions; it fails harmlessly if
+    // the process already has a console or its parent has none.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
@@ -0,0 +1,51 @@
+//! Reading and writing `initialization.json`.
+//!
+//! The engine only notices a malformed config once a run is under way, often
+//! with a bare `KeyError`. The shell validates the file against the typed
+//! model in `ck3-config` instead and reports every problem with the path of
+//! the offending value, so the UI can point at the broken field.
+
+use std::fs;
+use std::path::PathBuf;
+
+use ck3_config::{ConfigError, InitializationConfig};
+use tauri::AppHandle;
+
+use crate::output;
+
+/// Name of the config file inside the engine's config directory.
+pub const INITIALIZATION_FILE: &str = "initialization.json";
+
+async fn initialization_path(app: &AppHandle) -> Result<PathBuf, Vec<ConfigError>> {
+    let paths = output::current_paths(app).await.map_err(document_error)?;
+    Ok(paths.config_dir.join(INITIALIZATION_FILE))
+}
+
+/// An error that concerns the file as a whole rather than one value.
+pub fn document_error(message: impl ToString) -> Vec<ConfigError> {
+    vec![ConfigError::new("", message.to_string())]
+}
+
+/// Read and validate the engine's `initialization.json`.
+#[tauri::command]
+pub async fn load_config(app: AppHandle) -> Result<InitializationConfig, Vec<ConfigError>> {
+    let path = initialization_path(&app).await?;
+    let text = fs::read_to_string(&path)
+        .map_err(|err| document_error(format!("cannot read {}: {err}", path.display())))?;
+    ck3_config::parse_initialization(&text)
+}
+
+/// Validate `config` and write it to the engine's `initialization.json`.
+///
+/// Nothing is written unless the whole document is valid.
+#[tauri::command]
+pub async fn save_config(
+    app: AppHandle,
+    config: serde_json::Value,
+) -> Result<(), Vec<ConfigError>> {
+    let config = ck3_config::initialization_from_value(config)?;
+    let text = config.to_json_pretty().map_err(document_error)?;
+    let path = initialization_path(&app).await?;
+    fs::write(&path, text)
+        .map_err(|err| document_error(format!("cannot write {}: {err}", path.display())))
+}
@@ -0,0 +1,211 @@
+//! Crash reports for the shell and the engine.
+//!
+//! Most bug reports used to arrive as "it just closed". A panic in the shell
+//! and an engine exit the shell did not ask for now each leave a report zip
+//! (see [`sidecar_manager::crash`]) in the `crashes` folder of the log
+//! directory. Nothing leaves the machine unless the user sends a report with
+//! `submit_crash_report`.
+
+use std::backtrace::Backtrace;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use sidecar_manager::crash::{self, DEFAULT_MAX_REPORTS};
+use sidecar_manager::{CrashKind, CrashReport, EngineStatus};
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::config;
+use crate::endpoint::ApiEndpoint;
+use crate::health::EngineHealth;
+use crate::launch::EngineLaunch;
+use crate::logs;
+use crate::output;
+
+/// Emitted with a [`CrashReportInfo`] when the engine crashed and a report
+/// was written. Shell crashes are only found by `list_crash_reports` on the
+/// next launch.
+pub const CRASH_REPORT_EVENT: &str = "crash-report";
+
+/// Where `submit_crash_report` uploads reports, set at build time. Without it
+/// the user is sent to the issue tracker with the report to attach.
+const REPORT_URL: Option<&str> = option_env!("CK3GEN_CRASH_REPORT_URL");
+const NEW_ISSUE_URL: &str = "https://github.com/jj248/CK3-Character-History-Generator/issues/new";
+/// Folder inside the log directory holding the reports.
+const CRASH_DIR: &str = "crashes";
+/// Lines of engine output included in a report.
+const REPORT_LOG_LINES: usize = 500;
+/// Interval at which a new engine is asked for its config directory.
+const PATHS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReportInfo {
+    /// File name of the report, which `submit_crash_report` takes.
+    pub name: String,
+    pub path: PathBuf,
+    pub kind: Option<CrashKind>,
+}
+
+impl CrashReportInfo {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            name: file_name(&path),
+            kind: CrashKind::of_report(&path),
+            path,
+        }
+    }
+}
+
+/// Managed state remembering where the running engine reads its config, so
+/// it can be included once the engine is gone.
+#[derive(Default)]
+pub struct CrashContext {
+    config_dir: Mutex<Option<PathBuf>>,
+}
+
+/// Write a report for every panic and keep track of the engine's config.
+///
+/// Called first in setup so panics during startup are reported too.
+pub fn start(app: &AppHandle) {
+    let handle = app.clone();
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let message = format!("{info}\n\n{}", Backtrace::force_capture());
+        // Printed rather than logged: the panic may hold the log's locks.
+        match write(&handle, CrashKind::Shell, message, None) {
+            Ok(path) => eprintln!("crash report written to {}", path.display()),
+            Err(err) => eprintln!("failed to write a crash report: {err}"),
+        }
+    }));
+
+    tauri::async_runtime::spawn(follow_config_dir(app.clone()));
+}
+
+/// Ask each new engine for its config directory once it is up.
+async fn follow_config_dir(app: AppHandle) {
+    let mut asked = None;
+    loop {
+        tokio::time::sleep(PATHS_POLL_INTERVAL).await;
+        let status = app.state::<EngineHealth>().status();
+        let base_url = app.state::<ApiEndpoint>().base_url();
+        if !matches!(status, EngineStatus::Ready | EngineStatus::Busy)
+            || asked.as_ref() == Some(&base_url)
+        {
+            continue;
+        }
+        if let Ok(paths) = output::current_paths(&app).await {
+            *app.state::<CrashContext>().config_dir.lock().unwrap() = Some(paths.config_dir);
+            asked = Some(base_url);
+        }
+    }
+}
+
+/// Write a report for an engine that exited with `exit_code` on its own.
+pub fn engine_exited(app: &AppHandle, exit_code: Option<i32>, message: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        match write(&app, CrashKind::Engine, message, exit_code) {
+            Ok(path) => {
+                let _ = app.emit(CRASH_REPORT_EVENT, CrashReportInfo::new(path));
+            }
+            Err(err) => tracing::error!("failed to write a crash report: {err}"),
+        }
+    });
+}
+
+fn write(
+    app: &AppHandle,
+    kind: CrashKind,
+    message: String,
+    exit_code: Option<i32>,
+) -> std::io::Result<PathBuf> {
+    let report = CrashReport {
+        kind,
+        message,
+        exit_code,
+        app_version: app.package_info().version.to_string(),
+        // Read from disk: a panic may have interrupted a write to the log.
+        log_lines: logs::read_recent(app, REPORT_LOG_LINES).unwrap_or_default(),
+        config: config_snapshot(app),
+        home_dir: app.path().home_dir().ok(),
+    };
+    report.write(&crash_dir(app)?, DEFAULT_MAX_REPORTS)
+}
+
+/// The launch options and the engine's `initialization.json`, if readable.
+fn config_snapshot(app: &AppHandle) -> Value {
+    let launch = app.state::<EngineLaunch>().options();
+    let config_dir = app
+        .state::<CrashContext>()
+        .config_dir
+        .lock()
+        .unwrap()
+        .clone();
+    let initialization = config_dir
+        .or_else(|| launch.config_dir.clone())
+        .and_then(|dir| fs::read_to_string(dir.join(config::INITIALIZATION_FILE)).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or(Value::Null);
+    json!({ "launch": launch, "initialization": initialization })
+}
+
+fn crash_dir(app: &AppHandle) -> std::io::Result<PathBuf> {
+    Ok(logs::log_dir(app)?.join(CRASH_DIR))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// List the crash reports on disk, newest first.
+#[tauri::command]
+pub fn list_crash_reports(app: AppHandle) -> Result<Vec<CrashReportInfo>, String> {
+    let dir = crash_dir(&app).map_err(|err| err.to_string())?;
+    let reports = crash::list(&dir).map_err(|err| err.to_string())?;
+    Ok(reports.into_iter().map(CrashReportInfo::new).collect())
+}
+
+/// Send the report `name` to the developers.
+///
+/// Builds with a report endpoint upload it. Otherwise a new issue is opened in
+/// the browser and the report is revealed in the file manager to attach.
+#[tauri::command]
+pub async fn submit_crash_report(app: AppHandle, name: String) -> Result<(), String> {
+    let dir = crash_dir(&app).map_err(|err| err.to_string())?;
+    let path = crash::list(&dir)
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .find(|path| file_name(path) == name)
+        .ok_or_else(|| format!("{name} is not a crash report"))?;
+
+    let Some(url) = REPORT_URL else {
+        let issue = Url::parse_with_params(NEW_ISSUE_URL, [("title", format!("Crash: {name}"))])
+            .map_err(|err| err.to_string())?;
+        app.opener()
+            .reveal_item_in_dir(&path)
+            .map_err(|err| err.to_string())?;
+        return app
+            .opener()
+            .open_url(issue.as_str(), None::<&str>)
+            .map_err(|err| err.to_string());
+    };
+
+    let body = fs::read(&path).map_err(|err| err.to_string())?;
+    reqwest::Client::new()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/zip")
+        .body(body)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|err| err.to_string())?;
+    Ok(())
+}
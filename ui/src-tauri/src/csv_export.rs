@@ -0,0 +1,42 @@
+//! Exporting the generated characters as CSV tables.
+//!
+//! `export_csv` writes the characters and events of the last export,
+//! whichever engine generated them, as `characters.csv` and `events.csv`,
+//! for analysis in a spreadsheet or a data frame.
+
+use std::fs;
+use std::path::PathBuf;
+
+use ck3_sim::HISTORY_FILE;
+use tauri::AppHandle;
+
+use crate::output;
+
+const CHARACTERS_FILE: &str = "characters.csv";
+const EVENTS_FILE: &str = "events.csv";
+
+/// Write the last export's characters and events to `dir`, replacing the
+/// tables if they exist.
+///
+/// Resolves to the files written.
+#[tauri::command]
+pub async fn export_csv(app: AppHandle, dir: PathBuf) -> Result<Vec<PathBuf>, String> {
+    let character_dir = output::current_paths(&app).await?.character_dir;
+    if !character_dir.join(HISTORY_FILE).is_file() {
+        return Err("nothing has been generated yet".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let (characters, events) =
+            ck3_sim::output_csv(&character_dir).map_err(|err| err.to_string())?;
+        fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+        let mut written = Vec::new();
+        for (file, table) in [(CHARACTERS_FILE, characters), (EVENTS_FILE, events)] {
+            let path = dir.join(file);
+            fs::write(&path, table).map_err(|err| err.to_string())?;
+            written.push(path);
+        }
+        Ok(written)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,218 @@
+//! The engine run inside the shell, in builds with the `embedded-engine`
+//! feature.
+//!
+//! Such a build spawns no `api_server` sidecar: it starts a Python
+//! interpreter in its own process with PyO3 and imports the engine from its
+//! sources, through the entry points of `ck3gen/embedded.py`. Pausing and
+//! cancelling its runs are then function calls rather than requests, the
+//! engine's state never crosses a process boundary, and a call that fails
+//! hands back its Python exception, traceback and all. The
+//! notifications the sidecar writes to its stdout are written to a stream
+//! object of the shell's instead, and passed on just as [`crate::sidecar`]
+//! passes them on. Builds without the feature keep the sidecar, for the
+//! platforms where embedding Python is awkward.
+//!
+//! The engine's sources are imported from the folder in
+//! `CK3GEN_PYTHON_PATH` if it is set, and otherwise from the repository in
+//! development and the app's resources once packaged, where
+//! `tauri_conf.embedded.json` bundles them. The engine reads its launch
+//! options once, when it is imported, so changing them takes effect the
+//! next time the app starts. The app's own generations are native, like
+//! those of any build.
+
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use sidecar_manager::{EngineStatus, Message, StderrEntry, StderrKind, Stream};
+use tauri::{AppHandle, Manager};
+
+use crate::generation::{self, SimulationProgress};
+use crate::health;
+use crate::launch::EngineLaunch;
+use crate::logs::EngineLog;
+use crate::output;
+use crate::stderr;
+
+/// Environment variable naming the folder the engine is imported from.
+const PYTHON_PATH_ENV: &str = "CK3GEN_PYTHON_PATH";
+/// Module of the engine's entry points.
+const ENTRY_MODULE: &str = "ck3gen.embedded";
+/// Notification the embedded engine sends on every change of the state its
+/// server would push to `/events`.
+const STATE_METHOD: &str = "state";
+
+/// Managed state holding the imported engine.
+#[derive(Default)]
+pub struct EmbeddedEngine {
+    /// The engine's entry points, once imported.
+    module: OnceLock<Py<PyModule>>,
+    /// Number of the last finished run accounted for.
+    seen: Mutex<Option<u64>>,
+}
+
+/// The stream the engine writes its notifications to.
+#[pyclass]
+struct Notifications {
+    app: AppHandle,
+    /// What has been written since the last complete line.
+    pending: Mutex<String>,
+}
+
+#[pymethods]
+impl Notifications {
+    fn write(&self, text: &str) -> usize {
+        let mut pending = self.pending.lock().unwrap();
+        pending.push_str(text);
+        while let Some(end) = pending.find('\n') {
+            let line: String = pending.drain(..=end).collect();
+            receive(&self.app, line.trim_end());
+        }
+        text.len()
+    }
+
+    fn flush(&self) {}
+}
+
+/// Import the engine in the background, with the saved launch options.
+pub fn start(app: &AppHandle) {
+    app.manage(EmbeddedEngine::default());
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || match import(&app) {
+        Ok(module) => {
+            let _ = app.state::<EmbeddedEngine>().module.set(module);
+            health::set_status(&app, EngineStatus::Ready);
+        }
+        Err(err) => {
+            let message = format!("ERROR: the embedded engine could not be started: {err}");
+            app.state::<EngineLog>()
+                .write(&app, Stream::Stderr, &message);
+            stderr::record(
+                &app,
+                StderrEntry {
+                    kind: StderrKind::Traceback,
+                    message,
+                },
+            );
+            health::set_status(&app, EngineStatus::Dead);
+        }
+    });
+}
+
+fn import(app: &AppHandle) -> Result<Py<PyModule>, String> {
+    let launch = app.state::<EngineLaunch>();
+    let options = launch.options();
+    let engine = output::engine_options(app, &options)?;
+    let sources = match std::env::var_os(PYTHON_PATH_ENV) {
+        Some(dir) => PathBuf::from(dir),
+        None => output::resource_dir(app)?,
+    };
+    let module = Python::with_gil(|py| -> PyResult<Py<PyModule>> {
+        // ck3gen reads its paths and seed from the environment as it is
+        // imported, as the sidecar has them on its command line.
+        let environ = py.import("os")?.getattr("environ")?;
+        let variables = [
+            (
+                "CK3GEN_CONFIG_DIR",
+                engine.config_dir.map(|dir| dir.display().to_string()),
+            ),
+            ("CK3GEN_SEED", engine.seed.map(|seed| seed.to_string())),
+            (
+                "CK3GEN_OUTPUT_DIR",
+                engine.output_dir.map(|dir| dir.display().to_string()),
+            ),
+        ];
+        for (name, value) in variables {
+            if let Some(value) = value {
+                environ.set_item(name, value)?;
+            }
+        }
+        py.import("sys")?
+            .getattr("path")?
+            .downcast::<PyList>()?
+            .insert(0, sources.display().to_string())?;
+        let module = py.import(ENTRY_MODULE)?;
+        let stream = Notifications {
+            app: app.clone(),
+            pending: Mutex::new(String::new()),
+        };
+        module.call_method1("start", (stream,))?;
+        Ok(module.unbind())
+    })
+    .map_err(describe)?;
+    launch.set_running(options);
+    Ok(module)
+}
+
+/// Pass on the stdout line `line` of the engine.
+fn receive(app: &AppHandle, line: &str) {
+    let log = app.state::<EngineLog>();
+    match Message::parse(line) {
+        Some(Message::Progress(progress)) => generation::report(app, progress),
+        Some(Message::Log(record)) => {
+            let entry = StderrEntry::from(record);
+            log.write(app, Stream::Stderr, &entry.message);
+            stderr::record(app, entry);
+        }
+        Some(Message::Hello(hello)) => {
+            tracing::info!(target: "engine", "speaks protocol version {}", hello.protocol);
+            if let Some(mismatch) = hello.mismatch() {
+                log.write(app, Stream::Stderr, &format!("ERROR: {mismatch}"));
+                stderr::record(
+                    app,
+                    StderrEntry {
+                        kind: StderrKind::Error,
+                        message: mismatch,
+                    },
+                );
+            }
+        }
+        Some(Message::Unknown { method, params }) if method == STATE_METHOD => {
+            let Ok(progress) = serde_json::from_value::<SimulationProgress>(params) else {
+                return;
+            };
+            let status = if progress.running {
+                EngineStatus::Busy
+            } else {
+                EngineStatus::Ready
+            };
+            health::set_status(app, status);
+            let engine = app.state::<EmbeddedEngine>();
+            generation::update(app, &mut engine.seen.lock().unwrap(), progress);
+        }
+        Some(Message::Unknown { method, .. }) => {
+            tracing::debug!(target: "engine", "ignored a {method} notification");
+        }
+        None => {
+            tracing::info!(target: "engine", "{line}");
+            log.write(app, Stream::Stdout, line);
+        }
+    }
+}
+
+/// Call the engine's `action` (`pause`, `resume` or `cancel`).
+pub async fn control(app: &AppHandle, action: &'static str) -> Result<(), String> {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let engine = app.state::<EmbeddedEngine>();
+        let module = engine
+            .module
+            .get()
+            .ok_or("the embedded engine has not started")?;
+        Python::with_gil(|py| module.call_method0(py, action).map(drop)).map_err(describe)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// The Python exception `err` with its traceback, as it would be printed.
+fn describe(err: PyErr) -> String {
+    Python::with_gil(|py| {
+        let traceback = err
+            .traceback(py)
+            .and_then(|traceback| traceback.format().ok())
+            .unwrap_or_default();
+        format!("{traceback}{err}")
+    })
+}
@@ -0,0 +1,104 @@
+//! Address of the engine's HTTP API.
+//!
+//! The packaged sidecar no longer binds a fixed port: a free loopback port is
+//! picked every time it is (re)spawned and handed to it with `--port`. Only
+//! the shell talks to the engine; the frontend asks for the address of the
+//! shell's own server (see [`crate::server`]) with `get_api_base_url`.
+
+use std::net::Ipv4Addr;
+use std::ops::ControlFlow;
+use std::sync::Mutex;
+
+use sidecar_manager::port::free_loopback_port;
+use tauri::State;
+
+use crate::server::LocalServer;
+
+/// Port the development server started by run_ui.bat listens on.
+const DEV_PORT: u16 = 8000;
+/// Timeout for short requests to the engine made by the shell itself.
+#[cfg(not(feature = "embedded-engine"))]
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Managed state holding the port the engine is currently bound to.
+pub struct ApiEndpoint {
+    port: Mutex<u16>,
+}
+
+impl Default for ApiEndpoint {
+    fn default() -> Self {
+        Self {
+            port: Mutex::new(DEV_PORT),
+        }
+    }
+}
+
+impl ApiEndpoint {
+    pub fn port(&self) -> u16 {
+        *self.port.lock().unwrap()
+    }
+
+    /// Base URL of the engine API, without a trailing slash.
+    pub fn base_url(&self) -> String {
+        format!("http://{}:{}", Ipv4Addr::LOCALHOST, self.port())
+    }
+
+    /// Point the frontend at an engine already bound to `port`.
+    pub fn set_port(&self, port: u16) {
+        *self.port.lock().unwrap() = port;
+    }
+
+    /// Pick a fresh free port for the next sidecar run and return it.
+    pub fn allocate(&self) -> std::io::Result<u16> {
+        let port = free_loopback_port()?;
+        *self.port.lock().unwrap() = port;
+        Ok(port)
+    }
+}
+
+/// Send a body-less POST request to the engine, failing on an error status.
+#[cfg(not(feature = "embedded-engine"))]
+pub async fn post(client: &reqwest::Client, url: &str) -> Result<(), String> {
+    client
+        .post(url)
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map(drop)
+        .map_err(|err| err.to_string())
+}
+
+/// Read the server-sent events of `response`, handing the data of each to
+/// `on_data` until it breaks, with what it breaks with, or the stream ends,
+/// with none.
+pub async fn read_events<T>(
+    mut response: reqwest::Response,
+    mut on_data: impl FnMut(&str) -> ControlFlow<T>,
+) -> Result<Option<T>, String> {
+    let mut buffer = String::new();
+    while let Some(chunk) = response.chunk().await.map_err(|err| err.to_string())? {
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+        while let Some(end) = buffer.find('\n') {
+            let line: String = buffer.drain(..=end).collect();
+            // Comments and blank lines between events carry no data.
+            let Some(data) = line.trim_end().strip_prefix("data: ") else {
+                continue;
+            };
+            if let ControlFlow::Break(value) = on_data(data) {
+                return Ok(Some(value));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Return the base URL the frontend should send API requests to: the
+/// shell's server, or the engine's if that could not be started.
+#[tauri::command]
+pub fn get_api_base_url(
+    endpoint: State<'_, ApiEndpoint>,
+    server: State<'_, LocalServer>,
+) -> String {
+    server.base_url().unwrap_or_else(|| endpoint.base_url())
+}
@@ -0,0 +1,242 @@
+//! Finding the game installation and the Paradox mod folder.
+//!
+//! Steam keeps the libraries it installs games into in
+//! `steamapps/libraryfolders.vdf` under its own folder, which is looked for
+//! where Steam puts itself on each platform, and each library is checked for
+//! the game. The folders the Xbox app and GOG install to by default are
+//! checked too. The mod folder is `Paradox Interactive/Crusader Kings
+//! III/mod` under the documents folder, or under `~/.local/share` on Linux.
+//! `detect_game_paths` gives what it finds, with the version of each
+//! installation from its launcher settings, and the game and mod pickers
+//! start there.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ck3_config::GameVersion;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+/// Folder of the game in a Steam library's `steamapps/common`, and its
+/// default name elsewhere.
+const GAME_FOLDER: &str = "Crusader Kings III";
+/// The launcher's settings, with the game's version, relative to an
+/// installation.
+const LAUNCHER_SETTINGS: &str = "launcher/launcher-settings.json";
+/// Steam's list of libraries, relative to its folder.
+const LIBRARY_FOLDERS: &str = "steamapps/libraryfolders.vdf";
+
+/// Where a game installation was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Store {
+    Steam,
+    Xbox,
+    Gog,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Installation {
+    pub path: PathBuf,
+    pub store: Store,
+    /// The game's version, if its launcher settings give it.
+    pub version: Option<GameVersion>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GamePaths {
+    /// Installations found, Steam's first, without duplicates.
+    pub installations: Vec<Installation>,
+    /// The mod folder, if it exists.
+    pub mod_dir: Option<PathBuf>,
+}
+
+/// Look for the game installations and the mod folder on this machine.
+#[tauri::command]
+pub async fn detect_game_paths(app: AppHandle) -> Result<GamePaths, String> {
+    let home = app.path().home_dir().ok();
+    let documents = app.path().document_dir().ok();
+    tauri::async_runtime::spawn_blocking(move || GamePaths {
+        installations: installations(home.as_deref()),
+        mod_dir: mod_dir(home.as_deref(), documents.as_deref()),
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// The first game installation found, for a picker to start in.
+pub fn first_installation(app: &AppHandle) -> Option<PathBuf> {
+    let home = app.path().home_dir().ok();
+    installations(home.as_deref())
+        .into_iter()
+        .next()
+        .map(|installation| installation.path)
+}
+
+/// The mod folder, if it exists, for a picker to start in.
+pub fn detected_mod_dir(app: &AppHandle) -> Option<PathBuf> {
+    let home = app.path().home_dir().ok();
+    let documents = app.path().document_dir().ok();
+    mod_dir(home.as_deref(), documents.as_deref())
+}
+
+/// The game installations under the Steam libraries and the default Xbox
+/// and GOG folders, given the home folder `home`.
+fn installations(home: Option<&Path>) -> Vec<Installation> {
+    let mut found: Vec<Installation> = Vec::new();
+    let mut add = |path: PathBuf, store| {
+        // An installation has the game's own `game` folder.
+        if path.join("game").is_dir() && !found.iter().any(|other| same_dir(&other.path, &path)) {
+            let version = installed_version(&path);
+            found.push(Installation {
+                path,
+                store,
+                version,
+            });
+        }
+    };
+    for steam in steam_dirs(home) {
+        let mut libraries = vec![steam.clone()];
+        if let Ok(vdf) = fs::read_to_string(steam.join(LIBRARY_FOLDERS)) {
+            libraries.extend(library_paths(&vdf).into_iter().map(PathBuf::from));
+        }
+        for library in libraries {
+            add(
+                library.join("steamapps").join("common").join(GAME_FOLDER),
+                Store::Steam,
+            );
+        }
+    }
+    if cfg!(windows) {
+        for drive in drives() {
+            add(
+                drive.join("XboxGames").join(GAME_FOLDER).join("Content"),
+                Store::Xbox,
+            );
+        }
+        for program_files in program_files() {
+            let windows_apps = program_files
+                .join("ModifiableWindowsApps")
+                .join(GAME_FOLDER);
+            add(windows_apps, Store::Xbox);
+            let galaxy = program_files
+                .join("GOG Galaxy")
+                .join("Games")
+                .join(GAME_FOLDER);
+            add(galaxy, Store::Gog);
+        }
+        for drive in drives() {
+            add(drive.join("GOG Games").join(GAME_FOLDER), Store::Gog);
+        }
+    }
+    found
+}
+
+/// The version of the game installed in `dir`, by the `rawVersion` of its
+/// launcher settings, such as `1.12.5`.
+pub fn installed_version(dir: &Path) -> Option<GameVersion> {
+    let text = fs::read_to_string(dir.join(LAUNCHER_SETTINGS)).ok()?;
+    let settings: serde_json::Value = serde_json::from_str(&text).ok()?;
+    settings.get("rawVersion")?.as_str()?.parse().ok()
+}
+
+/// Where Steam may be installed, given the home folder `home`.
+fn steam_dirs(home: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if cfg!(windows) {
+        dirs.extend(program_files().into_iter().map(|dir| dir.join("Steam")));
+    } else if let Some(home) = home {
+        if cfg!(target_os = "macos") {
+            dirs.push(home.join("Library/Application Support/Steam"));
+        } else {
+            dirs.push(home.join(".steam/steam"));
+            dirs.push(home.join(".local/share/Steam"));
+            // Steam installed with Flatpak.
+            dirs.push(home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+        }
+    }
+    dirs.retain(|dir| dir.is_dir());
+    dirs
+}
+
+/// The `Program Files` folders of Windows, 32-bit first.
+fn program_files() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = ["ProgramFiles(x86)", "ProgramFiles"]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+        .collect();
+    if dirs.is_empty() {
+        dirs.push(PathBuf::from(r"C:\Program Files (x86)"));
+        dirs.push(PathBuf::from(r"C:\Program Files"));
+    }
+    dirs
+}
+
+/// The roots of the Windows drives that exist.
+fn drives() -> Vec<PathBuf> {
+    (b'C'..=b'Z')
+        .map(|letter| PathBuf::from(format!(r"{}:\", letter as char)))
+        .filter(|drive| drive.is_dir())
+        .collect()
+}
+
+/// The library paths of the Steam library list `vdf`: the values of its
+/// `"path"` keys.
+fn library_paths(vdf: &str) -> Vec<String> {
+    vdf_strings(vdf)
+        .windows(2)
+        .filter(|pair| pair[0].eq_ignore_ascii_case("path"))
+        .map(|pair| pair[1].clone())
+        .collect()
+}
+
+/// The quoted strings of the Valve KeyValues text `vdf`, keys and values
+/// alike, unescaped.
+pub fn vdf_strings(vdf: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut chars = vdf.chars();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+        let mut string = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => string.extend(chars.next()),
+                c => string.push(c),
+            }
+        }
+        strings.push(string);
+    }
+    strings
+}
+
+/// The mod folder, given the home and documents folders, if it exists.
+fn mod_dir(home: Option<&Path>, documents: Option<&Path>) -> Option<PathBuf> {
+    let paradox = |dir: &Path| {
+        dir.join("Paradox Interactive")
+            .join(GAME_FOLDER)
+            .join("mod")
+    };
+    let mut candidates: Vec<PathBuf> = documents.map(paradox).into_iter().collect();
+    if let Some(home) = home {
+        if cfg!(target_os = "linux") {
+            candidates.push(paradox(&home.join(".local/share")));
+        }
+        candidates.push(paradox(&home.join("Documents")));
+    }
+    candidates.into_iter().find(|dir| dir.is_dir())
+}
+
+/// Whether `a` and `b` are the same folder, as a symlinked Steam folder
+/// lists the same library twice.
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
@@ -0,0 +1,29 @@
+//! Exporting the family trees for genealogy programs.
+//!
+//! `export_gedcom` writes the characters of the last export, whichever
+//! engine generated them, to a GEDCOM file that programs such as Gramps can
+//! open, browse and print trees from.
+
+use std::fs;
+use std::path::PathBuf;
+
+use ck3_sim::HISTORY_FILE;
+use tauri::AppHandle;
+
+use crate::output;
+
+/// Write the last export's characters and families to the GEDCOM file
+/// `path`, replacing it if it exists.
+#[tauri::command]
+pub async fn export_gedcom(app: AppHandle, path: PathBuf) -> Result<(), String> {
+    let dir = output::current_paths(&app).await?.character_dir;
+    if !dir.join(HISTORY_FILE).is_file() {
+        return Err("nothing has been generated yet".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let gedcom = ck3_sim::output_gedcom(&dir).map_err(|err| err.to_string())?;
+        fs::write(&path, gedcom).map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
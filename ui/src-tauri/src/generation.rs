@@ -0,0 +1,422 @@
+//! Generations, run natively by the app or by the primary engine.
+//!
+//! `run_simulation` generates the project on the native engine (see
+//! `native.rs`) and passes on its log lines and how it ended as
+//! `simulation-message` events; the browser build of the frontend asks the
+//! Python engine instead. The shell also keeps the engine's `/events` stream
+//! open, which pushes the state of the engine's simulation on every change,
+//! so a run started there is followed without polling, and the stream is
+//! opened again whenever it closes, as it does when the engine restarts; an
+//! embedded engine (see `embedded.rs`) has its state pushed to it rather
+//! than streamed. Changes are shown in the tray and the menu bar, and a
+//! finished run raises a completion notification, is recorded in the
+//! project database and has its output linted. The commands here pause,
+//! resume or cancel the running generation; a cancelled run stops after the
+//! year or output file in progress, and is reported with a
+//! `generation-cancelled` event.
+//!
+//! The progress of a native run, and the progress notifications the engine
+//! sends over its stdout, with the phase, the year and the time left, are
+//! passed on to the frontend as `generation-progress` events, which drive
+//! its progress bar until a `generation-ended` event.
+
+use std::ops::ControlFlow;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sidecar_manager::Progress;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::bookmarks;
+use crate::endpoint::{self, ApiEndpoint};
+use crate::launch::EngineLaunch;
+use crate::menu;
+use crate::native::{self, Control, CANCELLED};
+use crate::notify::{self, Outcome};
+use crate::output;
+use crate::problems;
+use crate::project_db;
+use crate::tray;
+use crate::tree_window;
+
+/// Emitted with a [`Progress`] for every step of a native run and every
+/// progress line the engine writes.
+pub const GENERATION_PROGRESS_EVENT: &str = "generation-progress";
+/// Emitted once the run that reported progress has ended.
+pub const GENERATION_ENDED_EVENT: &str = "generation-ended";
+/// Emitted with a [`GenerationCancelled`] once a cancelled run has stopped.
+pub const GENERATION_CANCELLED_EVENT: &str = "generation-cancelled";
+/// Emitted with every log line of a native run and how it ended.
+pub const SIMULATION_MESSAGE_EVENT: &str = "simulation-message";
+
+/// Time to wait before opening the event stream again once it has closed.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// An event of the engine's `/events` stream.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum EngineEvent {
+    State(SimulationProgress),
+}
+
+/// Body returned by the engine's `/simulation/progress` endpoint, and pushed
+/// to its `/events` stream.
+#[derive(Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationProgress {
+    pub running: bool,
+    pub paused: bool,
+    pub percent: Option<f64>,
+    last_run: Option<LastRun>,
+}
+
+/// How the engine's last finished run ended.
+#[derive(Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LastRun {
+    /// Number of the run, counting from 1 since the engine started.
+    run: u64,
+    /// `complete`, `failed` or `cancelled`.
+    status: String,
+    characters: Option<u64>,
+    elapsed_seconds: f64,
+    error: Option<String>,
+    /// Number of partial output files a cancelled run deleted.
+    removed_files: Option<u64>,
+}
+
+/// How a cancelled run was cleaned up.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationCancelled {
+    /// Number of partial output files deleted, if known.
+    pub removed_files: Option<u64>,
+}
+
+/// Managed state holding the last progress of the running generation.
+#[derive(Default)]
+pub struct Generation {
+    progress: Mutex<SimulationProgress>,
+    /// The last progress reported.
+    reported: Mutex<Option<Progress>>,
+    /// Controls the native run in progress.
+    native: Mutex<Option<Arc<Control>>>,
+}
+
+impl Generation {
+    pub fn progress(&self) -> SimulationProgress {
+        self.progress.lock().unwrap().clone()
+    }
+}
+
+/// Start following the engine's progress in the background.
+///
+/// An embedded engine pushes its state to the shell instead.
+pub fn start(app: &AppHandle) {
+    if cfg!(feature = "embedded-engine") {
+        return;
+    }
+    tauri::async_runtime::spawn(follow(app.clone()));
+}
+
+async fn follow(app: AppHandle) {
+    let client = reqwest::Client::new();
+    loop {
+        let url = format!("{}/events", app.state::<ApiEndpoint>().base_url());
+        if let Err(err) = follow_events(&app, &client, &url).await {
+            tracing::debug!("event stream of {url} failed: {err}");
+        }
+        // An unreachable or restarting engine is not generating anything.
+        update(&app, &mut None, SimulationProgress::default());
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Follow the event stream at `url` until it closes.
+async fn follow_events(app: &AppHandle, client: &reqwest::Client, url: &str) -> Result<(), String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|err| err.to_string())?;
+    // Number of the last finished run already accounted for. Unknown until
+    // the first state arrives, so runs that ended before are not announced;
+    // the engine's run numbers start over once it restarts.
+    let mut seen: Option<u64> = None;
+    endpoint::read_events(response, |data| {
+        if let Ok(EngineEvent::State(progress)) = serde_json::from_str(data) {
+            update(app, &mut seen, progress);
+        }
+        ControlFlow::<()>::Continue(())
+    })
+    .await
+    .map(drop)
+}
+
+/// Account for the state `progress` pushed by the engine.
+pub fn update(app: &AppHandle, seen: &mut Option<u64>, progress: SimulationProgress) {
+    let last_run = progress.last_run.as_ref().map_or(0, |last| last.run);
+    if let (Some(seen), Some(last)) = (*seen, &progress.last_run) {
+        if last.run != seen {
+            finished(app, last);
+        }
+    }
+    *seen = Some(last_run);
+    // While a native run is in progress, it is the one shown.
+    if app.state::<Generation>().native.lock().unwrap().is_some() {
+        return;
+    }
+    if !progress.running {
+        ended(app);
+    }
+    show(app, progress);
+}
+
+/// Tell the frontend that the run that reported progress has ended.
+fn ended(app: &AppHandle) {
+    let reported = app.state::<Generation>().reported.lock().unwrap().take();
+    if reported.is_some() {
+        let _ = app.emit(GENERATION_ENDED_EVENT, ());
+    }
+}
+
+/// Record `progress`, updating the tray and menu if it changed.
+fn show(app: &AppHandle, progress: SimulationProgress) {
+    let generation = app.state::<Generation>();
+    let mut shown = generation.progress.lock().unwrap();
+    if *shown == progress {
+        return;
+    }
+    *shown = progress.clone();
+    // The tray is updated on the main thread, which reads the progress.
+    drop(shown);
+    tray::show_progress(app, &progress);
+    menu::show_progress(app, &progress);
+}
+
+fn finished(app: &AppHandle, last: &LastRun) {
+    let elapsed = Duration::from_secs_f64(last.elapsed_seconds.max(0.0));
+    let outcome = match last.status.as_str() {
+        "complete" => {
+            tree_window::trees_updated(app);
+            project_db::record_finished(app);
+            bookmarks::write_finished(app);
+            problems::check_finished(app);
+            Outcome::Complete {
+                characters: last.characters,
+            }
+        }
+        "failed" => Outcome::Failed {
+            error: last.error.clone().unwrap_or_default(),
+        },
+        // The user asked for it; no notification, only the window is told.
+        _ => {
+            cancelled(app, last.removed_files);
+            return;
+        }
+    };
+    notify::generation_finished(app, &outcome, elapsed);
+}
+
+/// Pass on the progress `progress` of the running generation.
+pub fn report(app: &AppHandle, progress: Progress) {
+    *app.state::<Generation>().reported.lock().unwrap() = Some(progress.clone());
+    let _ = app.emit(GENERATION_PROGRESS_EVENT, progress);
+}
+
+/// The last progress of the running generation, for a window opened in the
+/// middle of a run.
+#[tauri::command]
+pub fn get_generation_progress(generation: State<'_, Generation>) -> Option<Progress> {
+    generation.reported.lock().unwrap().clone()
+}
+
+/// Generate the project natively and pass on its log lines and how it
+/// ended as `simulation-message` events. Resolves once the run has ended.
+#[tauri::command]
+pub async fn run_simulation(app: AppHandle) -> Result<(), String> {
+    run_native(&app, None).await
+}
+
+/// Generate the project natively, in parallel on `threads` threads if set,
+/// passing on its progress and messages. Nothing is written by a run that
+/// fails or is cancelled; only a failure is an error.
+pub async fn run_native(app: &AppHandle, threads: Option<usize>) -> Result<(), String> {
+    let paths = output::current_paths(app).await?;
+    let seed = app.state::<EngineLaunch>().options().seed;
+    let control = Arc::new(Control::default());
+    {
+        let generation = app.state::<Generation>();
+        let mut native = generation.native.lock().unwrap();
+        if native.is_some() {
+            return Err("a generation is already running".into());
+        }
+        *native = Some(control.clone());
+    }
+    show(
+        app,
+        SimulationProgress {
+            running: true,
+            ..SimulationProgress::default()
+        },
+    );
+    let started = Instant::now();
+    let result = tauri::async_runtime::spawn_blocking({
+        let app = app.clone();
+        move || {
+            let generated = native::generate(&paths, None, seed, threads, &control, |progress| {
+                show(
+                    &app,
+                    SimulationProgress {
+                        running: true,
+                        paused: control.is_paused(),
+                        percent: Some(progress.percent),
+                        last_run: None,
+                    },
+                );
+                report(&app, progress);
+            })?;
+            project_db::record(&app, &paths.config_dir, &paths.character_dir, "native");
+            Ok(generated)
+        }
+    })
+    .await
+    .map_err(|err| err.to_string())
+    .and_then(|result| result);
+    app.state::<Generation>().native.lock().unwrap().take();
+    ended(app);
+    show(app, SimulationProgress::default());
+
+    let generated = match result {
+        Ok(generated) => generated,
+        Err(err) if err == CANCELLED => {
+            // The user asked for it; no notification, only the window is told.
+            cancelled(app, Some(0));
+            message(app, json!({ "status": "cancelled", "removedFiles": 0 }));
+            return Ok(());
+        }
+        Err(error) => {
+            notify::generation_finished(
+                app,
+                &Outcome::Failed {
+                    error: error.clone(),
+                },
+                started.elapsed(),
+            );
+            return Err(error);
+        }
+    };
+    let summary = &generated.summary;
+    for error in &summary.plugin_errors {
+        message(app, json!({ "log": format!("ERROR: {error}") }));
+    }
+    for warning in &generated.warnings {
+        message(app, json!({ "log": format!("WARNING: {warning}") }));
+    }
+    message(
+        app,
+        json!({ "log": format!(
+            "Generated {} characters from {} to {}, {} of them living at the end",
+            summary.characters, summary.first_year, summary.last_year, summary.living_at_end
+        ) }),
+    );
+    let characters = summary.characters as u64;
+    native::LastRun::set(app, generated.simulation);
+    tree_window::trees_updated(app);
+    problems::check_finished(app);
+    message(app, json!({ "status": "complete" }));
+    notify::generation_finished(
+        app,
+        &Outcome::Complete {
+            characters: Some(characters),
+        },
+        started.elapsed(),
+    );
+    Ok(())
+}
+
+/// Pass on `message` of a native run as a `simulation-message` event.
+fn message(app: &AppHandle, message: serde_json::Value) {
+    let _ = app.emit(SIMULATION_MESSAGE_EVENT, message);
+}
+
+/// Hold, release or stop the native run in progress with `action`
+/// (`pause`, `resume` or `cancel`), returning whether there is one.
+fn control_native(app: &AppHandle, action: &str) -> bool {
+    let generation = app.state::<Generation>();
+    let Some(control) = generation.native.lock().unwrap().clone() else {
+        return false;
+    };
+    match action {
+        "pause" => control.pause(),
+        "resume" => control.resume(),
+        _ => control.cancel(),
+    }
+    let progress = SimulationProgress {
+        paused: control.is_paused(),
+        ..generation.progress()
+    };
+    show(app, progress);
+    true
+}
+
+/// Send `action` (`pause`, `resume` or `cancel`) to the native run in
+/// progress, or else to the engine.
+#[cfg(feature = "embedded-engine")]
+pub async fn control(app: &AppHandle, action: &'static str) -> Result<(), String> {
+    if control_native(app, action) {
+        return Ok(());
+    }
+    crate::embedded::control(app, action).await
+}
+
+/// Send `action` (`pause`, `resume` or `cancel`) to the native run in
+/// progress, or else to the engine.
+#[cfg(not(feature = "embedded-engine"))]
+pub async fn control(app: &AppHandle, action: &'static str) -> Result<(), String> {
+    if control_native(app, action) {
+        return Ok(());
+    }
+    let url = format!(
+        "{}/simulation/{action}",
+        app.state::<ApiEndpoint>().base_url()
+    );
+    endpoint::post(&reqwest::Client::new(), &url).await
+}
+
+/// Pause the running generation before the next simulated year.
+#[tauri::command]
+pub async fn pause_generation(app: AppHandle) -> Result<(), String> {
+    control(&app, "pause").await
+}
+
+/// Resume a paused generation.
+#[tauri::command]
+pub async fn resume_generation(app: AppHandle) -> Result<(), String> {
+    control(&app, "resume").await
+}
+
+/// Tell the window that a run was cancelled, deleting `removed_files` of
+/// its output.
+pub fn cancelled(app: &AppHandle, removed_files: Option<u64>) {
+    let _ = app.emit(
+        GENERATION_CANCELLED_EVENT,
+        GenerationCancelled { removed_files },
+    );
+}
+
+/// Cancel the running generation.
+pub async fn cancel(app: &AppHandle) -> Result<(), String> {
+    control(app, "cancel").await
+}
+
+/// Cancel the running generation at the next simulated year or output file;
+/// a native run has written nothing by then, and the engine deletes what it
+/// wrote, so nothing is exported.
+#[tauri::command]
+pub async fn cancel_generation(app: AppHandle) -> Result<(), String> {
+    cancel(&app).await
+}
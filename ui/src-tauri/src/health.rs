@@ -0,0 +1,107 @@
+//! Heartbeat between the shell and the engine.
+//!
+//! The FastAPI server is polled on its `/health` endpoint every few seconds
+//! and the result is folded into a single [`EngineStatus`] that the frontend
+//! can query with `get_engine_status` or follow through status-change events.
+//! This is what lets the UI tell a long, silent simulation apart from a hung
+//! or dead process.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Deserialize;
+use sidecar_manager::{EngineStatus, HeartbeatMonitor};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::endpoint::ApiEndpoint;
+
+/// Emitted with the new [`EngineStatus`] whenever it changes.
+pub const ENGINE_STATUS_EVENT: &str = "engine-status-changed";
+
+/// Time between two health checks.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(2);
+/// How long a single health check may take before it counts as missed.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Managed state holding the last known engine status.
+pub struct EngineHealth {
+    status: Mutex<EngineStatus>,
+}
+
+impl Default for EngineHealth {
+    fn default() -> Self {
+        Self {
+            status: Mutex::new(EngineStatus::Starting),
+        }
+    }
+}
+
+impl EngineHealth {
+    pub fn status(&self) -> EngineStatus {
+        *self.status.lock().unwrap()
+    }
+}
+
+/// Body returned by the engine's `/health` endpoint.
+#[derive(Deserialize)]
+struct HealthResponse {
+    status: String,
+}
+
+/// Record a new engine status, notifying the frontend if it changed.
+pub fn set_status(app: &AppHandle, status: EngineStatus) {
+    let health = app.state::<EngineHealth>();
+    let previous = std::mem::replace(&mut *health.status.lock().unwrap(), status);
+    if previous != status {
+        let _ = app.emit(ENGINE_STATUS_EVENT, status);
+    }
+}
+
+/// Start the heartbeat in the background.
+///
+/// Unlike the sidecar supervisor this also runs in development, where it
+/// watches the uvicorn server started by run_ui.bat. An embedded engine has
+/// no server to poll, and reports its status as it changes instead.
+pub fn start(app: &AppHandle) {
+    if cfg!(feature = "embedded-engine") {
+        return;
+    }
+    tauri::async_runtime::spawn(heartbeat(app.clone()));
+}
+
+async fn heartbeat(app: AppHandle) {
+    let client = match reqwest::Client::builder()
+        .timeout(HEARTBEAT_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(err) => {
+            tracing::warn!("health check disabled: {err}");
+            return;
+        }
+    };
+
+    let mut monitor = HeartbeatMonitor::default();
+    loop {
+        tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+
+        let current = app.state::<EngineHealth>().status();
+        let url = format!("{}/health", app.state::<ApiEndpoint>().base_url());
+        if let Some(status) = monitor.observe(current, check(&client, &url).await) {
+            set_status(&app, status);
+        }
+    }
+}
+
+/// Run a single health check, returning `None` if the engine did not answer.
+pub async fn check(client: &reqwest::Client, url: &str) -> Option<EngineStatus> {
+    let response = client.get(url).send().await.ok()?;
+    let body: HealthResponse = response.error_for_status().ok()?.json().await.ok()?;
+    Some(EngineStatus::from_health(&body.status))
+}
+
+/// Return the last known engine status.
+#[tauri::command]
+pub fn get_engine_status(health: State<'_, EngineHealth>) -> EngineStatus {
+    health.status()
+}
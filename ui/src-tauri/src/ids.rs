@@ -0,0 +1,103 @@
+//! Finding and renumbering colliding IDs.
+//!
+//! `find_id_collisions` lists the characters, dynasties and houses the last
+//! export defines twice, or that other history and dynasty files the user
+//! picks define too, such as the mod's own or an imported history's.
+//! `renumber_ids` gives the export's colliding IDs free ones from the range
+//! the project configures and rewrites every reference to them.
+//! `scan_character_ids` reads the character histories of the game and the
+//! mods enabled with it beforehand, so that the config's `initialCharID` can
+//! be set to number the next run's characters clear of theirs.
+
+use std::path::PathBuf;
+
+use ck3_sim::{Collision, IdRange, IdScan, Renumbered, SimConfig, HISTORY_FILE};
+use tauri::AppHandle;
+
+use crate::{game_paths, output, play, scaffold};
+
+/// Where character histories are, in the game and in a mod.
+const CHARACTERS_DIR: &str = "history/characters";
+
+/// The last export's IDs that are defined twice or in the files `others`,
+/// each of which may be a folder of them.
+#[tauri::command]
+pub async fn find_id_collisions(
+    app: AppHandle,
+    others: Vec<PathBuf>,
+) -> Result<Vec<Collision>, String> {
+    let dir = output::current_paths(&app).await?.character_dir;
+    if !dir.join(HISTORY_FILE).is_file() {
+        return Err("nothing has been generated yet".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || ck3_sim::id_collisions(&dir, &others))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Renumber the last export's colliding IDs into `range`, rewriting its
+/// files. Resolves to the IDs changed.
+#[tauri::command]
+pub async fn renumber_ids(
+    app: AppHandle,
+    others: Vec<PathBuf>,
+    range: IdRange,
+) -> Result<Vec<Renumbered>, String> {
+    let dir = output::current_paths(&app).await?.character_dir;
+    if !dir.join(HISTORY_FILE).is_file() {
+        return Err("nothing has been generated yet".into());
+    }
+    if range.first > range.last {
+        return Err(format!(
+            "the ID range {} to {} is empty",
+            range.first, range.last
+        ));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        ck3_sim::renumber_ids(&dir, &others, range).map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// The character IDs of the game installed in `game_dir`, if given, of the
+/// mods enabled beside the mod folder `mod_dir`, or the one found, and of
+/// the files `others`, and the `initialCharID` numbering the config's
+/// characters clear of them. The mod the app deploys is left out, since its
+/// IDs are the last run's own.
+#[tauri::command]
+pub async fn scan_character_ids(
+    app: AppHandle,
+    game_dir: Option<PathBuf>,
+    mod_dir: Option<PathBuf>,
+    others: Vec<PathBuf>,
+) -> Result<IdScan, String> {
+    let paths = output::current_paths(&app).await?;
+    let mod_dir = mod_dir.or_else(|| game_paths::detected_mod_dir(&app));
+    tauri::async_runtime::spawn_blocking(move || {
+        let config = SimConfig::load(&paths.config_dir).map_err(|err| err.to_string())?;
+        let own = config
+            .initialization
+            .mod_settings
+            .as_ref()
+            .map_or(scaffold::DEFAULT_MOD_NAME, |settings| {
+                settings.name.as_str()
+            });
+        let own = scaffold::safe_folder_name(own);
+        let mut scanned: Vec<PathBuf> = game_dir
+            .map(|dir| dir.join("game").join(CHARACTERS_DIR))
+            .into_iter()
+            .collect();
+        if let Some(mod_dir) = mod_dir {
+            let mods = play::enabled_mod_dirs(&mod_dir).into_iter();
+            scanned.extend(
+                mods.filter(|dir| dir.file_name().is_none_or(|name| *name != *own))
+                    .map(|dir| dir.join(CHARACTERS_DIR)),
+            );
+        }
+        scanned.extend(others);
+        Ok(ck3_sim::scan_character_ids(&scanned, &config))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
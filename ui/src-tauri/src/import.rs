@@ -0,0 +1,321 @@
+//! Import of existing history files dropped onto the window.
+//!
+//! Users often start from hand-written histories. Dropping their
+//! `history/characters/*.txt` or `common/dynasties/*.txt` files onto the window
+//! parses them into characters and dynasties, which are sent to the frontend
+//! as a `history-import` event so it can extend them instead of starting from
+//! scratch. The same import is available as a command for files chosen by
+//! other means.
+//!
+//! Only the fields the generator itself writes are picked up; everything else
+//! in a block is skipped.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{DragDropEvent, Emitter, Manager, Window, WindowEvent};
+
+/// Emitted with a [`HistoryImport`] after files have been dropped.
+pub const HISTORY_IMPORT_EVENT: &str = "history-import";
+
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryImport {
+    pub files: Vec<ImportedFile>,
+    /// Dropped paths that are not `.txt` files.
+    pub skipped: Vec<PathBuf>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedFile {
+    pub path: PathBuf,
+    pub characters: Vec<ImportedCharacter>,
+    pub dynasties: Vec<ImportedDynasty>,
+    /// Syntax errors, with the line they were found on. Blocks before the
+    /// first error are still imported.
+    pub errors: Vec<String>,
+}
+
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedCharacter {
+    pub id: String,
+    pub name: Option<String>,
+    pub female: bool,
+    pub dynasty: Option<String>,
+    pub dynasty_house: Option<String>,
+    pub culture: Option<String>,
+    pub religion: Option<String>,
+    pub father: Option<String>,
+    pub mother: Option<String>,
+    /// Date of the `birth` entry, e.g. `"7010.3.14"`.
+    pub birth: Option<String>,
+    /// Date of the `death` entry.
+    pub death: Option<String>,
+    pub traits: Vec<String>,
+}
+
+/// A dynasty, or a house if `dynasty` names the dynasty it belongs to.
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedDynasty {
+    pub id: String,
+    pub name: Option<String>,
+    pub culture: Option<String>,
+    pub motto: Option<String>,
+    pub dynasty: Option<String>,
+}
+
+/// Handle file drops on any window.
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    let WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) = event else {
+        return;
+    };
+    let app = window.app_handle().clone();
+    let paths = paths.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let _ = app.emit(HISTORY_IMPORT_EVENT, import(&paths));
+    });
+}
+
+/// Parse the history files at `paths`.
+#[tauri::command]
+pub async fn import_history_files(paths: Vec<PathBuf>) -> Result<HistoryImport, String> {
+    tauri::async_runtime::spawn_blocking(move || import(&paths))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+fn import(paths: &[PathBuf]) -> HistoryImport {
+    let mut import = HistoryImport::default();
+    for path in paths {
+        let is_txt = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"));
+        if is_txt && path.is_file() {
+            import.files.push(import_file(path));
+        } else {
+            import.skipped.push(path.clone());
+        }
+    }
+    import
+}
+
+fn import_file(path: &Path) -> ImportedFile {
+    let mut file = ImportedFile {
+        path: path.to_path_buf(),
+        characters: Vec::new(),
+        dynasties: Vec::new(),
+        errors: Vec::new(),
+    };
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            file.errors.push(err.to_string());
+            return file;
+        }
+    };
+    // The game writes UTF-8 with a byte order mark; older mods may be ANSI.
+    let text = String::from_utf8_lossy(&bytes);
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+
+    let (entries, error) = parse(text);
+    file.errors.extend(error);
+    for (key, value) in entries {
+        let Value::Block(block) = value else {
+            continue;
+        };
+        if is_character(&block) {
+            file.characters.push(character(key, &block));
+        } else if scalar(&block, "name").is_some() {
+            file.dynasties.push(ImportedDynasty {
+                id: key,
+                name: scalar(&block, "name"),
+                culture: scalar(&block, "culture"),
+                motto: scalar(&block, "motto"),
+                dynasty: scalar(&block, "dynasty"),
+            });
+        }
+    }
+    file
+}
+
+/// Characters have a religion or dated entries; dynasties and houses never do.
+fn is_character(block: &[(String, Value)]) -> bool {
+    block
+        .iter()
+        .any(|(key, _)| key == "religion" || key == "faith" || is_date(key))
+}
+
+fn character(id: String, block: &[(String, Value)]) -> ImportedCharacter {
+    let mut character = ImportedCharacter {
+        id,
+        name: scalar(block, "name"),
+        female: scalar(block, "female").as_deref() == Some("yes"),
+        dynasty: scalar(block, "dynasty"),
+        dynasty_house: scalar(block, "dynasty_house"),
+        culture: scalar(block, "culture"),
+        religion: scalar(block, "religion").or_else(|| scalar(block, "faith")),
+        father: scalar(block, "father"),
+        mother: scalar(block, "mother"),
+        ..Default::default()
+    };
+    for (key, value) in block {
+        match value {
+            Value::Scalar(trait_) if key == "trait" => character.traits.push(trait_.clone()),
+            Value::Block(entries) if is_date(key) => {
+                for (field, _) in entries {
+                    match field.as_str() {
+                        "birth" => character.birth = Some(key.clone()),
+                        "death" => character.death = Some(key.clone()),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    character
+}
+
+/// The first scalar value of `key` in `block`.
+fn scalar(block: &[(String, Value)], key: &str) -> Option<String> {
+    block.iter().find_map(|(k, value)| match value {
+        Value::Scalar(value) if k == key => Some(value.clone()),
+        _ => None,
+    })
+}
+
+/// History dates, `year.month.day` with an optional leading minus.
+fn is_date(key: &str) -> bool {
+    let parts: Vec<&str> = key.trim_start_matches('-').split('.').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+}
+
+enum Value {
+    Scalar(String),
+    Block(Vec<(String, Value)>),
+}
+
+#[derive(PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Equals,
+    Word(String),
+}
+
+/// Parse a Paradox script into its top-level `key = value` entries.
+///
+/// Bare values inside a block, such as the members of a list, are dropped.
+/// Parsing stops at the first syntax error, which is returned alongside the
+/// entries read up to that point.
+fn parse(text: &str) -> (Vec<(String, Value)>, Option<String>) {
+    let tokens = tokenize(text);
+    let mut stack: Vec<(String, Vec<(String, Value)>)> = vec![(String::new(), Vec::new())];
+    let mut i = 0;
+    while i < tokens.len() {
+        let (line, token) = &tokens[i];
+        match token {
+            Token::Close => {
+                if stack.len() == 1 {
+                    return (
+                        stack.pop().unwrap().1,
+                        Some(format!("line {line}: unmatched '}}'")),
+                    );
+                }
+                let (key, entries) = stack.pop().unwrap();
+                stack
+                    .last_mut()
+                    .unwrap()
+                    .1
+                    .push((key, Value::Block(entries)));
+                i += 1;
+            }
+            Token::Word(key) if tokens.get(i + 1).map(|(_, t)| t) == Some(&Token::Equals) => {
+                match tokens.get(i + 2) {
+                    Some((_, Token::Word(value))) => {
+                        let entry = (key.clone(), Value::Scalar(value.clone()));
+                        stack.last_mut().unwrap().1.push(entry);
+                        i += 3;
+                    }
+                    Some((_, Token::Open)) => {
+                        stack.push((key.clone(), Vec::new()));
+                        i += 3;
+                    }
+                    _ => {
+                        let entries = stack.swap_remove(0).1;
+                        return (
+                            entries,
+                            Some(format!("line {line}: '{key} =' has no value")),
+                        );
+                    }
+                }
+            }
+            Token::Open => {
+                // An anonymous block, e.g. inside a list; keep its nesting.
+                stack.push((String::new(), Vec::new()));
+                i += 1;
+            }
+            Token::Word(_) => i += 1,
+            Token::Equals => {
+                let entries = stack.swap_remove(0).1;
+                return (entries, Some(format!("line {line}: unexpected '='")));
+            }
+        }
+    }
+    if stack.len() > 1 {
+        let entries = stack.swap_remove(0).1;
+        return (entries, Some("unclosed '{' at end of file".into()));
+    }
+    (stack.pop().unwrap().1, None)
+}
+
+/// Split a script into tokens tagged with their 1-based line.
+fn tokenize(text: &str) -> Vec<(usize, Token)> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            '#' => while chars.next_if(|&c| c != '\n').is_some() {},
+            '{' => tokens.push((line, Token::Open)),
+            '}' => tokens.push((line, Token::Close)),
+            '=' => tokens.push((line, Token::Equals)),
+            '"' => {
+                let start = line;
+                let mut word = String::new();
+                for c in chars.by_ref() {
+                    match c {
+                        '"' => break,
+                        '\n' => {
+                            line += 1;
+                            word.push(c);
+                        }
+                        _ => word.push(c),
+                    }
+                }
+                tokens.push((start, Token::Word(word)));
+            }
+            c if c.is_whitespace() => {}
+            // Comparison operators only appear in triggers, never in history.
+            '<' | '>' | '!' | '?' => {}
+            c => {
+                let mut word = String::from(c);
+                while let Some(c) = chars
+                    .next_if(|&c| !c.is_whitespace() && !matches!(c, '{' | '}' | '=' | '#' | '"'))
+                {
+                    word.push(c);
+                }
+                tokens.push((line, Token::Word(word)));
+            }
+        }
+    }
+    tokens
+}
@@ -0,0 +1,266 @@
+//! The queue of generation jobs.
+//!
+//! `enqueue_job` queues a run of a config directory, the project's if none
+//! is given, with a seed of its own. Each job is generated natively, as any
+//! generation of the app, and writes into a folder of its own under the app
+//! data folder instead of the project's output. As many jobs run side by side as the job
+//! concurrency allows, one by default, so a batch of seeds or configs can be
+//! left to run one after another. Waiting jobs can be moved up or down the
+//! queue or cancelled, and a running one is cancelled like any generation.
+//! Every change of the queue is sent to the frontend as a `jobs-changed`
+//! event, and every finished job is recorded in its project database.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use sidecar_manager::{Job, JobQueue, JobSpec, JobStatus, LaunchOptions, Progress};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::native::{self, Control, CANCELLED};
+use crate::output;
+use crate::project_db;
+
+/// Emitted with every job whenever the queue changes.
+pub const JOBS_CHANGED_EVENT: &str = "jobs-changed";
+
+/// Upper bound accepted by `set_job_concurrency`.
+const MAX_CONCURRENT_JOBS: usize = 8;
+
+/// Managed state holding the queue.
+pub struct Jobs {
+    queue: Mutex<JobQueue>,
+    /// Number of jobs run side by side.
+    concurrency: Mutex<usize>,
+    /// Controls of the running jobs, for cancelling them.
+    controls: Mutex<HashMap<u64, Arc<Control>>>,
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        Self {
+            queue: Mutex::new(JobQueue::default()),
+            concurrency: Mutex::new(1),
+            controls: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// What `enqueue_job` is asked to run.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobRequest {
+    name: Option<String>,
+    /// The project's config directory if none.
+    config_dir: Option<PathBuf>,
+    seed: Option<u64>,
+}
+
+/// Queue a generation job and start it if a slot is free, returning it.
+#[tauri::command]
+pub async fn enqueue_job(app: AppHandle, job: JobRequest) -> Result<Job, String> {
+    let config_dir = match job.config_dir {
+        Some(dir) => dir,
+        None => output::current_paths(&app).await?.config_dir,
+    };
+    enqueue(
+        &app,
+        JobSpec {
+            name: job.name,
+            config_dir,
+            seed: job.seed,
+        },
+    )
+}
+
+/// Queue the job `spec` and start it if a slot is free, returning it.
+pub fn enqueue(app: &AppHandle, spec: JobSpec) -> Result<Job, String> {
+    if !spec.config_dir.join("initialization.json").is_file() {
+        return Err(format!(
+            "{} holds no initialization.json",
+            spec.config_dir.display()
+        ));
+    }
+    let root = app
+        .path()
+        .app_local_data_dir()
+        .map_err(|err| err.to_string())?
+        .join("jobs");
+    let jobs = app.state::<Jobs>();
+    let id = {
+        let mut queue = jobs.queue.lock().unwrap();
+        // Stamped, as job numbers start over with every session.
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let output_dir = root.join(format!("{stamp}-{}", queue.next_id()));
+        queue.push(spec, output_dir)
+    };
+    changed(app);
+    dispatch(app);
+    let queue = jobs.queue.lock().unwrap();
+    queue
+        .get(id)
+        .cloned()
+        .ok_or_else(|| "the job is gone".into())
+}
+
+/// The jobs waiting or running, as they were queued.
+pub fn unfinished(app: &AppHandle) -> Vec<JobSpec> {
+    app.state::<Jobs>()
+        .queue
+        .lock()
+        .unwrap()
+        .jobs()
+        .into_iter()
+        .filter(|job| !job.status.is_finished())
+        .map(|job| JobSpec {
+            name: Some(job.name),
+            config_dir: job.config_dir,
+            seed: job.seed,
+        })
+        .collect()
+}
+
+/// Every job, running and finished ones first, then the waiting ones in the
+/// order they will start.
+#[tauri::command]
+pub fn list_jobs(jobs: State<'_, Jobs>) -> Vec<Job> {
+    jobs.queue.lock().unwrap().jobs()
+}
+
+/// Move the waiting job `id` to `position` among the waiting jobs, counting
+/// from 0.
+#[tauri::command]
+pub fn move_job(app: AppHandle, id: u64, position: usize) -> Result<Vec<Job>, String> {
+    app.state::<Jobs>()
+        .queue
+        .lock()
+        .unwrap()
+        .move_to(id, position)?;
+    Ok(changed(&app))
+}
+
+/// Cancel the job `id`: a waiting one does not start, and a running one
+/// stops before its next year; nothing of it is written.
+#[tauri::command]
+pub fn cancel_job(app: AppHandle, id: u64) -> Result<Vec<Job>, String> {
+    let jobs = app.state::<Jobs>();
+    let status = jobs.queue.lock().unwrap().cancel(id)?;
+    if status == JobStatus::Running {
+        if let Some(control) = jobs.controls.lock().unwrap().get(&id) {
+            control.cancel();
+        }
+    }
+    Ok(changed(&app))
+}
+
+/// Drop the finished jobs from the list; their output stays on disk.
+#[tauri::command]
+pub fn clear_finished_jobs(app: AppHandle) -> Vec<Job> {
+    app.state::<Jobs>().queue.lock().unwrap().clear_finished();
+    changed(&app)
+}
+
+#[tauri::command]
+pub fn get_job_concurrency(jobs: State<'_, Jobs>) -> usize {
+    *jobs.concurrency.lock().unwrap()
+}
+
+/// Set the number of jobs run side by side, starting waiting ones if that
+/// frees slots; running jobs beyond it are left to finish.
+#[tauri::command]
+pub fn set_job_concurrency(app: AppHandle, count: usize) -> Result<(), String> {
+    if !(1..=MAX_CONCURRENT_JOBS).contains(&count) {
+        return Err(format!(
+            "job concurrency must be between 1 and {MAX_CONCURRENT_JOBS}"
+        ));
+    }
+    *app.state::<Jobs>().concurrency.lock().unwrap() = count;
+    dispatch(&app);
+    Ok(())
+}
+
+/// Record the progress `progress` of job `id`.
+fn report(app: &AppHandle, id: u64, progress: &Progress) {
+    if let Some(job) = app.state::<Jobs>().queue.lock().unwrap().get_mut(id) {
+        job.percent = Some(progress.percent);
+    }
+    changed(app);
+}
+
+/// Start waiting jobs while there are free slots.
+fn dispatch(app: &AppHandle) {
+    let jobs = app.state::<Jobs>();
+    let concurrency = *jobs.concurrency.lock().unwrap();
+    loop {
+        let job = {
+            let mut queue = jobs.queue.lock().unwrap();
+            if queue.running() >= concurrency {
+                break;
+            }
+            match queue.start_next() {
+                Some(job) => job,
+                None => break,
+            }
+        };
+        tauri::async_runtime::spawn(run(app.clone(), job));
+    }
+    changed(app);
+}
+
+/// Run `job` natively, then start the next.
+async fn run(app: AppHandle, job: Job) {
+    let started = Instant::now();
+    let control = Arc::new(Control::default());
+    let jobs = app.state::<Jobs>();
+    jobs.controls
+        .lock()
+        .unwrap()
+        .insert(job.id, control.clone());
+    let result = generate(&app, &job, control).await;
+    jobs.controls.lock().unwrap().remove(&job.id);
+    let (status, error) = match result {
+        Ok(()) => (JobStatus::Complete, None),
+        Err(err) if err == CANCELLED => (JobStatus::Cancelled, None),
+        Err(err) => (JobStatus::Failed, Some(err)),
+    };
+    jobs.queue
+        .lock()
+        .unwrap()
+        .finish(job.id, status, error, started.elapsed().as_secs_f64());
+    dispatch(&app);
+}
+
+/// Generate `job` into its output directory, recording it once done.
+async fn generate(app: &AppHandle, job: &Job, control: Arc<Control>) -> Result<(), String> {
+    let options = LaunchOptions {
+        config_dir: Some(job.config_dir.clone()),
+        seed: job.seed,
+        output_dir: Some(job.output_dir.clone()),
+    };
+    let paths = output::paths_for(app, &options)?;
+    let app = app.clone();
+    let (id, seed, config_dir) = (job.id, job.seed, job.config_dir.clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        let generated = native::generate(&paths, None, seed, None, &control, |progress| {
+            report(&app, id, &progress)
+        })?;
+        for warning in &generated.warnings {
+            tracing::warn!("job {id}: {warning}");
+        }
+        project_db::record(&app, &config_dir, &paths.character_dir, "job");
+        Ok(())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Tell the frontend about the queue, returning it.
+fn changed(app: &AppHandle) -> Vec<Job> {
+    let jobs = app.state::<Jobs>().queue.lock().unwrap().jobs();
+    let _ = app.emit(JOBS_CHANGED_EVENT, jobs.clone());
+    jobs
+}
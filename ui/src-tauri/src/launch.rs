@@ -0,0 +1,123 @@
+//! Launch options for the packaged engine.
+//!
+//! The config directory, RNG seed and output directory the sidecar is started
+//! with can be set from the UI, so a run can be reproduced exactly. The options
+//! are saved to `launch.json` in the app config directory and forwarded as
+//! command-line arguments every time the sidecar is spawned; changing them
+//! takes effect on the next spawn, which `restart_engine` triggers on demand.
+//!
+//! The seed is also used by native runs, straight away. Every run records
+//! its seed at the top of `family_history.txt`, so one that was left unseeded
+//! can still be reproduced from `get_last_seed`.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use ck3_sim::HISTORY_FILE;
+use sidecar_manager::LaunchOptions;
+use tauri::{AppHandle, Manager, State};
+
+use crate::output;
+use crate::store;
+
+/// File in the app config directory holding the saved options.
+const LAUNCH_FILE: &str = "launch.json";
+
+/// Managed state holding the options the next sidecar is launched with.
+#[derive(Default)]
+pub struct EngineLaunch {
+    options: Mutex<LaunchOptions>,
+    /// The options the running engine was launched with, once one has been.
+    running: Mutex<Option<LaunchOptions>>,
+}
+
+impl EngineLaunch {
+    pub fn options(&self) -> LaunchOptions {
+        self.options.lock().unwrap().clone()
+    }
+
+    /// The options the running engine was launched with, or, before one
+    /// has been, those the next will be.
+    pub fn running(&self) -> LaunchOptions {
+        let running = self.running.lock().unwrap().clone();
+        running.unwrap_or_else(|| self.options())
+    }
+
+    pub fn set_running(&self, options: LaunchOptions) {
+        *self.running.lock().unwrap() = Some(options);
+    }
+}
+
+/// Load the saved options, if any, so the first sidecar is launched with them.
+pub fn load(app: &AppHandle) {
+    let options = store::load(store::config_file(app, LAUNCH_FILE));
+    *app.state::<EngineLaunch>().options.lock().unwrap() = options;
+}
+
+/// Return the options the engine is launched with.
+#[tauri::command]
+pub fn get_launch_options(launch: State<'_, EngineLaunch>) -> LaunchOptions {
+    launch.options()
+}
+
+/// Save the options the engine is launched with.
+///
+/// The config directory must exist; the output directory is created if it
+/// does not. The running engine keeps its current options until it is
+/// restarted.
+#[tauri::command]
+pub fn set_launch_options(
+    app: AppHandle,
+    launch: State<'_, EngineLaunch>,
+    options: LaunchOptions,
+) -> Result<(), String> {
+    if let Some(dir) = &options.config_dir {
+        if !dir.is_dir() {
+            return Err(format!("config directory {} does not exist", dir.display()));
+        }
+    }
+    if let Some(dir) = &options.output_dir {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+
+    save(&app, &launch, options)
+}
+
+/// Set the seed runs are started with, or `None` for a random one each run.
+#[tauri::command]
+pub fn set_seed(
+    app: AppHandle,
+    launch: State<'_, EngineLaunch>,
+    seed: Option<u64>,
+) -> Result<(), String> {
+    let options = LaunchOptions {
+        seed,
+        ..launch.options()
+    };
+    save(&app, &launch, options)
+}
+
+/// Return the seed recorded in the last generated history, if any.
+#[tauri::command]
+pub async fn get_last_seed(app: AppHandle) -> Result<Option<u64>, String> {
+    last_seed(&output::current_paths(&app).await?.character_dir)
+}
+
+/// The seed recorded in the history in `character_dir`, if there is one.
+pub fn last_seed(character_dir: &Path) -> Result<Option<u64>, String> {
+    match fs::read_to_string(character_dir.join(HISTORY_FILE)) {
+        Ok(history) => Ok(ck3_sim::history_seed(&history)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn save(app: &AppHandle, launch: &EngineLaunch, options: LaunchOptions) -> Result<(), String> {
+    store::config_file(app, LAUNCH_FILE)
+        .and_then(|path| store::save(&path, &options))
+        .map_err(|err| err.to_string())?;
+
+    *launch.options.lock().unwrap() = options;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//! Rotating log files for engine output.
+//!
+//! Every stdout and stderr line from the sidecar is appended to a timestamped
+//! `engine-<date>-<time>.log` file in the app log directory, rotated by size
+//! (see [`sidecar_manager::RotatingLog`]). Packaged builds have no console, so
+//! without these files the engine's output would be lost.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use sidecar_manager::{RotatingLog, Stream};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_opener::OpenerExt;
+
+/// Prefix shared by all engine log files.
+const LOG_PREFIX: &str = "engine-";
+
+/// Managed state owning the engine log, opened on first use.
+#[derive(Default)]
+pub struct EngineLog {
+    log: Mutex<Option<RotatingLog>>,
+}
+
+impl EngineLog {
+    /// Append a line to the engine log.
+    pub fn write(&self, app: &AppHandle, stream: Stream, line: &str) {
+        let result = self.with_log(app, |log| log.write_line(stream, line));
+        if let Err(err) = result {
+            tracing::warn!("failed to write engine log: {err}");
+        }
+    }
+
+    /// Return the last `limit` lines of engine output, newest last.
+    pub fn recent_lines(&self, app: &AppHandle, limit: usize) -> std::io::Result<Vec<String>> {
+        self.with_log(app, |log| log.recent_lines(limit))
+    }
+
+    fn with_log<T>(
+        &self,
+        app: &AppHandle,
+        f: impl FnOnce(&mut RotatingLog) -> std::io::Result<T>,
+    ) -> std::io::Result<T> {
+        let mut log = self.log.lock().unwrap();
+        if log.is_none() {
+            *log = Some(RotatingLog::new(log_dir(app)?, LOG_PREFIX));
+        }
+        f(log.as_mut().expect("log was just opened"))
+    }
+}
+
+/// Directory holding the engine logs, created on first use.
+pub fn log_dir(app: &AppHandle) -> std::io::Result<PathBuf> {
+    let dir = app.path().app_log_dir().map_err(std::io::Error::other)?;
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Return the last `limit` lines of the newest log file, read from disk.
+///
+/// Unlike [`EngineLog::recent_lines`] this does not wait for the log, so it is
+/// safe from a panic hook that may have interrupted a write.
+pub fn read_recent(app: &AppHandle, limit: usize) -> std::io::Result<Vec<String>> {
+    RotatingLog::new(log_dir(app)?, LOG_PREFIX).recent_lines(limit)
+}
+
+/// Open the log directory in the platform file manager.
+#[tauri::command]
+pub fn open_log_folder(app: AppHandle) -> Result<(), String> {
+    let dir = log_dir(&app).map_err(|err| err.to_string())?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|err| err.to_string())
+}
@@ -1,30 +1,257 @@
 // Prevents an extra console window from appearing on Windows in release builds.
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-fn main() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_shell::init())
-        .setup(|_app| {
-            // Spawn the compiled FastAPI server as a sidecar.
-            // In development the server is started by run_ui.bat; the sidecar
-            // is only active in a packaged (release) build.
-            #[cfg(not(debug_assertions))]
-            {
-                use tauri_plugin_shell::ShellExt;
+mod archive;
+mod autosave;
+mod bookmarks;
+mod boot;
+mod chronicle;
+mod cli;
+mod config;
+mod crash;
+mod csv_export;
+#[cfg(feature = "embedded-engine")]
+mod embedded;
+mod endpoint;
+mod game_paths;
+mod gedcom;
+mod generation;
+mod health;
+mod ids;
+mod import;
+mod jobs;
+mod launch;
+mod logs;
+mod menu;
+mod names;
+mod native;
+mod notify;
+mod open;
+mod orphans;
+mod output;
+mod pickers;
+mod play;
+mod preview;
+mod problems;
+mod project_db;
+mod project_file;
+mod projects;
+mod report;
+mod scaffold;
+mod server;
+mod shell_log;
+mod sidecar;
+mod snippets;
+mod splash;
+mod stats;
+mod stderr;
+mod store;
+mod templates;
+mod tray;
+mod tree_export;
+mod tree_window;
+mod undo;
+mod updater;
+mod window_state;
+mod workers;
+mod workshop;
 
-                let shell = _app.shell();
-                let sidecar = shell
-                    .sidecar("api_server")
-                    .expect("api_server sidecar not found in bundle");
-
-                // Spawn without blocking — the child runs for the app lifetime.
-                sidecar
-                    .spawn()
-                    .expect("failed to spawn api_server sidecar");
-            }
+use tauri::RunEvent;
 
+fn main() -> std::process::ExitCode {
+    if let Some(code) = cli::run() {
+        return code;
+    }
+    shell_log::init();
+    tauri::Builder::default()
+        // Must come first so a second instance exits before anything else
+        // starts up.
+        .plugin(tauri_plugin_single_instance::init(open::on_second_instance))
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_shell::init())
+        .plugin(updater::plugin())
+        .manage(autosave::Autosave::default())
+        .manage(crash::CrashContext::default())
+        .manage(endpoint::ApiEndpoint::default())
+        .manage(generation::Generation::default())
+        .manage(health::EngineHealth::default())
+        .manage(jobs::Jobs::default())
+        .manage(launch::EngineLaunch::default())
+        .manage(logs::EngineLog::default())
+        .manage(menu::AppMenu::default())
+        .manage(native::LastRun::default())
+        .manage(native::NativeCache::default())
+        .manage(open::PendingProject::default())
+        .manage(pickers::RecentPaths::default())
+        .manage(project_db::ProjectDb::default())
+        .manage(projects::RecentProjects::default())
+        .manage(server::LocalServer::default())
+        .manage(sidecar::Sidecar::default())
+        .manage(splash::Splash::default())
+        .manage(templates::Templates::default())
+        .manage(tray::Tray::default())
+        .manage(tree_window::TreeWindows::default())
+        .manage(undo::UndoHistory::default())
+        .manage(updater::PendingUpdate::default())
+        .manage(window_state::WindowState::default())
+        .manage(workers::WorkerPool::default())
+        .invoke_handler(tauri::generate_handler![
+            archive::export_project_archive,
+            archive::import_project_archive,
+            autosave::discard_recovery,
+            autosave::get_recovery,
+            autosave::restore_recovery,
+            autosave::set_working_config,
+            chronicle::export_chronicle,
+            config::load_config,
+            config::save_config,
+            crash::list_crash_reports,
+            crash::submit_crash_report,
+            csv_export::export_csv,
+            endpoint::get_api_base_url,
+            game_paths::detect_game_paths,
+            gedcom::export_gedcom,
+            generation::cancel_generation,
+            generation::get_generation_progress,
+            generation::pause_generation,
+            generation::resume_generation,
+            generation::run_simulation,
+            health::get_engine_status,
+            ids::find_id_collisions,
+            ids::renumber_ids,
+            ids::scan_character_ids,
+            import::import_history_files,
+            jobs::cancel_job,
+            jobs::clear_finished_jobs,
+            jobs::enqueue_job,
+            jobs::get_job_concurrency,
+            jobs::list_jobs,
+            jobs::move_job,
+            jobs::set_job_concurrency,
+            launch::get_last_seed,
+            launch::get_launch_options,
+            launch::set_launch_options,
+            launch::set_seed,
+            logs::open_log_folder,
+            names::suggest_names,
+            native::check_game_data,
+            native::continue_native_simulation,
+            native::regenerate_native_simulation,
+            native::resume_native_simulation,
+            native::run_native_simulation,
+            native::save_native_state,
+            open::take_pending_project,
+            output::open_output_folder,
+            output::reveal_output_file,
+            pickers::pick_archive_file,
+            pickers::pick_archive_save_file,
+            pickers::pick_chronicle_file,
+            pickers::pick_csv_export_dir,
+            pickers::pick_game_dir,
+            pickers::pick_gedcom_file,
+            pickers::pick_history_file,
+            pickers::pick_id_check_dir,
+            pickers::pick_mod_build_dir,
+            pickers::pick_mod_dir,
+            pickers::pick_output_dir,
+            pickers::pick_project_file,
+            pickers::pick_project_save_file,
+            pickers::pick_report_file,
+            pickers::pick_state_file,
+            pickers::pick_state_save_file,
+            pickers::pick_tree_export_dir,
+            play::play_with_mod,
+            preview::preview_dynasty,
+            problems::check_marriages,
+            problems::check_survivors,
+            problems::check_traits,
+            problems::lint_output,
+            problems::repair_survivors,
+            project_db::character_facets,
+            project_db::compare_runs,
+            project_db::delete_run,
+            project_db::dynasty_stats,
+            project_db::get_project_setting,
+            project_db::list_runs,
+            project_db::search_characters,
+            project_db::set_project_setting,
+            project_file::open_project,
+            project_file::save_project,
+            projects::add_recent_project,
+            projects::get_recent_projects,
+            projects::pin_project,
+            report::export_report,
+            scaffold::deploy_mod,
+            scaffold::scaffold_mod,
+            shell_log::query_logs,
+            sidecar::restart_engine,
+            snippets::copy_character_block,
+            snippets::copy_dynasty_block,
+            snippets::copy_ruler_designer_dna,
+            templates::apply_dynasty_template,
+            templates::delete_dynasty_template,
+            templates::list_dynasty_templates,
+            templates::save_dynasty_template,
+            tree_export::export_family_trees,
+            tree_window::get_tree_view,
+            tree_window::list_tree_windows,
+            tree_window::open_tree_window,
+            tree_window::set_tree_dynasty,
+            undo::edit_character,
+            undo::get_undo_state,
+            undo::record_config_edit,
+            undo::redo,
+            undo::undo,
+            updater::check_for_update,
+            updater::install_update,
+            workers::get_worker_count,
+            workers::run_parallel_simulation,
+            workers::set_worker_count,
+            workshop::package_for_workshop,
+        ])
+        .on_window_event(|window, event| {
+            import::on_window_event(window, event);
+            window_state::on_window_event(window, event);
+            tray::on_window_event(window, event);
+            tree_window::on_window_event(window, event);
+        })
+        .on_page_load(splash::on_page_load)
+        .setup(|app| {
+            // Spawn the compiled FastAPI server as a supervised sidecar that is
+            // restarted if it crashes, or import the engine in a build that
+            // embeds it. In development the server is started by run_ui.bat
+            // instead.
+            crash::start(app.handle());
+            shell_log::start(app.handle());
+            window_state::restore(app.handle());
+            splash::start(app.handle());
+            launch::load(app.handle());
+            open::start(app.handle());
+            server::start(app.handle());
+            sidecar::start(app.handle());
+            #[cfg(feature = "embedded-engine")]
+            embedded::start(app.handle());
+            health::start(app.handle());
+            boot::start(app.handle());
+            stats::start(app.handle());
+            tray::start(app.handle());
+            menu::start(app.handle());
+            updater::start(app.handle());
+            generation::start(app.handle());
+            autosave::start(app.handle());
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
\ No newline at end of file
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Let the engine flush its output before the process goes away.
+            if let RunEvent::Exit = event {
+                sidecar::shutdown(app);
+                autosave::shutdown(app);
+            }
+        });
+    std::process::ExitCode::SUCCESS
+}
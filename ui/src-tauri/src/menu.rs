@@ -0,0 +1,175 @@
+//! The application menu bar.
+//!
+//! File, Generate and Help menus with the usual accelerators. Actions the
+//! frontend owns (a new or saved project, starting a run) are sent to it as
+//! `menu-action` events; the others are carried out here: Open shows a native
+//! picker and opens the project like a double-clicked file, Cancel goes
+//! straight to the engine, and Logs opens the log folder. Run and Cancel are
+//! enabled according to the generation's progress.
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::menu::{AboutMetadata, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::generation::{self, SimulationProgress};
+use crate::logs;
+use crate::open;
+use crate::pickers;
+
+/// Emitted with the [`MenuAction`] of a menu item the frontend handles.
+pub const MENU_ACTION_EVENT: &str = "menu-action";
+
+const NEW_ID: &str = "menu-new";
+const OPEN_ID: &str = "menu-open";
+const SAVE_ID: &str = "menu-save";
+const QUIT_ID: &str = "menu-quit";
+const RUN_ID: &str = "menu-run";
+const CANCEL_ID: &str = "menu-cancel";
+const LOGS_ID: &str = "menu-logs";
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MenuAction {
+    /// Start a new project.
+    New,
+    /// Save the current project.
+    Save,
+    /// Start a generation.
+    Run,
+}
+
+/// The menu items that change with the progress.
+#[derive(Clone)]
+struct MenuItems {
+    run: MenuItem<tauri::Wry>,
+    cancel: MenuItem<tauri::Wry>,
+}
+
+/// Managed state holding the menu once it has been created.
+#[derive(Default)]
+pub struct AppMenu {
+    items: Mutex<Option<MenuItems>>,
+}
+
+/// Create the menu bar and attach it to the main window.
+pub fn start(app: &AppHandle) {
+    match build(app) {
+        Ok(items) => *app.state::<AppMenu>().items.lock().unwrap() = Some(items),
+        Err(err) => tracing::warn!("menu bar disabled: {err}"),
+    }
+}
+
+fn build(app: &AppHandle) -> tauri::Result<MenuItems> {
+    let run = MenuItem::with_id(app, RUN_ID, "Run", true, Some("CmdOrCtrl+G"))?;
+    let cancel = MenuItem::with_id(app, CANCEL_ID, "Cancel", false, None::<&str>)?;
+    let about = AboutMetadata {
+        name: Some(app.package_info().name.clone()),
+        version: Some(app.package_info().version.to_string()),
+        ..Default::default()
+    };
+
+    let file = Submenu::with_items(
+        app,
+        "File",
+        true,
+        &[
+            &MenuItem::with_id(app, NEW_ID, "New", true, Some("CmdOrCtrl+N"))?,
+            &MenuItem::with_id(app, OPEN_ID, "Open...", true, Some("CmdOrCtrl+O"))?,
+            &MenuItem::with_id(app, SAVE_ID, "Save", true, Some("CmdOrCtrl+S"))?,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, QUIT_ID, "Quit", true, Some("CmdOrCtrl+Q"))?,
+        ],
+    )?;
+    let generate = Submenu::with_items(app, "Generate", true, &[&run, &cancel])?;
+    let help = Submenu::with_items(
+        app,
+        "Help",
+        true,
+        &[
+            &MenuItem::with_id(app, LOGS_ID, "Open log folder", true, None::<&str>)?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::about(app, None, Some(about))?,
+        ],
+    )?;
+
+    // macOS has one menu bar for the whole app, and without an Edit menu the
+    // webview gets no copy and paste shortcuts. Elsewhere the menu belongs to
+    // the main window only, so tree windows stay bare.
+    #[cfg(target_os = "macos")]
+    {
+        let edit = Submenu::with_items(
+            app,
+            "Edit",
+            true,
+            &[
+                &PredefinedMenuItem::undo(app, None)?,
+                &PredefinedMenuItem::redo(app, None)?,
+                &PredefinedMenuItem::separator(app)?,
+                &PredefinedMenuItem::cut(app, None)?,
+                &PredefinedMenuItem::copy(app, None)?,
+                &PredefinedMenuItem::paste(app, None)?,
+                &PredefinedMenuItem::select_all(app, None)?,
+            ],
+        )?;
+        let menu = Menu::with_items(app, &[&file, &edit, &generate, &help])?;
+        app.set_menu(menu)?;
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let menu = Menu::with_items(app, &[&file, &generate, &help])?;
+        if let Some(window) = app.get_webview_window("main") {
+            window.set_menu(menu)?;
+        }
+    }
+
+    app.on_menu_event(on_menu_event);
+    Ok(MenuItems { run, cancel })
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    let app = app.clone();
+    match event.id().as_ref() {
+        NEW_ID => emit(&app, MenuAction::New),
+        SAVE_ID => emit(&app, MenuAction::Save),
+        RUN_ID => emit(&app, MenuAction::Run),
+        OPEN_ID => {
+            tauri::async_runtime::spawn(async move {
+                match pickers::pick_project_file(app.clone()).await {
+                    Ok(Some(path)) => open::forward(&app, path),
+                    Ok(None) => {}
+                    Err(err) => tracing::warn!("failed to open a project: {err}"),
+                }
+            });
+        }
+        CANCEL_ID => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = generation::cancel(&app).await {
+                    tracing::warn!("failed to cancel the generation: {err}");
+                }
+            });
+        }
+        LOGS_ID => {
+            if let Err(err) = logs::open_log_folder(app) {
+                tracing::warn!("failed to open the log folder: {err}");
+            }
+        }
+        QUIT_ID => app.exit(0),
+        _ => {}
+    }
+}
+
+fn emit(app: &AppHandle, action: MenuAction) {
+    let _ = app.emit(MENU_ACTION_EVENT, action);
+}
+
+/// Enable Run or Cancel depending on whether a generation is running.
+pub fn show_progress(app: &AppHandle, progress: &SimulationProgress) {
+    // Menu changes are carried out on the main thread; see `tray::show_progress`.
+    let Some(items) = app.state::<AppMenu>().items.lock().unwrap().clone() else {
+        return;
+    };
+    let _ = items.run.set_enabled(!progress.running);
+    let _ = items.cancel.set_enabled(progress.running);
+}
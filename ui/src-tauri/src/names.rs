@@ -0,0 +1,58 @@
+//! Name suggestions for the dynasty editor.
+//!
+//! `suggest_names` draws names from the same lists the native engine names
+//! characters from: the project's bundled packs first, then the name lists
+//! of the game installation if one is given.
+
+use std::path::PathBuf;
+
+use ck3_sim::{NameLists, Sex};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::output;
+
+/// Most names returned by one call.
+const MAX_SUGGESTIONS: usize = 100;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestedName {
+    pub name: String,
+    /// The name with its patronym, for a culture that uses them and when the
+    /// father's name was given.
+    pub patronymic: Option<String>,
+}
+
+/// Suggest up to `count` different names of `culture` for `gender`, `male`
+/// or `female`, picking common names more often.
+#[tauri::command]
+pub async fn suggest_names(
+    app: AppHandle,
+    culture: String,
+    gender: String,
+    count: usize,
+    game_dir: Option<PathBuf>,
+    father: Option<String>,
+) -> Result<Vec<SuggestedName>, String> {
+    let sex = Sex::from_key(&gender).ok_or_else(|| format!("unknown gender '{gender}'"))?;
+    let dir = output::current_paths(&app).await?.name_lists_dir;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut names = NameLists::new(dir);
+        if let Some(game_dir) = &game_dir {
+            names = names.with_game_dir(game_dir);
+        }
+        let suggestions = names.suggest(&culture, sex, count.min(MAX_SUGGESTIONS), None);
+        suggestions
+            .into_iter()
+            .map(|name| {
+                let patronymic = father
+                    .as_deref()
+                    .and_then(|father| names.patronymic(&culture, sex, &name, father));
+                SuggestedName { name, patronymic }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
@@ -0,0 +1,541 @@
+//! The native simulation engine.
+//!
+//! `run_native_simulation` runs the project in-process with the `ck3-sim`
+//! crate instead of asking the Python engine to. It reads the same config
+//! and name lists and writes `family_history.txt`, with
+//! `dynasty_definitions.txt` and `dynasty_houses.txt` for the dynasties and
+//! the cadet houses they split into, `dna_data.txt` for portrait DNA and the
+//! `localization` files of dynasty and house names, the title histories and
+//! a `family_tree_<dynasty>.png` image of each dynasty into the same output
+//! directories, so the rest of the shell treats its output like any other
+//! run's. [`generate`] is the run every generation of the app makes, which
+//! reports its progress as it goes and can be paused, resumed and cancelled
+//! through a [`Control`]. Runs use the launch options' seed, a random one if
+//! it is unset.
+//!
+//! A parallel run simulates each dynasty on a thread of its own and only
+//! marries members of different dynasties once all of them are done, which
+//! makes projects with dozens of dynasties scale with the number of cores.
+//! `regenerate_native_simulation` runs the same way but keeps every
+//! dynasty's run in memory, so that after a tweak only the dynasties whose
+//! settings changed are simulated again and only the sections of the history
+//! they touch are rewritten. `continue_native_simulation` starts from the
+//! characters of an existing history file instead of fresh progenitors, so
+//! that a hand-written dynasty can be carried on over the project's period.
+//! `save_native_state` writes everything the last run simulated, characters,
+//! relationships, events and seed, as JSON, and `resume_native_simulation`
+//! carries such a state on over the project's period.
+//!
+//! A history is only written once its marriages pass the checks the
+//! config's `marriageChecks` section makes errors of, and once every
+//! character, dynasty and house it refers to is one it defines.
+//!
+//! Every run is recorded in the project database, and can be given a game
+//! installation or total conversion mod, in which case its history leaves
+//! out the traits that game does not define and, unless the config names a
+//! `gameVersion`, is written for the version of an installation.
+//! `check_game_data` lists what the project's config refers to that the
+//! game lacks.
+
+use std::fs;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ck3_config::{InitializationConfig, Severity};
+use ck3_sim::{
+    DanglingReference, GameData, NameLists, Regeneration, ShardCache, SimConfig, Simulation,
+    Summary, DNA_FILE,
+};
+use serde::Serialize;
+use sidecar_manager::{Phase, Progress};
+use tauri::{AppHandle, Manager};
+
+use crate::game_paths;
+use crate::launch::EngineLaunch;
+use crate::output::{self, EnginePaths};
+use crate::project_db;
+use crate::tree_export;
+
+/// How a native run that was cancelled fails.
+pub const CANCELLED: &str = "cancelled";
+
+/// Share of a run's percentage the simulated years take up, the rest going
+/// to writing its output in [`WRITING_STEPS`].
+const SIMULATING_SHARE: f64 = 90.0;
+/// The history files, then the family trees.
+const WRITING_STEPS: u32 = 2;
+/// How often a paused run looks whether it may go on.
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Number of the next native run, counting from 1 since the app started.
+static NEXT_RUN: AtomicU64 = AtomicU64::new(1);
+
+/// Managed state holding the dynasty runs of the last regeneration.
+#[derive(Default)]
+pub struct NativeCache(Mutex<ShardCache>);
+
+/// Managed state holding the last native run, for `save_native_state`.
+#[derive(Default)]
+pub struct LastRun(Mutex<Option<Simulation>>);
+
+impl LastRun {
+    pub fn set(app: &AppHandle, simulation: Simulation) {
+        *app.state::<LastRun>().0.lock().unwrap() = Some(simulation);
+    }
+}
+
+/// Lets a native run be paused, resumed and cancelled from another thread.
+#[derive(Default)]
+pub struct Control {
+    paused: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+impl Control {
+    /// Hold the run before its next year.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Stop the run before its next year, or before it writes anything.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Wait while the run is paused, then say whether it may go on.
+    fn proceed(&self) -> ControlFlow<()> {
+        while self.is_paused() && !self.cancelled.load(Ordering::SeqCst) {
+            thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+        if self.cancelled.load(Ordering::SeqCst) {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+/// A finished native run.
+pub struct Generated {
+    pub simulation: Simulation,
+    pub summary: Summary,
+    /// What could not be written without failing the run, such as family
+    /// trees without Graphviz.
+    pub warnings: Vec<String>,
+}
+
+/// Simulate the project at `paths` natively, targeting the game in
+/// `game_dir` if one is given, and write its output. A run with `threads`
+/// set simulates its dynasties in parallel on that many threads.
+/// `on_progress` is given the run's progress before every simulated year,
+/// where `control` may hold or stop it, and output step.
+///
+/// Fails with [`CANCELLED`] if the run was cancelled, before anything is
+/// written.
+pub fn generate(
+    paths: &EnginePaths,
+    game_dir: Option<&Path>,
+    seed: Option<u64>,
+    threads: Option<usize>,
+    control: &Control,
+    on_progress: impl Fn(Progress) + Sync,
+) -> Result<Generated, String> {
+    let config = load_config(&paths.config_dir, game_dir)?;
+    let rules = config.initialization.clone();
+    let names = NameLists::new(paths.name_lists_dir.clone());
+    let shards = if threads.is_some() {
+        rules.dynasties.len()
+    } else {
+        1
+    };
+    let tracker = Tracker::new(&rules, shards);
+    on_progress(tracker.seeding());
+    let before_year = |year| {
+        let flow = control.proceed();
+        if flow.is_continue() {
+            on_progress(tracker.simulating(year));
+        }
+        flow
+    };
+    let run = match threads {
+        Some(threads) => rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(|err| err.to_string())?
+            .install(|| Simulation::run_parallel_until(config, names, seed, before_year)),
+        None => {
+            let mut simulation = Simulation::new(config, names, seed);
+            simulation
+                .run_until(before_year)
+                .map(|summary| (simulation, summary))
+        }
+    };
+    let (simulation, summary) = run
+        .filter(|_| control.proceed().is_continue())
+        .ok_or(CANCELLED)?;
+    check_marriages(&simulation.history(), &rules)?;
+    check_references(simulation.dangling_references())?;
+
+    on_progress(tracker.writing(0));
+    simulation
+        .write_history(&paths.character_dir)
+        .map_err(|err| err.to_string())?;
+    on_progress(tracker.writing(1));
+    let warnings = draw_trees(paths).into_iter().collect();
+    Ok(Generated {
+        simulation,
+        summary,
+        warnings,
+    })
+}
+
+/// Draw the family trees of the history just written to `paths`, saying why
+/// if they could not be.
+fn draw_trees(paths: &EnginePaths) -> Option<String> {
+    tree_export::draw_family_trees(&paths.character_dir, &paths.tree_dir)
+        .err()
+        .map(|err| format!("the family trees were not drawn: {err}"))
+}
+
+/// Works out a native run's progress the way the Python engine reports it.
+struct Tracker {
+    run: u64,
+    first_year: i32,
+    years: u32,
+    /// Runs each simulating every year: one, or a parallel run's dynasties.
+    shards: u32,
+    /// Years simulated so far, counting each shard's.
+    simulated: AtomicU32,
+    started: Instant,
+}
+
+impl Tracker {
+    fn new(rules: &InitializationConfig, shards: usize) -> Self {
+        let years = rules.max_year - rules.min_year + 1;
+        Self {
+            run: NEXT_RUN.fetch_add(1, Ordering::SeqCst),
+            first_year: rules.min_year,
+            years: years.max(0) as u32,
+            shards: shards.max(1) as u32,
+            simulated: AtomicU32::new(0),
+            started: Instant::now(),
+        }
+    }
+
+    fn seeding(&self) -> Progress {
+        Progress {
+            run: self.run,
+            phase: Phase::Seeding,
+            year: None,
+            year_index: None,
+            years: Some(self.years),
+            percent: 0.0,
+            eta_seconds: None,
+        }
+    }
+
+    /// The progress of a shard about to simulate `year`.
+    fn simulating(&self, year: i32) -> Progress {
+        let done = self.simulated.fetch_add(1, Ordering::SeqCst);
+        let total = self.years * self.shards;
+        // The years done so far say how long the rest will take; the first
+        // gives no measure yet.
+        let eta = (done > 0).then(|| {
+            let elapsed = self.started.elapsed().as_secs_f64();
+            round(elapsed / f64::from(done) * f64::from(total - done))
+        });
+        Progress {
+            run: self.run,
+            phase: Phase::Simulating,
+            year: Some(year),
+            year_index: Some(done / self.shards + 1),
+            years: Some(self.years),
+            percent: round(SIMULATING_SHARE * f64::from(done) / f64::from(total)),
+            eta_seconds: eta,
+        }
+    }
+
+    /// The progress once `step` of the [`WRITING_STEPS`] are written.
+    fn writing(&self, step: u32) -> Progress {
+        let rest = 100.0 - SIMULATING_SHARE;
+        Progress {
+            run: self.run,
+            phase: Phase::Writing,
+            year: Some(self.first_year + self.years as i32 - 1),
+            year_index: Some(self.years),
+            years: Some(self.years),
+            percent: round(SIMULATING_SHARE + rest * f64::from(step) / f64::from(WRITING_STEPS)),
+            eta_seconds: None,
+        }
+    }
+}
+
+/// `value` to one decimal, as progress is reported.
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// Simulate the project natively and write its output.
+#[tauri::command]
+pub async fn run_native_simulation(
+    app: AppHandle,
+    parallel: bool,
+    game_dir: Option<PathBuf>,
+) -> Result<Summary, String> {
+    let paths = output::current_paths(&app).await?;
+    let seed = app.state::<EngineLaunch>().options().seed;
+    tauri::async_runtime::spawn_blocking(move || {
+        let threads = parallel.then(rayon::current_num_threads);
+        let generated = generate(
+            &paths,
+            game_dir.as_deref(),
+            seed,
+            threads,
+            &Control::default(),
+            drop,
+        )?;
+        for warning in &generated.warnings {
+            tracing::warn!("{warning}");
+        }
+        project_db::record(&app, &paths.config_dir, &paths.character_dir, "native");
+        LastRun::set(&app, generated.simulation);
+        Ok(generated.summary)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Simulate the project natively from the characters of the history file
+/// at `history` and write the extended history.
+///
+/// Portrait DNA is read from the DNA file next to it, if there is one.
+#[tauri::command]
+pub async fn continue_native_simulation(
+    app: AppHandle,
+    history: PathBuf,
+    game_dir: Option<PathBuf>,
+) -> Result<Summary, String> {
+    let paths = output::current_paths(&app).await?;
+    let seed = app.state::<EngineLaunch>().options().seed;
+    tauri::async_runtime::spawn_blocking(move || {
+        let config = load_config(&paths.config_dir, game_dir.as_deref())?;
+        let rules = config.initialization.clone();
+        let names = NameLists::new(paths.name_lists_dir.clone());
+        // The game writes UTF-8 with a byte order mark; older mods may be ANSI.
+        let bytes = fs::read(&history).map_err(|err| err.to_string())?;
+        let text = String::from_utf8_lossy(&bytes);
+        let dna = fs::read(history.with_file_name(DNA_FILE)).ok();
+        let dna = dna.as_deref().map(String::from_utf8_lossy);
+        let mut simulation = Simulation::from_history(config, names, seed, &text, dna.as_deref())
+            .map_err(|err| err.to_string())?;
+        let summary = simulation.run();
+        check_marriages(&simulation.history(), &rules)?;
+        check_references(simulation.dangling_references())?;
+        simulation
+            .write_history(&paths.character_dir)
+            .map_err(|err| err.to_string())?;
+        if let Some(warning) = draw_trees(&paths) {
+            tracing::warn!("{warning}");
+        }
+        project_db::record(&app, &paths.config_dir, &paths.character_dir, "native");
+        LastRun::set(&app, simulation);
+        Ok(summary)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Simulate the project natively on from the simulation state saved at
+/// `state` and write the extended history.
+///
+/// Without a seed in the launch options, the saved run's seed is used.
+#[tauri::command]
+pub async fn resume_native_simulation(
+    app: AppHandle,
+    state: PathBuf,
+    game_dir: Option<PathBuf>,
+) -> Result<Summary, String> {
+    let paths = output::current_paths(&app).await?;
+    let seed = app.state::<EngineLaunch>().options().seed;
+    tauri::async_runtime::spawn_blocking(move || {
+        let config = load_config(&paths.config_dir, game_dir.as_deref())?;
+        let rules = config.initialization.clone();
+        let names = NameLists::new(paths.name_lists_dir.clone());
+        let text = fs::read_to_string(&state).map_err(|err| err.to_string())?;
+        let mut simulation =
+            Simulation::from_state(config, names, seed, &text).map_err(|err| err.to_string())?;
+        let summary = simulation.run();
+        check_marriages(&simulation.history(), &rules)?;
+        check_references(simulation.dangling_references())?;
+        simulation
+            .write_history(&paths.character_dir)
+            .map_err(|err| err.to_string())?;
+        if let Some(warning) = draw_trees(&paths) {
+            tracing::warn!("{warning}");
+        }
+        project_db::record(&app, &paths.config_dir, &paths.character_dir, "native");
+        LastRun::set(&app, simulation);
+        Ok(summary)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Write the whole state of the last native run this session to `path` as
+/// JSON, replacing the file if it exists.
+#[tauri::command]
+pub async fn save_native_state(app: AppHandle, path: PathBuf) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<LastRun>();
+        let last = state.0.lock().unwrap();
+        let simulation = last
+            .as_ref()
+            .ok_or("nothing has been simulated natively yet")?;
+        fs::write(&path, simulation.save_state()).map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Simulate again the dynasties whose inputs changed since the last call and
+/// rewrite their part of the character history.
+///
+/// The first call simulates every dynasty. Later calls keep the seed of the
+/// first unless the launch options set one.
+#[tauri::command]
+pub async fn regenerate_native_simulation(
+    app: AppHandle,
+    game_dir: Option<PathBuf>,
+) -> Result<Regeneration, String> {
+    let paths = output::current_paths(&app).await?;
+    let seed = app.state::<EngineLaunch>().options().seed;
+    tauri::async_runtime::spawn_blocking(move || {
+        let config = load_config(&paths.config_dir, game_dir.as_deref())?;
+        let rules = config.initialization.clone();
+        let names = NameLists::new(paths.name_lists_dir.clone());
+        let state = app.state::<NativeCache>();
+        let mut cache = state.0.lock().unwrap();
+        let (simulation, regeneration) =
+            Simulation::run_incremental(config, names, seed, &mut cache);
+        check_marriages(&cache.history(), &rules)?;
+        check_references(cache.dangling_references())?;
+        cache
+            .write_history(&paths.character_dir)
+            .map_err(|err| err.to_string())?;
+        if let Some(warning) = draw_trees(&paths) {
+            tracing::warn!("{warning}");
+        }
+        project_db::record(&app, &paths.config_dir, &paths.character_dir, "native");
+        LastRun::set(&app, simulation);
+        Ok(regeneration)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// What a game defines, and what the project's config refers to that it
+/// does not.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameDataCheck {
+    pub traits: usize,
+    pub cultures: usize,
+    pub faiths: usize,
+    /// Game files that could not be read, each with why.
+    pub unreadable: Vec<String>,
+    /// The config's problems against the game, one per file.
+    pub problems: Vec<String>,
+}
+
+/// Read the game installation or mod in `game_dir` and check the project's
+/// config against it.
+#[tauri::command]
+pub async fn check_game_data(app: AppHandle, game_dir: PathBuf) -> Result<GameDataCheck, String> {
+    let paths = output::current_paths(&app).await?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let config = SimConfig::load(&paths.config_dir).map_err(|err| err.to_string())?;
+        let game = GameData::load(&game_dir);
+        let [traits, cultures, faiths] = game.counts();
+        Ok(GameDataCheck {
+            traits,
+            cultures,
+            faiths,
+            unreadable: game.errors().to_vec(),
+            problems: game
+                .check(&config)
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Refuse to write a history whose marriages break a rule the config makes
+/// an error of.
+pub fn check_marriages(history: &str, config: &InitializationConfig) -> Result<(), String> {
+    let people = ck3_sim::parse_people(history).map_err(|err| err.to_string())?;
+    let errors: Vec<String> = ck3_sim::check_marriages(&people, config)
+        .into_iter()
+        .filter(|problem| problem.severity == Severity::Error)
+        .map(|problem| problem.message)
+        .collect();
+    match errors.as_slice() {
+        [] => Ok(()),
+        [error] => Err(format!("the history was not written: {error}")),
+        [error, rest @ ..] => Err(format!(
+            "the history was not written: {error}, and {} more marriage problems",
+            rest.len()
+        )),
+    }
+}
+
+/// Refuse to write a history referring to IDs it does not define.
+pub fn check_references(references: Vec<DanglingReference>) -> Result<(), String> {
+    let Some(first) = references.first() else {
+        return Ok(());
+    };
+    let mut message = format!(
+        "the history was not written: {} at {}:{}",
+        first.message, first.file, first.line
+    );
+    if references.len() > 1 {
+        message.push_str(&format!(
+            ", and {} more dangling references",
+            references.len() - 1
+        ));
+    }
+    Err(message)
+}
+
+/// The project's config, targeting the game in `game_dir` if one is given,
+/// and writing for its version if the config names none.
+fn load_config(config_dir: &Path, game_dir: Option<&Path>) -> Result<SimConfig, String> {
+    let config = SimConfig::load(config_dir).map_err(|err| err.to_string())?;
+    Ok(target_game(config, game_dir))
+}
+
+/// `config` targeting the game in `game_dir` if one is given, and writing
+/// for its version if the config names none.
+pub fn target_game(mut config: SimConfig, game_dir: Option<&Path>) -> SimConfig {
+    let Some(dir) = game_dir else {
+        return config;
+    };
+    if config.initialization.game_version.is_none() {
+        config.initialization.game_version = game_paths::installed_version(dir);
+    }
+    config.with_game_data(GameData::load(dir))
+}
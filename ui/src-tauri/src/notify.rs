@@ -0,0 +1,120 @@
+//! OS notifications for finished generations.
+//!
+//! Long runs would otherwise finish silently while the user is in another
+//! app. A notification is shown when a generation completes or fails, unless
+//! the main window has focus; clicking it brings the window back and emits
+//! `show-results` so the frontend can switch to the results view.
+
+use std::time::Duration;
+
+use notify_rust::Notification;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Emitted when a completion notification is clicked.
+pub const SHOW_RESULTS_EVENT: &str = "show-results";
+
+/// Name notifications are attributed to outside Windows.
+const APP_NAME: &str = "CK3 Character History Generator";
+/// Action the notification body or its button invokes.
+const SHOW_ACTION: &str = "default";
+
+/// How a generation ended.
+pub enum Outcome {
+    /// `characters` is unknown if the engine did not report it.
+    Complete {
+        characters: Option<u64>,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+/// Tell the user that a generation finished after `elapsed`.
+pub fn generation_finished(app: &AppHandle, outcome: &Outcome, elapsed: Duration) {
+    if let Some(window) = app.get_webview_window("main") {
+        let visible = window.is_visible().unwrap_or(false);
+        if visible && window.is_focused().unwrap_or(false) {
+            return;
+        }
+    }
+
+    let elapsed = format_duration(elapsed);
+    let (summary, body) = match outcome {
+        Outcome::Complete {
+            characters: Some(characters),
+        } => (
+            "Generation complete",
+            format!("Generated {characters} characters in {elapsed}."),
+        ),
+        Outcome::Complete { characters: None } => {
+            ("Generation complete", format!("Finished in {elapsed}."))
+        }
+        Outcome::Failed { error } => ("Generation failed", format!("After {elapsed}: {error}")),
+    };
+
+    let mut notification = Notification::new();
+    notification
+        .appname(APP_NAME)
+        .summary(summary)
+        .body(&body)
+        .action(SHOW_ACTION, "Show results");
+    // Installed builds are registered under the bundle identifier; dev builds
+    // are not registered at all and fall back to PowerShell's.
+    #[cfg(windows)]
+    if !cfg!(debug_assertions) {
+        notification.app_id(&app.config().identifier);
+    }
+
+    let app = app.clone();
+    // Waiting for a click blocks until the notification goes away.
+    std::thread::spawn(move || match notification.show() {
+        Ok(handle) => wait_for_click(handle, move || show_results(&app)),
+        Err(err) => tracing::warn!("failed to show a notification: {err}"),
+    });
+}
+
+#[cfg(windows)]
+fn wait_for_click(handle: notify_rust::NotificationHandle, on_click: impl FnOnce()) {
+    use notify_rust::NotificationResponse;
+
+    let _ = handle.wait_for_response(|response: &NotificationResponse| match response {
+        NotificationResponse::Default => on_click(),
+        NotificationResponse::Action(action) if action == SHOW_ACTION => on_click(),
+        _ => {}
+    });
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn wait_for_click(handle: notify_rust::NotificationHandle, on_click: impl FnOnce()) {
+    handle.wait_for_action(|action| {
+        if action == SHOW_ACTION {
+            on_click();
+        }
+    });
+}
+
+/// Notification Center only reports clicks to a bundled app's delegate.
+#[cfg(target_os = "macos")]
+fn wait_for_click(_handle: notify_rust::NotificationHandle, _on_click: impl FnOnce()) {}
+
+fn show_results(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+    let _ = app.emit(SHOW_RESULTS_EVENT, ());
+}
+
+/// `1h 02m`, `3m 12s` or `45s`.
+fn format_duration(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds:02}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
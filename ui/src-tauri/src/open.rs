@@ -0,0 +1,117 @@
+//! Opening `.ck3hist` project files from outside the app.
+//!
+//! A project reaches the shell in three ways: as a command-line argument when
+//! the OS launches the app for a double-clicked file, as the arguments of a
+//! second instance that the single-instance plugin forwards to this one, or as
+//! a `ck3gen://open?path=...` deep link. Each is reported to the frontend as an
+//! `open-project` event. A project passed at launch arrives before the
+//! frontend is listening, so it is also kept until the frontend collects it
+//! with [`take_pending_project`].
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+/// Emitted with an [`OpenProject`] when a project is opened from outside.
+pub const OPEN_PROJECT_EVENT: &str = "open-project";
+
+/// Extension of project files, registered with the OS by the installer.
+const PROJECT_EXTENSION: &str = "ck3hist";
+/// URL scheme of deep links, declared in `tauri.conf.json`.
+const DEEP_LINK_SCHEME: &str = "ck3gen";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenProject {
+    pub path: PathBuf,
+}
+
+/// Managed state holding the project passed at launch until the frontend
+/// asks for it.
+#[derive(Default)]
+pub struct PendingProject(Mutex<Option<PathBuf>>);
+
+/// Pick up a project passed at launch and listen for deep links.
+pub fn start(app: &AppHandle) {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let args: Vec<String> = std::env::args().collect();
+    let launched_with = project_from_args(&args, &cwd).or_else(|| {
+        let urls = app.deep_link().get_current().ok().flatten()?;
+        urls.iter().find_map(project_from_url)
+    });
+    *app.state::<PendingProject>().0.lock().unwrap() = launched_with;
+
+    // Installed builds have the scheme registered by the installer; register
+    // it at runtime so links also reach development builds.
+    #[cfg(any(windows, target_os = "linux"))]
+    if cfg!(debug_assertions) {
+        if let Err(err) = app.deep_link().register_all() {
+            tracing::warn!("failed to register the {DEEP_LINK_SCHEME} scheme: {err}");
+        }
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        if let Some(path) = event.urls().iter().find_map(project_from_url) {
+            forward(&handle, path);
+        }
+    });
+}
+
+/// Called by the single-instance plugin with the arguments of a second
+/// instance, which exits right after.
+///
+/// Deep links among the arguments are handled by the deep-link plugin.
+pub fn on_second_instance(app: &AppHandle, args: Vec<String>, cwd: String) {
+    if let Some(path) = project_from_args(&args, Path::new(&cwd)) {
+        forward(app, path);
+    } else if let Some(window) = app.get_webview_window("main") {
+        // Starting the app again should at least bring it back, even from
+        // the tray.
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Hand `path` to the frontend and bring the window to the front.
+pub fn forward(app: &AppHandle, path: PathBuf) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+    let _ = app.emit(OPEN_PROJECT_EVENT, OpenProject { path });
+}
+
+/// The first project file among `args`, resolved against `cwd`.
+///
+/// The first argument is the executable and is skipped.
+fn project_from_args(args: &[String], cwd: &Path) -> Option<PathBuf> {
+    args.iter().skip(1).find_map(|arg| {
+        let path = Path::new(arg);
+        let is_project = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(PROJECT_EXTENSION));
+        is_project.then(|| cwd.join(path))
+    })
+}
+
+/// The project named by a `ck3gen://open?path=<path>` link.
+fn project_from_url(url: &Url) -> Option<PathBuf> {
+    if url.scheme() != DEEP_LINK_SCHEME || url.host_str() != Some("open") {
+        return None;
+    }
+    url.query_pairs()
+        .find(|(key, _)| key == "path")
+        .map(|(_, path)| PathBuf::from(path.into_owned()))
+}
+
+/// Return the project the app was launched with, if any, once.
+#[tauri::command]
+pub fn take_pending_project(pending: State<'_, PendingProject>) -> Option<PathBuf> {
+    pending.0.lock().unwrap().take()
+}
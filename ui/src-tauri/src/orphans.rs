@@ -0,0 +1,127 @@
+//! Detection of engine processes left behind by an earlier session.
+//!
+//! Every sidecar the shell spawns is recorded in a PID file under the app's
+//! local data directory, and the file is removed once the process exits. If
+//! the app crashed, the files survive and point at orphans on the next launch:
+//! orphaned workers are terminated, while for an orphaned primary engine that
+//! still answers its heartbeat the user can choose to keep using it instead.
+
+use std::fs;
+use std::path::PathBuf;
+
+use sidecar_manager::pidfile::{self, EngineRecord, PidFile};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::health;
+use crate::sidecar::SIDECAR_NAME;
+
+/// PID file of the primary engine.
+const ENGINE_PID_FILE: &str = "api_server.pid";
+/// Prefix of the PID files of the parallel workers older versions spawned.
+const WORKER_PID_PREFIX: &str = "worker-";
+
+fn pid_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path().app_local_data_dir().ok()
+}
+
+/// PID file of the primary engine.
+pub fn engine_pid_file(app: &AppHandle) -> Option<PidFile> {
+    pid_dir(app).map(|dir| PidFile::new(dir.join(ENGINE_PID_FILE)))
+}
+
+/// Record a spawned process, logging rather than failing if that is not possible.
+pub fn record(file: Option<PidFile>, record: EngineRecord) {
+    if let Some(file) = file {
+        if let Err(err) = file.write(record) {
+            tracing::warn!("failed to write {}: {err}", file.path().display());
+        }
+    }
+}
+
+/// Forget a process that has exited.
+pub fn forget(file: Option<PidFile>) {
+    if let Some(file) = file {
+        if let Err(err) = file.remove() {
+            tracing::warn!("failed to remove {}: {err}", file.path().display());
+        }
+    }
+}
+
+/// Deal with orphans from an earlier session before the engine is started.
+///
+/// Returns the orphaned primary engine if the user chose to reuse it; in every
+/// other case the orphans are terminated and their PID files removed.
+pub async fn resolve(app: &AppHandle) -> Option<EngineRecord> {
+    clean_up_workers(app);
+
+    let file = engine_pid_file(app)?;
+    let record = file.read();
+    let Some(record) = record.filter(|record| pidfile::is_running(record.pid, SIDECAR_NAME)) else {
+        forget(Some(file));
+        return None;
+    };
+
+    if ask_to_reuse(app, record).await {
+        return Some(record);
+    }
+    terminate(record);
+    forget(Some(file));
+    None
+}
+
+/// Whether the orphan answers its heartbeat and the user wants to keep it.
+async fn ask_to_reuse(app: &AppHandle, record: EngineRecord) -> bool {
+    let client = reqwest::Client::new();
+    let url = format!("http://127.0.0.1:{}/health", record.port);
+    if health::check(&client, &url).await.is_none() {
+        return false;
+    }
+
+    let dialog = app
+        .dialog()
+        .message(format!(
+            "An engine from a previous session is still running (process {}, port {}).\n\n\
+             Reuse it, or terminate it and start a fresh one?",
+            record.pid, record.port
+        ))
+        .title("Engine already running")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Reuse".into(),
+            "Terminate".into(),
+        ));
+    tauri::async_runtime::spawn_blocking(move || dialog.blocking_show())
+        .await
+        .unwrap_or(false)
+}
+
+/// Terminate workers left running by an earlier session.
+fn clean_up_workers(app: &AppHandle) {
+    let Some(entries) = pid_dir(app).and_then(|dir| fs::read_dir(dir).ok()) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if !name.to_string_lossy().starts_with(WORKER_PID_PREFIX) {
+            continue;
+        }
+        let file = PidFile::new(entry.path());
+        if let Some(record) = file.read() {
+            if pidfile::is_running(record.pid, SIDECAR_NAME) {
+                terminate(record);
+            }
+        }
+        forget(Some(file));
+    }
+}
+
+fn terminate(record: EngineRecord) {
+    tracing::info!(
+        "terminating orphaned {SIDECAR_NAME} (process {})",
+        record.pid
+    );
+    if let Err(err) = pidfile::terminate(record.pid) {
+        tracing::warn!("{err}");
+    }
+}
@@ -0,0 +1,167 @@
+//! Access to the generated files.
+//!
+//! After a run the history files and family trees sit in the engine's output
+//! directories, which in the packaged app are buried in an app data folder.
+//! These commands open them in Explorer, Finder or the desktop's file
+//! manager instead of leaving users to hunt for them.
+//!
+//! The shell works out those directories itself, as the engine does from the
+//! options it was launched with, rather than asking it, so that they are
+//! known while the engine is down. In development they are those of the
+//! repository, which run_ui.bat starts the engine in; a packaged sidecar is
+//! always launched with an explicit config and output directory, by default
+//! in the app data folders, where the config bundled with the app is copied
+//! on first use so that it can be edited.
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sidecar_manager::launch::{CHARACTER_DIR, TREE_DIR};
+use sidecar_manager::LaunchOptions;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::launch::EngineLaunch;
+
+/// Folders of the config and name lists, in the repository and among the
+/// resources bundled with the app.
+const CONFIG_DIR: &str = "config";
+const NAME_LISTS_DIR: &str = "name_lists";
+/// Folder of the packaged app's output, in its local app data folder.
+const OUTPUT_DIR: &str = "output";
+/// Folder of the config files the engine falls back on, in its config
+/// directory.
+pub const FALLBACK_CONFIG_DIR: &str = "fallback_config_files";
+
+/// Where an engine reads its config and writes output, as its `/paths`
+/// endpoint reports them.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnginePaths {
+    pub config_dir: PathBuf,
+    pub character_dir: PathBuf,
+    pub tree_dir: PathBuf,
+    pub name_lists_dir: PathBuf,
+}
+
+/// Where the running engine reads its config and writes output.
+pub async fn current_paths(app: &AppHandle) -> Result<EnginePaths, String> {
+    let options = app.state::<EngineLaunch>().running();
+    paths_for(app, &options)
+}
+
+/// Where an engine launched with `options` reads its config and writes
+/// output.
+pub fn paths_for(app: &AppHandle, options: &LaunchOptions) -> Result<EnginePaths, String> {
+    let options = engine_options(app, options)?;
+    let (Some(config_dir), Some(output_dir)) = (options.config_dir, options.output_dir) else {
+        unreachable!("engine_options sets both directories");
+    };
+    Ok(EnginePaths {
+        config_dir,
+        character_dir: output_dir.join(CHARACTER_DIR),
+        tree_dir: output_dir.join(TREE_DIR),
+        name_lists_dir: resource_dir(app)?.join(NAME_LISTS_DIR),
+    })
+}
+
+/// `options` with the config and output directories the engine would
+/// default to set, copying the bundled config to its default directory if
+/// it is not there yet.
+pub fn engine_options(app: &AppHandle, options: &LaunchOptions) -> Result<LaunchOptions, String> {
+    let config_dir = match &options.config_dir {
+        Some(dir) => dir.clone(),
+        None if cfg!(debug_assertions) => resource_dir(app)?.join(CONFIG_DIR),
+        None => {
+            let dir = app
+                .path()
+                .app_data_dir()
+                .map_err(|err| err.to_string())?
+                .join(CONFIG_DIR);
+            if !dir.is_dir() {
+                copy_dir(&resource_dir(app)?.join(CONFIG_DIR), &dir).map_err(|err| {
+                    format!("cannot copy the bundled config to {}: {err}", dir.display())
+                })?;
+            }
+            dir
+        }
+    };
+    let output_dir = match &options.output_dir {
+        Some(dir) => dir.clone(),
+        None if cfg!(debug_assertions) => resource_dir(app)?,
+        None => app
+            .path()
+            .app_local_data_dir()
+            .map_err(|err| err.to_string())?
+            .join(OUTPUT_DIR),
+    };
+    Ok(LaunchOptions {
+        config_dir: Some(config_dir),
+        output_dir: Some(output_dir),
+        ..options.clone()
+    })
+}
+
+/// The folder the config and name lists come with: the repository in
+/// development, the app's resources once packaged.
+pub fn resource_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    if cfg!(debug_assertions) {
+        let repository = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        return Ok(repository.canonicalize().unwrap_or(repository));
+    }
+    app.path().resource_dir().map_err(|err| err.to_string())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Open the directory holding the exported history files.
+#[tauri::command]
+pub async fn open_output_folder(app: AppHandle) -> Result<(), String> {
+    let dir = current_paths(&app).await?.character_dir;
+    if !dir.is_dir() {
+        return Err("nothing has been generated yet".into());
+    }
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|err| err.to_string())
+}
+
+/// Reveal a generated file in the platform file manager, selected.
+///
+/// `file` is a name such as `family_history.txt` or `family_tree_<dynasty>.png`;
+/// it is looked up in the history directory first, then among the family
+/// trees. Paths leading outside those directories are rejected.
+#[tauri::command]
+pub async fn reveal_output_file(app: AppHandle, file: String) -> Result<(), String> {
+    let relative = Path::new(&file);
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(format!("{file} is not a generated file"));
+    }
+
+    let paths = current_paths(&app).await?;
+    let path = [paths.character_dir, paths.tree_dir]
+        .into_iter()
+        .map(|dir| dir.join(relative))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("{file} has not been generated"))?;
+    app.opener()
+        .reveal_item_in_dir(path)
+        .map_err(|err| err.to_string())
+}
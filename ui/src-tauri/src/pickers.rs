@@ -0,0 +1,374 @@
+//! Native file and folder pickers.
+//!
+//! Browser file inputs cannot return real filesystem paths, which the engine
+//! needs for output and mod directories, so the frontend asks the shell to
+//! show a native dialog instead. The last path picked for each purpose is
+//! saved to `recent_paths.json` in the app config directory and used as the
+//! starting directory next time.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, FileDialogBuilder};
+
+use crate::{game_paths, store};
+
+/// File in the app config directory holding the last-used paths.
+const RECENT_PATHS_FILE: &str = "recent_paths.json";
+
+/// Managed state remembering the last path picked for each purpose.
+#[derive(Default)]
+pub struct RecentPaths {
+    /// Loaded from disk on first use.
+    paths: Mutex<Option<HashMap<String, PathBuf>>>,
+}
+
+impl RecentPaths {
+    fn get(&self, app: &AppHandle, purpose: &str) -> Option<PathBuf> {
+        let mut paths = self.paths.lock().unwrap();
+        paths.get_or_insert_with(|| load(app)).get(purpose).cloned()
+    }
+
+    fn remember(&self, app: &AppHandle, purpose: &str, path: &Path) {
+        let mut paths = self.paths.lock().unwrap();
+        let paths = paths.get_or_insert_with(|| load(app));
+        paths.insert(purpose.to_string(), path.to_path_buf());
+        if let Err(err) = save(app, paths) {
+            tracing::warn!("failed to save {RECENT_PATHS_FILE}: {err}");
+        }
+    }
+}
+
+fn load(app: &AppHandle) -> HashMap<String, PathBuf> {
+    store::load(store::config_file(app, RECENT_PATHS_FILE))
+}
+
+fn save(app: &AppHandle, paths: &HashMap<String, PathBuf>) -> std::io::Result<()> {
+    store::save(&store::config_file(app, RECENT_PATHS_FILE)?, paths)
+}
+
+/// What a dialog picks.
+#[derive(Clone, Copy, PartialEq)]
+enum Pick {
+    Folder,
+    File,
+    /// A file to save to, which need not exist yet.
+    NewFile,
+}
+
+/// Show `dialog` off the main thread, starting where the last pick for
+/// `purpose` was made (or at `fallback`), and remember the result if it
+/// passes `validate`.
+async fn pick(
+    app: &AppHandle,
+    purpose: &'static str,
+    fallback: Option<PathBuf>,
+    kind: Pick,
+    dialog: FileDialogBuilder<tauri::Wry>,
+    validate: impl FnOnce(&Path) -> Result<(), String>,
+) -> Result<Option<PathBuf>, String> {
+    let recent = app.state::<RecentPaths>();
+    let start = recent
+        .get(app, purpose)
+        .map(|path| {
+            if kind == Pick::Folder {
+                path
+            } else {
+                parent_or_self(path)
+            }
+        })
+        .or(fallback)
+        .filter(|dir| dir.is_dir());
+    let dialog = match start {
+        Some(dir) => dialog.set_directory(dir),
+        None => dialog,
+    };
+
+    let picked = tauri::async_runtime::spawn_blocking(move || match kind {
+        Pick::Folder => dialog.blocking_pick_folder(),
+        Pick::File => dialog.blocking_pick_file(),
+        Pick::NewFile => dialog.blocking_save_file(),
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked.into_path().map_err(|err| err.to_string())?;
+    validate(&path)?;
+    recent.remember(app, purpose, &path);
+    Ok(Some(path))
+}
+
+fn parent_or_self(path: PathBuf) -> PathBuf {
+    match path.parent() {
+        Some(parent) => parent.to_path_buf(),
+        None => path,
+    }
+}
+
+/// Let the user pick the directory generated files are written to.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_output_dir(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let dialog = app.dialog().file().set_title("Choose the output folder");
+    pick(&app, "output", None, Pick::Folder, dialog, |_| Ok(())).await
+}
+
+/// Let the user pick the Paradox mod folder, the one holding the `.mod`
+/// descriptors the launcher reads.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_mod_dir(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = game_paths::detected_mod_dir(&app);
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Choose the Paradox mod folder");
+    pick(&app, "mod", fallback, Pick::Folder, dialog, |_| Ok(())).await
+}
+
+/// Let the user pick a `.ck3hist` project file.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_project_file(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Open a project")
+        .add_filter("CK3 History Project", &["ck3hist"]);
+    pick(&app, "project", fallback, Pick::File, dialog, |_| Ok(())).await
+}
+
+/// Let the user choose where to save the project as a `.ck3hist` file.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_project_save_file(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Save the project")
+        .set_file_name("project.ck3hist")
+        .add_filter("CK3 History Project", &["ck3hist"]);
+    pick(&app, "project", fallback, Pick::NewFile, dialog, |_| Ok(())).await
+}
+
+/// Let the user choose a character or dynasty history file to import.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_history_file(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Import a history file")
+        .add_filter("Character or Dynasty History", &["txt"]);
+    pick(&app, "history", fallback, Pick::File, dialog, |_| Ok(())).await
+}
+
+/// Let the user choose a saved simulation state to resume.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_state_file(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Resume a simulation")
+        .add_filter("Simulation State", &["json"]);
+    pick(&app, "state", fallback, Pick::File, dialog, |_| Ok(())).await
+}
+
+/// Let the user choose where to save the last native run's state.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_state_save_file(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Save the simulation state")
+        .set_file_name("simulation.json")
+        .add_filter("Simulation State", &["json"]);
+    pick(&app, "state", fallback, Pick::NewFile, dialog, |_| Ok(())).await
+}
+
+/// Let the user choose a project archive to import.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_archive_file(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().download_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Import a project archive")
+        .add_filter("Project Archive", &["zip"]);
+    pick(&app, "archive", fallback, Pick::File, dialog, |_| Ok(())).await
+}
+
+/// Let the user choose where to export the project as an archive.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_archive_save_file(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Export the project")
+        .set_file_name("project.zip")
+        .add_filter("Project Archive", &["zip"]);
+    pick(&app, "archive", fallback, Pick::NewFile, dialog, |_| Ok(())).await
+}
+
+/// Let the user choose where to save a Markdown chronicle.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_chronicle_file(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Export a chronicle")
+        .set_file_name("chronicle.md")
+        .add_filter("Markdown", &["md"]);
+    pick(&app, "chronicle", fallback, Pick::NewFile, dialog, |_| {
+        Ok(())
+    })
+    .await
+}
+
+/// Let the user pick the folder the CSV tables are exported to.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_csv_export_dir(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Choose a folder for the CSV tables");
+    pick(&app, "csv", fallback, Pick::Folder, dialog, |_| Ok(())).await
+}
+
+/// Let the user choose where to save a GEDCOM family tree.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_gedcom_file(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Export a family tree")
+        .set_file_name("family_tree.ged")
+        .add_filter("GEDCOM", &["ged"]);
+    pick(&app, "gedcom", fallback, Pick::NewFile, dialog, |_| Ok(())).await
+}
+
+/// Let the user choose where to save an HTML report.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_report_file(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Export a report")
+        .set_file_name("report.html")
+        .add_filter("HTML", &["html"]);
+    pick(&app, "report", fallback, Pick::NewFile, dialog, |_| Ok(())).await
+}
+
+/// Let the user pick the folder dynasty tree graphs are exported to.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_tree_export_dir(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Choose a folder for the dynasty trees");
+    pick(&app, "trees", fallback, Pick::Folder, dialog, |_| Ok(())).await
+}
+
+/// Let the user pick a folder of history or dynasty files to check the
+/// export's IDs against.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_id_check_dir(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Choose a folder of history or dynasty files");
+    pick(&app, "ids", fallback, Pick::Folder, dialog, |_| Ok(())).await
+}
+
+/// Let the user pick the folder a mod is built in around the last export.
+///
+/// Resolves to `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_mod_build_dir(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let fallback = app.path().document_dir().ok();
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Choose the folder to build the mod in");
+    pick(
+        &app,
+        "mod-build",
+        fallback,
+        Pick::Folder,
+        dialog,
+        |_| Ok(()),
+    )
+    .await
+}
+
+/// Let the user pick the Crusader Kings III installation directory.
+///
+/// Resolves to `None` if the dialog was cancelled, and fails if the chosen
+/// directory does not look like a game installation.
+#[tauri::command]
+pub async fn pick_game_dir(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title("Choose the Crusader Kings III installation folder");
+    pick(
+        &app,
+        "game",
+        game_paths::first_installation(&app),
+        Pick::Folder,
+        dialog,
+        |dir| {
+            if dir.join("game").is_dir() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{} is not a Crusader Kings III installation (no game folder)",
+                    dir.display()
+                ))
+            }
+        },
+    )
+    .await
+}
@@ -0,0 +1,160 @@
+//! Starting the game with the deployed mod enabled.
+//!
+//! The game, started without the Paradox launcher, loads the mods that
+//! `dlc_load.json`, next to the mod folder, lists as enabled; the launcher
+//! rewrites that file from its active playset each time it starts the game.
+//! `play_with_mod` adds the mod `deploy_mod` put in the mod folder to the
+//! list, or makes it the only mod, and starts the game's own executable, so a
+//! run can be tried in game straight after it is generated.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::scaffold::{self, DEFAULT_MOD_NAME};
+use crate::{game_paths, output};
+
+/// The game's list of enabled mods and disabled DLCs, in the folder above
+/// the mod folder.
+const DLC_LOAD_FILE: &str = "dlc_load.json";
+/// The game's executables, relative to the installation, by platform.
+const EXECUTABLES: [&str; 3] = [
+    "binaries/ck3.exe",
+    "binaries/ck3",
+    "binaries/ck3.app/Contents/MacOS/ck3",
+];
+
+/// Enable the deployed mod in `dlc_load.json` and start the game installed
+/// in `game_dir`, or the one found on this machine, returning the mods now
+/// enabled.
+///
+/// The other mods enabled stay so unless `exclusive`. Fails if the mod has
+/// not been deployed to the mod folder `mod_dir`, or the one found, yet.
+#[tauri::command]
+pub async fn play_with_mod(
+    app: AppHandle,
+    game_dir: Option<PathBuf>,
+    mod_dir: Option<PathBuf>,
+    exclusive: bool,
+) -> Result<Vec<String>, String> {
+    let paths = output::current_paths(&app).await?;
+    let game_dir = game_dir
+        .or_else(|| game_paths::first_installation(&app))
+        .ok_or("no Crusader Kings III installation was found; choose one instead")?;
+    let mod_dir = mod_dir
+        .or_else(|| game_paths::detected_mod_dir(&app))
+        .ok_or("no Paradox mod folder was found; choose one instead")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let name = scaffold::mod_settings(&paths, DEFAULT_MOD_NAME)?.name;
+        let entry = format!("{}.mod", scaffold::safe_folder_name(&name));
+        if !mod_dir.join(&entry).is_file() {
+            return Err(format!(
+                "{name} has not been deployed to {} yet",
+                mod_dir.display()
+            ));
+        }
+        let executable = EXECUTABLES
+            .iter()
+            .map(|executable| game_dir.join(executable))
+            .find(|executable| executable.is_file())
+            .ok_or_else(|| {
+                format!(
+                    "{} has no game executable in its binaries folder",
+                    game_dir.display()
+                )
+            })?;
+
+        let dlc_load = mod_dir
+            .parent()
+            .ok_or("the mod folder has no parent folder")?
+            .join(DLC_LOAD_FILE);
+        let enabled = enable(&dlc_load, &format!("mod/{entry}"), exclusive)
+            .map_err(|err| format!("cannot update {}: {err}", dlc_load.display()))?;
+        start(&executable)?;
+        Ok(enabled)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Add the mod `entry`, such as `mod/Generated histories.mod`, to the mods
+/// `dlc_load` enables, if not there already, and return them all. The
+/// others are dropped if `exclusive`; whatever else the file holds is kept.
+fn enable(dlc_load: &Path, entry: &str, exclusive: bool) -> io::Result<Vec<String>> {
+    let mut load = match fs::read_to_string(dlc_load) {
+        Ok(text) => serde_json::from_str(&text).map_err(io::Error::other)?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => json!({ "disabled_dlcs": [] }),
+        Err(err) => return Err(err),
+    };
+    let Value::Object(fields) = &mut load else {
+        return Err(io::Error::other("it does not hold a JSON object"));
+    };
+    let mut enabled: Vec<String> = match fields.get("enabled_mods") {
+        Some(Value::Array(mods)) if !exclusive => mods
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    if !enabled.iter().any(|other| other == entry) {
+        enabled.push(entry.to_string());
+    }
+    fields.insert("enabled_mods".into(), json!(enabled));
+    let text = serde_json::to_string(&load).map_err(io::Error::other)?;
+    fs::write(dlc_load, text)?;
+    Ok(enabled)
+}
+
+/// The folders of the mods `dlc_load.json` enables beside the mod folder
+/// `mod_dir`, as their launcher entries give them, that exist.
+pub fn enabled_mod_dirs(mod_dir: &Path) -> Vec<PathBuf> {
+    let Some(paradox) = mod_dir.parent() else {
+        return Vec::new();
+    };
+    let load: Value = fs::read_to_string(paradox.join(DLC_LOAD_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    let Some(Value::Array(entries)) = load.get("enabled_mods") else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(Value::as_str)
+        .filter_map(|entry| fs::read_to_string(paradox.join(entry)).ok())
+        .filter_map(|descriptor| {
+            // Only a `path` relative to the folder above the mod folder is
+            // relative, such as `mod/ugc_123`.
+            let path = descriptor.lines().find_map(|line| {
+                let value = line.trim().strip_prefix("path")?.trim_start();
+                Some(
+                    value
+                        .strip_prefix('=')?
+                        .trim()
+                        .trim_matches('"')
+                        .to_string(),
+                )
+            })?;
+            Some(paradox.join(path))
+        })
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// Start the game's `executable` from its own folder, without waiting for
+/// it.
+fn start(executable: &Path) -> Result<(), String> {
+    let mut command = Command::new(executable);
+    if let Some(dir) = executable.parent() {
+        command.current_dir(dir);
+    }
+    command
+        .spawn()
+        .map(drop)
+        .map_err(|err| format!("failed to start {}: {err}", executable.display()))
+}
@@ -0,0 +1,42 @@
+//! Quick previews of a dynasty.
+//!
+//! `preview_dynasty` simulates the first generations of one dynasty natively,
+//! in a fraction of a second, from the initialization config being edited,
+//! which the frontend sends along unsaved, and the project's life stages and
+//! traits. The frontend asks again after every change, so the mini tree
+//! follows the settings before a whole run is started.
+
+use ck3_sim::{NameLists, Preview, SimConfig, Simulation};
+use tauri::AppHandle;
+
+use crate::output;
+
+/// The first `generations` generations of the dynasty `dynasty_id` of the
+/// initialization config `config`, simulated with `seed`.
+#[tauri::command]
+pub async fn preview_dynasty(
+    app: AppHandle,
+    config: serde_json::Value,
+    dynasty_id: String,
+    generations: u32,
+    seed: Option<u64>,
+) -> Result<Preview, String> {
+    let paths = output::current_paths(&app).await?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let initialization =
+            ck3_config::parse_initialization(&config.to_string()).map_err(|errors| {
+                errors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })?;
+        let config = SimConfig::load_with(&paths.config_dir, initialization)
+            .map_err(|err| err.to_string())?;
+        let names = NameLists::new(paths.name_lists_dir);
+        Simulation::preview(&config, names, &dynasty_id, generations, seed)
+            .ok_or_else(|| format!("there is no dynasty {dynasty_id}"))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
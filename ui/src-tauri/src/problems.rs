@@ -0,0 +1,141 @@
+//! The problems panel.
+//!
+//! `lint_output` checks the last export the way the game's error log would,
+//! so broken references and malformed dates show up before the game is
+//! started. The game installation or mod the history is for can be given,
+//! to also flag the traits, cultures and faiths it does not define, and so
+//! can other history and dynasty files defining characters, dynasties and
+//! houses the history may refer to.
+//! `check_marriages` goes through its marriages by the rules of the
+//! project's config, whichever engine wrote it, `check_traits` its
+//! characters' traits by the config's and the game's, and `check_survivors` finds
+//! the characters alive at the end of the period past their lifespan cap,
+//! whom `repair_survivors` gives deaths.
+//!
+//! Once a generation completes, its output is linted without being asked and
+//! the problems are pushed to the frontend as an `output-problems` event.
+
+use std::path::{Path, PathBuf};
+
+use ck3_sim::{GameData, MarriageProblem, Problem, SimConfig, SurvivorReport, HISTORY_FILE};
+use tauri::{AppHandle, Emitter};
+
+use crate::output;
+
+/// The problems of the generated history and definitions, in file and line
+/// order.
+#[tauri::command]
+pub async fn lint_output(
+    app: AppHandle,
+    game_dir: Option<PathBuf>,
+    others: Vec<PathBuf>,
+) -> Result<Vec<Problem>, String> {
+    let dir = output::current_paths(&app).await?.character_dir;
+    if !dir.join(HISTORY_FILE).is_file() {
+        return Err("nothing has been generated yet".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let game = game_dir.map(|dir| GameData::load(&dir));
+        ck3_sim::lint_output(&dir, game.as_ref(), &others)
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Emitted with the [`Problem`]s found once a generation has completed.
+pub const OUTPUT_PROBLEMS_EVENT: &str = "output-problems";
+
+/// Lint the output of the generation that has just completed in the
+/// background and push what is found, as `lint_output` finds it without a
+/// game or other files to check against.
+pub fn check_finished(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let dir = match output::current_paths(&app).await {
+            Ok(paths) => paths.character_dir,
+            Err(err) => {
+                tracing::warn!("not linting the output: {err}");
+                return;
+            }
+        };
+        let linted =
+            tauri::async_runtime::spawn_blocking(move || ck3_sim::lint_output(&dir, None, &[]))
+                .await;
+        match linted {
+            Ok(problems) => {
+                let _ = app.emit(OUTPUT_PROBLEMS_EVENT, problems);
+            }
+            Err(err) => tracing::warn!("linting the output failed: {err}"),
+        }
+    });
+}
+
+/// The marriages of the generated history that break the rules of the
+/// config's `marriageChecks` section, in the order they happen.
+#[tauri::command]
+pub async fn check_marriages(app: AppHandle) -> Result<Vec<MarriageProblem>, String> {
+    let paths = output::current_paths(&app).await?;
+    if !paths.character_dir.join(HISTORY_FILE).is_file() {
+        return Err("nothing has been generated yet".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let config = SimConfig::load(&paths.config_dir).map_err(|err| err.to_string())?;
+        let people = ck3_sim::read_people(&paths.character_dir).map_err(|err| err.to_string())?;
+        Ok(ck3_sim::check_marriages(&people, &config.initialization))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// The traits of the generated history's characters that cannot go with
+/// others they hold, by the rules of the config and of the game in
+/// `game_dir` if one is given, in line order.
+#[tauri::command]
+pub async fn check_traits(
+    app: AppHandle,
+    game_dir: Option<PathBuf>,
+) -> Result<Vec<Problem>, String> {
+    let paths = output::current_paths(&app).await?;
+    if !paths.character_dir.join(HISTORY_FILE).is_file() {
+        return Err("nothing has been generated yet".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut config = SimConfig::load(&paths.config_dir).map_err(|err| err.to_string())?;
+        if let Some(dir) = game_dir {
+            config = config.with_game_data(GameData::load(&dir));
+        }
+        ck3_sim::check_traits(&paths.character_dir, &config).map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// The characters of the generated history alive at the end of the period
+/// past their lifespan cap.
+#[tauri::command]
+pub async fn check_survivors(app: AppHandle) -> Result<SurvivorReport, String> {
+    survivors(app, ck3_sim::check_survivors).await
+}
+
+/// Give the survivors `check_survivors` finds their new deaths in the
+/// history file, returning what was fixed.
+#[tauri::command]
+pub async fn repair_survivors(app: AppHandle) -> Result<SurvivorReport, String> {
+    survivors(app, ck3_sim::repair_survivors).await
+}
+
+async fn survivors(
+    app: AppHandle,
+    run: fn(&Path, &SimConfig) -> Result<SurvivorReport, ck3_sim::SimError>,
+) -> Result<SurvivorReport, String> {
+    let paths = output::current_paths(&app).await?;
+    if !paths.character_dir.join(HISTORY_FILE).is_file() {
+        return Err("nothing has been generated yet".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let config = SimConfig::load(&paths.config_dir).map_err(|err| err.to_string())?;
+        run(&paths.character_dir, &config).map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
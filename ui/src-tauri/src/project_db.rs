@@ -0,0 +1,206 @@
+//! The project database.
+//!
+//! Every finished run, native or Python, is recorded in `project.db` in the
+//! project's config directory, with its characters, marriages and dynasties,
+//! by the `project-store` crate. The commands here search a run's characters,
+//! give the statistics of its dynasties, compare two runs and keep the
+//! project's settings, without reading a history file again. A run is the
+//! latest one unless the UI names another.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use project_store::{
+    CharacterPage, CharacterQuery, DynastyStats, Facets, Run, RunDiff, Store, StoreError,
+    STORE_FILE,
+};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::output;
+
+/// Managed state holding the database of the project last used, and its
+/// path.
+#[derive(Default)]
+pub struct ProjectDb(Mutex<Option<(PathBuf, Store)>>);
+
+/// Run `f` on the database of the project in `config_dir`, opening it first
+/// if the last one used was another project's.
+fn with_store<T>(
+    app: &AppHandle,
+    config_dir: &Path,
+    f: impl FnOnce(&mut Store) -> Result<T, StoreError>,
+) -> Result<T, String> {
+    let path = config_dir.join(STORE_FILE);
+    let db = app.state::<ProjectDb>();
+    let mut db = db.0.lock().unwrap();
+    if db.as_ref().is_none_or(|(open, _)| *open != path) {
+        let store = Store::open(&path).map_err(|err| err.to_string())?;
+        *db = Some((path, store));
+    }
+    let (_, store) = db.as_mut().expect("the store was just opened");
+    f(store).map_err(|err| err.to_string())
+}
+
+/// Run `f` on the current project's database, off the async runtime.
+async fn query<T: Send + 'static>(
+    app: AppHandle,
+    f: impl FnOnce(&mut Store) -> Result<T, StoreError> + Send + 'static,
+) -> Result<T, String> {
+    let config_dir = output::current_paths(&app).await?.config_dir;
+    tauri::async_runtime::spawn_blocking(move || with_store(&app, &config_dir, f))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+/// Record the run `engine` just wrote to `character_dir` in the database of
+/// the project in `config_dir`.
+///
+/// Blocks; a failure is logged rather than failing the run, whose files are
+/// written all the same.
+pub fn record(app: &AppHandle, config_dir: &Path, character_dir: &Path, engine: &str) {
+    if let Err(err) = with_store(app, config_dir, |store| {
+        store.record_run(character_dir, engine)
+    }) {
+        tracing::warn!("failed to record the run in {STORE_FILE}: {err}");
+    }
+}
+
+/// Record the run the primary engine just finished, in the background.
+pub fn record_finished(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match output::current_paths(&app).await {
+            Ok(paths) => {
+                let _ = tauri::async_runtime::spawn_blocking(move || {
+                    record(&app, &paths.config_dir, &paths.character_dir, "python")
+                })
+                .await;
+            }
+            Err(err) => tracing::warn!("failed to record the run in {STORE_FILE}: {err}"),
+        }
+    });
+}
+
+/// The run `run`, or the latest one; none is 0, which holds nothing.
+fn run_id(store: &Store, run: Option<i64>) -> Result<i64, StoreError> {
+    match run {
+        Some(run) => Ok(run),
+        None => Ok(store.latest_run()?.map_or(0, |run| run.id)),
+    }
+}
+
+/// List the project's recorded runs, the latest first.
+#[tauri::command]
+pub async fn list_runs(app: AppHandle) -> Result<Vec<Run>, String> {
+    query(app, |store| store.runs()).await
+}
+
+/// Forget the recorded run `run`.
+#[tauri::command]
+pub async fn delete_run(app: AppHandle, run: i64) -> Result<(), String> {
+    query(app, move |store| store.delete_run(run)).await
+}
+
+/// Search the characters of run `run`, or of the latest run.
+#[tauri::command]
+pub async fn search_characters(
+    app: AppHandle,
+    run: Option<i64>,
+    query: CharacterQuery,
+) -> Result<CharacterPage, String> {
+    self::query(app, move |store| {
+        let run = run_id(store, run)?;
+        store.search_characters(run, &query)
+    })
+    .await
+}
+
+/// The cultures and religions the characters of run `run`, or of the latest
+/// run, have.
+#[tauri::command]
+pub async fn character_facets(app: AppHandle, run: Option<i64>) -> Result<Facets, String> {
+    query(app, move |store| {
+        let run = run_id(store, run)?;
+        store.facets(run)
+    })
+    .await
+}
+
+/// The dynasties of run `run`, or of the latest run, the largest first.
+#[tauri::command]
+pub async fn dynasty_stats(app: AppHandle, run: Option<i64>) -> Result<Vec<DynastyStats>, String> {
+    query(app, move |store| {
+        let run = run_id(store, run)?;
+        store.dynasty_stats(run)
+    })
+    .await
+}
+
+/// Set the field `field` of the character `id` of the latest run of the
+/// project in `config_dir`, whose history was edited by hand. Blocks.
+pub fn edit_character(
+    app: &AppHandle,
+    config_dir: &Path,
+    id: &str,
+    field: &str,
+    value: &str,
+) -> Result<(), String> {
+    with_store(app, config_dir, |store| {
+        let run = run_id(store, None)?;
+        store.edit_character(run, id, field, value)
+    })
+}
+
+/// What changed from run `before` to run `after`.
+#[tauri::command]
+pub async fn compare_runs(app: AppHandle, before: i64, after: i64) -> Result<RunDiff, String> {
+    query(app, move |store| store.compare_runs(before, after)).await
+}
+
+/// The project setting `key`, or null if it is unset.
+#[tauri::command]
+pub async fn get_project_setting(app: AppHandle, key: String) -> Result<Option<Value>, String> {
+    query(app, move |store| store.setting(&key)).await
+}
+
+/// Every setting of the project in `config_dir`, by key. Blocks.
+pub fn settings(app: &AppHandle, config_dir: &Path) -> Result<BTreeMap<String, Value>, String> {
+    with_store(app, config_dir, |store| store.settings())
+}
+
+/// The setting `key` of the project in `config_dir`, if it is set. Blocks.
+pub fn setting(app: &AppHandle, config_dir: &Path, key: &str) -> Result<Option<Value>, String> {
+    with_store(app, config_dir, |store| store.setting(key))
+}
+
+/// Set the setting `key` of the project in `config_dir`. Blocks.
+pub fn set_setting(
+    app: &AppHandle,
+    config_dir: &Path,
+    key: &str,
+    value: &Value,
+) -> Result<(), String> {
+    with_store(app, config_dir, |store| store.set_setting(key, value))
+}
+
+/// Set the settings `settings` of the project in `config_dir`, keeping the
+/// others. Blocks.
+pub fn set_settings(
+    app: &AppHandle,
+    config_dir: &Path,
+    settings: &BTreeMap<String, Value>,
+) -> Result<(), String> {
+    with_store(app, config_dir, |store| {
+        settings
+            .iter()
+            .try_for_each(|(key, value)| store.set_setting(key, value))
+    })
+}
+
+/// Set the project setting `key`, or unset it if `value` is null.
+#[tauri::command]
+pub async fn set_project_setting(app: AppHandle, key: String, value: Value) -> Result<(), String> {
+    query(app, move |store| store.set_setting(&key, &value)).await
+}
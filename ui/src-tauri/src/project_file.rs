@@ -0,0 +1,144 @@
+//! Saving and opening `.ck3hist` project files.
+//!
+//! `save_project` bundles the config files of the engine's config directory,
+//! the settings kept in the project database, the seed runs are started
+//! with, the seed of the last history and the output folder into a project
+//! file, in the format `ck3-config` defines. `open_project` reads a file of
+//! any version of that format up to the current one, migrating it, and puts
+//! it in place: its config files replace those in the config directory, its
+//! settings are set in the project database, and its seed and output folder
+//! become the launch options. Nothing is put in place unless the whole file
+//! is valid, and problems are reported like those of a config file.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ck3_config::{ConfigError, Project, ProjectConfig};
+use ck3_sim::config::{LIFE_STAGES_FILE, SKILLS_AND_TRAITS_FILE};
+use serde::Serialize;
+use serde_json::Value;
+use sidecar_manager::LaunchOptions;
+use tauri::{AppHandle, Manager};
+
+use crate::config::{document_error, INITIALIZATION_FILE};
+use crate::launch::{self, EngineLaunch};
+use crate::output;
+use crate::project_db;
+use crate::undo;
+
+/// How an opened project was put in place.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenedProject {
+    /// Whether the seed or output folder changed, which the engine only
+    /// picks up once restarted.
+    pub restart_needed: bool,
+}
+
+/// Write the current project to the project file at `path`.
+#[tauri::command]
+pub async fn save_project(app: AppHandle, path: PathBuf) -> Result<(), Vec<ConfigError>> {
+    let project = current_project(&app).await?;
+    fs::write(&path, project.to_json())
+        .map_err(|err| document_error(format!("cannot write {}: {err}", path.display())))
+}
+
+/// The project of the engine's config directory and the launch options.
+pub async fn current_project(app: &AppHandle) -> Result<Project, Vec<ConfigError>> {
+    let paths = output::current_paths(app).await.map_err(document_error)?;
+    let options = app.state::<EngineLaunch>().options();
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let dir = &paths.config_dir;
+        let initialization = read_json(&dir.join(INITIALIZATION_FILE))?.ok_or_else(|| {
+            document_error(format!("{} holds no {INITIALIZATION_FILE}", dir.display()))
+        })?;
+        let mut project = Project::new(ProjectConfig {
+            initialization,
+            life_stages: read_json(&dir.join(LIFE_STAGES_FILE))?,
+            skills_and_traits: read_json(&dir.join(SKILLS_AND_TRAITS_FILE))?,
+        });
+        project.settings = project_db::settings(&app, dir).map_err(document_error)?;
+        project.seed = options.seed;
+        project.last_seed = launch::last_seed(&paths.character_dir).map_err(document_error)?;
+        project.output.output_dir = options.output_dir;
+        Ok(project)
+    })
+    .await
+    .map_err(document_error)?
+}
+
+/// Read the project file at `path` and put it in place.
+#[tauri::command]
+pub async fn open_project(
+    app: AppHandle,
+    path: PathBuf,
+) -> Result<OpenedProject, Vec<ConfigError>> {
+    let text = fs::read_to_string(&path)
+        .map_err(|err| document_error(format!("cannot read {}: {err}", path.display())))?;
+    let project = ck3_config::parse_project(&text)?;
+    put_in_place(&app, project).await
+}
+
+/// Put `project` in place of the current one.
+pub async fn put_in_place(
+    app: &AppHandle,
+    project: Project,
+) -> Result<OpenedProject, Vec<ConfigError>> {
+    let paths = output::current_paths(app).await.map_err(document_error)?;
+
+    let dir = &paths.config_dir;
+    write_json(
+        &dir.join(INITIALIZATION_FILE),
+        &project.config.initialization,
+    )?;
+    if let Some(life_stages) = &project.config.life_stages {
+        write_json(&dir.join(LIFE_STAGES_FILE), life_stages)?;
+    }
+    if let Some(skills_and_traits) = &project.config.skills_and_traits {
+        write_json(&dir.join(SKILLS_AND_TRAITS_FILE), skills_and_traits)?;
+    }
+    let settings_app = app.clone();
+    let config_dir = dir.clone();
+    let settings = project.settings;
+    tauri::async_runtime::spawn_blocking(move || {
+        project_db::set_settings(&settings_app, &config_dir, &settings)
+    })
+    .await
+    .map_err(document_error)?
+    .map_err(document_error)?;
+    // The project's settings hold its own undo history.
+    undo::forget(app);
+
+    let current = app.state::<EngineLaunch>().options();
+    let options = LaunchOptions {
+        seed: project.seed,
+        output_dir: project.output.output_dir,
+        ..current.clone()
+    };
+    let restart_needed = options != current;
+    if restart_needed {
+        launch::set_launch_options(app.clone(), app.state(), options).map_err(document_error)?;
+    }
+    Ok(OpenedProject { restart_needed })
+}
+
+/// The JSON document in the file at `path`, or none if there is no file.
+fn read_json(path: &Path) -> Result<Option<Value>, Vec<ConfigError>> {
+    match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|err| document_error(format!("{} is not JSON: {err}", path.display()))),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(document_error(format!(
+            "cannot read {}: {err}",
+            path.display()
+        ))),
+    }
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<(), Vec<ConfigError>> {
+    let text = serde_json::to_string_pretty(value).map_err(document_error)?;
+    fs::write(path, text)
+        .map_err(|err| document_error(format!("cannot write {}: {err}", path.display())))
+}
@@ -0,0 +1,137 @@
+//! Recently opened projects.
+//!
+//! Every project or config file the UI opens is recorded with the time it was
+//! last opened in `recent_projects.json` in the app data directory, so the UI
+//! can offer a start page. Pinned projects are listed first and are never
+//! dropped; the rest are capped at [`MAX_UNPINNED`], oldest first.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::store;
+
+/// File in the app data directory holding the list.
+const RECENT_PROJECTS_FILE: &str = "recent_projects.json";
+/// Unpinned projects kept in the list.
+const MAX_UNPINNED: usize = 20;
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentProject {
+    pub path: PathBuf,
+    /// Display name, the file or folder name of `path`.
+    pub name: String,
+    /// When the project was last opened, in milliseconds since the Unix epoch.
+    pub last_opened: u64,
+    pub pinned: bool,
+}
+
+/// Managed state holding the list, loaded from disk on first use.
+#[derive(Default)]
+pub struct RecentProjects {
+    projects: Mutex<Option<Vec<RecentProject>>>,
+}
+
+impl RecentProjects {
+    fn list(&self, app: &AppHandle) -> Vec<RecentProject> {
+        let mut projects = self.projects.lock().unwrap();
+        projects.get_or_insert_with(|| load(app)).clone()
+    }
+
+    /// Run `f` on the list, then sort, trim and save it, returning the new list.
+    fn update(
+        &self,
+        app: &AppHandle,
+        f: impl FnOnce(&mut Vec<RecentProject>) -> Result<(), String>,
+    ) -> Result<Vec<RecentProject>, String> {
+        let mut projects = self.projects.lock().unwrap();
+        let projects = projects.get_or_insert_with(|| load(app));
+        f(projects)?;
+
+        projects.sort_by(|a, b| {
+            b.pinned
+                .cmp(&a.pinned)
+                .then(b.last_opened.cmp(&a.last_opened))
+        });
+        let mut unpinned = 0;
+        projects.retain(|project| {
+            unpinned += usize::from(!project.pinned);
+            project.pinned || unpinned <= MAX_UNPINNED
+        });
+
+        store::data_file(app, RECENT_PROJECTS_FILE)
+            .and_then(|path| store::save(&path, projects))
+            .map_err(|err| err.to_string())?;
+        Ok(projects.clone())
+    }
+}
+
+/// Read the saved list; it is kept sorted whenever it is written.
+fn load(app: &AppHandle) -> Vec<RecentProject> {
+    store::load(store::data_file(app, RECENT_PROJECTS_FILE))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// Return the recent projects, pinned ones first, most recently opened first.
+#[tauri::command]
+pub fn get_recent_projects(
+    app: AppHandle,
+    recent: State<'_, RecentProjects>,
+) -> Vec<RecentProject> {
+    recent.list(&app)
+}
+
+/// Record that the project at `path` was opened, returning the new list.
+#[tauri::command]
+pub fn add_recent_project(
+    app: AppHandle,
+    recent: State<'_, RecentProjects>,
+    path: PathBuf,
+) -> Result<Vec<RecentProject>, String> {
+    if !path.exists() {
+        return Err(format!("{} does not exist", path.display()));
+    }
+    recent.update(&app, |projects| {
+        let last_opened = now_millis();
+        match projects.iter_mut().find(|project| project.path == path) {
+            Some(project) => project.last_opened = last_opened,
+            None => projects.push(RecentProject {
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.display().to_string()),
+                path,
+                last_opened,
+                pinned: false,
+            }),
+        }
+        Ok(())
+    })
+}
+
+/// Pin or unpin a recent project, returning the new list.
+#[tauri::command]
+pub fn pin_project(
+    app: AppHandle,
+    recent: State<'_, RecentProjects>,
+    path: PathBuf,
+    pinned: bool,
+) -> Result<Vec<RecentProject>, String> {
+    recent.update(&app, |projects| {
+        let project = projects
+            .iter_mut()
+            .find(|project| project.path == path)
+            .ok_or_else(|| format!("{} is not a recent project", path.display()))?;
+        project.pinned = pinned;
+        Ok(())
+    })
+}
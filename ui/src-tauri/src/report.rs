@@ -0,0 +1,30 @@
+//! Exporting a generation as an HTML report.
+//!
+//! `export_report` writes the last export, whichever engine generated it, as
+//! a single HTML file with its statistics, a searchable list of its
+//! characters and the tree of each dynasty, which opens in any browser, so
+//! that the results can be shared with people who do not have the app.
+
+use std::fs;
+use std::path::PathBuf;
+
+use ck3_sim::HISTORY_FILE;
+use tauri::AppHandle;
+
+use crate::output;
+
+/// Write the last export's report to the HTML file `path`, replacing it if
+/// it exists.
+#[tauri::command]
+pub async fn export_report(app: AppHandle, path: PathBuf) -> Result<(), String> {
+    let dir = output::current_paths(&app).await?.character_dir;
+    if !dir.join(HISTORY_FILE).is_file() {
+        return Err("nothing has been generated yet".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let report = ck3_sim::output_report(&dir).map_err(|err| err.to_string())?;
+        fs::write(&path, report).map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
@@ -0,0 +1,336 @@
+//! Packaging the generated files as a mod.
+//!
+//! `scaffold_mod` lays a mod out around the last export: the history files
+//! go where the game looks for them, under `history` and `common`, with the
+//! localisation in `localization`. The mod is described both by a
+//! `descriptor.mod`, with a launcher entry `<folder>.mod` next to the mod
+//! folder pointing at it, and by the `.metadata/metadata.json` newer
+//! launchers read. Name, version and tags come from the project's
+//! `modSettings`; a project without them gets a mod named after its folder.
+//! A placeholder thumbnail is added unless the mod already has one.
+//!
+//! `deploy_mod` does the same straight into the Paradox mod folder, in a
+//! folder named after the mod, after clearing out the files of the last
+//! deploy so that none of a dynasty dropped since lingers, or after moving
+//! them aside to `<folder>.backup`, which the launcher does not list.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ck3_config::{InitializationConfig, ModSettings};
+use serde::Serialize;
+use serde_json::json;
+use tauri::AppHandle;
+
+use crate::config::INITIALIZATION_FILE;
+use crate::game_paths;
+use crate::output::{self, EnginePaths};
+
+/// The generated files, relative to the history directory, by the mod
+/// directory they belong in.
+const MOD_FILES: [(&str, &str); 5] = [
+    ("family_history.txt", "history/characters"),
+    ("dynasty_definitions.txt", "common/dynasties"),
+    ("dynasty_houses.txt", "common/dynasty_houses"),
+    ("dna_data.txt", "common/dna_data"),
+    ("title_history.txt", "history/titles"),
+];
+/// Per-dynasty title histories of the configured titles, which replace
+/// `title_history.txt` and its placeholder titles when the engine wrote them.
+const TITLES_DIR: &str = "history/titles";
+const LOCALIZATION_DIR: &str = "localization";
+const DESCRIPTOR_FILE: &str = "descriptor.mod";
+const METADATA_FILE: &str = ".metadata/metadata.json";
+pub const THUMBNAIL_FILE: &str = "thumbnail.png";
+/// The app icon, standing in for a thumbnail of the mod's own.
+const THUMBNAIL: &[u8] = include_bytes!("../icons/128x128@2x.png");
+/// Name of the mod of a project without `modSettings` deployed.
+pub const DEFAULT_MOD_NAME: &str = "Generated histories";
+/// Extension of the folder the last deploy is moved to.
+const BACKUP_EXTENSION: &str = "backup";
+
+/// What `deploy_mod` did.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deployment {
+    /// The mod's folder in the mod folder.
+    pub dir: PathBuf,
+    pub written: Vec<PathBuf>,
+    /// Where the last deploy was moved to, if it was backed up.
+    pub backup: Option<PathBuf>,
+}
+
+/// Build a mod in `dir` from the last export, creating the directory if
+/// needed, and return the files written.
+///
+/// Files from an earlier scaffold are overwritten, except the thumbnail.
+#[tauri::command]
+pub async fn scaffold_mod(app: AppHandle, dir: PathBuf) -> Result<Vec<PathBuf>, String> {
+    let paths = output::current_paths(&app).await?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let settings = mod_settings(&paths, &folder_name(&dir))?;
+        scaffold(&paths.character_dir, &dir, &settings).map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Build the mod from the last export in its own folder of the Paradox mod
+/// folder `mod_dir`, or the one found on this machine, with its launcher
+/// entry beside it, and return what was done.
+///
+/// The files of an earlier deploy are moved to `<folder>.backup` if
+/// `backup`, replacing an older backup, and deleted otherwise; the
+/// thumbnail is kept either way.
+#[tauri::command]
+pub async fn deploy_mod(
+    app: AppHandle,
+    mod_dir: Option<PathBuf>,
+    backup: bool,
+) -> Result<Deployment, String> {
+    let paths = output::current_paths(&app).await?;
+    let mod_dir = match mod_dir {
+        Some(dir) => dir,
+        None => game_paths::detected_mod_dir(&app)
+            .ok_or("no Paradox mod folder was found; choose one instead")?,
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let settings = mod_settings(&paths, DEFAULT_MOD_NAME)?;
+        let dir = mod_dir.join(safe_folder_name(&settings.name));
+        let backup = if backup {
+            back_up(&dir).map_err(|err| format!("cannot back up {}: {err}", dir.display()))?
+        } else {
+            clear(&dir).map_err(|err| format!("cannot clear {}: {err}", dir.display()))?;
+            None
+        };
+        fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+        let written =
+            scaffold(&paths.character_dir, &dir, &settings).map_err(|err| err.to_string())?;
+        Ok(Deployment {
+            dir,
+            written,
+            backup,
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// The `modSettings` of the config in `paths`, or those of a mod named
+/// `name`, claiming the config's `gameVersion` for the launcher if they
+/// claim no version of their own. Fails if nothing has been generated yet.
+pub fn mod_settings(paths: &EnginePaths, name: &str) -> Result<ModSettings, String> {
+    if !paths.character_dir.join(MOD_FILES[0].0).is_file() {
+        return Err("nothing has been generated yet".to_string());
+    }
+    let text = fs::read_to_string(paths.config_dir.join(INITIALIZATION_FILE))
+        .map_err(|err| err.to_string())?;
+    let config = ck3_config::parse_initialization(&text).map_err(|errors| {
+        let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        errors.join("\n")
+    })?;
+    Ok(settings_of(&config, name))
+}
+
+/// The `modSettings` of `config`, or those of a mod named `name`, claiming
+/// the config's `gameVersion` for the launcher if they claim no version of
+/// their own.
+pub fn settings_of(config: &InitializationConfig, name: &str) -> ModSettings {
+    let mut settings = config
+        .mod_settings
+        .clone()
+        .unwrap_or_else(|| ModSettings::new(name));
+    if let Some(version) = config.game_version {
+        if settings.supported_version == ModSettings::new(name).supported_version {
+            settings.supported_version = version.supported_version();
+        }
+    }
+    settings
+}
+
+/// Move the mod in `dir`, if there is one, to `<dir>.backup`, keeping a copy
+/// of its thumbnail in `dir`, and return where it went.
+fn back_up(dir: &Path) -> io::Result<Option<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+    let backup = dir.with_file_name(format!("{}.{BACKUP_EXTENSION}", folder_name(dir)));
+    match fs::remove_dir_all(&backup) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    fs::rename(dir, &backup)?;
+    let thumbnail = backup.join(THUMBNAIL_FILE);
+    if thumbnail.is_file() {
+        fs::create_dir_all(dir)?;
+        fs::copy(&thumbnail, dir.join(THUMBNAIL_FILE))?;
+    }
+    Ok(Some(backup))
+}
+
+/// Delete the folders of `dir` a scaffold writes files to, so that only
+/// those of the next one are left.
+fn clear(dir: &Path) -> io::Result<()> {
+    let folders = MOD_FILES
+        .iter()
+        .map(|(_, target)| *target)
+        .chain([TITLES_DIR, LOCALIZATION_DIR]);
+    for folder in folders {
+        match fs::remove_dir_all(dir.join(folder)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+pub fn scaffold(history: &Path, dir: &Path, settings: &ModSettings) -> io::Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    let per_dynasty_titles = history.join(TITLES_DIR).is_dir();
+    for (file, target) in MOD_FILES {
+        let target = dir.join(target);
+        fs::create_dir_all(&target)?;
+        if file == "title_history.txt" && per_dynasty_titles {
+            continue;
+        }
+        let source = history.join(file);
+        if source.is_file() {
+            fs::copy(&source, target.join(file))?;
+            written.push(target.join(file));
+        }
+    }
+    if per_dynasty_titles {
+        copy_tree(
+            &history.join(TITLES_DIR),
+            &dir.join(TITLES_DIR),
+            &mut written,
+        )?;
+    }
+    copy_tree(
+        &history.join(LOCALIZATION_DIR),
+        &dir.join(LOCALIZATION_DIR),
+        &mut written,
+    )?;
+
+    fs::write(dir.join(DESCRIPTOR_FILE), descriptor(settings, None))?;
+    written.push(dir.join(DESCRIPTOR_FILE));
+    // The launcher finds mods by the `.mod` files beside their folders.
+    let dir = dir.canonicalize()?;
+    if let Some(parent) = dir.parent() {
+        let entry = parent.join(format!("{}.mod", folder_name(&dir)));
+        fs::write(&entry, descriptor(settings, Some(&dir)))?;
+        written.push(entry);
+    }
+
+    let metadata_path = dir.join(METADATA_FILE);
+    fs::create_dir_all(
+        metadata_path
+            .parent()
+            .expect("the metadata file is in a folder"),
+    )?;
+    let metadata = json!({
+        "name": settings.name,
+        "id": mod_id(&settings.name),
+        "version": settings.version,
+        "supported_game_version": settings.supported_version,
+        "short_description": "",
+        "tags": settings.tags,
+        "relationships": [],
+        "game_custom_data": { "multiplayer_synchronized": true },
+    });
+    let metadata = serde_json::to_string_pretty(&metadata).map_err(io::Error::other)?;
+    fs::write(&metadata_path, metadata)?;
+    written.push(metadata_path);
+
+    let thumbnail = dir.join(THUMBNAIL_FILE);
+    if !thumbnail.exists() {
+        fs::write(&thumbnail, THUMBNAIL)?;
+        written.push(thumbnail);
+    }
+    Ok(written)
+}
+
+/// The text of a descriptor, with the path of the mod for a launcher entry.
+fn descriptor(settings: &ModSettings, path: Option<&Path>) -> String {
+    let mut lines = vec![
+        format!("version=\"{}\"", settings.version),
+        format!("name=\"{}\"", settings.name),
+    ];
+    if !settings.tags.is_empty() {
+        lines.push("tags={".into());
+        lines.extend(settings.tags.iter().map(|tag| format!("\t\"{tag}\"")));
+        lines.push("}".into());
+    }
+    lines.push(format!(
+        "supported_version=\"{}\"",
+        settings.supported_version
+    ));
+    if let Some(path) = path {
+        // The launcher wants forward slashes on every platform.
+        let path = path.to_string_lossy().replace('\\', "/");
+        let path = path.strip_prefix("//?/").unwrap_or(&path);
+        lines.push(format!("path=\"{path}\""));
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// A launcher ID for the mod named `name`: its letters and digits in lower
+/// case, words joined by dots.
+fn mod_id(name: &str) -> String {
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    words.join(".")
+}
+
+pub fn folder_name(dir: &Path) -> String {
+    dir.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| DEFAULT_MOD_NAME.into())
+}
+
+/// The mod name `name` as a folder name, without the characters Windows
+/// does not allow in one.
+pub fn safe_folder_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Windows also drops trailing dots and spaces.
+    let name = name.trim_end_matches(['.', ' ']).trim_start();
+    if name.is_empty() {
+        DEFAULT_MOD_NAME.into()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Copy the directory `from` and everything under it to `to`, if it exists,
+/// adding the files copied to `written`.
+fn copy_tree(from: &Path, to: &Path, written: &mut Vec<PathBuf>) -> io::Result<()> {
+    let entries = match fs::read_dir(from) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    fs::create_dir_all(to)?;
+    for entry in entries {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_tree(&entry.path(), &target, written)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+            written.push(target);
+        }
+    }
+    Ok(())
+}
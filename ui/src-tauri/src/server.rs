@@ -0,0 +1,312 @@
+//! The shell's own HTTP API.
+//!
+//! The config and family tree endpoints the frontend fetches are served by
+//! an axum server inside the shell rather than by the engine, on a loopback
+//! port picked once at startup, so that the config can be edited while the
+//! engine is starting, restarting or gone, and a restarted engine no longer
+//! sends the frontend after a new port. They answer as the engine's own do,
+//! errors included, with a `detail`; a config is validated with `ck3-config`
+//! before it is written, and reported with every problem found otherwise.
+//! Runs are still the engine's, and the shell streams them itself. Only the
+//! app's own windows may call it from a page: other origins are refused by
+//! CORS, since it writes the config to disk.
+
+use std::fs;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
+use std::path::Path;
+use std::sync::Mutex;
+
+use axum::extract::{Path as UrlPath, State};
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use ck3_config::ConfigError;
+use ck3_sim::config::LIFE_STAGES_FILE;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::config::INITIALIZATION_FILE;
+use crate::output::{self, EnginePaths, FALLBACK_CONFIG_DIR};
+
+/// Managed state holding the port the server listens on, once it does.
+#[derive(Default)]
+pub struct LocalServer(Mutex<Option<u16>>);
+
+impl LocalServer {
+    /// Base URL of the server, without a trailing slash.
+    pub fn base_url(&self) -> Option<String> {
+        let port = (*self.0.lock().unwrap())?;
+        Some(format!("http://{}:{port}", Ipv4Addr::LOCALHOST))
+    }
+}
+
+/// An error answered as the engine answers one.
+struct ApiError(StatusCode, Value);
+
+impl ApiError {
+    fn new(status: StatusCode, detail: impl ToString) -> Self {
+        Self(status, Value::String(detail.to_string()))
+    }
+
+    fn invalid(errors: Vec<ConfigError>) -> Self {
+        Self(StatusCode::UNPROCESSABLE_ENTITY, json!(errors))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "detail": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+#[derive(Serialize)]
+struct Status {
+    status: &'static str,
+}
+
+/// Start serving on a free loopback port.
+pub fn start(app: &AppHandle) {
+    let listener = match TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))) {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::error!("cannot start the shell's API server: {err}");
+            return;
+        }
+    };
+    let port = listener.local_addr().map(|addr| addr.port()).ok();
+    *app.state::<LocalServer>().0.lock().unwrap() = port;
+
+    let router = Router::new()
+        .route("/health", get(health))
+        .route("/paths", get(paths))
+        .route(
+            "/config/initialization",
+            get(get_initialization).put(put_initialization),
+        )
+        .route("/config/initialization/reset", post(reset_initialization))
+        .route(
+            "/config/initialization/set-fallback",
+            post(set_initialization_fallback),
+        )
+        .route(
+            "/config/life-stages",
+            get(get_life_stages).put(put_life_stages),
+        )
+        .route(
+            "/config/life-stages/fallback",
+            get(get_life_stages_fallback),
+        )
+        .route("/config/life-stages/reset", post(reset_life_stages))
+        .route("/images", get(list_images))
+        .route("/images/{filename}", get(get_image))
+        .layer(
+            CorsLayer::new()
+                .allow_origin(AllowOrigin::list(allowed_origins(app)))
+                .allow_methods([Method::GET, Method::PUT, Method::POST])
+                .allow_headers([header::CONTENT_TYPE]),
+        )
+        .with_state(app.clone());
+    tauri::async_runtime::spawn(async move {
+        let served = async {
+            listener.set_nonblocking(true)?;
+            let listener = tokio::net::TcpListener::from_std(listener)?;
+            axum::serve(listener, router).await
+        };
+        if let Err(err) = served.await {
+            tracing::error!("the shell's API server stopped: {err}");
+        }
+    });
+}
+
+/// Origins the app's windows are served from: its own scheme, as each
+/// platform spells it, and the dev server in development.
+fn allowed_origins(app: &AppHandle) -> Vec<HeaderValue> {
+    let mut origins = vec![
+        HeaderValue::from_static("tauri://localhost"),
+        HeaderValue::from_static("http://tauri.localhost"),
+        HeaderValue::from_static("https://tauri.localhost"),
+    ];
+    if cfg!(debug_assertions) {
+        let dev = app.config().build.dev_url.as_ref();
+        if let Some(origin) =
+            dev.and_then(|url| HeaderValue::from_str(&url.origin().ascii_serialization()).ok())
+        {
+            origins.push(origin);
+        }
+    }
+    origins
+}
+
+async fn health() -> Json<Status> {
+    Json(Status { status: "ready" })
+}
+
+async fn paths(State(app): State<AppHandle>) -> ApiResult<Json<EnginePaths>> {
+    Ok(Json(current_paths(&app).await?))
+}
+
+async fn current_paths(app: &AppHandle) -> ApiResult<EnginePaths> {
+    output::current_paths(app)
+        .await
+        .map_err(|err| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
+
+async fn get_initialization(State(app): State<AppHandle>) -> ApiResult<Json<Value>> {
+    let paths = current_paths(&app).await?;
+    read_json(&paths.config_dir.join(INITIALIZATION_FILE)).map(Json)
+}
+
+async fn put_initialization(
+    State(app): State<AppHandle>,
+    Json(config): Json<Value>,
+) -> ApiResult<Json<Value>> {
+    ck3_config::initialization_from_value(config.clone()).map_err(ApiError::invalid)?;
+    let paths = current_paths(&app).await?;
+    write_json(&paths.config_dir.join(INITIALIZATION_FILE), &config)?;
+    Ok(Json(json!({ "status": "saved" })))
+}
+
+async fn reset_initialization(State(app): State<AppHandle>) -> ApiResult<Json<Value>> {
+    let paths = current_paths(&app).await?;
+    let dir = &paths.config_dir;
+    copy_config(
+        &dir.join(FALLBACK_CONFIG_DIR).join(INITIALIZATION_FILE),
+        &dir.join(INITIALIZATION_FILE),
+        "Fallback initialization config not found",
+    )?;
+    Ok(Json(json!({ "status": "reset" })))
+}
+
+async fn set_initialization_fallback(State(app): State<AppHandle>) -> ApiResult<Json<Value>> {
+    let paths = current_paths(&app).await?;
+    let dir = &paths.config_dir;
+    copy_config(
+        &dir.join(INITIALIZATION_FILE),
+        &dir.join(FALLBACK_CONFIG_DIR).join(INITIALIZATION_FILE),
+        "Active initialization config not found",
+    )?;
+    Ok(Json(json!({ "status": "fallback updated" })))
+}
+
+async fn get_life_stages(State(app): State<AppHandle>) -> ApiResult<Json<Value>> {
+    let paths = current_paths(&app).await?;
+    read_json(&paths.config_dir.join(LIFE_STAGES_FILE)).map(Json)
+}
+
+async fn get_life_stages_fallback(State(app): State<AppHandle>) -> ApiResult<Json<Value>> {
+    let paths = current_paths(&app).await?;
+    let path = paths
+        .config_dir
+        .join(FALLBACK_CONFIG_DIR)
+        .join(LIFE_STAGES_FILE);
+    read_json(&path).map(Json)
+}
+
+async fn put_life_stages(
+    State(app): State<AppHandle>,
+    Json(config): Json<Value>,
+) -> ApiResult<Json<Value>> {
+    ck3_config::parse_life_stages(&config.to_string()).map_err(ApiError::invalid)?;
+    let paths = current_paths(&app).await?;
+    write_json(&paths.config_dir.join(LIFE_STAGES_FILE), &config)?;
+    Ok(Json(json!({ "status": "saved" })))
+}
+
+async fn reset_life_stages(State(app): State<AppHandle>) -> ApiResult<Json<Value>> {
+    let paths = current_paths(&app).await?;
+    let dir = &paths.config_dir;
+    copy_config(
+        &dir.join(FALLBACK_CONFIG_DIR).join(LIFE_STAGES_FILE),
+        &dir.join(LIFE_STAGES_FILE),
+        "Fallback life stages config not found",
+    )?;
+    Ok(Json(json!({ "status": "reset" })))
+}
+
+/// The family tree images, by name.
+async fn list_images(State(app): State<AppHandle>) -> ApiResult<Json<Vec<String>>> {
+    let paths = current_paths(&app).await?;
+    let Ok(entries) = fs::read_dir(&paths.tree_dir) else {
+        return Ok(Json(Vec::new()));
+    };
+    let mut images: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| is_tree_image(name))
+        .collect();
+    images.sort();
+    Ok(Json(images))
+}
+
+async fn get_image(
+    State(app): State<AppHandle>,
+    UrlPath(filename): UrlPath<String>,
+) -> ApiResult<Response> {
+    if !is_tree_image(&filename) || filename.contains(['/', '\\']) {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "Invalid image filename",
+        ));
+    }
+    let paths = current_paths(&app).await?;
+    let bytes = fs::read(paths.tree_dir.join(&filename))
+        .map_err(|_| ApiError::new(StatusCode::NOT_FOUND, "Image not found"))?;
+    Ok(([(header::CONTENT_TYPE, "image/png")], bytes).into_response())
+}
+
+fn is_tree_image(name: &str) -> bool {
+    name.starts_with("family_tree_") && name.ends_with(".png")
+}
+
+fn read_json(path: &Path) -> ApiResult<Value> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let text = fs::read_to_string(path).map_err(|_| {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Config file not found: {name}"),
+        )
+    })?;
+    serde_json::from_str(&text).map_err(|err| {
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("{name} is not JSON: {err}"),
+        )
+    })
+}
+
+/// Write `value` as the engine writes config files, indented by four
+/// spaces.
+fn write_json(path: &Path, value: &Value) -> ApiResult<()> {
+    let mut text = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut text, formatter);
+    value
+        .serialize(&mut serializer)
+        .map_err(|err| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, err))?;
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(path, text));
+    written.map_err(|err| {
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("cannot write {}: {err}", path.display()),
+        )
+    })
+}
+
+fn copy_config(from: &Path, to: &Path, missing: &str) -> ApiResult<()> {
+    if !from.is_file() {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, missing));
+    }
+    fs::copy(from, to).map(drop).map_err(|err| {
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("cannot write {}: {err}", to.display()),
+        )
+    })
+}
@@ -0,0 +1,288 @@
+//! Structured logging of the shell.
+//!
+//! The shell logs through `tracing`. The subscriber here filters events by
+//! level per module, as the `CK3GEN_LOG` environment variable sets them, in
+//! the form `warn,ck3_generator::sidecar=debug`: a default level and any
+//! number of `module=level` overrides, the longest matching module winning.
+//! The default is `info`. Events are written to the console and to rotating
+//! `shell-<date>-<time>.log` files in the app log directory, and the latest
+//! are kept in memory for `query_logs`, which backs the in-app log viewer.
+//!
+//! The engine's output is logged under the `engine` target: its classified
+//! stderr entries at their own level and every raw line at `debug`, so that
+//! the viewer can show the shell's and the engine's logs side by side.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use sidecar_manager::RotatingLog;
+use tauri::AppHandle;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+use crate::logs;
+
+/// Environment variable holding the per-module levels.
+const LOG_LEVELS_ENV: &str = "CK3GEN_LOG";
+/// Prefix of the shell's log files, beside the engine's.
+const LOG_PREFIX: &str = "shell-";
+/// Number of records kept in memory for `query_logs`.
+const MAX_RECORDS: usize = 10_000;
+/// Most records `query_logs` returns, the newest ones.
+const MAX_QUERY_RECORDS: usize = 2_000;
+
+/// One logged event.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRecord {
+    /// Milliseconds since the Unix epoch.
+    pub time: u64,
+    /// `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`.
+    pub level: &'static str,
+    /// The module that logged it, or `engine` for the engine's output.
+    pub target: String,
+    pub message: String,
+    /// The event's other fields, by name, in the order given.
+    pub fields: Vec<(String, String)>,
+    #[serde(skip)]
+    severity: Level,
+}
+
+impl LogRecord {
+    /// The record as one line of the log file, without the time and level.
+    fn line(&self) -> String {
+        let mut line = format!("{}: {}", self.target, self.message);
+        for (name, value) in &self.fields {
+            let _ = write!(line, " {name}={value}");
+        }
+        line
+    }
+}
+
+/// The level of each module, from the `CK3GEN_LOG` directives.
+#[derive(Debug)]
+struct Directives {
+    default: LevelFilter,
+    /// Module paths and their levels, longest first.
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl Directives {
+    /// Directives such as `warn,ck3_generator::sidecar=debug`; ones that do
+    /// not parse are reported and skipped.
+    fn parse(text: &str) -> Self {
+        let mut directives = Self {
+            default: LevelFilter::INFO,
+            modules: Vec::new(),
+        };
+        for directive in text.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let parsed = match directive.split_once('=') {
+                Some((module, level)) => LevelFilter::from_str(level.trim())
+                    .map(|level| directives.modules.push((module.trim().to_string(), level))),
+                None => LevelFilter::from_str(directive).map(|level| directives.default = level),
+            };
+            if parsed.is_err() {
+                eprintln!("ignoring {LOG_LEVELS_ENV} directive '{directive}'");
+            }
+        }
+        directives
+            .modules
+            .sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+        directives
+    }
+
+    /// The level `target` logs at.
+    fn level(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .find(|(module, _)| {
+                target
+                    .strip_prefix(module.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    /// The most verbose level any module logs at.
+    fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, LevelFilter::max)
+    }
+}
+
+/// What the subscriber keeps, shared with `query_logs`.
+struct Shared {
+    records: Mutex<VecDeque<LogRecord>>,
+    /// The log file, once the app log directory is known.
+    file: Mutex<Option<RotatingLog>>,
+}
+
+static SHARED: OnceLock<Shared> = OnceLock::new();
+
+fn shared() -> &'static Shared {
+    SHARED.get_or_init(|| Shared {
+        records: Mutex::new(VecDeque::new()),
+        file: Mutex::new(None),
+    })
+}
+
+/// The shell's `tracing` subscriber.
+struct ShellSubscriber {
+    directives: Directives,
+    /// Source of span IDs; spans are not recorded otherwise.
+    next_span: AtomicU64,
+}
+
+impl Subscriber for ShellSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= self.directives.level(metadata.target())
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(self.directives.max_level())
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let metadata = event.metadata();
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let record = LogRecord {
+            time: now_millis(),
+            level: metadata.level().as_str(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+            severity: *metadata.level(),
+        };
+
+        let line = record.line();
+        eprintln!("{:>5} {line}", record.level);
+        let shared = shared();
+        if let Some(file) = shared.file.lock().unwrap().as_mut() {
+            // Reporting a failed write through the log would only fail again.
+            let _ = file.write_labelled(record.level, &line);
+        }
+        let mut records = shared.records.lock().unwrap();
+        if records.len() == MAX_RECORDS {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+/// Collects an event's message and other fields.
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.push(field, format!("{value:?}"));
+    }
+}
+
+impl FieldVisitor {
+    fn push(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = value;
+        } else {
+            self.fields.push((field.name().to_string(), value));
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as u64)
+}
+
+/// Install the subscriber, with the levels `CK3GEN_LOG` sets. Called first
+/// thing, so that nothing logged at startup is lost.
+pub fn init() {
+    let directives = Directives::parse(&std::env::var(LOG_LEVELS_ENV).unwrap_or_default());
+    let subscriber = ShellSubscriber {
+        directives,
+        next_span: AtomicU64::new(1),
+    };
+    if tracing::subscriber::set_global_default(subscriber).is_err() {
+        eprintln!("a tracing subscriber is already installed");
+    }
+}
+
+/// Start writing the log to a file in the app log directory, which is only
+/// known once the app has started.
+pub fn start(app: &AppHandle) {
+    match logs::log_dir(app) {
+        Ok(dir) => *shared().file.lock().unwrap() = Some(RotatingLog::new(dir, LOG_PREFIX)),
+        Err(err) => tracing::warn!("shell log file disabled: {err}"),
+    }
+}
+
+/// The records of this session at `level` or more severe, logged at or
+/// after `since`, in milliseconds since the Unix epoch, whose target,
+/// message or fields contain `filter`, ignoring case; the newest 2000 at
+/// most, oldest first.
+#[tauri::command]
+pub fn query_logs(
+    level: Option<String>,
+    since: Option<u64>,
+    filter: Option<String>,
+) -> Result<Vec<LogRecord>, String> {
+    let level = level
+        .map(|level| Level::from_str(&level).map_err(|_| format!("'{level}' is not a log level")))
+        .transpose()?
+        .unwrap_or(Level::TRACE);
+    let filter = filter
+        .map(|filter| filter.trim().to_lowercase())
+        .filter(|filter| !filter.is_empty());
+    let matches = |record: &LogRecord| {
+        filter.as_deref().is_none_or(|filter| {
+            record.target.to_lowercase().contains(filter)
+                || record.message.to_lowercase().contains(filter)
+                || record
+                    .fields
+                    .iter()
+                    .any(|(name, value)| format!("{name}={value}").to_lowercase().contains(filter))
+        })
+    };
+
+    let records = shared().records.lock().unwrap();
+    let mut found: Vec<LogRecord> = records
+        .iter()
+        .rev()
+        .take_while(|record| since.is_none_or(|since| record.time >= since))
+        .filter(|record| record.severity <= level && matches(record))
+        .take(MAX_QUERY_RECORDS)
+        .cloned()
+        .collect();
+    found.reverse();
+    Ok(found)
+}
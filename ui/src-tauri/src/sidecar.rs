@@ -0,0 +1,549 @@
+//! Supervision of the `api_server` sidecar.
+//!
+//! The packaged app runs the compiled FastAPI server as a sidecar for the
+//! lifetime of the window. If the process dies (a Python crash, a killed
+//! process, a failed bind), it is respawned with exponential backoff and the
+//! frontend is told about it through the events below, so a crash mid-run no
+//! longer leaves the UI waiting on a server that is gone. Each such exit also
+//! leaves a crash report (see [`crate::crash`]).
+//!
+//! On exit the engine is asked to shut down over HTTP and given time to
+//! finish writing its output; it is only killed if it does not exit within
+//! the shutdown timeout. The request carries the token the engine was given
+//! in its environment at launch, and recorded with its PID, without which it
+//! refuses to stop. The same path backs `restart_engine`, which lets the
+//! user replace an engine stuck in a bad state without restarting the app.
+//!
+//! An engine left running by a crashed session can be adopted at startup
+//! instead of spawning a new one (see [`crate::orphans`]). An adopted engine
+//! is not a child of this process, so it is watched by PID and replaced by a
+//! supervised sidecar once it exits.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use sidecar_manager::pidfile::{self, EngineRecord};
+use sidecar_manager::shutdown::{SHUTDOWN_TOKEN_ENV, SHUTDOWN_TOKEN_HEADER};
+use sidecar_manager::{
+    Classifier, EngineStatus, Message, RestartPolicy, Restarts, ShutdownPolicy, ShutdownToken,
+    StderrEntry, StderrKind, Stream,
+};
+use tauri::async_runtime::Receiver;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+
+use crate::crash;
+use crate::endpoint::ApiEndpoint;
+use crate::generation;
+use crate::health;
+use crate::launch::EngineLaunch;
+use crate::logs::EngineLog;
+use crate::orphans;
+use crate::output;
+use crate::stderr;
+
+/// Name of the sidecar binary as declared in `bundle.externalBin`.
+pub const SIDECAR_NAME: &str = "api_server";
+
+/// Emitted after the sidecar has been respawned following a crash.
+pub const ENGINE_RESTARTED_EVENT: &str = "engine-restarted";
+/// Emitted once the restart budget is exhausted; the supervisor stops here.
+pub const ENGINE_UNRECOVERABLE_EVENT: &str = "engine-unrecoverable";
+/// Emitted with a [`RestartProgress`] as `restart_engine` moves through its stages.
+pub const ENGINE_RESTART_PROGRESS_EVENT: &str = "engine-restart-progress";
+
+/// How long `restart_engine` waits for the new engine to answer its heartbeat.
+const RESTART_READY_TIMEOUT: Duration = Duration::from_secs(30);
+/// Interval at which `restart_engine` checks the new engine's status.
+const RESTART_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Interval at which an adopted engine is checked for having exited.
+const ADOPTED_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Managed state tracking the running sidecar process.
+#[derive(Default)]
+pub struct Sidecar {
+    /// Handle of the running process; cleared by the supervisor when it exits.
+    child: Mutex<Option<CommandChild>>,
+    /// Set once the app is exiting, so a terminated process is not respawned.
+    shutting_down: AtomicBool,
+    /// Set by `restart_engine` until the replacement process has been spawned,
+    /// so the supervisor respawns at once instead of treating it as a crash.
+    restart_requested: AtomicBool,
+    /// Whether an engine is being kept alive, either by the supervisor or as
+    /// an adopted orphan; cleared once the engine is unrecoverable.
+    supervising: AtomicBool,
+    /// Engine from an earlier session that is being reused, if any.
+    adopted: Mutex<Option<EngineRecord>>,
+    /// Token the spawned engine accepts shutdown requests with.
+    token: Mutex<ShutdownToken>,
+}
+
+impl Sidecar {
+    /// Whether an engine process is still being kept alive.
+    pub fn is_supervising(&self) -> bool {
+        self.supervising.load(Ordering::SeqCst)
+    }
+
+    /// PID of the running engine, whether spawned or adopted.
+    pub fn pid(&self) -> Option<u32> {
+        let adopted = self.adopted.lock().unwrap().map(|record| record.pid);
+        adopted.or_else(|| self.child.lock().unwrap().as_ref().map(CommandChild::pid))
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EngineRestarted {
+    /// 1-based restart attempt that brought the engine back, or 0 for a
+    /// restart requested by the user.
+    attempt: u32,
+    /// Exit code of the process that died, if the OS reported one.
+    exit_code: Option<i32>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EngineUnrecoverable {
+    attempts: u32,
+    exit_code: Option<i32>,
+    message: String,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum RestartStage {
+    /// The running engine is being shut down.
+    Stopping,
+    /// A new engine has been requested and is booting.
+    Starting,
+    /// The new engine answered its heartbeat.
+    Ready,
+    Failed,
+}
+
+#[derive(Clone, Serialize)]
+struct RestartProgress {
+    stage: RestartStage,
+    message: Option<String>,
+}
+
+/// How the last sidecar run ended.
+enum Exit {
+    /// The process ran and terminated with the given exit code.
+    Terminated(Option<i32>),
+    /// The process could not be spawned at all.
+    SpawnFailed(String),
+}
+
+/// Start supervising the sidecar in the background.
+///
+/// Orphans from an earlier session are dealt with first. In development the
+/// server is started by run_ui.bat, so there is nothing to supervise; the
+/// sidecar is only active in a packaged (release) build, and never in one
+/// that embeds the engine.
+pub fn start(app: &AppHandle) {
+    if cfg!(debug_assertions) || cfg!(feature = "embedded-engine") {
+        return;
+    }
+    app.state::<Sidecar>()
+        .supervising
+        .store(true, Ordering::SeqCst);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Some(record) = orphans::resolve(&app).await {
+            if !adopt(&app, record).await {
+                return;
+            }
+        }
+        supervise(app).await;
+    });
+}
+
+/// Use an orphaned engine until it exits.
+///
+/// Returns whether a supervised sidecar should take over afterwards; not when
+/// the app is exiting or `stop` ended the adoption.
+async fn adopt(app: &AppHandle, record: EngineRecord) -> bool {
+    let sidecar = app.state::<Sidecar>();
+    tracing::info!(
+        "reusing {SIDECAR_NAME} from a previous session (process {})",
+        record.pid
+    );
+    app.state::<ApiEndpoint>().set_port(record.port);
+    *sidecar.adopted.lock().unwrap() = Some(record);
+
+    loop {
+        tokio::time::sleep(ADOPTED_POLL_INTERVAL).await;
+        if sidecar.adopted.lock().unwrap().is_none() {
+            return false;
+        }
+        if !pidfile::is_running(record.pid, SIDECAR_NAME) {
+            break;
+        }
+    }
+
+    tracing::info!("adopted {SIDECAR_NAME} exited");
+    sidecar.adopted.lock().unwrap().take();
+    orphans::forget(orphans::engine_pid_file(app));
+    !sidecar.shutting_down.load(Ordering::SeqCst)
+}
+
+async fn supervise(app: AppHandle) {
+    let sidecar = app.state::<Sidecar>();
+    let mut restarts = Restarts::new(RestartPolicy::default());
+    let mut last_exit_code = None;
+    let mut manual_restart = false;
+
+    loop {
+        let started = Instant::now();
+        health::set_status(&app, EngineStatus::Starting);
+        let spawned = spawn(&app);
+        sidecar.restart_requested.store(false, Ordering::SeqCst);
+        let exit = match spawned {
+            Ok(events) => {
+                if restarts.attempt() > 0 || manual_restart {
+                    let _ = app.emit(
+                        ENGINE_RESTARTED_EVENT,
+                        EngineRestarted {
+                            attempt: restarts.attempt(),
+                            exit_code: last_exit_code,
+                        },
+                    );
+                }
+                wait_for_exit(&app, events).await
+            }
+            Err(err) => Exit::SpawnFailed(err),
+        };
+
+        sidecar.child.lock().unwrap().take();
+        orphans::forget(orphans::engine_pid_file(&app));
+        health::set_status(&app, EngineStatus::Dead);
+        if sidecar.shutting_down.load(Ordering::SeqCst) {
+            sidecar.supervising.store(false, Ordering::SeqCst);
+            return;
+        }
+
+        let crashed = matches!(exit, Exit::Terminated(_));
+        let message = match exit {
+            Exit::Terminated(code) => {
+                last_exit_code = code;
+                format!("{SIDECAR_NAME} exited with code {code:?}")
+            }
+            Exit::SpawnFailed(err) => {
+                last_exit_code = None;
+                format!("failed to spawn {SIDECAR_NAME}: {err}")
+            }
+        };
+        tracing::warn!("{message}");
+
+        // A process stopped by restart_engine is replaced straight away and
+        // starts a fresh restart budget.
+        manual_restart = sidecar.restart_requested.load(Ordering::SeqCst);
+        if manual_restart {
+            restarts = Restarts::new(RestartPolicy::default());
+            continue;
+        }
+        if crashed {
+            crash::engine_exited(&app, last_exit_code, message.clone());
+        }
+
+        let Some(delay) = restarts.next_delay(started.elapsed()) else {
+            let attempts = restarts.attempt();
+            tracing::error!("{SIDECAR_NAME} is unrecoverable after {attempts} restarts");
+            let _ = app.emit(
+                ENGINE_UNRECOVERABLE_EVENT,
+                EngineUnrecoverable {
+                    attempts,
+                    exit_code: last_exit_code,
+                    message,
+                },
+            );
+            sidecar.supervising.store(false, Ordering::SeqCst);
+            return;
+        };
+
+        tracing::info!(
+            "restarting {SIDECAR_NAME} in {delay:?} (attempt {}/{})",
+            restarts.attempt(),
+            restarts.policy().max_restarts
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Spawn the sidecar on a freshly allocated port with the saved launch
+/// options, returning its event stream.
+fn spawn(app: &AppHandle) -> Result<Receiver<CommandEvent>, String> {
+    let port = app
+        .state::<ApiEndpoint>()
+        .allocate()
+        .map_err(|err| format!("no free port: {err}"))?;
+
+    // The engine is told every directory, so that the shell knows them
+    // without asking.
+    let launch = app.state::<EngineLaunch>();
+    let options = launch.options();
+    let args = output::engine_options(app, &options)?.args();
+    let token = ShutdownToken::generate();
+    let (events, child) = app
+        .shell()
+        .sidecar(SIDECAR_NAME)
+        .and_then(|command| {
+            command
+                .args(["--port", &port.to_string()])
+                .args(args)
+                .env(SHUTDOWN_TOKEN_ENV, token.to_string())
+                .spawn()
+        })
+        .map_err(|err| err.to_string())?;
+    launch.set_running(options);
+    let pid = child.pid();
+    orphans::record(
+        orphans::engine_pid_file(app),
+        EngineRecord { pid, port, token },
+    );
+    let sidecar = app.state::<Sidecar>();
+    *sidecar.token.lock().unwrap() = token;
+    *sidecar.child.lock().unwrap() = Some(child);
+    Ok(events)
+}
+
+/// Forward the sidecar's output until it terminates.
+///
+/// Both streams are written to the engine log and the shell's, stdout at
+/// `info` and stderr at `debug`, except the protocol messages of stdout:
+/// progress is passed on as progress events, and log records, like stderr
+/// classified, are logged at their level and forwarded to the frontend so
+/// Python warnings and tracebacks are visible in the UI. An engine speaking
+/// another version of the protocol is reported as an error.
+async fn wait_for_exit(app: &AppHandle, mut events: Receiver<CommandEvent>) -> Exit {
+    let log = app.state::<EngineLog>();
+    let mut classifier = Classifier::default();
+    let mut exit = Exit::Terminated(None);
+
+    while let Some(event) = events.recv().await {
+        match event {
+            CommandEvent::Stdout(line) => {
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim_end();
+                match Message::parse(line) {
+                    // Progress becomes events rather than log noise.
+                    Some(Message::Progress(progress)) => generation::report(app, progress),
+                    Some(Message::Log(record)) => {
+                        let entry = StderrEntry::from(record);
+                        log.write(app, Stream::Stderr, &entry.message);
+                        stderr::record(app, entry);
+                    }
+                    Some(Message::Hello(hello)) => {
+                        tracing::info!(target: "engine", "speaks protocol version {}", hello.protocol);
+                        if let Some(mismatch) = hello.mismatch() {
+                            log.write(app, Stream::Stderr, &format!("ERROR: {mismatch}"));
+                            stderr::record(
+                                app,
+                                StderrEntry {
+                                    kind: StderrKind::Error,
+                                    message: mismatch,
+                                },
+                            );
+                        }
+                    }
+                    Some(Message::Unknown { method, .. }) => {
+                        tracing::debug!(target: "engine", "ignored a {method} notification");
+                    }
+                    None => {
+                        tracing::info!(target: "engine", "{line}");
+                        log.write(app, Stream::Stdout, line);
+                    }
+                }
+            }
+            CommandEvent::Stderr(line) => {
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim_end();
+                tracing::debug!(target: "engine", "{line}");
+                log.write(app, Stream::Stderr, line);
+                if let Some(entry) = classifier.push(line) {
+                    stderr::record(app, entry);
+                }
+            }
+            CommandEvent::Error(err) => {
+                tracing::warn!(target: "engine", "{err}");
+                log.write(app, Stream::Stderr, &err);
+            }
+            CommandEvent::Terminated(payload) => {
+                exit = Exit::Terminated(payload.code);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(entry) = classifier.finish() {
+        stderr::record(app, entry);
+    }
+    // If the channel closed without a Terminated event, the process vanished
+    // in a way the OS did not report.
+    exit
+}
+
+/// Stop the sidecar on app exit, giving it a chance to exit cleanly first.
+///
+/// Blocks until the process has exited or the shutdown timeout has elapsed,
+/// after which it is killed. Does nothing if no sidecar is running.
+pub fn shutdown(app: &AppHandle) {
+    app.state::<Sidecar>()
+        .shutting_down
+        .store(true, Ordering::SeqCst);
+    tauri::async_runtime::block_on(stop(app, &ShutdownPolicy::from_env()));
+}
+
+/// Start the sidecar again after `shutdown`, for when the app carries on
+/// after all, such as when an update failed to install.
+pub async fn resume(app: &AppHandle) {
+    let sidecar = app.state::<Sidecar>();
+    // The supervisor of the stopped engine has to see it stop as a shutdown
+    // first; one that is still running respawns it as after a crash.
+    let idle = ShutdownPolicy::from_env()
+        .wait_for_exit(|| !sidecar.supervising.load(Ordering::SeqCst))
+        .await;
+    sidecar.shutting_down.store(false, Ordering::SeqCst);
+    if idle {
+        start(app);
+    }
+}
+
+/// Ask the running sidecar to exit, killing it if it has not done so within
+/// the policy's timeout.
+async fn stop(app: &AppHandle, policy: &ShutdownPolicy) {
+    let sidecar = app.state::<Sidecar>();
+    let adopted = sidecar.adopted.lock().unwrap().take();
+    if let Some(record) = adopted {
+        stop_adopted(app, record, policy).await;
+        return;
+    }
+    if sidecar.child.lock().unwrap().is_none() {
+        return;
+    }
+
+    let url = format!("{}/shutdown", app.state::<ApiEndpoint>().base_url());
+    let token = *sidecar.token.lock().unwrap();
+    match request_shutdown(&url, token, policy).await {
+        Ok(()) => {
+            let exited = policy
+                .wait_for_exit(|| sidecar.child.lock().unwrap().is_none())
+                .await;
+            if !exited {
+                tracing::warn!("{SIDECAR_NAME} did not exit within {:?}", policy.timeout);
+            }
+        }
+        Err(err) => tracing::warn!("{SIDECAR_NAME} did not accept the shutdown request: {err}"),
+    }
+
+    // Last resort: the process ignored the request or is hung.
+    let child = sidecar.child.lock().unwrap().take();
+    if let Some(child) = child {
+        tracing::warn!("killing {SIDECAR_NAME}");
+        if let Err(err) = child.kill() {
+            tracing::error!("failed to kill {SIDECAR_NAME}: {err}");
+        }
+    }
+    orphans::forget(orphans::engine_pid_file(app));
+}
+
+/// Like `stop`, for an adopted engine that can only be reached by PID.
+async fn stop_adopted(app: &AppHandle, record: EngineRecord, policy: &ShutdownPolicy) {
+    let url = format!("{}/shutdown", app.state::<ApiEndpoint>().base_url());
+    let running = || pidfile::is_running(record.pid, SIDECAR_NAME);
+    let exited = match request_shutdown(&url, record.token, policy).await {
+        Ok(()) => policy.wait_for_exit(|| !running()).await,
+        Err(_) => false,
+    };
+    if !exited && running() {
+        tracing::info!("terminating adopted {SIDECAR_NAME}");
+        if let Err(err) = pidfile::terminate(record.pid) {
+            tracing::warn!("{err}");
+        }
+    }
+    // Nothing is keeping an engine alive until `start` is called again.
+    app.state::<Sidecar>()
+        .supervising
+        .store(false, Ordering::SeqCst);
+    health::set_status(app, EngineStatus::Dead);
+    orphans::forget(orphans::engine_pid_file(app));
+}
+
+/// Ask the engine at `url`, which was given `token`, to shut down.
+pub async fn request_shutdown(
+    url: &str,
+    token: ShutdownToken,
+    policy: &ShutdownPolicy,
+) -> reqwest::Result<()> {
+    reqwest::Client::builder()
+        .timeout(policy.request_timeout)
+        .build()?
+        .post(url)
+        .header(SHUTDOWN_TOKEN_HEADER, token.to_string())
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+fn report_restart(app: &AppHandle, stage: RestartStage, message: Option<String>) {
+    let _ = app.emit(
+        ENGINE_RESTART_PROGRESS_EVENT,
+        RestartProgress { stage, message },
+    );
+}
+
+/// Replace the running engine with a fresh process.
+///
+/// The current sidecar is shut down gracefully, a new one is spawned on a new
+/// port, and the command resolves once it answers its heartbeat. Progress is
+/// reported through `engine-restart-progress` events.
+#[tauri::command]
+pub async fn restart_engine(app: AppHandle) -> Result<(), String> {
+    if cfg!(feature = "embedded-engine") {
+        return Err("the engine runs inside the app in this build; restart the app instead".into());
+    }
+    if cfg!(debug_assertions) {
+        return Err("the engine is started by run_ui.bat in development; restart it there".into());
+    }
+    let sidecar = app.state::<Sidecar>();
+    if sidecar
+        .restart_requested
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("an engine restart is already in progress".into());
+    }
+
+    report_restart(&app, RestartStage::Stopping, None);
+    stop(&app, &ShutdownPolicy::from_env()).await;
+
+    report_restart(&app, RestartStage::Starting, None);
+    health::set_status(&app, EngineStatus::Starting);
+    // An engine that was given up on has no supervisor left to respawn it.
+    if !sidecar.supervising.load(Ordering::SeqCst) {
+        start(&app);
+    }
+
+    let deadline = Instant::now() + RESTART_READY_TIMEOUT;
+    loop {
+        // Ignore the old process's final status until the new one is spawned.
+        let respawned = !sidecar.restart_requested.load(Ordering::SeqCst);
+        let status = app.state::<health::EngineHealth>().status();
+        if respawned && matches!(status, EngineStatus::Ready | EngineStatus::Busy) {
+            report_restart(&app, RestartStage::Ready, None);
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            let message = format!("engine did not become ready within {RESTART_READY_TIMEOUT:?}");
+            report_restart(&app, RestartStage::Failed, Some(message.clone()));
+            return Err(message);
+        }
+        tokio::time::sleep(RESTART_POLL_INTERVAL).await;
+    }
+}
@@ -0,0 +1,137 @@
+//! Copying single characters and dynasties to the clipboard.
+//!
+//! Modders often want one generated character in their own files rather than
+//! the whole export. These commands look the entity up in the engine's last
+//! export and put its block on the clipboard exactly as it was written,
+//! comments and indentation included. Characters' faces can be copied too,
+//! as the persistent DNA strings the game's ruler designer pastes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::output;
+
+/// Export holding the character blocks.
+const CHARACTER_FILE: &str = "family_history.txt";
+/// Export holding the dynasty blocks.
+const DYNASTY_FILE: &str = "dynasty_definitions.txt";
+/// Prefix of dynasty keys in [`DYNASTY_FILE`], which config IDs may omit.
+const DYNASTY_PREFIX: &str = "dynasty_";
+
+/// Copy the block of character `id`, e.g. `lotr_1234`, to the clipboard.
+#[tauri::command]
+pub async fn copy_character_block(app: AppHandle, id: String) -> Result<(), String> {
+    let dir = output::current_paths(&app).await?.character_dir;
+    let block = find_block(&dir.join(CHARACTER_FILE), &[id.as_str()])?
+        .ok_or_else(|| format!("character {id} is not in the last export"))?;
+    copy(&app, block)
+}
+
+/// Copy the block of dynasty `id` to the clipboard.
+///
+/// `id` may be given with or without its `dynasty_` prefix.
+#[tauri::command]
+pub async fn copy_dynasty_block(app: AppHandle, id: String) -> Result<(), String> {
+    let dir = output::current_paths(&app).await?.character_dir;
+    let prefixed = format!("{DYNASTY_PREFIX}{id}");
+    let block = find_block(&dir.join(DYNASTY_FILE), &[id.as_str(), prefixed.as_str()])?
+        .ok_or_else(|| format!("dynasty {id} is not in the last export"))?;
+    copy(&app, block)
+}
+
+/// Copy the persistent DNA strings of the characters `ids`, laid out by the
+/// genes of the installation or mod in `game_dir`, to the clipboard: the
+/// string alone for one character, a line of ID and string each for more.
+///
+/// Resolves to each character's ID and string.
+#[tauri::command]
+pub async fn copy_ruler_designer_dna(
+    app: AppHandle,
+    ids: Vec<String>,
+    game_dir: PathBuf,
+) -> Result<Vec<(String, String)>, String> {
+    let dir = output::current_paths(&app).await?.character_dir;
+    if !dir.join(CHARACTER_FILE).is_file() {
+        return Err("nothing has been generated yet".into());
+    }
+    let strings = tauri::async_runtime::spawn_blocking(move || {
+        ck3_sim::ruler_designer_dna(&dir, &game_dir, &ids).map_err(|err| err.to_string())
+    })
+    .await
+    .map_err(|err| err.to_string())??;
+    let text = match strings.as_slice() {
+        [(_, dna)] => dna.clone(),
+        _ => strings
+            .iter()
+            .map(|(id, dna)| format!("{id}: {dna}\n"))
+            .collect(),
+    };
+    copy(&app, text)?;
+    Ok(strings)
+}
+
+fn copy(app: &AppHandle, block: String) -> Result<(), String> {
+    app.clipboard()
+        .write_text(block)
+        .map_err(|err| err.to_string())
+}
+
+/// The first top-level block in `path` under one of `keys`.
+fn find_block(path: &Path, keys: &[&str]) -> Result<Option<String>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err("nothing has been generated yet".into());
+        }
+        Err(err) => return Err(format!("cannot read {}: {err}", path.display())),
+    };
+    Ok(keys
+        .iter()
+        .find_map(|key| top_level_block(&text, key))
+        .map(|block| format!("{block}\n")))
+}
+
+/// The text of the top-level `key = { ... }` entry in `text`, from the key
+/// to the closing brace.
+///
+/// Braces inside quoted strings and comments do not count towards nesting.
+fn top_level_block<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    let mut depth = 0usize;
+    let mut start = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '#' => while chars.next_if(|&(_, c)| c != '\n').is_some() {},
+            '"' => while chars.next().is_some_and(|(_, c)| c != '"') {},
+            '{' => depth += 1,
+            '}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    if let Some(start) = start {
+                        return Some(&text[start..=i]);
+                    }
+                }
+            }
+            c if depth == 0 && start.is_none() && !c.is_whitespace() => {
+                // A top-level word; it starts the block if it is the key
+                // followed by `= {`.
+                let word_end = text[i..]
+                    .find(|c: char| c.is_whitespace() || matches!(c, '=' | '{' | '}' | '#' | '"'))
+                    .map_or(text.len(), |end| i + end);
+                let rest = text[word_end..].trim_start();
+                if &text[i..word_end] == key
+                    && rest.starts_with('=')
+                    && rest[1..].trim_start().starts_with('{')
+                {
+                    start = Some(i);
+                }
+                while chars.next_if(|&(j, _)| j < word_end).is_some() {}
+            }
+            _ => {}
+        }
+    }
+    None
+}
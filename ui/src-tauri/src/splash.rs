@@ -0,0 +1,146 @@
+//! Splash window shown while the packaged engine boots.
+//!
+//! FastAPI takes several seconds to start, during which the main window would
+//! be an empty webview. Packaged builds keep the main window hidden and show a
+//! small splash (`public/splash.html`) with the boot stage instead: spawning
+//! the engine, waiting for it to answer on its port, then loading the app.
+//! The main window replaces it once the engine is ready and the app's page
+//! has loaded. If boot fails, the main window is shown behind the watchdog's
+//! error dialog instead (see [`crate::boot`]).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use sidecar_manager::EngineStatus;
+use tauri::webview::{PageLoadEvent, PageLoadPayload};
+use tauri::{AppHandle, Manager, Webview, WebviewUrl, WebviewWindowBuilder};
+
+use crate::health::EngineHealth;
+use crate::sidecar::Sidecar;
+
+const SPLASH_WINDOW: &str = "splash";
+const MAIN_WINDOW: &str = "main";
+/// Interval at which the boot stage is checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Stage {
+    Spawning,
+    WaitingForPort,
+    LoadingApp,
+}
+
+impl Stage {
+    fn text(self) -> &'static str {
+        match self {
+            Stage::Spawning => "Starting the engine...",
+            Stage::WaitingForPort => "Waiting for the engine to answer...",
+            Stage::LoadingApp => "Loading...",
+        }
+    }
+}
+
+/// Managed state recording which pages have finished loading.
+#[derive(Default)]
+pub struct Splash {
+    splash_loaded: AtomicBool,
+    main_loaded: AtomicBool,
+}
+
+/// Show the splash until the engine is up, then the main window.
+///
+/// In development the engine is started by run_ui.bat and the main window is
+/// shown straight away.
+pub fn start(app: &AppHandle) {
+    if cfg!(debug_assertions) {
+        show_main(app);
+        return;
+    }
+    let built =
+        WebviewWindowBuilder::new(app, SPLASH_WINDOW, WebviewUrl::App("splash.html".into()))
+            .title(app.package_info().name.clone())
+            .inner_size(420.0, 220.0)
+            .resizable(false)
+            .decorations(false)
+            .center()
+            .build();
+    if let Err(err) = built {
+        tracing::warn!("splash window disabled: {err}");
+        show_main(app);
+        return;
+    }
+    tauri::async_runtime::spawn(follow_boot(app.clone()));
+}
+
+/// Record finished page loads, passed to the builder's `on_page_load`.
+pub fn on_page_load(webview: &Webview, payload: &PageLoadPayload<'_>) {
+    if payload.event() != PageLoadEvent::Finished {
+        return;
+    }
+    let splash = webview.state::<Splash>();
+    match webview.label() {
+        SPLASH_WINDOW => splash.splash_loaded.store(true, Ordering::SeqCst),
+        MAIN_WINDOW => splash.main_loaded.store(true, Ordering::SeqCst),
+        _ => {}
+    }
+}
+
+async fn follow_boot(app: AppHandle) {
+    let splash = app.state::<Splash>();
+    let mut shown = None;
+    loop {
+        if app.get_webview_window(SPLASH_WINDOW).is_none() {
+            // Closed by a failed boot.
+            return;
+        }
+        let ready = matches!(
+            app.state::<EngineHealth>().status(),
+            EngineStatus::Ready | EngineStatus::Busy
+        );
+        let stage = if ready {
+            Stage::LoadingApp
+        } else if app.state::<Sidecar>().pid().is_some() {
+            Stage::WaitingForPort
+        } else {
+            Stage::Spawning
+        };
+        if ready && splash.main_loaded.load(Ordering::SeqCst) {
+            break;
+        }
+        if shown != Some(stage) && splash.splash_loaded.load(Ordering::SeqCst) {
+            show_stage(&app, stage);
+            shown = Some(stage);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    show_main(&app);
+    close(&app);
+}
+
+fn show_stage(app: &AppHandle, stage: Stage) {
+    let Some(window) = app.get_webview_window(SPLASH_WINDOW) else {
+        return;
+    };
+    let text = serde_json::to_string(stage.text()).expect("a string serializes");
+    let _ = window.eval(format!("setStage({text})"));
+}
+
+fn show_main(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn close(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(SPLASH_WINDOW) {
+        let _ = window.close();
+    }
+}
+
+/// Replace the splash with the main window after the engine failed to boot,
+/// so the app is not left without a window once the error is dismissed.
+pub fn boot_failed(app: &AppHandle) {
+    close(app);
+    show_main(app);
+}
@@ -0,0 +1,43 @@
+//! Resource usage of the engine.
+//!
+//! A background task samples the sidecar's CPU and memory every few seconds
+//! and emits them as `engine-stats` events, so users generating very large
+//! projects can tell an engine that is working apart from one that is
+//! swapping itself to death.
+
+use std::time::Duration;
+
+use sidecar_manager::ResourceMonitor;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::sidecar::Sidecar;
+
+/// Emitted with an [`sidecar_manager::EngineStats`] sample of the engine.
+pub const ENGINE_STATS_EVENT: &str = "engine-stats";
+
+/// Time between two samples.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Start sampling in the background.
+///
+/// Does nothing in development, where the engine is not a process the shell
+/// knows the PID of.
+pub fn start(app: &AppHandle) {
+    if cfg!(debug_assertions) {
+        return;
+    }
+    tauri::async_runtime::spawn(sample(app.clone()));
+}
+
+async fn sample(app: AppHandle) {
+    let mut monitor = ResourceMonitor::default();
+    loop {
+        tokio::time::sleep(SAMPLE_INTERVAL).await;
+        let Some(pid) = app.state::<Sidecar>().pid() else {
+            continue;
+        };
+        if let Some(stats) = monitor.sample(pid) {
+            let _ = app.emit(ENGINE_STATS_EVENT, stats);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Forwarding of the engine's classified stderr output.
+//!
+//! Every entry produced by [`sidecar_manager::Classifier`] is logged under the
+//! `engine` target at its level and forwarded to the frontend as an
+//! `engine-stderr` event. Warnings, errors and tracebacks are
+//! also appended to `engine-errors.log` in the app log directory so they
+//! survive the session.
+
+use sidecar_manager::{StderrEntry, StderrKind};
+use tauri::{AppHandle, Emitter};
+
+use crate::logs;
+
+/// Emitted with a [`StderrEntry`] for every classified stderr entry.
+pub const ENGINE_STDERR_EVENT: &str = "engine-stderr";
+
+/// File in the app log directory that collects warnings and errors.
+const ERROR_LOG_FILE: &str = "engine-errors.log";
+
+/// Forward an entry to the frontend and persist it if it is not routine output.
+pub fn record(app: &AppHandle, entry: StderrEntry) {
+    match entry.kind {
+        StderrKind::Info => tracing::info!(target: "engine", "{}", entry.message),
+        StderrKind::Warning => tracing::warn!(target: "engine", "{}", entry.message),
+        StderrKind::Error | StderrKind::Traceback => {
+            tracing::error!(target: "engine", "{}", entry.message)
+        }
+    }
+    if entry.is_problem() {
+        let appended =
+            logs::log_dir(app).and_then(|dir| entry.append_to(&dir.join(ERROR_LOG_FILE)));
+        if let Err(err) = appended {
+            tracing::warn!("failed to write {ERROR_LOG_FILE}: {err}");
+        }
+    }
+    let _ = app.emit(ENGINE_STDERR_EVENT, entry);
+}
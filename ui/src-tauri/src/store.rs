@@ -0,0 +1,47 @@
+//! Small JSON files the shell keeps between sessions.
+//!
+//! Settings live in the app config directory and app-managed state in the app
+//! data directory. A missing or unreadable file is treated as empty, so a
+//! corrupt file never keeps the app from starting.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+/// Path of the settings file `name` in the app config directory.
+pub fn config_file(app: &AppHandle, name: &str) -> io::Result<PathBuf> {
+    let dir = app.path().app_config_dir().map_err(io::Error::other)?;
+    Ok(dir.join(name))
+}
+
+/// Path of the state file `name` in the app data directory.
+pub fn data_file(app: &AppHandle, name: &str) -> io::Result<PathBuf> {
+    let dir = app.path().app_data_dir().map_err(io::Error::other)?;
+    Ok(dir.join(name))
+}
+
+/// Read `path`, falling back to the default if it is missing or invalid.
+pub fn load<T: DeserializeOwned + Default>(path: io::Result<PathBuf>) -> T {
+    let Ok(path) = path else {
+        return T::default();
+    };
+    let Ok(text) = fs::read_to_string(&path) else {
+        return T::default();
+    };
+    serde_json::from_str(&text).unwrap_or_else(|err| {
+        tracing::warn!("ignoring invalid {}: {err}", path.display());
+        T::default()
+    })
+}
+
+/// Write `value` to `path`, creating its directory if needed.
+pub fn save<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(value)?)
+}
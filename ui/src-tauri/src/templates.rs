@@ -0,0 +1,145 @@
+//! The library of dynasty templates.
+//!
+//! The templates bundled with `ck3-config` are listed first, followed by
+//! those the user saved, which are kept in `dynasty_templates.json` in the
+//! app data directory so that they serve every project. A user template
+//! can be saved over or deleted, a bundled one neither, and none may take a
+//! bundled template's name. Every template is checked as it is saved, so
+//! applying one to a valid dynasty gives a valid dynasty.
+
+use std::sync::Mutex;
+
+use ck3_config::{bundled_templates, ConfigError, DynastyTemplate};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tauri::{AppHandle, State};
+
+use crate::config::document_error;
+use crate::store;
+
+/// File in the app data directory holding the user's templates.
+const TEMPLATES_FILE: &str = "dynasty_templates.json";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateEntry {
+    #[serde(flatten)]
+    pub template: DynastyTemplate,
+    /// Whether it comes with the app rather than from the user.
+    pub bundled: bool,
+}
+
+/// Managed state holding the user's templates, loaded from disk on first
+/// use.
+#[derive(Default)]
+pub struct Templates(Mutex<Option<Vec<DynastyTemplate>>>);
+
+impl Templates {
+    /// Every template, the bundled ones first, then the user's by name.
+    fn list(&self, app: &AppHandle) -> Vec<TemplateEntry> {
+        let mut user = self.0.lock().unwrap();
+        let user = user.get_or_insert_with(|| store::load(store::data_file(app, TEMPLATES_FILE)));
+        let bundled = bundled_templates()
+            .into_iter()
+            .map(|template| TemplateEntry {
+                template,
+                bundled: true,
+            });
+        let user = user.iter().map(|template| TemplateEntry {
+            template: template.clone(),
+            bundled: false,
+        });
+        bundled.chain(user).collect()
+    }
+
+    /// Run `f` on the user's templates, then sort and save them, returning
+    /// every template.
+    fn update(
+        &self,
+        app: &AppHandle,
+        f: impl FnOnce(&mut Vec<DynastyTemplate>) -> Result<(), Vec<ConfigError>>,
+    ) -> Result<Vec<TemplateEntry>, Vec<ConfigError>> {
+        {
+            let mut user = self.0.lock().unwrap();
+            let user =
+                user.get_or_insert_with(|| store::load(store::data_file(app, TEMPLATES_FILE)));
+            f(user)?;
+            user.sort_by(|a, b| a.name.cmp(&b.name));
+            store::data_file(app, TEMPLATES_FILE)
+                .and_then(|path| store::save(&path, user))
+                .map_err(document_error)?;
+        }
+        Ok(self.list(app))
+    }
+}
+
+/// List the dynasty templates, the bundled ones first.
+#[tauri::command]
+pub fn list_dynasty_templates(
+    app: AppHandle,
+    templates: State<'_, Templates>,
+) -> Vec<TemplateEntry> {
+    templates.list(&app)
+}
+
+/// Return `dynasty` with the fields of the template `name` set.
+#[tauri::command]
+pub fn apply_dynasty_template(
+    app: AppHandle,
+    templates: State<'_, Templates>,
+    name: String,
+    dynasty: Map<String, Value>,
+) -> Result<Map<String, Value>, String> {
+    templates
+        .list(&app)
+        .into_iter()
+        .find(|entry| entry.template.name == name)
+        .map(|entry| entry.template.apply(&dynasty))
+        .ok_or_else(|| format!("there is no template named {name}"))
+}
+
+/// Save the fields of `dynasty` that are not its own as the user template
+/// `name`, replacing any of that name, and return every template.
+#[tauri::command]
+pub fn save_dynasty_template(
+    app: AppHandle,
+    templates: State<'_, Templates>,
+    name: String,
+    description: String,
+    dynasty: Map<String, Value>,
+) -> Result<Vec<TemplateEntry>, Vec<ConfigError>> {
+    let template = DynastyTemplate::from_dynasty(&name, &description, &dynasty)?;
+    if bundled_templates()
+        .iter()
+        .any(|bundled| bundled.name == template.name)
+    {
+        return Err(vec![ConfigError::new(
+            "name",
+            "is the name of a template bundled with the app",
+        )]);
+    }
+    templates.update(&app, |user| {
+        user.retain(|saved| saved.name != template.name);
+        user.push(template);
+        Ok(())
+    })
+}
+
+/// Delete the user template `name`, and return every template.
+#[tauri::command]
+pub fn delete_dynasty_template(
+    app: AppHandle,
+    templates: State<'_, Templates>,
+    name: String,
+) -> Result<Vec<TemplateEntry>, Vec<ConfigError>> {
+    templates.update(&app, |user| {
+        let count = user.len();
+        user.retain(|saved| saved.name != name);
+        if user.len() == count {
+            return Err(document_error(format!(
+                "there is no user template named {name}"
+            )));
+        }
+        Ok(())
+    })
+}
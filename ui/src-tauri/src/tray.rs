@@ -0,0 +1,165 @@
+//! System tray icon for generations running in the background.
+//!
+//! The tray shows how far the current generation is ("Generation: 43%", also
+//! in the tooltip) and can pause, resume or cancel it and bring the window
+//! back. Closing the main window while a generation runs hides it to the tray
+//! instead of quitting.
+
+use std::sync::Mutex;
+
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager, Window, WindowEvent};
+
+use crate::generation::{self, Generation, SimulationProgress};
+
+/// Name shown in front of the progress in the tooltip.
+const APP_NAME: &str = "CK3 Character History Generator";
+
+const PAUSE_ID: &str = "tray-pause";
+const CANCEL_ID: &str = "tray-cancel";
+const SHOW_ID: &str = "tray-show";
+const QUIT_ID: &str = "tray-quit";
+
+fn label(progress: &SimulationProgress) -> String {
+    let percent = progress.percent.unwrap_or(0.0).floor();
+    match (progress.running, progress.paused) {
+        (false, _) => "Generation: idle".into(),
+        (true, false) => format!("Generation: {percent}%"),
+        (true, true) => format!("Generation: paused at {percent}%"),
+    }
+}
+
+/// The menu items that change with the progress.
+#[derive(Clone)]
+struct TrayItems {
+    icon: TrayIcon,
+    status: MenuItem<tauri::Wry>,
+    pause: MenuItem<tauri::Wry>,
+    cancel: MenuItem<tauri::Wry>,
+}
+
+/// Managed state holding the tray once it has been created.
+#[derive(Default)]
+pub struct Tray {
+    items: Mutex<Option<TrayItems>>,
+}
+
+/// Create the tray icon.
+pub fn start(app: &AppHandle) {
+    match build(app) {
+        Ok(items) => *app.state::<Tray>().items.lock().unwrap() = Some(items),
+        Err(err) => tracing::warn!("tray icon disabled: {err}"),
+    }
+}
+
+fn build(app: &AppHandle) -> tauri::Result<TrayItems> {
+    let idle = label(&SimulationProgress::default());
+    let status = MenuItem::with_id(app, "tray-status", &idle, false, None::<&str>)?;
+    let pause = MenuItem::with_id(app, PAUSE_ID, "Pause", false, None::<&str>)?;
+    let cancel = MenuItem::with_id(app, CANCEL_ID, "Cancel", false, None::<&str>)?;
+    let show = MenuItem::with_id(app, SHOW_ID, "Show window", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, QUIT_ID, "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status,
+            &PredefinedMenuItem::separator(app)?,
+            &pause,
+            &cancel,
+            &PredefinedMenuItem::separator(app)?,
+            &show,
+            &quit,
+        ],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id("main")
+        .tooltip(format!("{APP_NAME} - {idle}"))
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(on_menu_event)
+        .on_tray_icon_event(|icon, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_window(icon.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    Ok(TrayItems {
+        icon: builder.build(app)?,
+        status,
+        pause,
+        cancel,
+    })
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    let app = app.clone();
+    match event.id().as_ref() {
+        PAUSE_ID => {
+            tauri::async_runtime::spawn(async move {
+                let paused = app.state::<Generation>().progress().paused;
+                let action = if paused { "resume" } else { "pause" };
+                if let Err(err) = generation::control(&app, action).await {
+                    tracing::warn!("failed to {action} the generation: {err}");
+                }
+            });
+        }
+        CANCEL_ID => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = generation::cancel(&app).await {
+                    tracing::warn!("failed to cancel the generation: {err}");
+                }
+            });
+        }
+        SHOW_ID => show_window(&app),
+        QUIT_ID => app.exit(0),
+        _ => {}
+    }
+}
+
+/// Bring the main window back from the tray or the taskbar.
+fn show_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Hide the main window instead of closing it while a generation runs.
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::CloseRequested { api, .. } = event {
+        let has_tray = window.state::<Tray>().items.lock().unwrap().is_some();
+        let running = window.state::<Generation>().progress().running;
+        if window.label() == "main" && has_tray && running {
+            api.prevent_close();
+            let _ = window.hide();
+        }
+    }
+}
+
+/// Show `progress` in the tray.
+pub fn show_progress(app: &AppHandle, progress: &SimulationProgress) {
+    // Menu changes are carried out on the main thread, which takes this lock
+    // when a window closes, so it must not be held across them.
+    let Some(items) = app.state::<Tray>().items.lock().unwrap().clone() else {
+        return;
+    };
+    let label = label(progress);
+    let _ = items.status.set_text(&label);
+    let _ = items
+        .pause
+        .set_text(if progress.paused { "Resume" } else { "Pause" });
+    let _ = items.pause.set_enabled(progress.running);
+    let _ = items.cancel.set_enabled(progress.running);
+    let _ = items
+        .icon
+        .set_tooltip(Some(format!("{APP_NAME} - {label}")));
+}
@@ -0,0 +1,122 @@
+//! Exporting the dynasty trees as Graphviz graphs.
+//!
+//! `export_family_trees` writes a DOT graph of each dynasty of the last
+//! export to a folder, and can have Graphviz's `dot`, which the family tree
+//! images already need, lay each out as an SVG beside it. The family tree
+//! images of a generation are drawn the same way, by
+//! [`draw_family_trees`].
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use ck3_sim::{TreeOptions, HISTORY_FILE};
+use tauri::AppHandle;
+
+use crate::output;
+
+/// Prefix of the family tree images of a generation, followed by the
+/// dynasty.
+const TREE_IMAGE_PREFIX: &str = "family_tree_";
+
+/// Write `<dynasty>.dot` to `dir` for each dynasty of the last export, and
+/// `<dynasty>.svg` too if `svg` is set, replacing any already there.
+///
+/// Resolves to the files written.
+#[tauri::command]
+pub async fn export_family_trees(
+    app: AppHandle,
+    dir: PathBuf,
+    options: TreeOptions,
+    svg: bool,
+) -> Result<Vec<PathBuf>, String> {
+    let character_dir = output::current_paths(&app).await?.character_dir;
+    if !character_dir.join(HISTORY_FILE).is_file() {
+        return Err("nothing has been generated yet".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let graphs =
+            ck3_sim::output_dot(&character_dir, &options).map_err(|err| err.to_string())?;
+        fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+        let mut written = Vec::new();
+        for (dynasty, graph) in graphs {
+            let path = dir.join(format!("{dynasty}.dot"));
+            fs::write(&path, graph).map_err(|err| err.to_string())?;
+            if svg {
+                let image = path.with_extension("svg");
+                render(&path, &image, "svg")?;
+                written.push(image);
+            }
+            written.push(path);
+        }
+        Ok(written)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Draw `family_tree_<dynasty>.png` in `tree_dir` for each dynasty of the
+/// history in `character_dir`, with its rulers filled in, replacing the
+/// images of an earlier generation.
+pub fn draw_family_trees(character_dir: &Path, tree_dir: &Path) -> Result<(), String> {
+    let options = TreeOptions {
+        highlight_rulers: true,
+        ..TreeOptions::default()
+    };
+    let graphs = ck3_sim::output_dot(character_dir, &options).map_err(|err| err.to_string())?;
+    fs::create_dir_all(tree_dir).map_err(|err| err.to_string())?;
+    // Left in place, the trees of dynasties no longer generated would still
+    // be shown.
+    for entry in fs::read_dir(tree_dir)
+        .map_err(|err| err.to_string())?
+        .flatten()
+    {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with(TREE_IMAGE_PREFIX) && name.ends_with(".png") {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+    for (dynasty, graph) in graphs {
+        let path = tree_dir.join(format!("{TREE_IMAGE_PREFIX}{dynasty}.dot"));
+        fs::write(&path, graph).map_err(|err| err.to_string())?;
+        let drawn = render(&path, &path.with_extension("png"), "png");
+        let _ = fs::remove_file(&path);
+        drawn?;
+    }
+    Ok(())
+}
+
+/// Lay out the DOT graph `path` as `image`, in the Graphviz output
+/// `format`.
+fn render(path: &Path, image: &Path, format: &str) -> Result<(), String> {
+    let mut command = Command::new("dot");
+    command
+        .arg(format!("-T{format}"))
+        .arg(path)
+        .arg("-o")
+        .arg(image);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        /// `CREATE_NO_WINDOW` process creation flag.
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().map_err(|err| {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            "Graphviz is not installed: install it and put its bin folder on PATH".to_string()
+        } else {
+            format!("failed to run Graphviz: {err}")
+        }
+    })?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Graphviz could not lay out {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
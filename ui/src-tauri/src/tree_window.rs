@@ -0,0 +1,170 @@
+//! Detached family tree windows.
+//!
+//! A dynasty's tree can be opened in a window of its own, so it stays visible
+//! while the config is edited in the main window. Each tree window loads the
+//! same frontend, which asks for its [`TreeView`] with `get_tree_view` to know
+//! what to render. The shell keeps the list of open tree windows, broadcasts
+//! it as `tree-windows-changed` whenever it changes, and emits `trees-updated`
+//! after a generation so open trees reload their image. Tree windows close
+//! with the main window.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{
+    AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder, Window,
+    WindowEvent,
+};
+
+/// Emitted to every window with the open [`TreeView`]s when one opens, closes
+/// or switches dynasty.
+pub const TREE_WINDOWS_EVENT: &str = "tree-windows-changed";
+/// Emitted to every window once a generation has written new tree images.
+pub const TREES_UPDATED_EVENT: &str = "trees-updated";
+
+/// Prefix of tree window labels, also matched by the app's capability.
+const LABEL_PREFIX: &str = "tree-";
+const MAIN_WINDOW: &str = "main";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeView {
+    /// Label of the window showing the tree.
+    pub label: String,
+    /// Dynasty as named in its `family_tree_<dynasty>.png` image.
+    pub dynasty: String,
+}
+
+/// Managed state listing the open tree windows.
+#[derive(Default)]
+pub struct TreeWindows {
+    views: Mutex<Vec<TreeView>>,
+    /// Number used in the label of the next window.
+    next: AtomicU32,
+}
+
+impl TreeWindows {
+    fn views(&self) -> Vec<TreeView> {
+        self.views.lock().unwrap().clone()
+    }
+}
+
+fn broadcast(app: &AppHandle) {
+    let views = app.state::<TreeWindows>().views();
+    let _ = app.emit(TREE_WINDOWS_EVENT, views);
+}
+
+/// Tell open tree windows that a generation has replaced the images.
+pub fn trees_updated(app: &AppHandle) {
+    let _ = app.emit(TREES_UPDATED_EVENT, ());
+}
+
+/// Forget tree windows once they are gone, and close them with the main one.
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if !matches!(event, WindowEvent::Destroyed) {
+        return;
+    }
+    let app = window.app_handle();
+    if window.label() == MAIN_WINDOW {
+        for view in app.state::<TreeWindows>().views() {
+            if let Some(tree) = app.get_webview_window(&view.label) {
+                let _ = tree.close();
+            }
+        }
+        return;
+    }
+    if !window.label().starts_with(LABEL_PREFIX) {
+        return;
+    }
+    app.state::<TreeWindows>()
+        .views
+        .lock()
+        .unwrap()
+        .retain(|view| view.label != window.label());
+    broadcast(app);
+}
+
+/// Open a window showing the tree of `dynasty`, or bring back the one that
+/// already does. Resolves to the window's label.
+// Async so the window is not created on the main thread, which deadlocks on
+// Windows.
+#[tauri::command]
+pub async fn open_tree_window(app: AppHandle, dynasty: String) -> Result<String, String> {
+    let windows = app.state::<TreeWindows>();
+    let existing = windows
+        .views()
+        .into_iter()
+        .find(|view| view.dynasty == dynasty);
+    if let Some(window) = existing.and_then(|view| app.get_webview_window(&view.label)) {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+        return Ok(window.label().to_string());
+    }
+
+    let label = format!(
+        "{LABEL_PREFIX}{}",
+        windows.next.fetch_add(1, Ordering::SeqCst) + 1
+    );
+    // Listed before the window exists, so its first `get_tree_view` finds it.
+    windows.views.lock().unwrap().push(TreeView {
+        label: label.clone(),
+        dynasty: dynasty.clone(),
+    });
+    let built = WebviewWindowBuilder::new(&app, &label, WebviewUrl::default())
+        .title(title(&dynasty))
+        .inner_size(900.0, 700.0)
+        .min_inner_size(400.0, 300.0)
+        .resizable(true)
+        .build();
+    if let Err(err) = built {
+        windows
+            .views
+            .lock()
+            .unwrap()
+            .retain(|view| view.label != label);
+        return Err(err.to_string());
+    }
+    broadcast(&app);
+    Ok(label)
+}
+
+fn title(dynasty: &str) -> String {
+    format!("Family tree of {dynasty}")
+}
+
+/// Return what the calling tree window shows, or `None` for other windows.
+#[tauri::command]
+pub fn get_tree_view(window: WebviewWindow, windows: State<'_, TreeWindows>) -> Option<TreeView> {
+    windows
+        .views()
+        .into_iter()
+        .find(|view| view.label == window.label())
+}
+
+/// Switch the calling tree window to another dynasty.
+#[tauri::command]
+pub fn set_tree_dynasty(
+    app: AppHandle,
+    window: WebviewWindow,
+    dynasty: String,
+) -> Result<(), String> {
+    let windows = app.state::<TreeWindows>();
+    {
+        let mut views = windows.views.lock().unwrap();
+        let view = views
+            .iter_mut()
+            .find(|view| view.label == window.label())
+            .ok_or("only a tree window can switch dynasty")?;
+        view.dynasty.clone_from(&dynasty);
+    }
+    let _ = window.set_title(&title(&dynasty));
+    broadcast(&app);
+    Ok(())
+}
+
+/// Return the open tree windows.
+#[tauri::command]
+pub fn list_tree_windows(windows: State<'_, TreeWindows>) -> Vec<TreeView> {
+    windows.views()
+}
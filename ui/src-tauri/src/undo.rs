@@ -0,0 +1,371 @@
+//! Undoing and redoing edits.
+//!
+//! Every change the UI makes to a config file, and every character edited
+//! by hand with `edit_character`, is recorded as an [`Edit`] that knows how
+//! to make itself and take itself back. `undo` takes back the last edit and
+//! `redo` makes again the last one taken back; any new edit forgets those
+//! taken back. Config edits hold the top-level values of the file they
+//! changed, before and after, and undoing or redoing one hands them to the
+//! UI, which keeps the config being edited until it is saved. Character
+//! edits change the latest history and its run in the project database
+//! themselves. Changes to the same values of a file in quick succession,
+//! such as the keystrokes of a number being typed, are one edit.
+//!
+//! The history, up to the last [`MAX_EDITS`] edits, is kept in the project
+//! database under the `undoHistory` setting, so it is there again when the
+//! project is, and travels with its `.ck3hist` files. Every change of it is
+//! sent to the frontend as an `undo-changed` event.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::output::{self, EnginePaths};
+use crate::project_db;
+
+/// Emitted with the [`UndoState`] whenever the history changes.
+pub const UNDO_CHANGED_EVENT: &str = "undo-changed";
+
+/// Project setting the history is kept under.
+pub const HISTORY_SETTING: &str = "undoHistory";
+
+/// Most edits that can be undone.
+const MAX_EDITS: usize = 100;
+
+/// Changes to the same values of a file less than this apart are one edit.
+const MERGE_WITHIN_MS: u64 = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigFile {
+    Initialization,
+    LifeStages,
+}
+
+/// One edit, as it is recorded.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Edit {
+    /// Top-level values of a config file, null where there was none.
+    #[serde(rename_all = "camelCase")]
+    Config {
+        file: ConfigFile,
+        before: Map<String, Value>,
+        after: Map<String, Value>,
+        /// Milliseconds since the Unix epoch.
+        at: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Character {
+        id: String,
+        field: String,
+        before: String,
+        after: String,
+    },
+}
+
+/// What undoing or redoing an edit leaves for the UI to apply.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Step {
+    /// Set these top-level values of the file being edited, removing those
+    /// that are null.
+    Config {
+        file: ConfigFile,
+        values: Map<String, Value>,
+    },
+    /// The character was edited already; only what is shown of them needs
+    /// reading again.
+    Character {
+        id: String,
+        field: String,
+        value: String,
+    },
+}
+
+impl Edit {
+    /// Make the edit, or take it back if `undo`.
+    fn apply(&self, app: &AppHandle, paths: &EnginePaths, undo: bool) -> Result<Step, String> {
+        match self {
+            Edit::Config {
+                file,
+                before,
+                after,
+                ..
+            } => Ok(Step::Config {
+                file: *file,
+                values: if undo { before } else { after }.clone(),
+            }),
+            Edit::Character {
+                id,
+                field,
+                before,
+                after,
+            } => {
+                let value = if undo { before } else { after };
+                edit(app, paths, id, field, value)?;
+                Ok(Step::Character {
+                    id: id.clone(),
+                    field: field.clone(),
+                    value: value.clone(),
+                })
+            }
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Edit::Config { after, .. } => {
+                let keys: Vec<&str> = after.keys().map(String::as_str).collect();
+                format!("change of {}", keys.join(", "))
+            }
+            Edit::Character { id, field, .. } => format!("{field} of {id}"),
+        }
+    }
+}
+
+/// What can be undone and redone, for the UI.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoState {
+    /// What undoing would take back, if there is anything.
+    pub undo: Option<String>,
+    pub redo: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct History {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+}
+
+impl History {
+    fn state(&self) -> UndoState {
+        UndoState {
+            undo: self.undo.last().map(Edit::label),
+            redo: self.redo.last().map(Edit::label),
+        }
+    }
+
+    fn push(&mut self, edit: Edit) {
+        self.redo.clear();
+        if let (
+            Some(Edit::Config {
+                file: last_file,
+                after: last_after,
+                at: last_at,
+                ..
+            }),
+            Edit::Config {
+                file, after, at, ..
+            },
+        ) = (self.undo.last_mut(), &edit)
+        {
+            if last_file == file
+                && at.saturating_sub(*last_at) < MERGE_WITHIN_MS
+                && last_after.keys().eq(after.keys())
+            {
+                last_after.clone_from(after);
+                *last_at = *at;
+                return;
+            }
+        }
+        self.undo.push(edit);
+        if self.undo.len() > MAX_EDITS {
+            self.undo.remove(0);
+        }
+    }
+}
+
+/// Managed state holding the history of the project last used, and its
+/// config directory.
+#[derive(Default)]
+pub struct UndoHistory(Mutex<Option<(PathBuf, History)>>);
+
+/// Forget the history held, so that it is read from the project database
+/// again, as after a project is opened over the current one.
+pub fn forget(app: &AppHandle) {
+    *app.state::<UndoHistory>().0.lock().unwrap() = None;
+}
+
+/// Run `f` on the history of the current project, reading it first if the
+/// one held is another project's. Blocks.
+fn with_history<T>(
+    app: &AppHandle,
+    paths: &EnginePaths,
+    f: impl FnOnce(&mut History) -> Result<T, String>,
+) -> Result<T, String> {
+    let held = app.state::<UndoHistory>();
+    let mut held = held.0.lock().unwrap();
+    if held
+        .as_ref()
+        .is_none_or(|(dir, _)| *dir != paths.config_dir)
+    {
+        let history = project_db::setting(app, &paths.config_dir, HISTORY_SETTING)?
+            // A history this version cannot read is started over.
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default();
+        *held = Some((paths.config_dir.clone(), history));
+    }
+    let (_, history) = held.as_mut().expect("the history was just read");
+    f(history)
+}
+
+/// Keep the changed `history` in the project database and announce it.
+fn keep(app: &AppHandle, paths: &EnginePaths, history: &History) -> Result<(), String> {
+    let value = serde_json::to_value(history).map_err(|err| err.to_string())?;
+    project_db::set_setting(app, &paths.config_dir, HISTORY_SETTING, &value)?;
+    let _ = app.emit(UNDO_CHANGED_EVENT, history.state());
+    Ok(())
+}
+
+/// Run `f` with the current project's paths, off the async runtime.
+async fn blocking<T: Send + 'static>(
+    app: AppHandle,
+    f: impl FnOnce(&AppHandle, &EnginePaths) -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    let paths = output::current_paths(&app).await?;
+    tauri::async_runtime::spawn_blocking(move || f(&app, &paths))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+/// Edit the character in the latest history and its recorded run.
+fn edit(
+    app: &AppHandle,
+    paths: &EnginePaths,
+    id: &str,
+    field: &str,
+    value: &str,
+) -> Result<String, String> {
+    let old = ck3_sim::edit_character(&paths.character_dir, id, field, value)
+        .map_err(|err| err.to_string())?;
+    project_db::edit_character(app, &paths.config_dir, id, field, value)?;
+    Ok(old)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as u64)
+}
+
+/// Record that the UI changed the config file `file` from `before` to
+/// `after`.
+#[tauri::command]
+pub async fn record_config_edit(
+    app: AppHandle,
+    file: ConfigFile,
+    before: Map<String, Value>,
+    after: Map<String, Value>,
+) -> Result<(), String> {
+    let mut changed_before = Map::new();
+    let mut changed_after = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        let old = before.get(key).unwrap_or(&Value::Null);
+        let new = after.get(key).unwrap_or(&Value::Null);
+        if old != new && !changed_after.contains_key(key) {
+            changed_before.insert(key.clone(), old.clone());
+            changed_after.insert(key.clone(), new.clone());
+        }
+    }
+    if changed_after.is_empty() {
+        return Ok(());
+    }
+    let edit = Edit::Config {
+        file,
+        before: changed_before,
+        after: changed_after,
+        at: now_ms(),
+    };
+    blocking(app, move |app, paths| {
+        with_history(app, paths, |history| {
+            history.push(edit);
+            keep(app, paths, history)
+        })
+    })
+    .await
+}
+
+/// Set the field `field` of the character `id` of the latest history to
+/// `value`, as an edit that can be undone.
+#[tauri::command]
+pub async fn edit_character(
+    app: AppHandle,
+    id: String,
+    field: String,
+    value: String,
+) -> Result<(), String> {
+    blocking(app, move |app, paths| {
+        with_history(app, paths, |history| {
+            let before = edit(app, paths, &id, &field, &value)?;
+            if before != value {
+                history.push(Edit::Character {
+                    id,
+                    field,
+                    before,
+                    after: value,
+                });
+                keep(app, paths, history)?;
+            }
+            Ok(())
+        })
+    })
+    .await
+}
+
+/// Take back the last edit, if there is one.
+///
+/// An edit that cannot be taken back, such as one of a character no longer
+/// in the history, is forgotten and the error returned.
+#[tauri::command]
+pub async fn undo(app: AppHandle) -> Result<Option<Step>, String> {
+    blocking(app, |app, paths| {
+        with_history(app, paths, |history| {
+            let Some(edit) = history.undo.pop() else {
+                return Ok(None);
+            };
+            let step = edit.apply(app, paths, true);
+            if step.is_ok() {
+                history.redo.push(edit);
+            }
+            keep(app, paths, history)?;
+            step.map(Some)
+        })
+    })
+    .await
+}
+
+/// Make again the last edit taken back, if there is one.
+///
+/// An edit that cannot be made again is forgotten and the error returned.
+#[tauri::command]
+pub async fn redo(app: AppHandle) -> Result<Option<Step>, String> {
+    blocking(app, |app, paths| {
+        with_history(app, paths, |history| {
+            let Some(edit) = history.redo.pop() else {
+                return Ok(None);
+            };
+            let step = edit.apply(app, paths, false);
+            if step.is_ok() {
+                history.undo.push(edit);
+            }
+            keep(app, paths, history)?;
+            step.map(Some)
+        })
+    })
+    .await
+}
+
+/// What can be undone and redone in the current project.
+#[tauri::command]
+pub async fn get_undo_state(app: AppHandle) -> Result<UndoState, String> {
+    blocking(app, |app, paths| {
+        with_history(app, paths, |history| Ok(history.state()))
+    })
+    .await
+}
@@ -0,0 +1,192 @@
+//! Updates of the packaged app.
+//!
+//! Packaged builds check the release feed named in `tauri.conf.json` shortly
+//! after launch and offer to install a newer version. Updates are signed: the
+//! release pipeline signs the installers with its private key and this build
+//! verifies them against the public key it was compiled with, taken from the
+//! `CK3GEN_UPDATER_PUBKEY` environment variable. Builds made without one never
+//! look for updates.
+//!
+//! The engine sidecar ships inside the installer, so an update replaces the
+//! shell and the engine together and they cannot end up at different
+//! versions. The engine is stopped before the installer runs so none of its
+//! files are locked. The feed serves full installers; the
+//! Tauri updater has no delta updates.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_updater::{Update, Updater, UpdaterExt};
+
+use crate::sidecar;
+
+/// Emitted with an [`UpdateProgress`] while an update downloads.
+pub const UPDATE_PROGRESS_EVENT: &str = "update-progress";
+
+/// Public key update signatures are checked against, set at build time.
+const PUBKEY: Option<&str> = option_env!("CK3GEN_UPDATER_PUBKEY");
+/// Delay before the launch check, so it does not compete with the engine boot.
+const CHECK_DELAY: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    /// Release notes.
+    pub notes: Option<String>,
+}
+
+impl From<&Update> for UpdateInfo {
+    fn from(update: &Update) -> Self {
+        Self {
+            version: update.version.clone(),
+            current_version: update.current_version.clone(),
+            notes: update.body.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProgress {
+    pub downloaded: u64,
+    /// Size of the download, if the server announced it.
+    pub total: Option<u64>,
+}
+
+/// Managed state holding the last update found, until it is installed.
+#[derive(Default)]
+pub struct PendingUpdate(Mutex<Option<Update>>);
+
+/// The updater plugin, verifying against the compiled-in key if there is one.
+pub fn plugin() -> TauriPlugin<tauri::Wry, tauri_plugin_updater::Config> {
+    let builder = tauri_plugin_updater::Builder::new();
+    match PUBKEY {
+        Some(pubkey) => builder.pubkey(pubkey),
+        None => builder,
+    }
+    .build()
+}
+
+fn updater(app: &AppHandle) -> Result<Updater, String> {
+    if cfg!(debug_assertions) {
+        return Err("updates are only available in the packaged app".into());
+    }
+    if PUBKEY.is_none() {
+        return Err("this build was made without an update signing key".into());
+    }
+    app.updater().map_err(|err| err.to_string())
+}
+
+/// Check for an update in the background and offer to install it.
+///
+/// Does nothing in development or in builds that cannot verify updates.
+pub fn start(app: &AppHandle) {
+    if cfg!(debug_assertions) || PUBKEY.is_none() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(CHECK_DELAY).await;
+        match check(&app).await {
+            Ok(Some(update)) => {
+                if prompt(&app, &update).await {
+                    if let Err(err) = install(&app, update).await {
+                        tracing::error!("failed to install the update: {err}");
+                    }
+                }
+            }
+            Ok(None) => {}
+            Err(err) => tracing::warn!("update check failed: {err}"),
+        }
+    });
+}
+
+async fn check(app: &AppHandle) -> Result<Option<Update>, String> {
+    let update = updater(app)?.check().await.map_err(|err| err.to_string())?;
+    app.state::<PendingUpdate>()
+        .0
+        .lock()
+        .unwrap()
+        .clone_from(&update);
+    Ok(update)
+}
+
+/// Ask whether to install `update` now.
+async fn prompt(app: &AppHandle, update: &Update) -> bool {
+    let mut message = format!(
+        "Version {} is available; you have {}.",
+        update.version, update.current_version
+    );
+    if let Some(notes) = update.body.as_deref().filter(|notes| !notes.is_empty()) {
+        message.push_str("\n\n");
+        message.push_str(notes);
+    }
+    message.push_str("\n\nInstall it now? The app will restart.");
+    let dialog = app
+        .dialog()
+        .message(message)
+        .title("Update available")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Install and restart".into(),
+            "Later".into(),
+        ));
+    tauri::async_runtime::spawn_blocking(move || dialog.blocking_show())
+        .await
+        .unwrap_or(false)
+}
+
+/// Download and verify `update`, stop the engine and run the installer,
+/// restarting on the new version once it is installed.
+async fn install(app: &AppHandle, update: Update) -> Result<(), String> {
+    let mut downloaded = 0;
+    let bytes = update
+        .download(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                let _ = app.emit(UPDATE_PROGRESS_EVENT, UpdateProgress { downloaded, total });
+            },
+            || {},
+        )
+        .await
+        .map_err(|err| err.to_string())?;
+
+    // The installer replaces the sidecar binary, which must not be running.
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        sidecar::shutdown(&handle);
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+
+    if let Err(err) = update.install(bytes) {
+        // The app carries on on this version, so it needs its engine back.
+        sidecar::resume(app).await;
+        return Err(err.to_string());
+    }
+    // Come back on the new version. On Windows the installer has already
+    // taken over and this is not reached.
+    app.restart()
+}
+
+/// Check the release feed for a newer version.
+///
+/// Resolves to `None` if this is the latest version.
+#[tauri::command]
+pub async fn check_for_update(app: AppHandle) -> Result<Option<UpdateInfo>, String> {
+    Ok(check(&app).await?.as_ref().map(UpdateInfo::from))
+}
+
+/// Install the update found by the last check, then restart.
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<(), String> {
+    let update = app.state::<PendingUpdate>().0.lock().unwrap().take();
+    let update = update.ok_or("no update is pending; check for one first")?;
+    install(&app, update).await
+}
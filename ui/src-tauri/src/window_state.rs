@@ -0,0 +1,158 @@
+//! Main window geometry across sessions.
+//!
+//! The size, position and maximized state of the main window are saved to
+//! `window_state.json` in the app config directory when it closes and restored
+//! at launch. The window is created hidden and only shown once restored (by
+//! [`crate::splash`]), so it does not jump from its default place. A saved position is dropped if it
+//! would leave the title bar off every connected monitor, as happens when the
+//! window was last on a monitor that has since been unplugged.
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{
+    AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow, Window, WindowEvent,
+};
+
+use crate::store;
+
+/// File in the app config directory holding the geometry.
+const WINDOW_STATE_FILE: &str = "window_state.json";
+/// Label of the window configured in `tauri.conf.json`.
+const MAIN_WINDOW: &str = "main";
+/// Height of the strip along the top of the window that must be on screen
+/// for it to be dragged back.
+const TITLE_BAR_HEIGHT: i32 = 32;
+/// Width of that strip that must be on screen.
+const MIN_VISIBLE_WIDTH: i32 = 100;
+
+/// Geometry of the main window in physical pixels.
+#[derive(Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Geometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedState {
+    /// Geometry while not maximized, so un-maximizing after a restore goes
+    /// back to the size the user chose.
+    geometry: Option<Geometry>,
+    maximized: bool,
+    /// Name of the monitor the window was on.
+    monitor: Option<String>,
+}
+
+/// Managed state tracking the main window's last normal geometry.
+#[derive(Default)]
+pub struct WindowState {
+    normal: Mutex<Option<Geometry>>,
+}
+
+/// Restore the saved geometry of the main window, which stays hidden.
+pub fn restore(app: &AppHandle) {
+    let Some(window) = app.get_webview_window(MAIN_WINDOW) else {
+        return;
+    };
+    let saved: SavedState = store::load(store::config_file(app, WINDOW_STATE_FILE));
+    if let Some(geometry) = saved.geometry.and_then(|geometry| {
+        let monitors = window.available_monitors().unwrap_or_default();
+        fit(geometry, saved.monitor.as_deref(), &monitors)
+    }) {
+        let _ = window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+        let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+        *app.state::<WindowState>().normal.lock().unwrap() = Some(geometry);
+    }
+    if saved.maximized {
+        let _ = window.maximize();
+    }
+}
+
+/// Track the normal geometry of the main window and save it on close.
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if window.label() != MAIN_WINDOW {
+        return;
+    }
+    let Some(window) = window.get_webview_window(MAIN_WINDOW) else {
+        return;
+    };
+    match event {
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
+            let maximized = window.is_maximized().unwrap_or(false);
+            let minimized = window.is_minimized().unwrap_or(false);
+            if !maximized && !minimized {
+                if let Some(geometry) = geometry(&window) {
+                    let state = window.state::<WindowState>();
+                    *state.normal.lock().unwrap() = Some(geometry);
+                }
+            }
+        }
+        WindowEvent::CloseRequested { .. } => save(&window),
+        _ => {}
+    }
+}
+
+fn save(window: &WebviewWindow) {
+    let maximized = window.is_maximized().unwrap_or(false);
+    let normal = *window.state::<WindowState>().normal.lock().unwrap();
+    let state = SavedState {
+        geometry: normal.or_else(|| geometry(window)),
+        maximized,
+        monitor: window
+            .current_monitor()
+            .ok()
+            .flatten()
+            .and_then(|monitor| monitor.name().cloned()),
+    };
+    let app = window.app_handle();
+    if let Err(err) =
+        store::config_file(app, WINDOW_STATE_FILE).and_then(|path| store::save(&path, &state))
+    {
+        tracing::warn!("failed to save {WINDOW_STATE_FILE}: {err}");
+    }
+}
+
+fn geometry(window: &WebviewWindow) -> Option<Geometry> {
+    let position = window.outer_position().ok()?;
+    let size = window.inner_size().ok()?;
+    Some(Geometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+/// Fit `geometry` onto the connected monitors.
+///
+/// The monitor the window was last on is preferred, then any monitor that
+/// shows its title bar. The size is shrunk to fit that monitor's work area.
+/// Returns `None` if no monitor would show the title bar, in which case the
+/// window keeps the default geometry from the config.
+fn fit(geometry: Geometry, monitor: Option<&str>, monitors: &[Monitor]) -> Option<Geometry> {
+    let shows_title_bar = |candidate: &&Monitor| {
+        let area = candidate.work_area();
+        let (left, top) = (area.position.x, area.position.y);
+        let right = left + area.size.width as i32;
+        let bottom = top + area.size.height as i32;
+        let visible_width = (geometry.x + geometry.width as i32).min(right) - geometry.x.max(left);
+        let visible_height = (geometry.y + TITLE_BAR_HEIGHT).min(bottom) - geometry.y.max(top);
+        visible_width >= MIN_VISIBLE_WIDTH && visible_height > 0
+    };
+    let target = monitors
+        .iter()
+        .filter(|candidate| candidate.name().map(String::as_str) == monitor)
+        .find(shows_title_bar)
+        .or_else(|| monitors.iter().find(shows_title_bar))?;
+
+    let area = target.work_area();
+    Some(Geometry {
+        width: geometry.width.min(area.size.width),
+        height: geometry.height.min(area.size.height),
+        ..geometry
+    })
+}
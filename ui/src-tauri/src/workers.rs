@@ -0,0 +1,58 @@
+//! The worker count of parallel generation.
+//!
+//! Large multi-dynasty projects are slow to simulate one dynasty after
+//! another. `run_parallel_simulation` generates the project natively with
+//! each dynasty simulated on a thread of its own, as many at a time as the
+//! worker count allows, and only marries members of different dynasties
+//! once all of them are done. Its progress and messages are passed on like
+//! those of any generation.
+
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager, State};
+
+use crate::generation;
+
+/// Upper bound accepted by `set_worker_count`.
+const MAX_WORKERS: usize = 16;
+
+/// Managed state holding the worker count.
+pub struct WorkerPool {
+    /// Number of threads a parallel run simulates dynasties on.
+    count: Mutex<usize>,
+}
+
+impl Default for WorkerPool {
+    fn default() -> Self {
+        Self {
+            count: Mutex::new(1),
+        }
+    }
+}
+
+/// Return the number of threads parallel runs simulate dynasties on.
+#[tauri::command]
+pub fn get_worker_count(pool: State<'_, WorkerPool>) -> usize {
+    *pool.count.lock().unwrap()
+}
+
+/// Set the number of threads parallel runs simulate dynasties on, from the
+/// next run.
+#[tauri::command]
+pub fn set_worker_count(pool: State<'_, WorkerPool>, count: usize) -> Result<(), String> {
+    if !(1..=MAX_WORKERS).contains(&count) {
+        return Err(format!("worker count must be between 1 and {MAX_WORKERS}"));
+    }
+    *pool.count.lock().unwrap() = count;
+    Ok(())
+}
+
+/// Generate the project natively with its dynasties simulated in parallel
+/// across the workers, passing on its log lines and how it ended as
+/// `simulation-message` events. Resolves once the run has ended, and fails
+/// if it could not be finished, in which case nothing is written.
+#[tauri::command]
+pub async fn run_parallel_simulation(app: AppHandle) -> Result<(), String> {
+    let count = *app.state::<WorkerPool>().count.lock().unwrap();
+    generation::run_native(&app, Some(count)).await
+}
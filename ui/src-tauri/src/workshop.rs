@@ -0,0 +1,252 @@
+//! Packaging the generated mod for the Steam Workshop.
+//!
+//! `package_for_workshop` scaffolds the mod into a folder of its own, as
+//! `scaffold_mod` does, and checks what the Workshop is fussy about: a PNG
+//! preview under 1 MB, square as the launcher shows it, and tags the
+//! launcher knows, which are the only ones it lets players filter by. Next to
+//! the folder it writes `<folder>.workshop.vdf`, the item description
+//! `steamcmd +workshop_build_item` uploads from, keeping the published file
+//! ID of an earlier upload so that the next one updates the same item. Given
+//! a Steam account that `steamcmd` on the PATH has logged in before, it also
+//! uploads the item, hidden until the author makes it public on Steam.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use ck3_config::ModSettings;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::scaffold::{self, THUMBNAIL_FILE};
+use crate::{game_paths, output};
+
+/// Steam app ID of Crusader Kings III.
+const APP_ID: &str = "1158310";
+/// Largest preview the Workshop accepts, in bytes.
+const MAX_PREVIEW_BYTES: u64 = 1024 * 1024;
+/// Signature every PNG file starts with.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// Visibility of a new upload: hidden, so the author can check the item page
+/// before anyone else sees it.
+const HIDDEN: &str = "2";
+/// The tags the launcher offers for CK3 mods.
+const LAUNCHER_TAGS: [&str; 24] = [
+    "Alternative History",
+    "Balance",
+    "Bookmarks",
+    "Character Focus",
+    "Character Interactions",
+    "Culture",
+    "Decisions",
+    "Events",
+    "Fixes",
+    "Gameplay",
+    "Graphics",
+    "Historical",
+    "Loading Screen",
+    "Map",
+    "Models",
+    "Music",
+    "Portraits",
+    "Religion",
+    "Schemes",
+    "Sound",
+    "Total Conversion",
+    "Translation",
+    "Utilities",
+    "Warfare",
+];
+
+/// What `package_for_workshop` did.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkshopPackage {
+    /// The upload-ready folder.
+    pub dir: PathBuf,
+    pub written: Vec<PathBuf>,
+    /// The `steamcmd` item description.
+    pub vdf: PathBuf,
+    /// What the Workshop or the launcher would take issue with.
+    pub warnings: Vec<String>,
+    /// The item's ID on the Workshop, once it has been uploaded.
+    pub published_file_id: Option<String>,
+    pub uploaded: bool,
+}
+
+/// Build the mod from the last export in `dir`, check it for the Workshop
+/// and write its `steamcmd` item description, uploading it with the Steam
+/// account `steam_user` if given.
+///
+/// Nothing is uploaded if the package has warnings.
+#[tauri::command]
+pub async fn package_for_workshop(
+    app: AppHandle,
+    dir: PathBuf,
+    steam_user: Option<String>,
+) -> Result<WorkshopPackage, String> {
+    let paths = output::current_paths(&app).await?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let settings = scaffold::mod_settings(&paths, &scaffold::folder_name(&dir))?;
+        fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+        let written = scaffold::scaffold(&paths.character_dir, &dir, &settings)
+            .map_err(|err| err.to_string())?;
+        let dir = dir.canonicalize().map_err(|err| err.to_string())?;
+
+        let preview = dir.join(THUMBNAIL_FILE);
+        let mut warnings = check_preview(&preview);
+        warnings.extend(
+            settings
+                .tags
+                .iter()
+                .filter(|tag| !LAUNCHER_TAGS.contains(&tag.as_str()))
+                .map(|tag| format!("the launcher has no tag '{tag}'")),
+        );
+        if settings.tags.is_empty() {
+            warnings.push("the mod has no tags to be found by".into());
+        }
+
+        let vdf = dir.with_file_name(format!("{}.workshop.vdf", scaffold::folder_name(&dir)));
+        let published = fs::read_to_string(&vdf)
+            .ok()
+            .and_then(|text| vdf_value(&text, "publishedfileid"))
+            .filter(|id| id != "0");
+        let item = item_vdf(&settings, &dir, &preview, published.as_deref());
+        fs::write(&vdf, item).map_err(|err| format!("cannot write {}: {err}", vdf.display()))?;
+
+        let mut package = WorkshopPackage {
+            dir,
+            written,
+            vdf,
+            warnings,
+            published_file_id: published,
+            uploaded: false,
+        };
+        if let Some(user) = steam_user.filter(|user| !user.trim().is_empty()) {
+            if !package.warnings.is_empty() {
+                return Err(format!(
+                    "not uploaded, since {}",
+                    package.warnings.join("; ")
+                ));
+            }
+            upload(&package.vdf, user.trim())?;
+            // `steamcmd` writes the ID of a new item back into the file.
+            package.published_file_id = fs::read_to_string(&package.vdf)
+                .ok()
+                .and_then(|text| vdf_value(&text, "publishedfileid"))
+                .filter(|id| id != "0");
+            package.uploaded = true;
+        }
+        Ok(package)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// What the Workshop would refuse, or the launcher show badly, about the
+/// preview image `path`.
+fn check_preview(path: &Path) -> Vec<String> {
+    let Ok(bytes) = fs::read(path) else {
+        return vec![format!("{THUMBNAIL_FILE} is missing")];
+    };
+    let mut warnings = Vec::new();
+    if bytes.len() as u64 >= MAX_PREVIEW_BYTES {
+        warnings.push(format!(
+            "{THUMBNAIL_FILE} is {} KB, and the Workshop takes previews under 1 MB",
+            bytes.len() / 1024
+        ));
+    }
+    // The width and height open the IHDR chunk right after the signature.
+    let size = |at: usize| {
+        bytes
+            .get(at..at + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    if !bytes.starts_with(PNG_SIGNATURE) || bytes.get(12..16) != Some(b"IHDR") {
+        warnings.push(format!("{THUMBNAIL_FILE} is not a PNG image"));
+    } else if let (Some(width), Some(height)) = (size(16), size(20)) {
+        if width != height {
+            warnings.push(format!(
+                "{THUMBNAIL_FILE} is {width}×{height}, and the launcher crops previews to a square"
+            ));
+        }
+    }
+    warnings
+}
+
+/// The `steamcmd` description of the item in the folder `dir`, previewed by
+/// `preview`, updating the item `published` if it was uploaded before. The
+/// description on the item page is left as the author writes it there.
+fn item_vdf(settings: &ModSettings, dir: &Path, preview: &Path, published: Option<&str>) -> String {
+    let quoted = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+    let path = |path: &Path| {
+        let path = path.to_string_lossy();
+        quoted(path.strip_prefix(r"\\?\").unwrap_or(&path))
+    };
+    let mut fields = vec![("appid", quoted(APP_ID))];
+    match published {
+        Some(id) => fields.push(("publishedfileid", quoted(id))),
+        None => fields.push(("visibility", quoted(HIDDEN))),
+    }
+    fields.extend([
+        ("contentfolder", path(dir)),
+        ("previewfile", path(preview)),
+        ("title", quoted(&settings.name)),
+        (
+            "changenote",
+            quoted(&format!("Version {}", settings.version)),
+        ),
+    ]);
+    let mut text = String::from("\"workshopitem\"\n{\n");
+    for (key, value) in fields {
+        text.push_str(&format!("\t\"{key}\"\t\t{value}\n"));
+    }
+    text.push_str("}\n");
+    text
+}
+
+/// The value of the first `key` of the VDF text `text`.
+fn vdf_value(text: &str, key: &str) -> Option<String> {
+    game_paths::vdf_strings(text)
+        .windows(2)
+        .find(|pair| pair[0].eq_ignore_ascii_case(key))
+        .map(|pair| pair[1].clone())
+}
+
+/// Upload the item `vdf` describes with `steamcmd`, logged in as `user`
+/// from the credentials it cached the last time.
+fn upload(vdf: &Path, user: &str) -> Result<(), String> {
+    let mut command = Command::new("steamcmd");
+    command
+        .arg("+login")
+        .arg(user)
+        .arg("+workshop_build_item")
+        .arg(vdf)
+        .arg("+quit");
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        /// `CREATE_NO_WINDOW` process creation flag.
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().map_err(|err| {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            "steamcmd is not installed: install it and put it on PATH to upload from here, or upload the folder with it yourself"
+                .to_string()
+        } else {
+            format!("failed to run steamcmd: {err}")
+        }
+    })?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let failed = stdout
+        .lines()
+        .find(|line| line.contains("ERROR") || line.contains("FAILED"));
+    match failed {
+        None if output.status.success() => Ok(()),
+        failed => Err(format!(
+            "steamcmd could not upload the item: {}; log in with `steamcmd +login {user}` once to cache your credentials",
+            failed.map_or_else(|| String::from_utf8_lossy(&output.stderr).trim().to_string(), |line| line.trim().to_string())
+        )),
+    }
+}
@@ -6,32 +6,98 @@
  * unexpected render errors produce a recoverable UI rather than a blank screen.
  */
 
-import { useEffect, useState } from "react";
+import { useEffect, useRef, useState } from "react";
+import { isTauri } from "@tauri-apps/api/core";
 import {
+  addRecentProject,
+  ConfigError,
+  discardRecovery,
+  exportProjectArchive,
   fetchInitializationConfig,
   fetchLifeStagesConfig,
+  getRecovery,
+  getUndoState,
+  importProjectArchive,
   InitializationConfig,
   LifeStagesConfig,
+  MenuAction,
+  onCrashReport,
+  onEngineRestarted,
+  onEngineUnrecoverable,
+  onMenuAction,
+  onOpenProject,
+  onShowResults,
+  onUndoChanged,
+  openProject,
+  pickArchiveFile,
+  pickArchiveSaveFile,
+  pickProjectFile,
+  pickProjectSaveFile,
+  recordConfigEdit,
+  Recovery,
+  redo,
+  resetInitializationConfig,
+  resetLifeStagesConfig,
+  restartEngine,
+  restoreRecovery,
+  saveProject,
+  setWorkingConfig,
+  takePendingProject,
+  undo,
+  UndoState,
+  UndoStep,
 } from "./api";
 import ErrorBoundary from "./components/ErrorBoundary";
+import Characters from "./components/Characters";
+import CompareRuns from "./components/CompareRuns";
 import DynastySettings from "./components/DynastySettings";
 import NegativeEvents from "./components/NegativeEvents";
 import DynastyTrees from "./components/DynastyTrees";
+import EngineStatusBadge from "./components/EngineStatusBadge";
 import LifeCycleModifiers from "./components/LifeCycleModifiers";
+import Jobs from "./components/Jobs";
+import Logs from "./components/Logs";
+import ModTools from "./components/ModTools";
+import Problems from "./components/Problems";
+import RecentProjects from "./components/RecentProjects";
+import Settings from "./components/Settings";
 
 // ---------------------------------------------------------------------------
 //  Types
 // ---------------------------------------------------------------------------
 
-type TabId = "dynasties" | "trees" | "events" | "lifecycle";
+type TabId = "dynasties" | "trees" | "characters" | "compare" | "events" | "lifecycle" | "problems" | "mod" | "jobs" | "logs" | "settings";
 
 const TABS: { id: TabId; label: string }[] = [
   { id: "dynasties", label: "Dynasty Settings" },
   { id: "trees",     label: "Dynasty Trees" },
+  // Searching the recorded runs needs the shell's project database.
+  ...(isTauri() ? [{ id: "characters" as const, label: "Characters" }] : []),
+  ...(isTauri() ? [{ id: "compare" as const, label: "Compare Runs" }] : []),
   { id: "events",    label: "Negative Events" },
   { id: "lifecycle", label: "Life Cycle Modifiers" },
+  // Checking the output needs the shell's Rust engine.
+  ...(isTauri() ? [{ id: "problems" as const, label: "Problems" }] : []),
+  // So does building a mod from it.
+  ...(isTauri() ? [{ id: "mod" as const, label: "Mod" }] : []),
+  // Jobs run on the shell's native engine.
+  ...(isTauri() ? [{ id: "jobs" as const, label: "Jobs" }] : []),
+  // The logs are the shell's own.
+  ...(isTauri() ? [{ id: "logs" as const, label: "Logs" }] : []),
+  // So are the engine's launch options.
+  ...(isTauri() ? [{ id: "settings" as const, label: "Settings" }] : []),
 ];
 
+// Project files reject with a list of problems rather than one error.
+function describeProjectError(err: unknown): string {
+  if (Array.isArray(err)) {
+    return (err as ConfigError[])
+      .map((problem) => (problem.path ? `${problem.path}: ${problem.message}` : problem.message))
+      .join("\n");
+  }
+  return String(err);
+}
+
 // ---------------------------------------------------------------------------
 //  Component
 // ---------------------------------------------------------------------------
@@ -41,23 +107,261 @@ function AppShell() {
   const [initConfig, setInitConfig] = useState<InitializationConfig | null>(null);
   const [lifeConfig, setLifeConfig] = useState<LifeStagesConfig | null>(null);
   const [loadError, setLoadError]   = useState<string | null>(null);
+  const [projectMsg, setProjectMsg] = useState<{ ok: boolean; text: string } | null>(null);
+  // Set by Generate > Run until the Dynasty Settings tab starts the run.
+  const [runRequested, setRunRequested] = useState(false);
+  const [recentOpen, setRecentOpen] = useState(false);
+
+  const loadConfigs = () =>
+    Promise.all([fetchInitializationConfig(), fetchLifeStagesConfig()]).then(([init, life]) => {
+      setInitConfig(init);
+      setLifeConfig(life);
+    });
 
   useEffect(() => {
-    Promise.all([fetchInitializationConfig(), fetchLifeStagesConfig()])
-      .then(([init, life]) => {
-        setInitConfig(init);
-        setLifeConfig(life);
-      })
-      .catch((err: Error) => {
-        setLoadError(`Failed to load configuration: ${err.message}`);
+    loadConfigs().catch((err: Error) => {
+      setLoadError(`Failed to load configuration: ${err.message}`);
+    });
+  }, []);
+
+  const open = async (path: string) => {
+    try {
+      const opened = await openProject(path);
+      if (opened.restartNeeded) {
+        await restartEngine();
+      }
+      await loadConfigs();
+      await addRecentProject(path);
+      setProjectMsg({ ok: true, text: `Opened ${path}` });
+    } catch (err) {
+      setProjectMsg({ ok: false, text: describeProjectError(err) });
+    }
+  };
+
+  const save = async () => {
+    const path = await pickProjectSaveFile();
+    if (!path) return;
+    try {
+      await saveProject(path);
+      await addRecentProject(path);
+      setProjectMsg({ ok: true, text: `Saved ${path}` });
+    } catch (err) {
+      setProjectMsg({ ok: false, text: describeProjectError(err) });
+    }
+  };
+
+  // A new project starts from the fallback config, as Reset does.
+  const startNew = async () => {
+    try {
+      await Promise.all([resetInitializationConfig(), resetLifeStagesConfig()]);
+      await loadConfigs();
+      setProjectMsg({ ok: true, text: "Started a new project from the fallback config." });
+    } catch (err) {
+      setProjectMsg({ ok: false, text: describeProjectError(err) });
+    }
+  };
+
+  const exportArchive = async () => {
+    const path = await pickArchiveSaveFile();
+    if (!path) return;
+    try {
+      const contents = await exportProjectArchive(path);
+      setProjectMsg({
+        ok: true,
+        text: `Exported ${path} with ${contents.nameLists} name lists and ${contents.outputs} output files`,
+      });
+    } catch (err) {
+      setProjectMsg({ ok: false, text: describeProjectError(err) });
+    }
+  };
+
+  const importArchive = async () => {
+    const path = await pickArchiveFile();
+    if (!path) return;
+    try {
+      const imported = await importProjectArchive(path);
+      if (imported.restartNeeded) {
+        await restartEngine();
+      }
+      await loadConfigs();
+      setProjectMsg({
+        ok: true,
+        text: `Imported ${path} with ${imported.nameLists} name lists and ${imported.outputs} output files`,
       });
+    } catch (err) {
+      setProjectMsg({ ok: false, text: describeProjectError(err) });
+    }
+  };
+
+  // Every change of the config is recorded in the shell's undo history, and
+  // kept for its autosave.
+  const changeInit = (next: InitializationConfig) => {
+    if (isTauri() && initConfig) {
+      recordConfigEdit("initialization", initConfig, next).catch(() => {});
+      setWorkingConfig("initialization", next).catch(() => {});
+    }
+    setInitConfig(next);
+  };
+  const changeLife = (next: LifeStagesConfig) => {
+    if (isTauri() && lifeConfig) {
+      recordConfigEdit("lifeStages", lifeConfig, next).catch(() => {});
+      setWorkingConfig("lifeStages", next).catch(() => {});
+    }
+    setLifeConfig(next);
+  };
+
+  // Work left by a session that did not exit cleanly, offered once the
+  // config has loaded so that restored edits apply on top of it.
+  const [recovery, setRecovery] = useState<Recovery | null>(null);
+  const configLoaded = initConfig !== null && lifeConfig !== null;
+  useEffect(() => {
+    if (!isTauri() || !configLoaded) return;
+    getRecovery().then(setRecovery).catch(() => {});
+  }, [configLoaded]);
+
+  const restore = async () => {
+    setRecovery(null);
+    try {
+      const restored = await restoreRecovery();
+      if (restored.initialization) changeInit(restored.initialization);
+      if (restored.lifeStages) changeLife(restored.lifeStages);
+      const parts = [
+        restored.initialization || restored.lifeStages ? "unsaved config edits" : null,
+        restored.jobs > 0 ? `${restored.jobs} job${restored.jobs === 1 ? "" : "s"}` : null,
+      ].filter(Boolean);
+      setProjectMsg({ ok: true, text: `Restored ${parts.join(" and ") || "the last session"}.` });
+    } catch (err) {
+      setProjectMsg({ ok: false, text: String(err) });
+    }
+  };
+
+  // Character steps were applied by the shell already.
+  const applyStep = (step: UndoStep | null) => {
+    if (step?.kind !== "config") return;
+    const apply = <T extends object>(config: T): T => {
+      const next: Record<string, unknown> = { ...config };
+      for (const [key, value] of Object.entries(step.values)) {
+        if (value === null) delete next[key];
+        else next[key] = value;
+      }
+      return next as T;
+    };
+    if (step.file === "initialization") setInitConfig((config) => config && apply(config));
+    else setLifeConfig((config) => config && apply(config));
+  };
+
+  const [undoState, setUndoState] = useState<UndoState>({ undo: null, redo: null });
+  const travel = (action: typeof undo) => {
+    action()
+      .then(applyStep)
+      .catch((err) => setProjectMsg({ ok: false, text: String(err) }));
+  };
+
+  // Ctrl+Z undoes; Ctrl+Y and Ctrl+Shift+Z redo.
+  useEffect(() => {
+    if (!isTauri()) return;
+    getUndoState().then(setUndoState).catch(() => {});
+    const unlisten = onUndoChanged(setUndoState);
+    const onKey = (e: KeyboardEvent) => {
+      if (!(e.ctrlKey || e.metaKey)) return;
+      const key = e.key.toLowerCase();
+      const action = key === "z" && !e.shiftKey ? undo : key === "y" || key === "z" ? redo : null;
+      if (!action) return;
+      e.preventDefault();
+      travel(action);
+    };
+    window.addEventListener("keydown", onKey);
+    return () => {
+      window.removeEventListener("keydown", onKey);
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Projects opened from outside the app: the one it was launched with, then
+  // any double-clicked while it runs.
+  useEffect(() => {
+    if (!isTauri()) return;
+    takePendingProject().then((path) => path && open(path));
+    const unlisten = onOpenProject(open);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // File > New (Ctrl+N), File > Save (Ctrl+S) and Generate > Run (Ctrl+G).
+  // The handlers are read through a ref, as the listener outlives renders.
+  const menuHandlers = useRef<Record<MenuAction, () => void> | null>(null);
+  menuHandlers.current = {
+    new: startNew,
+    save,
+    run: () => {
+      setActiveTab("dynasties");
+      setRunRequested(true);
+    },
+  };
+  useEffect(() => {
+    if (!isTauri()) return;
+    const unlisten = onMenuAction((action) => menuHandlers.current?.[action]());
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // A crash of the engine or the app is reported wherever the user is.
+  useEffect(() => {
+    if (!isTauri()) return;
+    const unlisten = onCrashReport((report) =>
+      setProjectMsg({
+        ok: false,
+        text: `The engine crashed. A report was saved as ${report.name}; submit it from the Settings tab.`,
+      })
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // The engine exiting on its own is reported, whether or not it came back.
+  useEffect(() => {
+    if (!isTauri()) return;
+    const exited = (exitCode: number | null) =>
+      exitCode === null ? "The engine stopped" : `The engine stopped with exit code ${exitCode}`;
+    // Kept under the crash report banner, if the exit left one.
+    const report = (ok: boolean, text: string) =>
+      setProjectMsg((prev) => (prev && !prev.ok ? { ok: false, text: `${prev.text}\n${text}` } : { ok, text }));
+    const unlisten = [
+      onEngineRestarted(({ attempt, exitCode }) => {
+        if (attempt > 0) {
+          report(true, `${exited(exitCode)} and was restarted. A generation it was running has to be run again.`);
+        }
+      }),
+      onEngineUnrecoverable(({ attempts, exitCode, message }) =>
+        report(
+          false,
+          `${exited(exitCode)} and could not be restarted after ${attempts} attempts (${message}). ` +
+            "Restart it from the Settings tab."
+        )
+      ),
+    ];
+    return () => {
+      unlisten.forEach((pending) => pending.then((stop) => stop()));
+    };
+  }, []);
+
+  // A clicked completion notification shows the family trees of the run.
+  useEffect(() => {
+    if (!isTauri()) return;
+    const unlisten = onShowResults(() => setActiveTab("trees"));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
   if (loadError) {
     return (
       <div className="app-shell">
         <div className="tab-content">
-          <div className="msg msg-error">{loadError}</div>
+          <div className="msg msg-error" style={{ whiteSpace: "pre-line" }}>{loadError}</div>
         </div>
       </div>
     );
@@ -81,8 +385,93 @@ function AppShell() {
     <div className="app-shell">
       <header className="app-header">
         <h1>CK3 Character History Generator</h1>
+        {isTauri() && (
+          <div className="btn-row">
+            <button
+              className="btn btn-secondary btn-sm"
+              onClick={() => pickProjectFile().then((path) => path && open(path))}
+            >
+              Open Project
+            </button>
+            <button className="btn btn-secondary btn-sm" onClick={() => setRecentOpen((o) => !o)}>
+              {recentOpen ? "Hide Recent" : "Recent Projects"}
+            </button>
+            <button className="btn btn-secondary btn-sm" onClick={save}>
+              Save Project
+            </button>
+            <button className="btn btn-secondary btn-sm" onClick={importArchive}>
+              Import Archive
+            </button>
+            <button className="btn btn-secondary btn-sm" onClick={exportArchive}>
+              Export Archive
+            </button>
+            <button
+              className="btn btn-secondary btn-sm"
+              disabled={!undoState.undo}
+              title={undoState.undo ? `Undo ${undoState.undo} (Ctrl+Z)` : undefined}
+              onClick={() => travel(undo)}
+            >
+              Undo
+            </button>
+            <button
+              className="btn btn-secondary btn-sm"
+              disabled={!undoState.redo}
+              title={undoState.redo ? `Redo ${undoState.redo} (Ctrl+Y)` : undefined}
+              onClick={() => travel(redo)}
+            >
+              Redo
+            </button>
+          </div>
+        )}
+        {isTauri() && <EngineStatusBadge />}
       </header>
 
+      {recovery && (
+        <div className="msg msg-error" style={{ display: "flex", alignItems: "center", gap: "0.75rem" }}>
+          <span style={{ flex: 1 }}>
+            The last session ended unexpectedly. Work autosaved at {new Date(recovery.savedAt).toLocaleString()} can be
+            restored:{" "}
+            {[
+              recovery.configEdits ? "unsaved config edits" : null,
+              recovery.jobs > 0 ? `${recovery.jobs} queued job${recovery.jobs === 1 ? "" : "s"}` : null,
+              recovery.interruptedAt !== null
+                ? `a generation interrupted at ${Math.round(recovery.interruptedAt)}%, to run again`
+                : null,
+            ]
+              .filter(Boolean)
+              .join(", ")}
+            .
+          </span>
+          <button className="btn btn-secondary btn-sm" onClick={restore}>
+            Restore
+          </button>
+          <button
+            className="btn btn-secondary btn-sm"
+            onClick={() => {
+              setRecovery(null);
+              discardRecovery().catch(() => {});
+            }}
+          >
+            Discard
+          </button>
+        </div>
+      )}
+
+      {projectMsg && (
+        <div className={`msg ${projectMsg.ok ? "msg-success" : "msg-error"}`} style={{ whiteSpace: "pre-line" }}>
+          {projectMsg.text}
+        </div>
+      )}
+
+      {recentOpen && (
+        <RecentProjects
+          onOpen={(path) => {
+            setRecentOpen(false);
+            open(path);
+          }}
+        />
+      )}
+
       <nav className="tab-bar">
         {TABS.map((tab) => (
           <button
@@ -97,15 +486,27 @@ function AppShell() {
 
       <main className="tab-content">
         {activeTab === "dynasties" && (
-          <DynastySettings config={initConfig} onConfigChange={setInitConfig} />
+          <DynastySettings
+            config={initConfig}
+            onConfigChange={changeInit}
+            runRequested={runRequested}
+            onRunRequestTaken={() => setRunRequested(false)}
+          />
         )}
         {activeTab === "trees" && <DynastyTrees />}
+        {activeTab === "characters" && <Characters />}
+        {activeTab === "compare" && <CompareRuns />}
         {activeTab === "events" && (
-          <NegativeEvents config={initConfig} onConfigChange={setInitConfig} />
+          <NegativeEvents config={initConfig} onConfigChange={changeInit} />
         )}
         {activeTab === "lifecycle" && (
-          <LifeCycleModifiers config={lifeConfig} onConfigChange={setLifeConfig} />
+          <LifeCycleModifiers config={lifeConfig} onConfigChange={changeLife} />
         )}
+        {activeTab === "problems" && <Problems />}
+        {activeTab === "mod" && <ModTools />}
+        {activeTab === "jobs" && <Jobs />}
+        {activeTab === "logs" && <Logs />}
+        {activeTab === "settings" && <Settings />}
       </main>
     </div>
   );
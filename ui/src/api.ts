@@ -1,9 +1,26 @@
-// Typed wrappers around every FastAPI endpoint.
-// In development the Vite proxy rewrites /api -> http://127.0.0.1:8000.
-// In the packaged app the sidecar is already bound on port 8000 so the
-// same base URL works without modification.
+// Typed wrappers around every HTTP endpoint.
+// Inside the Tauri shell the config and image endpoints are served by the
+// shell itself, on a port it picks at startup and tells with
+// get_api_base_url; runs are streamed by the shell from the engine. In a
+// plain browser the Vite proxy rewrites /api -> http://127.0.0.1:8000, where
+// the FastAPI engine serves every endpoint.
 
-const BASE = "/api";
+import { invoke, isTauri } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+const DEV_BASE = "/api";
+
+let base = DEV_BASE;
+let basePromise: Promise<string> | null = null;
+
+function apiBase(): Promise<string> {
+  if (!basePromise) {
+    basePromise = isTauri()
+      ? invoke<string>("get_api_base_url").then((url) => (base = url))
+      : Promise.resolve(DEV_BASE);
+  }
+  return basePromise;
+}
 
 // ---------------------------------------------------------------------------
 //  Shared types
@@ -15,6 +32,40 @@ export interface NameInheritance {
   noNameInheritanceChance: number;
 }
 
+export interface DynastyTitle {
+  id: string;
+  succession?: string;
+  gender_law?: string;
+  liege?: string;
+  development?: number;
+}
+
+// Read by the native simulation only.
+export interface FaithConversion {
+  faithID: string;
+  startYear: number;
+  endYear: number;
+}
+
+// Read by the native simulation only.
+export interface War {
+  name?: string;
+  startYear: number;
+  endYear: number;
+  battleDeathChance: number;
+  woundChance?: number;
+  veteranChance?: number;
+  womenFight?: boolean;
+}
+
+// Read by the native simulation only.
+export interface MarriageDoctrine {
+  // Wives at once, the first included.
+  maxSpouses?: number;
+  maxConcubines?: number;
+  consortChance?: number;
+}
+
 export interface Dynasty {
   dynastyID: string;
   dynastyName: string;
@@ -27,9 +78,18 @@ export interface Dynasty {
   progenitorMaleBirthYear: number;
   allowFirstCousinMarriage: boolean;
   prioritiseLowbornMarriage: boolean;
+  // Read by the native simulation only.
+  matrilinealMarriage?: boolean;
+  // Read by the native simulation only; over the matchmaking weights.
+  differentFaithWeight?: number;
+  differentCultureWeight?: number;
   forceDynastyAlive: boolean;
   numenorBloodTier?: number;
   languages?: string[];
+  titles?: DynastyTitle[];
+  conversions?: FaithConversion[];
+  wars?: War[];
+  marriageDoctrine?: MarriageDoctrine;
   nameInheritance: NameInheritance;
 }
 
@@ -50,39 +110,191 @@ export interface InitializationConfig {
   maxYear: number;
   generationMax: number;
   initialCharID: number;
+  matchmaking?: MatchmakingConfig;
+  marriageChecks?: MarriageChecksConfig;
+  comingOfAge?: ComingOfAgeConfig;
+  cadetHouses?: CadetHousesConfig;
+  portraitGenetics?: PortraitGeneticsConfig | null;
+  calendar?: CalendarConfig;
+  // Languages the localisation files are written for, e.g. "english" or
+  // "simp_chinese"; English alone if unset.
+  localizationLanguages?: string[];
+  numenorBlood?: NumenorBloodConfig | null;
+  // Bookmark dates, "year.month.day" in the history's dates, to snapshot
+  // the living characters on into bookmarks/<date>.md; none if unset.
+  bookmarkSnapshots?: string[];
+  modSettings?: ModSettings;
+  // The game version the files are written for, e.g. "1.12"; the latest
+  // format if unset. Older versions go without the effects they lack.
+  gameVersion?: string;
   [key: string]: unknown;
 }
 
+// Númenórean blood as a strength from 0 to 1 mixed from both parents, each
+// tier a band of it. Read by the native simulation only.
+export interface NumenorBloodConfig {
+  // Strength each tier ("1" to "10") begins at; default (tier - 0.5) / 10.
+  tierThresholds?: Record<string, number>;
+  drift?: number; // most a child strays from its parents' mean; default 0.05
+}
+
+// The calendar output dates are written in; config years stay simulation
+// years. Calendar year = epochOffset + simulation year / yearLength.
+export interface CalendarConfig {
+  epochOffset?: number; // default 0
+  yearLength?: number; // simulation years per calendar year; default 1
+}
+
+// How scaffoldMod describes the mod to the launcher.
+export interface ModSettings {
+  name: string;
+  version?: string; // default "1.0.0"
+  supportedVersion?: string; // e.g. "1.12.*"; default "1.*"
+  tags?: string[];
+}
+
+// Read by the native simulation only.
+export interface ComingOfAgeConfig {
+  swapChildhoodTraits?: boolean;
+  // Bookmark start dates as "year.month.day".
+  bookmarks?: string[];
+}
+
+// Read by the native simulation only.
+export interface CadetHousesConfig {
+  maxHouseSize?: number;
+  youngerChildChance?: number;
+}
+
+// Read by the native simulation only.
+export interface PortraitGeneticsConfig {
+  // Largest drift, out of 255, from an inherited gene value.
+  mutation?: number;
+}
+
+// Read by the native simulation only.
+export interface MatchmakingConfig {
+  maxHusbandOlder?: number;
+  maxWifeOlder?: number;
+  preferredAgeGap?: number;
+  differentFaithWeight?: number;
+  differentCultureWeight?: number;
+  dynastyRelations?: Record<string, Record<string, number>>;
+  allianceWeight?: number;
+  maxRelatedness?: number;
+  consanguinityGenerations?: number;
+  // By faith ID; faiths left out are monogamous.
+  marriageDoctrines?: Record<string, MarriageDoctrine>;
+}
+
+export type Severity = "off" | "warning" | "error";
+
+// The rules checkMarriages applies; the native simulation will not write a
+// history breaking one set to "error".
+export interface MarriageChecksConfig {
+  minAge?: number; // default 16
+  underage?: Severity; // default "warning"
+  deadSpouse?: Severity; // default "error"
+  alreadyMarried?: Severity; // default "error"
+  parentChild?: Severity; // default "error"
+}
+
 export interface RateSet {
   Male: number[];
   Female: number[];
 }
 
+export interface SurvivalCurve {
+  infant: number;
+  infantDecay: number;
+  background: number;
+  aging: number;
+  agingRate: number;
+}
+
+export interface MortalityEra {
+  name?: string;
+  startYear: number;
+  endYear: number;
+  curve?: { Male: SurvivalCurve; Female: SurvivalCurve } | null;
+  multiplier?: number;
+}
+
+export interface MortalityConfig {
+  eras: MortalityEra[];
+  traitMultipliers: Record<string, number>;
+  maxLifespan: number;
+  bloodTierMaxLifespan: Record<string, number>;
+}
+
+// Read by the native simulation only.
+export interface PregnancyConfig {
+  minMaternalAge: number;
+  maxMaternalAge: number;
+  gestationMonths: number;
+  monthsBetweenBirths: number;
+  fertilityOnset: number;
+  bloodTierFertilityOnset: Record<string, number>;
+}
+
+// Read by the native simulation only.
+export interface Epidemic {
+  name?: string;
+  startYear: number;
+  endYear: number;
+  // Dynasty and culture IDs it strikes; everyone if empty.
+  dynasties?: string[];
+  cultures?: string[];
+  lethality: { fromAge: number; toAge: number; multiplier: number }[];
+  deathReason?: string;
+}
+
+// Read by the native simulation only.
+export interface BastardyConfig {
+  legitimizationChance?: number;
+  maxLegitimizationAge?: number;
+  lowbornLineChance?: number;
+}
+
 export interface LifeStagesConfig {
   mortalityRates: RateSet;
   marriageRates: RateSet;
   fertilityRates: RateSet;
+  // Read by the native simulation only; tiers left out stay at peak
+  // fertility 10 years a tier, with a factor of 1 - 0.025 a tier after it.
+  bloodTierFertility?: Record<string, { peakYears: number; factor: number }>;
   desperationMarriageRates: number[];
   marriageMaxAgeDifference: number;
   maximumNumberOfChildren: number;
   minimumYearsBetweenChildren: number;
   bastardyChanceMale: number;
   bastardyChanceFemale: number;
+  mortality?: MortalityConfig | null;
+  pregnancy?: PregnancyConfig | null;
+  epidemics?: Epidemic[];
+  bastardy?: BastardyConfig;
   [key: string]: unknown;
 }
 
 export interface SimulationMessage {
   log?: string;
   error?: string;
+  // "complete", or "cancelled" if the run was cancelled.
   status?: string;
+  // Partial output files a cancelled run deleted.
+  removedFiles?: number;
 }
 
 // ---------------------------------------------------------------------------
 //  Config — Initialization
 // ---------------------------------------------------------------------------
 
+// Inside the Tauri shell the config is read and written by the shell, which
+// validates it and rejects with every problem found; in a plain browser the
+// Python engine serves it.
 export async function fetchInitializationConfig(): Promise<InitializationConfig> {
-  const res = await fetch(`${BASE}/config/initialization`);
+  if (isTauri()) return loadConfig<InitializationConfig>().catch(configProblems);
+  const res = await fetch(`${await apiBase()}/config/initialization`);
   if (!res.ok) throw new Error(await res.text());
   return res.json();
 }
@@ -90,7 +302,8 @@ export async function fetchInitializationConfig(): Promise<InitializationConfig>
 export async function saveInitializationConfig(
   config: InitializationConfig
 ): Promise<void> {
-  const res = await fetch(`${BASE}/config/initialization`, {
+  if (isTauri()) return saveConfig(config).catch(configProblems);
+  const res = await fetch(`${await apiBase()}/config/initialization`, {
     method: "PUT",
     headers: { "Content-Type": "application/json" },
     body: JSON.stringify(config),
@@ -99,14 +312,14 @@ export async function saveInitializationConfig(
 }
 
 export async function resetInitializationConfig(): Promise<void> {
-  const res = await fetch(`${BASE}/config/initialization/reset`, {
+  const res = await fetch(`${await apiBase()}/config/initialization/reset`, {
     method: "POST",
   });
   if (!res.ok) throw new Error(await res.text());
 }
 
 export async function setInitializationFallback(): Promise<void> {
-  const res = await fetch(`${BASE}/config/initialization/set-fallback`, {
+  const res = await fetch(`${await apiBase()}/config/initialization/set-fallback`, {
     method: "POST",
   });
   if (!res.ok) throw new Error(await res.text());
@@ -117,13 +330,13 @@ export async function setInitializationFallback(): Promise<void> {
 // ---------------------------------------------------------------------------
 
 export async function fetchLifeStagesConfig(): Promise<LifeStagesConfig> {
-  const res = await fetch(`${BASE}/config/life-stages`);
+  const res = await fetch(`${await apiBase()}/config/life-stages`);
   if (!res.ok) throw new Error(await res.text());
   return res.json();
 }
 
 export async function fetchLifeStagesFallback(): Promise<LifeStagesConfig> {
-  const res = await fetch(`${BASE}/config/life-stages/fallback`);
+  const res = await fetch(`${await apiBase()}/config/life-stages/fallback`);
   if (!res.ok) throw new Error(await res.text());
   return res.json();
 }
@@ -131,7 +344,7 @@ export async function fetchLifeStagesFallback(): Promise<LifeStagesConfig> {
 export async function saveLifeStagesConfig(
   config: LifeStagesConfig
 ): Promise<void> {
-  const res = await fetch(`${BASE}/config/life-stages`, {
+  const res = await fetch(`${await apiBase()}/config/life-stages`, {
     method: "PUT",
     headers: { "Content-Type": "application/json" },
     body: JSON.stringify(config),
@@ -140,7 +353,7 @@ export async function saveLifeStagesConfig(
 }
 
 export async function resetLifeStagesConfig(): Promise<void> {
-  const res = await fetch(`${BASE}/config/life-stages/reset`, {
+  const res = await fetch(`${await apiBase()}/config/life-stages/reset`, {
     method: "POST",
   });
   if (!res.ok) throw new Error(await res.text());
@@ -151,30 +364,56 @@ export async function resetLifeStagesConfig(): Promise<void> {
 // ---------------------------------------------------------------------------
 
 export async function fetchImageList(): Promise<string[]> {
-  const res = await fetch(`${BASE}/images`);
+  const res = await fetch(`${await apiBase()}/images`);
   if (!res.ok) throw new Error(await res.text());
   return res.json();
 }
 
 export function imageUrl(filename: string): string {
-  return `${BASE}/images/${encodeURIComponent(filename)}`;
+  return `${base}/images/${encodeURIComponent(filename)}`;
 }
 
 // ---------------------------------------------------------------------------
 //  Simulation — SSE streaming run
 // ---------------------------------------------------------------------------
 
+// Inside the Tauri shell the run is native and the shell pushes its messages
+// as "simulation-message" events; in a plain browser the Python engine's
+// stream is read here.
 export function streamSimulation(
   onMessage: (msg: SimulationMessage) => void,
   onDone: () => void,
   onError: (err: Error) => void
 ): () => void {
+  if (isTauri()) {
+    let stopped = false;
+    const unlisten = listen<SimulationMessage>("simulation-message", (event) => {
+      if (!stopped) onMessage(event.payload);
+    });
+    unlisten
+      .then(() => invoke("run_simulation"))
+      .then(() => {
+        if (!stopped) onDone();
+      })
+      .catch((err) => {
+        if (!stopped) onError(err instanceof Error ? err : new Error(String(err)));
+      })
+      .finally(() => unlisten.then((fn) => fn()));
+    // Stops listening; the run goes on, as with an aborted stream.
+    return () => {
+      stopped = true;
+    };
+  }
+
   const controller = new AbortController();
 
-  fetch(`${BASE}/simulation/run`, {
-    method: "POST",
-    signal: controller.signal,
-  })
+  apiBase()
+    .then((url) =>
+      fetch(`${url}/simulation/run`, {
+        method: "POST",
+        signal: controller.signal,
+      })
+    )
     .then(async (res) => {
       if (!res.ok || !res.body) {
         throw new Error(`Simulation request failed: ${res.status}`);
@@ -214,4 +453,1494 @@ export function streamSimulation(
 
   // Return a cancel function
   return () => controller.abort();
-}
\ No newline at end of file
+}
+// ---------------------------------------------------------------------------
+//  Parallel workers (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export function getWorkerCount(): Promise<number> {
+  return invoke<number>("get_worker_count");
+}
+
+export function setWorkerCount(count: number): Promise<void> {
+  return invoke("set_worker_count", { count });
+}
+
+// Runs natively with the dynasties simulated on as many threads as there are
+// workers, resolving once the output is written. Its messages arrive as
+// "simulation-message" events, like those of streamSimulation.
+export async function runParallelSimulation(
+  onMessage: (msg: SimulationMessage) => void
+): Promise<void> {
+  const unlisten = await listen<SimulationMessage>("simulation-message", (event) =>
+    onMessage(event.payload)
+  );
+  try {
+    await invoke("run_parallel_simulation");
+  } finally {
+    unlisten();
+  }
+}
+
+// ---------------------------------------------------------------------------
+//  Engine status (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// From the shell's heartbeat: busy while a simulation runs, dead once the
+// engine exits or stops answering.
+export type EngineStatus = "starting" | "ready" | "busy" | "dead";
+
+export function getEngineStatus(): Promise<EngineStatus> {
+  return invoke<EngineStatus>("get_engine_status");
+}
+
+// Calls back with every change of status; returns an unsubscribe function.
+export function onEngineStatus(callback: (status: EngineStatus) => void): Promise<() => void> {
+  return listen<EngineStatus>("engine-status-changed", (event) => callback(event.payload));
+}
+
+export interface EngineRestarted {
+  attempt: number; // 1-based; 0 for a restart asked for with restartEngine()
+  exitCode: number | null;
+}
+
+export interface EngineUnrecoverable {
+  attempts: number;
+  exitCode: number | null;
+  message: string;
+}
+
+// Calls back whenever the shell has started the engine again after it exited;
+// returns an unsubscribe function.
+export function onEngineRestarted(callback: (restarted: EngineRestarted) => void): Promise<() => void> {
+  return listen<EngineRestarted>("engine-restarted", (event) => callback(event.payload));
+}
+
+// Calls back once the shell has given up restarting the engine; returns an
+// unsubscribe function.
+export function onEngineUnrecoverable(
+  callback: (unrecoverable: EngineUnrecoverable) => void
+): Promise<() => void> {
+  return listen<EngineUnrecoverable>("engine-unrecoverable", (event) => callback(event.payload));
+}
+
+// ---------------------------------------------------------------------------
+//  Engine launch options (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export interface LaunchOptions {
+  configDir: string | null;
+  seed: number | null;
+  outputDir: string | null;
+}
+
+export function getLaunchOptions(): Promise<LaunchOptions> {
+  return invoke<LaunchOptions>("get_launch_options");
+}
+
+// Saved options apply from the next engine start; call restartEngine() to
+// apply them straight away.
+export function setLaunchOptions(options: LaunchOptions): Promise<void> {
+  return invoke("set_launch_options", { options });
+}
+
+export function restartEngine(): Promise<void> {
+  return invoke("restart_engine");
+}
+
+export interface RestartProgress {
+  stage: "stopping" | "starting" | "ready" | "failed";
+  message: string | null; // why it failed
+}
+
+// Calls back as restartEngine() stops the engine and waits for the new one;
+// returns an unsubscribe function.
+export function onEngineRestartProgress(callback: (progress: RestartProgress) => void): Promise<() => void> {
+  return listen<RestartProgress>("engine-restart-progress", (event) => callback(event.payload));
+}
+
+// Saves the seed runs start with, null for a random one each run. Native runs
+// use it straight away, the engine from its next start.
+export function setSeed(seed: number | null): Promise<void> {
+  return invoke("set_seed", { seed });
+}
+
+// The seed recorded in the last generated history, null if there is none.
+export function getLastSeed(): Promise<number | null> {
+  return invoke<number | null>("get_last_seed");
+}
+
+// ---------------------------------------------------------------------------
+//  Engine resource usage (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export interface EngineStats {
+  // 100 means one fully used core.
+  cpuPercent: number;
+  memoryBytes: number;
+  totalMemoryBytes: number;
+  processes: number;
+}
+
+// Calls back with a new sample every few seconds; returns an unsubscribe function.
+export function onEngineStats(callback: (stats: EngineStats) => void): Promise<() => void> {
+  return listen<EngineStats>("engine-stats", (event) => callback(event.payload));
+}
+
+// ---------------------------------------------------------------------------
+//  Native pickers (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// Each resolves to the chosen path, or null if the dialog was cancelled.
+
+export function pickOutputDir(): Promise<string | null> {
+  return invoke<string | null>("pick_output_dir");
+}
+
+// The Paradox mod folder, holding the launcher's .mod descriptors.
+export function pickModDir(): Promise<string | null> {
+  return invoke<string | null>("pick_mod_dir");
+}
+
+export function pickGameDir(): Promise<string | null> {
+  return invoke<string | null>("pick_game_dir");
+}
+
+// Where the game was found: the store's library or default folder.
+export type GameStore = "steam" | "xbox" | "gog";
+
+export interface GameInstallation {
+  path: string;
+  store: GameStore;
+  version: string | null; // e.g. "1.12", from the launcher settings
+}
+
+export interface GamePaths {
+  // Steam's first, without duplicates.
+  installations: GameInstallation[];
+  // The Paradox mod folder, or null if it does not exist.
+  modDir: string | null;
+}
+
+// Looks for the game installations and the mod folder on this machine; the
+// game and mod pickers start in what it finds.
+export function detectGamePaths(): Promise<GamePaths> {
+  return invoke<GamePaths>("detect_game_paths");
+}
+
+export function pickProjectFile(): Promise<string | null> {
+  return invoke<string | null>("pick_project_file");
+}
+
+// Asks where to save the project; the file need not exist yet.
+export function pickProjectSaveFile(): Promise<string | null> {
+  return invoke<string | null>("pick_project_save_file");
+}
+
+export function pickArchiveFile(): Promise<string | null> {
+  return invoke<string | null>("pick_archive_file");
+}
+
+// Asks where to export the project archive; the file need not exist yet.
+export function pickArchiveSaveFile(): Promise<string | null> {
+  return invoke<string | null>("pick_archive_save_file");
+}
+
+// Asks where to save a Markdown chronicle; the file need not exist yet.
+export function pickChronicleFile(): Promise<string | null> {
+  return invoke<string | null>("pick_chronicle_file");
+}
+
+export function pickCsvExportDir(): Promise<string | null> {
+  return invoke<string | null>("pick_csv_export_dir");
+}
+
+// Asks where to save a GEDCOM file; the file need not exist yet.
+export function pickGedcomFile(): Promise<string | null> {
+  return invoke<string | null>("pick_gedcom_file");
+}
+
+// Asks where to save an HTML report; the file need not exist yet.
+export function pickReportFile(): Promise<string | null> {
+  return invoke<string | null>("pick_report_file");
+}
+
+export function pickIdCheckDir(): Promise<string | null> {
+  return invoke<string | null>("pick_id_check_dir");
+}
+
+export function pickTreeExportDir(): Promise<string | null> {
+  return invoke<string | null>("pick_tree_export_dir");
+}
+
+// ---------------------------------------------------------------------------
+//  Generated files (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export function openOutputFolder(): Promise<void> {
+  return invoke("open_output_folder");
+}
+
+// `file` is a generated file name, e.g. "family_history.txt" or an image name
+// returned by fetchImageList().
+export function revealOutputFile(file: string): Promise<void> {
+  return invoke("reveal_output_file", { file });
+}
+
+// ---------------------------------------------------------------------------
+//  Recent projects (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export interface RecentProject {
+  path: string;
+  name: string;
+  // Milliseconds since the Unix epoch.
+  lastOpened: number;
+  pinned: boolean;
+}
+
+// Each call resolves to the updated list, pinned projects first.
+
+export function getRecentProjects(): Promise<RecentProject[]> {
+  return invoke<RecentProject[]>("get_recent_projects");
+}
+
+export function addRecentProject(path: string): Promise<RecentProject[]> {
+  return invoke<RecentProject[]>("add_recent_project", { path });
+}
+
+export function pinProject(path: string, pinned: boolean): Promise<RecentProject[]> {
+  return invoke<RecentProject[]>("pin_project", { path, pinned });
+}
+
+// ---------------------------------------------------------------------------
+//  Config validation (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// One problem with initialization.json. `path` locates the value, e.g.
+// "dynasties[2].nameInheritance", and is empty for file-level problems;
+// `line` and `column` are only set for JSON syntax errors.
+export interface ConfigError {
+  path: string;
+  message: string;
+  line: number | null;
+  column: number | null;
+}
+
+// Both reject with a ConfigError[] listing every problem found.
+
+export function loadConfig<T = unknown>(): Promise<T> {
+  return invoke<T>("load_config");
+}
+
+export function saveConfig(config: unknown): Promise<void> {
+  return invoke("save_config", { config });
+}
+
+// Rethrows a ConfigError[] rejection as an Error listing one problem a line.
+function configProblems(err: unknown): never {
+  if (!Array.isArray(err)) throw err instanceof Error ? err : new Error(String(err));
+  throw new Error(
+    (err as ConfigError[])
+      .map((problem) => (problem.path ? `${problem.path}: ${problem.message}` : problem.message))
+      .join("\n")
+  );
+}
+
+// ---------------------------------------------------------------------------
+//  History import (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export interface ImportedCharacter {
+  id: string;
+  name: string | null;
+  female: boolean;
+  dynasty: string | null;
+  dynastyHouse: string | null;
+  culture: string | null;
+  religion: string | null;
+  father: string | null;
+  mother: string | null;
+  // History dates such as "7010.3.14".
+  birth: string | null;
+  death: string | null;
+  traits: string[];
+}
+
+// A dynasty, or a house if `dynasty` is set.
+export interface ImportedDynasty {
+  id: string;
+  name: string | null;
+  culture: string | null;
+  motto: string | null;
+  dynasty: string | null;
+}
+
+export interface ImportedFile {
+  path: string;
+  characters: ImportedCharacter[];
+  dynasties: ImportedDynasty[];
+  errors: string[];
+}
+
+export interface HistoryImport {
+  files: ImportedFile[];
+  // Dropped paths that are not .txt files.
+  skipped: string[];
+}
+
+// Asks for a character or dynasty history file to import.
+export function pickHistoryFile(): Promise<string | null> {
+  return invoke<string | null>("pick_history_file");
+}
+
+export function importHistoryFiles(paths: string[]): Promise<HistoryImport> {
+  return invoke<HistoryImport>("import_history_files", { paths });
+}
+
+// Calls back whenever history files are dropped onto the window; returns an
+// unsubscribe function.
+export function onHistoryImport(callback: (data: HistoryImport) => void): Promise<() => void> {
+  return listen<HistoryImport>("history-import", (event) => callback(event.payload));
+}
+
+// ---------------------------------------------------------------------------
+//  Opening projects from outside the app (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// The .ck3hist project the app was launched with, or null. Resolves to the
+// path only once, so call it when the UI is ready to load a project.
+export function takePendingProject(): Promise<string | null> {
+  return invoke<string | null>("take_pending_project");
+}
+
+// Calls back with the path of each project double-clicked or opened through a
+// ck3gen://open?path=... link while the app runs; returns an unsubscribe
+// function.
+export function onOpenProject(callback: (path: string) => void): Promise<() => void> {
+  return listen<{ path: string }>("open-project", (event) => callback(event.payload.path));
+}
+
+// ---------------------------------------------------------------------------
+//  Project files (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// A .ck3hist file holds the config files, the project's settings, the seed,
+// the seed of the last history and the output folder. Files written by older
+// versions of the app are migrated as they are opened.
+
+export interface OpenedProject {
+  // The seed or output folder changed; they apply once restartEngine() is
+  // called.
+  restartNeeded: boolean;
+}
+
+// Both reject with a ConfigError[] listing every problem found.
+
+export function saveProject(path: string): Promise<void> {
+  return invoke("save_project", { path });
+}
+
+// Replaces the config files and settings with the project's; nothing is
+// replaced if the file is not a valid project.
+export function openProject(path: string): Promise<OpenedProject> {
+  return invoke<OpenedProject>("open_project", { path });
+}
+
+// ---------------------------------------------------------------------------
+//  Project archives (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// A .zip of the project file, the name lists and the last history and family
+// trees, for sharing a complete setup. The exporter's output folder is left
+// out; importing keeps the importer's own.
+
+export interface ArchiveContents {
+  nameLists: number;
+  outputs: number;
+}
+
+export interface ImportedArchive extends ArchiveContents {
+  // The seed changed; it applies once restartEngine() is called.
+  restartNeeded: boolean;
+}
+
+// Both reject with a ConfigError[] listing every problem found.
+
+export function exportProjectArchive(path: string): Promise<ArchiveContents> {
+  return invoke<ArchiveContents>("export_project_archive", { path });
+}
+
+// Nothing is put in place if any part of the archive is invalid.
+export function importProjectArchive(path: string): Promise<ImportedArchive> {
+  return invoke<ImportedArchive>("import_project_archive", { path });
+}
+
+// ---------------------------------------------------------------------------
+//  Dynasty templates (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// A partial dynasty: its succession, marriage customs, name inheritance and
+// the like, without its ID, names, faith, culture, birth year, titles,
+// conversions or wars. User templates are kept with the app, not a project.
+export interface DynastyTemplate {
+  name: string;
+  description: string;
+  fields: Partial<Dynasty>;
+  // Bundled templates come with the app and cannot be deleted.
+  bundled: boolean;
+}
+
+// Bundled templates first.
+export function listDynastyTemplates(): Promise<DynastyTemplate[]> {
+  return invoke<DynastyTemplate[]>("list_dynasty_templates");
+}
+
+// The dynasty with the template's fields set; nested objects such as
+// nameInheritance are merged key by key.
+export function applyDynastyTemplate(name: string, dynasty: Dynasty): Promise<Dynasty> {
+  return invoke<Dynasty>("apply_dynasty_template", { name, dynasty });
+}
+
+// Both resolve to every template, and reject with a ConfigError[].
+
+// Saves what is not the dynasty's own as a user template, replacing any of
+// the same name.
+export function saveDynastyTemplate(name: string, description: string, dynasty: Dynasty): Promise<DynastyTemplate[]> {
+  return invoke<DynastyTemplate[]>("save_dynasty_template", { name, description, dynasty });
+}
+
+export function deleteDynastyTemplate(name: string): Promise<DynastyTemplate[]> {
+  return invoke<DynastyTemplate[]>("delete_dynasty_template", { name });
+}
+
+// ---------------------------------------------------------------------------
+//  Undo and redo (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// The history is the shell's and is kept with the project, so it survives a
+// restart and travels with .ck3hist files.
+
+export type ConfigFile = "initialization" | "lifeStages";
+
+// What undo() or redo() leaves to apply. A config step sets the top-level
+// values of the config being edited, removing those that are null; the
+// character of a character step was already edited on disk.
+export type UndoStep =
+  | { kind: "config"; file: ConfigFile; values: Record<string, unknown> }
+  | { kind: "character"; id: string; field: CharacterField; value: string };
+
+export interface UndoState {
+  // What each would undo or redo, or null if there is nothing to.
+  undo: string | null;
+  redo: string | null;
+}
+
+export type CharacterField = "name" | "culture" | "religion";
+
+// Records a change of the config being edited; changes to the same values in
+// quick succession are undone together.
+export function recordConfigEdit(file: ConfigFile, before: object, after: object): Promise<void> {
+  return invoke("record_config_edit", { file, before, after });
+}
+
+// Edits a character of the latest history in place, as an edit that can be
+// undone.
+export function editCharacter(id: string, field: CharacterField, value: string): Promise<void> {
+  return invoke("edit_character", { id, field, value });
+}
+
+// Both resolve to null if there is nothing to undo or redo.
+
+export function undo(): Promise<UndoStep | null> {
+  return invoke<UndoStep | null>("undo");
+}
+
+export function redo(): Promise<UndoStep | null> {
+  return invoke<UndoStep | null>("redo");
+}
+
+export function getUndoState(): Promise<UndoState> {
+  return invoke<UndoState>("get_undo_state");
+}
+
+// Calls back whenever the history changes; returns an unsubscribe function.
+export function onUndoChanged(callback: (state: UndoState) => void): Promise<() => void> {
+  return listen<UndoState>("undo-changed", (event) => callback(event.payload));
+}
+
+// ---------------------------------------------------------------------------
+//  Autosave and recovery (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// The shell snapshots the config being edited and the jobs in progress every
+// 30 seconds; if the app does not exit cleanly, the next launch can restore
+// them.
+
+export interface Recovery {
+  // Milliseconds since the Unix epoch.
+  savedAt: number;
+  configDir: string | null;
+  // Whether the config being edited differs from the one on disk.
+  configEdits: boolean;
+  jobs: number;
+  // Percent the interrupted generation had got to, or null if none was going.
+  interruptedAt: number | null;
+}
+
+export interface Restored {
+  // The configs to take up as unsaved edits, or null if unchanged.
+  initialization: InitializationConfig | null;
+  lifeStages: LifeStagesConfig | null;
+  // Jobs queued again, the interrupted generation included.
+  jobs: number;
+}
+
+// Tells the shell the config being edited, for the next snapshot.
+export function setWorkingConfig(file: ConfigFile, config: object): Promise<void> {
+  return invoke("set_working_config", { file, config });
+}
+
+// What the last session left, or null if it exited cleanly or left nothing.
+export function getRecovery(): Promise<Recovery | null> {
+  return invoke<Recovery | null>("get_recovery");
+}
+
+export function restoreRecovery(): Promise<Restored> {
+  return invoke<Restored>("restore_recovery");
+}
+
+export function discardRecovery(): Promise<void> {
+  return invoke("discard_recovery");
+}
+
+// ---------------------------------------------------------------------------
+//  Generation control (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// Act on the running generation, also available from the tray icon. Pausing
+// takes effect once the year being simulated is done, cancelling once that
+// year or the output file being written is; a cancelled run deletes what it
+// wrote, and its stream ends with a { status: "cancelled" } message.
+
+export function pauseGeneration(): Promise<void> {
+  return invoke("pause_generation");
+}
+
+export function resumeGeneration(): Promise<void> {
+  return invoke("resume_generation");
+}
+
+export function cancelGeneration(): Promise<void> {
+  return invoke("cancel_generation");
+}
+
+export type GenerationPhase = "seeding" | "simulating" | "writing";
+
+export interface GenerationProgress {
+  run: number;
+  phase: GenerationPhase;
+  year: number | null;
+  // Which of the `years` the `year` is, counting from 1.
+  yearIndex: number | null;
+  years: number | null;
+  // Share of the whole run done, from 0 to 100.
+  percent: number;
+  // Seconds the simulated years should take still; null until the first
+  // year is done and once all of them are.
+  etaSeconds: number | null;
+}
+
+// The last progress of the running generation, or null.
+export function getGenerationProgress(): Promise<GenerationProgress | null> {
+  return invoke<GenerationProgress | null>("get_generation_progress");
+}
+
+// Calls back each time a generation's phase or simulated year changes;
+// returns an unsubscribe function.
+export function onGenerationProgress(
+  callback: (progress: GenerationProgress) => void
+): Promise<() => void> {
+  return listen<GenerationProgress>("generation-progress", (event) => callback(event.payload));
+}
+
+export interface GenerationCancelled {
+  // Partial output files deleted; null for a parallel run.
+  removedFiles: number | null;
+}
+
+// Calls back once a cancelled run, the primary engine's or a parallel one,
+// has stopped and cleaned up; returns an unsubscribe function.
+export function onGenerationCancelled(
+  callback: (cancelled: GenerationCancelled) => void
+): Promise<() => void> {
+  return listen<GenerationCancelled>("generation-cancelled", (event) => callback(event.payload));
+}
+
+// Calls back once the run the primary engine reported progress for has
+// ended, however it ended; returns an unsubscribe function.
+export function onGenerationEnded(callback: () => void): Promise<() => void> {
+  return listen("generation-ended", () => callback());
+}
+
+// ---------------------------------------------------------------------------
+//  Quick preview (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export interface PreviewMember {
+  id: string;
+  name: string;
+  female: boolean;
+  generation: number;
+  born: number;
+  died: number | null; // null for the living
+  father: string | null;
+  mother: string | null;
+  spouses: string[];
+  // Married into the dynasty rather than born to it.
+  marriedIn: boolean;
+}
+
+export interface DynastyPreview {
+  dynastyId: string;
+  generations: number;
+  firstYear: number;
+  lastYear: number; // where the preview stopped
+  // Members by generation, then birth, followed by the spouses who married in.
+  members: PreviewMember[];
+}
+
+// Simulates the first generations, at most 5, of one dynasty of config, which
+// need not be saved, natively and in a fraction of a second.
+export function previewDynasty(
+  config: InitializationConfig,
+  dynastyId: string,
+  generations: number,
+  seed?: number
+): Promise<DynastyPreview> {
+  return invoke<DynastyPreview>("preview_dynasty", {
+    config,
+    dynastyId,
+    generations,
+    seed: seed ?? null,
+  });
+}
+
+// ---------------------------------------------------------------------------
+//  Generation jobs (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export type JobStatus = "queued" | "running" | "complete" | "failed" | "cancelled";
+
+export interface Job {
+  id: number;
+  name: string;
+  configDir: string;
+  seed: number | null; // null for a random one
+  // The folder the job writes into, of its own under the app data folder.
+  outputDir: string;
+  status: JobStatus;
+  // Share of the run done, from 0 to 100, while it runs.
+  percent: number | null;
+  error: string | null;
+  elapsedSeconds: number | null;
+}
+
+// Queues a run of configDir, the project's if none, and starts it once a
+// slot is free. The name defaults to the config folder's.
+export function enqueueJob(job: { name?: string; configDir?: string; seed?: number }): Promise<Job> {
+  return invoke<Job>("enqueue_job", {
+    job: { name: job.name ?? null, configDir: job.configDir ?? null, seed: job.seed ?? null },
+  });
+}
+
+// Running and finished jobs first, then the waiting ones in the order they
+// will start.
+export function listJobs(): Promise<Job[]> {
+  return invoke<Job[]>("list_jobs");
+}
+
+// Moves a waiting job to position among the waiting jobs, counting from 0.
+export function moveJob(id: number, position: number): Promise<Job[]> {
+  return invoke<Job[]>("move_job", { id, position });
+}
+
+export function cancelJob(id: number): Promise<Job[]> {
+  return invoke<Job[]>("cancel_job", { id });
+}
+
+// Drops the finished jobs from the list; their output stays on disk.
+export function clearFinishedJobs(): Promise<Job[]> {
+  return invoke<Job[]>("clear_finished_jobs");
+}
+
+export function getJobConcurrency(): Promise<number> {
+  return invoke<number>("get_job_concurrency");
+}
+
+// How many jobs run side by side, from 1 to 8.
+export function setJobConcurrency(count: number): Promise<void> {
+  return invoke("set_job_concurrency", { count });
+}
+
+// Calls back with every job whenever the queue changes; returns an
+// unsubscribe function.
+export function onJobsChanged(callback: (jobs: Job[]) => void): Promise<() => void> {
+  return listen<Job[]>("jobs-changed", (event) => callback(event.payload));
+}
+
+// ---------------------------------------------------------------------------
+//  Completion notifications (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// Calls back when the user clicks the notification of a finished generation,
+// after the window has been brought back; returns an unsubscribe function.
+export function onShowResults(callback: () => void): Promise<() => void> {
+  return listen("show-results", () => callback());
+}
+
+// ---------------------------------------------------------------------------
+//  Updates (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// Packaged builds also check once shortly after launch and offer the update
+// in a native dialog. Both calls reject in development builds.
+
+export interface UpdateInfo {
+  version: string;
+  currentVersion: string;
+  notes: string | null;
+}
+
+// Resolves to null if this is the latest version.
+export function checkForUpdate(): Promise<UpdateInfo | null> {
+  return invoke<UpdateInfo | null>("check_for_update");
+}
+
+// Installs the update found by the last checkForUpdate with the engine
+// stopped, then restarts the app.
+export function installUpdate(): Promise<void> {
+  return invoke("install_update");
+}
+
+// Calls back with the bytes downloaded so far while an update downloads;
+// returns an unsubscribe function.
+export function onUpdateProgress(
+  callback: (downloaded: number, total: number | null) => void,
+): Promise<() => void> {
+  return listen<{ downloaded: number; total: number | null }>("update-progress", (event) =>
+    callback(event.payload.downloaded, event.payload.total),
+  );
+}
+
+// ---------------------------------------------------------------------------
+//  Logs (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export type LogLevel = "ERROR" | "WARN" | "INFO" | "DEBUG" | "TRACE";
+
+export interface LogRecord {
+  // Milliseconds since the Unix epoch.
+  time: number;
+  level: LogLevel;
+  // The shell module that logged it, or "engine" for the engine's output.
+  target: string;
+  message: string;
+  fields: [string, string][];
+}
+
+// This session's log records at level or more severe, logged at or after
+// since (milliseconds since the Unix epoch), whose target, message or fields
+// contain filter, ignoring case; the newest 2000 at most, oldest first. The
+// levels each module logs at are set with the CK3GEN_LOG environment
+// variable, such as "warn,ck3_generator::sidecar=debug".
+export function queryLogs(
+  options: { level?: LogLevel; since?: number; filter?: string } = {}
+): Promise<LogRecord[]> {
+  return invoke<LogRecord[]>("query_logs", {
+    level: options.level ?? null,
+    since: options.since ?? null,
+    filter: options.filter ?? null,
+  });
+}
+
+export function openLogFolder(): Promise<void> {
+  return invoke("open_log_folder");
+}
+
+export interface EngineStderr {
+  kind: "info" | "warning" | "error" | "traceback";
+  // The line, or the whole traceback joined with newlines.
+  message: string;
+}
+
+// Calls back with every line or traceback the engine writes to stderr;
+// warnings, errors and tracebacks are also kept in engine-errors.log in the
+// log folder. Returns an unsubscribe function.
+export function onEngineStderr(callback: (entry: EngineStderr) => void): Promise<() => void> {
+  return listen<EngineStderr>("engine-stderr", (event) => callback(event.payload));
+}
+
+// ---------------------------------------------------------------------------
+//  Crash reports (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// A report zip holds the recent engine output, the configuration with paths
+// redacted and system details. Reports stay on disk until the user submits
+// one.
+
+export interface CrashReportInfo {
+  name: string;
+  path: string;
+  kind: "shell" | "engine" | null;
+}
+
+// Newest first. Reports of shell crashes are found here on the next launch.
+export function listCrashReports(): Promise<CrashReportInfo[]> {
+  return invoke<CrashReportInfo[]>("list_crash_reports");
+}
+
+// Uploads the report in builds with a report endpoint; otherwise opens a new
+// GitHub issue and reveals the report in the file manager to attach.
+export function submitCrashReport(name: string): Promise<void> {
+  return invoke("submit_crash_report", { name });
+}
+
+// Calls back when the engine crashed and a report was written; returns an
+// unsubscribe function.
+export function onCrashReport(callback: (report: CrashReportInfo) => void): Promise<() => void> {
+  return listen<CrashReportInfo>("crash-report", (event) => callback(event.payload));
+}
+
+// ---------------------------------------------------------------------------
+//  Family tree windows (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// A dynasty's tree can be detached into a window of its own. Every tree
+// window runs this same frontend and renders its TreeView instead of the app.
+
+export interface TreeView {
+  label: string;
+  // As named in the dynasty's family_tree_<dynasty>.png image.
+  dynasty: string;
+}
+
+// Opens a window for the dynasty's tree, or focuses the one already showing
+// it; resolves to the window's label.
+export function openTreeWindow(dynasty: string): Promise<string> {
+  return invoke<string>("open_tree_window", { dynasty });
+}
+
+// The calling window's tree, or null outside a tree window.
+export function getTreeView(): Promise<TreeView | null> {
+  return invoke<TreeView | null>("get_tree_view");
+}
+
+// Switches the calling tree window to another dynasty.
+export function setTreeDynasty(dynasty: string): Promise<void> {
+  return invoke("set_tree_dynasty", { dynasty });
+}
+
+export function listTreeWindows(): Promise<TreeView[]> {
+  return invoke<TreeView[]>("list_tree_windows");
+}
+
+// Calls back with the open tree windows whenever one opens, closes or
+// switches dynasty; returns an unsubscribe function.
+export function onTreeWindowsChanged(callback: (views: TreeView[]) => void): Promise<() => void> {
+  return listen<TreeView[]>("tree-windows-changed", (event) => callback(event.payload));
+}
+
+// Calls back after a generation has written new tree images; returns an
+// unsubscribe function.
+export function onTreesUpdated(callback: () => void): Promise<() => void> {
+  return listen("trees-updated", () => callback());
+}
+
+// ---------------------------------------------------------------------------
+//  Copying snippets (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// Put one entity's block from the last export on the clipboard, as written.
+// Both reject if the entity is not in the export.
+
+// id as in the history file, e.g. "lotr_1234".
+export function copyCharacterBlock(id: string): Promise<void> {
+  return invoke("copy_character_block", { id });
+}
+
+// id with or without its "dynasty_" prefix.
+export function copyDynastyBlock(id: string): Promise<void> {
+  return invoke("copy_dynasty_block", { id });
+}
+
+// Put the faces of the characters ids on the clipboard as the ruler
+// designer's persistent DNA strings, laid out by the genes of the CK3
+// installation or mod at gameDir: the string alone for one character, an
+// "id: string" line each for more. Resolves to each [id, string].
+export function copyRulerDesignerDna(ids: string[], gameDir: string): Promise<[string, string][]> {
+  return invoke<[string, string][]>("copy_ruler_designer_dna", { ids, gameDir });
+}
+
+// ---------------------------------------------------------------------------
+//  Mod scaffolding (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// Asks for the folder to build the mod in.
+export function pickModBuildDir(): Promise<string | null> {
+  return invoke<string | null>("pick_mod_build_dir");
+}
+
+// Lays out a mod in dir around the last export, with its descriptor,
+// metadata and a placeholder thumbnail, and resolves to the files written.
+// Name, version and tags come from the config's modSettings.
+export function scaffoldMod(dir: string): Promise<string[]> {
+  return invoke<string[]>("scaffold_mod", { dir });
+}
+
+export interface Deployment {
+  // The mod's own folder in the mod folder.
+  dir: string;
+  written: string[];
+  // Where the last deploy was moved to, or null if it was not backed up.
+  backup: string | null;
+}
+
+// Builds the mod like scaffoldMod, but in a folder named after it in the
+// Paradox mod folder modDir, or the one found on this machine, with its
+// launcher entry beside it. The last deploy is moved to <folder>.backup if
+// backup, and its files deleted otherwise.
+export function deployMod(backup: boolean, modDir?: string): Promise<Deployment> {
+  return invoke<Deployment>("deploy_mod", { modDir: modDir ?? null, backup });
+}
+
+// Enables the mod deployMod put in the mod folder modDir, or the one found,
+// in the game's dlc_load.json, alone if exclusive, and starts the game
+// installed in gameDir, or the one found, without the launcher. Resolves to
+// the mods now enabled.
+export function playWithMod(exclusive: boolean, gameDir?: string, modDir?: string): Promise<string[]> {
+  return invoke<string[]>("play_with_mod", { gameDir: gameDir ?? null, modDir: modDir ?? null, exclusive });
+}
+
+export interface WorkshopPackage {
+  dir: string; // the upload-ready folder
+  written: string[];
+  vdf: string; // the steamcmd item description beside it
+  warnings: string[]; // what the Workshop or the launcher would take issue with
+  publishedFileId: string | null; // once uploaded
+  uploaded: boolean;
+}
+
+// Builds the mod like scaffoldMod in dir, checks its preview and tags for
+// the Workshop and writes <folder>.workshop.vdf for steamcmd beside it. With
+// steamUser, whose credentials steamcmd has cached, it also uploads the
+// item, hidden at first; nothing is uploaded while there are warnings.
+export function packageForWorkshop(dir: string, steamUser?: string): Promise<WorkshopPackage> {
+  return invoke<WorkshopPackage>("package_for_workshop", { dir, steamUser: steamUser ?? null });
+}
+
+// ---------------------------------------------------------------------------
+//  CSV export (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// Writes characters.csv, one row per character of the last export, and
+// events.csv, one row per event of their histories, to dir. Resolves to the
+// files written.
+export function exportCsv(dir: string): Promise<string[]> {
+  return invoke<string[]>("export_csv", { dir });
+}
+
+// ---------------------------------------------------------------------------
+//  GEDCOM export (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// Writes the characters and families of the last export to a GEDCOM 5.5.1
+// file at path, for genealogy programs such as Gramps.
+export function exportGedcom(path: string): Promise<void> {
+  return invoke("export_gedcom", { path });
+}
+
+// ---------------------------------------------------------------------------
+//  Chronicle (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// Writes the last export to a Markdown file at path, a chapter per dynasty
+// telling year by year who was born, married, came to a title and died.
+export function exportChronicle(path: string): Promise<void> {
+  return invoke("export_chronicle", { path });
+}
+
+// ---------------------------------------------------------------------------
+//  HTML report (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// Writes the last export to a single HTML file at path, with its statistics,
+// a searchable character list and the tree of each dynasty, to share with
+// people who do not have the app.
+export function exportReport(path: string): Promise<void> {
+  return invoke("export_report", { path });
+}
+
+// ---------------------------------------------------------------------------
+//  Dynasty tree graphs (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export interface TreeExportOptions {
+  // Generations drawn, founders first; all if unset.
+  maxGenerations?: number;
+  // Fill in the members who held a title.
+  highlightRulers: boolean;
+  // Box the members of each cadet house.
+  markCadetHouses: boolean;
+}
+
+// Writes a Graphviz DOT graph of each dynasty of the last export to dir, and
+// with svg an SVG laid out by Graphviz too. Resolves to the files written.
+export function exportFamilyTrees(
+  dir: string,
+  options: TreeExportOptions,
+  svg: boolean
+): Promise<string[]> {
+  return invoke<string[]>("export_family_trees", { dir, options, svg });
+}
+
+// ---------------------------------------------------------------------------
+//  Project database (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// Every finished run is recorded in project.db in the project's config
+// folder. Queries take a run ID from listRuns, or the latest run if none.
+
+export interface RecordedRun {
+  id: number;
+  recordedAt: number; // milliseconds since the Unix epoch
+  engine: "native" | "python";
+  seed: number | null;
+  characters: number;
+}
+
+export interface CharacterQuery {
+  // Part of the name, in any case, or the whole ID.
+  text?: string;
+  // Matches the members of its houses too.
+  dynasty?: string;
+  house?: string;
+  culture?: string;
+  religion?: string;
+  female?: boolean;
+  // Born by this year and not dead before it.
+  aliveIn?: number;
+  trait?: string;
+  // At most and by default 100.
+  limit?: number;
+  offset?: number;
+}
+
+export interface StoredCharacter {
+  id: string;
+  name: string;
+  female: boolean;
+  dynasty: string | null;
+  dynastyName: string | null;
+  house: string | null;
+  culture: string | null;
+  religion: string | null;
+  sexuality: string | null;
+  father: string | null;
+  mother: string | null;
+  birth: string | null; // e.g. "867.3.1"
+  death: string | null;
+  deathReason: string | null;
+  // Diplomacy, martial, stewardship, intrigue, learning and prowess.
+  skills: (number | null)[];
+  traits: string[];
+}
+
+export interface CharacterPage {
+  // How many characters match in all.
+  total: number;
+  characters: StoredCharacter[];
+}
+
+export interface DynastyStats {
+  id: string;
+  name: string | null;
+  members: number;
+  living: number;
+  houses: number;
+  firstBirth: number | null;
+  lastBirth: number | null;
+}
+
+export interface CharacterFacets {
+  cultures: string[];
+  religions: string[];
+}
+
+export function listRuns(): Promise<RecordedRun[]> {
+  return invoke<RecordedRun[]>("list_runs");
+}
+
+export function deleteRun(run: number): Promise<void> {
+  return invoke("delete_run", { run });
+}
+
+// A page of the run's characters matching every filter of query, in order of
+// birth.
+export function searchCharacters(query: CharacterQuery, run?: number): Promise<CharacterPage> {
+  return invoke<CharacterPage>("search_characters", { run: run ?? null, query });
+}
+
+// The cultures and religions the run's characters have, to filter by.
+export function getCharacterFacets(run?: number): Promise<CharacterFacets> {
+  return invoke<CharacterFacets>("character_facets", { run: run ?? null });
+}
+
+// The run's dynasties, the largest first.
+export function getDynastyStats(run?: number): Promise<DynastyStats[]> {
+  return invoke<DynastyStats[]>("dynasty_stats", { run: run ?? null });
+}
+
+// A field of a character in each run, null where it is unset. field is a
+// StoredCharacter field, or a skill such as "martial".
+export interface FieldChange {
+  field: string;
+  before: string | null;
+  after: string | null;
+}
+
+export interface CharacterChange {
+  id: string;
+  // In the later run.
+  name: string;
+  // Dates first.
+  fields: FieldChange[];
+  traitsGained: string[];
+  traitsLost: string[];
+}
+
+// Characters are lined up by ID. Each list holds at most 500 characters, in
+// order of birth; the totals count them all.
+export interface RunDiff {
+  before: number;
+  after: number;
+  added: StoredCharacter[];
+  addedTotal: number;
+  removed: StoredCharacter[];
+  removedTotal: number;
+  changed: CharacterChange[];
+  changedTotal: number;
+  unchanged: number;
+}
+
+// What changed from run before to run after.
+export function compareRuns(before: number, after: number): Promise<RunDiff> {
+  return invoke<RunDiff>("compare_runs", { before, after });
+}
+
+// Settings kept with the project rather than the app, as JSON.
+export function getProjectSetting<T = unknown>(key: string): Promise<T | null> {
+  return invoke<T | null>("get_project_setting", { key });
+}
+
+// Unsets the setting if value is null.
+export function setProjectSetting(key: string, value: unknown): Promise<void> {
+  return invoke("set_project_setting", { key, value });
+}
+
+// ---------------------------------------------------------------------------
+//  Menu bar (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+// File > Open, Generate > Cancel and Help > Open log folder are handled by
+// the shell; an opened project arrives through onOpenProject.
+
+export type MenuAction = "new" | "save" | "run";
+
+// Calls back when File > New (Ctrl+N), File > Save (Ctrl+S) or Generate > Run
+// (Ctrl+G) is chosen; returns an unsubscribe function.
+export function onMenuAction(callback: (action: MenuAction) => void): Promise<() => void> {
+  return listen<MenuAction>("menu-action", (event) => callback(event.payload));
+}
+
+// ---------------------------------------------------------------------------
+//  Native engine (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export interface NativeDynastySummary {
+  dynastyId: string;
+  members: number;
+  livingAtEnd: number;
+  adopted: number;
+  // Marriages with each other dynasty, by its ID.
+  alliances: Record<string, number>;
+  // Cadet houses split off the dynasty.
+  houses: number;
+}
+
+export interface NativeSummary {
+  firstYear: number;
+  lastYear: number;
+  characters: number;
+  livingAtEnd: number;
+  dynasties: NativeDynastySummary[];
+  // Plugin hooks that failed, as "plugins/<file>: <hook>: <error>".
+  pluginErrors: string[];
+}
+
+// Simulates the project in the shell's Rust engine, writing the character
+// and title histories and the family trees like any generation. A parallel
+// run simulates the dynasties on separate threads and marries them to each
+// other afterwards.
+// Given gameDir, a CK3 installation or total conversion mod, the history
+// leaves out the traits that game does not define.
+export function runNativeSimulation(
+  parallel = false,
+  gameDir?: string
+): Promise<NativeSummary> {
+  return invoke<NativeSummary>("run_native_simulation", {
+    parallel,
+    gameDir: gameDir ?? null,
+  });
+}
+
+// Like runNativeSimulation, but starts from the characters of the history
+// file at historyPath, carrying its dynasties on over the project's period.
+// Portrait DNA is read from a dna_data.txt next to it, if there is one.
+export function continueNativeSimulation(
+  historyPath: string,
+  gameDir?: string
+): Promise<NativeSummary> {
+  return invoke<NativeSummary>("continue_native_simulation", {
+    history: historyPath,
+    gameDir: gameDir ?? null,
+  });
+}
+
+// Asks for a state written by saveNativeState to resume.
+export function pickStateFile(): Promise<string | null> {
+  return invoke<string | null>("pick_state_file");
+}
+
+// Asks where saveNativeState should write the state.
+export function pickStateSaveFile(): Promise<string | null> {
+  return invoke<string | null>("pick_state_save_file");
+}
+
+// Writes everything the last native run of the session simulated, every
+// character, relationship and event and the seed, to path as versioned JSON.
+export function saveNativeState(path: string): Promise<void> {
+  return invoke("save_native_state", { path });
+}
+
+// Like runNativeSimulation, but starts from a state written by
+// saveNativeState, carrying its run on over the project's period. Without a
+// seed in the launch options, the saved run's seed is used.
+export function resumeNativeSimulation(
+  statePath: string,
+  gameDir?: string
+): Promise<NativeSummary> {
+  return invoke<NativeSummary>("resume_native_simulation", {
+    state: statePath,
+    gameDir: gameDir ?? null,
+  });
+}
+
+export interface NativeRegeneration {
+  summary: NativeSummary;
+  // Dynasty IDs simulated again, in config order.
+  simulated: string[];
+  // Dynasty IDs whose section of the history was rewritten.
+  rewritten: string[];
+}
+
+// Like a parallel runNativeSimulation, but only simulates again the dynasties
+// whose settings changed since the last call; the others keep their history.
+export function regenerateNativeSimulation(gameDir?: string): Promise<NativeRegeneration> {
+  return invoke<NativeRegeneration>("regenerate_native_simulation", {
+    gameDir: gameDir ?? null,
+  });
+}
+
+export interface GameDataCheck {
+  // How many of each the game defines; 0 when it has no such folder.
+  traits: number;
+  cultures: number;
+  faiths: number;
+  // Game files that could not be read, each with why.
+  unreadable: string[];
+  // The config's cultures, faiths and traits the game lacks, one entry per
+  // config file.
+  problems: string[];
+}
+
+// Read common/traits, common/culture and common/religion of the CK3
+// installation or mod at gameDir and check the project's config against them.
+export function checkGameData(gameDir: string): Promise<GameDataCheck> {
+  return invoke<GameDataCheck>("check_game_data", { gameDir });
+}
+
+// ---------------------------------------------------------------------------
+//  ID collisions (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export type IdKind = "character" | "dynasty" | "house";
+
+export interface IdCollision {
+  kind: IdKind;
+  id: string;
+  file: string; // the export's file, e.g. "family_history.txt"
+  line: number;
+  otherFile: string; // the export's own file for an ID it defines twice
+  otherLine: number;
+}
+
+// Numeric IDs renumbered characters, dynasties and houses may take, both
+// included.
+export interface IdRange {
+  first: number;
+  last: number;
+}
+
+export interface RenumberedId {
+  kind: IdKind;
+  from: string;
+  to: string;
+}
+
+// The last export's IDs defined twice, or also defined by the history and
+// dynasty files in others, each of which may be a folder.
+export function findIdCollisions(others: string[]): Promise<IdCollision[]> {
+  return invoke<IdCollision[]>("find_id_collisions", { others });
+}
+
+// Gives the colliding IDs free ones from range and rewrites every reference
+// to them in the export's files. Resolves to the IDs changed.
+export function renumberIds(others: string[], range: IdRange): Promise<RenumberedId[]> {
+  return invoke<RenumberedId[]>("renumber_ids", { others, range });
+}
+
+// The numbers the character IDs of some files take up after one prefix.
+export interface UsedIds {
+  prefix: string; // e.g. "lineofelros"; empty for IDs that are only a number
+  first: number;
+  last: number;
+  count: number;
+  ours: boolean; // whether the config's characters get IDs with this prefix
+}
+
+export interface IdScan {
+  used: UsedIds[];
+  // The config's initialCharID, or the first round thousand past every
+  // number our prefixes take up if that is higher.
+  initialCharId: number;
+}
+
+// Reads the character histories of the game at gameDir, of the mods enabled
+// beside modDir, or the mod folder found, and of the files in others, and
+// suggests an initialCharID numbering the next run clear of them. The mod
+// deployMod writes is left out.
+export function scanCharacterIds(gameDir?: string, modDir?: string, others: string[] = []): Promise<IdScan> {
+  return invoke<IdScan>("scan_character_ids", { gameDir: gameDir ?? null, modDir: modDir ?? null, others });
+}
+
+// ---------------------------------------------------------------------------
+//  Output linting (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export interface OutputProblem {
+  file: string; // e.g. "family_history.txt"
+  line: number; // 1-based; 0 for the whole file
+  message: string;
+}
+
+// Calls back with the problems lintOutput finds, without a game or other
+// files, each time a generation completes; returns an unsubscribe function.
+export function onOutputProblems(callback: (problems: OutputProblem[]) => void): Promise<() => void> {
+  return listen<OutputProblem[]>("output-problems", (event) => callback(event.payload));
+}
+
+// Checks the last export the way CK3's error.log would. Given gameDir, also
+// flags traits, cultures and faiths that game does not define. References to
+// characters, dynasties and houses the history and dynasty files of others
+// define are not flagged.
+export function lintOutput(gameDir?: string, others: string[] = []): Promise<OutputProblem[]> {
+  return invoke<OutputProblem[]>("lint_output", { gameDir: gameDir ?? null, others });
+}
+
+export interface MarriageProblem {
+  severity: Severity;
+  character: string;
+  spouse: string;
+  message: string;
+}
+
+// Checks the marriages of the last export by the config's marriageChecks
+// rules, in the order they happen.
+export function checkMarriages(): Promise<MarriageProblem[]> {
+  return invoke<MarriageProblem[]>("check_marriages");
+}
+
+// Flags traits gained against others held: those the config's personality
+// traits exclude, or given gameDir, the game makes opposites or groups,
+// second educations and personality traits past the config's count.
+export function checkTraits(gameDir?: string): Promise<OutputProblem[]> {
+  return invoke<OutputProblem[]>("check_traits", { gameDir: gameDir ?? null });
+}
+
+// A character alive at the end of the period past their blood tier's
+// lifespan cap. Dates are written as the history's date keys.
+export interface Survivor {
+  id: string;
+  name: string;
+  dynasty: string | null;
+  birth: string;
+  death: string | null; // a death the history dates after the end
+  age: number; // at that death, or at the end if there is none
+  maxAge: number;
+  newDeath: string;
+}
+
+export interface SurvivorReport {
+  end: string;
+  survivors: Survivor[];
+  extinct: string[]; // dynasties only the survivors keep alive
+}
+
+export function checkSurvivors(): Promise<SurvivorReport> {
+  return invoke<SurvivorReport>("check_survivors");
+}
+
+// Writes the survivors' new deaths into the last export's history.
+// Resolves to what was fixed.
+export function repairSurvivors(): Promise<SurvivorReport> {
+  return invoke<SurvivorReport>("repair_survivors");
+}
+
+// ---------------------------------------------------------------------------
+//  Name suggestions (Tauri shell only)
+// ---------------------------------------------------------------------------
+
+export interface SuggestedName {
+  name: string;
+  // e.g. "Astrid Bjorndottir", when the culture uses patronyms and a father
+  // was given.
+  patronymic: string | null;
+}
+
+// Up to n different names of a culture, common ones more often. Names come
+// from the bundled name lists, then from the game's if gameDir is given.
+export function suggestNames(
+  culture: string,
+  gender: "male" | "female",
+  n: number,
+  options: { gameDir?: string; father?: string } = {}
+): Promise<SuggestedName[]> {
+  return invoke<SuggestedName[]>("suggest_names", {
+    culture,
+    gender,
+    count: n,
+    gameDir: options.gameDir ?? null,
+    father: options.father ?? null,
+  });
+}
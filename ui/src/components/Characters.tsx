@@ -0,0 +1,418 @@
+import { useEffect, useState } from "react";
+import {
+  CharacterFacets,
+  CharacterField,
+  CharacterPage,
+  CharacterQuery,
+  copyCharacterBlock,
+  copyDynastyBlock,
+  copyRulerDesignerDna,
+  deleteRun,
+  DynastyStats,
+  editCharacter,
+  getCharacterFacets,
+  getDynastyStats,
+  listRuns,
+  onUndoChanged,
+  pickGameDir,
+  RecordedRun,
+  searchCharacters,
+} from "../api";
+
+const PAGE_SIZE = 50;
+
+const cell = { padding: "0.3rem 0.5rem", borderBottom: "1px solid var(--border)", textAlign: "left" as const };
+
+export default function Characters() {
+  const [runs, setRuns] = useState<RecordedRun[]>([]);
+  const [run, setRun] = useState<number | undefined>(undefined);
+  const [facets, setFacets] = useState<CharacterFacets>({ cultures: [], religions: [] });
+  const [dynasties, setDynasties] = useState<DynastyStats[]>([]);
+  const [query, setQuery] = useState<CharacterQuery>({});
+  const [page, setPage] = useState<CharacterPage | null>(null);
+  const [offset, setOffset] = useState(0);
+  const [loading, setLoading] = useState(true);
+  const [error, setError] = useState<string | null>(null);
+  const [selected, setSelected] = useState<Set<string>>(new Set());
+  const [gameDir, setGameDir] = useState<string | null>(null);
+  const [copied, setCopied] = useState<string | null>(null);
+  const [editing, setEditing] = useState<{ id: string; field: CharacterField; value: string } | null>(null);
+  // Bumped to read the page again after a character is edited, undone or
+  // redone.
+  const [edits, setEdits] = useState(0);
+
+  const loadRuns = () => {
+    listRuns()
+      .then((runs) => {
+        setRuns(runs);
+        setRun((current) => current ?? runs[0]?.id);
+      })
+      .catch((err) => setError(String(err)));
+  };
+
+  useEffect(loadRuns, []);
+
+  // The newest run left is shown instead.
+  const removeRun = () => {
+    if (run === undefined) return;
+    if (!window.confirm("Delete this run and its characters?")) return;
+    deleteRun(run)
+      .then(() => {
+        setOffset(0);
+        setSelected(new Set());
+        setRun(undefined);
+        loadRuns();
+      })
+      .catch((err) => setError(String(err)));
+  };
+
+  useEffect(() => {
+    const unlisten = onUndoChanged(() => setEdits((n) => n + 1));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  useEffect(() => {
+    if (run === undefined) return;
+    Promise.all([getCharacterFacets(run), getDynastyStats(run)])
+      .then(([facets, dynasties]) => {
+        setFacets(facets);
+        setDynasties(dynasties);
+      })
+      .catch((err) => setError(String(err)));
+  }, [run]);
+
+  useEffect(() => {
+    if (run === undefined) {
+      setLoading(false);
+      return;
+    }
+    setLoading(true);
+    setError(null);
+    searchCharacters({ ...query, limit: PAGE_SIZE, offset }, run)
+      .then(setPage)
+      .catch((err) => {
+        setPage(null);
+        setError(String(err));
+      })
+      .finally(() => setLoading(false));
+  }, [run, query, offset, edits]);
+
+  const filter = (changes: CharacterQuery) => {
+    setOffset(0);
+    setQuery({ ...query, ...changes });
+  };
+
+  const toggle = (id: string) => {
+    setSelected((prev) => {
+      const next = new Set(prev);
+      next.has(id) ? next.delete(id) : next.add(id);
+      return next;
+    });
+  };
+
+  // The strings are laid out by a game's genes, so the game is asked for the
+  // first time.
+  const copyDna = async () => {
+    setError(null);
+    setCopied(null);
+    try {
+      const dir = gameDir ?? (await pickGameDir());
+      if (!dir) return;
+      setGameDir(dir);
+      const strings = await copyRulerDesignerDna([...selected], dir);
+      setCopied(
+        strings.length === 1
+          ? "Ruler designer DNA copied to the clipboard."
+          : `Ruler designer DNA of ${strings.length} characters copied to the clipboard.`
+      );
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  // Only the latest run's history is on disk to edit or copy from.
+  const editable = run !== undefined && run === runs[0]?.id;
+
+  const copyBlock = (action: Promise<void>, what: string) => {
+    setError(null);
+    setCopied(null);
+    action.then(() => setCopied(`${what} copied to the clipboard.`)).catch((err) => setError(String(err)));
+  };
+
+  const commitEdit = () => {
+    if (!editing) return;
+    const { id, field, value } = editing;
+    setEditing(null);
+    editCharacter(id, field, value.trim()).catch((err) => setError(String(err)));
+  };
+
+  // Double-clicking a name, culture or religion of the latest run edits it.
+  const editableCell = (id: string, field: CharacterField, value: string | null) =>
+    editing?.id === id && editing.field === field ? (
+      <input
+        type="text"
+        autoFocus
+        value={editing.value}
+        onChange={(e) => setEditing({ ...editing, value: e.target.value })}
+        onBlur={commitEdit}
+        onKeyDown={(e) => {
+          if (e.key === "Enter") commitEdit();
+          if (e.key === "Escape") setEditing(null);
+        }}
+      />
+    ) : (
+      <span onDoubleClick={() => editable && setEditing({ id, field, value: value ?? "" })}>{value}</span>
+    );
+
+  const runLabel = (run: RecordedRun) =>
+    `${new Date(run.recordedAt).toLocaleString()} (${run.engine}, ${run.characters} characters)`;
+
+  return (
+    <div>
+      <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center", marginBottom: "1rem" }}>
+        <h2 style={{ margin: 0 }}>Characters</h2>
+        <span style={{ display: "flex", gap: "0.5rem" }}>
+          <select
+            value={run ?? ""}
+            onChange={(e) => {
+              setOffset(0);
+              setSelected(new Set());
+              setRun(Number(e.target.value));
+            }}
+          >
+            {runs.map((run) => (
+              <option key={run.id} value={run.id}>
+                {runLabel(run)}
+              </option>
+            ))}
+          </select>
+          <button className="btn btn-secondary btn-sm" disabled={run === undefined} onClick={removeRun}>
+            Delete Run
+          </button>
+          <button className="btn btn-secondary btn-sm" disabled={selected.size === 0} onClick={copyDna}>
+            Copy Ruler Designer DNA{selected.size > 1 ? ` (${selected.size})` : ""}
+          </button>
+          <button className="btn btn-secondary btn-sm" onClick={loadRuns}>
+            Refresh
+          </button>
+        </span>
+      </div>
+
+      {runs.length === 0 && !error && (
+        <div className="msg msg-info">No runs recorded in this project yet. Run the simulation first.</div>
+      )}
+
+      {run !== undefined && (
+        <div className="field-row" style={{ alignItems: "flex-end", marginBottom: "1rem" }}>
+          <div className="field">
+            <label>Name or ID</label>
+            <input
+              type="text"
+              value={query.text ?? ""}
+              onChange={(e) => filter({ text: e.target.value || undefined })}
+            />
+          </div>
+          <div className="field">
+            <label>Dynasty</label>
+            <select
+              value={query.dynasty ?? ""}
+              onChange={(e) => filter({ dynasty: e.target.value || undefined })}
+            >
+              <option value="">Any</option>
+              {dynasties.map((dynasty) => (
+                <option key={dynasty.id} value={dynasty.id}>
+                  {dynasty.name ?? dynasty.id}
+                </option>
+              ))}
+            </select>
+          </div>
+          <div className="field">
+            <label>Culture</label>
+            <select
+              value={query.culture ?? ""}
+              onChange={(e) => filter({ culture: e.target.value || undefined })}
+            >
+              <option value="">Any</option>
+              {facets.cultures.map((culture) => (
+                <option key={culture}>{culture}</option>
+              ))}
+            </select>
+          </div>
+          <div className="field">
+            <label>Religion</label>
+            <select
+              value={query.religion ?? ""}
+              onChange={(e) => filter({ religion: e.target.value || undefined })}
+            >
+              <option value="">Any</option>
+              {facets.religions.map((religion) => (
+                <option key={religion}>{religion}</option>
+              ))}
+            </select>
+          </div>
+          <div className="field">
+            <label>Sex</label>
+            <select
+              value={query.female === undefined ? "" : String(query.female)}
+              onChange={(e) => filter({ female: e.target.value ? e.target.value === "true" : undefined })}
+            >
+              <option value="">Any</option>
+              <option value="false">Male</option>
+              <option value="true">Female</option>
+            </select>
+          </div>
+          <div className="field">
+            <label>Alive in</label>
+            <input
+              type="number"
+              step={1}
+              value={query.aliveIn ?? ""}
+              onChange={(e) => filter({ aliveIn: e.target.value ? Number(e.target.value) : undefined })}
+            />
+          </div>
+          <div className="field">
+            <label>Trait</label>
+            <input
+              type="text"
+              value={query.trait ?? ""}
+              onChange={(e) => filter({ trait: e.target.value.trim() || undefined })}
+            />
+          </div>
+        </div>
+      )}
+
+      {error && <div className="msg msg-error">{error}</div>}
+      {copied && <div className="msg msg-success">{copied}</div>}
+
+      {loading && (
+        <div style={{ display: "flex", alignItems: "center", gap: "0.75rem", color: "var(--text-muted)" }}>
+          <span className="spinner" /> Searching...
+        </div>
+      )}
+
+      {!loading && page && (
+        <>
+          <p style={{ color: "var(--text-muted)", fontSize: "0.85rem" }}>
+            {page.total === 0
+              ? "No characters match."
+              : `${offset + 1}–${offset + page.characters.length} of ${page.total} characters`}
+          </p>
+          {page.characters.length > 0 && (
+            <table style={{ width: "100%", borderCollapse: "collapse", fontSize: "0.85rem" }}>
+              <thead>
+                <tr>
+                  <th style={cell} />
+                  {["Name", "Dynasty", "Culture", "Religion", "Born", "Died", "Traits"].map((heading) => (
+                    <th key={heading} style={{ ...cell, color: "var(--text-label)" }}>
+                      {heading}
+                    </th>
+                  ))}
+                  {editable && <th style={cell} />}
+                </tr>
+              </thead>
+              <tbody>
+                {page.characters.map((character) => (
+                  <tr key={character.id}>
+                    <td style={cell}>
+                      <input
+                        type="checkbox"
+                        checked={selected.has(character.id)}
+                        onChange={() => toggle(character.id)}
+                      />
+                    </td>
+                    <td style={cell} title={character.id}>
+                      {editableCell(character.id, "name", character.name)} {character.female ? "♀" : "♂"}
+                    </td>
+                    <td style={cell}>{character.dynastyName ?? character.dynasty ?? "lowborn"}</td>
+                    <td style={cell}>{editableCell(character.id, "culture", character.culture)}</td>
+                    <td style={cell}>{editableCell(character.id, "religion", character.religion)}</td>
+                    <td style={cell}>{character.birth}</td>
+                    <td style={cell} title={character.deathReason ?? undefined}>
+                      {character.death}
+                    </td>
+                    <td style={cell}>{character.traits.join(", ")}</td>
+                    {editable && (
+                      <td style={cell}>
+                        <button
+                          className="btn btn-secondary btn-sm"
+                          title="Copy this character's block of the history"
+                          onClick={() => copyBlock(copyCharacterBlock(character.id), `${character.name}'s block`)}
+                        >
+                          Copy
+                        </button>
+                      </td>
+                    )}
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          )}
+          <div style={{ display: "flex", gap: "0.5rem", marginTop: "0.75rem" }}>
+            <button
+              className="btn btn-secondary btn-sm"
+              disabled={offset === 0}
+              onClick={() => setOffset(Math.max(0, offset - PAGE_SIZE))}
+            >
+              Previous
+            </button>
+            <button
+              className="btn btn-secondary btn-sm"
+              disabled={offset + PAGE_SIZE >= page.total}
+              onClick={() => setOffset(offset + PAGE_SIZE)}
+            >
+              Next
+            </button>
+          </div>
+        </>
+      )}
+
+      {dynasties.length > 0 && (
+        <>
+          <h3>Dynasties</h3>
+          <table style={{ width: "100%", borderCollapse: "collapse", fontSize: "0.85rem" }}>
+            <thead>
+              <tr>
+                {["Dynasty", "Members", "Living", "Houses", "Births"].map((heading) => (
+                  <th key={heading} style={{ ...cell, color: "var(--text-label)" }}>
+                    {heading}
+                  </th>
+                ))}
+                {editable && <th style={cell} />}
+              </tr>
+            </thead>
+            <tbody>
+              {dynasties.map((dynasty) => (
+                <tr key={dynasty.id}>
+                  <td style={cell} title={dynasty.id}>
+                    {dynasty.name ?? dynasty.id}
+                  </td>
+                  <td style={cell}>{dynasty.members}</td>
+                  <td style={cell}>{dynasty.living}</td>
+                  <td style={cell}>{dynasty.houses}</td>
+                  <td style={cell}>
+                    {dynasty.firstBirth}–{dynasty.lastBirth}
+                  </td>
+                  {editable && (
+                    <td style={cell}>
+                      <button
+                        className="btn btn-secondary btn-sm"
+                        title="Copy this dynasty's definition"
+                        onClick={() =>
+                          copyBlock(copyDynastyBlock(dynasty.id), `${dynasty.name ?? dynasty.id}'s definition`)
+                        }
+                      >
+                        Copy
+                      </button>
+                    </td>
+                  )}
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,162 @@
+import { useEffect, useState } from "react";
+import { CharacterChange, RecordedRun, RunDiff, StoredCharacter, compareRuns, listRuns } from "../api";
+
+const cell = { padding: "0.3rem 0.5rem", borderBottom: "1px solid var(--border)", textAlign: "left" as const };
+
+const runLabel = (run: RecordedRun) =>
+  `${new Date(run.recordedAt).toLocaleString()} (${run.engine}, ${run.characters} characters)`;
+
+function more(shown: number, total: number): string | null {
+  return total > shown ? `and ${total - shown} more` : null;
+}
+
+function CharacterList({ characters, total }: { characters: StoredCharacter[]; total: number }) {
+  return (
+    <>
+      <table style={{ width: "100%", borderCollapse: "collapse", fontSize: "0.85rem" }}>
+        <thead>
+          <tr>
+            {["Name", "Dynasty", "Born", "Died"].map((heading) => (
+              <th key={heading} style={{ ...cell, color: "var(--text-label)" }}>
+                {heading}
+              </th>
+            ))}
+          </tr>
+        </thead>
+        <tbody>
+          {characters.map((character) => (
+            <tr key={character.id}>
+              <td style={cell} title={character.id}>
+                {character.name} {character.female ? "♀" : "♂"}
+              </td>
+              <td style={cell}>{character.dynastyName ?? character.dynasty ?? "lowborn"}</td>
+              <td style={cell}>{character.birth}</td>
+              <td style={cell}>{character.death}</td>
+            </tr>
+          ))}
+        </tbody>
+      </table>
+      {more(characters.length, total) && <p style={{ color: "var(--text-muted)" }}>{more(characters.length, total)}</p>}
+    </>
+  );
+}
+
+function describe(change: CharacterChange): string[] {
+  const lines = change.fields.map(
+    (field) => `${field.field}: ${field.before ?? "none"} → ${field.after ?? "none"}`
+  );
+  if (change.traitsGained.length > 0) lines.push(`gained ${change.traitsGained.join(", ")}`);
+  if (change.traitsLost.length > 0) lines.push(`lost ${change.traitsLost.join(", ")}`);
+  return lines;
+}
+
+export default function CompareRuns() {
+  const [runs, setRuns] = useState<RecordedRun[]>([]);
+  const [before, setBefore] = useState<number | undefined>(undefined);
+  const [after, setAfter] = useState<number | undefined>(undefined);
+  const [diff, setDiff] = useState<RunDiff | null>(null);
+  const [loading, setLoading] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  // The latest run against the one before it, to begin with.
+  useEffect(() => {
+    listRuns()
+      .then((runs) => {
+        setRuns(runs);
+        setAfter(runs[0]?.id);
+        setBefore(runs[1]?.id);
+      })
+      .catch((err) => setError(String(err)));
+  }, []);
+
+  useEffect(() => {
+    if (before === undefined || after === undefined) return;
+    setLoading(true);
+    setError(null);
+    compareRuns(before, after)
+      .then(setDiff)
+      .catch((err) => {
+        setDiff(null);
+        setError(String(err));
+      })
+      .finally(() => setLoading(false));
+  }, [before, after]);
+
+  const picker = (value: number | undefined, onChange: (run: number) => void) => (
+    <select value={value ?? ""} onChange={(e) => onChange(Number(e.target.value))}>
+      {runs.map((run) => (
+        <option key={run.id} value={run.id}>
+          {runLabel(run)}
+        </option>
+      ))}
+    </select>
+  );
+
+  return (
+    <div>
+      <h2>Compare Runs</h2>
+      {runs.length < 2 ? (
+        <p>Two recorded runs are needed to compare.</p>
+      ) : (
+        <div className="field-row">
+          <div className="field">
+            <label>Before</label>
+            {picker(before, setBefore)}
+          </div>
+          <div className="field">
+            <label>After</label>
+            {picker(after, setAfter)}
+          </div>
+        </div>
+      )}
+
+      {loading && <span className="spinner" />}
+      {error && <div className="msg msg-error">{error}</div>}
+
+      {diff && !loading && (
+        <>
+          <p style={{ color: "var(--text-muted)", fontSize: "0.85rem" }}>
+            {diff.addedTotal} added, {diff.removedTotal} removed, {diff.changedTotal} changed, {diff.unchanged} unchanged
+          </p>
+
+          {diff.addedTotal > 0 && (
+            <div className="panel">
+              <h3>Added</h3>
+              <CharacterList characters={diff.added} total={diff.addedTotal} />
+            </div>
+          )}
+          {diff.removedTotal > 0 && (
+            <div className="panel">
+              <h3>Removed</h3>
+              <CharacterList characters={diff.removed} total={diff.removedTotal} />
+            </div>
+          )}
+          {diff.changedTotal > 0 && (
+            <div className="panel">
+              <h3>Changed</h3>
+              <table style={{ width: "100%", borderCollapse: "collapse", fontSize: "0.85rem" }}>
+                <tbody>
+                  {diff.changed.map((change) => (
+                    <tr key={change.id}>
+                      <td style={{ ...cell, verticalAlign: "top" }} title={change.id}>
+                        {change.name}
+                      </td>
+                      <td style={cell}>
+                        {describe(change).map((line, i) => (
+                          <div key={i}>{line}</div>
+                        ))}
+                      </td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+              {more(diff.changed.length, diff.changedTotal) && (
+                <p style={{ color: "var(--text-muted)" }}>{more(diff.changed.length, diff.changedTotal)}</p>
+              )}
+            </div>
+          )}
+        </>
+      )}
+    </div>
+  );
+}
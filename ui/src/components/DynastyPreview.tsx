@@ -0,0 +1,127 @@
+import { useEffect, useState } from "react";
+import { DynastyPreview as Preview, InitializationConfig, PreviewMember, previewDynasty } from "../api";
+
+// Time to wait after the last change before simulating again.
+const DEBOUNCE_MS = 300;
+
+const randomSeed = () => Math.floor(Math.random() * 2 ** 32);
+
+function lifespan(member: PreviewMember): string {
+  return `${member.born}–${member.died ?? ""}`;
+}
+
+function Branch({ member, preview }: { member: PreviewMember; preview: Preview }) {
+  const byId = new Map(preview.members.map((m) => [m.id, m]));
+  const spouses = member.spouses.map((id) => byId.get(id)).filter((s): s is PreviewMember => !!s);
+  const children = preview.members.filter(
+    (m) => !m.marriedIn && (m.father === member.id || m.mother === member.id)
+  );
+  return (
+    <li>
+      {member.name} {member.female ? "♀" : "♂"}{" "}
+      <span style={{ color: "var(--text-muted)" }}>{lifespan(member)}</span>
+      {spouses.map((spouse) => (
+        <span key={spouse.id} style={{ color: "var(--text-muted)" }}>
+          {" "}
+          ∞ {spouse.name} {lifespan(spouse)}
+        </span>
+      ))}
+      {children.length > 0 && (
+        <ul>
+          {children.map((child) => (
+            <Branch key={child.id} member={child} preview={preview} />
+          ))}
+        </ul>
+      )}
+    </li>
+  );
+}
+
+export default function DynastyPreview({ config }: { config: InitializationConfig }) {
+  const dynasties = config.dynasties;
+  const [dynastyId, setDynastyId] = useState(dynasties[0]?.dynastyID ?? "");
+  const [generations, setGenerations] = useState(3);
+  const [seed, setSeed] = useState(randomSeed);
+  const [preview, setPreview] = useState<Preview | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  // A removed dynasty gives way to the first one left.
+  const selected = dynasties.some((d) => d.dynastyID === dynastyId) ? dynastyId : dynasties[0]?.dynastyID;
+
+  // Simulated again shortly after every change, unsaved ones included; an
+  // answer overtaken by a later change is dropped.
+  useEffect(() => {
+    if (!selected) return;
+    let stale = false;
+    const timer = setTimeout(() => {
+      setBusy(true);
+      previewDynasty(config, selected, generations, seed)
+        .then((preview) => {
+          if (stale) return;
+          setPreview(preview);
+          setError(null);
+        })
+        .catch((err) => !stale && setError(String(err)))
+        .finally(() => !stale && setBusy(false));
+    }, DEBOUNCE_MS);
+    return () => {
+      stale = true;
+      clearTimeout(timer);
+    };
+  }, [config, selected, generations, seed]);
+
+  if (!selected) return null;
+  const roots = preview?.members.filter((m) => !m.marriedIn && m.generation === 1) ?? [];
+
+  return (
+    <div className="panel">
+      <h3>Quick Preview</h3>
+      <p style={{ color: "var(--text-muted)", fontSize: "0.85rem", marginBottom: "0.75rem" }}>
+        The first generations of one dynasty, simulated again as you change its settings.
+      </p>
+      <div className="field-row">
+        <div className="field">
+          <label>Dynasty</label>
+          <select value={selected} onChange={(e) => setDynastyId(e.target.value)}>
+            {dynasties.map((d, i) => (
+              <option key={d.dynastyID + i} value={d.dynastyID}>
+                {d.dynastyName || d.dynastyID}
+              </option>
+            ))}
+          </select>
+        </div>
+        <div className="field">
+          <label>Generations</label>
+          <select value={generations} onChange={(e) => setGenerations(Number(e.target.value))}>
+            {[2, 3, 4, 5].map((n) => (
+              <option key={n} value={n}>
+                {n}
+              </option>
+            ))}
+          </select>
+        </div>
+      </div>
+      <div className="btn-row">
+        <button className="btn btn-secondary btn-sm" onClick={() => setSeed(randomSeed())}>
+          Reroll
+        </button>
+        {busy && <span className="spinner" />}
+      </div>
+
+      {error && <div className="msg msg-error">{error}</div>}
+      {preview && !error && (
+        <>
+          <p style={{ color: "var(--text-muted)", fontSize: "0.85rem" }}>
+            {preview.firstYear}–{preview.lastYear}, {preview.members.length} characters
+          </p>
+          <ul style={{ fontSize: "0.85rem" }}>
+            {roots.map((root) => (
+              <Branch key={root.id} member={root} preview={preview} />
+            ))}
+          </ul>
+        </>
+      )}
+    </div>
+  );
+}
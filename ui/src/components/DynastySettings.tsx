@@ -6,14 +6,31 @@
  * server-side Pydantic rules so errors are caught without a network round-trip.
  */
 
-import { useState } from "react";
+import { useEffect, useState } from "react";
+import { isTauri } from "@tauri-apps/api/core";
 import {
+  cancelGeneration,
+  detectGamePaths,
   Dynasty,
+  GenerationProgress,
+  getGenerationProgress,
+  getWorkerCount,
+  ImportedDynasty,
   InitializationConfig,
   NameInheritance,
+  onEngineStderr,
+  onGenerationCancelled,
+  onGenerationEnded,
+  onGenerationProgress,
+  onOutputProblems,
+  pauseGeneration,
+  resumeGeneration,
   saveInitializationConfig,
   resetInitializationConfig,
+  runParallelSimulation,
+  scanCharacterIds,
   setInitializationFallback,
+  setWorkerCount,
   streamSimulation,
   SimulationMessage,
 } from "../api";
@@ -22,6 +39,11 @@ import {
   useValidation,
   ValidationErrors,
 } from "../hooks/useValidation";
+import DynastyPreview from "./DynastyPreview";
+import DynastyTemplates, { TemplatePicker } from "./DynastyTemplates";
+import HistoryImport from "./HistoryImport";
+import NameSuggestions from "./NameSuggestions";
+import NativeRuns from "./NativeRuns";
 
 // ---------------------------------------------------------------------------
 //  Constants
@@ -39,6 +61,8 @@ const SUCCESSION_OPTIONS = [
   "PRIMOGENITURE",
   "ULTIMOGENITURE",
   "SENIORITY",
+  "PARTITION",
+  "ELECTIVE",
 ] as const;
 
 const DEFAULT_NAME_INHERITANCE: NameInheritance = {
@@ -334,6 +358,54 @@ function DynastyForm({ value, onChange, errors = {} }: DynastyFormProps) {
   );
 }
 
+// ---------------------------------------------------------------------------
+//  Progress bar
+// ---------------------------------------------------------------------------
+
+// "about 3 min left" for an estimate of `seconds`.
+function formatEta(seconds: number): string {
+  if (seconds < 60) return `about ${Math.max(1, Math.round(seconds))} s left`;
+  if (seconds < 3600) return `about ${Math.round(seconds / 60)} min left`;
+  return `about ${(seconds / 3600).toFixed(1)} h left`;
+}
+
+function progressLabel(progress: GenerationProgress): string {
+  const parts: string[] = [];
+  if (progress.phase === "seeding") {
+    parts.push("Seeding dynasties");
+  } else if (progress.phase === "simulating") {
+    parts.push(
+      progress.yearIndex !== null && progress.years !== null
+        ? `Simulating year ${progress.year} (${progress.yearIndex} of ${progress.years})`
+        : "Simulating"
+    );
+  } else {
+    parts.push("Writing files");
+  }
+  parts.push(`${Math.floor(progress.percent)}%`);
+  if (progress.etaSeconds !== null) parts.push(formatEta(progress.etaSeconds));
+  return parts.join(" · ");
+}
+
+// The log line for a cancelled run; removed is null for a parallel one.
+function cancelledLog(removed: number | null): string {
+  if (removed === null) return "Cancelled.";
+  return removed === 0
+    ? "Cancelled before anything was written."
+    : `Cancelled; ${removed} partial output file${removed === 1 ? "" : "s"} removed.`;
+}
+
+function ProgressBar({ progress }: { progress: GenerationProgress }) {
+  return (
+    <div className="sim-progress">
+      <div className="sim-progress-bar">
+        <div className="sim-progress-fill" style={{ width: `${progress.percent}%` }} />
+      </div>
+      <div className="sim-progress-label">{progressLabel(progress)}</div>
+    </div>
+  );
+}
+
 // ---------------------------------------------------------------------------
 //  Main component
 // ---------------------------------------------------------------------------
@@ -341,17 +413,61 @@ function DynastyForm({ value, onChange, errors = {} }: DynastyFormProps) {
 interface Props {
   config: InitializationConfig;
   onConfigChange: (cfg: InitializationConfig) => void;
+  // Generate > Run was chosen; onRunRequestTaken is called as the run starts.
+  runRequested?: boolean;
+  onRunRequestTaken?: () => void;
 }
 
-export default function DynastySettings({ config, onConfigChange }: Props) {
+export default function DynastySettings({
+  config,
+  onConfigChange,
+  runRequested = false,
+  onRunRequestTaken,
+}: Props) {
   const [saving, setSaving]           = useState(false);
   const [simRunning, setSimRunning]   = useState(false);
   const [logs, setLogs]               = useState<string[]>([]);
+  const [progress, setProgress]       = useState<GenerationProgress | null>(null);
+  const [cancelling, setCancelling]   = useState(false);
+  const [paused, setPaused]           = useState(false);
+  const [workers, setWorkers]         = useState(1);
   const [feedback, setFeedback]       = useState<{ type: "success" | "error"; text: string } | null>(null);
   const [newDynasty, setNewDynasty]   = useState<Dynasty>({ ...EMPTY_DYNASTY, nameInheritance: { ...DEFAULT_NAME_INHERITANCE } });
   const [addOpen, setAddOpen]         = useState(false);
   const [expandedIdx, setExpandedIdx] = useState<number | null>(null);
 
+  // The shell passes on the generation's progress; a run already going when
+  // the window opens is picked up where it is.
+  useEffect(() => {
+    if (!isTauri()) return;
+    getWorkerCount().then(setWorkers).catch(() => {});
+    getGenerationProgress().then((current) => current && setProgress(current)).catch(() => {});
+    const unlisten = [
+      onGenerationProgress(setProgress),
+      onGenerationEnded(() => setProgress(null)),
+      // Also sent for a run the engine was following, or cancelled from the
+      // tray or the menu bar.
+      onGenerationCancelled(({ removedFiles }) => setLogs((prev) => [...prev, cancelledLog(removedFiles)])),
+      // The engine's warnings and tracebacks, such as those of a failed run.
+      onEngineStderr(({ kind, message }) => {
+        if (kind === "info") return;
+        setLogs((prev) => [...prev, `${kind === "warning" ? "WARNING" : "ERROR"}: ${message}`]);
+      }),
+      // The shell lints every completed run's output.
+      onOutputProblems((problems) =>
+        setLogs((prev) => [
+          ...prev,
+          problems.length === 0
+            ? "The output has no problems."
+            : `The output has ${problems.length} problem${problems.length === 1 ? "" : "s"}; see the Problems tab.`,
+        ])
+      ),
+    ];
+    return () => {
+      unlisten.forEach((pending) => pending.then((stop) => stop()));
+    };
+  }, []);
+
   // Validation for the "add new dynasty" form only.
   // Inline edits use per-row validation state managed in EditableDynastyRow.
   const { errors: newDynastyErrors, validate: validateNew, clearErrors: clearNew } =
@@ -431,25 +547,120 @@ export default function DynastySettings({ config, onConfigChange }: Props) {
   const handleEditDynasty = (idx: number, updated: Dynasty) =>
     updateDynasties(dynasties.map((d, i) => (i === idx ? updated : d)));
 
+  const handleImportDynasty = (imported: ImportedDynasty) =>
+    updateDynasties([
+      ...dynasties,
+      {
+        ...EMPTY_DYNASTY,
+        dynastyID: imported.id,
+        dynastyName: imported.name ?? "",
+        dynastyMotto: imported.motto ?? "",
+        cultureID: imported.culture ?? "",
+        isHouse: imported.dynasty !== null,
+        nameInheritance: { ...DEFAULT_NAME_INHERITANCE },
+      },
+    ]);
+
   const handleDeleteAll = () => onConfigChange({ ...config, dynasties: [] });
 
   // ── Simulation ────────────────────────────────────────────────────────────
 
+  // Numbers the characters past the IDs the game and the enabled mods use.
+  const handleClearIds = () => {
+    detectGamePaths()
+      .then((paths) => scanCharacterIds(paths.installations[0]?.path))
+      .then((scan) => {
+        onConfigChange({ ...config, initialCharID: scan.initialCharId });
+        const ours = scan.used.filter((range) => range.ours);
+        show(
+          "success",
+          ours.length === 0
+            ? "No character of the game or the enabled mods shares a prefix with ours."
+            : `Characters are numbered from ${scan.initialCharId}, past ` +
+                ours.map((range) => `${range.prefix}${range.last}`).join(", ") +
+                ".",
+        );
+      })
+      .catch((err) => show("error", String(err)));
+  };
+
+  // Writes for the version of the installation found, if its launcher says.
+  const handleDetectVersion = () => {
+    detectGamePaths()
+      .then((paths) => {
+        const version = paths.installations.find((installation) => installation.version)?.version;
+        if (version) {
+          onConfigChange({ ...config, gameVersion: version });
+          show("success", `Writing for the installed game, version ${version}.`);
+        } else {
+          show("error", "No installation of the game with a known version was found.");
+        }
+      })
+      .catch((err) => show("error", String(err)));
+  };
+
+  const handleMessage = (msg: SimulationMessage) => {
+    if (msg.log)   setLogs((prev) => [...prev, msg.log!]);
+    if (msg.error) setLogs((prev) => [...prev, `ERROR: ${msg.error!}`]);
+    // The shell reports it with a generation-cancelled event instead.
+    if (msg.status === "cancelled" && !isTauri()) {
+      setLogs((prev) => [...prev, cancelledLog(msg.removedFiles ?? 0)]);
+    }
+  };
+
+  const handleRunEnded = () => {
+    setSimRunning(false);
+    setPaused(false);
+    setProgress(null);
+  };
+
+  const handleRunFailed = (err: unknown) => {
+    setLogs((prev) => [...prev, `ERROR: ${err instanceof Error ? err.message : String(err)}`]);
+    handleRunEnded();
+  };
+
   const handleRunSimulation = () => {
     setLogs([]);
+    setProgress(null);
     setSimRunning(true);
+    streamSimulation(handleMessage, handleRunEnded, handleRunFailed);
+  };
 
-    streamSimulation(
-      (msg: SimulationMessage) => {
-        if (msg.log)   setLogs((prev) => [...prev, msg.log!]);
-        if (msg.error) setLogs((prev) => [...prev, `ERROR: ${msg.error!}`]);
-      },
-      () => setSimRunning(false),
-      (err) => {
-        setLogs((prev) => [...prev, `ERROR: ${err.message}`]);
-        setSimRunning(false);
-      }
-    );
+  // Simulates the dynasties on as many threads as there are workers.
+  const handleRunParallel = () => {
+    setLogs([]);
+    setProgress(null);
+    setSimRunning(true);
+    runParallelSimulation(handleMessage).then(handleRunEnded, handleRunFailed);
+  };
+
+  const handleWorkersChange = (raw: string) => {
+    const count = Number(raw);
+    setWorkers(count);
+    setWorkerCount(count).catch((err) => show("error", String(err)));
+  };
+
+  // Generate > Run starts a run unless one is going already.
+  useEffect(() => {
+    if (!runRequested) return;
+    onRunRequestTaken?.();
+    if (!simRunning) handleRunSimulation();
+  }, [runRequested]);
+
+  // Holds the run before its next year, or lets a held one go on.
+  const handlePauseSimulation = () => {
+    (paused ? resumeGeneration() : pauseGeneration())
+      .then(() => setPaused(!paused))
+      .catch((err) => show("error", String(err)));
+  };
+
+  // Stops the run before its next year; the run then ends with a
+  // "cancelled" message.
+  const handleCancelSimulation = () => {
+    setCancelling(true);
+    cancelGeneration()
+      .catch((err) => show("error", String(err)))
+      .finally(() => setCancelling(false));
   };
 
   // ── Render ────────────────────────────────────────────────────────────────
@@ -491,6 +702,35 @@ export default function DynastySettings({ config, onConfigChange }: Props) {
               onChange={(e) => onConfigChange({ ...config, generationMax: Number(e.target.value) })}
             />
           </div>
+          <div className="field">
+            <label>First Character ID</label>
+            <input
+              type="number"
+              step={1}
+              min={1}
+              value={config.initialCharID}
+              onChange={(e) => onConfigChange({ ...config, initialCharID: Number(e.target.value) })}
+            />
+            {isTauri() && (
+              <button className="btn btn-secondary btn-sm" style={{ marginTop: "0.35rem" }} onClick={handleClearIds}>
+                Clear of game and mods
+              </button>
+            )}
+          </div>
+          <div className="field">
+            <label>Game Version</label>
+            <input
+              type="text"
+              placeholder="latest"
+              value={config.gameVersion ?? ""}
+              onChange={(e) => onConfigChange({ ...config, gameVersion: e.target.value.trim() || undefined })}
+            />
+            {isTauri() && (
+              <button className="btn btn-secondary btn-sm" style={{ marginTop: "0.35rem" }} onClick={handleDetectVersion}>
+                Detect
+              </button>
+            )}
+          </div>
         </div>
       </div>
 
@@ -519,13 +759,14 @@ export default function DynastySettings({ config, onConfigChange }: Props) {
       </div>
 
       {feedback && (
-        <div className={`msg msg-${feedback.type}`}>{feedback.text}</div>
+        <div className={`msg msg-${feedback.type}`} style={{ whiteSpace: "pre-line" }}>{feedback.text}</div>
       )}
 
       {/* Add dynasty panel */}
       {addOpen && (
         <div className="panel" style={{ marginTop: "0.75rem" }}>
           <h3>New Dynasty</h3>
+          {isTauri() && <TemplatePicker value={newDynasty} onApply={setNewDynasty} />}
           <DynastyForm
             value={newDynasty}
             onChange={setNewDynasty}
@@ -559,20 +800,82 @@ export default function DynastySettings({ config, onConfigChange }: Props) {
         />
       ))}
 
+      {/* Template library, kept by the shell */}
+      {isTauri() && (
+        <>
+          <hr className="divider" />
+          <DynastyTemplates dynasties={dynasties} />
+          <hr className="divider" />
+          <HistoryImport existing={dynasties.map((d) => d.dynastyID)} onAdd={handleImportDynasty} />
+          <hr className="divider" />
+          <NameSuggestions cultures={[...new Set(dynasties.map((d) => d.cultureID).filter(Boolean))]} />
+        </>
+      )}
+
+      {/* Quick preview, simulated natively by the shell */}
+      {isTauri() && dynasties.length > 0 && (
+        <>
+          <hr className="divider" />
+          <DynastyPreview config={config} />
+          <hr className="divider" />
+          <NativeRuns />
+        </>
+      )}
+
       {/* Run simulation */}
       <hr className="divider" />
       <div className="panel">
         <h3>Run Simulation</h3>
         <p style={{ color: "var(--text-muted)", fontSize: "0.85rem", marginBottom: "0.75rem" }}>
-          Save all changes before running. Logs stream live below.
+          Save all changes before running. Progress and logs stream live below.
         </p>
-        <button
-          className="btn btn-primary"
-          onClick={handleRunSimulation}
-          disabled={simRunning}
-        >
-          {simRunning ? <><span className="spinner" /> Running...</> : "Run Simulation"}
-        </button>
+        <div className="btn-row">
+          <button
+            className="btn btn-primary"
+            onClick={handleRunSimulation}
+            disabled={simRunning}
+          >
+            {simRunning ? <><span className="spinner" /> Running...</> : "Run Simulation"}
+          </button>
+          {isTauri() && (
+            <button className="btn btn-secondary" onClick={handleRunParallel} disabled={simRunning}>
+              Run in Parallel
+            </button>
+          )}
+          {simRunning && isTauri() && (
+            <>
+              <button className="btn btn-secondary" onClick={handlePauseSimulation} disabled={cancelling}>
+                {paused ? "Resume" : "Pause"}
+              </button>
+              <button
+                className="btn btn-secondary"
+                onClick={handleCancelSimulation}
+                disabled={cancelling}
+              >
+                {cancelling ? "Cancelling..." : "Cancel"}
+              </button>
+            </>
+          )}
+        </div>
+
+        {isTauri() && (
+          <div className="field-row" style={{ marginTop: "0.75rem" }}>
+            <div className="field">
+              <label>Parallel Workers</label>
+              <input
+                type="number"
+                min={1}
+                max={16}
+                step={1}
+                value={workers}
+                disabled={simRunning}
+                onChange={(e) => handleWorkersChange(e.target.value)}
+              />
+            </div>
+          </div>
+        )}
+
+        {progress && <ProgressBar progress={progress} />}
 
         {logs.length > 0 && (
           <div className="sim-log">
@@ -0,0 +1,167 @@
+import { useEffect, useState } from "react";
+import {
+  ConfigError,
+  Dynasty,
+  DynastyTemplate,
+  applyDynastyTemplate,
+  deleteDynastyTemplate,
+  listDynastyTemplates,
+  saveDynastyTemplate,
+} from "../api";
+
+const cell = { padding: "0.3rem 0.5rem", borderBottom: "1px solid var(--border)", textAlign: "left" as const };
+
+// Saving and deleting reject with a list of problems rather than one error.
+function describe(err: unknown): string {
+  if (Array.isArray(err)) {
+    return (err as ConfigError[])
+      .map((problem) => (problem.path ? `${problem.path}: ${problem.message}` : problem.message))
+      .join("\n");
+  }
+  return String(err);
+}
+
+// Fills in the new dynasty form from a template.
+export function TemplatePicker({ value, onApply }: { value: Dynasty; onApply: (dynasty: Dynasty) => void }) {
+  const [templates, setTemplates] = useState<DynastyTemplate[]>([]);
+  const [name, setName] = useState("");
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    listDynastyTemplates()
+      .then((templates) => {
+        setTemplates(templates);
+        setName(templates[0]?.name ?? "");
+      })
+      .catch((err) => setError(String(err)));
+  }, []);
+
+  if (templates.length === 0) return null;
+  const selected = templates.find((template) => template.name === name);
+
+  return (
+    <div className="field-row">
+      <div className="field">
+        <label>Start from template</label>
+        <select value={name} onChange={(e) => setName(e.target.value)}>
+          {templates.map((template) => (
+            <option key={template.name} value={template.name}>
+              {template.name}
+            </option>
+          ))}
+        </select>
+        {selected?.description && (
+          <span style={{ color: "var(--text-muted)", fontSize: "0.8rem" }}>{selected.description}</span>
+        )}
+      </div>
+      <div className="field" style={{ justifyContent: "flex-end" }}>
+        <button
+          className="btn btn-secondary btn-sm"
+          onClick={() =>
+            applyDynastyTemplate(name, value)
+              .then(onApply)
+              .catch((err) => setError(String(err)))
+          }
+        >
+          Apply
+        </button>
+      </div>
+      {error && <div className="msg msg-error">{error}</div>}
+    </div>
+  );
+}
+
+// The template library: every template, and saving a dynasty of the config
+// as a new one.
+export default function DynastyTemplates({ dynasties }: { dynasties: Dynasty[] }) {
+  const [templates, setTemplates] = useState<DynastyTemplate[]>([]);
+  const [source, setSource] = useState(0);
+  const [name, setName] = useState("");
+  const [description, setDescription] = useState("");
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    listDynastyTemplates()
+      .then(setTemplates)
+      .catch((err) => setError(String(err)));
+  }, []);
+
+  const run = (action: Promise<DynastyTemplate[]>) => {
+    action
+      .then((templates) => {
+        setTemplates(templates);
+        setError(null);
+      })
+      .catch((err) => setError(describe(err)));
+  };
+
+  const save = () => {
+    const dynasty = dynasties[source];
+    if (!dynasty) return;
+    run(saveDynastyTemplate(name, description, dynasty));
+  };
+
+  return (
+    <div className="panel">
+      <h3>Dynasty Templates</h3>
+      <p style={{ color: "var(--text-muted)", fontSize: "0.85rem", marginBottom: "0.75rem" }}>
+        Templates set a dynasty's succession, marriage customs and name inheritance, but not its names, faith or
+        culture. Pick one when adding a dynasty, or save one of yours for other projects.
+      </p>
+      <table style={{ width: "100%", borderCollapse: "collapse", fontSize: "0.85rem", marginBottom: "0.75rem" }}>
+        <tbody>
+          {templates.map((template) => (
+            <tr key={template.name}>
+              <td style={cell}>{template.name}</td>
+              <td style={{ ...cell, color: "var(--text-muted)" }}>{template.description}</td>
+              <td style={{ ...cell, whiteSpace: "nowrap" }}>
+                {template.bundled ? (
+                  <span style={{ color: "var(--text-muted)" }}>bundled</span>
+                ) : (
+                  <button className="btn btn-danger btn-sm" onClick={() => run(deleteDynastyTemplate(template.name))}>
+                    Delete
+                  </button>
+                )}
+              </td>
+            </tr>
+          ))}
+        </tbody>
+      </table>
+
+      {dynasties.length > 0 && (
+        <>
+          <div className="field-row">
+            <div className="field">
+              <label>Save from</label>
+              <select value={source} onChange={(e) => setSource(Number(e.target.value))}>
+                {dynasties.map((dynasty, i) => (
+                  <option key={dynasty.dynastyID + i} value={i}>
+                    {dynasty.dynastyName || dynasty.dynastyID}
+                  </option>
+                ))}
+              </select>
+            </div>
+            <div className="field">
+              <label>Name</label>
+              <input type="text" value={name} onChange={(e) => setName(e.target.value)} />
+            </div>
+            <div className="field">
+              <label>Description</label>
+              <input type="text" value={description} onChange={(e) => setDescription(e.target.value)} />
+            </div>
+          </div>
+          <div className="btn-row">
+            <button className="btn btn-secondary btn-sm" disabled={!name.trim()} onClick={save}>
+              Save as Template
+            </button>
+          </div>
+        </>
+      )}
+      {error && (
+        <div className="msg msg-error" style={{ whiteSpace: "pre-line" }}>
+          {error}
+        </div>
+      )}
+    </div>
+  );
+}
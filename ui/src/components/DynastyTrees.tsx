@@ -1,11 +1,45 @@
 import { useEffect, useState } from "react";
-import { fetchImageList, imageUrl } from "../api";
+import { isTauri } from "@tauri-apps/api/core";
+import {
+  exportChronicle,
+  exportCsv,
+  exportFamilyTrees,
+  exportGedcom,
+  exportReport,
+  fetchImageList,
+  getProjectSetting,
+  imageUrl,
+  listTreeWindows,
+  onTreeWindowsChanged,
+  openOutputFolder,
+  openTreeWindow,
+  pickChronicleFile,
+  pickCsvExportDir,
+  pickGedcomFile,
+  pickReportFile,
+  pickTreeExportDir,
+  revealOutputFile,
+  setProjectSetting,
+  TreeExportOptions,
+  TreeView,
+} from "../api";
+
+// Project setting remembering how the project last exported its graphs.
+const GRAPH_OPTIONS_SETTING = "treeExportOptions";
 
 export default function DynastyTrees() {
   const [images, setImages] = useState<string[]>([]);
   const [expanded, setExpanded] = useState<Set<string>>(new Set());
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
+  const [exported, setExported] = useState<string | null>(null);
+  const [graphOptions, setGraphOptions] = useState<TreeExportOptions>({
+    highlightRulers: true,
+    markCadetHouses: true,
+  });
+  const [svg, setSvg] = useState(false);
+  // Trees detached into windows of their own.
+  const [windows, setWindows] = useState<TreeView[]>([]);
 
   const load = () => {
     setLoading(true);
@@ -18,6 +52,88 @@ export default function DynastyTrees() {
 
   useEffect(load, []);
 
+  useEffect(() => {
+    if (!isTauri()) return;
+    listTreeWindows().then(setWindows).catch(() => {});
+    const unlisten = onTreeWindowsChanged(setWindows);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  useEffect(() => {
+    if (!isTauri()) return;
+    getProjectSetting<TreeExportOptions>(GRAPH_OPTIONS_SETTING)
+      .then((options) => options && setGraphOptions(options))
+      .catch(() => {});
+  }, []);
+
+  const exportTree = () => {
+    setError(null);
+    setExported(null);
+    pickGedcomFile()
+      .then(async (path) => {
+        if (path) {
+          await exportGedcom(path);
+          setExported(`Family tree exported to ${path}`);
+        }
+      })
+      .catch((err) => setError(String(err)));
+  };
+
+  const exportPage = () => {
+    setError(null);
+    setExported(null);
+    pickReportFile()
+      .then(async (path) => {
+        if (path) {
+          await exportReport(path);
+          setExported(`Report exported to ${path}`);
+        }
+      })
+      .catch((err) => setError(String(err)));
+  };
+
+  const exportStory = () => {
+    setError(null);
+    setExported(null);
+    pickChronicleFile()
+      .then(async (path) => {
+        if (path) {
+          await exportChronicle(path);
+          setExported(`Chronicle exported to ${path}`);
+        }
+      })
+      .catch((err) => setError(String(err)));
+  };
+
+  const exportTables = () => {
+    setError(null);
+    setExported(null);
+    pickCsvExportDir()
+      .then(async (dir) => {
+        if (dir) {
+          await exportCsv(dir);
+          setExported(`Characters and events exported to ${dir}`);
+        }
+      })
+      .catch((err) => setError(String(err)));
+  };
+
+  const exportGraphs = () => {
+    setError(null);
+    setExported(null);
+    pickTreeExportDir()
+      .then(async (dir) => {
+        if (dir) {
+          const files = await exportFamilyTrees(dir, graphOptions, svg);
+          await setProjectSetting(GRAPH_OPTIONS_SETTING, graphOptions);
+          setExported(`${files.length} files exported to ${dir}`);
+        }
+      })
+      .catch((err) => setError(String(err)));
+  };
+
   const toggle = (name: string) => {
     setExpanded((prev) => {
       const next = new Set(prev);
@@ -33,11 +149,80 @@ export default function DynastyTrees() {
     <div>
       <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center", marginBottom: "1rem" }}>
         <h2 style={{ margin: 0 }}>Generated Dynasty Trees</h2>
-        <button className="btn btn-secondary btn-sm" onClick={load}>
-          Refresh
-        </button>
+        <span style={{ display: "flex", gap: "0.5rem" }}>
+          {isTauri() && (
+            <>
+              <button className="btn btn-secondary btn-sm" onClick={exportTables}>
+                Export CSV
+              </button>
+              <button className="btn btn-secondary btn-sm" onClick={exportTree}>
+                Export GEDCOM
+              </button>
+              <button className="btn btn-secondary btn-sm" onClick={exportPage}>
+                Export Report
+              </button>
+              <button className="btn btn-secondary btn-sm" onClick={exportStory}>
+                Export Chronicle
+              </button>
+              <button
+                className="btn btn-secondary btn-sm"
+                onClick={() => openOutputFolder().catch((err) => setError(String(err)))}
+              >
+                Open Output Folder
+              </button>
+            </>
+          )}
+          <button className="btn btn-secondary btn-sm" onClick={load}>
+            Refresh
+          </button>
+        </span>
       </div>
 
+      {isTauri() && (
+        <div className="field-row" style={{ alignItems: "flex-end", marginBottom: "1rem" }}>
+          <div className="field">
+            <label>Generations (blank = all)</label>
+            <input
+              type="number"
+              min={1}
+              step={1}
+              value={graphOptions.maxGenerations ?? ""}
+              onChange={(e) =>
+                setGraphOptions({
+                  ...graphOptions,
+                  maxGenerations: e.target.value ? Number(e.target.value) : undefined,
+                })
+              }
+            />
+          </div>
+          <label className="checkbox-field">
+            <input
+              type="checkbox"
+              checked={graphOptions.highlightRulers}
+              onChange={(e) => setGraphOptions({ ...graphOptions, highlightRulers: e.target.checked })}
+            />
+            Highlight Rulers
+          </label>
+          <label className="checkbox-field">
+            <input
+              type="checkbox"
+              checked={graphOptions.markCadetHouses}
+              onChange={(e) => setGraphOptions({ ...graphOptions, markCadetHouses: e.target.checked })}
+            />
+            Mark Cadet Houses
+          </label>
+          <label className="checkbox-field">
+            <input type="checkbox" checked={svg} onChange={(e) => setSvg(e.target.checked)} />
+            Render SVG (needs Graphviz)
+          </label>
+          <button className="btn btn-secondary btn-sm" onClick={exportGraphs}>
+            Export Graphviz
+          </button>
+        </div>
+      )}
+
+      {exported && <div className="msg msg-success">{exported}</div>}
+
       {loading && (
         <div style={{ display: "flex", alignItems: "center", gap: "0.75rem", color: "var(--text-muted)" }}>
           <span className="spinner" /> Loading images...
@@ -64,8 +249,34 @@ export default function DynastyTrees() {
                 <span style={{ color: "var(--text-label)" }}>
                   {dynastyLabel(filename)}
                 </span>
-                <span style={{ color: "var(--text-muted)", fontSize: "0.8rem" }}>
-                  {isOpen ? "collapse" : "expand"}
+                <span style={{ display: "flex", alignItems: "center", gap: "0.75rem" }}>
+                  {isTauri() && (
+                    <>
+                      <button
+                        className="btn btn-secondary btn-sm"
+                        onClick={(e) => {
+                          e.stopPropagation();
+                          revealOutputFile(filename).catch((err) => setError(String(err)));
+                        }}
+                      >
+                        Reveal file
+                      </button>
+                      <button
+                        className="btn btn-secondary btn-sm"
+                        onClick={(e) => {
+                          e.stopPropagation();
+                          openTreeWindow(dynastyLabel(filename)).catch((err) => setError(String(err)));
+                        }}
+                      >
+                        {windows.some((view) => view.dynasty === dynastyLabel(filename))
+                          ? "Show window"
+                          : "Open in window"}
+                      </button>
+                    </>
+                  )}
+                  <span style={{ color: "var(--text-muted)", fontSize: "0.8rem" }}>
+                    {isOpen ? "collapse" : "expand"}
+                  </span>
                 </span>
               </div>
               {isOpen && (
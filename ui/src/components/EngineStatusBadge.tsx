@@ -0,0 +1,27 @@
+import { useEffect, useState } from "react";
+import { EngineStatus, getEngineStatus, onEngineStatus } from "../api";
+
+const LABELS: Record<EngineStatus, { text: string; color: string }> = {
+  starting: { text: "Engine starting", color: "var(--text-muted)" },
+  ready: { text: "Engine ready", color: "var(--success-text)" },
+  busy: { text: "Engine simulating", color: "var(--accent)" },
+  dead: { text: "Engine not responding", color: "var(--error-text)" },
+};
+
+// The engine's heartbeat, to tell a long, silent simulation from a hung or
+// dead engine.
+export default function EngineStatusBadge() {
+  const [status, setStatus] = useState<EngineStatus | null>(null);
+
+  useEffect(() => {
+    getEngineStatus().then(setStatus).catch(() => {});
+    const unlisten = onEngineStatus(setStatus);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  if (!status) return null;
+  const { text, color } = LABELS[status];
+  return <span style={{ fontSize: "0.85rem", color, marginLeft: "auto" }}>● {text}</span>;
+}
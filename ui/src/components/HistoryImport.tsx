@@ -0,0 +1,101 @@
+import { useEffect, useState } from "react";
+import {
+  HistoryImport as Import,
+  ImportedDynasty,
+  importHistoryFiles,
+  onHistoryImport,
+  pickHistoryFile,
+} from "../api";
+
+const cell = { padding: "0.3rem 0.5rem", borderBottom: "1px solid var(--border)", textAlign: "left" as const };
+
+interface Props {
+  // IDs of the dynasties and houses already in the config.
+  existing: string[];
+  onAdd: (dynasty: ImportedDynasty) => void;
+}
+
+// History files dropped onto the window or picked here, with what each
+// defines, and adding the dynasties and houses that are not in the config yet.
+export default function HistoryImport({ existing, onAdd }: Props) {
+  const [imported, setImported] = useState<Import | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    const unlisten = onHistoryImport((data) => {
+      setImported(data);
+      setError(null);
+    });
+    return () => { unlisten.then((fn) => fn()); };
+  }, []);
+
+  const handlePick = async () => {
+    try {
+      const path = await pickHistoryFile();
+      if (!path) return;
+      setImported(await importHistoryFiles([path]));
+      setError(null);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  return (
+    <div className="panel">
+      <h3>Import History</h3>
+      <p style={{ color: "var(--text-muted)", fontSize: "0.85rem", marginBottom: "0.75rem" }}>
+        Drop history/characters or common/dynasties files onto the window, or pick one, to read the characters and
+        dynasties they define.
+      </p>
+      <div className="btn-row">
+        <button className="btn btn-secondary btn-sm" onClick={handlePick}>
+          Import History File…
+        </button>
+      </div>
+
+      {imported?.files.map((file) => (
+        <div key={file.path} style={{ marginTop: "0.75rem" }}>
+          <strong style={{ fontSize: "0.85rem" }}>{file.path}</strong>
+          <p style={{ color: "var(--text-muted)", fontSize: "0.85rem" }}>
+            {file.characters.length} characters, {file.dynasties.length} dynasties and houses
+          </p>
+          {file.dynasties.length > 0 && (
+            <table style={{ width: "100%", borderCollapse: "collapse", fontSize: "0.85rem" }}>
+              <tbody>
+                {file.dynasties.map((dynasty) => (
+                  <tr key={dynasty.id}>
+                    <td style={cell}>{dynasty.id}</td>
+                    <td style={cell}>{dynasty.name ?? ""}</td>
+                    <td style={{ ...cell, color: "var(--text-muted)" }}>
+                      {dynasty.dynasty ? `house of ${dynasty.dynasty}` : "dynasty"}
+                    </td>
+                    <td style={{ ...cell, whiteSpace: "nowrap" }}>
+                      {existing.includes(dynasty.id) ? (
+                        <span style={{ color: "var(--text-muted)" }}>in config</span>
+                      ) : (
+                        <button className="btn btn-secondary btn-sm" onClick={() => onAdd(dynasty)}>
+                          Add
+                        </button>
+                      )}
+                    </td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          )}
+          {file.errors.length > 0 && (
+            <div className="msg msg-error" style={{ whiteSpace: "pre-line" }}>
+              {file.errors.join("\n")}
+            </div>
+          )}
+        </div>
+      ))}
+      {imported && imported.skipped.length > 0 && (
+        <div className="msg msg-error" style={{ whiteSpace: "pre-line" }}>
+          {`Skipped, not .txt files:\n${imported.skipped.join("\n")}`}
+        </div>
+      )}
+      {error && <div className="msg msg-error">{error}</div>}
+    </div>
+  );
+}
@@ -0,0 +1,166 @@
+import { useEffect, useState } from "react";
+import {
+  findIdCollisions,
+  getProjectSetting,
+  IdCollision,
+  IdRange,
+  pickIdCheckDir,
+  renumberIds,
+  setProjectSetting,
+} from "../api";
+
+// Project settings remembering the folders checked against and the range
+// renumbered IDs are taken from.
+export const CHECK_PATHS_SETTING = "idCheckPaths";
+const RANGE_SETTING = "idRange";
+
+const DEFAULT_RANGE: IdRange = { first: 900000000, last: 999999999 };
+
+export default function IdCollisions({
+  onRenumbered,
+  onFoldersChanged,
+}: {
+  onRenumbered: () => void;
+  onFoldersChanged: () => void;
+}) {
+  const [others, setOthers] = useState<string[]>([]);
+  const [range, setRange] = useState<IdRange>(DEFAULT_RANGE);
+  const [collisions, setCollisions] = useState<IdCollision[] | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [renumbered, setRenumbered] = useState<string | null>(null);
+
+  const check = (paths: string[] = others) => {
+    setBusy(true);
+    setError(null);
+    findIdCollisions(paths)
+      .then(setCollisions)
+      .catch((err) => {
+        setCollisions(null);
+        setError(String(err));
+      })
+      .finally(() => setBusy(false));
+  };
+
+  useEffect(() => {
+    Promise.all([
+      getProjectSetting<string[]>(CHECK_PATHS_SETTING),
+      getProjectSetting<IdRange>(RANGE_SETTING),
+    ])
+      .then(([paths, range]) => {
+        if (range) setRange(range);
+        setOthers(paths ?? []);
+        check(paths ?? []);
+      })
+      .catch(() => check([]));
+  }, []);
+
+  const changeOthers = (paths: string[]) => {
+    setOthers(paths);
+    setProjectSetting(CHECK_PATHS_SETTING, paths)
+      .catch(() => {})
+      .then(onFoldersChanged);
+    check(paths);
+  };
+
+  const addFolder = () => {
+    pickIdCheckDir()
+      .then((dir) => {
+        if (dir && !others.includes(dir)) changeOthers([...others, dir]);
+      })
+      .catch((err) => setError(String(err)));
+  };
+
+  const renumber = () => {
+    setBusy(true);
+    setError(null);
+    setRenumbered(null);
+    setProjectSetting(RANGE_SETTING, range)
+      .catch(() => {})
+      .then(() => renumberIds(others, range))
+      .then((changed) => {
+        setRenumbered(`${changed.length} IDs renumbered.`);
+        onRenumbered();
+        return findIdCollisions(others).then(setCollisions);
+      })
+      .catch((err) => setError(String(err)))
+      .finally(() => setBusy(false));
+  };
+
+  return (
+    <div style={{ marginTop: "1.5rem" }}>
+      <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center" }}>
+        <h3 style={{ margin: 0 }}>ID Collisions</h3>
+        <button className="btn btn-secondary btn-sm" onClick={addFolder}>
+          Check against folder
+        </button>
+      </div>
+
+      {others.length > 0 && (
+        <ul style={{ color: "var(--text-muted)", fontSize: "0.85rem" }}>
+          {others.map((path) => (
+            <li key={path}>
+              {path}{" "}
+              <button
+                className="btn btn-secondary btn-sm"
+                onClick={() => changeOthers(others.filter((other) => other !== path))}
+              >
+                Remove
+              </button>
+            </li>
+          ))}
+        </ul>
+      )}
+
+      {error && <div className="msg msg-error">{error}</div>}
+      {renumbered && <div className="msg msg-success">{renumbered}</div>}
+
+      {!busy && collisions?.length === 0 && (
+        <div className="msg msg-success">
+          No character, dynasty or house ID is defined twice
+          {others.length > 0 ? " or by the folders checked" : ""}.
+        </div>
+      )}
+
+      {collisions && collisions.length > 0 && (
+        <>
+          <ul style={{ listStyle: "none", padding: 0, margin: 0 }}>
+            {collisions.map((collision, i) => (
+              <li key={i} style={{ padding: "0.3rem 0", borderBottom: "1px solid var(--border)" }}>
+                <span style={{ color: "var(--text-label)", fontFamily: "monospace" }}>
+                  {collision.file}:{collision.line}
+                </span>{" "}
+                {collision.kind} '{collision.id}' is also defined in {collision.otherFile}:{collision.otherLine}
+              </li>
+            ))}
+          </ul>
+          <div className="field-row" style={{ alignItems: "flex-end", marginTop: "0.75rem" }}>
+            <div className="field">
+              <label>First new ID</label>
+              <input
+                type="number"
+                min={0}
+                step={1}
+                value={range.first}
+                onChange={(e) => setRange({ ...range, first: Number(e.target.value) })}
+              />
+            </div>
+            <div className="field">
+              <label>Last new ID</label>
+              <input
+                type="number"
+                min={0}
+                step={1}
+                value={range.last}
+                onChange={(e) => setRange({ ...range, last: Number(e.target.value) })}
+              />
+            </div>
+            <button className="btn btn-secondary btn-sm" disabled={busy} onClick={renumber}>
+              Renumber
+            </button>
+          </div>
+        </>
+      )}
+    </div>
+  );
+}
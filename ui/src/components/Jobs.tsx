@@ -0,0 +1,182 @@
+import { useEffect, useState } from "react";
+import {
+  Job,
+  cancelJob,
+  clearFinishedJobs,
+  enqueueJob,
+  getJobConcurrency,
+  listJobs,
+  moveJob,
+  onJobsChanged,
+  setJobConcurrency,
+} from "../api";
+
+const cell = { padding: "0.3rem 0.5rem", borderBottom: "1px solid var(--border)", textAlign: "left" as const };
+
+function formatStatus(job: Job): string {
+  if (job.status === "running") {
+    return `running ${Math.round(job.percent ?? 0)}%`;
+  }
+  if (job.elapsedSeconds !== null && job.status !== "queued") {
+    return `${job.status} after ${Math.round(job.elapsedSeconds)}s`;
+  }
+  return job.status;
+}
+
+export default function Jobs() {
+  const [jobs, setJobs] = useState<Job[]>([]);
+  const [concurrency, setConcurrency] = useState(1);
+  const [name, setName] = useState("");
+  const [configDir, setConfigDir] = useState("");
+  // One job per seed; blank for a single job with a random seed.
+  const [seeds, setSeeds] = useState("");
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    listJobs().then(setJobs).catch((err) => setError(String(err)));
+    getJobConcurrency().then(setConcurrency).catch(() => {});
+    const unlisten = onJobsChanged(setJobs);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const run = (action: Promise<unknown>) => {
+    action.then(() => setError(null)).catch((err) => setError(String(err)));
+  };
+
+  const enqueue = async () => {
+    const parsed = seeds
+      .split(/[\s,]+/)
+      .filter((seed) => seed !== "")
+      .map(Number);
+    if (parsed.some((seed) => !Number.isInteger(seed) || seed < 0)) {
+      throw new Error("Seeds must be whole numbers.");
+    }
+    for (const seed of parsed.length > 0 ? parsed : [undefined]) {
+      await enqueueJob({
+        name: name.trim() || undefined,
+        configDir: configDir.trim() || undefined,
+        seed,
+      });
+    }
+  };
+
+  const waiting = jobs.filter((job) => job.status === "queued");
+
+  return (
+    <div>
+      <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center", marginBottom: "1rem" }}>
+        <h2 style={{ margin: 0 }}>Jobs</h2>
+        <button
+          className="btn btn-secondary btn-sm"
+          disabled={!jobs.some((job) => job.status !== "queued" && job.status !== "running")}
+          onClick={() => run(clearFinishedJobs())}
+        >
+          Clear finished
+        </button>
+      </div>
+
+      <div className="field-row">
+        <div className="field">
+          <label>Name</label>
+          <input type="text" placeholder="the config folder's" value={name} onChange={(e) => setName(e.target.value)} />
+        </div>
+        <div className="field">
+          <label>Config folder</label>
+          <input
+            type="text"
+            placeholder="the project's"
+            value={configDir}
+            onChange={(e) => setConfigDir(e.target.value)}
+          />
+        </div>
+        <div className="field">
+          <label>Seeds</label>
+          <input type="text" placeholder="random" value={seeds} onChange={(e) => setSeeds(e.target.value)} />
+        </div>
+        <div className="field">
+          <label>Run side by side</label>
+          <input
+            type="number"
+            min={1}
+            max={8}
+            value={concurrency}
+            onChange={(e) => {
+              const count = Number(e.target.value);
+              setConcurrency(count);
+              run(setJobConcurrency(count));
+            }}
+          />
+        </div>
+      </div>
+
+      <div className="btn-row">
+        <button className="btn btn-primary" onClick={() => run(enqueue())}>
+          Queue
+        </button>
+      </div>
+
+      {error && <div className="msg msg-error">{error}</div>}
+
+      {jobs.length === 0 ? (
+        <p>No jobs queued.</p>
+      ) : (
+        <table style={{ width: "100%", borderCollapse: "collapse", fontSize: "0.85rem", marginTop: "1rem" }}>
+          <thead>
+            <tr>
+              {["#", "Name", "Seed", "Status", "Output", ""].map((heading, i) => (
+                <th key={i} style={{ ...cell, color: "var(--text-label)" }}>
+                  {heading}
+                </th>
+              ))}
+            </tr>
+          </thead>
+          <tbody>
+            {jobs.map((job) => {
+              const position = waiting.indexOf(job);
+              return (
+                <tr key={job.id}>
+                  <td style={cell}>{job.id}</td>
+                  <td style={cell} title={job.configDir}>
+                    {job.name}
+                  </td>
+                  <td style={cell}>{job.seed ?? "random"}</td>
+                  <td style={{ ...cell, color: job.status === "failed" ? "var(--error-text)" : undefined }} title={job.error ?? undefined}>
+                    {formatStatus(job)}
+                  </td>
+                  <td style={cell}>{job.outputDir}</td>
+                  <td style={{ ...cell, whiteSpace: "nowrap" }}>
+                    {position >= 0 && (
+                      <>
+                        <button
+                          className="btn btn-secondary btn-sm"
+                          disabled={position === 0}
+                          onClick={() => run(moveJob(job.id, position - 1))}
+                        >
+                          Up
+                        </button>{" "}
+                        <button
+                          className="btn btn-secondary btn-sm"
+                          disabled={position === waiting.length - 1}
+                          onClick={() => run(moveJob(job.id, position + 1))}
+                        >
+                          Down
+                        </button>{" "}
+                      </>
+                    )}
+                    {(job.status === "queued" || job.status === "running") && (
+                      <button className="btn btn-danger btn-sm" onClick={() => run(cancelJob(job.id))}>
+                        Cancel
+                      </button>
+                    )}
+                  </td>
+                </tr>
+              );
+            })}
+          </tbody>
+        </table>
+      )}
+    </div>
+  );
+}
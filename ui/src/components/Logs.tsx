@@ -0,0 +1,132 @@
+import { useEffect, useState } from "react";
+import { LogLevel, LogRecord, openLogFolder, queryLogs } from "../api";
+
+const LEVELS: LogLevel[] = ["ERROR", "WARN", "INFO", "DEBUG", "TRACE"];
+
+// How far back to look, in minutes; 0 for the whole session.
+const PERIODS: { minutes: number; label: string }[] = [
+  { minutes: 0, label: "This session" },
+  { minutes: 60, label: "Last hour" },
+  { minutes: 15, label: "Last 15 minutes" },
+  { minutes: 5, label: "Last 5 minutes" },
+];
+
+const LEVEL_COLORS: Partial<Record<LogLevel, string>> = {
+  ERROR: "var(--error-text)",
+  WARN: "var(--warning-text)",
+};
+
+function formatRecord(record: LogRecord): string {
+  const time = new Date(record.time).toISOString();
+  const fields = record.fields.map(([name, value]) => ` ${name}=${value}`).join("");
+  return `${time} ${record.level.padEnd(5)} ${record.target}: ${record.message}${fields}`;
+}
+
+export default function Logs() {
+  const [level, setLevel] = useState<LogLevel>("INFO");
+  const [minutes, setMinutes] = useState(0);
+  const [filter, setFilter] = useState("");
+  const [records, setRecords] = useState<LogRecord[]>([]);
+  const [error, setError] = useState<string | null>(null);
+  const [copied, setCopied] = useState(false);
+
+  const refresh = () => {
+    const since = minutes > 0 ? Date.now() - minutes * 60_000 : undefined;
+    queryLogs({ level, since, filter })
+      .then((records) => {
+        setRecords(records);
+        setError(null);
+      })
+      .catch((err) => setError(String(err)));
+  };
+
+  useEffect(refresh, [level, minutes, filter]);
+
+  // Exactly what is shown, ready to paste into a support request.
+  const copy = () => {
+    navigator.clipboard
+      .writeText(records.map(formatRecord).join("\n"))
+      .then(() => setCopied(true))
+      .catch((err) => setError(String(err)));
+  };
+
+  return (
+    <div>
+      <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center", marginBottom: "1rem" }}>
+        <h2 style={{ margin: 0 }}>Logs</h2>
+        <span style={{ display: "flex", gap: "0.5rem" }}>
+          <button className="btn btn-secondary btn-sm" onClick={refresh}>
+            Refresh
+          </button>
+          <button className="btn btn-secondary btn-sm" onClick={copy} disabled={records.length === 0}>
+            {copied ? "Copied" : "Copy"}
+          </button>
+          <button
+            className="btn btn-secondary btn-sm"
+            onClick={() => openLogFolder().catch((err) => setError(String(err)))}
+          >
+            Open log folder
+          </button>
+        </span>
+      </div>
+
+      <div className="field-row">
+        <div className="field">
+          <label>Level</label>
+          <select
+            value={level}
+            onChange={(e) => {
+              setLevel(e.target.value as LogLevel);
+              setCopied(false);
+            }}
+          >
+            {LEVELS.map((level) => (
+              <option key={level} value={level}>
+                {level} and above
+              </option>
+            ))}
+          </select>
+        </div>
+        <div className="field">
+          <label>Since</label>
+          <select
+            value={minutes}
+            onChange={(e) => {
+              setMinutes(Number(e.target.value));
+              setCopied(false);
+            }}
+          >
+            {PERIODS.map((period) => (
+              <option key={period.minutes} value={period.minutes}>
+                {period.label}
+              </option>
+            ))}
+          </select>
+        </div>
+        <div className="field">
+          <label>Filter</label>
+          <input
+            type="text"
+            placeholder="sidecar, engine, failed..."
+            value={filter}
+            onChange={(e) => {
+              setFilter(e.target.value);
+              setCopied(false);
+            }}
+          />
+        </div>
+      </div>
+
+      {error && <div className="msg msg-error">{error}</div>}
+
+      <div className="sim-log" style={{ height: "60vh" }}>
+        {records.length === 0 && <p>No log records match.</p>}
+        {records.map((record, i) => (
+          <p key={i} style={{ color: LEVEL_COLORS[record.level] }}>
+            {formatRecord(record)}
+          </p>
+        ))}
+      </div>
+    </div>
+  );
+}
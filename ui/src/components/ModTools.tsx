@@ -0,0 +1,154 @@
+import { useState } from "react";
+import { deployMod, packageForWorkshop, pickModBuildDir, pickModDir, playWithMod, scaffoldMod } from "../api";
+
+// Turning the last export into a mod. Name, version and tags come from the
+// config's mod settings.
+export default function ModTools() {
+  const [busy, setBusy] = useState(false);
+  const [backup, setBackup] = useState(true);
+  const [exclusive, setExclusive] = useState(false);
+  // steamcmd must have this account's credentials cached to upload.
+  const [steamUser, setSteamUser] = useState("");
+  // The Paradox mod folder; null for the one found on this machine.
+  const [modDir, setModDir] = useState<string | null>(null);
+  const [message, setMessage] = useState<{ ok: boolean; text: string } | null>(null);
+
+  const run = (action: () => Promise<string | null>) => {
+    setBusy(true);
+    setMessage(null);
+    action()
+      .then((text) => text && setMessage({ ok: true, text }))
+      .catch((err) => setMessage({ ok: false, text: String(err) }))
+      .finally(() => setBusy(false));
+  };
+
+  const scaffold = () =>
+    run(async () => {
+      const dir = await pickModBuildDir();
+      if (!dir) return null;
+      const written = await scaffoldMod(dir);
+      return `Built the mod in ${dir}:\n${written.join("\n")}`;
+    });
+
+  const deploy = () =>
+    run(async () => {
+      const deployment = await deployMod(backup, modDir ?? undefined);
+      return [
+        `Deployed ${deployment.written.length} files to ${deployment.dir}.`,
+        deployment.backup ? `The last deploy was moved to ${deployment.backup}.` : null,
+      ]
+        .filter(Boolean)
+        .join("\n");
+    });
+
+  // Enables the deployed mod and starts the game found on this machine,
+  // without the launcher.
+  const play = () =>
+    run(async () => {
+      const enabled = await playWithMod(exclusive, undefined, modDir ?? undefined);
+      return `The game is starting with ${enabled.length === 1 ? "this mod" : "these mods"}:\n${enabled.join("\n")}`;
+    });
+
+  const packageMod = () =>
+    run(async () => {
+      const dir = await pickModBuildDir();
+      if (!dir) return null;
+      const built = await packageForWorkshop(dir, steamUser.trim() || undefined);
+      const lines = [`Packaged ${built.written.length} files in ${built.dir}, described for steamcmd in ${built.vdf}.`];
+      if (built.warnings.length > 0) {
+        lines.push("Fix these before uploading:", ...built.warnings);
+      } else if (built.uploaded) {
+        lines.push(`Uploaded as Workshop item ${built.publishedFileId}, hidden until you publish it.`);
+      }
+      return lines.join("\n");
+    });
+
+  return (
+    <div>
+      <h2>Mod</h2>
+      <div className="panel">
+        <h3>Mod Skeleton</h3>
+        <p style={{ color: "var(--text-muted)", fontSize: "0.85rem", marginBottom: "0.75rem" }}>
+          Lay out a mod around the last export, with its descriptor, metadata and a placeholder thumbnail. Building
+          it again keeps a thumbnail you replaced.
+        </p>
+        <div className="btn-row">
+          <button className="btn btn-secondary btn-sm" disabled={busy} onClick={scaffold}>
+            Build Mod…
+          </button>
+        </div>
+      </div>
+
+      <hr className="divider" />
+      <div className="panel">
+        <h3>Deploy</h3>
+        <p style={{ color: "var(--text-muted)", fontSize: "0.85rem", marginBottom: "0.75rem" }}>
+          Build the mod straight into the Paradox mod folder, with its launcher entry beside it, replacing the last
+          deploy.
+        </p>
+        <div className="btn-row" style={{ alignItems: "center" }}>
+          <span style={{ fontSize: "0.85rem" }}>Mod folder: {modDir ?? "the one found"}</span>
+          <button
+            className="btn btn-secondary btn-sm"
+            onClick={() =>
+              pickModDir()
+                .then((dir) => dir && setModDir(dir))
+                .catch((err) => setMessage({ ok: false, text: String(err) }))
+            }
+          >
+            Choose…
+          </button>
+        </div>
+        <div className="btn-row">
+          <label className="checkbox-field">
+            <input type="checkbox" checked={backup} onChange={(e) => setBackup(e.target.checked)} />
+            Back up the last deploy
+          </label>
+          <button className="btn btn-primary btn-sm" disabled={busy} onClick={deploy}>
+            Deploy to Mod Folder
+          </button>
+        </div>
+        <div className="btn-row">
+          <label className="checkbox-field">
+            <input type="checkbox" checked={exclusive} onChange={(e) => setExclusive(e.target.checked)} />
+            Disable the other mods
+          </label>
+          <button className="btn btn-secondary btn-sm" disabled={busy} onClick={play}>
+            Play with the Mod
+          </button>
+        </div>
+      </div>
+
+      <hr className="divider" />
+      <div className="panel">
+        <h3>Steam Workshop</h3>
+        <p style={{ color: "var(--text-muted)", fontSize: "0.85rem", marginBottom: "0.75rem" }}>
+          Build the mod in an upload-ready folder and check its thumbnail and tags. Given a Steam account, steamcmd
+          also uploads it unless there are warnings.
+        </p>
+        <div className="field-row">
+          <div className="field">
+            <label>Steam Account</label>
+            <input
+              type="text"
+              placeholder="package only"
+              value={steamUser}
+              onChange={(e) => setSteamUser(e.target.value)}
+            />
+          </div>
+        </div>
+        <div className="btn-row">
+          <button className="btn btn-secondary btn-sm" disabled={busy} onClick={packageMod}>
+            Package for Workshop…
+          </button>
+        </div>
+      </div>
+
+      {message && (
+        <div className={`msg ${message.ok ? "msg-success" : "msg-error"}`} style={{ whiteSpace: "pre-line" }}>
+          {message.text}
+        </div>
+      )}
+    </div>
+  );
+}
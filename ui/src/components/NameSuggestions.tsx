@@ -0,0 +1,62 @@
+import { useState } from "react";
+import { SuggestedName, suggestNames } from "../api";
+
+const COUNT = 10;
+
+// Names of a culture picked as the simulation picks them, common ones more
+// often, for naming characters by hand.
+export default function NameSuggestions({ cultures }: { cultures: string[] }) {
+  const [culture, setCulture] = useState(cultures[0] ?? "");
+  const [gender, setGender] = useState<"male" | "female">("male");
+  const [father, setFather] = useState("");
+  const [names, setNames] = useState<SuggestedName[]>([]);
+  const [error, setError] = useState<string | null>(null);
+
+  const suggest = () => {
+    suggestNames(culture.trim(), gender, COUNT, { father: father.trim() || undefined })
+      .then((names) => {
+        setNames(names);
+        setError(names.length === 0 ? `No names are listed for ${culture}.` : null);
+      })
+      .catch((err) => setError(String(err)));
+  };
+
+  return (
+    <div className="panel">
+      <h3>Name Suggestions</h3>
+      <div className="field-row">
+        <div className="field">
+          <label>Culture</label>
+          <input type="text" list="name-cultures" value={culture} onChange={(e) => setCulture(e.target.value)} />
+          <datalist id="name-cultures">
+            {cultures.map((culture) => (
+              <option key={culture} value={culture} />
+            ))}
+          </datalist>
+        </div>
+        <div className="field">
+          <label>Gender</label>
+          <select value={gender} onChange={(e) => setGender(e.target.value as "male" | "female")}>
+            <option value="male">Male</option>
+            <option value="female">Female</option>
+          </select>
+        </div>
+        <div className="field">
+          <label>Father's Name (for patronyms)</label>
+          <input type="text" value={father} onChange={(e) => setFather(e.target.value)} />
+        </div>
+      </div>
+      <div className="btn-row">
+        <button className="btn btn-secondary btn-sm" disabled={!culture.trim()} onClick={suggest}>
+          Suggest Names
+        </button>
+      </div>
+      {names.length > 0 && (
+        <p style={{ fontSize: "0.85rem", marginTop: "0.75rem" }}>
+          {names.map((name) => name.patronymic ?? name.name).join(", ")}
+        </p>
+      )}
+      {error && <div className="msg msg-error">{error}</div>}
+    </div>
+  );
+}
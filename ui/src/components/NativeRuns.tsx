@@ -0,0 +1,225 @@
+import { useState } from "react";
+import {
+  checkGameData,
+  continueNativeSimulation,
+  GameDataCheck,
+  NativeSummary,
+  pickGameDir,
+  pickHistoryFile,
+  pickStateFile,
+  pickStateSaveFile,
+  regenerateNativeSimulation,
+  resumeNativeSimulation,
+  runNativeSimulation,
+  saveNativeState,
+} from "../api";
+
+const cell = { padding: "0.3rem 0.5rem", borderBottom: "1px solid var(--border)", textAlign: "left" as const };
+
+function Summary({ summary }: { summary: NativeSummary }) {
+  return (
+    <>
+      <p style={{ fontSize: "0.85rem", marginTop: "0.75rem" }}>
+        {summary.firstYear}–{summary.lastYear}: {summary.characters} characters, {summary.livingAtEnd} living at
+        the end.
+      </p>
+      <table style={{ width: "100%", borderCollapse: "collapse", fontSize: "0.85rem" }}>
+        <thead>
+          <tr>
+            {["Dynasty", "Members", "Living", "Adopted", "Cadet Houses", "Marriages Between Dynasties"].map(
+              (heading) => (
+                <th key={heading} style={{ ...cell, color: "var(--text-label)" }}>
+                  {heading}
+                </th>
+              )
+            )}
+          </tr>
+        </thead>
+        <tbody>
+          {summary.dynasties.map((dynasty) => (
+            <tr key={dynasty.dynastyId}>
+              <td style={cell}>{dynasty.dynastyId}</td>
+              <td style={cell}>{dynasty.members}</td>
+              <td style={cell}>{dynasty.livingAtEnd}</td>
+              <td style={cell}>{dynasty.adopted}</td>
+              <td style={cell}>{dynasty.houses}</td>
+              <td style={cell}>
+                {Object.entries(dynasty.alliances)
+                  .map(([other, marriages]) => `${other} ×${marriages}`)
+                  .join(", ")}
+              </td>
+            </tr>
+          ))}
+        </tbody>
+      </table>
+      {summary.pluginErrors.length > 0 && (
+        <div className="msg msg-error" style={{ whiteSpace: "pre-line" }}>
+          {summary.pluginErrors.join("\n")}
+        </div>
+      )}
+    </>
+  );
+}
+
+// Native runs that answer with what was simulated, dynasty by dynasty.
+export default function NativeRuns() {
+  const [parallel, setParallel] = useState(false);
+  const [busy, setBusy] = useState(false);
+  const [summary, setSummary] = useState<NativeSummary | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  // What the last regeneration simulated again and rewrote, or where the
+  // state was saved.
+  const [regenerated, setRegenerated] = useState<string | null>(null);
+  // The CK3 installation or total conversion mod written for; null writes
+  // every trait the simulation gives.
+  const [gameDir, setGameDir] = useState<string | null>(null);
+  const [check, setCheck] = useState<GameDataCheck | null>(null);
+
+  // Resolves to null if nothing was run, as when a dialog is cancelled.
+  const run = (action: () => Promise<NativeSummary | null>) => {
+    setBusy(true);
+    setError(null);
+    setRegenerated(null);
+    action()
+      .then((summary) => summary && setSummary(summary))
+      .catch((err) => setError(String(err)))
+      .finally(() => setBusy(false));
+  };
+
+  return (
+    <div className="panel">
+      <h3>Native Runs</h3>
+      <p style={{ color: "var(--text-muted)", fontSize: "0.85rem", marginBottom: "0.75rem" }}>
+        Generate the saved config and see how each dynasty fared. A parallel run simulates the dynasties on every core
+        and marries them to each other afterwards. Regenerating simulates again only the dynasties whose settings
+        changed since it was last done, and rewrites those married to them. A history file of your own, such as a
+        hand-written dynasty, can be carried on over the config's period. With a target game, the traits it does not
+        define are left out, and checking it lists the config's cultures, faiths and traits it lacks. A run's full state
+        can be saved as JSON, to inspect or attach to a bug report, and resumed later.
+      </p>
+      <div className="btn-row" style={{ alignItems: "center" }}>
+        <span style={{ fontSize: "0.85rem" }}>Target game: {gameDir ?? "any"}</span>
+        <button
+          className="btn btn-secondary btn-sm"
+          onClick={() =>
+            pickGameDir()
+              .then((dir) => {
+                if (!dir) return;
+                setGameDir(dir);
+                setCheck(null);
+              })
+              .catch((err) => setError(String(err)))
+          }
+        >
+          Choose…
+        </button>
+        {gameDir && (
+          <>
+            <button
+              className="btn btn-secondary btn-sm"
+              onClick={() =>
+                checkGameData(gameDir)
+                  .then(setCheck)
+                  .catch((err) => setError(String(err)))
+              }
+            >
+              Check Game Data
+            </button>
+            <button
+              className="btn btn-secondary btn-sm"
+              onClick={() => {
+                setGameDir(null);
+                setCheck(null);
+              }}
+            >
+              Clear
+            </button>
+          </>
+        )}
+      </div>
+      {check && (
+        <div
+          className={`msg ${check.problems.length + check.unreadable.length === 0 ? "msg-success" : "msg-error"}`}
+          style={{ whiteSpace: "pre-line" }}
+        >
+          {[
+            `The game defines ${check.traits} traits, ${check.cultures} cultures and ${check.faiths} faiths.`,
+            ...check.unreadable,
+            ...check.problems,
+          ].join("\n")}
+        </div>
+      )}
+      <div className="btn-row">
+        <label className="checkbox-field">
+          <input type="checkbox" checked={parallel} onChange={(e) => setParallel(e.target.checked)} />
+          Parallel
+        </label>
+        <button
+          className="btn btn-secondary btn-sm"
+          disabled={busy}
+          onClick={() => run(() => runNativeSimulation(parallel, gameDir ?? undefined))}
+        >
+          {busy ? <><span className="spinner" /> Running...</> : "Run and Summarise"}
+        </button>
+        <button
+          className="btn btn-secondary btn-sm"
+          disabled={busy}
+          onClick={() =>
+            run(async () => {
+              const regeneration = await regenerateNativeSimulation(gameDir ?? undefined);
+              const list = (ids: string[]) => (ids.length === 0 ? "none" : ids.join(", "));
+              setRegenerated(
+                `Simulated again: ${list(regeneration.simulated)}. Rewritten: ${list(regeneration.rewritten)}.`
+              );
+              return regeneration.summary;
+            })
+          }
+        >
+          Regenerate Changed Dynasties
+        </button>
+        <button
+          className="btn btn-secondary btn-sm"
+          disabled={busy}
+          onClick={() =>
+            run(async () => {
+              const path = await pickHistoryFile();
+              return path ? continueNativeSimulation(path, gameDir ?? undefined) : null;
+            })
+          }
+        >
+          Continue a History File…
+        </button>
+        <button
+          className="btn btn-secondary btn-sm"
+          disabled={busy}
+          onClick={() =>
+            run(async () => {
+              const path = await pickStateFile();
+              return path ? resumeNativeSimulation(path, gameDir ?? undefined) : null;
+            })
+          }
+        >
+          Resume a Saved State…
+        </button>
+        <button
+          className="btn btn-secondary btn-sm"
+          disabled={busy || summary === null}
+          onClick={() =>
+            pickStateSaveFile()
+              .then(async (path) => {
+                if (!path) return;
+                await saveNativeState(path);
+                setRegenerated(`Saved every character, relationship and event of the run, and its seed, to ${path}.`);
+              })
+              .catch((err) => setError(String(err)))
+          }
+        >
+          Save State…
+        </button>
+      </div>
+      {regenerated && <div className="msg msg-success">{regenerated}</div>}
+      {summary && <Summary summary={summary} />}
+      {error && <div className="msg msg-error">{error}</div>}
+    </div>
+  );
+}
@@ -252,7 +252,7 @@ export default function NegativeEvents({ config, onConfigChange }: Props) {
       </div>
 
       {feedback && (
-        <div className={`msg msg-${feedback.type}`}>{feedback.text}</div>
+        <div className={`msg msg-${feedback.type}`} style={{ whiteSpace: "pre-line" }}>{feedback.text}</div>
       )}
 
       {addOpen && (
@@ -0,0 +1,139 @@
+import { useEffect, useState } from "react";
+import {
+  checkMarriages,
+  checkTraits,
+  getProjectSetting,
+  lintOutput,
+  MarriageProblem,
+  onOutputProblems,
+  OutputProblem,
+  pickGameDir,
+} from "../api";
+import IdCollisions, { CHECK_PATHS_SETTING } from "./IdCollisions";
+import Survivors from "./Survivors";
+
+export default function Problems() {
+  const [problems, setProblems] = useState<OutputProblem[] | null>(null);
+  const [marriages, setMarriages] = useState<MarriageProblem[]>([]);
+  const [gameDir, setGameDir] = useState<string | null>(null);
+  const [loading, setLoading] = useState(true);
+  const [error, setError] = useState<string | null>(null);
+
+  const check = (dir: string | null = gameDir) => {
+    setLoading(true);
+    setError(null);
+    // The folders IDs are checked against define what the history may refer to.
+    const others = getProjectSetting<string[]>(CHECK_PATHS_SETTING).catch(() => null);
+    // A config that does not load leaves the marriages unchecked, not the files.
+    Promise.all([
+      others.then((others) => lintOutput(dir ?? undefined, others ?? [])),
+      checkMarriages().catch(() => []),
+      checkTraits(dir ?? undefined).catch(() => []),
+    ])
+      .then(([problems, marriages, traits]) => {
+        setProblems(
+          [...problems, ...traits].sort((a, b) => a.file.localeCompare(b.file) || a.line - b.line),
+        );
+        setMarriages(marriages);
+      })
+      .catch((err) => {
+        setProblems(null);
+        setMarriages([]);
+        setError(String(err));
+      })
+      .finally(() => setLoading(false));
+  };
+
+  useEffect(() => check(), []);
+
+  // A completed generation is checked again, against the game chosen too.
+  useEffect(() => {
+    const unlisten = onOutputProblems(() => check());
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [gameDir]);
+
+  const chooseGame = () => {
+    pickGameDir()
+      .then((dir) => {
+        if (dir) {
+          setGameDir(dir);
+          check(dir);
+        }
+      })
+      .catch((err) => setError(String(err)));
+  };
+
+  return (
+    <div>
+      <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center", marginBottom: "1rem" }}>
+        <h2 style={{ margin: 0 }}>Problems</h2>
+        <span style={{ display: "flex", gap: "0.5rem" }}>
+          <button className="btn btn-secondary btn-sm" onClick={chooseGame}>
+            {gameDir ? "Change game" : "Check against game"}
+          </button>
+          <button className="btn btn-secondary btn-sm" onClick={() => check()}>
+            Check again
+          </button>
+        </span>
+      </div>
+
+      {gameDir && (
+        <p style={{ color: "var(--text-muted)", fontSize: "0.85rem" }}>
+          Traits, cultures and faiths, and which traits go together, checked against {gameDir}
+        </p>
+      )}
+
+      {loading && (
+        <div style={{ display: "flex", alignItems: "center", gap: "0.75rem", color: "var(--text-muted)" }}>
+          <span className="spinner" /> Checking the generated files...
+        </div>
+      )}
+
+      {error && <div className="msg msg-error">{error}</div>}
+
+      {!loading && problems?.length === 0 && marriages.length === 0 && (
+        <div className="msg msg-success">No problems found in the generated files.</div>
+      )}
+
+      {!loading && problems && problems.length > 0 && (
+        <ul style={{ listStyle: "none", padding: 0, margin: 0 }}>
+          {problems.map((problem, i) => (
+            <li key={i} style={{ padding: "0.3rem 0", borderBottom: "1px solid var(--border)" }}>
+              <span style={{ color: "var(--text-label)", fontFamily: "monospace" }}>
+                {problem.file}
+                {problem.line > 0 && `:${problem.line}`}
+              </span>{" "}
+              {problem.message}
+            </li>
+          ))}
+        </ul>
+      )}
+
+      {!loading && marriages.length > 0 && (
+        <>
+          <h3>Marriages</h3>
+          <ul style={{ listStyle: "none", padding: 0, margin: 0 }}>
+            {marriages.map((marriage, i) => (
+              <li key={i} style={{ padding: "0.3rem 0", borderBottom: "1px solid var(--border)" }}>
+                <span
+                  style={{
+                    color: marriage.severity === "error" ? "var(--error-text)" : "var(--warning-text)",
+                    fontFamily: "monospace",
+                  }}
+                >
+                  {marriage.severity}
+                </span>{" "}
+                {marriage.message}
+              </li>
+            ))}
+          </ul>
+        </>
+      )}
+
+      <Survivors onRepaired={() => check()} />
+      <IdCollisions onRenumbered={() => check()} onFoldersChanged={() => check()} />
+    </div>
+  );
+}
@@ -0,0 +1,56 @@
+import { useEffect, useState } from "react";
+import { getRecentProjects, pinProject, RecentProject } from "../api";
+
+const cell = { padding: "0.3rem 0.5rem", borderBottom: "1px solid var(--border)", textAlign: "left" as const };
+
+// The projects opened lately, pinned ones first, to open again or pin.
+export default function RecentProjects({ onOpen }: { onOpen: (path: string) => void }) {
+  const [projects, setProjects] = useState<RecentProject[]>([]);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    getRecentProjects()
+      .then(setProjects)
+      .catch((err) => setError(String(err)));
+  }, []);
+
+  const pin = (project: RecentProject) => {
+    pinProject(project.path, !project.pinned)
+      .then(setProjects)
+      .catch((err) => setError(String(err)));
+  };
+
+  return (
+    <div className="panel">
+      <h3>Recent Projects</h3>
+      {projects.length === 0 ? (
+        <p style={{ color: "var(--text-muted)", fontSize: "0.85rem" }}>No project has been opened yet.</p>
+      ) : (
+        <table style={{ width: "100%", borderCollapse: "collapse", fontSize: "0.85rem" }}>
+          <tbody>
+            {projects.map((project) => (
+              <tr key={project.path}>
+                <td style={cell}>
+                  <strong>{project.name}</strong>
+                  <div style={{ color: "var(--text-muted)" }}>{project.path}</div>
+                </td>
+                <td style={{ ...cell, color: "var(--text-muted)" }}>
+                  {new Date(project.lastOpened).toLocaleString()}
+                </td>
+                <td style={{ ...cell, whiteSpace: "nowrap" }}>
+                  <button className="btn btn-secondary btn-sm" onClick={() => onOpen(project.path)}>
+                    Open
+                  </button>{" "}
+                  <button className="btn btn-secondary btn-sm" onClick={() => pin(project)}>
+                    {project.pinned ? "Unpin" : "Pin"}
+                  </button>
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+      {error && <div className="msg msg-error">{error}</div>}
+    </div>
+  );
+}
@@ -0,0 +1,323 @@
+import { useEffect, useState } from "react";
+import {
+  checkForUpdate,
+  CrashReportInfo,
+  EngineStats,
+  getLastSeed,
+  getLaunchOptions,
+  installUpdate,
+  LaunchOptions,
+  listCrashReports,
+  onCrashReport,
+  onEngineRestartProgress,
+  onEngineStats,
+  onUpdateProgress,
+  pickOutputDir,
+  RestartProgress,
+  restartEngine,
+  setLaunchOptions,
+  submitCrashReport,
+  UpdateInfo,
+} from "../api";
+
+function formatBytes(bytes: number): string {
+  if (bytes >= 1024 ** 3) return `${(bytes / 1024 ** 3).toFixed(1)} GB`;
+  return `${Math.round(bytes / 1024 ** 2)} MB`;
+}
+
+const RESTART_STAGES: Record<RestartProgress["stage"], string> = {
+  stopping: "Stopping the engine...",
+  starting: "Waiting for the new engine...",
+  ready: "The engine has restarted.",
+  failed: "The engine did not restart",
+};
+
+// What the engine process uses, sampled every few seconds, to tell an engine
+// that is working from one swapping itself to death. A stuck engine can be
+// replaced here without restarting the app.
+function EngineUsage() {
+  const [stats, setStats] = useState<EngineStats | null>(null);
+  const [restart, setRestart] = useState<RestartProgress | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    const unlisten = [onEngineStats(setStats), onEngineRestartProgress(setRestart)];
+    return () => {
+      unlisten.forEach((pending) => pending.then((stop) => stop()));
+    };
+  }, []);
+
+  const restarting = restart !== null && (restart.stage === "stopping" || restart.stage === "starting");
+
+  // A restart that fails once under way is reported by its progress too.
+  const restartNow = () => {
+    setRestart(null);
+    setError(null);
+    restartEngine().catch((err) => setError(String(err)));
+  };
+
+  const restartClass =
+    restart?.stage === "failed" ? "msg-error" : restart?.stage === "ready" ? "msg-success" : "msg-info";
+
+  return (
+    <div className="panel">
+      <h3>Engine Usage</h3>
+      {stats ? (
+        <p style={{ fontSize: "0.85rem" }}>
+          {Math.round(stats.cpuPercent)}% CPU · {formatBytes(stats.memoryBytes)} of{" "}
+          {formatBytes(stats.totalMemoryBytes)} memory
+          {stats.processes > 1 ? ` · ${stats.processes} processes` : ""}
+        </p>
+      ) : (
+        <p style={{ color: "var(--text-muted)", fontSize: "0.85rem" }}>
+          Waiting for the first sample. None are taken of an engine started by hand in development.
+        </p>
+      )}
+      <div className="btn-row">
+        <button className="btn btn-secondary btn-sm" disabled={restarting} onClick={restartNow}>
+          {restarting ? <><span className="spinner" /> Restarting...</> : "Restart Engine"}
+        </button>
+      </div>
+      {restart && (
+        <div className={`msg ${restartClass}`}>
+          {RESTART_STAGES[restart.stage]}
+          {restart.message ? `: ${restart.message}` : ""}
+        </div>
+      )}
+      {error && restart?.stage !== "failed" && <div className="msg msg-error">{error}</div>}
+    </div>
+  );
+}
+
+// The config folder, seed and output folder the engine is started with, each
+// left to its default when blank.
+function LaunchSettings() {
+  const [configDir, setConfigDir] = useState("");
+  const [seed, setSeed] = useState("");
+  const [outputDir, setOutputDir] = useState("");
+  const [message, setMessage] = useState<{ ok: boolean; text: string } | null>(null);
+
+  useEffect(() => {
+    getLaunchOptions()
+      .then((options) => {
+        setConfigDir(options.configDir ?? "");
+        setSeed(options.seed === null ? "" : String(options.seed));
+        setOutputDir(options.outputDir ?? "");
+      })
+      .catch((err) => setMessage({ ok: false, text: String(err) }));
+  }, []);
+
+  // To generate the last history again, byte for byte.
+  const fillLastSeed = () => {
+    getLastSeed()
+      .then((last) =>
+        last === null
+          ? setMessage({ ok: false, text: "The last history records no seed." })
+          : setSeed(String(last))
+      )
+      .catch((err) => setMessage({ ok: false, text: String(err) }));
+  };
+
+  const save = async (restart: boolean) => {
+    const parsed = seed.trim() === "" ? null : Number(seed);
+    if (parsed !== null && (!Number.isInteger(parsed) || parsed < 0)) {
+      setMessage({ ok: false, text: "The seed must be a whole number." });
+      return;
+    }
+    const options: LaunchOptions = {
+      configDir: configDir.trim() || null,
+      seed: parsed,
+      outputDir: outputDir.trim() || null,
+    };
+    try {
+      await setLaunchOptions(options);
+      if (restart) await restartEngine();
+      setMessage({ ok: true, text: restart ? "Saved; the engine has restarted." : "Saved for the next engine start." });
+    } catch (err) {
+      setMessage({ ok: false, text: String(err) });
+    }
+  };
+
+  return (
+    <div className="panel">
+      <h3>Engine Launch</h3>
+      <p style={{ color: "var(--text-muted)", fontSize: "0.85rem", marginBottom: "0.75rem" }}>
+        Start the engine in a reproducible state. Native runs use the seed straight away; the engine takes all three
+        from its next start.
+      </p>
+      <div className="field-row">
+        <div className="field">
+          <label>Config Folder</label>
+          <input type="text" placeholder="default" value={configDir} onChange={(e) => setConfigDir(e.target.value)} />
+        </div>
+        <div className="field">
+          <label>Seed</label>
+          <input type="text" placeholder="random" value={seed} onChange={(e) => setSeed(e.target.value)} />
+          <button className="btn btn-secondary btn-sm" style={{ marginTop: "0.35rem" }} onClick={fillLastSeed}>
+            Use the last history's
+          </button>
+        </div>
+        <div className="field">
+          <label>Output Folder</label>
+          <input type="text" placeholder="default" value={outputDir} onChange={(e) => setOutputDir(e.target.value)} />
+          <button
+            className="btn btn-secondary btn-sm"
+            style={{ marginTop: "0.35rem" }}
+            onClick={() =>
+              pickOutputDir()
+                .then((dir) => dir && setOutputDir(dir))
+                .catch((err) => setMessage({ ok: false, text: String(err) }))
+            }
+          >
+            Browse…
+          </button>
+        </div>
+      </div>
+      <div className="btn-row">
+        <button className="btn btn-secondary btn-sm" onClick={() => save(false)}>
+          Save
+        </button>
+        <button className="btn btn-secondary btn-sm" onClick={() => save(true)}>
+          Save and Restart Engine
+        </button>
+      </div>
+      {message && <div className={`msg ${message.ok ? "msg-success" : "msg-error"}`}>{message.text}</div>}
+    </div>
+  );
+}
+
+// Checking the release feed and installing what it offers, engine included;
+// the app restarts once the update is in.
+function Updates() {
+  // undefined until checked, null when this is the latest version.
+  const [update, setUpdate] = useState<UpdateInfo | null | undefined>(undefined);
+  const [checking, setChecking] = useState(false);
+  const [downloaded, setDownloaded] = useState<{ bytes: number; total: number | null } | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    const unlisten = onUpdateProgress((bytes, total) => setDownloaded({ bytes, total }));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const check = () => {
+    setChecking(true);
+    setError(null);
+    checkForUpdate()
+      .then(setUpdate)
+      .catch((err) => setError(String(err)))
+      .finally(() => setChecking(false));
+  };
+
+  const install = () => {
+    setError(null);
+    setDownloaded({ bytes: 0, total: null });
+    installUpdate().catch((err) => {
+      setDownloaded(null);
+      setError(String(err));
+    });
+  };
+
+  return (
+    <div className="panel">
+      <h3>Updates</h3>
+      <div className="btn-row">
+        <button className="btn btn-secondary btn-sm" onClick={check} disabled={checking || downloaded !== null}>
+          {checking ? "Checking..." : "Check for Updates"}
+        </button>
+        {update && (
+          <button className="btn btn-primary btn-sm" onClick={install} disabled={downloaded !== null}>
+            Install {update.version}
+          </button>
+        )}
+      </div>
+      {update === null && <div className="msg msg-success">This is the latest version.</div>}
+      {update && (
+        <p style={{ fontSize: "0.85rem", marginTop: "0.75rem", whiteSpace: "pre-line" }}>
+          Version {update.version} is out; this is {update.currentVersion}.
+          {update.notes ? `\n${update.notes}` : ""}
+        </p>
+      )}
+      {downloaded && (
+        <p style={{ color: "var(--text-muted)", fontSize: "0.85rem" }}>
+          Downloading: {formatBytes(downloaded.bytes)}
+          {downloaded.total !== null ? ` of ${formatBytes(downloaded.total)}` : ""}
+        </p>
+      )}
+      {error && <div className="msg msg-error">{error}</div>}
+    </div>
+  );
+}
+
+const cell = { padding: "0.3rem 0.5rem", borderBottom: "1px solid var(--border)", textAlign: "left" as const };
+
+// The crash reports written so far, newest first, each ready to submit.
+function CrashReports() {
+  const [reports, setReports] = useState<CrashReportInfo[]>([]);
+  const [message, setMessage] = useState<{ ok: boolean; text: string } | null>(null);
+
+  useEffect(() => {
+    listCrashReports()
+      .then(setReports)
+      .catch((err) => setMessage({ ok: false, text: String(err) }));
+    const unlisten = onCrashReport((report) => setReports((prev) => [report, ...prev]));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const submit = (report: CrashReportInfo) => {
+    submitCrashReport(report.name)
+      .then(() => setMessage({ ok: true, text: `Submitted ${report.name}.` }))
+      .catch((err) => setMessage({ ok: false, text: String(err) }));
+  };
+
+  return (
+    <div className="panel">
+      <h3>Crash Reports</h3>
+      <p style={{ color: "var(--text-muted)", fontSize: "0.85rem", marginBottom: "0.75rem" }}>
+        Each report holds the last log lines, the config with its paths left out, the system and how the engine
+        exited. Nothing is sent unless you submit it.
+      </p>
+      {reports.length === 0 ? (
+        <p style={{ color: "var(--text-muted)", fontSize: "0.85rem" }}>No crash has been reported.</p>
+      ) : (
+        <table style={{ width: "100%", borderCollapse: "collapse", fontSize: "0.85rem" }}>
+          <tbody>
+            {reports.map((report) => (
+              <tr key={report.name}>
+                <td style={cell}>{report.name}</td>
+                <td style={{ ...cell, color: "var(--text-muted)" }}>
+                  {report.kind === "shell" ? "app" : report.kind === "engine" ? "engine" : ""}
+                </td>
+                <td style={{ ...cell, whiteSpace: "nowrap" }}>
+                  <button className="btn btn-secondary btn-sm" onClick={() => submit(report)}>
+                    Submit
+                  </button>
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+      {message && <div className={`msg ${message.ok ? "msg-success" : "msg-error"}`}>{message.text}</div>}
+    </div>
+  );
+}
+
+export default function Settings() {
+  return (
+    <div>
+      <h2>Settings</h2>
+      <EngineUsage />
+      <hr className="divider" />
+      <LaunchSettings />
+      <hr className="divider" />
+      <Updates />
+      <hr className="divider" />
+      <CrashReports />
+    </div>
+  );
+}
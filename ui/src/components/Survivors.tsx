@@ -0,0 +1,88 @@
+import { useEffect, useState } from "react";
+import { checkSurvivors, repairSurvivors, SurvivorReport } from "../api";
+
+export default function Survivors({ onRepaired }: { onRepaired: () => void }) {
+  const [report, setReport] = useState<SurvivorReport | null>(null);
+  const [repaired, setRepaired] = useState<SurvivorReport | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const check = () => {
+    setBusy(true);
+    setError(null);
+    checkSurvivors()
+      .then(setReport)
+      .catch((err) => {
+        setReport(null);
+        setError(String(err));
+      })
+      .finally(() => setBusy(false));
+  };
+
+  useEffect(() => check(), []);
+
+  const repair = () => {
+    setBusy(true);
+    setError(null);
+    repairSurvivors()
+      .then((fixed) => {
+        setRepaired(fixed);
+        onRepaired();
+        return checkSurvivors().then(setReport);
+      })
+      .catch((err) => setError(String(err)))
+      .finally(() => setBusy(false));
+  };
+
+  return (
+    <div style={{ marginTop: "1.5rem" }}>
+      <h3 style={{ margin: 0 }}>Survivors</h3>
+
+      {error && <div className="msg msg-error">{error}</div>}
+
+      {repaired && (
+        <div className="msg msg-success">
+          {repaired.survivors.length} characters given new deaths
+          {repaired.extinct.length > 0 && `, ending ${repaired.extinct.join(", ")}`}.
+          <ul style={{ margin: "0.5rem 0 0" }}>
+            {repaired.survivors.map((survivor) => (
+              <li key={survivor.id}>
+                {survivor.name} ({survivor.id}) now dies on {survivor.newDeath}
+                {survivor.death && `, not ${survivor.death}`}
+              </li>
+            ))}
+          </ul>
+        </div>
+      )}
+
+      {!busy && report?.survivors.length === 0 && !repaired && (
+        <div className="msg msg-success">
+          No one is alive at the end of the period, {report.end}, past their lifespan cap.
+        </div>
+      )}
+
+      {report && report.survivors.length > 0 && (
+        <>
+          <ul style={{ listStyle: "none", padding: 0, margin: 0 }}>
+            {report.survivors.map((survivor) => (
+              <li key={survivor.id} style={{ padding: "0.3rem 0", borderBottom: "1px solid var(--border)" }}>
+                <span style={{ color: "var(--text-label)", fontFamily: "monospace" }}>{survivor.id}</span>{" "}
+                {survivor.name}, born {survivor.birth},{" "}
+                {survivor.death ? `dies aged ${survivor.age} on ${survivor.death}` : `is ${survivor.age} at the end`}
+                , past the cap of {survivor.maxAge}; dies on {survivor.newDeath} instead
+              </li>
+            ))}
+          </ul>
+          {report.extinct.length > 0 && (
+            <p style={{ color: "var(--text-muted)", fontSize: "0.85rem" }}>
+              Only survivors keep {report.extinct.join(", ")} alive at the end.
+            </p>
+          )}
+          <button className="btn btn-secondary btn-sm" style={{ marginTop: "0.75rem" }} disabled={busy} onClick={repair}>
+            Repair
+          </button>
+        </>
+      )}
+    </div>
+  );
+}
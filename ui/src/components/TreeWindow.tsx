@@ -0,0 +1,84 @@
+/**
+ * TreeWindow.tsx
+ *
+ * Contents of a detached family tree window. Shows the tree of the dynasty
+ * the shell opened the window for, lets the user switch to another one, and
+ * reloads the image whenever a generation writes new trees.
+ */
+
+import { useEffect, useState } from "react";
+import { fetchImageList, getTreeView, imageUrl, onTreesUpdated, setTreeDynasty } from "../api";
+
+const treeFile = (dynasty: string) => `family_tree_${dynasty}.png`;
+
+const dynastyLabel = (filename: string) =>
+  filename.replace("family_tree_", "").replace(".png", "");
+
+export default function TreeWindow() {
+  const [dynasty, setDynasty] = useState<string | null>(null);
+  const [images, setImages]   = useState<string[]>([]);
+  // Bumped after each generation so the browser does not show a cached tree.
+  const [version, setVersion] = useState(0);
+  const [error, setError]     = useState<string | null>(null);
+
+  const load = () => {
+    setError(null);
+    fetchImageList()
+      .then(setImages)
+      .catch((err: Error) => setError(err.message));
+  };
+
+  useEffect(() => {
+    getTreeView().then((view) => setDynasty(view?.dynasty ?? null));
+    load();
+    const unlisten = onTreesUpdated(() => {
+      load();
+      setVersion((v) => v + 1);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const choose = (next: string) => {
+    setTreeDynasty(next)
+      .then(() => setDynasty(next))
+      .catch((err) => setError(String(err)));
+  };
+
+  const file = dynasty !== null ? treeFile(dynasty) : null;
+
+  return (
+    <div className="app-shell">
+      <main className="tab-content">
+        <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center", marginBottom: "1rem" }}>
+          <h2 style={{ margin: 0 }}>{dynasty ?? "Family tree"}</h2>
+          <select value={dynasty ?? ""} onChange={(e) => choose(e.target.value)}>
+            {images.map((filename) => (
+              <option key={filename} value={dynastyLabel(filename)}>
+                {dynastyLabel(filename)}
+              </option>
+            ))}
+          </select>
+        </div>
+
+        {error && <div className="msg msg-error">{error}</div>}
+
+        {!error && file !== null && !images.includes(file) && (
+          <div className="msg msg-info">
+            No family tree image for {dynasty}. Run the simulation first.
+          </div>
+        )}
+
+        {file !== null && images.includes(file) && (
+          <div className="dynasty-image-wrap">
+            <img
+              src={`${imageUrl(file)}?v=${version}`}
+              alt={`Family tree for ${dynasty}`}
+            />
+          </div>
+        )}
+      </main>
+    </div>
+  );
+}
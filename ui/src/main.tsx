@@ -4,19 +4,26 @@
  * React entry point. Mounts the application with StrictMode and a top-level
  * ErrorBoundary as a last-resort safety net for errors that escape the
  * boundary in App.tsx (e.g. errors thrown during the very first render before
- * App's own boundary initialises).
+ * App's own boundary initialises). Detached family tree windows load the same
+ * page and render only their tree.
  */
 
 import React from "react";
 import ReactDOM from "react-dom/client";
+import { isTauri } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import App from "./App";
 import ErrorBoundary from "./components/ErrorBoundary";
+import TreeWindow from "./components/TreeWindow";
 import "./index.css";
 
+// Labels of tree windows are given by the shell's tree_window module.
+const isTreeWindow = isTauri() && getCurrentWindow().label.startsWith("tree-");
+
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
     <ErrorBoundary>
-      <App />
+      {isTreeWindow ? <TreeWindow /> : <App />}
     </ErrorBoundary>
   </React.StrictMode>
 );
\ No newline at end of file
@@ -16,11 +16,25 @@ def generate_random_date(year: int) -> str:
     return f"{year}.{month:02}.{day:02}"
 
 
-def generate_char_id(dynasty_prefix: str, dynasty_char_counters: dict[str, int]) -> str:
+def generate_char_id(
+    dynasty_prefix: str, dynasty_char_counters: dict[str, int], first: int = 1
+) -> str:
     """Generate a unique character ID using the dynasty prefix.
 
     Increments the per-dynasty counter on every call and returns an ID of
-    the form ``lineof<prefix><n>``.
+    the form ``lineof<prefix><n>``, numbering each prefix from ``first``.
     """
-    dynasty_char_counters[dynasty_prefix] = dynasty_char_counters.get(dynasty_prefix, 0) + 1
-    return f"lineof{dynasty_prefix}{dynasty_char_counters[dynasty_prefix]}"
\ No newline at end of file
+    dynasty_char_counters[dynasty_prefix] = dynasty_char_counters.get(dynasty_prefix, first - 1) + 1
+    return f"lineof{dynasty_prefix}{dynasty_char_counters[dynasty_prefix]}"
+
+
+def parse_game_version(text: str | None) -> tuple[int, int] | None:
+    """Return the (major, minor) of a game version such as ``1.12`` or
+    ``1.12.5``, or None for no version or one that does not parse."""
+    if not text:
+        return None
+    parts = text.strip().lstrip("vV").split(".")
+    try:
+        return int(parts[0]), int(parts[1])
+    except (IndexError, ValueError):
+        return None